@@ -0,0 +1,101 @@
+//! Benchmarks writing the row instructions of a byte operation chip one row at a time and in
+//! parallel chunks.
+//!
+//! To run, use:
+//! ```
+//! cargo bench --bench row_instructions
+//! ```
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use curta::chip::builder::AirBuilder;
+use curta::chip::trace::generator::ArithmeticGenerator;
+use curta::chip::trace::writer::TraceWriter;
+use curta::chip::uint::bytes::lookup_table::table::ByteLookupTable;
+use curta::chip::uint::bytes::lookup_table::ByteInstructionSet;
+use curta::chip::uint::bytes::operations::value::ByteOperation;
+use curta::chip::uint::bytes::register::ByteRegister;
+use curta::chip::AirParameters;
+use curta::math::goldilocks::cubic::GoldilocksCubicParameters;
+use curta::math::prelude::*;
+use plonky2::field::goldilocks_field::GoldilocksField;
+use rand::{thread_rng, Rng};
+use serde::{Deserialize, Serialize};
+
+type F = GoldilocksField;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RowInstructionsBench;
+
+impl AirParameters for RowInstructionsBench {
+    type Field = GoldilocksField;
+    type CubicParams = GoldilocksCubicParameters;
+
+    type Instruction = ByteInstructionSet;
+
+    const NUM_FREE_COLUMNS: usize = 141;
+    const EXTENDED_COLUMNS: usize = 132;
+    const NUM_ARITHMETIC_COLUMNS: usize = 0;
+
+    fn num_rows_bits() -> usize {
+        16
+    }
+}
+
+type L = RowInstructionsBench;
+
+/// Builds a chip with ten pairs of byte inputs and one `And` and one `Xor` lookup per pair, and
+/// writes random inputs and the table entries to its trace.
+fn byte_ops_generator() -> (ArithmeticGenerator<L>, ByteLookupTable) {
+    let mut builder = AirBuilder::<L>::new();
+    let (mut operations, table) = builder.byte_operations();
+
+    let mut inputs = Vec::new();
+    for _ in 0..10 {
+        let a = builder.alloc::<ByteRegister>();
+        let b = builder.alloc::<ByteRegister>();
+        let a_and_b = builder.alloc::<ByteRegister>();
+        builder.set_byte_operation(&ByteOperation::And(a, b, a_and_b), &mut operations);
+        let a_xor_b = builder.alloc::<ByteRegister>();
+        builder.set_byte_operation(&ByteOperation::Xor(a, b, a_xor_b), &mut operations);
+        inputs.push((a, b));
+    }
+    builder.register_byte_lookup(operations, &table);
+
+    let (_, trace_data) = builder.build();
+    let generator = ArithmeticGenerator::new(trace_data);
+
+    let writer: TraceWriter<F> = generator.new_writer();
+    table.write_table_entries(&writer);
+    let mut rng = thread_rng();
+    for i in 0..L::num_rows() {
+        for (a, b) in inputs.iter() {
+            writer.write(a, &F::from_canonical_u8(rng.gen()), i);
+            writer.write(b, &F::from_canonical_u8(rng.gen()), i);
+        }
+    }
+    (generator, table)
+}
+
+fn bench_row_instructions(c: &mut Criterion) {
+    let (generator, _table) = byte_ops_generator();
+    let mut group = c.benchmark_group("row_instructions");
+    group.sample_size(10);
+
+    group.bench_function("serial", |b| {
+        let writer = generator.new_writer();
+        b.iter(|| {
+            for i in 0..L::num_rows() {
+                writer.write_row_instructions(&generator.air_data, i);
+            }
+        })
+    });
+
+    group.bench_function("parallel", |b| {
+        b.iter(|| generator.write_row_instructions_par(1 << 10))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_row_instructions);
+criterion_main!(benches);
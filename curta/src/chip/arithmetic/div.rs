@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+
+use crate::air::parser::AirParser;
+use crate::air::AirConstraint;
+use crate::chip::builder::AirBuilder;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::memory::MemorySlice;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::TraceWriter;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+
+/// Division in the native field of the AIR. Witnesses `result = a / b` and the inverse of `b`,
+/// asserting that
+///
+/// result * b - a = 0,
+/// b * b_inverse - 1 = 0.
+///
+/// The second constraint asserts that `b` is nonzero, so that the quotient is unique. If `b` is
+/// zero, the instruction cannot be satisfied.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct FieldDivInstruction {
+    pub a: ElementRegister,
+    pub b: ElementRegister,
+    pub result: ElementRegister,
+    b_inverse: ElementRegister,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Computes `a / b` in the native field, asserting that `b` is nonzero.
+    pub fn field_div(&mut self, a: &ElementRegister, b: &ElementRegister) -> ElementRegister
+    where
+        L::Instruction: From<FieldDivInstruction>,
+    {
+        let instr = FieldDivInstruction {
+            a: *a,
+            b: *b,
+            result: self.alloc::<ElementRegister>(),
+            b_inverse: self.alloc::<ElementRegister>(),
+        };
+        self.register_instruction(instr);
+        instr.result
+    }
+}
+
+impl<AP: AirParser> AirConstraint<AP> for FieldDivInstruction {
+    fn eval(&self, parser: &mut AP) {
+        let a = self.a.eval(parser);
+        let b = self.b.eval(parser);
+        let result = self.result.eval(parser);
+        let b_inverse = self.b_inverse.eval(parser);
+
+        // result * b = a
+        let result_times_b = parser.mul(result, b);
+        let quotient_constraint = parser.sub(result_times_b, a);
+        parser.constraint(quotient_constraint);
+
+        // b * b_inverse = 1
+        let b_times_inverse = parser.mul(b, b_inverse);
+        let nonzero_constraint = parser.sub_const(b_times_inverse, AP::Field::ONE);
+        parser.constraint(nonzero_constraint);
+    }
+}
+
+impl<F: Field> Instruction<F> for FieldDivInstruction {
+    fn trace_layout(&self) -> Vec<MemorySlice> {
+        vec![*self.result.register(), *self.b_inverse.register()]
+    }
+
+    fn inputs(&self) -> Vec<MemorySlice> {
+        vec![*self.a.register(), *self.b.register()]
+    }
+
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let a = writer.read(&self.a, row_index);
+        let b = writer.read(&self.b, row_index);
+
+        // A zero divisor has no valid witness, so the inverse is set to zero and the constraints
+        // fail.
+        let b_inverse = b.try_inverse().unwrap_or(F::ZERO);
+        writer.write(&self.result, &(a * b_inverse), row_index);
+        writer.write(&self.b_inverse, &b_inverse, row_index);
+    }
+
+    fn constraint_degree(&self) -> usize {
+        2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chip::builder::tests::*;
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub struct FieldDivTest;
+
+    impl AirParameters for FieldDivTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = FieldDivInstruction;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 0;
+        const NUM_FREE_COLUMNS: usize = 4;
+
+        fn num_rows_bits() -> usize {
+            10
+        }
+    }
+
+    /// Proves the division of `a` by `b` for the values given by `values` in each row.
+    fn prove_field_div(values: impl Fn(usize) -> (GoldilocksField, GoldilocksField) + Sync) {
+        type L = FieldDivTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let a = builder.alloc::<ElementRegister>();
+        let b = builder.alloc::<ElementRegister>();
+        let result = builder.field_div(&a, &b);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let writer = generator.new_writer();
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            let (a_value, b_value) = values(i);
+            writer.write(&a, &a_value, i);
+            writer.write(&b, &b_value, i);
+            writer.write_row_instructions(&generator.air_data, i);
+
+            if b_value != GoldilocksField::ZERO {
+                assert_eq!(writer.read(&result, i) * b_value, a_value);
+            }
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+
+    #[test]
+    fn test_field_div() {
+        type F = GoldilocksField;
+
+        // Random divisions, and divisions of zero and by one.
+        prove_field_div(|i| match i % 3 {
+            0 => (F::rand(), F::rand()),
+            1 => (F::ZERO, F::rand()),
+            _ => (F::from_canonical_usize(i), F::ONE),
+        });
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_field_div_by_zero() {
+        type F = GoldilocksField;
+
+        prove_field_div(|i| {
+            if i == 7 {
+                (F::ONE, F::ZERO)
+            } else {
+                (F::rand(), F::ONE)
+            }
+        });
+    }
+}
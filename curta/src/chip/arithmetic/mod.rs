@@ -4,6 +4,7 @@ use self::expression::ArithmeticExpression;
 use crate::air::parser::AirParser;
 use crate::air::AirConstraint;
 
+pub mod div;
 pub mod expression;
 pub mod expression_slice;
 
@@ -0,0 +1,244 @@
+use num::{BigUint, One};
+use serde::{Deserialize, Serialize};
+
+use super::{limbs_to_biguint, witness_offset};
+use crate::air::AirConstraint;
+use crate::chip::builder::AirBuilder;
+use crate::chip::field::util;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::memory::MemorySlice;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::TraceWriter;
+use crate::chip::utils::split_u32_limbs_to_u16_limbs;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::parser::PolynomialParser;
+use crate::polynomial::Polynomial;
+
+/// Big integer addition `a + b = result + carry * 2^(16 * n)`, where `n` is the number of limbs of
+/// the operands.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct BigNumAddInstruction {
+    pub a: ArrayRegister<U16Register>,
+    pub b: ArrayRegister<U16Register>,
+    pub result: ArrayRegister<U16Register>,
+    pub carry: BitRegister,
+    pub(crate) witness_low: ArrayRegister<U16Register>,
+    pub(crate) witness_high: ArrayRegister<U16Register>,
+    witness_offset: usize,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Given two big integers `a` and `b` with the same number of limbs, computes the sum
+    /// `a + b` and returns its low limbs and the carry bit.
+    pub fn bignum_add(
+        &mut self,
+        a: &ArrayRegister<U16Register>,
+        b: &ArrayRegister<U16Register>,
+    ) -> (ArrayRegister<U16Register>, BitRegister)
+    where
+        L::Instruction: From<BigNumAddInstruction>,
+    {
+        assert_eq!(
+            a.len(),
+            b.len(),
+            "Operands must have the same number of limbs"
+        );
+        let result = self.alloc_array::<U16Register>(a.len());
+        let carry = self.alloc::<BitRegister>();
+        let witness_low = self.alloc_array::<U16Register>(a.len());
+        let witness_high = self.alloc_array::<U16Register>(a.len());
+        let instr = BigNumAddInstruction {
+            a: *a,
+            b: *b,
+            result,
+            carry,
+            witness_low,
+            witness_high,
+            witness_offset: witness_offset(2),
+        };
+        self.register_instruction(instr);
+        (result, carry)
+    }
+}
+
+impl<AP: PolynomialParser> AirConstraint<AP> for BigNumAddInstruction {
+    fn eval(&self, parser: &mut AP) {
+        let p_a = Polynomial::from_coefficients(self.a.eval_vec(parser));
+        let p_b = Polynomial::from_coefficients(self.b.eval_vec(parser));
+        let p_result = Polynomial::from_coefficients(self.result.eval_vec(parser));
+
+        // carry * x^n
+        let zero = parser.zero();
+        let mut carry_coefficients = vec![zero; self.a.len()];
+        carry_coefficients.push(self.carry.eval(parser));
+        let p_carry = Polynomial::from_coefficients(carry_coefficients);
+
+        let p_a_plus_b = parser.poly_add(&p_a, &p_b);
+        let p_a_plus_b_minus_result = parser.poly_sub(&p_a_plus_b, &p_result);
+        let p_vanishing = parser.poly_sub(&p_a_plus_b_minus_result, &p_carry);
+
+        let p_witness_low = Polynomial::from_coefficients(self.witness_low.eval_vec(parser));
+        let p_witness_high = Polynomial::from_coefficients(self.witness_high.eval_vec(parser));
+
+        util::eval_root_quotient(
+            parser,
+            &p_vanishing,
+            &p_witness_low,
+            &p_witness_high,
+            self.witness_offset,
+        )
+    }
+}
+
+impl<F: PrimeField64> Instruction<F> for BigNumAddInstruction {
+    fn trace_layout(&self) -> Vec<MemorySlice> {
+        vec![
+            *self.result.register(),
+            *self.carry.register(),
+            *self.witness_low.register(),
+            *self.witness_high.register(),
+        ]
+    }
+
+    fn inputs(&self) -> Vec<MemorySlice> {
+        vec![*self.a.register(), *self.b.register()]
+    }
+
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let a_limbs = writer.read_vec(&self.a, row_index);
+        let b_limbs = writer.read_vec(&self.b, row_index);
+        let a = limbs_to_biguint(&a_limbs);
+        let b = limbs_to_biguint(&b_limbs);
+
+        // Compute the addition in the integers.
+        let nb_limbs = self.a.len();
+        let sum = a + b;
+        let result = &sum % (BigUint::one() << (16 * nb_limbs));
+        let carry = if sum.bits() > 16 * nb_limbs as u64 {
+            F::ONE
+        } else {
+            F::ZERO
+        };
+
+        // Make little endian polynomial limbs.
+        let p_a = Polynomial::from_coefficients(a_limbs);
+        let p_b = Polynomial::from_coefficients(b_limbs);
+        let p_result = Polynomial::<F>::from_biguint_field(&result, 16, nb_limbs);
+        let mut carry_coefficients = vec![F::ZERO; nb_limbs];
+        carry_coefficients.push(carry);
+        let p_carry = Polynomial::from_coefficients(carry_coefficients);
+
+        // Compute the vanishing polynomial.
+        let p_vanishing = &p_a + &p_b - &p_result - &p_carry;
+        debug_assert_eq!(p_vanishing.degree(), nb_limbs);
+
+        // Compute the witness.
+        let p_witness = util::compute_root_quotient_and_shift(&p_vanishing, self.witness_offset);
+        let (p_witness_low, p_witness_high) = split_u32_limbs_to_u16_limbs(&p_witness);
+
+        let mut values = p_result.coefficients;
+        values.push(carry);
+        values.extend_from_slice(&p_witness_low);
+        values.extend_from_slice(&p_witness_high);
+
+        // Row must match layout of instruction.
+        writer.write_unsafe_batch_raw(
+            &[
+                *self.result.register(),
+                *self.carry.register(),
+                *self.witness_low.register(),
+                *self.witness_high.register(),
+            ],
+            &values,
+            row_index,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct BigNumAddTest;
+
+    impl AirParameters for BigNumAddTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 80;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 129;
+
+        type Instruction = BigNumAddInstruction;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    #[test]
+    fn test_bignum_add() {
+        type F = GoldilocksField;
+        type L = BigNumAddTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let a = builder.alloc_array::<U16Register>(16);
+        let b = builder.alloc_array::<U16Register>(16);
+        let (result, carry) = builder.bignum_add(&a, &b);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let max = (BigUint::one() << 256) - BigUint::one();
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            let writer = generator.new_writer();
+            // The first rows cover the edge cases of all-max and carry-chain limbs.
+            let (a_int, b_int) = match i {
+                0 => (max.clone(), max.clone()),
+                1 => (max.clone(), BigUint::one()),
+                2 => (BigUint::from(0u32), BigUint::from(0u32)),
+                _ => {
+                    let mut rng = thread_rng();
+                    (rng.gen_biguint(256), rng.gen_biguint(256))
+                }
+            };
+            writer.write_slice(
+                &a,
+                &Polynomial::<F>::from_biguint_field(&a_int, 16, 16).coefficients,
+                i,
+            );
+            writer.write_slice(
+                &b,
+                &Polynomial::<F>::from_biguint_field(&b_int, 16, 16).coefficients,
+                i,
+            );
+            writer.write_row_instructions(&generator.air_data, i);
+
+            let sum = &a_int + &b_int;
+            let result_int = limbs_to_biguint(&writer.read_vec(&result, i));
+            assert_eq!(result_int, &sum % (BigUint::one() << 256));
+            let expected_carry = if sum > max { F::ONE } else { F::ZERO };
+            assert_eq!(writer.read(&carry, i), expected_carry);
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+}
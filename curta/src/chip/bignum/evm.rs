@@ -0,0 +1,505 @@
+//! Two's-complement arithmetic on 256-bit EVM words.
+//!
+//! A word is a big integer of `EVM_WORD_NB_LIMBS` limbs, read as a signed integer in
+//! `[-2^255, 2^255)` by the signed opcodes. Addition and multiplication modulo `2^256` agree for
+//! the signed and unsigned readings, so only the division needs to handle the signs.
+
+use num::{BigUint, Integer, Zero};
+use serde::{Deserialize, Serialize};
+
+use super::add::BigNumAddInstruction;
+use super::limbs_to_biguint;
+use super::mul::BigNumMulInstruction;
+use super::sub::BigNumSubInstruction;
+use crate::air::parser::AirParser;
+use crate::air::AirConstraint;
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::builder::AirBuilder;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::memory::MemorySlice;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::TraceWriter;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::parser::PolynomialParser;
+use crate::polynomial::Polynomial;
+
+/// The number of 16-bit limbs of an EVM word.
+pub const EVM_WORD_NB_LIMBS: usize = 16;
+
+/// Witnesses the quotient and remainder of the division of `a` by `b`, and whether `b` is zero.
+///
+/// The instruction only constrains `b_is_zero`, by asserting that
+///
+/// s * b_is_zero = 0,
+/// s * s_inverse + b_is_zero - 1 = 0,
+///
+/// where `s` is the sum of the limbs of `b`. The quotient and the remainder are constrained by
+/// `evm_signed_div`. If `b` is zero, the quotient is zero and the remainder is `a`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct EvmDivWitnessInstruction {
+    pub a: ArrayRegister<U16Register>,
+    pub b: ArrayRegister<U16Register>,
+    pub quotient: ArrayRegister<U16Register>,
+    pub remainder: ArrayRegister<U16Register>,
+    pub b_is_zero: BitRegister,
+    limb_sum_inverse: ElementRegister,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Computes `a + b` modulo `2^256`, as the EVM `ADD` opcode, which is also the two's-complement
+    /// sum of the signed words.
+    pub fn evm_signed_add(
+        &mut self,
+        a: &ArrayRegister<U16Register>,
+        b: &ArrayRegister<U16Register>,
+    ) -> ArrayRegister<U16Register>
+    where
+        L::Instruction: From<BigNumAddInstruction>,
+    {
+        assert_eq!(a.len(), EVM_WORD_NB_LIMBS, "Invalid number of limbs");
+        let (result, _) = self.bignum_add(a, b);
+        result
+    }
+
+    /// Computes `a * b` modulo `2^256`, as the EVM `MUL` opcode, which is also the two's-complement
+    /// product of the signed words.
+    pub fn evm_signed_mul(
+        &mut self,
+        a: &ArrayRegister<U16Register>,
+        b: &ArrayRegister<U16Register>,
+    ) -> ArrayRegister<U16Register>
+    where
+        L::Instruction: From<BigNumMulInstruction>,
+    {
+        assert_eq!(a.len(), EVM_WORD_NB_LIMBS, "Invalid number of limbs");
+        assert_eq!(b.len(), EVM_WORD_NB_LIMBS, "Invalid number of limbs");
+        let product = self.bignum_mul(a, b);
+        product.get_subarray(0..EVM_WORD_NB_LIMBS)
+    }
+
+    /// Computes the signed division of `a` by `b`, as the EVM `SDIV` opcode.
+    ///
+    /// The quotient is rounded towards zero, a division by zero gives zero, and the division of
+    /// `-2^255` by `-1` wraps around to `-2^255`.
+    pub fn evm_signed_div(
+        &mut self,
+        a: &ArrayRegister<U16Register>,
+        b: &ArrayRegister<U16Register>,
+    ) -> ArrayRegister<U16Register>
+    where
+        L::Instruction: From<BigNumAddInstruction>
+            + From<BigNumSubInstruction>
+            + From<BigNumMulInstruction>
+            + From<EvmDivWitnessInstruction>,
+    {
+        assert_eq!(a.len(), EVM_WORD_NB_LIMBS, "Invalid number of limbs");
+        assert_eq!(b.len(), EVM_WORD_NB_LIMBS, "Invalid number of limbs");
+
+        let zero = self.alloc_array::<U16Register>(EVM_WORD_NB_LIMBS);
+        for limb in zero.iter() {
+            self.set_to_expression(&limb, ArithmeticExpression::zero());
+        }
+
+        // The sign of a word is the carry of its doubling, and its absolute value is either the
+        // word or its negation.
+        let mut abs_values = Vec::with_capacity(2);
+        let mut signs = Vec::with_capacity(2);
+        for x in [a, b] {
+            let (_, sign) = self.bignum_add(x, x);
+            let (negation, _) = self.bignum_sub(&zero, x);
+            abs_values.push(self.bignum_select(&sign, &negation, x));
+            signs.push(sign);
+        }
+        let (abs_a, abs_b) = (abs_values[0], abs_values[1]);
+
+        let instr = EvmDivWitnessInstruction {
+            a: abs_a,
+            b: abs_b,
+            quotient: self.alloc_array::<U16Register>(EVM_WORD_NB_LIMBS),
+            remainder: self.alloc_array::<U16Register>(EVM_WORD_NB_LIMBS),
+            b_is_zero: self.alloc::<BitRegister>(),
+            limb_sum_inverse: self.alloc::<ElementRegister>(),
+        };
+        self.register_instruction(instr);
+        let b_is_zero = instr.b_is_zero.expr();
+        let one = ArithmeticExpression::<L::Field>::one();
+
+        // Assert that |a| = quotient * |b| + remainder without overflow.
+        let product = self.bignum_mul(&instr.quotient, &abs_b);
+        let product_low = product.get_subarray(0..EVM_WORD_NB_LIMBS);
+        let product_high = product.get_subarray(EVM_WORD_NB_LIMBS..2 * EVM_WORD_NB_LIMBS);
+        self.assert_expression_zero(product_high.expr());
+        let (sum, carry) = self.bignum_add(&product_low, &instr.remainder);
+        self.assert_expression_zero(carry.expr());
+        self.assert_expressions_equal(sum.expr(), abs_a.expr());
+
+        // Assert that remainder < |b| unless b is zero.
+        let (_, remainder_is_less) = self.bignum_sub(&instr.remainder, &abs_b);
+        self.assert_expression_zero(
+            (one.clone() - b_is_zero.clone()) * (one.clone() - remainder_is_less.expr()),
+        );
+
+        // The quotient is negative if exactly one of the operands is.
+        let (sign_a, sign_b) = (signs[0].expr(), signs[1].expr());
+        let quotient_sign = self.alloc::<BitRegister>();
+        self.set_to_expression(
+            &quotient_sign,
+            sign_a.clone() + sign_b.clone() - sign_a * sign_b * L::Field::from_canonical_u8(2),
+        );
+        let (negated_quotient, _) = self.bignum_sub(&zero, &instr.quotient);
+        let signed_quotient =
+            self.bignum_select(&quotient_sign, &negated_quotient, &instr.quotient);
+
+        let result = self.alloc_array::<U16Register>(EVM_WORD_NB_LIMBS);
+        for (limb, quotient_limb) in result.iter().zip(signed_quotient.iter()) {
+            self.set_to_expression(
+                &limb,
+                (one.clone() - b_is_zero.clone()) * quotient_limb.expr(),
+            );
+        }
+        result
+    }
+}
+
+impl<AP: AirParser> AirConstraint<AP> for EvmDivWitnessInstruction {
+    fn eval(&self, parser: &mut AP) {
+        let b = self.b.eval_vec(parser);
+        let b_is_zero = self.b_is_zero.eval(parser);
+        let limb_sum_inverse = self.limb_sum_inverse.eval(parser);
+
+        let zero = parser.zero();
+        let limb_sum = b.iter().fold(zero, |acc, limb| parser.add(acc, *limb));
+
+        // limb_sum * b_is_zero = 0
+        let zero_constraint = parser.mul(limb_sum, b_is_zero);
+        parser.constraint(zero_constraint);
+
+        // limb_sum * limb_sum_inverse + b_is_zero - 1 = 0
+        let product = parser.mul(limb_sum, limb_sum_inverse);
+        let product_plus_bit = parser.add(product, b_is_zero);
+        let inverse_constraint = parser.sub_const(product_plus_bit, AP::Field::ONE);
+        parser.constraint(inverse_constraint);
+    }
+}
+
+impl<F: PrimeField64> Instruction<F> for EvmDivWitnessInstruction {
+    fn trace_layout(&self) -> Vec<MemorySlice> {
+        vec![
+            *self.quotient.register(),
+            *self.remainder.register(),
+            *self.b_is_zero.register(),
+            *self.limb_sum_inverse.register(),
+        ]
+    }
+
+    fn inputs(&self) -> Vec<MemorySlice> {
+        vec![*self.a.register(), *self.b.register()]
+    }
+
+    fn constraint_degree(&self) -> usize {
+        2
+    }
+
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let b_limbs = writer.read_vec(&self.b, row_index);
+        let a = limbs_to_biguint(&writer.read_vec(&self.a, row_index));
+        let b = limbs_to_biguint(&b_limbs);
+
+        let (quotient, remainder) = if b.is_zero() {
+            (BigUint::zero(), a)
+        } else {
+            a.div_rem(&b)
+        };
+        let limb_sum = b_limbs.iter().fold(F::ZERO, |acc, limb| acc + *limb);
+
+        let p_quotient = Polynomial::<F>::from_biguint_field(&quotient, 16, EVM_WORD_NB_LIMBS);
+        let p_remainder = Polynomial::<F>::from_biguint_field(&remainder, 16, EVM_WORD_NB_LIMBS);
+        writer.write_slice(&self.quotient, &p_quotient.coefficients, row_index);
+        writer.write_slice(&self.remainder, &p_remainder.coefficients, row_index);
+        writer.write(
+            &self.b_is_zero,
+            &F::from_canonical_u8(b.is_zero() as u8),
+            row_index,
+        );
+        writer.write(
+            &self.limb_sum_inverse,
+            &limb_sum.try_inverse().unwrap_or(F::ZERO),
+            row_index,
+        );
+    }
+}
+
+/// The instructions needed for the EVM word arithmetic.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum EvmArithmeticInstruction {
+    Add(BigNumAddInstruction),
+    Sub(BigNumSubInstruction),
+    Mul(BigNumMulInstruction),
+    DivWitness(EvmDivWitnessInstruction),
+}
+
+impl<AP: PolynomialParser> AirConstraint<AP> for EvmArithmeticInstruction {
+    fn eval(&self, parser: &mut AP) {
+        match self {
+            EvmArithmeticInstruction::Add(instruction) => {
+                AirConstraint::<AP>::eval(instruction, parser)
+            }
+            EvmArithmeticInstruction::Sub(instruction) => {
+                AirConstraint::<AP>::eval(instruction, parser)
+            }
+            EvmArithmeticInstruction::Mul(instruction) => {
+                AirConstraint::<AP>::eval(instruction, parser)
+            }
+            EvmArithmeticInstruction::DivWitness(instruction) => {
+                AirConstraint::<AP>::eval(instruction, parser)
+            }
+        }
+    }
+}
+
+impl<F: PrimeField64> Instruction<F> for EvmArithmeticInstruction {
+    fn trace_layout(&self) -> Vec<MemorySlice> {
+        match self {
+            EvmArithmeticInstruction::Add(instruction) => {
+                Instruction::<F>::trace_layout(instruction)
+            }
+            EvmArithmeticInstruction::Sub(instruction) => {
+                Instruction::<F>::trace_layout(instruction)
+            }
+            EvmArithmeticInstruction::Mul(instruction) => {
+                Instruction::<F>::trace_layout(instruction)
+            }
+            EvmArithmeticInstruction::DivWitness(instruction) => {
+                Instruction::<F>::trace_layout(instruction)
+            }
+        }
+    }
+
+    fn inputs(&self) -> Vec<MemorySlice> {
+        match self {
+            EvmArithmeticInstruction::Add(instruction) => Instruction::<F>::inputs(instruction),
+            EvmArithmeticInstruction::Sub(instruction) => Instruction::<F>::inputs(instruction),
+            EvmArithmeticInstruction::Mul(instruction) => Instruction::<F>::inputs(instruction),
+            EvmArithmeticInstruction::DivWitness(instruction) => {
+                Instruction::<F>::inputs(instruction)
+            }
+        }
+    }
+
+    fn constraint_degree(&self) -> usize {
+        match self {
+            EvmArithmeticInstruction::Add(instruction) => {
+                Instruction::<F>::constraint_degree(instruction)
+            }
+            EvmArithmeticInstruction::Sub(instruction) => {
+                Instruction::<F>::constraint_degree(instruction)
+            }
+            EvmArithmeticInstruction::Mul(instruction) => {
+                Instruction::<F>::constraint_degree(instruction)
+            }
+            EvmArithmeticInstruction::DivWitness(instruction) => {
+                Instruction::<F>::constraint_degree(instruction)
+            }
+        }
+    }
+
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        match self {
+            EvmArithmeticInstruction::Add(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            EvmArithmeticInstruction::Sub(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            EvmArithmeticInstruction::Mul(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            EvmArithmeticInstruction::DivWitness(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+        }
+    }
+}
+
+impl From<BigNumAddInstruction> for EvmArithmeticInstruction {
+    fn from(instr: BigNumAddInstruction) -> Self {
+        EvmArithmeticInstruction::Add(instr)
+    }
+}
+
+impl From<BigNumSubInstruction> for EvmArithmeticInstruction {
+    fn from(instr: BigNumSubInstruction) -> Self {
+        EvmArithmeticInstruction::Sub(instr)
+    }
+}
+
+impl From<BigNumMulInstruction> for EvmArithmeticInstruction {
+    fn from(instr: BigNumMulInstruction) -> Self {
+        EvmArithmeticInstruction::Mul(instr)
+    }
+}
+
+impl From<EvmDivWitnessInstruction> for EvmArithmeticInstruction {
+    fn from(instr: EvmDivWitnessInstruction) -> Self {
+        EvmArithmeticInstruction::DivWitness(instr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::{RandBigInt, Sign};
+    use num::{BigInt, One, Signed};
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct EvmArithmeticTest;
+
+    impl AirParameters for EvmArithmeticTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 716;
+        const NUM_FREE_COLUMNS: usize = 12;
+        const EXTENDED_COLUMNS: usize = 1083;
+
+        type Instruction = EvmArithmeticInstruction;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    /// The two's-complement encoding of `x` as a word.
+    fn to_word(x: &BigInt) -> BigUint {
+        let modulus = BigInt::one() << 256;
+        x.mod_floor(&modulus).to_biguint().unwrap()
+    }
+
+    /// The signed integer encoded by the word `x`.
+    fn from_word(x: &BigUint) -> BigInt {
+        let x = BigInt::from_biguint(Sign::Plus, x.clone());
+        if x >= BigInt::one() << 255 {
+            x - (BigInt::one() << 256)
+        } else {
+            x
+        }
+    }
+
+    /// The result of the `SDIV` opcode.
+    fn sdiv(a: &BigUint, b: &BigUint) -> BigUint {
+        if b.is_zero() {
+            return BigUint::zero();
+        }
+        let (a, b) = (from_word(a), from_word(b));
+        // Rust's division of big integers rounds towards zero.
+        let quotient = a.abs() / b.abs();
+        let quotient = if a.is_negative() != b.is_negative() {
+            -quotient
+        } else {
+            quotient
+        };
+        to_word(&quotient)
+    }
+
+    #[test]
+    fn test_evm_reference() {
+        let int = |x: i64| to_word(&BigInt::from(x));
+        let int_min = to_word(&-(BigInt::one() << 255));
+        let int_max = to_word(&((BigInt::one() << 255) - 1));
+
+        // Known results of the EVM opcodes.
+        assert_eq!(sdiv(&int_min, &int(-1)), int_min);
+        assert_eq!(sdiv(&int(-10), &int(3)), int(-3));
+        assert_eq!(sdiv(&int(10), &int(-3)), int(-3));
+        assert_eq!(sdiv(&int(-10), &int(-3)), int(3));
+        assert_eq!(sdiv(&int(7), &int(0)), int(0));
+        assert_eq!((&int_max + int(1)) % (BigUint::one() << 256), int_min);
+        assert_eq!((int(-1) * int(-1)) % (BigUint::one() << 256), int(1));
+    }
+
+    #[test]
+    fn test_evm_signed_arithmetic() {
+        type F = GoldilocksField;
+        type L = EvmArithmeticTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let a = builder.alloc_array::<U16Register>(EVM_WORD_NB_LIMBS);
+        let b = builder.alloc_array::<U16Register>(EVM_WORD_NB_LIMBS);
+        let sum = builder.evm_signed_add(&a, &b);
+        let product = builder.evm_signed_mul(&a, &b);
+        let quotient = builder.evm_signed_div(&a, &b);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let int = |x: i64| to_word(&BigInt::from(x));
+        let int_min = to_word(&-(BigInt::one() << 255));
+        let int_max = to_word(&((BigInt::one() << 255) - 1));
+        let edge_cases = [
+            (int_min.clone(), int(-1)),
+            (int_min.clone(), int(1)),
+            (int_min.clone(), int_min.clone()),
+            (int_max.clone(), int(1)),
+            (int_max, int(-1)),
+            (int(-10), int(3)),
+            (int(10), int(-3)),
+            (int(-10), int(-3)),
+            (int(7), int(0)),
+            (int_min, int(0)),
+            (int(0), int(-5)),
+            (int(-1), int(-1)),
+        ];
+
+        let modulus = BigUint::one() << 256;
+        let writer = generator.new_writer();
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            let (a_int, b_int) = if i < edge_cases.len() {
+                edge_cases[i].clone()
+            } else {
+                let mut rng = thread_rng();
+                // Mix full-size operands with small divisors.
+                let b_bits = if i % 2 == 0 { 256 } else { 64 };
+                let b_int = to_word(&BigInt::from_biguint(
+                    if i % 4 < 2 { Sign::Plus } else { Sign::Minus },
+                    rng.gen_biguint(b_bits),
+                ));
+                (rng.gen_biguint(256), b_int)
+            };
+            writer.write_slice(
+                &a,
+                &Polynomial::<F>::from_biguint_field(&a_int, 16, 16).coefficients,
+                i,
+            );
+            writer.write_slice(
+                &b,
+                &Polynomial::<F>::from_biguint_field(&b_int, 16, 16).coefficients,
+                i,
+            );
+            writer.write_row_instructions(&generator.air_data, i);
+
+            let read = |x: &ArrayRegister<U16Register>| limbs_to_biguint(&writer.read_vec(x, i));
+            assert_eq!(read(&sum), (&a_int + &b_int) % &modulus);
+            assert_eq!(read(&product), (&a_int * &b_int) % &modulus);
+            assert_eq!(read(&quotient), sdiv(&a_int, &b_int));
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+}
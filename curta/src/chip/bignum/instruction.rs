@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+
+use super::add::BigNumAddInstruction;
+use super::mul::BigNumMulInstruction;
+use super::reduce::BigNumReduceInstruction;
+use super::rem::BigNumDivRemInstruction;
+use super::sub::BigNumSubInstruction;
+use crate::air::AirConstraint;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::memory::MemorySlice;
+use crate::chip::trace::writer::TraceWriter;
+use crate::math::prelude::*;
+use crate::polynomial::parser::PolynomialParser;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum BigNumInstruction<P: FieldParameters> {
+    Add(BigNumAddInstruction),
+    Sub(BigNumSubInstruction),
+    Mul(BigNumMulInstruction),
+    Reduce(BigNumReduceInstruction<P>),
+    DivRem(BigNumDivRemInstruction),
+}
+
+pub trait FromBigNumInstruction<P: FieldParameters>:
+    From<BigNumAddInstruction>
+    + From<BigNumSubInstruction>
+    + From<BigNumMulInstruction>
+    + From<BigNumReduceInstruction<P>>
+    + From<BigNumDivRemInstruction>
+{
+}
+
+impl<P: FieldParameters> FromBigNumInstruction<P> for BigNumInstruction<P> {}
+
+impl<AP: PolynomialParser, P: FieldParameters> AirConstraint<AP> for BigNumInstruction<P> {
+    fn eval(&self, parser: &mut AP) {
+        match self {
+            BigNumInstruction::Add(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+            BigNumInstruction::Sub(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+            BigNumInstruction::Mul(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+            BigNumInstruction::Reduce(instruction) => {
+                AirConstraint::<AP>::eval(instruction, parser)
+            }
+            BigNumInstruction::DivRem(instruction) => {
+                AirConstraint::<AP>::eval(instruction, parser)
+            }
+        }
+    }
+}
+
+impl<F: PrimeField64, P: FieldParameters> Instruction<F> for BigNumInstruction<P> {
+    fn trace_layout(&self) -> Vec<MemorySlice> {
+        match self {
+            BigNumInstruction::Add(instruction) => Instruction::<F>::trace_layout(instruction),
+            BigNumInstruction::Sub(instruction) => Instruction::<F>::trace_layout(instruction),
+            BigNumInstruction::Mul(instruction) => Instruction::<F>::trace_layout(instruction),
+            BigNumInstruction::Reduce(instruction) => Instruction::<F>::trace_layout(instruction),
+            BigNumInstruction::DivRem(instruction) => Instruction::<F>::trace_layout(instruction),
+        }
+    }
+
+    fn inputs(&self) -> Vec<MemorySlice> {
+        match self {
+            BigNumInstruction::Add(instruction) => Instruction::<F>::inputs(instruction),
+            BigNumInstruction::Sub(instruction) => Instruction::<F>::inputs(instruction),
+            BigNumInstruction::Mul(instruction) => Instruction::<F>::inputs(instruction),
+            BigNumInstruction::Reduce(instruction) => Instruction::<F>::inputs(instruction),
+            BigNumInstruction::DivRem(instruction) => Instruction::<F>::inputs(instruction),
+        }
+    }
+
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        match self {
+            BigNumInstruction::Add(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            BigNumInstruction::Sub(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            BigNumInstruction::Mul(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            BigNumInstruction::Reduce(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            BigNumInstruction::DivRem(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+        }
+    }
+}
+
+impl<P: FieldParameters> From<BigNumAddInstruction> for BigNumInstruction<P> {
+    fn from(instr: BigNumAddInstruction) -> Self {
+        BigNumInstruction::Add(instr)
+    }
+}
+
+impl<P: FieldParameters> From<BigNumSubInstruction> for BigNumInstruction<P> {
+    fn from(instr: BigNumSubInstruction) -> Self {
+        BigNumInstruction::Sub(instr)
+    }
+}
+
+impl<P: FieldParameters> From<BigNumMulInstruction> for BigNumInstruction<P> {
+    fn from(instr: BigNumMulInstruction) -> Self {
+        BigNumInstruction::Mul(instr)
+    }
+}
+
+impl<P: FieldParameters> From<BigNumReduceInstruction<P>> for BigNumInstruction<P> {
+    fn from(instr: BigNumReduceInstruction<P>) -> Self {
+        BigNumInstruction::Reduce(instr)
+    }
+}
+
+impl<P: FieldParameters> From<BigNumDivRemInstruction> for BigNumInstruction<P> {
+    fn from(instr: BigNumDivRemInstruction) -> Self {
+        BigNumInstruction::DivRem(instr)
+    }
+}
@@ -0,0 +1,82 @@
+//! Implements arithmetic over the integers for multi-limb numbers, as needed to emulate
+//! non-native fields.
+//!
+//! A big integer is represented, as the elements of `FieldRegister`, by an array of little-endian
+//! 16-bit limbs, each of which is range checked using a lookup. The operations use the technique
+//! of the field instructions: for `a + b = result + carry * 2^(16 * n)`, say, the prover witnesses
+//! a polynomial `w(x)` such that
+//!
+//! a(x) + b(x) - result(x) - carry * x^n - (x - 2^16) * w(x) = 0.
+//!
+//! Unlike the field instructions, the operations are not reduced modulo a prime, so that the
+//! results of additions and subtractions carry an overflow bit and products have the combined
+//! number of limbs of their factors. The reduction modulo the prime of a `FieldParameters` type is
+//! a separate instruction.
+
+pub mod add;
+pub mod evm;
+pub mod instruction;
+pub mod mul;
+pub mod reduce;
+pub mod rem;
+pub mod sub;
+
+use num::BigUint;
+
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::builder::AirBuilder;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::Register;
+use crate::chip::utils::digits_to_biguint;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+
+/// The offset of the witness polynomial of an operation whose vanishing polynomial has
+/// coefficients that are sums of at most `num_terms` products of limbs.
+///
+/// Such coefficients are less than `num_terms * 2^32` in absolute value, so that the coefficients
+/// of the witness are less than `num_terms * 2^16 * (1 + 2^-15)`.
+pub(crate) fn witness_offset(num_terms: usize) -> usize {
+    assert!(
+        num_terms <= 1 << 14,
+        "Too many terms for the witness to fit in two limbs"
+    );
+    num_terms.next_power_of_two() << 17
+}
+
+/// The integer with the given little-endian 16-bit limbs.
+pub(crate) fn limbs_to_biguint<F: PrimeField64>(limbs: &[F]) -> BigUint {
+    let digits = limbs
+        .iter()
+        .map(|x| x.as_canonical_u64() as u16)
+        .collect::<Vec<_>>();
+    digits_to_biguint(&digits)
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Given two big integers `a` and `b` with the same number of limbs, returns `a` if `bit` is
+    /// one and `b` otherwise.
+    pub fn bignum_select(
+        &mut self,
+        bit: &BitRegister,
+        a: &ArrayRegister<U16Register>,
+        b: &ArrayRegister<U16Register>,
+    ) -> ArrayRegister<U16Register> {
+        assert_eq!(
+            a.len(),
+            b.len(),
+            "Operands must have the same number of limbs"
+        );
+        let result = self.alloc_array::<U16Register>(a.len());
+        let one = ArithmeticExpression::<L::Field>::one();
+        for ((limb, a_limb), b_limb) in result.iter().zip(a.iter()).zip(b.iter()) {
+            self.set_to_expression(
+                &limb,
+                bit.expr() * a_limb.expr() + (one.clone() - bit.expr()) * b_limb.expr(),
+            );
+        }
+        result
+    }
+}
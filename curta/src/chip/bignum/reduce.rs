@@ -0,0 +1,244 @@
+use serde::{Deserialize, Serialize};
+
+use super::{limbs_to_biguint, witness_offset};
+use crate::air::AirConstraint;
+use crate::chip::builder::AirBuilder;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::field::register::FieldRegister;
+use crate::chip::field::util;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::memory::MemorySlice;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::TraceWriter;
+use crate::chip::utils::split_u32_limbs_to_u16_limbs;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::parser::PolynomialParser;
+use crate::polynomial::{to_u16_le_limbs_polynomial, Polynomial};
+
+/// Reduction of a big integer modulo the prime `p` of `P`, witnessing the quotient `q` such that
+/// `a = q * p + result`.
+///
+/// As with the field instructions, the result is not constrained to be less than `p`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct BigNumReduceInstruction<P: FieldParameters> {
+    pub a: ArrayRegister<U16Register>,
+    pub result: FieldRegister<P>,
+    pub(crate) quotient: ArrayRegister<U16Register>,
+    pub(crate) witness_low: ArrayRegister<U16Register>,
+    pub(crate) witness_high: ArrayRegister<U16Register>,
+    witness_offset: usize,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Given a big integer `a` with at least `P::NB_LIMBS` limbs, computes `a mod p`.
+    pub fn bignum_mod_reduce<P: FieldParameters>(
+        &mut self,
+        a: &ArrayRegister<U16Register>,
+    ) -> FieldRegister<P>
+    where
+        L::Instruction: From<BigNumReduceInstruction<P>>,
+    {
+        assert!(
+            a.len() >= P::NB_LIMBS,
+            "Cannot reduce a number with fewer limbs than the modulus"
+        );
+        // Since p >= 2^(16 * (k - 1)), where k is the number of limbs of p, the quotient fits in
+        // a.len() - k + 1 limbs.
+        let nb_modulus_limbs = (P::modulus().bits() as usize + 15) / 16;
+        let nb_quotient_limbs = a.len() - nb_modulus_limbs + 1;
+
+        // The modulus polynomial has `P::NB_LIMBS` coefficients, which bounds the degree of the
+        // vanishing polynomial.
+        let nb_witness_limbs = nb_quotient_limbs + P::NB_LIMBS - 2;
+
+        let result = self.alloc::<FieldRegister<P>>();
+        let quotient = self.alloc_array::<U16Register>(nb_quotient_limbs);
+        let witness_low = self.alloc_array::<U16Register>(nb_witness_limbs);
+        let witness_high = self.alloc_array::<U16Register>(nb_witness_limbs);
+        let instr = BigNumReduceInstruction {
+            a: *a,
+            result,
+            quotient,
+            witness_low,
+            witness_high,
+            witness_offset: witness_offset(nb_quotient_limbs.min(P::NB_LIMBS) + 2),
+        };
+        self.register_instruction(instr);
+        result
+    }
+}
+
+impl<AP: PolynomialParser, P: FieldParameters> AirConstraint<AP> for BigNumReduceInstruction<P> {
+    fn eval(&self, parser: &mut AP) {
+        let p_a = Polynomial::from_coefficients(self.a.eval_vec(parser));
+        let p_result = self.result.eval(parser);
+        let p_quotient = Polynomial::from_coefficients(self.quotient.eval_vec(parser));
+        let p_limbs = parser.constant_poly(&Polynomial::from_iter(util::modulus_field_iter::<
+            AP::Field,
+            P,
+        >()));
+
+        let p_quotient_mul_modulus = parser.poly_mul(&p_quotient, &p_limbs);
+        let p_a_minus_result = parser.poly_sub(&p_a, &p_result);
+        let p_vanishing = parser.poly_sub(&p_a_minus_result, &p_quotient_mul_modulus);
+
+        let p_witness_low = Polynomial::from_coefficients(self.witness_low.eval_vec(parser));
+        let p_witness_high = Polynomial::from_coefficients(self.witness_high.eval_vec(parser));
+
+        util::eval_root_quotient(
+            parser,
+            &p_vanishing,
+            &p_witness_low,
+            &p_witness_high,
+            self.witness_offset,
+        )
+    }
+}
+
+impl<F: PrimeField64, P: FieldParameters> Instruction<F> for BigNumReduceInstruction<P> {
+    fn trace_layout(&self) -> Vec<MemorySlice> {
+        vec![
+            *self.result.register(),
+            *self.quotient.register(),
+            *self.witness_low.register(),
+            *self.witness_high.register(),
+        ]
+    }
+
+    fn inputs(&self) -> Vec<MemorySlice> {
+        vec![*self.a.register()]
+    }
+
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let a_limbs = writer.read_vec(&self.a, row_index);
+        let a = limbs_to_biguint(&a_limbs);
+
+        // Compute the reduction in the integers.
+        let modulus = P::modulus();
+        let result = &a % &modulus;
+        let quotient = &a / &modulus;
+        debug_assert_eq!(&quotient * &modulus + &result, a);
+
+        // Make little endian polynomial limbs.
+        let p_a = Polynomial::from_coefficients(a_limbs);
+        let p_modulus = to_u16_le_limbs_polynomial::<F, P>(&modulus);
+        let p_result = to_u16_le_limbs_polynomial::<F, P>(&result);
+        let p_quotient = Polynomial::<F>::from_biguint_field(&quotient, 16, self.quotient.len());
+
+        // Compute the vanishing polynomial.
+        let p_vanishing = &p_a - &p_result - &p_quotient * &p_modulus;
+        debug_assert_eq!(p_vanishing.degree(), self.witness_low.len());
+
+        // Compute the witness.
+        let p_witness = util::compute_root_quotient_and_shift(&p_vanishing, self.witness_offset);
+        let (p_witness_low, p_witness_high) = split_u32_limbs_to_u16_limbs(&p_witness);
+
+        let mut values = p_result.coefficients;
+        values.extend_from_slice(p_quotient.coefficients());
+        values.extend_from_slice(&p_witness_low);
+        values.extend_from_slice(&p_witness_high);
+
+        // Row must match layout of instruction.
+        writer.write_unsafe_batch_raw(
+            &[
+                *self.result.register(),
+                *self.quotient.register(),
+                *self.witness_low.register(),
+                *self.witness_high.register(),
+            ],
+            &values,
+            row_index,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use num::{BigUint, One};
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::bignum::instruction::BigNumInstruction;
+    use crate::chip::builder::tests::*;
+    use crate::chip::field::parameters::tests::Fp25519;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct BigNumReduceTest;
+
+    impl AirParameters for BigNumReduceTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 222;
+        const NUM_FREE_COLUMNS: usize = 1;
+        const EXTENDED_COLUMNS: usize = 342;
+
+        type Instruction = BigNumInstruction<Fp25519>;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    #[test]
+    fn test_bignum_mul_mod_reduce() {
+        type F = GoldilocksField;
+        type L = BigNumReduceTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type P = Fp25519;
+
+        let p = P::modulus();
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let a = builder.alloc_array::<U16Register>(16);
+        let b = builder.alloc_array::<U16Register>(16);
+        let product = builder.bignum_mul(&a, &b);
+        let result = builder.bignum_mod_reduce::<P>(&product);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let max = (BigUint::one() << 256) - BigUint::one();
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            let writer = generator.new_writer();
+            // The first rows have a maximal product and a product that is a multiple of p.
+            let (a_int, b_int) = match i {
+                0 => (max.clone(), max.clone()),
+                1 => (p.clone(), max.clone()),
+                _ => {
+                    let mut rng = thread_rng();
+                    (rng.gen_biguint(256), rng.gen_biguint(256))
+                }
+            };
+            writer.write_slice(
+                &a,
+                &Polynomial::<F>::from_biguint_field(&a_int, 16, 16).coefficients,
+                i,
+            );
+            writer.write_slice(
+                &b,
+                &Polynomial::<F>::from_biguint_field(&b_int, 16, 16).coefficients,
+                i,
+            );
+            writer.write_row_instructions(&generator.air_data, i);
+
+            let result_int = limbs_to_biguint(&writer.read(&result, i).coefficients);
+            assert_eq!(result_int, (a_int * b_int) % &p);
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+}
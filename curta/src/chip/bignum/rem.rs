@@ -0,0 +1,189 @@
+use num::{BigUint, Integer, Zero};
+use serde::{Deserialize, Serialize};
+
+use super::add::BigNumAddInstruction;
+use super::limbs_to_biguint;
+use super::mul::BigNumMulInstruction;
+use super::sub::BigNumSubInstruction;
+use crate::air::parser::AirParser;
+use crate::air::AirConstraint;
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::builder::AirBuilder;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::memory::MemorySlice;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::TraceWriter;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::Polynomial;
+
+/// Witnesses the quotient and remainder of the division of `a` by a `modulus` given in the trace.
+///
+/// The instruction has no constraints of its own: the division is checked by `bignum_rem` using
+/// the other big integer instructions. The remainder has the combined number of limbs of `a` and
+/// the modulus, so that it can be added to the product of the quotient and the modulus.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct BigNumDivRemInstruction {
+    pub a: ArrayRegister<U16Register>,
+    pub modulus: ArrayRegister<U16Register>,
+    pub quotient: ArrayRegister<U16Register>,
+    pub remainder: ArrayRegister<U16Register>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Given a big integer `a` and a nonzero big integer `modulus`, computes `a mod modulus` with
+    /// the number of limbs of the modulus.
+    ///
+    /// Unlike `bignum_mod_reduce`, the modulus is a register, and the result is asserted to be
+    /// less than the modulus. The constraints cannot be satisfied if the modulus is zero.
+    pub fn bignum_rem(
+        &mut self,
+        a: &ArrayRegister<U16Register>,
+        modulus: &ArrayRegister<U16Register>,
+    ) -> ArrayRegister<U16Register>
+    where
+        L::Instruction: From<BigNumAddInstruction>
+            + From<BigNumSubInstruction>
+            + From<BigNumMulInstruction>
+            + From<BigNumDivRemInstruction>,
+    {
+        let nb_limbs = a.len();
+        let nb_modulus_limbs = modulus.len();
+        let instr = BigNumDivRemInstruction {
+            a: *a,
+            modulus: *modulus,
+            quotient: self.alloc_array::<U16Register>(nb_limbs),
+            remainder: self.alloc_array::<U16Register>(nb_limbs + nb_modulus_limbs),
+        };
+        self.register_instruction(instr);
+
+        let remainder = instr.remainder.get_subarray(0..nb_modulus_limbs);
+        let remainder_high = instr
+            .remainder
+            .get_subarray(nb_modulus_limbs..nb_limbs + nb_modulus_limbs);
+        self.assert_expression_zero(remainder_high.expr());
+
+        // Assert that a = quotient * modulus + remainder without overflow.
+        let product = self.bignum_mul(&instr.quotient, modulus);
+        let (sum, carry) = self.bignum_add(&product, &instr.remainder);
+        self.assert_expression_zero(carry.expr());
+        self.assert_expressions_equal(sum.get_subarray(0..nb_limbs).expr(), a.expr());
+        self.assert_expression_zero(
+            sum.get_subarray(nb_limbs..nb_limbs + nb_modulus_limbs)
+                .expr(),
+        );
+
+        // Assert that remainder < modulus.
+        let (_, is_less) = self.bignum_sub(&remainder, modulus);
+        self.assert_expression_zero(is_less.expr() - ArithmeticExpression::one());
+
+        remainder
+    }
+}
+
+impl<AP: AirParser> AirConstraint<AP> for BigNumDivRemInstruction {
+    fn eval(&self, _parser: &mut AP) {}
+}
+
+impl<F: PrimeField64> Instruction<F> for BigNumDivRemInstruction {
+    fn trace_layout(&self) -> Vec<MemorySlice> {
+        vec![*self.quotient.register(), *self.remainder.register()]
+    }
+
+    fn inputs(&self) -> Vec<MemorySlice> {
+        vec![*self.a.register(), *self.modulus.register()]
+    }
+
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let a = limbs_to_biguint(&writer.read_vec(&self.a, row_index));
+        let modulus = limbs_to_biguint(&writer.read_vec(&self.modulus, row_index));
+
+        // A zero modulus has no valid witness, so the remainder is set to `a` and the constraints
+        // fail.
+        let (quotient, remainder) = if modulus.is_zero() {
+            (BigUint::zero(), a)
+        } else {
+            a.div_rem(&modulus)
+        };
+
+        let p_quotient = Polynomial::<F>::from_biguint_field(&quotient, 16, self.quotient.len());
+        let p_remainder = Polynomial::<F>::from_biguint_field(&remainder, 16, self.remainder.len());
+        writer.write_slice(&self.quotient, &p_quotient.coefficients, row_index);
+        writer.write_slice(&self.remainder, &p_remainder.coefficients, row_index);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::bignum::instruction::BigNumInstruction;
+    use crate::chip::builder::tests::*;
+    use crate::chip::field::parameters::tests::Fp25519;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct BigNumRemTest;
+
+    impl AirParameters for BigNumRemTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 230;
+        const NUM_FREE_COLUMNS: usize = 3;
+        const EXTENDED_COLUMNS: usize = 354;
+
+        type Instruction = BigNumInstruction<Fp25519>;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    #[test]
+    fn test_bignum_rem() {
+        type F = GoldilocksField;
+        type L = BigNumRemTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let a = builder.alloc_array::<U16Register>(16);
+        let modulus = builder.alloc_array::<U16Register>(8);
+        let result = builder.bignum_rem(&a, &modulus);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let writer = generator.new_writer();
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            let mut rng = thread_rng();
+            let a_int = rng.gen_biguint(256);
+            // Mix full-size moduli with small ones.
+            let modulus_bits = if i % 2 == 0 { 128 } else { 20 };
+            let modulus_int =
+                rng.gen_biguint_range(&BigUint::from(1u32), &(BigUint::from(1u32) << modulus_bits));
+
+            let p_a = Polynomial::<F>::from_biguint_field(&a_int, 16, 16);
+            let p_modulus = Polynomial::<F>::from_biguint_field(&modulus_int, 16, 8);
+            writer.write_slice(&a, &p_a.coefficients, i);
+            writer.write_slice(&modulus, &p_modulus.coefficients, i);
+            writer.write_row_instructions(&generator.air_data, i);
+
+            let result_int = limbs_to_biguint(&writer.read_vec(&result, i));
+            assert_eq!(result_int, &a_int % &modulus_int);
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+}
@@ -37,7 +37,9 @@ impl<L: AirParameters> AirBuilder<L> {
     }
 
     fn get_public_memory(&mut self, size: usize) -> MemorySlice {
-        self.shared_memory.get_public_memory(size)
+        let register = self.shared_memory.get_public_memory(size);
+        self.public_registers.push(register);
+        register
     }
 
     /// Allocates `size` cells/columns worth of memory and returns it as a `MemorySlice`. Each
@@ -3,9 +3,11 @@ pub mod memory;
 pub mod range_check;
 pub mod shared_memory;
 
+use alloc::collections::BTreeMap;
 use core::cmp::Ordering;
 
 use anyhow::Result;
+use num::BigUint;
 use serde::{Deserialize, Serialize};
 
 use self::shared_memory::SharedMemory;
@@ -13,12 +15,13 @@ use super::arithmetic::expression::ArithmeticExpression;
 use super::constraint::Constraint;
 use super::instruction::set::AirInstruction;
 use super::register::element::ElementRegister;
+use super::register::memory::MemorySlice;
 use super::register::{Register, RegisterSerializable};
 use super::table::accumulator::Accumulator;
 use super::table::bus::channel::BusChannel;
 use super::table::evaluation::Evaluation;
 use super::table::lookup::Lookup;
-use super::{AirParameters, Chip};
+use super::{AirLayout, AirParameters, AirUsage, Chip};
 use crate::math::prelude::*;
 
 #[derive(Debug, Clone)]
@@ -30,6 +33,7 @@ pub struct AirBuilder<L: AirParameters> {
     extended_index: usize,
     shared_memory: SharedMemory,
     global_arithmetic: Vec<ElementRegister>,
+    public_registers: Vec<MemorySlice>,
     pub(crate) instructions: Vec<AirInstruction<L::Field, L::Instruction>>,
     pub(crate) global_instructions: Vec<AirInstruction<L::Field, L::Instruction>>,
     pub(crate) constraints: Vec<Constraint<L>>,
@@ -39,6 +43,7 @@ pub struct AirBuilder<L: AirParameters> {
     pub(crate) lookup_data: Vec<Lookup<L::Field, L::CubicParams>>,
     pub(crate) evaluation_data: Vec<Evaluation<L::Field, L::CubicParams>>,
     range_data: Option<Lookup<L::Field, L::CubicParams>>,
+    pub(crate) field_constants: BTreeMap<MemorySlice, BigUint>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,6 +58,14 @@ pub struct AirTraceData<L: AirParameters> {
     pub lookup_data: Vec<Lookup<L::Field, L::CubicParams>>,
     pub evaluation_data: Vec<Evaluation<L::Field, L::CubicParams>>,
     pub range_data: Option<Lookup<L::Field, L::CubicParams>>,
+    pub(crate) usage: AirUsage,
+}
+
+impl<L: AirParameters> AirTraceData<L> {
+    /// Returns the columns and lookup values used by the chip.
+    pub fn report_usage(&self) -> AirUsage {
+        self.usage
+    }
 }
 
 impl<L: AirParameters> AirBuilder<L> {
@@ -68,6 +81,7 @@ impl<L: AirParameters> AirBuilder<L> {
             next_arithmetic_index: 0,
             extended_index: L::NUM_ARITHMETIC_COLUMNS + L::NUM_FREE_COLUMNS,
             global_arithmetic: Vec::new(),
+            public_registers: Vec::new(),
             shared_memory,
             instructions: Vec::new(),
             global_instructions: Vec::new(),
@@ -78,6 +92,7 @@ impl<L: AirParameters> AirBuilder<L> {
             lookup_data: Vec::new(),
             evaluation_data: Vec::new(),
             range_data: None,
+            field_constants: BTreeMap::new(),
         }
     }
 
@@ -174,6 +189,22 @@ impl<L: AirParameters> AirBuilder<L> {
 
         // Check the number of columns in comparison to config
         let num_free_columns = self.local_index - L::NUM_ARITHMETIC_COLUMNS;
+        let num_arithmetic_columns = self.local_arithmetic_index;
+        let num_extended_columns =
+            self.extended_index - L::NUM_ARITHMETIC_COLUMNS - L::NUM_FREE_COLUMNS;
+
+        let layout = AirLayout {
+            num_arithmetic_columns,
+            num_free_columns,
+            num_extended_columns,
+        };
+        #[cfg(debug_assertions)]
+        L::validate_layout(&layout);
+
+        let usage = AirUsage {
+            layout,
+            num_lookup_values: self.lookup_data.iter().map(Lookup::num_values).sum(),
+        };
 
         match num_free_columns.cmp(&L::NUM_FREE_COLUMNS) {
             Ordering::Greater => panic!(
@@ -190,8 +221,6 @@ impl<L: AirParameters> AirBuilder<L> {
             Ordering::Equal => {}
         }
 
-        let num_arithmetic_columns = self.local_arithmetic_index;
-
         match num_arithmetic_columns.cmp(&L::NUM_ARITHMETIC_COLUMNS) {
             Ordering::Greater => panic!(
                 "Not enough arithmetic columns. Expected {} arithmetic columns, got {}.",
@@ -207,9 +236,6 @@ impl<L: AirParameters> AirBuilder<L> {
             Ordering::Equal => {}
         }
 
-        let num_extended_columns =
-            self.extended_index - L::NUM_ARITHMETIC_COLUMNS - L::NUM_FREE_COLUMNS;
-
         match num_extended_columns.cmp(&L::EXTENDED_COLUMNS) {
             Ordering::Greater => panic!(
                 "Not enough extended columns. Expected {} extended columns, got {}.",
@@ -234,6 +260,7 @@ impl<L: AirParameters> AirBuilder<L> {
                 execution_trace_length,
                 num_public_values: self.shared_memory.public_index(),
                 num_global_values: self.shared_memory.global_index(),
+                public_inputs_layout: self.public_registers,
             },
             AirTraceData {
                 num_challenges: self.shared_memory.challenge_index(),
@@ -246,6 +273,7 @@ impl<L: AirParameters> AirBuilder<L> {
                 lookup_data: self.lookup_data,
                 evaluation_data: self.evaluation_data,
                 range_data: self.range_data,
+                usage,
             },
         )
     }
@@ -479,4 +507,77 @@ pub(crate) mod tests {
         // Test the recursive proof.
         test_recursive_starky(stark, config, generator, &public_inputs);
     }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct StrictLayoutParameters;
+
+    impl AirParameters for StrictLayoutParameters {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = EmptyInstruction<GoldilocksField>;
+        const NUM_ARITHMETIC_COLUMNS: usize = 0;
+        const NUM_FREE_COLUMNS: usize = 3;
+
+        fn num_rows_bits() -> usize {
+            10
+        }
+
+        fn validate_layout(layout: &crate::chip::AirLayout) {
+            layout.assert_declared::<Self>();
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Column layout mismatch")]
+    fn test_builder_validate_layout() {
+        let mut builder = AirBuilder::<StrictLayoutParameters>::new();
+        let x_0 = builder.alloc::<ElementRegister>();
+        let x_1 = builder.alloc::<ElementRegister>();
+        builder.set_to_expression_transition(&x_0.next(), x_1.expr());
+
+        // Only two of the three declared free columns are used.
+        builder.build();
+    }
+
+    #[test]
+    fn test_builder_public_inputs_layout() {
+        use plonky2::plonk::circuit_builder::CircuitBuilder;
+        use plonky2::plonk::circuit_data::CircuitConfig;
+
+        type L = FibonacciParameters;
+
+        let build_chip = || {
+            let mut builder = AirBuilder::<L>::new();
+            let x_0 = builder.alloc::<ElementRegister>();
+            let x_1 = builder.alloc::<ElementRegister>();
+            let initial = builder.alloc_array_public::<ElementRegister>(2);
+            let last = builder.alloc_public::<ElementRegister>();
+            builder.set_to_expression_transition(&x_0.next(), x_1.expr());
+            builder.set_to_expression_transition(&x_1.next(), x_0.expr() + x_1.expr());
+            builder.assert_equal_first_row(&x_0, &initial.get(0));
+            builder.assert_equal_first_row(&x_1, &initial.get(1));
+            builder.assert_equal_last_row(&x_1, &last);
+            let (air, _) = builder.build();
+            (air, *initial.register(), *last.register())
+        };
+
+        let (air, initial, last) = build_chip();
+        let (other_air, _, _) = build_chip();
+        assert_eq!(air.public_inputs_layout(), &[initial, last]);
+        assert_eq!(air.public_inputs_layout(), other_air.public_inputs_layout());
+
+        let mut circuit_builder =
+            CircuitBuilder::<GoldilocksField, 2>::new(CircuitConfig::standard_recursion_config());
+        let initial_targets = circuit_builder.add_virtual_targets(2);
+        let last_target = circuit_builder.add_virtual_target();
+
+        // The order of the bindings does not affect the public inputs.
+        let public_inputs =
+            air.bind_public_inputs(&[(last, &[last_target][..]), (initial, &initial_targets[..])]);
+        assert_eq!(
+            public_inputs,
+            vec![initial_targets[0], initial_targets[1], last_target]
+        );
+    }
 }
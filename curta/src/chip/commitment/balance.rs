@@ -0,0 +1,121 @@
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::Target;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+/// The number of bits of a note value and of the transaction fee.
+pub const BALANCE_VALUE_BITS: usize = 32;
+
+/// The number of bits the running sums of a transaction are range checked to. Since the sums are
+/// checked at every step, they can never wrap around the field modulus.
+pub const BALANCE_SUM_BITS: usize = 48;
+
+pub trait CircuitBuilderBalance<F: RichField + Extendable<D>, const D: usize> {
+    /// Range checks `a` and `b` to `BALANCE_SUM_BITS` bits and returns their sum, which is range
+    /// checked to the same number of bits.
+    fn add_range_checked(&mut self, a: Target, b: Target) -> Target;
+
+    /// Asserts that the sum of `input_values` is equal to the sum of `output_values` plus `fee`.
+    ///
+    /// All values are range checked to `BALANCE_VALUE_BITS` bits, and all partial sums to
+    /// `BALANCE_SUM_BITS` bits, so that the balance equation holds over the integers.
+    fn verify_balance(&mut self, input_values: &[Target], output_values: &[Target], fee: Target);
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderBalance<F, D>
+    for CircuitBuilder<F, D>
+{
+    fn add_range_checked(&mut self, a: Target, b: Target) -> Target {
+        self.range_check(a, BALANCE_SUM_BITS);
+        self.range_check(b, BALANCE_SUM_BITS);
+        let sum = self.add(a, b);
+        self.range_check(sum, BALANCE_SUM_BITS);
+        sum
+    }
+
+    fn verify_balance(&mut self, input_values: &[Target], output_values: &[Target], fee: Target) {
+        let max_values = 1 << (BALANCE_SUM_BITS - BALANCE_VALUE_BITS);
+        assert!(
+            input_values.len() < max_values && output_values.len() < max_values,
+            "Too many values in transaction"
+        );
+
+        for value in input_values.iter().chain(output_values).chain([&fee]) {
+            self.range_check(*value, BALANCE_VALUE_BITS);
+        }
+
+        let input_sum = input_values.iter().fold(self.zero(), |acc, value| {
+            self.add_range_checked(acc, *value)
+        });
+        let output_sum = output_values
+            .iter()
+            .fold(fee, |acc, value| self.add_range_checked(acc, *value));
+
+        self.connect(input_sum, output_sum);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::{Field, Field64};
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_data::{CircuitConfig, CircuitData};
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+
+    use super::*;
+
+    type F = GoldilocksField;
+    type C = PoseidonGoldilocksConfig;
+    const D: usize = 2;
+
+    fn balance_circuit(
+        num_inputs: usize,
+        num_outputs: usize,
+    ) -> (CircuitData<F, C, D>, Vec<Target>, Vec<Target>, Target) {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let inputs = builder.add_virtual_targets(num_inputs);
+        let outputs = builder.add_virtual_targets(num_outputs);
+        let fee = builder.add_virtual_target();
+        builder.verify_balance(&inputs, &outputs, fee);
+
+        (builder.build::<C>(), inputs, outputs, fee)
+    }
+
+    fn prove_balance(input_values: &[u64], output_values: &[u64], fee_value: u64) {
+        let (data, inputs, outputs, fee) = balance_circuit(input_values.len(), output_values.len());
+
+        let mut pw = PartialWitness::new();
+        for (target, value) in inputs.iter().zip(input_values) {
+            pw.set_target(*target, F::from_canonical_u64(*value));
+        }
+        for (target, value) in outputs.iter().zip(output_values) {
+            pw.set_target(*target, F::from_canonical_u64(*value));
+        }
+        pw.set_target(fee, F::from_canonical_u64(fee_value));
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+
+    #[test]
+    fn test_balance() {
+        prove_balance(&[100, 250, u32::MAX as u64], &[300, u32::MAX as u64], 50);
+        prove_balance(&[10], &[], 10);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_unbalanced() {
+        prove_balance(&[100, 250], &[300], 49);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_balance_wraparound() {
+        // An output of `p - 1` would balance the transaction modulo the field order.
+        prove_balance(&[100], &[F::ORDER - 1], 101);
+    }
+}
@@ -0,0 +1,127 @@
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::{BoolTarget, Target};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+/// Returns the number of bits needed to represent every integer in `[0, max_count]`.
+fn count_bits(max_count: usize) -> usize {
+    (usize::BITS - max_count.leading_zeros()) as usize
+}
+
+pub trait CircuitBuilderBitmap<F: RichField + Extendable<D>, const D: usize> {
+    /// Asserts that every entry of `bitmap` is a bit and returns the number of set bits.
+    fn popcount(&mut self, bitmap: &[Target]) -> Target;
+
+    /// Asserts that `bitmap` is a bitmap with exactly `claimed_count` set bits.
+    fn verify_bitmap_popcount(&mut self, bitmap: &[Target], claimed_count: Target);
+
+    /// Asserts that `count >= threshold`, where `count` is at most `max_count`.
+    ///
+    /// The difference `count - threshold` is range checked to the bit length of `max_count`, which
+    /// is sound as long as `threshold` is small compared to the field order.
+    fn assert_count_at_least(&mut self, count: Target, threshold: Target, max_count: usize);
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderBitmap<F, D>
+    for CircuitBuilder<F, D>
+{
+    fn popcount(&mut self, bitmap: &[Target]) -> Target {
+        for bit in bitmap.iter() {
+            self.assert_bool(BoolTarget::new_unsafe(*bit));
+        }
+        self.add_many(bitmap)
+    }
+
+    fn verify_bitmap_popcount(&mut self, bitmap: &[Target], claimed_count: Target) {
+        let count = self.popcount(bitmap);
+        self.connect(count, claimed_count);
+    }
+
+    fn assert_count_at_least(&mut self, count: Target, threshold: Target, max_count: usize) {
+        let difference = self.sub(count, threshold);
+        match count_bits(max_count) {
+            0 => self.assert_zero(difference),
+            bits => self.range_check(difference, bits),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::Field;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+
+    use super::*;
+
+    type F = GoldilocksField;
+    type C = PoseidonGoldilocksConfig;
+    const D: usize = 2;
+
+    const BITMAP: [bool; 10] = [
+        true, false, true, true, false, false, true, false, true, false,
+    ];
+
+    fn prove_bitmap(bitmap_values: &[u64], claimed_count: u64, threshold: u64) {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let bitmap = builder.add_virtual_targets(bitmap_values.len());
+        let count = builder.add_virtual_target();
+        let threshold_target = builder.add_virtual_target();
+        builder.verify_bitmap_popcount(&bitmap, count);
+        builder.assert_count_at_least(count, threshold_target, bitmap.len());
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+
+        for (bit, value) in bitmap.iter().zip(bitmap_values.iter()) {
+            pw.set_target(*bit, F::from_canonical_u64(*value));
+        }
+        pw.set_target(count, F::from_canonical_u64(claimed_count));
+        pw.set_target(threshold_target, F::from_canonical_u64(threshold));
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+
+    fn bitmap_values() -> Vec<u64> {
+        BITMAP.iter().map(|bit| *bit as u64).collect()
+    }
+
+    #[test]
+    fn test_bitmap_popcount() {
+        assert_eq!(count_bits(10), 4);
+        assert_eq!(count_bits(16), 5);
+
+        prove_bitmap(&bitmap_values(), 5, 0);
+        // The count meets the threshold exactly.
+        prove_bitmap(&bitmap_values(), 5, 5);
+        prove_bitmap(&[1; 16], 16, 11);
+        prove_bitmap(&[], 0, 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bitmap_popcount_wrong_count() {
+        prove_bitmap(&bitmap_values(), 4, 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bitmap_popcount_not_bits() {
+        // The entries sum to the claimed count, but one of them is not a bit.
+        let mut values = bitmap_values();
+        values[0] = 2;
+        values[2] = 0;
+        prove_bitmap(&values, 5, 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bitmap_popcount_below_threshold() {
+        prove_bitmap(&bitmap_values(), 5, 6);
+    }
+}
@@ -0,0 +1,245 @@
+use plonky2::field::extension::Extendable;
+use plonky2::field::types::{Field, PrimeField64};
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::generator::{GeneratedValues, SimpleGenerator};
+use plonky2::iop::target::{BoolTarget, Target};
+use plonky2::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::circuit_data::CommonCircuitData;
+use plonky2::util::serialization::{Buffer, IoResult, Read, Write};
+
+/// The number of bits of a value and of the interval bounds.
+pub const INTERVAL_VALUE_BITS: usize = 32;
+
+pub trait CircuitBuilderIntervalSet<F: RichField + Extendable<D>, const D: usize> {
+    /// Returns a bit which is one if and only if `a <= b`. Both inputs must be range checked to
+    /// `INTERVAL_VALUE_BITS` bits.
+    fn is_less_or_equal(&mut self, a: Target, b: Target) -> BoolTarget;
+
+    /// Returns a bit which is one if and only if `lo <= value <= hi`. All inputs must be range
+    /// checked to `INTERVAL_VALUE_BITS` bits.
+    fn is_in_interval(&mut self, value: Target, interval: (Target, Target)) -> BoolTarget;
+
+    /// Asserts that `value` lies in at least one of the closed intervals `[lo_i, hi_i]`.
+    ///
+    /// The value and the bounds are range checked to `INTERVAL_VALUE_BITS` bits, and the
+    /// membership bits of all intervals are ORed together.
+    fn assert_in_interval_set(&mut self, value: Target, intervals: &[(Target, Target)]);
+
+    /// Asserts that `value` lies in one of the closed intervals `[lo_i, hi_i]`, which are sorted
+    /// by their lower bounds.
+    ///
+    /// The witness generator looks up the only interval that can contain `value` by a binary
+    /// search, and the circuit checks membership in that interval only, selected by a random
+    /// access. This costs two comparisons instead of two comparisons per interval. The ordering
+    /// is not needed for soundness, but an unsorted set may make an honest proof fail.
+    fn assert_in_sorted_interval_set(&mut self, value: Target, intervals: &[(Target, Target)]);
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderIntervalSet<F, D>
+    for CircuitBuilder<F, D>
+{
+    fn is_less_or_equal(&mut self, a: Target, b: Target) -> BoolTarget {
+        // Since both inputs are less than 2^n, `b - a + 2^n` is a positive (n + 1)-bit integer
+        // whose top bit is set exactly when `a <= b`.
+        let offset = self.constant(F::from_canonical_u64(1 << INTERVAL_VALUE_BITS));
+        let difference = self.sub(b, a);
+        let shifted = self.add(difference, offset);
+        let bits = self.split_le(shifted, INTERVAL_VALUE_BITS + 1);
+        bits[INTERVAL_VALUE_BITS]
+    }
+
+    fn is_in_interval(&mut self, value: Target, interval: (Target, Target)) -> BoolTarget {
+        let (lo, hi) = interval;
+        let above_lo = self.is_less_or_equal(lo, value);
+        let below_hi = self.is_less_or_equal(value, hi);
+        self.and(above_lo, below_hi)
+    }
+
+    fn assert_in_interval_set(&mut self, value: Target, intervals: &[(Target, Target)]) {
+        assert!(!intervals.is_empty(), "The interval set must not be empty");
+
+        self.range_check(value, INTERVAL_VALUE_BITS);
+        for (lo, hi) in intervals.iter() {
+            self.range_check(*lo, INTERVAL_VALUE_BITS);
+            self.range_check(*hi, INTERVAL_VALUE_BITS);
+        }
+
+        let mut is_member = self._false();
+        for interval in intervals.iter() {
+            let in_interval = self.is_in_interval(value, *interval);
+            is_member = self.or(is_member, in_interval);
+        }
+        let one = self.one();
+        self.connect(is_member.target, one);
+    }
+
+    fn assert_in_sorted_interval_set(&mut self, value: Target, intervals: &[(Target, Target)]) {
+        assert!(!intervals.is_empty(), "The interval set must not be empty");
+
+        // Pad the bounds to a power of two for the random access by repeating the last interval,
+        // which leaves the set unchanged.
+        let padded_len = intervals.len().next_power_of_two();
+        let last = intervals[intervals.len() - 1];
+        let (lows, highs): (Vec<_>, Vec<_>) = intervals
+            .iter()
+            .copied()
+            .chain(core::iter::repeat(last))
+            .take(padded_len)
+            .unzip();
+
+        let index = self.add_virtual_target();
+        self.add_simple_generator(IntervalIndexGenerator {
+            value,
+            lows: lows[..intervals.len()].to_vec(),
+            index,
+        });
+
+        let lo = self.random_access(index, lows);
+        let hi = self.random_access(index, highs);
+
+        self.range_check(value, INTERVAL_VALUE_BITS);
+        self.range_check(lo, INTERVAL_VALUE_BITS);
+        self.range_check(hi, INTERVAL_VALUE_BITS);
+        let is_member = self.is_in_interval(value, (lo, hi));
+        let one = self.one();
+        self.connect(is_member.target, one);
+    }
+}
+
+/// Witnesses the index of the last interval whose lower bound is at most the value.
+#[derive(Debug, Clone)]
+pub struct IntervalIndexGenerator {
+    value: Target,
+    lows: Vec<Target>,
+    index: Target,
+}
+
+impl IntervalIndexGenerator {
+    pub fn id() -> String {
+        "IntervalIndexGenerator".to_string()
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
+    for IntervalIndexGenerator
+{
+    fn id(&self) -> String {
+        Self::id()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        let mut dependencies = vec![self.value];
+        dependencies.extend_from_slice(&self.lows);
+        dependencies
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _: &CommonCircuitData<F, D>) -> IoResult<()> {
+        dst.write_target(self.value)?;
+        dst.write_target_vec(&self.lows)?;
+        dst.write_target(self.index)
+    }
+
+    fn deserialize(src: &mut Buffer, _: &CommonCircuitData<F, D>) -> IoResult<Self>
+    where
+        Self: Sized,
+    {
+        let value = src.read_target()?;
+        let lows = src.read_target_vec()?;
+        let index = src.read_target()?;
+        Ok(Self { value, lows, index })
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let value = witness.get_target(self.value).to_canonical_u64();
+        let lows = witness
+            .get_targets(&self.lows)
+            .into_iter()
+            .map(|x| x.to_canonical_u64())
+            .collect::<Vec<_>>();
+
+        let count = lows.partition_point(|lo| *lo <= value);
+        let index = count.saturating_sub(1);
+        out_buffer.set_target(self.index, F::from_canonical_usize(index));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::iop::witness::PartialWitness;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+
+    use super::*;
+
+    type F = GoldilocksField;
+    type C = PoseidonGoldilocksConfig;
+    const D: usize = 2;
+
+    fn prove_interval_membership(value: u64, interval_values: &[(u64, u64)], sorted: bool) {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let value_target = builder.add_virtual_target();
+        let intervals = interval_values
+            .iter()
+            .map(|_| (builder.add_virtual_target(), builder.add_virtual_target()))
+            .collect::<Vec<_>>();
+        if sorted {
+            builder.assert_in_sorted_interval_set(value_target, &intervals);
+        } else {
+            builder.assert_in_interval_set(value_target, &intervals);
+        }
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+
+        pw.set_target(value_target, F::from_canonical_u64(value));
+        for ((lo, hi), (lo_value, hi_value)) in intervals.iter().zip(interval_values) {
+            pw.set_target(*lo, F::from_canonical_u64(*lo_value));
+            pw.set_target(*hi, F::from_canonical_u64(*hi_value));
+        }
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+
+    const INTERVALS: [(u64, u64); 3] = [(10, 20), (100, 200), (1000, 1000)];
+
+    #[test]
+    fn test_interval_set() {
+        for value in [10, 15, 20, 150, 1000] {
+            prove_interval_membership(value, &INTERVALS, false);
+        }
+        // The intervals do not need to be sorted.
+        prove_interval_membership(15, &[(100, 200), (10, 20)], false);
+        prove_interval_membership(u32::MAX as u64, &[(0, u32::MAX as u64)], false);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_interval_set_outside() {
+        prove_interval_membership(50, &INTERVALS, false);
+    }
+
+    #[test]
+    fn test_sorted_interval_set() {
+        for value in [10, 15, 20, 150, 1000] {
+            prove_interval_membership(value, &INTERVALS, true);
+        }
+        prove_interval_membership(5, &[(0, 10)], true);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sorted_interval_set_outside() {
+        prove_interval_membership(50, &INTERVALS, true);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sorted_interval_set_below() {
+        prove_interval_membership(5, &INTERVALS, true);
+    }
+}
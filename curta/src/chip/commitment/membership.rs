@@ -0,0 +1,104 @@
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::{BoolTarget, Target};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+pub trait CircuitBuilderDigestSet<F: RichField + Extendable<D>, const D: usize> {
+    /// Returns a bit which is one if and only if the byte digests `a` and `b` are equal.
+    fn digest_is_equal(&mut self, a: [Target; 32], b: [Target; 32]) -> BoolTarget;
+
+    /// Returns a bit which is one if and only if `digest` is equal to one of the digests of `set`.
+    ///
+    /// The equality bits of all entries are ORed together, so that the membership bit of an empty
+    /// set is zero.
+    fn digest_is_member(&mut self, digest: [Target; 32], set: &[[Target; 32]]) -> BoolTarget;
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderDigestSet<F, D>
+    for CircuitBuilder<F, D>
+{
+    fn digest_is_equal(&mut self, a: [Target; 32], b: [Target; 32]) -> BoolTarget {
+        let mut is_equal = self._true();
+        for (a_byte, b_byte) in a.iter().zip(b.iter()) {
+            let byte_is_equal = self.is_equal(*a_byte, *b_byte);
+            is_equal = self.and(is_equal, byte_is_equal);
+        }
+        is_equal
+    }
+
+    fn digest_is_member(&mut self, digest: [Target; 32], set: &[[Target; 32]]) -> BoolTarget {
+        let mut is_member = self._false();
+        for entry in set.iter() {
+            let is_entry = self.digest_is_equal(digest, *entry);
+            is_member = self.or(is_member, is_entry);
+        }
+        is_member
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::Field;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+
+    use super::*;
+
+    type F = GoldilocksField;
+    type C = PoseidonGoldilocksConfig;
+    const D: usize = 2;
+
+    fn digest(seed: u8) -> [u8; 32] {
+        core::array::from_fn(|i| seed.wrapping_mul(31).wrapping_add(i as u8))
+    }
+
+    fn prove_membership(query: [u8; 32], set_values: &[[u8; 32]], expected: bool) {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let query_target = builder.add_virtual_target_arr::<32>();
+        let set = set_values
+            .iter()
+            .map(|_| builder.add_virtual_target_arr::<32>())
+            .collect::<Vec<_>>();
+        let is_member = builder.digest_is_member(query_target, &set);
+        let expected_target = builder.constant_bool(expected);
+        builder.connect(is_member.target, expected_target.target);
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+
+        let to_field = |bytes: &[u8; 32]| bytes.map(F::from_canonical_u8);
+        pw.set_target_arr(&query_target, &to_field(&query));
+        for (entry, value) in set.iter().zip(set_values.iter()) {
+            pw.set_target_arr(entry, &to_field(value));
+        }
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+
+    #[test]
+    fn test_digest_is_member() {
+        let set = [digest(1), digest(2), digest(3)];
+
+        prove_membership(digest(2), &set, true);
+        prove_membership(digest(4), &set, false);
+
+        // A digest differing from an entry in its last byte only is not a member.
+        let mut almost = digest(3);
+        almost[31] ^= 1;
+        prove_membership(almost, &set, false);
+
+        // Nothing is a member of the empty set.
+        prove_membership(digest(1), &[], false);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_digest_is_member_wrong_claim() {
+        prove_membership(digest(4), &[digest(1), digest(2)], true);
+    }
+}
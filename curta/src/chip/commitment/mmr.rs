@@ -0,0 +1,231 @@
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::{HashOut, HashOutTarget, RichField};
+use plonky2::hash::poseidon::PoseidonHash;
+use plonky2::iop::target::Target;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::config::Hasher;
+
+/// Appends a leaf to a Merkle mountain range natively.
+///
+/// The peaks are indexed by height: the slot `h` holds the root of the perfect subtree of `2^h`
+/// leaves if bit `h` of `size` is set, and the zero digest otherwise. Appending merges the new leaf
+/// with the peaks of increasing height as long as they are present, with the older peak on the
+/// left.
+pub fn mmr_append<F: RichField>(
+    peaks: &[HashOut<F>],
+    size: u64,
+    leaf: HashOut<F>,
+) -> Vec<HashOut<F>> {
+    assert!(size + 1 < 1 << peaks.len(), "The mountain range is full");
+    let mut new_peaks = peaks.to_vec();
+    let mut carry = leaf;
+    for (height, peak) in new_peaks.iter_mut().enumerate() {
+        if (size >> height) & 1 == 0 {
+            *peak = carry;
+            break;
+        }
+        carry = PoseidonHash::two_to_one(*peak, carry);
+        *peak = HashOut::ZERO;
+    }
+    new_peaks
+}
+
+pub trait CircuitBuilderMmr<F: RichField + Extendable<D>, const D: usize> {
+    /// Asserts that appending `new_leaf` to the mountain range with peaks `old_peaks` and
+    /// `old_size` leaves gives the peaks `new_peaks` and `new_size` leaves, as in `mmr_append`.
+    ///
+    /// The number of peak slots bounds the size of the mountain range to less than
+    /// `2^old_peaks.len()` leaves. The old peaks are checked to be in canonical form, i.e., the
+    /// slots of the missing peaks hold the zero digest.
+    fn verify_mmr_append(
+        &mut self,
+        old_peaks: &[HashOutTarget],
+        old_size: Target,
+        new_leaf: HashOutTarget,
+        new_peaks: &[HashOutTarget],
+        new_size: Target,
+    );
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderMmr<F, D>
+    for CircuitBuilder<F, D>
+{
+    fn verify_mmr_append(
+        &mut self,
+        old_peaks: &[HashOutTarget],
+        old_size: Target,
+        new_leaf: HashOutTarget,
+        new_peaks: &[HashOutTarget],
+        new_size: Target,
+    ) {
+        assert_eq!(
+            old_peaks.len(),
+            new_peaks.len(),
+            "Mismatched number of peak slots"
+        );
+        let height = old_peaks.len();
+
+        let one = self.one();
+        let incremented_size = self.add(old_size, one);
+        self.connect(incremented_size, new_size);
+
+        let zero = self.zero();
+        let size_bits = self.split_le(old_size, height);
+        let mut is_carrying = self._true();
+        let mut carry = new_leaf;
+        for ((old_peak, new_peak), bit) in old_peaks.iter().zip(new_peaks).zip(size_bits) {
+            // A missing peak must be the zero digest.
+            let not_bit = self.not(bit);
+            for element in old_peak.elements {
+                let masked = self.mul(not_bit.target, element);
+                self.assert_zero(masked);
+            }
+
+            // While carrying, a present peak is merged into the carry and a missing peak is
+            // replaced by the carry. Otherwise, the peak is unchanged.
+            for ((old_element, new_element), carry_element) in old_peak
+                .elements
+                .iter()
+                .zip(new_peak.elements)
+                .zip(carry.elements)
+            {
+                let appended = self.select(bit, zero, carry_element);
+                let expected = self.select(is_carrying, appended, *old_element);
+                self.connect(expected, new_element);
+            }
+
+            let inputs = [old_peak.elements, carry.elements].concat();
+            carry = self.hash_n_to_hash_no_pad::<PoseidonHash>(inputs);
+            is_carrying = self.and(is_carrying, bit);
+        }
+
+        // The carry must have stopped at one of the slots.
+        self.assert_zero(is_carrying.target);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::{Field, Sample};
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+
+    use super::*;
+
+    type F = GoldilocksField;
+    type C = PoseidonGoldilocksConfig;
+    const D: usize = 2;
+
+    const HEIGHT: usize = 4;
+
+    /// The root of the perfect Merkle tree over `leaves`.
+    fn merkle_root(leaves: &[HashOut<F>]) -> HashOut<F> {
+        let mut layer = leaves.to_vec();
+        while layer.len() > 1 {
+            layer = layer
+                .chunks(2)
+                .map(|pair| PoseidonHash::two_to_one(pair[0], pair[1]))
+                .collect();
+        }
+        layer[0]
+    }
+
+    /// Proves a sequence of appends starting from the empty mountain range. The leaf `i` is
+    /// appended to the peaks `peaks[i]` and gives the peaks `peaks[i + 1]`.
+    fn prove_mmr_appends(leaves: &[HashOut<F>], peaks: &[Vec<HashOut<F>>]) {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let peak_targets = peaks
+            .iter()
+            .map(|_| {
+                (0..HEIGHT)
+                    .map(|_| builder.add_virtual_hash())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let leaf_targets = builder.add_virtual_hashes(leaves.len());
+        for (i, leaf) in leaf_targets.iter().enumerate() {
+            let old_size = builder.constant(F::from_canonical_usize(i));
+            let new_size = builder.constant(F::from_canonical_usize(i + 1));
+            builder.verify_mmr_append(
+                &peak_targets[i],
+                old_size,
+                *leaf,
+                &peak_targets[i + 1],
+                new_size,
+            );
+        }
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+
+        for (targets, values) in peak_targets.iter().zip(peaks) {
+            for (target, value) in targets.iter().zip(values) {
+                pw.set_hash_target(*target, *value);
+            }
+        }
+        for (target, value) in leaf_targets.iter().zip(leaves) {
+            pw.set_hash_target(*target, *value);
+        }
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+
+    /// The peaks after appending each of the leaves in turn, starting from the empty range.
+    fn append_all(leaves: &[HashOut<F>]) -> Vec<Vec<HashOut<F>>> {
+        let mut peaks = vec![vec![HashOut::ZERO; HEIGHT]];
+        for (i, leaf) in leaves.iter().enumerate() {
+            let next = mmr_append(&peaks[i], i as u64, *leaf);
+            peaks.push(next);
+        }
+        peaks
+    }
+
+    #[test]
+    fn test_mmr_append() {
+        let leaves = (0..7)
+            .map(|_| HashOut::from_partial(&F::rand_vec(4)))
+            .collect::<Vec<_>>();
+        let peaks = append_all(&leaves);
+
+        // After 7 = 0b111 appends, the peaks are the roots of the subtrees of 4, 2 and 1 leaves.
+        assert_eq!(peaks[7][2], merkle_root(&leaves[0..4]));
+        assert_eq!(peaks[7][1], merkle_root(&leaves[4..6]));
+        assert_eq!(peaks[7][0], leaves[6]);
+        assert_eq!(peaks[7][3], HashOut::ZERO);
+
+        // After 4 appends, the only peak is the root of all leaves.
+        assert_eq!(peaks[4][2], merkle_root(&leaves[0..4]));
+        assert_eq!(peaks[4][0], HashOut::ZERO);
+        assert_eq!(peaks[4][1], HashOut::ZERO);
+
+        prove_mmr_appends(&leaves, &peaks);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_mmr_append_wrong_peak() {
+        let leaves = (0..3)
+            .map(|_| HashOut::from_partial(&F::rand_vec(4)))
+            .collect::<Vec<_>>();
+        let mut peaks = append_all(&leaves);
+        // Skip the merge of the first two leaves.
+        peaks[2] = vec![leaves[1], leaves[0], HashOut::ZERO, HashOut::ZERO];
+        prove_mmr_appends(&leaves, &peaks);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_mmr_append_overflow() {
+        let leaves = (0..16)
+            .map(|_| HashOut::from_partial(&F::rand_vec(4)))
+            .collect::<Vec<_>>();
+        let mut peaks = append_all(&leaves[..15]);
+        peaks.push(vec![HashOut::ZERO; HEIGHT]);
+        prove_mmr_appends(&leaves, &peaks);
+    }
+}
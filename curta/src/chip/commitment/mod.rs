@@ -0,0 +1,14 @@
+//! Commitment gadgets for privacy protocols, hashed with Poseidon over the native field.
+
+pub mod balance;
+pub mod bitmap;
+pub mod interval;
+pub mod membership;
+pub mod mmr;
+pub mod note;
+pub mod nullifier;
+pub mod packed;
+pub mod public_inputs;
+pub mod rerandomize;
+pub mod sparse;
+pub mod state_diff;
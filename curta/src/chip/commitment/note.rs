@@ -0,0 +1,233 @@
+use plonky2::field::extension::Extendable;
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::RichField;
+use plonky2::hash::poseidon::PoseidonHash;
+use plonky2::iop::target::Target;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::config::Hasher;
+
+/// A field of a note.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoteField {
+    Value,
+    Owner,
+    Randomness,
+    Extra,
+}
+
+/// The order in which the note fields are absorbed by the hash, optionally preceded by a domain
+/// separator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteLayout {
+    pub domain_separator: Option<u64>,
+    pub fields: Vec<NoteField>,
+}
+
+/// A note with a value, an owner, a blinding randomness and any extra protocol-specific data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Note<T> {
+    pub value: T,
+    pub owner: Vec<T>,
+    pub randomness: Vec<T>,
+    pub extra: Vec<T>,
+}
+
+pub type NoteTarget = Note<Target>;
+
+impl NoteLayout {
+    pub fn new(domain_separator: Option<u64>, fields: Vec<NoteField>) -> Self {
+        assert!(
+            fields.contains(&NoteField::Randomness),
+            "A note commitment must include the randomness"
+        );
+        Self {
+            domain_separator,
+            fields,
+        }
+    }
+}
+
+impl Default for NoteLayout {
+    fn default() -> Self {
+        Self::new(
+            None,
+            vec![
+                NoteField::Value,
+                NoteField::Owner,
+                NoteField::Randomness,
+                NoteField::Extra,
+            ],
+        )
+    }
+}
+
+impl<T: Copy> Note<T> {
+    /// Packs the note fields in the order given by the layout.
+    pub fn pack(&self, layout: &NoteLayout) -> Vec<T> {
+        layout
+            .fields
+            .iter()
+            .flat_map(|field| match field {
+                NoteField::Value => vec![self.value],
+                NoteField::Owner => self.owner.clone(),
+                NoteField::Randomness => self.randomness.clone(),
+                NoteField::Extra => self.extra.clone(),
+            })
+            .collect()
+    }
+}
+
+impl<F: RichField> Note<F> {
+    /// Computes the note commitment natively.
+    pub fn commitment(&self, layout: &NoteLayout) -> [F; 4] {
+        let inputs = layout
+            .domain_separator
+            .map(F::from_canonical_u64)
+            .into_iter()
+            .chain(self.pack(layout))
+            .collect::<Vec<_>>();
+        PoseidonHash::hash_no_pad(&inputs).elements
+    }
+}
+
+pub trait CircuitBuilderNoteCommitment<F: RichField + Extendable<D>, const D: usize> {
+    fn add_virtual_note_target(
+        &mut self,
+        owner_len: usize,
+        randomness_len: usize,
+        extra_len: usize,
+    ) -> NoteTarget;
+
+    fn note_commitment(&mut self, note: &NoteTarget, layout: &NoteLayout) -> [Target; 4];
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderNoteCommitment<F, D>
+    for CircuitBuilder<F, D>
+{
+    fn add_virtual_note_target(
+        &mut self,
+        owner_len: usize,
+        randomness_len: usize,
+        extra_len: usize,
+    ) -> NoteTarget {
+        Note {
+            value: self.add_virtual_target(),
+            owner: self.add_virtual_targets(owner_len),
+            randomness: self.add_virtual_targets(randomness_len),
+            extra: self.add_virtual_targets(extra_len),
+        }
+    }
+
+    fn note_commitment(&mut self, note: &NoteTarget, layout: &NoteLayout) -> [Target; 4] {
+        let inputs = layout
+            .domain_separator
+            .map(|separator| self.constant(F::from_canonical_u64(separator)))
+            .into_iter()
+            .chain(note.pack(layout))
+            .collect::<Vec<_>>();
+        self.hash_n_to_hash_no_pad::<PoseidonHash>(inputs).elements
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::Sample;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+
+    use super::*;
+
+    fn random_note<F: RichField>() -> Note<F> {
+        Note {
+            value: F::rand(),
+            owner: F::rand_vec(4),
+            randomness: F::rand_vec(2),
+            extra: F::rand_vec(1),
+        }
+    }
+
+    fn set_note_target<F: RichField>(
+        pw: &mut PartialWitness<F>,
+        target: &NoteTarget,
+        note: &Note<F>,
+    ) {
+        pw.set_target(target.value, note.value);
+        pw.set_target_arr(&target.owner, &note.owner);
+        pw.set_target_arr(&target.randomness, &note.randomness);
+        pw.set_target_arr(&target.extra, &note.extra);
+    }
+
+    #[test]
+    fn test_note_commitment_layout() {
+        type F = GoldilocksField;
+
+        let note = random_note::<F>();
+
+        // The packing follows the layout order.
+        let layout = NoteLayout::new(
+            Some(7),
+            vec![NoteField::Owner, NoteField::Value, NoteField::Randomness],
+        );
+        let expected_packing = note
+            .owner
+            .iter()
+            .copied()
+            .chain([note.value])
+            .chain(note.randomness.iter().copied())
+            .collect::<Vec<_>>();
+        assert_eq!(note.pack(&layout), expected_packing);
+
+        let expected_inputs = [F::from_canonical_u64(7)]
+            .into_iter()
+            .chain(expected_packing)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            note.commitment(&layout),
+            PoseidonHash::hash_no_pad(&expected_inputs).elements
+        );
+        assert_ne!(
+            note.commitment(&layout),
+            note.commitment(&NoteLayout::default())
+        );
+    }
+
+    #[test]
+    fn test_note_commitment() {
+        type F = GoldilocksField;
+        type C = PoseidonGoldilocksConfig;
+        const D: usize = 2;
+
+        let layouts = [
+            NoteLayout::default(),
+            NoteLayout::new(
+                Some(1),
+                vec![NoteField::Owner, NoteField::Value, NoteField::Randomness],
+            ),
+        ];
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let note_target = builder.add_virtual_note_target(4, 2, 1);
+        for layout in layouts.iter() {
+            let commitment = builder.note_commitment(&note_target, layout);
+            builder.register_public_inputs(&commitment);
+        }
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+
+        let note = random_note::<F>();
+        set_note_target(&mut pw, &note_target, &note);
+
+        let proof = data.prove(pw).unwrap();
+        let expected = layouts
+            .iter()
+            .flat_map(|layout| note.commitment(layout))
+            .collect::<Vec<_>>();
+        assert_eq!(proof.public_inputs, expected);
+        data.verify(proof).unwrap();
+    }
+}
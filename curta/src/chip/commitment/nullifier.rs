@@ -0,0 +1,151 @@
+use plonky2::field::extension::Extendable;
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::RichField;
+use plonky2::hash::poseidon::PoseidonHash;
+use plonky2::iop::target::Target;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::config::Hasher;
+
+/// The default domain separator prepended to the nullifier hash inputs, the ASCII encoding of
+/// "nullifr".
+pub const NULLIFIER_DOMAIN_SEPARATOR: u64 = 0x006e_756c_6c69_6672;
+
+/// Computes the nullifier of a note commitment natively.
+pub fn nullifier<F: RichField>(
+    note_commitment: &[F; 4],
+    spending_key: &[F],
+    domain_separator: u64,
+) -> [F; 4] {
+    let inputs = [F::from_canonical_u64(domain_separator)]
+        .into_iter()
+        .chain(note_commitment.iter().copied())
+        .chain(spending_key.iter().copied())
+        .collect::<Vec<_>>();
+    PoseidonHash::hash_no_pad(&inputs).elements
+}
+
+pub trait CircuitBuilderNullifier<F: RichField + Extendable<D>, const D: usize> {
+    /// Derives the nullifier of a note from its commitment and the spending key, using the
+    /// default domain separator, and registers it as a public input.
+    fn derive_nullifier(
+        &mut self,
+        note_commitment: &[Target; 4],
+        spending_key: &[Target],
+    ) -> [Target; 4];
+
+    /// Derives the nullifier of a note with a protocol-specific domain separator and registers
+    /// it as a public input.
+    fn derive_nullifier_with_domain(
+        &mut self,
+        note_commitment: &[Target; 4],
+        spending_key: &[Target],
+        domain_separator: u64,
+    ) -> [Target; 4];
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderNullifier<F, D>
+    for CircuitBuilder<F, D>
+{
+    fn derive_nullifier(
+        &mut self,
+        note_commitment: &[Target; 4],
+        spending_key: &[Target],
+    ) -> [Target; 4] {
+        self.derive_nullifier_with_domain(note_commitment, spending_key, NULLIFIER_DOMAIN_SEPARATOR)
+    }
+
+    fn derive_nullifier_with_domain(
+        &mut self,
+        note_commitment: &[Target; 4],
+        spending_key: &[Target],
+        domain_separator: u64,
+    ) -> [Target; 4] {
+        let inputs = [self.constant(F::from_canonical_u64(domain_separator))]
+            .into_iter()
+            .chain(note_commitment.iter().copied())
+            .chain(spending_key.iter().copied())
+            .collect::<Vec<_>>();
+        let nullifier = self.hash_n_to_hash_no_pad::<PoseidonHash>(inputs).elements;
+        self.register_public_inputs(&nullifier);
+        nullifier
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::Sample;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+
+    use super::*;
+    use crate::chip::commitment::note::{CircuitBuilderNoteCommitment, Note, NoteLayout};
+
+    #[test]
+    fn test_nullifier() {
+        type F = GoldilocksField;
+        type C = PoseidonGoldilocksConfig;
+        const D: usize = 2;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let layout = NoteLayout::default();
+        let note_targets = (0..2)
+            .map(|_| builder.add_virtual_note_target(4, 2, 0))
+            .collect::<Vec<_>>();
+        let spending_key = builder.add_virtual_targets(4);
+        for note_target in note_targets.iter() {
+            let commitment = builder.note_commitment(note_target, &layout);
+            builder.derive_nullifier(&commitment, &spending_key);
+        }
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+
+        let notes = (0..2)
+            .map(|_| Note {
+                value: F::rand(),
+                owner: F::rand_vec(4),
+                randomness: F::rand_vec(2),
+                extra: vec![],
+            })
+            .collect::<Vec<_>>();
+        let spending_key_value = F::rand_vec(4);
+        for (note_target, note) in note_targets.iter().zip(notes.iter()) {
+            pw.set_target(note_target.value, note.value);
+            pw.set_target_arr(&note_target.owner, &note.owner);
+            pw.set_target_arr(&note_target.randomness, &note.randomness);
+        }
+        pw.set_target_arr(&spending_key, &spending_key_value);
+
+        let proof = data.prove(pw).unwrap();
+
+        let expected = notes
+            .iter()
+            .map(|note| {
+                nullifier(
+                    &note.commitment(&layout),
+                    &spending_key_value,
+                    NULLIFIER_DOMAIN_SEPARATOR,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_ne!(expected[0], expected[1]);
+        assert_eq!(proof.public_inputs, expected.concat());
+        data.verify(proof).unwrap();
+    }
+
+    #[test]
+    fn test_nullifier_domain_separation() {
+        type F = GoldilocksField;
+
+        let commitment = F::rand_array::<4>();
+        let spending_key = F::rand_vec(4);
+        assert_ne!(
+            nullifier(&commitment, &spending_key, NULLIFIER_DOMAIN_SEPARATOR),
+            nullifier(&commitment, &spending_key, 1)
+        );
+    }
+}
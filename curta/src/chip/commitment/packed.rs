@@ -0,0 +1,243 @@
+//! A commitment to a byte string which can be computed both in the AIR and in a plonky2 circuit.
+//!
+//! The bytes are packed into field elements of four little endian bytes each, the last one padded
+//! with zeros, and the commitment is the hash of the number of bytes followed by the packed
+//! elements. A STARK can expose the bytes and their commitment as public inputs, and a plonky2
+//! circuit verifying the STARK proof can then check the commitment with the same hash.
+
+use plonky2::field::extension::Extendable;
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::hash::hash_types::{RichField, NUM_HASH_OUT_ELTS};
+use plonky2::hash::poseidon::PoseidonHash;
+use plonky2::iop::target::Target;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::config::{AlgebraicHasher, Hasher};
+
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::builder::AirBuilder;
+use crate::chip::hash::poseidon::{GoldilocksPoseidon, PoseidonParameters};
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::uint::bytes::register::ByteRegister;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+
+/// The number of bytes packed into a field element.
+pub const BYTES_PER_ELEMENT: usize = 4;
+
+/// A hash function which has both an AIR and a plonky2 implementation computing the same values.
+pub trait CommitmentHasher<F: RichField>: PoseidonParameters {
+    type Hasher: AlgebraicHasher<F>;
+}
+
+impl CommitmentHasher<GoldilocksField> for GoldilocksPoseidon {
+    type Hasher = PoseidonHash;
+}
+
+/// Packs `bytes` into field elements of four little endian bytes, preceded by the number of bytes.
+pub fn pack_bytes<F: Field>(bytes: &[u8]) -> Vec<F> {
+    let mut elements = vec![F::from_canonical_usize(bytes.len())];
+    elements.extend(
+        bytes
+            .chunks(BYTES_PER_ELEMENT)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .rev()
+                    .fold(0u32, |acc, byte| (acc << 8) + *byte as u32)
+            })
+            .map(F::from_canonical_u32),
+    );
+    elements
+}
+
+/// Computes the packed commitment to `bytes` natively.
+pub fn packed_commitment<F: RichField, H: CommitmentHasher<F>>(
+    bytes: &[u8],
+) -> [F; NUM_HASH_OUT_ELTS] {
+    H::Hasher::hash_no_pad(&pack_bytes(bytes)).elements
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Computes the packed commitment to the bytes `input` with the hash `H`.
+    ///
+    /// The registers of `input` are not range checked, which is left to the caller.
+    pub fn packed_commitment<H: PoseidonParameters>(
+        &mut self,
+        input: &ArrayRegister<ByteRegister>,
+    ) -> ArrayRegister<ElementRegister> {
+        let num_chunks = input.len().div_ceil(BYTES_PER_ELEMENT);
+        let packed = self.alloc_array::<ElementRegister>(1 + num_chunks);
+
+        let length = L::Field::from_canonical_usize(input.len());
+        self.set_to_expression(&packed.get(0), ArithmeticExpression::from_constant(length));
+        for j in 0..num_chunks {
+            let end = (BYTES_PER_ELEMENT * (j + 1)).min(input.len());
+            let mut value = ArithmeticExpression::zero();
+            for (k, i) in (BYTES_PER_ELEMENT * j..end).enumerate() {
+                value = value + input.get(i).expr() * L::Field::from_canonical_u32(1 << (8 * k));
+            }
+            self.set_to_expression(&packed.get(1 + j), value);
+        }
+
+        self.hash_n_to_m::<H>(&packed, NUM_HASH_OUT_ELTS).output
+    }
+}
+
+pub trait CircuitBuilderPackedCommitment<F: RichField + Extendable<D>, const D: usize> {
+    /// Computes the packed commitment to the bytes `input` with the hash `H`.
+    ///
+    /// The targets of `input` are not range checked, which is left to the caller.
+    fn packed_commitment<H: CommitmentHasher<F>>(
+        &mut self,
+        input: &[Target],
+    ) -> [Target; NUM_HASH_OUT_ELTS];
+
+    /// Asserts that `commitment` is the packed commitment to the bytes `input`.
+    fn verify_packed_commitment<H: CommitmentHasher<F>>(
+        &mut self,
+        input: &[Target],
+        commitment: &[Target],
+    );
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderPackedCommitment<F, D>
+    for CircuitBuilder<F, D>
+{
+    fn packed_commitment<H: CommitmentHasher<F>>(
+        &mut self,
+        input: &[Target],
+    ) -> [Target; NUM_HASH_OUT_ELTS] {
+        let mut packed = vec![self.constant(F::from_canonical_usize(input.len()))];
+        for chunk in input.chunks(BYTES_PER_ELEMENT) {
+            let mut value = self.zero();
+            for (k, byte) in chunk.iter().enumerate() {
+                value = self.mul_const_add(F::from_canonical_u32(1 << (8 * k)), *byte, value);
+            }
+            packed.push(value);
+        }
+        self.hash_n_to_hash_no_pad::<H::Hasher>(packed).elements
+    }
+
+    fn verify_packed_commitment<H: CommitmentHasher<F>>(
+        &mut self,
+        input: &[Target],
+        commitment: &[Target],
+    ) {
+        assert_eq!(
+            commitment.len(),
+            NUM_HASH_OUT_ELTS,
+            "Invalid commitment length"
+        );
+        let expected = self.packed_commitment::<H>(input);
+        for (a, b) in expected.iter().zip(commitment) {
+            self.connect(*a, *b);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+    use rand::{thread_rng, Rng};
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::plonky2::stark::gadget::StarkGadget;
+    use crate::plonky2::stark::generator::simple::SimpleStarkWitnessGenerator;
+
+    const NUM_BYTES: usize = 18;
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub struct PackedCommitmentTest;
+
+    impl AirParameters for PackedCommitmentTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = EmptyInstruction<GoldilocksField>;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 0;
+        const NUM_FREE_COLUMNS: usize = 488;
+
+        fn num_rows_bits() -> usize {
+            10
+        }
+    }
+
+    #[test]
+    fn test_pack_bytes() {
+        type F = GoldilocksField;
+
+        let packed = pack_bytes::<F>(&[1, 2, 3, 4, 5]);
+        let expected = [5, 0x0403_0201, 5].map(F::from_canonical_u32);
+        assert_eq!(packed, expected);
+    }
+
+    #[test]
+    fn test_packed_commitment() {
+        type F = GoldilocksField;
+        type L = PackedCommitmentTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type C = PoseidonGoldilocksConfig;
+        type H = GoldilocksPoseidon;
+        const D: usize = 2;
+
+        // The STARK exposes the bytes and their commitment as public inputs.
+        let mut builder = AirBuilder::<L>::new();
+        let input = builder.alloc_array_public::<ByteRegister>(NUM_BYTES);
+        let commitment = builder.alloc_array_public::<ElementRegister>(NUM_HASH_OUT_ELTS);
+        let output = builder.packed_commitment::<H>(&input);
+        for (a, b) in output.iter().zip(commitment.iter()) {
+            builder.assert_equal(&a, &b);
+        }
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let mut rng = thread_rng();
+        let bytes = (0..NUM_BYTES).map(|_| rng.gen::<u8>()).collect::<Vec<_>>();
+        let expected = packed_commitment::<F, H>(&bytes);
+
+        let writer = generator.new_writer();
+        writer.write_array(&input, bytes.iter().map(|b| F::from_canonical_u8(*b)), 0);
+        writer.write_array(&commitment, expected, 0);
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            writer.write_row_instructions(&generator.air_data, i);
+        });
+        assert_eq!(writer.read_vec(&output, 0), expected.to_vec());
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+        let public_inputs = writer.public.read().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public_inputs);
+
+        // Verify the STARK proof in a plonky2 circuit, and check the commitment from the bytes.
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let virtual_proof = builder.add_virtual_stark_proof(&stark, &config);
+        let public_input_targets = builder.add_virtual_targets(public_inputs.len());
+        builder.verify_stark_proof(&config, &stark, &virtual_proof, &public_input_targets);
+        let (input_targets, commitment_targets) = public_input_targets.split_at(NUM_BYTES);
+        builder.verify_packed_commitment::<H>(input_targets, commitment_targets);
+
+        let mut pw = PartialWitness::new();
+        pw.set_target_arr(&public_input_targets, &public_inputs);
+        let stark_generator = SimpleStarkWitnessGenerator::new(
+            config,
+            stark,
+            virtual_proof,
+            public_input_targets,
+            generator,
+        );
+        builder.add_simple_generator(stark_generator);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+}
@@ -0,0 +1,127 @@
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::{HashOut, RichField, NUM_HASH_OUT_ELTS};
+use plonky2::hash::poseidon::PoseidonHash;
+use plonky2::iop::target::Target;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::config::Hasher;
+
+/// Computes the commitment to a list of public inputs natively, as in `commit_public_inputs`.
+pub fn public_inputs_commitment<F: RichField>(pis: &[F]) -> [F; NUM_HASH_OUT_ELTS] {
+    PoseidonHash::hash_no_pad(pis).elements
+}
+
+pub trait CircuitBuilderPublicInputs<F: RichField + Extendable<D>, const D: usize> {
+    /// Commits to the public inputs `pis` with the Poseidon sponge.
+    ///
+    /// The inputs are not padded, so the commitment is binding among lists of the same length.
+    /// A circuit can register the commitment as its only public inputs, and an aggregator which
+    /// knows the inputs can then bind to them with `verify_public_inputs_commitment`.
+    fn commit_public_inputs(&mut self, pis: &[Target]) -> [Target; NUM_HASH_OUT_ELTS];
+
+    /// Asserts that `commitment` is the commitment to the public inputs `pis`.
+    fn verify_public_inputs_commitment(
+        &mut self,
+        pis: &[Target],
+        commitment: &[Target; NUM_HASH_OUT_ELTS],
+    );
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderPublicInputs<F, D>
+    for CircuitBuilder<F, D>
+{
+    fn commit_public_inputs(&mut self, pis: &[Target]) -> [Target; NUM_HASH_OUT_ELTS] {
+        self.hash_n_to_hash_no_pad::<PoseidonHash>(pis.to_vec())
+            .elements
+    }
+
+    fn verify_public_inputs_commitment(
+        &mut self,
+        pis: &[Target],
+        commitment: &[Target; NUM_HASH_OUT_ELTS],
+    ) {
+        let expected = self.commit_public_inputs(pis);
+        for (a, b) in expected.iter().zip(commitment) {
+            self.connect(*a, *b);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::{Field, Sample};
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+
+    use super::*;
+
+    type F = GoldilocksField;
+    type C = PoseidonGoldilocksConfig;
+    const D: usize = 2;
+
+    const NUM_PUBLIC_INPUTS: usize = 10;
+
+    /// Proves an inner circuit exposing the commitment to its inputs, and an aggregator circuit
+    /// which verifies the inner proof and binds its commitment to `aggregator_pis`.
+    fn prove_aggregation(pis: &[F], aggregator_pis: &[F]) {
+        let config = CircuitConfig::standard_recursion_config();
+
+        // The inner circuit commits to its inputs and registers only the commitment.
+        let mut inner_builder = CircuitBuilder::<F, D>::new(config.clone());
+        let inner_pis = inner_builder.add_virtual_targets(NUM_PUBLIC_INPUTS);
+        let commitment = inner_builder.commit_public_inputs(&inner_pis);
+        inner_builder.register_public_inputs(&commitment);
+        let inner_data = inner_builder.build::<C>();
+
+        let mut pw = PartialWitness::new();
+        pw.set_target_arr(&inner_pis, pis);
+        let inner_proof = inner_data.prove(pw).unwrap();
+        assert_eq!(inner_proof.public_inputs, public_inputs_commitment(pis));
+        inner_data.verify(inner_proof.clone()).unwrap();
+
+        // The aggregator verifies the inner proof and re-derives the commitment.
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let proof_target = builder.add_virtual_proof_with_pis(&inner_data.common);
+        let verifier_data = builder.constant_verifier_data(&inner_data.verifier_only);
+        builder.verify_proof::<C>(&proof_target, &verifier_data, &inner_data.common);
+
+        let outer_pis = builder.add_virtual_targets(NUM_PUBLIC_INPUTS);
+        builder.register_public_inputs(&outer_pis);
+        let inner_commitment: [Target; NUM_HASH_OUT_ELTS] =
+            proof_target.public_inputs.clone().try_into().unwrap();
+        builder.verify_public_inputs_commitment(&outer_pis, &inner_commitment);
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+        pw.set_proof_with_pis_target(&proof_target, &inner_proof);
+        pw.set_target_arr(&outer_pis, aggregator_pis);
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+
+    #[test]
+    fn test_public_inputs_commitment() {
+        let pis = F::rand_vec(NUM_PUBLIC_INPUTS);
+
+        // The commitment is the Poseidon hash of the inputs.
+        let mut other_pis = pis.clone();
+        other_pis[0] += F::ONE;
+        assert_ne!(
+            public_inputs_commitment(&pis),
+            public_inputs_commitment(&other_pis)
+        );
+
+        prove_aggregation(&pis, &pis);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_public_inputs_commitment_wrong_inputs() {
+        let pis = F::rand_vec(NUM_PUBLIC_INPUTS);
+        let mut other_pis = pis.clone();
+        other_pis[NUM_PUBLIC_INPUTS - 1] += F::ONE;
+        prove_aggregation(&pis, &other_pis);
+    }
+}
@@ -0,0 +1,199 @@
+use num::BigUint;
+use serde::{Deserialize, Serialize};
+
+use crate::chip::builder::AirBuilder;
+use crate::chip::ec::edwards::add::EdAddGadget;
+use crate::chip::ec::edwards::scalar_mul::gadget::EdScalarMulGadget;
+use crate::chip::ec::edwards::EdwardsParameters;
+use crate::chip::ec::gadget::{EllipticCurveGadget, EllipticCurveWriter};
+use crate::chip::ec::point::{AffinePoint, AffinePointRegister};
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::Register;
+use crate::chip::trace::writer::TraceWriter;
+use crate::chip::utils::biguint_to_bits_le;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+
+/// The number of rows used to verify a single rerandomization.
+pub const RERANDOMIZE_CYCLE_LENGTH: usize = 256;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RerandomizeGadget<F, E: EdwardsParameters> {
+    pub old_commitment: AffinePointRegister<E>,
+    pub new_commitment: AffinePointRegister<E>,
+    /// The bits of the blinding factor update, one per row, least significant first.
+    pub delta_blinding: BitRegister,
+    /// The blinding generator `H`, written in the first row of each cycle.
+    pub h: AffinePointRegister<E>,
+    result: AffinePointRegister<E>,
+    scalar_mul: EdScalarMulGadget<F, E>,
+    sum: EdAddGadget<E>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Verifies that `new_commitment = old_commitment + delta_blinding * h`.
+    ///
+    /// Every cycle of `RERANDOMIZE_CYCLE_LENGTH` rows verifies one rerandomization, with the
+    /// scalar `delta_blinding` given in bits, one per row. The equation is checked on the last row
+    /// of each cycle.
+    pub fn verify_rerandomize<E: EdwardsParameters>(
+        &mut self,
+        old_commitment: &AffinePointRegister<E>,
+        new_commitment: &AffinePointRegister<E>,
+        delta_blinding: &BitRegister,
+        h: &AffinePointRegister<E>,
+    ) -> RerandomizeGadget<L::Field, E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        let result = self.alloc_unchecked_ec_point();
+        let scalar_mul = self.ed_scalar_mul(delta_blinding, &result, h);
+        let sum = self.ed_add(&scalar_mul.result(), old_commitment);
+
+        // On the last row of the cycle, new_commitment = old_commitment + delta_blinding * h.
+        let end_bit = scalar_mul.cycle.end_bit;
+        self.assert_expression_zero(
+            end_bit.expr() * (sum.result.x.expr() - new_commitment.x.expr()),
+        );
+        self.assert_expression_zero(
+            end_bit.expr() * (sum.result.y.expr() - new_commitment.y.expr()),
+        );
+
+        RerandomizeGadget {
+            old_commitment: *old_commitment,
+            new_commitment: *new_commitment,
+            delta_blinding: *delta_blinding,
+            h: *h,
+            result,
+            scalar_mul,
+            sum,
+        }
+    }
+}
+
+impl<F: PrimeField64> TraceWriter<F> {
+    /// Writes the inputs of the rerandomization verified in the cycle `cycle_index`.
+    ///
+    /// The row instructions of the cycle must be written afterwards, in order.
+    pub fn write_rerandomize<E: EdwardsParameters>(
+        &self,
+        gadget: &RerandomizeGadget<F, E>,
+        cycle_index: usize,
+        old_commitment: &AffinePoint<E>,
+        new_commitment: &AffinePoint<E>,
+        delta_blinding: &BigUint,
+        h: &AffinePoint<E>,
+    ) {
+        let starting_row = RERANDOMIZE_CYCLE_LENGTH * cycle_index;
+        self.write_ec_point(&gadget.result, &E::neutral(), starting_row);
+        self.write_ec_point(&gadget.h, h, starting_row);
+
+        let delta_bits = biguint_to_bits_le(delta_blinding, RERANDOMIZE_CYCLE_LENGTH);
+        for (i, bit) in delta_bits.iter().enumerate() {
+            let row = starting_row + i;
+            self.write(
+                &gadget.delta_blinding,
+                &F::from_canonical_u8(*bit as u8),
+                row,
+            );
+            self.write_ec_point(&gadget.old_commitment, old_commitment, row);
+            self.write_ec_point(&gadget.new_commitment, new_commitment, row);
+        }
+    }
+}
+
+/// Computes `old_commitment + delta_blinding * h`.
+pub fn rerandomize<E: EdwardsParameters>(
+    old_commitment: &AffinePoint<E>,
+    delta_blinding: &BigUint,
+    h: &AffinePoint<E>,
+) -> AffinePoint<E> {
+    old_commitment + &(h * delta_blinding)
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::ec::edwards::ed25519::{Ed25519, Ed25519BaseField};
+    use crate::chip::field::instruction::FpInstruction;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    pub struct RerandomizeTest;
+
+    impl AirParameters for RerandomizeTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 2304;
+        const NUM_FREE_COLUMNS: usize = 72;
+        const EXTENDED_COLUMNS: usize = 3465;
+        type Instruction = FpInstruction<Ed25519BaseField>;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    #[test]
+    fn test_rerandomize() {
+        type L = RerandomizeTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type E = Ed25519;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let old_commitment = builder.alloc_unchecked_ec_point();
+        let new_commitment = builder.alloc_unchecked_ec_point();
+        let h = builder.alloc_unchecked_ec_point();
+        let delta_blinding = builder.alloc::<BitRegister>();
+        let gadget =
+            builder.verify_rerandomize::<E>(&old_commitment, &new_commitment, &delta_blinding, &h);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let g = E::generator();
+        let mut rng = thread_rng();
+        let h_value = &g * &rng.gen_biguint(250);
+        let order = E::prime_group_order();
+
+        let writer = generator.new_writer();
+        let num_cycles = L::num_rows() / RERANDOMIZE_CYCLE_LENGTH;
+        for k in 0..num_cycles {
+            // A Pedersen commitment to `value` with blinding factor `blinding`.
+            let value = rng.gen_biguint_below(&order);
+            let blinding = rng.gen_biguint_below(&order);
+            let delta = rng.gen_biguint_below(&order);
+            let commitment = &(&g * &value) + &(&h_value * &blinding);
+
+            // The rerandomized commitment opens to the same value with the updated blinding.
+            let rerandomized = rerandomize(&commitment, &delta, &h_value);
+            let new_blinding = (&blinding + &delta) % &order;
+            assert_eq!(rerandomized, &(&g * &value) + &(&h_value * &new_blinding));
+
+            writer.write_rerandomize(&gadget, k, &commitment, &rerandomized, &delta, &h_value);
+        }
+        (0..num_cycles).into_par_iter().for_each(|k| {
+            for i in 0..RERANDOMIZE_CYCLE_LENGTH {
+                let row = RERANDOMIZE_CYCLE_LENGTH * k + i;
+                writer.write_row_instructions(&generator.air_data, row);
+            }
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+}
@@ -0,0 +1,169 @@
+use plonky2::field::extension::Extendable;
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::{HashOut, HashOutTarget, RichField};
+use plonky2::hash::poseidon::PoseidonHash;
+use plonky2::iop::target::Target;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::config::Hasher;
+
+/// The number of bits of an index of a sparse vector.
+pub const SPARSE_INDEX_BITS: usize = 32;
+
+/// Computes the commitment to a sparse vector natively.
+///
+/// Each nonzero entry is committed to as the leaf `H(index, value)`, and the commitment is the
+/// root of the Merkle tree over the leaves, padded with zero digests to a power of two.
+pub fn sparse_commitment<F: RichField>(indices: &[u64], values: &[F]) -> HashOut<F> {
+    assert_eq!(
+        indices.len(),
+        values.len(),
+        "Mismatched number of indices and values"
+    );
+    assert!(
+        !indices.is_empty(),
+        "Cannot commit to an empty sparse vector"
+    );
+
+    let mut layer = indices
+        .iter()
+        .zip(values.iter())
+        .map(|(index, value)| PoseidonHash::hash_no_pad(&[F::from_canonical_u64(*index), *value]))
+        .collect::<Vec<_>>();
+    layer.resize(layer.len().next_power_of_two(), HashOut::ZERO);
+    while layer.len() > 1 {
+        layer = layer
+            .chunks(2)
+            .map(|pair| PoseidonHash::two_to_one(pair[0], pair[1]))
+            .collect();
+    }
+    layer[0]
+}
+
+pub trait CircuitBuilderSparseCommitment<F: RichField + Extendable<D>, const D: usize> {
+    /// Computes the commitment to the sparse vector with the given nonzero entries, as in
+    /// `sparse_commitment`.
+    fn sparse_commitment(&mut self, indices: &[Target], values: &[Target]) -> HashOutTarget;
+
+    /// Asserts that `indices` are strictly increasing `SPARSE_INDEX_BITS`-bit integers, so that
+    /// the sparse vector is in canonical form, and that `commitment` is its commitment.
+    fn verify_sparse_commitment(
+        &mut self,
+        indices: &[Target],
+        values: &[Target],
+        commitment: HashOutTarget,
+    );
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderSparseCommitment<F, D>
+    for CircuitBuilder<F, D>
+{
+    fn sparse_commitment(&mut self, indices: &[Target], values: &[Target]) -> HashOutTarget {
+        assert_eq!(
+            indices.len(),
+            values.len(),
+            "Mismatched number of indices and values"
+        );
+        assert!(
+            !indices.is_empty(),
+            "Cannot commit to an empty sparse vector"
+        );
+
+        let mut layer = indices
+            .iter()
+            .zip(values.iter())
+            .map(|(index, value)| self.hash_n_to_hash_no_pad::<PoseidonHash>(vec![*index, *value]))
+            .collect::<Vec<_>>();
+        let zero = self.constant_hash(HashOut::ZERO);
+        layer.resize(layer.len().next_power_of_two(), zero);
+        while layer.len() > 1 {
+            layer = layer
+                .chunks(2)
+                .map(|pair| {
+                    let inputs = [pair[0].elements, pair[1].elements].concat();
+                    self.hash_n_to_hash_no_pad::<PoseidonHash>(inputs)
+                })
+                .collect();
+        }
+        layer[0]
+    }
+
+    fn verify_sparse_commitment(
+        &mut self,
+        indices: &[Target],
+        values: &[Target],
+        commitment: HashOutTarget,
+    ) {
+        for index in indices.iter() {
+            self.range_check(*index, SPARSE_INDEX_BITS);
+        }
+
+        // Since both indices are range checked, `next - index - 1` is a small integer exactly
+        // when `index < next`, and wraps around the field modulus otherwise.
+        for pair in indices.windows(2) {
+            let difference = self.sub(pair[1], pair[0]);
+            let one = self.one();
+            let gap = self.sub(difference, one);
+            self.range_check(gap, SPARSE_INDEX_BITS);
+        }
+
+        let computed = self.sparse_commitment(indices, values);
+        self.connect_hashes(computed, commitment);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::Sample;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+
+    use super::*;
+
+    type F = GoldilocksField;
+    type C = PoseidonGoldilocksConfig;
+    const D: usize = 2;
+
+    fn prove_sparse_commitment(index_values: &[u64], value_values: &[F]) {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let indices = builder.add_virtual_targets(index_values.len());
+        let values = builder.add_virtual_targets(index_values.len());
+        let commitment = builder.add_virtual_hash();
+        builder.verify_sparse_commitment(&indices, &values, commitment);
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+
+        for (target, index) in indices.iter().zip(index_values) {
+            pw.set_target(*target, F::from_canonical_u64(*index));
+        }
+        pw.set_target_arr(&values, value_values);
+        // The commitment is always consistent with the entries, so that only the ordering of the
+        // indices can make the proof fail.
+        pw.set_hash_target(commitment, sparse_commitment(index_values, value_values));
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+
+    #[test]
+    fn test_sparse_commitment() {
+        prove_sparse_commitment(&[3, 17, 18, 1000, u32::MAX as u64], &F::rand_vec(5));
+        prove_sparse_commitment(&[0], &F::rand_vec(1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sparse_commitment_out_of_order() {
+        prove_sparse_commitment(&[3, 18, 17, 1000], &F::rand_vec(4));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sparse_commitment_repeated_index() {
+        prove_sparse_commitment(&[3, 17, 17, 1000], &F::rand_vec(4));
+    }
+}
@@ -0,0 +1,257 @@
+use plonky2::field::extension::Extendable;
+use plonky2::field::types::PrimeField64;
+use plonky2::hash::hash_types::{HashOut, HashOutTarget, RichField};
+use plonky2::hash::poseidon::PoseidonHash;
+use plonky2::iop::target::{BoolTarget, Target};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::config::Hasher;
+
+/// An update of the state of the account at leaf `index` of the state tree from `old_state` to
+/// `new_state`, with the sibling digests of the path from the leaf to the root, leaf level first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountDiff<T, H> {
+    pub index: T,
+    pub old_state: Vec<T>,
+    pub new_state: Vec<T>,
+    pub siblings: Vec<H>,
+}
+
+pub type AccountDiffTarget = AccountDiff<Target, HashOutTarget>;
+
+/// Computes the root of the state tree natively from the state of the account at leaf `index`
+/// and the siblings of its path.
+///
+/// The leaf is the hash of the account state, and each node is the hash of its two children.
+pub fn state_root<F: RichField>(index: u64, state: &[F], siblings: &[HashOut<F>]) -> HashOut<F> {
+    assert!(index < 1 << siblings.len(), "Index out of range");
+    let mut node = PoseidonHash::hash_no_pad(state);
+    for (height, sibling) in siblings.iter().enumerate() {
+        node = if (index >> height) & 1 == 0 {
+            PoseidonHash::two_to_one(node, *sibling)
+        } else {
+            PoseidonHash::two_to_one(*sibling, node)
+        };
+    }
+    node
+}
+
+impl<F: RichField> AccountDiff<F, HashOut<F>> {
+    /// Checks that the diff applies to the state root `old_root` natively, and returns the new
+    /// state root.
+    pub fn apply(&self, old_root: HashOut<F>) -> Option<HashOut<F>> {
+        let index = self.index.to_canonical_u64();
+        if state_root(index, &self.old_state, &self.siblings) != old_root {
+            return None;
+        }
+        Some(state_root(index, &self.new_state, &self.siblings))
+    }
+}
+
+pub trait CircuitBuilderStateDiff<F: RichField + Extendable<D>, const D: usize> {
+    /// Computes the root of the state tree from the state of the account at the leaf with the
+    /// given index bits and the siblings of its path, as in `state_root`.
+    fn state_root(
+        &mut self,
+        index_bits: &[BoolTarget],
+        state: &[Target],
+        siblings: &[HashOutTarget],
+    ) -> HashOutTarget;
+
+    /// Asserts that applying the account diffs in order to the state tree with root `old_root`
+    /// gives the root `new_root`.
+    ///
+    /// Each diff is checked against the root obtained from the previous ones, so the same account
+    /// can be updated several times. The depth of the tree is the number of siblings of the
+    /// diffs, and the indices are checked to be less than `2^depth`.
+    fn verify_state_transition(
+        &mut self,
+        old_root: HashOutTarget,
+        diffs: &[AccountDiffTarget],
+        new_root: HashOutTarget,
+    );
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderStateDiff<F, D>
+    for CircuitBuilder<F, D>
+{
+    fn state_root(
+        &mut self,
+        index_bits: &[BoolTarget],
+        state: &[Target],
+        siblings: &[HashOutTarget],
+    ) -> HashOutTarget {
+        assert_eq!(
+            index_bits.len(),
+            siblings.len(),
+            "Mismatched number of index bits and siblings"
+        );
+        let mut node = self.hash_n_to_hash_no_pad::<PoseidonHash>(state.to_vec());
+        for (bit, sibling) in index_bits.iter().zip(siblings) {
+            let left = self.select_hash(*bit, *sibling, node);
+            let right = self.select_hash(*bit, node, *sibling);
+            let inputs = [left.elements, right.elements].concat();
+            node = self.hash_n_to_hash_no_pad::<PoseidonHash>(inputs);
+        }
+        node
+    }
+
+    fn verify_state_transition(
+        &mut self,
+        old_root: HashOutTarget,
+        diffs: &[AccountDiffTarget],
+        new_root: HashOutTarget,
+    ) {
+        let mut root = old_root;
+        for diff in diffs.iter() {
+            let index_bits = self.split_le(diff.index, diff.siblings.len());
+            let old_leaf_root = self.state_root(&index_bits, &diff.old_state, &diff.siblings);
+            self.connect_hashes(old_leaf_root, root);
+            root = self.state_root(&index_bits, &diff.new_state, &diff.siblings);
+        }
+        self.connect_hashes(root, new_root);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::{Field, Sample};
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+
+    use super::*;
+
+    type F = GoldilocksField;
+    type C = PoseidonGoldilocksConfig;
+    const D: usize = 2;
+
+    const DEPTH: usize = 4;
+
+    /// A state tree of accounts with a balance and a nonce.
+    struct StateTree {
+        accounts: Vec<[F; 2]>,
+    }
+
+    impl StateTree {
+        /// The layers of the tree, from the leaves to the root.
+        fn layers(&self) -> Vec<Vec<HashOut<F>>> {
+            let mut layers = vec![self
+                .accounts
+                .iter()
+                .map(|state| PoseidonHash::hash_no_pad(state))
+                .collect::<Vec<_>>()];
+            while layers.last().unwrap().len() > 1 {
+                let next = layers
+                    .last()
+                    .unwrap()
+                    .chunks(2)
+                    .map(|pair| PoseidonHash::two_to_one(pair[0], pair[1]))
+                    .collect();
+                layers.push(next);
+            }
+            layers
+        }
+
+        fn root(&self) -> HashOut<F> {
+            self.layers().last().unwrap()[0]
+        }
+
+        /// Sets the state of the account at `index` and returns the corresponding diff.
+        fn update(&mut self, index: usize, new_state: [F; 2]) -> AccountDiff<F, HashOut<F>> {
+            let layers = self.layers();
+            let siblings = (0..DEPTH)
+                .map(|height| layers[height][(index >> height) ^ 1])
+                .collect();
+            let old_state = self.accounts[index];
+            self.accounts[index] = new_state;
+            AccountDiff {
+                index: F::from_canonical_usize(index),
+                old_state: old_state.to_vec(),
+                new_state: new_state.to_vec(),
+                siblings,
+            }
+        }
+    }
+
+    /// Proves the transition from `old_root` to `new_root` by the given diffs.
+    fn prove_state_transition(
+        old_root: HashOut<F>,
+        diffs: &[AccountDiff<F, HashOut<F>>],
+        new_root: HashOut<F>,
+    ) {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let old_root_target = builder.add_virtual_hash();
+        let new_root_target = builder.add_virtual_hash();
+        let diff_targets = diffs
+            .iter()
+            .map(|diff| AccountDiffTarget {
+                index: builder.add_virtual_target(),
+                old_state: builder.add_virtual_targets(diff.old_state.len()),
+                new_state: builder.add_virtual_targets(diff.new_state.len()),
+                siblings: builder.add_virtual_hashes(diff.siblings.len()),
+            })
+            .collect::<Vec<_>>();
+        builder.verify_state_transition(old_root_target, &diff_targets, new_root_target);
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+
+        pw.set_hash_target(old_root_target, old_root);
+        pw.set_hash_target(new_root_target, new_root);
+        for (target, diff) in diff_targets.iter().zip(diffs) {
+            pw.set_target(target.index, diff.index);
+            pw.set_target_arr(&target.old_state, &diff.old_state);
+            pw.set_target_arr(&target.new_state, &diff.new_state);
+            for (sibling_target, sibling) in target.siblings.iter().zip(diff.siblings.iter()) {
+                pw.set_hash_target(*sibling_target, *sibling);
+            }
+        }
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+
+    /// Applies balance changes to a random state tree and returns the old root, the diffs and the
+    /// new root.
+    fn balance_changes() -> (HashOut<F>, Vec<AccountDiff<F, HashOut<F>>>, HashOut<F>) {
+        let mut tree = StateTree {
+            accounts: (0..1 << DEPTH).map(|_| F::rand_array()).collect(),
+        };
+        let old_root = tree.root();
+
+        // Transfer from account 3 to account 12, then from account 12 to account 5.
+        let mut diffs = Vec::new();
+        for (from, to, amount) in [(3, 12, 10u64), (12, 5, 4u64)] {
+            let amount = F::from_canonical_u64(amount);
+            let [balance, nonce] = tree.accounts[from];
+            diffs.push(tree.update(from, [balance - amount, nonce + F::ONE]));
+            let [balance, nonce] = tree.accounts[to];
+            diffs.push(tree.update(to, [balance + amount, nonce]));
+        }
+        (old_root, diffs, tree.root())
+    }
+
+    #[test]
+    fn test_state_transition() {
+        let (old_root, diffs, new_root) = balance_changes();
+
+        let mut root = old_root;
+        for diff in diffs.iter() {
+            root = diff.apply(root).unwrap();
+        }
+        assert_eq!(root, new_root);
+
+        prove_state_transition(old_root, &diffs, new_root);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_state_transition_wrong_balance() {
+        let (old_root, mut diffs, new_root) = balance_changes();
+        diffs[1].old_state[0] += F::ONE;
+        prove_state_transition(old_root, &diffs, new_root);
+    }
+}
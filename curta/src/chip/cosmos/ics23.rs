@@ -0,0 +1,489 @@
+//! Membership proofs of ICS-23, the format of the Merkle proofs of IBC.
+//!
+//! An existence proof computes the root of a tree from a key and a value. The leaf is the hash of
+//! the prefix of the leaf, the length-prefixed key and the length-prefixed hash of the value, and
+//! every inner node is the hash of the prefix of the operation, the current node and the suffix of
+//! the operation. The siblings of the path are part of the prefixes and the suffixes, so that the
+//! layout of a proof, i.e. the lengths of the key, the value and the prefixes and suffixes, is
+//! fixed when the circuit is built while their bytes are given by targets.
+//!
+//! The specs are restricted to trees hashed with SHA-256 whose leaves have an unhashed key, a
+//! value prehashed with SHA-256 and varint length prefixes, which is the case for the IAVL and the
+//! Tendermint specs.
+
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::Target;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+use super::encode_varint;
+use crate::chip::hash::sha::sha256::builder_gadget::{
+    CurtaBytes, SHA256Builder, SHA256BuilderGadget,
+};
+use crate::chip::hash::sha::sha256::SHA256Gadget;
+use crate::math::prelude::CubicParameters;
+
+/// The spec of the proofs of a tree with two children per inner node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofSpec {
+    /// The bytes that the prefix of a leaf starts with, and the prefix of an inner node does not.
+    pub leaf_prefix: Vec<u8>,
+    /// The number of bytes of a child of an inner node in the prefix or the suffix.
+    pub child_size: usize,
+    pub min_prefix_length: usize,
+    /// The maximal length of a prefix, without the bytes of the left child.
+    pub max_prefix_length: usize,
+}
+
+impl ProofSpec {
+    /// The spec of the IAVL trees of the Cosmos SDK.
+    pub fn iavl() -> Self {
+        Self {
+            leaf_prefix: vec![0],
+            child_size: 33,
+            min_prefix_length: 4,
+            max_prefix_length: 12,
+        }
+    }
+
+    /// The spec of the simple Merkle trees of Tendermint.
+    pub fn tendermint() -> Self {
+        Self {
+            leaf_prefix: vec![0],
+            child_size: 32,
+            min_prefix_length: 1,
+            max_prefix_length: 1,
+        }
+    }
+
+    /// Whether the lengths of the prefix and the suffix of an inner operation are valid.
+    pub fn is_valid_inner_op_layout(&self, prefix_len: usize, suffix_len: usize) -> bool {
+        prefix_len >= self.min_prefix_length
+            && prefix_len <= self.max_prefix_length + self.child_size
+            && suffix_len % self.child_size == 0
+    }
+
+    /// Whether `prefix` is a valid prefix of an inner operation, assuming that its length is valid.
+    fn is_valid_inner_prefix(&self, prefix: &[u8]) -> bool {
+        !prefix.starts_with(&self.leaf_prefix)
+    }
+}
+
+/// The operation computing a leaf from a key and a value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeafOp {
+    pub prefix: Vec<u8>,
+}
+
+/// The operation computing an inner node from one of its children.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InnerOp {
+    pub prefix: Vec<u8>,
+    pub suffix: Vec<u8>,
+}
+
+/// A proof that a key has a value in the tree of a root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExistenceProof {
+    pub key: Vec<u8>,
+    pub value: Vec<u8>,
+    pub leaf: LeafOp,
+    /// The inner operations from the leaf up to the root.
+    pub path: Vec<InnerOp>,
+}
+
+/// The lengths of the parts of an existence proof.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExistenceProofLayout {
+    pub key_len: usize,
+    pub value_len: usize,
+    pub leaf_prefix_len: usize,
+    /// The lengths of the prefix and the suffix of every inner operation.
+    pub path: Vec<(usize, usize)>,
+}
+
+/// The targets of the leaf prefix and the inner operations of an existence proof.
+#[derive(Debug, Clone)]
+pub struct ExistenceProofTarget {
+    pub leaf_prefix: Vec<Target>,
+    /// The prefix and the suffix of every inner operation.
+    pub path: Vec<(Vec<Target>, Vec<Target>)>,
+}
+
+/// The bytes hashed by a leaf operation, with the given hash of the value.
+fn leaf_message<T: Clone>(
+    prefix: &[T],
+    key: &[T],
+    value_hash: &[T],
+    mut constant: impl FnMut(u8) -> T,
+) -> Vec<T> {
+    let mut key_len = Vec::new();
+    encode_varint(key.len() as u64, &mut key_len);
+    let mut value_hash_len = Vec::new();
+    encode_varint(value_hash.len() as u64, &mut value_hash_len);
+
+    let mut message = prefix.to_vec();
+    message.extend(key_len.into_iter().map(&mut constant));
+    message.extend_from_slice(key);
+    message.extend(value_hash_len.into_iter().map(&mut constant));
+    message.extend_from_slice(value_hash);
+    message
+}
+
+impl ExistenceProof {
+    /// Computes the leaf of the key and the value.
+    pub fn leaf_hash(&self) -> [u8; 32] {
+        let value_hash = SHA256Gadget::hash(&self.value);
+        SHA256Gadget::hash(&leaf_message(
+            &self.leaf.prefix,
+            &self.key,
+            &value_hash,
+            |byte| byte,
+        ))
+    }
+
+    /// Computes the root of the tree from the leaf and the inner operations.
+    pub fn calculate_root(&self) -> [u8; 32] {
+        self.path.iter().fold(self.leaf_hash(), |node, op| {
+            SHA256Gadget::hash(&[&op.prefix[..], &node[..], &op.suffix[..]].concat())
+        })
+    }
+
+    /// Whether the proof follows `spec` and proves that `key` has the value `value` in the tree
+    /// of `root`.
+    pub fn verify(&self, spec: &ProofSpec, root: &[u8; 32], key: &[u8], value: &[u8]) -> bool {
+        self.leaf.prefix.starts_with(&spec.leaf_prefix)
+            && self.path.iter().all(|op| {
+                spec.is_valid_inner_op_layout(op.prefix.len(), op.suffix.len())
+                    && spec.is_valid_inner_prefix(&op.prefix)
+            })
+            && self.key == key
+            && self.value == value
+            && self.calculate_root() == *root
+    }
+
+    pub fn layout(&self) -> ExistenceProofLayout {
+        ExistenceProofLayout {
+            key_len: self.key.len(),
+            value_len: self.value.len(),
+            leaf_prefix_len: self.leaf.prefix.len(),
+            path: self
+                .path
+                .iter()
+                .map(|op| (op.prefix.len(), op.suffix.len()))
+                .collect(),
+        }
+    }
+}
+
+/// Computes the SHA-256 digest of `message`, whose length is fixed when the circuit is built so
+/// that its padding is constant.
+fn sha256_message<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    message: &[Target],
+    gadget: &mut SHA256BuilderGadget<F, E, D>,
+) -> CurtaBytes<32> {
+    let len = message.len();
+    let padding = SHA256Gadget::pad(&vec![0u8; len])[len..]
+        .iter()
+        .map(|byte| builder.constant(F::from_canonical_u8(*byte)))
+        .collect::<Vec<_>>();
+    let padded_message = [message, &padding[..]].concat();
+    SHA256Builder::<F, E, D>::sha256_bytes(builder, &padded_message, gadget)
+}
+
+pub trait CircuitBuilderIcs23<F: RichField + Extendable<D>, const D: usize> {
+    fn add_virtual_existence_proof(
+        &mut self,
+        layout: &ExistenceProofLayout,
+    ) -> ExistenceProofTarget;
+
+    /// Verifies that `proof` follows `spec` and proves that `key` has the value `value` in the
+    /// tree of `root`.
+    ///
+    /// The lengths of the prefixes and suffixes are checked against the spec when the circuit is
+    /// built, and their bytes in the circuit.
+    fn verify_membership<E: CubicParameters<F>>(
+        &mut self,
+        spec: &ProofSpec,
+        key: &[Target],
+        value: &[Target],
+        proof: &ExistenceProofTarget,
+        root: &CurtaBytes<32>,
+        gadget: &mut SHA256BuilderGadget<F, E, D>,
+    );
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderIcs23<F, D>
+    for CircuitBuilder<F, D>
+{
+    fn add_virtual_existence_proof(
+        &mut self,
+        layout: &ExistenceProofLayout,
+    ) -> ExistenceProofTarget {
+        ExistenceProofTarget {
+            leaf_prefix: self.add_virtual_targets(layout.leaf_prefix_len),
+            path: layout
+                .path
+                .iter()
+                .map(|(prefix_len, suffix_len)| {
+                    (
+                        self.add_virtual_targets(*prefix_len),
+                        self.add_virtual_targets(*suffix_len),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    fn verify_membership<E: CubicParameters<F>>(
+        &mut self,
+        spec: &ProofSpec,
+        key: &[Target],
+        value: &[Target],
+        proof: &ExistenceProofTarget,
+        root: &CurtaBytes<32>,
+        gadget: &mut SHA256BuilderGadget<F, E, D>,
+    ) {
+        // The prefix of the leaf starts with the leaf prefix of the spec.
+        assert!(
+            proof.leaf_prefix.len() >= spec.leaf_prefix.len(),
+            "Leaf prefix is too short"
+        );
+        for (target, byte) in proof.leaf_prefix.iter().zip(spec.leaf_prefix.iter()) {
+            let expected = self.constant(F::from_canonical_u8(*byte));
+            self.connect(*target, expected);
+        }
+
+        let value_hash = sha256_message(self, value, gadget);
+        let leaf = leaf_message(&proof.leaf_prefix, key, &value_hash.0, |byte| {
+            self.constant(F::from_canonical_u8(byte))
+        });
+        let mut current = sha256_message(self, &leaf, gadget);
+
+        for (prefix, suffix) in proof.path.iter() {
+            assert!(
+                spec.is_valid_inner_op_layout(prefix.len(), suffix.len()),
+                "Invalid inner operation layout"
+            );
+
+            // The prefix of an inner node does not start with the leaf prefix of the spec.
+            if prefix.len() >= spec.leaf_prefix.len() {
+                let mut starts_with_leaf_prefix = self._true();
+                for (target, byte) in prefix.iter().zip(spec.leaf_prefix.iter()) {
+                    let expected = self.constant(F::from_canonical_u8(*byte));
+                    let is_equal = self.is_equal(*target, expected);
+                    starts_with_leaf_prefix = self.and(starts_with_leaf_prefix, is_equal);
+                }
+                self.assert_zero(starts_with_leaf_prefix.target);
+            }
+
+            let message = [&prefix[..], &current.0[..], &suffix[..]].concat();
+            current = sha256_message(self, &message, gadget);
+        }
+
+        for (a, b) in current.0.iter().zip(root.0.iter()) {
+            self.connect(*a, *b);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::types::Field;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    type F = GoldilocksField;
+    type E = GoldilocksCubicParameters;
+    type SC = CurtaPoseidonGoldilocksConfig;
+    type C = PoseidonGoldilocksConfig;
+    const D: usize = 2;
+
+    fn to_field_bytes(bytes: &[u8]) -> Vec<F> {
+        bytes.iter().map(|x| F::from_canonical_u8(*x)).collect()
+    }
+
+    /// The proof of the entry at `index` in a Tendermint tree of four entries, and the root.
+    fn tendermint_proof(index: usize) -> (ExistenceProof, [u8; 32]) {
+        let entries = (0..4)
+            .map(|i| {
+                ExistenceProof {
+                    key: format!("key{}", i).into_bytes(),
+                    value: format!("value{}", i).into_bytes(),
+                    leaf: LeafOp { prefix: vec![0] },
+                    path: vec![],
+                }
+                .leaf_hash()
+            })
+            .collect::<Vec<_>>();
+        let inner = |left: &[u8; 32], right: &[u8; 32]| {
+            SHA256Gadget::hash(&[&[1u8][..], &left[..], &right[..]].concat())
+        };
+        let level_1 = [
+            inner(&entries[0], &entries[1]),
+            inner(&entries[2], &entries[3]),
+        ];
+        let root = inner(&level_1[0], &level_1[1]);
+
+        let mut path = Vec::new();
+        for (position, sibling) in [
+            (index, entries[index ^ 1]),
+            (index >> 1, level_1[1 - (index >> 1)]),
+        ] {
+            path.push(if position & 1 == 0 {
+                InnerOp {
+                    prefix: vec![1],
+                    suffix: sibling.to_vec(),
+                }
+            } else {
+                InnerOp {
+                    prefix: [&[1u8][..], &sibling[..]].concat(),
+                    suffix: vec![],
+                }
+            });
+        }
+        let proof = ExistenceProof {
+            key: format!("key{}", index).into_bytes(),
+            value: format!("value{}", index).into_bytes(),
+            leaf: LeafOp { prefix: vec![0] },
+            path,
+        };
+        (proof, root)
+    }
+
+    /// The proof of the right entry of an IAVL tree of two entries at version 5, and the root.
+    ///
+    /// The nodes start with the zigzag varints of their height, size and version, and the
+    /// children of an inner node are prefixed by their length.
+    fn iavl_proof() -> (ExistenceProof, [u8; 32]) {
+        let left = ExistenceProof {
+            key: b"alice".to_vec(),
+            value: b"100".to_vec(),
+            leaf: LeafOp {
+                prefix: vec![0x00, 0x02, 0x0a],
+            },
+            path: vec![],
+        };
+        let proof = ExistenceProof {
+            key: b"bob".to_vec(),
+            value: b"42".to_vec(),
+            leaf: LeafOp {
+                prefix: vec![0x00, 0x02, 0x0a],
+            },
+            path: vec![InnerOp {
+                prefix: [
+                    &[0x02, 0x04, 0x0a, 0x20][..],
+                    &left.leaf_hash()[..],
+                    &[0x20][..],
+                ]
+                .concat(),
+                suffix: vec![],
+            }],
+        };
+        let root = proof.calculate_root();
+        (proof, root)
+    }
+
+    #[test]
+    fn test_verify_membership_native() {
+        let (proof, root) = tendermint_proof(2);
+        let spec = ProofSpec::tendermint();
+        assert!(proof.verify(&spec, &root, b"key2", b"value2"));
+        assert!(!proof.verify(&spec, &root, b"key2", b"value3"));
+        // The prefixes of a Tendermint proof are too short for the IAVL spec.
+        assert!(!proof.verify(&ProofSpec::iavl(), &root, b"key2", b"value2"));
+
+        let (proof, root) = iavl_proof();
+        assert!(proof.verify(&ProofSpec::iavl(), &root, b"bob", b"42"));
+
+        // An inner node whose prefix starts with the leaf prefix is rejected.
+        let (mut proof, _) = tendermint_proof(2);
+        proof.path[0].prefix = vec![0];
+        let root = proof.calculate_root();
+        assert!(!proof.verify(&spec, &root, b"key2", b"value2"));
+    }
+
+    /// Verifies the membership proofs in a circuit, with `value` in place of the value of the
+    /// Tendermint proof if given.
+    fn prove_membership(value: Option<&[u8]>) {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let proofs = [
+            (ProofSpec::tendermint(), tendermint_proof(1)),
+            (ProofSpec::iavl(), iavl_proof()),
+        ];
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let mut gadget = SHA256Builder::<F, E, D>::init_sha256(&mut builder);
+
+        let mut targets = Vec::new();
+        for (spec, (proof, _)) in proofs.iter() {
+            let layout = proof.layout();
+            let key = builder.add_virtual_targets(layout.key_len);
+            let value = builder.add_virtual_targets(layout.value_len);
+            let proof_target = builder.add_virtual_existence_proof(&layout);
+            let root = CurtaBytes(builder.add_virtual_target_arr::<32>());
+            builder.verify_membership(spec, &key, &value, &proof_target, &root, &mut gadget);
+            targets.push((key, value, proof_target, root));
+        }
+
+        // The SHA256 gadget processes a fixed number of 1024 chunks.
+        let num_chunks = gadget.chunk_sizes.iter().sum::<usize>();
+        let dummy_messages = (0..1024 - num_chunks)
+            .map(|_| CurtaBytes(builder.add_virtual_target_arr::<64>()))
+            .collect::<Vec<_>>();
+        for message in dummy_messages.iter() {
+            builder.sha256(message, &mut gadget);
+        }
+        builder.constrain_sha256_gadget::<SC>(gadget);
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+
+        for (i, ((_, (proof, root)), (key, value_target, proof_target, root_target))) in
+            proofs.iter().zip(targets.iter()).enumerate()
+        {
+            let value_bytes = match value {
+                Some(value) if i == 0 => value,
+                _ => &proof.value[..],
+            };
+            pw.set_target_arr(key, &to_field_bytes(&proof.key));
+            pw.set_target_arr(value_target, &to_field_bytes(value_bytes));
+            pw.set_target_arr(
+                &proof_target.leaf_prefix,
+                &to_field_bytes(&proof.leaf.prefix),
+            );
+            for ((prefix, suffix), op) in proof_target.path.iter().zip(proof.path.iter()) {
+                pw.set_target_arr(prefix, &to_field_bytes(&op.prefix));
+                pw.set_target_arr(suffix, &to_field_bytes(&op.suffix));
+            }
+            pw.set_target_arr(&root_target.0, &to_field_bytes(root));
+        }
+
+        let dummy_padded_message = to_field_bytes(&SHA256Gadget::pad(b""));
+        for message in dummy_messages.iter() {
+            pw.set_target_arr(&message.0, &dummy_padded_message);
+        }
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+
+    #[test]
+    fn test_verify_membership() {
+        prove_membership(None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_verify_membership_wrong_value() {
+        prove_membership(Some(b"value2"));
+    }
+}
@@ -0,0 +1,13 @@
+//! Gadgets for Cosmos light clients.
+
+pub mod ics23;
+pub mod vote;
+
+/// Appends the protobuf varint encoding of `value` to `bytes`.
+pub fn encode_varint(mut value: u64, bytes: &mut Vec<u8>) {
+    while value >= 0x80 {
+        bytes.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
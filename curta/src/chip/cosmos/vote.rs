@@ -0,0 +1,657 @@
+//! The sign-bytes of Tendermint/CometBFT votes, which validators sign with Ed25519.
+//!
+//! The sign-bytes of a vote are the length-delimited protobuf encoding of its `CanonicalVote`,
+//! whose fields are, in order, the type of the vote, the height and the round as `sfixed64`, the
+//! block id, the timestamp and the chain id. Protobuf omits the fields with a default value and
+//! encodes integers as varints, so the position of every field depends on the values of the
+//! previous ones. A circuit is therefore built for a fixed [`VoteLayout`], which contains the
+//! type and the chain id, the fields which are present and the lengths of the varints, while the
+//! other values of the vote are given by targets.
+
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::Target;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+use super::encode_varint;
+use crate::chip::hash::sha::sha256::builder_gadget::{
+    CurtaBytes, SHA256Builder, SHA256BuilderGadget,
+};
+use crate::chip::hash::sha::sha256::SHA256Gadget;
+use crate::math::prelude::CubicParameters;
+
+/// The type of a prevote.
+pub const PREVOTE_TYPE: u8 = 1;
+
+/// The type of a precommit.
+pub const PRECOMMIT_TYPE: u8 = 2;
+
+// The protobuf tags of the fields, i.e. the field number followed by the wire type.
+const TYPE_TAG: u8 = 0x08;
+const HEIGHT_TAG: u8 = 0x11;
+const ROUND_TAG: u8 = 0x19;
+const BLOCK_ID_TAG: u8 = 0x22;
+const TIMESTAMP_TAG: u8 = 0x2a;
+const CHAIN_ID_TAG: u8 = 0x32;
+const BLOCK_HASH_TAG: u8 = 0x0a;
+const PART_SET_HEADER_TAG: u8 = 0x12;
+const PART_SET_TOTAL_TAG: u8 = 0x08;
+const PART_SET_HASH_TAG: u8 = 0x12;
+const SECONDS_TAG: u8 = 0x08;
+const NANOS_TAG: u8 = 0x10;
+
+/// The header of the parts of a block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartSetHeader {
+    pub total: u32,
+    pub hash: [u8; 32],
+}
+
+/// The id of a block, given by its hash and the header of its parts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockId {
+    pub hash: [u8; 32],
+    pub part_set_header: PartSetHeader,
+}
+
+/// A vote in its canonical form, as signed by the validators.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CanonicalVote {
+    pub vote_type: u8,
+    pub height: i64,
+    pub round: i64,
+    /// The id of the block, `None` for a vote for nil.
+    pub block_id: Option<BlockId>,
+    pub timestamp_seconds: i64,
+    /// The nanoseconds of the timestamp, between zero and `10^9 - 1`.
+    pub timestamp_nanos: i32,
+    pub chain_id: String,
+}
+
+/// The layout of the sign-bytes of a vote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VoteLayout {
+    pub vote_type: u8,
+    pub has_height: bool,
+    pub has_round: bool,
+    /// The length of the varint of the total of the part set header if the vote has a block id,
+    /// with a length of zero if the total is omitted.
+    pub part_set_total_len: Option<usize>,
+    /// The length of the varint of the seconds of the timestamp, zero if they are omitted.
+    pub seconds_len: usize,
+    /// The length of the varint of the nanoseconds of the timestamp, zero if they are omitted.
+    pub nanos_len: usize,
+    pub chain_id: String,
+}
+
+/// The targets of the values of a vote which are not fixed by its layout.
+///
+/// The integers are given by the little endian bytes of their two's complement encoding.
+#[derive(Debug, Clone, Copy)]
+pub struct CanonicalVoteTarget {
+    pub height: [Target; 8],
+    pub round: [Target; 8],
+    pub block_hash: CurtaBytes<32>,
+    pub part_set_total: [Target; 4],
+    pub part_set_hash: CurtaBytes<32>,
+    pub timestamp_seconds: [Target; 8],
+    pub timestamp_nanos: [Target; 4],
+}
+
+/// The length of the varint encoding of `value`, or zero if `value` is zero and thus omitted.
+fn varint_field_len(value: u64) -> usize {
+    if value == 0 {
+        return 0;
+    }
+    let mut bytes = Vec::new();
+    encode_varint(value, &mut bytes);
+    bytes.len()
+}
+
+/// Encodes `message` as a length-delimited field with the given tag, or with no tag at all.
+fn length_delimited(tag: Option<u8>, message: &[u8]) -> Vec<u8> {
+    let mut bytes = tag.into_iter().collect::<Vec<_>>();
+    encode_varint(message.len() as u64, &mut bytes);
+    bytes.extend_from_slice(message);
+    bytes
+}
+
+impl BlockId {
+    /// The protobuf encoding of the block id.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut part_set_header = Vec::new();
+        if self.part_set_header.total != 0 {
+            part_set_header.push(PART_SET_TOTAL_TAG);
+            encode_varint(self.part_set_header.total as u64, &mut part_set_header);
+        }
+        part_set_header.extend(length_delimited(
+            Some(PART_SET_HASH_TAG),
+            &self.part_set_header.hash,
+        ));
+
+        let mut bytes = length_delimited(Some(BLOCK_HASH_TAG), &self.hash);
+        bytes.extend(length_delimited(
+            Some(PART_SET_HEADER_TAG),
+            &part_set_header,
+        ));
+        bytes
+    }
+}
+
+impl CanonicalVote {
+    /// The protobuf encoding of the vote.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        if self.vote_type != 0 {
+            bytes.push(TYPE_TAG);
+            encode_varint(self.vote_type as u64, &mut bytes);
+        }
+        if self.height != 0 {
+            bytes.push(HEIGHT_TAG);
+            bytes.extend_from_slice(&self.height.to_le_bytes());
+        }
+        if self.round != 0 {
+            bytes.push(ROUND_TAG);
+            bytes.extend_from_slice(&self.round.to_le_bytes());
+        }
+        if let Some(block_id) = &self.block_id {
+            bytes.extend(length_delimited(Some(BLOCK_ID_TAG), &block_id.encode()));
+        }
+
+        let mut timestamp = Vec::new();
+        if self.timestamp_seconds != 0 {
+            timestamp.push(SECONDS_TAG);
+            encode_varint(self.timestamp_seconds as u64, &mut timestamp);
+        }
+        if self.timestamp_nanos != 0 {
+            timestamp.push(NANOS_TAG);
+            encode_varint(self.timestamp_nanos as u64, &mut timestamp);
+        }
+        bytes.extend(length_delimited(Some(TIMESTAMP_TAG), &timestamp));
+
+        if !self.chain_id.is_empty() {
+            bytes.extend(length_delimited(
+                Some(CHAIN_ID_TAG),
+                self.chain_id.as_bytes(),
+            ));
+        }
+        bytes
+    }
+
+    /// The sign-bytes of the vote, i.e. its length-delimited protobuf encoding.
+    pub fn sign_bytes(&self) -> Vec<u8> {
+        length_delimited(None, &self.encode())
+    }
+
+    /// The layout of the sign-bytes of the vote.
+    pub fn layout(&self) -> VoteLayout {
+        VoteLayout {
+            vote_type: self.vote_type,
+            has_height: self.height != 0,
+            has_round: self.round != 0,
+            part_set_total_len: self
+                .block_id
+                .as_ref()
+                .map(|block_id| varint_field_len(block_id.part_set_header.total as u64)),
+            seconds_len: varint_field_len(self.timestamp_seconds as u64),
+            nanos_len: varint_field_len(self.timestamp_nanos as u64),
+            chain_id: self.chain_id.clone(),
+        }
+    }
+}
+
+fn constant_bytes<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    bytes: &[u8],
+) -> Vec<Target> {
+    bytes
+        .iter()
+        .map(|byte| builder.constant(F::from_canonical_u8(*byte)))
+        .collect()
+}
+
+/// Encodes the targets of `message` as a length-delimited field with the given tag, or with no
+/// tag at all.
+fn length_delimited_targets<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    tag: Option<u8>,
+    message: Vec<Target>,
+) -> Vec<Target> {
+    let mut prefix = tag.into_iter().collect::<Vec<_>>();
+    encode_varint(message.len() as u64, &mut prefix);
+    let mut targets = constant_bytes(builder, &prefix);
+    targets.extend(message);
+    targets
+}
+
+/// Encodes the integer of little endian bytes `bytes` as a varint of `len` bytes, asserting that
+/// the integer fits in `len` bytes. The bytes are range checked.
+///
+/// The encoding is canonical only if the last byte is not zero, which is not checked. A vote whose
+/// values do not match its layout has different sign-bytes, so that its signature is invalid.
+fn varint_targets<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    bytes: &[Target],
+    len: usize,
+) -> Vec<Target> {
+    let bits = bytes
+        .iter()
+        .flat_map(|byte| builder.split_le(*byte, 8))
+        .collect::<Vec<_>>();
+    for bit in bits.iter().skip(7 * len) {
+        builder.assert_zero(bit.target);
+    }
+
+    (0..len)
+        .map(|i| {
+            let continuation = if i + 1 < len { 0x80 } else { 0 };
+            let mut value = builder.constant(F::from_canonical_u32(continuation));
+            for (j, bit) in bits.iter().skip(7 * i).take(7).enumerate() {
+                value = builder.mul_const_add(F::from_canonical_u32(1 << j), bit.target, value);
+            }
+            value
+        })
+        .collect()
+}
+
+pub trait CircuitBuilderCanonicalVote<F: RichField + Extendable<D>, const D: usize> {
+    fn add_virtual_canonical_vote(&mut self) -> CanonicalVoteTarget;
+
+    /// Computes the sign-bytes of `vote` with the given layout.
+    ///
+    /// The bytes of `vote` are range checked, and the values omitted by the layout are asserted to
+    /// be zero.
+    fn canonical_vote_sign_bytes(
+        &mut self,
+        vote: &CanonicalVoteTarget,
+        layout: &VoteLayout,
+    ) -> Vec<Target>;
+
+    /// Computes the SHA-256 hash of the sign-bytes of `vote` with the given layout, where `N` is
+    /// the length of the padded sign-bytes.
+    fn canonical_vote_hash<E: CubicParameters<F>, const N: usize>(
+        &mut self,
+        vote: &CanonicalVoteTarget,
+        layout: &VoteLayout,
+        gadget: &mut SHA256BuilderGadget<F, E, D>,
+    ) -> CurtaBytes<32>;
+
+    /// Asserts that `hash` is the SHA-256 hash of the sign-bytes of `vote` with the given layout.
+    fn verify_canonical_vote_hash<E: CubicParameters<F>, const N: usize>(
+        &mut self,
+        vote: &CanonicalVoteTarget,
+        layout: &VoteLayout,
+        hash: &CurtaBytes<32>,
+        gadget: &mut SHA256BuilderGadget<F, E, D>,
+    );
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderCanonicalVote<F, D>
+    for CircuitBuilder<F, D>
+{
+    fn add_virtual_canonical_vote(&mut self) -> CanonicalVoteTarget {
+        CanonicalVoteTarget {
+            height: self.add_virtual_target_arr(),
+            round: self.add_virtual_target_arr(),
+            block_hash: CurtaBytes(self.add_virtual_target_arr()),
+            part_set_total: self.add_virtual_target_arr(),
+            part_set_hash: CurtaBytes(self.add_virtual_target_arr()),
+            timestamp_seconds: self.add_virtual_target_arr(),
+            timestamp_nanos: self.add_virtual_target_arr(),
+        }
+    }
+
+    fn canonical_vote_sign_bytes(
+        &mut self,
+        vote: &CanonicalVoteTarget,
+        layout: &VoteLayout,
+    ) -> Vec<Target> {
+        assert!(layout.vote_type < 0x80, "Invalid vote type");
+
+        let mut bytes = Vec::new();
+        if layout.vote_type != 0 {
+            bytes.extend(constant_bytes(self, &[TYPE_TAG, layout.vote_type]));
+        }
+        for (tag, is_present, value) in [
+            (HEIGHT_TAG, layout.has_height, &vote.height),
+            (ROUND_TAG, layout.has_round, &vote.round),
+        ] {
+            if is_present {
+                bytes.extend(constant_bytes(self, &[tag]));
+                for byte in value.iter() {
+                    self.range_check(*byte, 8);
+                    bytes.push(*byte);
+                }
+            } else {
+                for byte in value.iter() {
+                    self.assert_zero(*byte);
+                }
+            }
+        }
+
+        match layout.part_set_total_len {
+            Some(total_len) => {
+                for byte in vote.block_hash.0.iter().chain(vote.part_set_hash.0.iter()) {
+                    self.range_check(*byte, 8);
+                }
+                let mut part_set_header = Vec::new();
+                if total_len > 0 {
+                    part_set_header.extend(constant_bytes(self, &[PART_SET_TOTAL_TAG]));
+                }
+                part_set_header.extend(varint_targets(self, &vote.part_set_total, total_len));
+                part_set_header.extend(length_delimited_targets(
+                    self,
+                    Some(PART_SET_HASH_TAG),
+                    vote.part_set_hash.0.to_vec(),
+                ));
+
+                let mut block_id = length_delimited_targets(
+                    self,
+                    Some(BLOCK_HASH_TAG),
+                    vote.block_hash.0.to_vec(),
+                );
+                block_id.extend(length_delimited_targets(
+                    self,
+                    Some(PART_SET_HEADER_TAG),
+                    part_set_header,
+                ));
+                bytes.extend(length_delimited_targets(self, Some(BLOCK_ID_TAG), block_id));
+            }
+            None => {
+                for byte in vote
+                    .block_hash
+                    .0
+                    .iter()
+                    .chain(vote.part_set_total.iter())
+                    .chain(vote.part_set_hash.0.iter())
+                {
+                    self.assert_zero(*byte);
+                }
+            }
+        }
+
+        let mut timestamp = Vec::new();
+        if layout.seconds_len > 0 {
+            timestamp.extend(constant_bytes(self, &[SECONDS_TAG]));
+        }
+        timestamp.extend(varint_targets(
+            self,
+            &vote.timestamp_seconds,
+            layout.seconds_len,
+        ));
+        if layout.nanos_len > 0 {
+            timestamp.extend(constant_bytes(self, &[NANOS_TAG]));
+        }
+        timestamp.extend(varint_targets(
+            self,
+            &vote.timestamp_nanos,
+            layout.nanos_len,
+        ));
+        bytes.extend(length_delimited_targets(
+            self,
+            Some(TIMESTAMP_TAG),
+            timestamp,
+        ));
+
+        if !layout.chain_id.is_empty() {
+            bytes.extend(constant_bytes(
+                self,
+                &length_delimited(Some(CHAIN_ID_TAG), layout.chain_id.as_bytes()),
+            ));
+        }
+
+        length_delimited_targets(self, None, bytes)
+    }
+
+    fn canonical_vote_hash<E: CubicParameters<F>, const N: usize>(
+        &mut self,
+        vote: &CanonicalVoteTarget,
+        layout: &VoteLayout,
+        gadget: &mut SHA256BuilderGadget<F, E, D>,
+    ) -> CurtaBytes<32> {
+        let sign_bytes = self.canonical_vote_sign_bytes(vote, layout);
+
+        // The length of the sign-bytes is fixed by the layout, so the padding is constant.
+        let len = sign_bytes.len();
+        let padding = SHA256Gadget::pad(&vec![0u8; len])[len..].to_vec();
+        assert_eq!(
+            len + padding.len(),
+            N,
+            "Invalid length of padded sign-bytes"
+        );
+
+        let mut padded_message = sign_bytes;
+        padded_message.extend(constant_bytes(self, &padding));
+        SHA256Builder::<F, E, D>::sha256(
+            self,
+            &CurtaBytes::<N>(padded_message.try_into().unwrap()),
+            gadget,
+        )
+    }
+
+    fn verify_canonical_vote_hash<E: CubicParameters<F>, const N: usize>(
+        &mut self,
+        vote: &CanonicalVoteTarget,
+        layout: &VoteLayout,
+        hash: &CurtaBytes<32>,
+        gadget: &mut SHA256BuilderGadget<F, E, D>,
+    ) {
+        let expected = self.canonical_vote_hash::<E, N>(vote, layout, gadget);
+        for (a, b) in expected.0.iter().zip(hash.0.iter()) {
+            self.connect(*a, *b);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::types::Field;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    type F = GoldilocksField;
+    type E = GoldilocksCubicParameters;
+    type SC = CurtaPoseidonGoldilocksConfig;
+    type C = PoseidonGoldilocksConfig;
+    const D: usize = 2;
+
+    /// The seconds of the zero time of Go, `0001-01-01T00:00:00Z`.
+    const GO_ZERO_TIME_SECONDS: i64 = -62135596800;
+
+    fn to_field_bytes(bytes: &[u8]) -> Vec<F> {
+        bytes.iter().map(|x| F::from_canonical_u8(*x)).collect()
+    }
+
+    /// A vote of the test vectors of CometBFT, with a zero timestamp and no block id.
+    fn test_vector_vote(vote_type: u8, height: i64, round: i64, chain_id: &str) -> CanonicalVote {
+        CanonicalVote {
+            vote_type,
+            height,
+            round,
+            block_id: None,
+            timestamp_seconds: GO_ZERO_TIME_SECONDS,
+            timestamp_nanos: 0,
+            chain_id: chain_id.to_string(),
+        }
+    }
+
+    /// A precommit for a block.
+    fn block_vote() -> CanonicalVote {
+        CanonicalVote {
+            vote_type: PRECOMMIT_TYPE,
+            height: 12_345_678,
+            round: 0,
+            block_id: Some(BlockId {
+                hash: [0xab; 32],
+                part_set_header: PartSetHeader {
+                    total: 1,
+                    hash: [0xcd; 32],
+                },
+            }),
+            timestamp_seconds: 1_700_000_000,
+            timestamp_nanos: 123_456_789,
+            chain_id: "cosmoshub-4".to_string(),
+        }
+    }
+
+    fn set_canonical_vote_target(
+        pw: &mut PartialWitness<F>,
+        target: &CanonicalVoteTarget,
+        vote: &CanonicalVote,
+    ) {
+        let block_id = vote.block_id.clone().unwrap_or(BlockId {
+            hash: [0; 32],
+            part_set_header: PartSetHeader {
+                total: 0,
+                hash: [0; 32],
+            },
+        });
+        pw.set_target_arr(&target.height, &to_field_bytes(&vote.height.to_le_bytes()));
+        pw.set_target_arr(&target.round, &to_field_bytes(&vote.round.to_le_bytes()));
+        pw.set_target_arr(&target.block_hash.0, &to_field_bytes(&block_id.hash));
+        pw.set_target_arr(
+            &target.part_set_total,
+            &to_field_bytes(&block_id.part_set_header.total.to_le_bytes()),
+        );
+        pw.set_target_arr(
+            &target.part_set_hash.0,
+            &to_field_bytes(&block_id.part_set_header.hash),
+        );
+        pw.set_target_arr(
+            &target.timestamp_seconds,
+            &to_field_bytes(&vote.timestamp_seconds.to_le_bytes()),
+        );
+        pw.set_target_arr(
+            &target.timestamp_nanos,
+            &to_field_bytes(&vote.timestamp_nanos.to_le_bytes()),
+        );
+    }
+
+    #[test]
+    fn test_sign_bytes_test_vectors() {
+        // The encoding of the zero time of Go.
+        let timestamp = [
+            0x2a, 0x0b, 0x08, 0x80, 0x92, 0xb8, 0xc3, 0x98, 0xfe, 0xff, 0xff, 0xff, 0x01,
+        ];
+        let height_and_round = [
+            0x11, 0x01, 0, 0, 0, 0, 0, 0, 0, 0x19, 0x01, 0, 0, 0, 0, 0, 0, 0,
+        ];
+
+        // All the fields but the timestamp are omitted.
+        let sign_bytes = test_vector_vote(0, 0, 0, "").sign_bytes();
+        assert_eq!(sign_bytes, [&[0x0d][..], &timestamp].concat());
+
+        let sign_bytes = test_vector_vote(PRECOMMIT_TYPE, 1, 1, "").sign_bytes();
+        let expected = [&[0x21, 0x08, 0x02][..], &height_and_round, &timestamp].concat();
+        assert_eq!(sign_bytes, expected);
+
+        let sign_bytes = test_vector_vote(0, 1, 1, "test_chain_id").sign_bytes();
+        let expected = [
+            &[0x2e][..],
+            &height_and_round,
+            &timestamp,
+            &[0x32, 0x0d],
+            b"test_chain_id",
+        ]
+        .concat();
+        assert_eq!(sign_bytes, expected);
+    }
+
+    #[test]
+    fn test_canonical_vote_sign_bytes() {
+        let votes = [
+            test_vector_vote(0, 1, 1, "test_chain_id"),
+            test_vector_vote(PREVOTE_TYPE, 0, 0, ""),
+            block_vote(),
+        ];
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let mut targets = Vec::new();
+        for vote in votes.iter() {
+            let target = builder.add_virtual_canonical_vote();
+            let sign_bytes = builder.canonical_vote_sign_bytes(&target, &vote.layout());
+            let expected = builder.add_virtual_targets(sign_bytes.len());
+            for (a, b) in sign_bytes.iter().zip(expected.iter()) {
+                builder.connect(*a, *b);
+            }
+            targets.push((target, expected));
+        }
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+        for (vote, (target, expected)) in votes.iter().zip(targets.iter()) {
+            set_canonical_vote_target(&mut pw, target, vote);
+            pw.set_target_arr(expected, &to_field_bytes(&vote.sign_bytes()));
+        }
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_canonical_vote_sign_bytes_invalid_layout() {
+        // The nanoseconds of the vote do not fit in the varint of the layout.
+        let vote = block_vote();
+        let mut layout = vote.layout();
+        layout.nanos_len -= 1;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let target = builder.add_virtual_canonical_vote();
+        builder.canonical_vote_sign_bytes(&target, &layout);
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+        set_canonical_vote_target(&mut pw, &target, &vote);
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+
+    #[test]
+    fn test_canonical_vote_hash() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let vote = block_vote();
+        let sign_bytes = vote.sign_bytes();
+        assert_eq!(SHA256Gadget::pad(&sign_bytes).len(), 128);
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let mut gadget = SHA256Builder::<F, E, D>::init_sha256(&mut builder);
+
+        let target = builder.add_virtual_canonical_vote();
+        let hash = CurtaBytes(builder.add_virtual_target_arr::<32>());
+        builder.verify_canonical_vote_hash::<E, 128>(&target, &vote.layout(), &hash, &mut gadget);
+
+        // The SHA256 gadget processes a fixed number of 1024 chunks, two for the vote.
+        let dummy_messages = (0..1024 - 2)
+            .map(|_| CurtaBytes(builder.add_virtual_target_arr::<64>()))
+            .collect::<Vec<_>>();
+        for message in dummy_messages.iter() {
+            builder.sha256(message, &mut gadget);
+        }
+        builder.constrain_sha256_gadget::<SC>(gadget);
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+        set_canonical_vote_target(&mut pw, &target, &vote);
+        pw.set_target_arr(&hash.0, &to_field_bytes(&SHA256Gadget::hash(&sign_bytes)));
+
+        let dummy_padded_message = to_field_bytes(&SHA256Gadget::pad(b""));
+        for message in dummy_messages.iter() {
+            pw.set_target_arr(&message.0, &dummy_padded_message);
+        }
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+}
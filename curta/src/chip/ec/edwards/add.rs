@@ -8,6 +8,7 @@ use crate::chip::field::inner_product::FpInnerProductInstruction;
 use crate::chip::field::instruction::FromFieldInstruction;
 use crate::chip::field::mul::FpMulInstruction;
 use crate::chip::field::mul_const::FpMulConstInstruction;
+use crate::chip::register::bit::BitRegister;
 use crate::chip::trace::writer::TraceWriter;
 use crate::chip::AirParameters;
 use crate::math::prelude::*;
@@ -99,6 +100,83 @@ impl<L: AirParameters> AirBuilder<L> {
     {
         self.ed_add(p, p)
     }
+
+    /// Sums the points in `points` by chaining `ed_add`. The sum of no points is the neutral
+    /// element, and the sum of a single point is the point itself, without any new constraints.
+    pub fn ed_sum_points<E: EdwardsParameters>(
+        &mut self,
+        points: &[AffinePointRegister<E>],
+    ) -> AffinePointRegister<E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        match points.split_first() {
+            None => self.ed_neutral(),
+            Some((first, rest)) => rest
+                .iter()
+                .fold(*first, |sum, point| self.ed_add(&sum, point).result),
+        }
+    }
+
+    /// Allocates a point that is equal to the neutral element in every row.
+    pub fn ed_neutral<E: EdwardsParameters>(&mut self) -> AffinePointRegister<E> {
+        let neutral = E::neutral();
+        let x = self.alloc_constant_field_register::<E::BaseField>(&neutral.x);
+        let y = self.alloc_constant_field_register::<E::BaseField>(&neutral.y);
+        AffinePointRegister::new(x, y)
+    }
+
+    /// Selects the keys whose bit in `bitmap` is set, replacing the others by the neutral
+    /// element so that the selection has the same length as `keys`.
+    pub fn ed_select_by_bitmap<E: EdwardsParameters>(
+        &mut self,
+        keys: &[AffinePointRegister<E>],
+        bitmap: &[BitRegister],
+    ) -> Vec<AffinePointRegister<E>>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        assert_eq!(
+            keys.len(),
+            bitmap.len(),
+            "The bitmap must have one bit per key"
+        );
+        let neutral = self.ed_neutral();
+        keys.iter()
+            .zip(bitmap.iter())
+            .map(|(key, bit)| self.select_point(bit, key, &neutral))
+            .collect()
+    }
+
+    /// Sums the keys whose bit in `bitmap` is set, i.e. the aggregate key of the signers.
+    pub fn ed_sum_by_bitmap<E: EdwardsParameters>(
+        &mut self,
+        keys: &[AffinePointRegister<E>],
+        bitmap: &[BitRegister],
+    ) -> AffinePointRegister<E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        let selected = self.ed_select_by_bitmap(keys, bitmap);
+        self.ed_sum_points(&selected)
+    }
+
+    /// Asserts that `p` satisfies the curve equation `-x^2 + y^2 = 1 + d * x^2 * y^2` in every
+    /// row.
+    pub fn ed_assert_on_curve<E: EdwardsParameters>(&mut self, p: &AffinePointRegister<E>)
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        let xx = self.fp_mul(&p.x, &p.x).result;
+        let yy = self.fp_mul(&p.y, &p.y).result;
+        let xx_yy = self.fp_mul(&xx, &yy).result;
+        let d_xx_yy = self.fp_mul_const(&xx_yy, E::D).result;
+
+        let lhs = self.fp_sub(&yy, &xx);
+        let one = self.alloc_constant_field_register::<E::BaseField>(&E::neutral().y);
+        let rhs = self.fp_add(&one, &d_xx_yy);
+        self.assert_equal(&lhs, &rhs);
+    }
 }
 
 impl<F: PrimeField64> TraceWriter<F> {
@@ -181,4 +259,142 @@ mod tests {
         // Test the recursive proof.
         test_recursive_starky(stark, config, generator, &[]);
     }
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    pub struct Ed25519SumTest;
+
+    impl AirParameters for Ed25519SumTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 2368;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 3561;
+        type Instruction = FpInstruction<Ed25519BaseField>;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    #[test]
+    fn test_ed25519_sum_points() {
+        type L = Ed25519SumTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type E = Ed25519;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let points = (0..4)
+            .map(|_| builder.alloc_ec_point())
+            .collect::<Vec<AffinePointRegister<E>>>();
+        let sum = builder.ed_sum_points(&points);
+        let empty_sum = builder.ed_sum_points::<E>(&[]);
+        let singleton_sum = builder.ed_sum_points(&points[..1]);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let base = E::generator();
+        let mut rng = thread_rng();
+        let multiples = (0..16)
+            .map(|_| &base * &rng.gen_biguint(256))
+            .collect::<Vec<_>>();
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            let writer = generator.new_writer();
+            let values = [0, 1, 3, 7]
+                .iter()
+                .map(|offset| multiples[(i + offset) % 16].clone())
+                .collect::<Vec<_>>();
+            for (point, value) in points.iter().zip(values.iter()) {
+                writer.write_ec_point(point, value, i);
+            }
+            writer.write_row_instructions(&generator.air_data, i);
+
+            let expected = &(&(&values[0] + &values[1]) + &values[2]) + &values[3];
+            assert_eq!(writer.read_ec_point(&sum, i), expected);
+            assert_eq!(writer.read_ec_point(&empty_sum, i), E::neutral());
+            assert_eq!(writer.read_ec_point(&singleton_sum, i), values[0]);
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    pub struct Ed25519BitmapSumTest;
+
+    impl AirParameters for Ed25519BitmapSumTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 2496;
+        const NUM_FREE_COLUMNS: usize = 6;
+        const EXTENDED_COLUMNS: usize = 3753;
+        type Instruction = FpInstruction<Ed25519BaseField>;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    #[test]
+    fn test_ed25519_sum_by_bitmap() {
+        type F = GoldilocksField;
+        type L = Ed25519BitmapSumTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type E = Ed25519;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let keys = (0..4)
+            .map(|_| builder.alloc_ec_point())
+            .collect::<Vec<AffinePointRegister<E>>>();
+        let bitmap = (0..4)
+            .map(|_| builder.alloc::<BitRegister>())
+            .collect::<Vec<_>>();
+        let aggregate = builder.ed_sum_by_bitmap(&keys, &bitmap);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let base = E::generator();
+        let mut rng = thread_rng();
+        let key_values = (0..4)
+            .map(|_| &base * &rng.gen_biguint(256))
+            .collect::<Vec<_>>();
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            let writer = generator.new_writer();
+            // Run through all the bitmaps of four keys.
+            let bits = (0..4).map(|j| (i >> j) & 1 == 1).collect::<Vec<_>>();
+            for (key, value) in keys.iter().zip(key_values.iter()) {
+                writer.write_ec_point(key, value, i);
+            }
+            for (bit, value) in bitmap.iter().zip(bits.iter()) {
+                writer.write(bit, &F::from_canonical_u8(*value as u8), i);
+            }
+            writer.write_row_instructions(&generator.air_data, i);
+
+            let expected = key_values
+                .iter()
+                .zip(bits.iter())
+                .filter(|(_, bit)| **bit)
+                .fold(E::neutral(), |sum, (value, _)| &sum + value);
+            assert_eq!(writer.read_ec_point(&aggregate, i), expected);
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
 }
@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+
+use super::gadget::EdMultiScalarMulGadget;
+use crate::chip::builder::{AirBuilder, AirTraceData};
+use crate::chip::ec::edwards::ed25519::{Ed25519, Ed25519BaseField};
+use crate::chip::ec::gadget::EllipticCurveGadget;
+use crate::chip::ec::point::AffinePointRegister;
+use crate::chip::field::instruction::FpInstruction;
+use crate::chip::instruction::set::AirInstruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::table::evaluation::Digest;
+use crate::chip::{AirParameters, Chip};
+use crate::math::prelude::*;
+
+/// The number of bits of the scalars of the batch verification equation.
+pub const BATCH_VERIFY_SCALAR_BITS: usize = 512;
+
+/// The number of 32-bit chunks of a scalar.
+pub const BATCH_VERIFY_SCALAR_CHUNKS: usize = BATCH_VERIFY_SCALAR_BITS / 32;
+
+const ED_ADD_ARITHMETIC_COLUMNS: usize = 736;
+
+const ED_ON_CURVE_ARITHMETIC_COLUMNS: usize = 568;
+
+/// The air for the batch verification of `N` Ed25519 signatures. The air computes the
+/// multi-scalar multiplication of `2 * N + 1` public points by public scalars, and asserts that
+/// the points `R_i` and `A_i` are on the curve.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ed25519BatchVerify<F: PrimeField64, E: CubicParameters<F>, const N: usize>(
+    core::marker::PhantomData<(F, E)>,
+);
+
+impl<F: PrimeField64, E: CubicParameters<F>, const N: usize> AirParameters
+    for Ed25519BatchVerify<F, E, N>
+{
+    type Field = F;
+    type CubicParams = E;
+
+    const NUM_ARITHMETIC_COLUMNS: usize = ED_ADD_ARITHMETIC_COLUMNS * (2 * N + 2)
+        + ED_ON_CURVE_ARITHMETIC_COLUMNS * 2 * N
+        + 32 * (2 * N + 1);
+    const NUM_FREE_COLUMNS: usize = 64 + 40 * (2 * N + 1);
+    const EXTENDED_COLUMNS: usize = 3 * Self::NUM_ARITHMETIC_COLUMNS / 2 + 9 + 16 * (2 * N + 1);
+    type Instruction = FpInstruction<Ed25519BaseField>;
+
+    fn num_rows_bits() -> usize {
+        9
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Ed25519BatchVerifyLayout<F: PrimeField64> {
+    pub msm: EdMultiScalarMulGadget<Ed25519>,
+    /// The public points of the multi-scalar multiplication.
+    pub points: Vec<AffinePointRegister<Ed25519>>,
+    /// The public scalars, as 32-bit chunks of their bits in the order of the rows.
+    pub scalar_chunks: Vec<ArrayRegister<ElementRegister>>,
+    /// The public result of the multi-scalar multiplication.
+    pub result: AffinePointRegister<Ed25519>,
+    /// The `(set_last, set_bit)` instructions of the bit evaluation of every scalar.
+    pub bit_instructions: Vec<(
+        AirInstruction<F, FpInstruction<Ed25519BaseField>>,
+        AirInstruction<F, FpInstruction<Ed25519BaseField>>,
+    )>,
+}
+
+impl<F: PrimeField64, E: CubicParameters<F>, const N: usize> Ed25519BatchVerify<F, E, N> {
+    /// The number of points of the multi-scalar multiplication.
+    pub const NUM_TERMS: usize = 2 * N + 1;
+
+    pub fn air() -> (Chip<Self>, AirTraceData<Self>, Ed25519BatchVerifyLayout<F>) {
+        let mut builder = AirBuilder::<Self>::new();
+
+        let trace_points = (0..Self::NUM_TERMS)
+            .map(|_| builder.alloc_unchecked_ec_point())
+            .collect::<Vec<_>>();
+        let bits = (0..Self::NUM_TERMS)
+            .map(|_| builder.alloc::<BitRegister>())
+            .collect::<Vec<_>>();
+        let msm = builder.ed_multi_scalar_mul::<Ed25519>(&bits, &trace_points);
+
+        // The first point is the constant `-B`, the others are given by the prover.
+        for point in trace_points[1..].iter() {
+            builder.ed_assert_on_curve(point);
+        }
+
+        // The points of the trace are the public points.
+        let points = (0..Self::NUM_TERMS)
+            .map(|_| builder.alloc_public_ec_point())
+            .collect::<Vec<AffinePointRegister<Ed25519>>>();
+        for (trace_point, point) in trace_points.iter().zip(points.iter()) {
+            builder.assert_equal_first_row(&trace_point.x, &point.x);
+            builder.assert_equal_first_row(&trace_point.y, &point.y);
+        }
+
+        // The bits of every scalar are those of its public chunks.
+        let scalar_chunks = (0..Self::NUM_TERMS)
+            .map(|_| builder.alloc_array_public::<ElementRegister>(BATCH_VERIFY_SCALAR_CHUNKS))
+            .collect::<Vec<_>>();
+        let bit_instructions = bits
+            .iter()
+            .zip(scalar_chunks.iter())
+            .map(|(bit, chunks)| {
+                let digest = Digest::from_values(chunks.iter());
+                let (_, set_last, set_bit) = builder.bit_evaluation(bit, digest);
+                (set_last, set_bit)
+            })
+            .collect::<Vec<_>>();
+
+        // The result of the multi-scalar multiplication on the last row is public.
+        let result = builder.alloc_public_ec_point();
+        builder.assert_equal_last_row(&msm.result.x, &result.x);
+        builder.assert_equal_last_row(&msm.result.y, &result.y);
+
+        let (air, trace_data) = builder.build();
+
+        (
+            air,
+            trace_data,
+            Ed25519BatchVerifyLayout {
+                msm,
+                points,
+                scalar_chunks,
+                result,
+                bit_instructions,
+            },
+        )
+    }
+}
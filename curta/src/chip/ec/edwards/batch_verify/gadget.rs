@@ -0,0 +1,183 @@
+use serde::{Deserialize, Serialize};
+
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::builder::AirBuilder;
+use crate::chip::ec::edwards::add::EdAddGadget;
+use crate::chip::ec::edwards::EdwardsParameters;
+use crate::chip::ec::gadget::EllipticCurveGadget;
+use crate::chip::ec::point::AffinePointRegister;
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::Register;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(dead_code)]
+pub struct EdMultiScalarMulGadget<E: EdwardsParameters> {
+    /// The scalar bits, one register per point, most significant bit first.
+    pub bits: Vec<BitRegister>,
+    /// The points, written in the first row and copied over to every other row.
+    pub points: Vec<AffinePointRegister<E>>,
+    pub accumulator: AffinePointRegister<E>,
+    /// The value of the accumulator after the step of the current row.
+    pub result: AffinePointRegister<E>,
+    double_gadget: EdAddGadget<E>,
+    add_gadgets: Vec<EdAddGadget<E>>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Computes the multi-scalar multiplication `sum_j scalar_j * point_j` with a shared
+    /// double-and-add (Straus' method). Every row computes one step of the algorithm:
+    ///
+    /// result = 2 * accumulator + sum_j bit_j * point_j
+    ///
+    /// and passes `result` to the accumulator of the next row, so that with the scalar bits
+    /// given most significant bit first, the result on the last row is the sum of the products.
+    pub fn ed_multi_scalar_mul<E: EdwardsParameters>(
+        &mut self,
+        bits: &[BitRegister],
+        points: &[AffinePointRegister<E>],
+    ) -> EdMultiScalarMulGadget<E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        assert_eq!(bits.len(), points.len(), "One scalar is needed per point");
+
+        // The accumulator starts at the neutral element (0, 1).
+        let accumulator = self.alloc_unchecked_ec_point();
+        let nb_limbs = E::BaseField::NB_LIMBS;
+        let mut one_limbs = vec![L::Field::ZERO; nb_limbs];
+        one_limbs[0] = L::Field::ONE;
+        self.set_to_expression_first_row(
+            &accumulator.x,
+            ArithmeticExpression::from_constant_vec(vec![L::Field::ZERO; nb_limbs]),
+        );
+        self.set_to_expression_first_row(
+            &accumulator.y,
+            ArithmeticExpression::from_constant_vec(one_limbs),
+        );
+
+        // result = 2 * accumulator.
+        let double_gadget = self.ed_double(&accumulator);
+        let mut result = double_gadget.result;
+
+        // result = if bit_j == 1 then result + point_j else result.
+        let mut add_gadgets = Vec::with_capacity(points.len());
+        for (bit, point) in bits.iter().zip(points.iter()) {
+            let add_gadget = self.ed_add(&result, point);
+            result = self.select_point(bit, &add_gadget.result, &result);
+            add_gadgets.push(add_gadget);
+        }
+
+        // accumulator[NEXT] <= result[LOCAL], and the points are the same in every row.
+        self.set_to_expression_transition(&accumulator.x.next(), result.x.expr());
+        self.set_to_expression_transition(&accumulator.y.next(), result.y.expr());
+        for point in points.iter() {
+            self.set_to_expression_transition(&point.x.next(), point.x.expr());
+            self.set_to_expression_transition(&point.y.next(), point.y.expr());
+        }
+
+        EdMultiScalarMulGadget {
+            bits: bits.to_vec(),
+            points: points.to_vec(),
+            accumulator,
+            result,
+            double_gadget,
+            add_gadgets,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::ec::edwards::ed25519::{Ed25519, Ed25519BaseField};
+    use crate::chip::ec::gadget::EllipticCurveWriter;
+    use crate::chip::ec::point::AffinePoint;
+    use crate::chip::field::instruction::FpInstruction;
+    use crate::chip::utils::biguint_to_bits_le;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    pub struct MultiScalarMulTest;
+
+    impl AirParameters for MultiScalarMulTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 2944;
+        const NUM_FREE_COLUMNS: usize = 136;
+        const EXTENDED_COLUMNS: usize = 4425;
+        type Instruction = FpInstruction<Ed25519BaseField>;
+
+        fn num_rows_bits() -> usize {
+            8
+        }
+    }
+
+    #[test]
+    fn test_ed_multi_scalar_mul() {
+        type F = GoldilocksField;
+        type L = MultiScalarMulTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type E = Ed25519;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let points = (0..3)
+            .map(|_| builder.alloc_unchecked_ec_point())
+            .collect::<Vec<AffinePointRegister<E>>>();
+        let bits = builder.alloc_array::<BitRegister>(3);
+        let gadget = builder.ed_multi_scalar_mul(&bits.iter().collect::<Vec<_>>(), &points);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+        let writer = generator.new_writer();
+
+        let mut rng = thread_rng();
+        let point_values = (0..3)
+            .map(|_| E::generator() * rng.gen_biguint(256))
+            .collect::<Vec<_>>();
+        let scalars = (0..3)
+            .map(|_| rng.gen_biguint(L::num_rows() as u64))
+            .collect::<Vec<_>>();
+        let scalar_bits = scalars
+            .iter()
+            .map(|s| biguint_to_bits_le(s, L::num_rows()))
+            .collect::<Vec<_>>();
+
+        for (point, value) in points.iter().zip(point_values.iter()) {
+            writer.write_ec_point(point, value, 0);
+        }
+        for i in 0..L::num_rows() {
+            for (bit, bit_values) in bits.iter().zip(scalar_bits.iter()) {
+                let value = bit_values[L::num_rows() - 1 - i];
+                writer.write(&bit, &F::from_canonical_u8(value as u8), i);
+            }
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+
+        let expected = point_values
+            .iter()
+            .zip(scalars.iter())
+            .fold(E::neutral(), |acc, (point, scalar)| {
+                &acc + &(point * scalar)
+            });
+        let result: AffinePoint<E> = writer.read_ec_point(&gadget.result, L::num_rows() - 1);
+        assert_eq!(result, expected);
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+}
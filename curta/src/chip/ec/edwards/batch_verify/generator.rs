@@ -0,0 +1,547 @@
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::hash::poseidon::PoseidonHash;
+use plonky2::iop::generator::{GeneratedValues, SimpleGenerator};
+use plonky2::iop::target::{BoolTarget, Target};
+use plonky2::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::circuit_data::CommonCircuitData;
+use serde::{Deserialize, Serialize};
+
+use super::air::{
+    Ed25519BatchVerify, Ed25519BatchVerifyLayout, BATCH_VERIFY_SCALAR_BITS,
+    BATCH_VERIFY_SCALAR_CHUNKS,
+};
+use crate::chip::ec::edwards::ed25519::Ed25519;
+use crate::chip::ec::edwards::scalar_mul::generator::{AffinePointTarget, ScalarMulEd25519Gadget};
+use crate::chip::ec::edwards::EdwardsParameters;
+use crate::chip::ec::gadget::EllipticCurveWriter;
+use crate::chip::ec::point::AffinePoint;
+use crate::chip::register::memory::MemorySlice;
+use crate::chip::register::RegisterSerializable;
+use crate::chip::trace::generator::ArithmeticGenerator;
+use crate::chip::utils::{biguint_to_16_digits_field, field_limbs_to_biguint};
+use crate::chip::AirParameters;
+use crate::math::extension::CubicParameters;
+use crate::math::prelude::*;
+use crate::plonky2::stark::config::{CurtaConfig, StarkyConfig};
+use crate::plonky2::stark::gadget::StarkGadget;
+use crate::plonky2::stark::generator::simple::SimpleStarkWitnessGenerator;
+use crate::plonky2::stark::Starky;
+use crate::utils::serde::{BufferRead, BufferWrite};
+
+/// The number of bits of the randomizers.
+const RANDOMIZER_BITS: usize = 64;
+
+/// The number of bits of the carry of a column of a scalar product. The columns of the products
+/// of `N` pairs of 16-bit limbs fit in `34 + log2(N)` bits, which leaves room for batches of up
+/// to `2^20` signatures.
+const CARRY_BITS: usize = 40;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ed25519BatchEntryTarget {
+    pub pubkey: AffinePointTarget,
+    pub sig_r: AffinePointTarget,
+    /// The scalar `s` of the signature, as 8 little-endian 32-bit limbs.
+    pub sig_s: Vec<Target>,
+    /// The challenge `h = SHA-512(R || A || M) mod l` of the message, as 8 little-endian 32-bit
+    /// limbs.
+    pub challenge: Vec<Target>,
+}
+
+pub trait Ed25519BatchVerifyGadget<F: RichField + Extendable<D>, const D: usize> {
+    fn add_virtual_ed25519_batch_entry(&mut self) -> Ed25519BatchEntryTarget;
+
+    /// Derives one randomizer per entry by hashing all the entries of the batch.
+    fn ed25519_batch_randomizers(&mut self, entries: &[Ed25519BatchEntryTarget]) -> Vec<Target>;
+
+    /// Checks the batch equation
+    ///
+    /// sum_i (z_i * s_i) * B = sum_i z_i * R_i + sum_i (z_i * h_i) * A_i
+    ///
+    /// for the randomizers `z_i`, and returns a target that is one if the equation holds and zero
+    /// otherwise. The products of scalars are computed over the integers, so the equation holds
+    /// exactly when `sum_i z_i * (s_i * B - R_i - h_i * A_i)` is the neutral element.
+    fn batch_verify_ed25519<
+        E: CubicParameters<F>,
+        C: CurtaConfig<D, F = F, FE = F::Extension>,
+        const N: usize,
+    >(
+        &mut self,
+        entries: &[Ed25519BatchEntryTarget],
+        randomizers: &[Target],
+    ) -> Target;
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Ed25519BatchVerifyGadget<F, D>
+    for CircuitBuilder<F, D>
+{
+    fn add_virtual_ed25519_batch_entry(&mut self) -> Ed25519BatchEntryTarget {
+        Ed25519BatchEntryTarget {
+            pubkey: AffinePointTarget {
+                x: self.add_virtual_target_arr(),
+                y: self.add_virtual_target_arr(),
+            },
+            sig_r: AffinePointTarget {
+                x: self.add_virtual_target_arr(),
+                y: self.add_virtual_target_arr(),
+            },
+            sig_s: self.add_virtual_targets(8),
+            challenge: self.add_virtual_targets(8),
+        }
+    }
+
+    fn ed25519_batch_randomizers(&mut self, entries: &[Ed25519BatchEntryTarget]) -> Vec<Target> {
+        let inputs = entries
+            .iter()
+            .flat_map(|entry| {
+                entry
+                    .pubkey
+                    .x
+                    .iter()
+                    .chain(entry.pubkey.y.iter())
+                    .chain(entry.sig_r.x.iter())
+                    .chain(entry.sig_r.y.iter())
+                    .chain(entry.sig_s.iter())
+                    .chain(entry.challenge.iter())
+                    .copied()
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        self.hash_n_to_m_no_pad::<PoseidonHash>(inputs, entries.len())
+    }
+
+    fn batch_verify_ed25519<
+        E: CubicParameters<F>,
+        C: CurtaConfig<D, F = F, FE = F::Extension>,
+        const N: usize,
+    >(
+        &mut self,
+        entries: &[Ed25519BatchEntryTarget],
+        randomizers: &[Target],
+    ) -> Target {
+        assert_eq!(entries.len(), N, "Expected a batch of {} signatures", N);
+        assert_eq!(
+            randomizers.len(),
+            N,
+            "Expected one randomizer per signature"
+        );
+
+        let (air, trace_data, layout) = Ed25519BatchVerify::<F, E, N>::air();
+
+        // The scalars of the multi-scalar multiplication, in bits.
+        let mut randomizer_bits = Vec::with_capacity(N);
+        let mut randomizer_limbs = Vec::with_capacity(N);
+        for z in randomizers.iter() {
+            let bits = self.split_le(*z, RANDOMIZER_BITS);
+            randomizer_limbs.push(bits_to_u16_limbs(self, &bits));
+            randomizer_bits.push(bits);
+        }
+
+        // sum_i z_i * s_i
+        let mut sum_columns = vec![self.zero(); 16 + RANDOMIZER_BITS / 16 - 1];
+        for (entry, z_limbs) in entries.iter().zip(randomizer_limbs.iter()) {
+            let s_limbs = u32_limbs_to_u16_limbs(self, &entry.sig_s);
+            let columns = product_columns(self, z_limbs, &s_limbs);
+            for (sum, column) in sum_columns.iter_mut().zip(columns) {
+                *sum = self.add(*sum, column);
+            }
+        }
+        let mut scalar_bits = vec![columns_to_bits(self, &sum_columns)];
+
+        // z_i
+        scalar_bits.extend(randomizer_bits);
+
+        // z_i * h_i
+        for (entry, z_limbs) in entries.iter().zip(randomizer_limbs.iter()) {
+            let h_limbs = u32_limbs_to_u16_limbs(self, &entry.challenge);
+            let columns = product_columns(self, z_limbs, &h_limbs);
+            scalar_bits.push(columns_to_bits(self, &columns));
+        }
+
+        let scalar_chunks = scalar_bits
+            .iter()
+            .map(|bits| bits_to_row_chunks(self, bits))
+            .collect::<Vec<_>>();
+
+        // The points matching the scalars: -B, R_i and A_i.
+        let mut points = vec![self.constant_affine_point(-Ed25519::generator())];
+        points.extend(entries.iter().map(|entry| entry.sig_r));
+        points.extend(entries.iter().map(|entry| entry.pubkey));
+
+        let result = AffinePointTarget {
+            x: self.add_virtual_target_arr(),
+            y: self.add_virtual_target_arr(),
+        };
+
+        let mut bindings: Vec<(MemorySlice, &[Target])> = Vec::new();
+        for (register, point) in layout.points.iter().zip(points.iter()) {
+            bindings.push((*register.x.register(), &point.x[..]));
+            bindings.push((*register.y.register(), &point.y[..]));
+        }
+        for (register, chunks) in layout.scalar_chunks.iter().zip(scalar_chunks.iter()) {
+            bindings.push((*register.register(), &chunks[..]));
+        }
+        bindings.push((*layout.result.x.register(), &result.x[..]));
+        bindings.push((*layout.result.y.register(), &result.y[..]));
+        let public_input_target = air.bind_public_inputs(&bindings);
+
+        let stark = Starky::new(air);
+        let config =
+            StarkyConfig::<C, D>::standard_fast_config(Ed25519BatchVerify::<F, E, N>::num_rows());
+        let virtual_proof = self.add_virtual_stark_proof(&stark, &config);
+        self.verify_stark_proof(&config, &stark, &virtual_proof, &public_input_target);
+
+        let trace_generator = ArithmeticGenerator::<Ed25519BatchVerify<F, E, N>>::new(trace_data);
+
+        let stark_generator = SimpleStarkWitnessGenerator::new(
+            config,
+            stark,
+            virtual_proof,
+            public_input_target,
+            trace_generator.clone(),
+        );
+
+        let generator = Ed25519BatchVerifyGenerator::<F, E, N, D> {
+            layout,
+            points,
+            scalar_chunks,
+            result,
+            trace_generator,
+        };
+
+        self.add_simple_generator(generator);
+        self.add_simple_generator(stark_generator);
+
+        // The batch is valid if the result is the neutral element (0, 1).
+        let zero = self.zero();
+        let one = self.one();
+        let mut is_valid = self._true();
+        for (i, (x, y)) in result.x.iter().zip(result.y.iter()).enumerate() {
+            let x_is_zero = self.is_equal(*x, zero);
+            let y_expected = if i == 0 { one } else { zero };
+            let y_is_expected = self.is_equal(*y, y_expected);
+            is_valid = self.and(is_valid, x_is_zero);
+            is_valid = self.and(is_valid, y_is_expected);
+        }
+        is_valid.target
+    }
+}
+
+/// Splits 32-bit limbs into 16-bit limbs, range-checking them.
+fn u32_limbs_to_u16_limbs<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    limbs: &[Target],
+) -> Vec<Target> {
+    let mut u16_limbs = Vec::with_capacity(2 * limbs.len());
+    for limb in limbs.iter() {
+        let bits = builder.split_le(*limb, 32);
+        u16_limbs.extend(bits_to_u16_limbs(builder, &bits));
+    }
+    u16_limbs
+}
+
+pub(crate) fn bits_to_u16_limbs<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    bits: &[BoolTarget],
+) -> Vec<Target> {
+    bits.chunks(16)
+        .map(|chunk| builder.le_sum(chunk.iter()))
+        .collect()
+}
+
+/// The columns `sum_{i + j = k} a_i * b_j` of the product of two integers in 16-bit limbs.
+pub(crate) fn product_columns<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    a: &[Target],
+    b: &[Target],
+) -> Vec<Target> {
+    let mut columns = vec![builder.zero(); a.len() + b.len() - 1];
+    for (i, a_i) in a.iter().enumerate() {
+        for (j, b_j) in b.iter().enumerate() {
+            columns[i + j] = builder.mul_add(*a_i, *b_j, columns[i + j]);
+        }
+    }
+    columns
+}
+
+/// Propagates the carries of the columns of a product, returning the little-endian bits of the
+/// integer `sum_k columns[k] * 2^(16 * k)`.
+pub(crate) fn columns_to_bits<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    columns: &[Target],
+) -> Vec<BoolTarget> {
+    let mut bits = Vec::with_capacity(16 * columns.len() + CARRY_BITS);
+    let mut carry = builder.zero();
+    for column in columns.iter() {
+        let value = builder.add(*column, carry);
+        let value_bits = builder.split_le(value, 16 + CARRY_BITS);
+        bits.extend_from_slice(&value_bits[..16]);
+        carry = builder.le_sum(value_bits[16..].iter());
+    }
+    bits.extend(builder.split_le(carry, CARRY_BITS));
+    bits
+}
+
+/// Packs the little-endian bits of a scalar into the 32-bit chunks of its bits in the order of the
+/// rows of the air, most significant bit first.
+fn bits_to_row_chunks<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    bits: &[BoolTarget],
+) -> Vec<Target> {
+    assert!(
+        bits.len() <= BATCH_VERIFY_SCALAR_BITS,
+        "Scalar does not fit in {} bits",
+        BATCH_VERIFY_SCALAR_BITS
+    );
+    let mut padded_bits = bits.to_vec();
+    padded_bits.resize(BATCH_VERIFY_SCALAR_BITS, builder._false());
+    padded_bits.reverse();
+
+    (0..BATCH_VERIFY_SCALAR_CHUNKS)
+        .map(|k| builder.le_sum(padded_bits[32 * k..32 * (k + 1)].iter()))
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Ed25519BatchVerifyGenerator<
+    F: RichField + Extendable<D>,
+    E: CubicParameters<F>,
+    const N: usize,
+    const D: usize,
+> {
+    layout: Ed25519BatchVerifyLayout<F>,
+    points: Vec<AffinePointTarget>,
+    scalar_chunks: Vec<Vec<Target>>,
+    result: AffinePointTarget,
+    trace_generator: ArithmeticGenerator<Ed25519BatchVerify<F, E, N>>,
+}
+
+impl<F: RichField + Extendable<D>, E: CubicParameters<F>, const N: usize, const D: usize>
+    Ed25519BatchVerifyGenerator<F, E, N, D>
+{
+    pub fn id() -> String {
+        format!("Ed25519BatchVerifyGenerator, N = {}", N)
+    }
+}
+
+impl<F: RichField + Extendable<D>, E: CubicParameters<F>, const N: usize, const D: usize>
+    SimpleGenerator<F, D> for Ed25519BatchVerifyGenerator<F, E, N, D>
+{
+    fn id(&self) -> String {
+        Self::id()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        self.points
+            .iter()
+            .flat_map(|point| [point.x.to_vec(), point.y.to_vec()].into_iter().flatten())
+            .chain(self.scalar_chunks.iter().flatten().copied())
+            .collect()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let points = self
+            .points
+            .iter()
+            .map(|point| {
+                let x = field_limbs_to_biguint(&witness.get_targets(&point.x));
+                let y = field_limbs_to_biguint(&witness.get_targets(&point.y));
+                AffinePoint::<Ed25519>::new(x, y)
+            })
+            .collect::<Vec<_>>();
+
+        let scalar_chunks = self
+            .scalar_chunks
+            .iter()
+            .map(|chunks| {
+                chunks
+                    .iter()
+                    .map(|chunk| F::as_canonical_u64(&witness.get_target(*chunk)) as u32)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        // Generate the trace
+        let trace_generator = &self.trace_generator;
+        let writer = trace_generator.new_writer();
+        let msm = &self.layout.msm;
+        let num_rows = Ed25519BatchVerify::<F, E, N>::num_rows();
+
+        for (register, point) in msm.points.iter().zip(points.iter()) {
+            writer.write_ec_point(register, point, 0);
+        }
+        for row in 0..num_rows {
+            let (chunk_index, bit_index) = (row / 32, row % 32);
+            for (bit, chunks) in msm.bits.iter().zip(scalar_chunks.iter()) {
+                let value = (chunks[chunk_index] >> bit_index) & 1;
+                writer.write(bit, &F::from_canonical_u32(value), row);
+            }
+            writer.write_row_instructions(&trace_generator.air_data, row);
+        }
+        for row in (0..num_rows).rev() {
+            for (set_last, set_bit) in self.layout.bit_instructions.iter() {
+                writer.write_instruction(set_last, row);
+                writer.write_instruction(set_bit, row);
+            }
+        }
+
+        let result = writer.read_ec_point(&msm.result, num_rows - 1);
+        let res_limbs_x: [_; 16] = biguint_to_16_digits_field(&result.x, 16)
+            .try_into()
+            .unwrap();
+        let res_limbs_y: [_; 16] = biguint_to_16_digits_field(&result.y, 16)
+            .try_into()
+            .unwrap();
+        out_buffer.set_target_arr(&self.result.x, &res_limbs_x);
+        out_buffer.set_target_arr(&self.result.y, &res_limbs_y);
+    }
+
+    fn serialize(
+        &self,
+        dst: &mut Vec<u8>,
+        _common_data: &CommonCircuitData<F, D>,
+    ) -> plonky2::util::serialization::IoResult<()> {
+        let data = bincode::serialize(&self).unwrap();
+        dst.write_bytes(&data)
+    }
+
+    fn deserialize(
+        src: &mut plonky2::util::serialization::Buffer,
+        _common_data: &CommonCircuitData<F, D>,
+    ) -> plonky2::util::serialization::IoResult<Self> {
+        let bytes = src.read_bytes()?;
+        let data = bincode::deserialize(&bytes).unwrap();
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use num::BigUint;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::iop::witness::PartialWitness;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+    use crate::chip::hash::sha::sha512::SHA512Gadget;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    const N: usize = 3;
+
+    /// The compressed encoding of a point: the little-endian bytes of `y`, with the sign of `x`
+    /// in the most significant bit.
+    fn encode_point(point: &AffinePoint<Ed25519>) -> Vec<u8> {
+        let mut bytes = point.y.to_bytes_le();
+        bytes.resize(32, 0);
+        if point.x.bit(0) {
+            bytes[31] |= 0x80;
+        }
+        bytes
+    }
+
+    /// Signs random messages with random keys, returning `(A, R, s, h)` for every signature.
+    fn signatures(
+        num_signatures: usize,
+    ) -> Vec<(AffinePoint<Ed25519>, AffinePoint<Ed25519>, BigUint, BigUint)> {
+        let mut rng = thread_rng();
+        let base = Ed25519::generator();
+        let order = Ed25519::prime_group_order();
+        (0..num_signatures)
+            .map(|_| {
+                let secret = rng.gen_biguint_below(&order);
+                let nonce = rng.gen_biguint_below(&order);
+                let pubkey = &base * &secret;
+                let sig_r = &base * &nonce;
+                let message = (0..32).map(|_| rng.gen::<u8>()).collect::<Vec<_>>();
+
+                let digest = SHA512Gadget::hash(
+                    &[encode_point(&sig_r), encode_point(&pubkey), message].concat(),
+                );
+                let challenge = BigUint::from_bytes_le(&digest) % &order;
+                let sig_s = (&nonce + &challenge * &secret) % &order;
+                (pubkey, sig_r, sig_s, challenge)
+            })
+            .collect()
+    }
+
+    fn set_u32_limbs(
+        pw: &mut PartialWitness<GoldilocksField>,
+        targets: &[Target],
+        value: &BigUint,
+    ) {
+        let mut limbs = value.to_u32_digits();
+        limbs.resize(targets.len(), 0);
+        for (target, limb) in targets.iter().zip(limbs) {
+            pw.set_target(*target, GoldilocksField::from_canonical_u32(limb));
+        }
+    }
+
+    fn set_point(
+        pw: &mut PartialWitness<GoldilocksField>,
+        target: &AffinePointTarget,
+        point: &AffinePoint<Ed25519>,
+    ) {
+        let x: [_; 16] = biguint_to_16_digits_field(&point.x, 16).try_into().unwrap();
+        let y: [_; 16] = biguint_to_16_digits_field(&point.y, 16).try_into().unwrap();
+        pw.set_target_arr(&target.x, &x);
+        pw.set_target_arr(&target.y, &y);
+    }
+
+    fn prove_batch(tampered: Option<usize>) {
+        type F = GoldilocksField;
+        type E = GoldilocksCubicParameters;
+        type SC = CurtaPoseidonGoldilocksConfig;
+        type C = PoseidonGoldilocksConfig;
+        const D: usize = 2;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let entries = (0..N)
+            .map(|_| builder.add_virtual_ed25519_batch_entry())
+            .collect::<Vec<_>>();
+        let randomizers = builder.ed25519_batch_randomizers(&entries);
+        let is_valid = builder.batch_verify_ed25519::<E, SC, N>(&entries, &randomizers);
+        builder.assert_one(is_valid);
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+
+        let order = Ed25519::prime_group_order();
+        for (i, (entry, (pubkey, sig_r, sig_s, challenge))) in
+            entries.iter().zip(signatures(N)).enumerate()
+        {
+            let sig_s = if tampered == Some(i) {
+                (sig_s + 1u32) % &order
+            } else {
+                sig_s
+            };
+            set_point(&mut pw, &entry.pubkey, &pubkey);
+            set_point(&mut pw, &entry.sig_r, &sig_r);
+            set_u32_limbs(&mut pw, &entry.sig_s, &sig_s);
+            set_u32_limbs(&mut pw, &entry.challenge, &challenge);
+        }
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+
+    #[test]
+    fn test_ed25519_batch_verify() {
+        prove_batch(None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_ed25519_batch_verify_bad_signature() {
+        prove_batch(Some(1));
+    }
+}
@@ -0,0 +1,15 @@
+//! Batch verification of Ed25519 signatures with a random linear combination.
+//!
+//! Given signatures `(R_i, s_i)` of public keys `A_i` with challenges `h_i = H(R_i || A_i || M_i)`
+//! and random scalars `z_i`, the batch is valid if
+//!
+//! sum_i (z_i * s_i) * B = sum_i z_i * R_i + sum_i (z_i * h_i) * A_i
+//!
+//! which is checked as a single multi-scalar multiplication of `2 * N + 1` points with a shared
+//! double-and-add, rather than `2 * N` separate scalar multiplications.
+
+pub mod air;
+pub mod gadget;
+
+#[cfg(feature = "plonky2")]
+pub mod generator;
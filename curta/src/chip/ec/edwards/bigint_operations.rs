@@ -1,4 +1,4 @@
-use core::ops::{Add, Mul};
+use core::ops::{Add, Mul, Neg};
 
 use num::BigUint;
 
@@ -83,6 +83,23 @@ impl<E: EdwardsParameters> Mul<BigUint> for AffinePoint<E> {
     }
 }
 
+impl<E: EdwardsParameters> Neg for &AffinePoint<E> {
+    type Output = AffinePoint<E>;
+
+    fn neg(self) -> AffinePoint<E> {
+        let p = E::BaseField::modulus();
+        AffinePoint::new((&p - &self.x) % &p, self.y.clone())
+    }
+}
+
+impl<E: EdwardsParameters> Neg for AffinePoint<E> {
+    type Output = AffinePoint<E>;
+
+    fn neg(self) -> AffinePoint<E> {
+        -&self
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -103,6 +120,8 @@ mod tests {
         assert_eq!(&base + &netural, base);
         assert_eq!(&netural + &base, base);
         assert_eq!(&netural + &netural, netural);
+        assert_eq!(&base + &(-&base), netural);
+        assert_eq!(-&netural, netural);
     }
 
     #[test]
@@ -0,0 +1,418 @@
+//! Compression and decompression of Ed25519 points in a plonky2 circuit.
+//!
+//! A point `(x, y)` is encoded as the 32 little-endian bytes of `y`, with the least significant
+//! bit of `x` in the most significant bit of the last byte, as in section 5.1.2 of RFC 8032.
+//! Decompression recovers `x` from the curve equation
+//!
+//! x^2 = (y^2 - 1) / (d * y^2 + 1)
+//!
+//! with a square root given by a hint, and checks the root in the circuit.
+
+use num::{BigUint, Zero};
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::generator::{GeneratedValues, SimpleGenerator};
+use plonky2::iop::target::{BoolTarget, Target};
+use plonky2::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::circuit_data::CommonCircuitData;
+use serde::{Deserialize, Serialize};
+
+use super::batch_verify::generator::{bits_to_u16_limbs, columns_to_bits, product_columns};
+use super::ed25519::{Ed25519, Ed25519BaseField};
+use super::scalar_mul::generator::AffinePointTarget;
+use super::EdwardsParameters;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::field::sqrt::biguint_sqrt;
+use crate::chip::hash::sha::sha256::builder_gadget::CurtaBytes;
+use crate::chip::utils::{biguint_to_16_digits_field, field_limbs_to_biguint};
+use crate::utils::serde::{BufferRead, BufferWrite};
+
+pub trait Ed25519CompressionGadget<F: RichField + Extendable<D>, const D: usize> {
+    /// Encodes the point `(x, y)`, whose coordinates must be given in reduced 16-bit limbs.
+    fn compress_ed25519_point(&mut self, point: &AffinePointTarget) -> CurtaBytes<32>;
+
+    /// Decodes a compressed point, returning its coordinates in reduced 16-bit limbs. The circuit
+    /// cannot be satisfied if the bytes are not the canonical encoding of a point of the curve.
+    fn decompress_ed25519_point(&mut self, bytes: &CurtaBytes<32>) -> AffinePointTarget;
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Ed25519CompressionGadget<F, D>
+    for CircuitBuilder<F, D>
+{
+    fn compress_ed25519_point(&mut self, point: &AffinePointTarget) -> CurtaBytes<32> {
+        let mut bits = point
+            .y
+            .iter()
+            .flat_map(|limb| self.split_le(*limb, 16))
+            .collect::<Vec<_>>();
+        // The coordinate `y` is less than `2^255`, so its most significant bit is free.
+        let top_bit = bits.pop().unwrap();
+        self.assert_zero(top_bit.target);
+        let sign = self.split_le(point.x[0], 16)[0];
+        bits.push(sign);
+
+        let bytes = bits
+            .chunks(8)
+            .map(|chunk| self.le_sum(chunk.iter()))
+            .collect::<Vec<_>>();
+        CurtaBytes(bytes.try_into().unwrap())
+    }
+
+    fn decompress_ed25519_point(&mut self, bytes: &CurtaBytes<32>) -> AffinePointTarget {
+        let point = decompress_point(self, &bytes.0);
+        assert_on_curve(self, &point);
+        point
+    }
+}
+
+/// Decompresses the encoding of a point: the little-endian bytes of `y`, with the sign of `x` in
+/// the most significant bit. The coordinate `x` is given by a hint and checked to be reduced and
+/// of the right sign, but not to be on the curve, which is left to the caller.
+pub(crate) fn decompress_point<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    bytes: &[Target; 32],
+) -> AffinePointTarget {
+    let modulus = Ed25519BaseField::modulus();
+
+    let mut y_bits = bytes_to_bits(builder, bytes);
+    let sign = y_bits.pop().unwrap();
+    let y: [Target; 16] = bits_to_u16_limbs(builder, &y_bits).try_into().unwrap();
+    let y_is_reduced = u16_limbs_less_than(builder, &y, &modulus);
+    builder.assert_one(y_is_reduced.target);
+
+    let x = builder.add_virtual_target_arr::<16>();
+    builder.add_simple_generator(Ed25519DecompressHintGenerator { y, sign, x });
+
+    let x_bits = x
+        .iter()
+        .flat_map(|limb| builder.split_le(*limb, 16))
+        .collect::<Vec<_>>();
+    builder.connect(x_bits[0].target, sign.target);
+    let x_is_reduced = u16_limbs_less_than(builder, &x, &modulus);
+    builder.assert_one(x_is_reduced.target);
+
+    AffinePointTarget { x, y }
+}
+
+/// Checks that a point with reduced coordinates is on the curve, in the form
+///
+/// x^2 * (d * y^2) + x^2 + 1 = y^2 mod p.
+fn assert_on_curve<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    point: &AffinePointTarget,
+) {
+    let d = Ed25519::D[..16]
+        .iter()
+        .map(|limb| builder.constant(F::from_canonical_u16(*limb)))
+        .collect::<Vec<_>>();
+
+    let y_squared = mul_add_mod_p(builder, &point.y, &point.y, &[]);
+    let x_squared = mul_add_mod_p(builder, &point.x, &point.x, &[]);
+    let d_y_squared = mul_add_mod_p(builder, &d, &y_squared, &[]);
+
+    let one = builder.one();
+    let mut x_squared_plus_one = x_squared.clone();
+    x_squared_plus_one[0] = builder.add(x_squared[0], one);
+    let lhs = mul_add_mod_p(builder, &x_squared, &d_y_squared, &x_squared_plus_one);
+
+    for (a, b) in lhs.iter().zip(y_squared.iter()) {
+        builder.connect(*a, *b);
+    }
+}
+
+/// Computes `a * b + c mod p` for integers in 16-bit limbs, where `a * b + c < p * 2^256`. The
+/// quotient and remainder are given by a hint and checked with
+///
+/// a * b + c = quotient * p + remainder, remainder < p.
+fn mul_add_mod_p<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    a: &[Target],
+    b: &[Target],
+    c: &[Target],
+) -> Vec<Target> {
+    let modulus = Ed25519BaseField::modulus();
+
+    let quotient = builder.add_virtual_targets(16);
+    let remainder = builder.add_virtual_targets(16);
+    builder.add_simple_generator(Ed25519MulAddHintGenerator {
+        a: a.to_vec(),
+        b: b.to_vec(),
+        c: c.to_vec(),
+        quotient: quotient.clone(),
+        remainder: remainder.clone(),
+    });
+    for limb in quotient.iter().chain(remainder.iter()) {
+        builder.range_check(*limb, 16);
+    }
+
+    let mut columns = product_columns(builder, a, b);
+    for (column, limb) in columns.iter_mut().zip(c.iter()) {
+        *column = builder.add(*column, *limb);
+    }
+    let bits = columns_to_bits(builder, &columns);
+
+    let modulus_limbs = biguint_to_16_digits_field::<F>(&modulus, 16)
+        .into_iter()
+        .map(|limb| builder.constant(limb))
+        .collect::<Vec<_>>();
+    let mut reduced_columns = product_columns(builder, &quotient, &modulus_limbs);
+    for (column, limb) in reduced_columns.iter_mut().zip(remainder.iter()) {
+        *column = builder.add(*column, *limb);
+    }
+    let reduced_bits = columns_to_bits(builder, &reduced_columns);
+
+    for (bit, reduced_bit) in bits.iter().zip(reduced_bits.iter()) {
+        builder.connect(bit.target, reduced_bit.target);
+    }
+
+    let remainder_is_reduced = u16_limbs_less_than(builder, &remainder, &modulus);
+    builder.assert_one(remainder_is_reduced.target);
+
+    remainder
+}
+
+/// The little-endian bits of little-endian bytes, range-checking the bytes.
+pub(crate) fn bytes_to_bits<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    bytes: &[Target],
+) -> Vec<BoolTarget> {
+    bytes
+        .iter()
+        .flat_map(|byte| builder.split_le(*byte, 8))
+        .collect()
+}
+
+/// Returns whether the integer of range-checked 16-bit `limbs` is less than the constant `bound`.
+///
+/// The comparison computes `(bound - 1) - a` limb by limb, and `a < bound` exactly when there is
+/// no borrow out of the most significant limb.
+pub(crate) fn u16_limbs_less_than<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    limbs: &[Target],
+    bound: &BigUint,
+) -> BoolTarget {
+    assert!(!bound.is_zero(), "The bound must be positive");
+    let bound_limbs = biguint_to_16_digits_field::<F>(&(bound - 1u32), limbs.len());
+
+    let mut no_borrow = builder._true();
+    for (limb, bound_limb) in limbs.iter().zip(bound_limbs) {
+        // bound_limb + 2^16 - limb - borrow, which is in [0, 2^17) and at least 2^16 when
+        // there is no borrow out of this limb.
+        let offset = builder.constant(bound_limb + F::from_canonical_u32(1 << 16));
+        let difference = builder.sub(offset, *limb);
+        let borrow = builder.not(no_borrow);
+        let difference = builder.sub(difference, borrow.target);
+        let difference_bits = builder.split_le(difference, 17);
+        no_borrow = difference_bits[16];
+    }
+    no_borrow
+}
+
+/// Computes the coordinate `x` of the point with coordinate `y` and sign `sign` from the curve
+/// equation `x^2 = (y^2 - 1) / (d * y^2 + 1)`. If there is no such point, the hint writes zero,
+/// which the constraints of the circuit reject.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ed25519DecompressHintGenerator {
+    y: [Target; 16],
+    sign: BoolTarget,
+    x: [Target; 16],
+}
+
+impl Ed25519DecompressHintGenerator {
+    pub fn id() -> String {
+        "Ed25519DecompressHintGenerator".to_string()
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
+    for Ed25519DecompressHintGenerator
+{
+    fn id(&self) -> String {
+        Self::id()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        self.y
+            .iter()
+            .copied()
+            .chain(core::iter::once(self.sign.target))
+            .collect()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let modulus = Ed25519BaseField::modulus();
+        let y = field_limbs_to_biguint(&witness.get_targets(&self.y));
+        let sign = witness.get_target(self.sign.target) == F::ONE;
+
+        let y_squared = &y * &y % &modulus;
+        let u = (&y_squared + &modulus - 1u32) % &modulus;
+        let v = (Ed25519::d_biguint() * &y_squared + 1u32) % &modulus;
+        let v_inv = v.modpow(&(&modulus - 2u32), &modulus);
+        let x_squared = u * v_inv % &modulus;
+
+        let x = match biguint_sqrt(&x_squared, &modulus) {
+            Some(root) if root.bit(0) != sign => (&modulus - root) % &modulus,
+            Some(root) => root,
+            None => BigUint::zero(),
+        };
+
+        let x_limbs: [_; 16] = biguint_to_16_digits_field(&x, 16).try_into().unwrap();
+        out_buffer.set_target_arr(&self.x, &x_limbs);
+    }
+
+    fn serialize(
+        &self,
+        dst: &mut Vec<u8>,
+        _common_data: &CommonCircuitData<F, D>,
+    ) -> plonky2::util::serialization::IoResult<()> {
+        let data = bincode::serialize(&self).unwrap();
+        dst.write_bytes(&data)
+    }
+
+    fn deserialize(
+        src: &mut plonky2::util::serialization::Buffer,
+        _common_data: &CommonCircuitData<F, D>,
+    ) -> plonky2::util::serialization::IoResult<Self> {
+        let bytes = src.read_bytes()?;
+        let data = bincode::deserialize(&bytes).unwrap();
+        Ok(data)
+    }
+}
+
+/// Computes the quotient and remainder of `a * b + c` by the modulus of the base field.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ed25519MulAddHintGenerator {
+    a: Vec<Target>,
+    b: Vec<Target>,
+    c: Vec<Target>,
+    quotient: Vec<Target>,
+    remainder: Vec<Target>,
+}
+
+impl Ed25519MulAddHintGenerator {
+    pub fn id() -> String {
+        "Ed25519MulAddHintGenerator".to_string()
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
+    for Ed25519MulAddHintGenerator
+{
+    fn id(&self) -> String {
+        Self::id()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        self.a
+            .iter()
+            .chain(self.b.iter())
+            .chain(self.c.iter())
+            .copied()
+            .collect()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let modulus = Ed25519BaseField::modulus();
+        let a = field_limbs_to_biguint(&witness.get_targets(&self.a));
+        let b = field_limbs_to_biguint(&witness.get_targets(&self.b));
+        let c = field_limbs_to_biguint(&witness.get_targets(&self.c));
+        let value = a * b + c;
+
+        let quotient = biguint_to_16_digits_field(&(&value / &modulus), self.quotient.len());
+        let remainder = biguint_to_16_digits_field(&(&value % &modulus), self.remainder.len());
+        for (target, value) in self.quotient.iter().zip(quotient) {
+            out_buffer.set_target(*target, value);
+        }
+        for (target, value) in self.remainder.iter().zip(remainder) {
+            out_buffer.set_target(*target, value);
+        }
+    }
+
+    fn serialize(
+        &self,
+        dst: &mut Vec<u8>,
+        _common_data: &CommonCircuitData<F, D>,
+    ) -> plonky2::util::serialization::IoResult<()> {
+        let data = bincode::serialize(&self).unwrap();
+        dst.write_bytes(&data)
+    }
+
+    fn deserialize(
+        src: &mut plonky2::util::serialization::Buffer,
+        _common_data: &CommonCircuitData<F, D>,
+    ) -> plonky2::util::serialization::IoResult<Self> {
+        let bytes = src.read_bytes()?;
+        let data = bincode::deserialize(&bytes).unwrap();
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::iop::witness::PartialWitness;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+
+    use super::*;
+
+    // The base point, and the public keys of tests 1 and 3 of RFC 8032, section 7.1.
+    const POINTS: [&str; 3] = [
+        "5866666666666666666666666666666666666666666666666666666666666666",
+        "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        "fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025",
+    ];
+
+    /// Decompresses and compresses back `encoding`, returning the coordinates of the point.
+    fn prove_round_trip(encoding: &[u8]) -> (BigUint, BigUint) {
+        type F = GoldilocksField;
+        type C = PoseidonGoldilocksConfig;
+        const D: usize = 2;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let bytes = CurtaBytes(builder.add_virtual_target_arr::<32>());
+        let point = builder.decompress_ed25519_point(&bytes);
+        let compressed = builder.compress_ed25519_point(&point);
+        for (a, b) in bytes.0.iter().zip(compressed.0.iter()) {
+            builder.connect(*a, *b);
+        }
+        builder.register_public_inputs(&point.x);
+        builder.register_public_inputs(&point.y);
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+        for (target, value) in bytes.0.iter().zip(encoding.iter()) {
+            pw.set_target(*target, F::from_canonical_u8(*value));
+        }
+
+        let proof = data.prove(pw).unwrap();
+        let x = field_limbs_to_biguint(&proof.public_inputs[..16]);
+        let y = field_limbs_to_biguint(&proof.public_inputs[16..]);
+        data.verify(proof).unwrap();
+        (x, y)
+    }
+
+    #[test]
+    fn test_ed25519_compression() {
+        let (x, y) = prove_round_trip(&hex::decode(POINTS[0]).unwrap());
+        let generator = Ed25519::generator();
+        assert_eq!(x, generator.x);
+        assert_eq!(y, generator.y);
+
+        for point in POINTS[1..].iter() {
+            prove_round_trip(&hex::decode(point).unwrap());
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_ed25519_decompress_not_on_curve() {
+        // `y = 2` is not the coordinate of a point of the curve.
+        let mut encoding = [0u8; 32];
+        encoding[0] = 2;
+        prove_round_trip(&encoding);
+    }
+}
@@ -0,0 +1,250 @@
+//! Scalar multiplication with a choice of coordinates for the intermediate points.
+//!
+//! Points in extended twisted Edwards coordinates `(X : Y : Z : T)` represent the affine point
+//! `(X / Z, Y / Z)`, with `X * Y = Z * T`. The addition formula of Hisil, Wong, Carter and Dawson
+//! for curves with `a = -1` is complete when `d` is not a square, as for Ed25519, and needs no
+//! division.
+//!
+//! Reference: https://eprint.iacr.org/2008/522
+
+use num::{BigUint, One, Zero};
+use serde::{Deserialize, Serialize};
+
+use super::EdwardsParameters;
+use crate::chip::builder::AirBuilder;
+use crate::chip::ec::point::AffinePointRegister;
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::register::FieldRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::AirParameters;
+
+/// The coordinate system of the intermediate points of a scalar multiplication.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CoordinateSystem {
+    /// Affine coordinates `(x, y)`. Every addition divides by `1 + d * f` and `1 - d * f`, but a
+    /// division is a single instruction whose quotient is witnessed, so that an affine addition
+    /// takes fewer columns than an extended one.
+    #[default]
+    Affine,
+    /// Extended twisted Edwards coordinates `(X : Y : Z : T)`, with no division in the additions
+    /// and two divisions to convert the result back to affine coordinates.
+    Extended,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ExtendedPointRegister<E: EdwardsParameters> {
+    pub x: FieldRegister<E::BaseField>,
+    pub y: FieldRegister<E::BaseField>,
+    pub z: FieldRegister<E::BaseField>,
+    pub t: FieldRegister<E::BaseField>,
+}
+
+impl<E: EdwardsParameters> ExtendedPointRegister<E> {
+    pub fn new(
+        x: FieldRegister<E::BaseField>,
+        y: FieldRegister<E::BaseField>,
+        z: FieldRegister<E::BaseField>,
+        t: FieldRegister<E::BaseField>,
+    ) -> Self {
+        Self { x, y, z, t }
+    }
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Converts an affine point to extended coordinates `(x : y : 1 : x * y)`.
+    pub fn ed_to_extended<E: EdwardsParameters>(
+        &mut self,
+        p: &AffinePointRegister<E>,
+    ) -> ExtendedPointRegister<E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        let z = self.alloc_constant_field_register(&BigUint::one());
+        let t = self.fp_mul(&p.x, &p.y).result;
+        ExtendedPointRegister::new(p.x, p.y, z, t)
+    }
+
+    /// Converts a point in extended coordinates to affine coordinates `(X / Z, Y / Z)`.
+    pub fn ed_extended_to_affine<E: EdwardsParameters>(
+        &mut self,
+        p: &ExtendedPointRegister<E>,
+    ) -> AffinePointRegister<E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        let x = self.fp_div(&p.x, &p.z);
+        let y = self.fp_div(&p.y, &p.z);
+        AffinePointRegister::new(x, y)
+    }
+
+    /// Allocates the neutral element `(0 : 1 : 1 : 0)` in extended coordinates.
+    pub fn ed_extended_neutral<E: EdwardsParameters>(&mut self) -> ExtendedPointRegister<E> {
+        let x = self.alloc_constant_field_register(&BigUint::zero());
+        let y = self.alloc_constant_field_register(&BigUint::one());
+        let z = self.alloc_constant_field_register(&BigUint::one());
+        let t = self.alloc_constant_field_register(&BigUint::zero());
+        ExtendedPointRegister::new(x, y, z, t)
+    }
+
+    /// Selects `p` if `bit` is one and `q` otherwise, coordinate by coordinate.
+    pub fn select_extended_point<E: EdwardsParameters>(
+        &mut self,
+        bit: &BitRegister,
+        p: &ExtendedPointRegister<E>,
+        q: &ExtendedPointRegister<E>,
+    ) -> ExtendedPointRegister<E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        let x = self.select(bit, &p.x, &q.x);
+        let y = self.select(bit, &p.y, &q.y);
+        let z = self.select(bit, &p.z, &q.z);
+        let t = self.select(bit, &p.t, &q.t);
+        ExtendedPointRegister::new(x, y, z, t)
+    }
+
+    /// Adds two points in extended coordinates with the unified formula for `a = -1`.
+    pub fn ed_extended_add<E: EdwardsParameters>(
+        &mut self,
+        p: &ExtendedPointRegister<E>,
+        q: &ExtendedPointRegister<E>,
+    ) -> ExtendedPointRegister<E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        // A = X1 * X2, B = Y1 * Y2, C = d * T1 * T2, D = Z1 * Z2.
+        let a = self.fp_mul(&p.x, &q.x).result;
+        let b = self.fp_mul(&p.y, &q.y).result;
+        let t1_t2 = self.fp_mul(&p.t, &q.t).result;
+        let c = self.fp_mul_const(&t1_t2, E::D).result;
+        let d = self.fp_mul(&p.z, &q.z).result;
+
+        // E = (X1 + Y1) * (X2 + Y2) - A - B.
+        let x1_plus_y1 = self.fp_add(&p.x, &p.y);
+        let x2_plus_y2 = self.fp_add(&q.x, &q.y);
+        let product = self.fp_mul(&x1_plus_y1, &x2_plus_y2).result;
+        let product_minus_a = self.fp_sub(&product, &a);
+        let e = self.fp_sub(&product_minus_a, &b);
+
+        // F = D - C, G = D + C, H = B + A.
+        let f = self.fp_sub(&d, &c);
+        let g = self.fp_add(&d, &c);
+        let h = self.fp_add(&b, &a);
+
+        // (X3 : Y3 : Z3 : T3) = (E * F : G * H : F * G : E * H).
+        let x = self.fp_mul(&e, &f).result;
+        let y = self.fp_mul(&g, &h).result;
+        let z = self.fp_mul(&f, &g).result;
+        let t = self.fp_mul(&e, &h).result;
+        ExtendedPointRegister::new(x, y, z, t)
+    }
+
+    /// Computes `scalar * point` by a double-and-add over the little-endian bits of the scalar,
+    /// with the intermediate points in the given coordinate system.
+    pub fn ed_scalar_mul_bits<E: EdwardsParameters>(
+        &mut self,
+        bits: &[BitRegister],
+        point: &AffinePointRegister<E>,
+        coordinates: CoordinateSystem,
+    ) -> AffinePointRegister<E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        match coordinates {
+            CoordinateSystem::Affine => self.ec_scalar_mul_bits::<E>(bits, point),
+            CoordinateSystem::Extended => {
+                let mut result = self.ed_extended_neutral();
+                let mut temp = self.ed_to_extended(point);
+                for (i, bit) in bits.iter().enumerate() {
+                    let sum = self.ed_extended_add(&result, &temp);
+                    result = self.select_extended_point(bit, &sum, &result);
+                    if i + 1 < bits.len() {
+                        temp = self.ed_extended_add(&temp, &temp);
+                    }
+                }
+                self.ed_extended_to_affine(&result)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::ec::edwards::ed25519::{Ed25519, Ed25519BaseField};
+    use crate::chip::ec::gadget::{EllipticCurveGadget, EllipticCurveWriter};
+    use crate::chip::field::instruction::FpInstruction;
+    use crate::math::prelude::*;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    pub struct Ed25519CoordinatesTest;
+
+    impl AirParameters for Ed25519CoordinatesTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 7600;
+        const NUM_FREE_COLUMNS: usize = 4;
+        const EXTENDED_COLUMNS: usize = 11409;
+        type Instruction = FpInstruction<Ed25519BaseField>;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    #[test]
+    fn test_ed25519_scalar_mul_coordinates() {
+        type F = GoldilocksField;
+        type L = Ed25519CoordinatesTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type E = Ed25519;
+
+        const NUM_BITS: usize = 2;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let point = builder.alloc_ec_point();
+        let bits = (0..NUM_BITS)
+            .map(|_| builder.alloc::<BitRegister>())
+            .collect::<Vec<_>>();
+        let affine = builder.ed_scalar_mul_bits(&bits, &point, CoordinateSystem::Affine);
+        let extended = builder.ed_scalar_mul_bits(&bits, &point, CoordinateSystem::Extended);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let base = E::generator();
+        let mut rng = thread_rng();
+        let points = (0..16)
+            .map(|_| &base * &rng.gen_biguint(256))
+            .collect::<Vec<_>>();
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            let writer = generator.new_writer();
+            let scalar = i % (1 << NUM_BITS);
+            let value = &points[i % 16];
+            writer.write_ec_point(&point, value, i);
+            for (j, bit) in bits.iter().enumerate() {
+                writer.write(bit, &F::from_canonical_usize((scalar >> j) & 1), i);
+            }
+            writer.write_row_instructions(&generator.air_data, i);
+
+            let expected = value * BigUint::from(scalar);
+            assert_eq!(writer.read_ec_point(&affine, i), expected);
+            assert_eq!(writer.read_ec_point(&extended, i), expected);
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+}
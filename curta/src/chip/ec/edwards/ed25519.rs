@@ -41,6 +41,10 @@ impl EdwardsParameters for Ed25519 {
         BigUint::from(2u32).pow(252) + BigUint::from(27742317777372353535851937790883648493u128)
     }
 
+    fn cofactor() -> BigUint {
+        BigUint::from(8u32)
+    }
+
     fn generator() -> AffinePoint<Self> {
         let x = BigUint::from_str_radix(
             "15112221349535400772501151409588531511454012693041857206046113283949847762202",
@@ -55,3 +59,32 @@ impl EdwardsParameters for Ed25519 {
         AffinePoint::new(x, y)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chip::utils::digits_to_biguint;
+
+    #[test]
+    fn test_ed25519_parameters() {
+        type E = Ed25519;
+        let p = Ed25519BaseField::modulus();
+        let base = E::generator();
+
+        // The limbs of the modulus match 2^255 - 19.
+        assert_eq!(digits_to_biguint(&Ed25519BaseField::MODULUS), p);
+
+        // The base point lies on the curve -x^2 + y^2 = 1 + d * x^2 * y^2.
+        let xx = (&base.x * &base.x) % &p;
+        let yy = (&base.y * &base.y) % &p;
+        assert_eq!(
+            (&p - &xx + &yy) % &p,
+            (BigUint::one() + E::d_biguint() * &xx * &yy) % &p
+        );
+
+        // The base point has order L.
+        let order = E::prime_group_order();
+        assert_eq!(&base * &order, E::neutral());
+        assert_ne!(&base * &(&order - 1u32), E::neutral());
+    }
+}
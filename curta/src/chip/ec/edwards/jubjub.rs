@@ -0,0 +1,86 @@
+use num::{BigUint, Num};
+use serde::{Deserialize, Serialize};
+
+use super::EdwardsParameters;
+use crate::chip::ec::point::AffinePoint;
+use crate::chip::ec::weierstrass::bls12_381::Bls12381ScalarField;
+use crate::chip::ec::EllipticCurveParameters;
+use crate::chip::field::parameters::MAX_NB_LIMBS;
+
+/// The Jubjub curve `-u^2 + v^2 = 1 + d * u^2 * v^2` with `d = -10240 / 10241`, defined over the
+/// scalar field of BLS12-381.
+///
+/// Reference: https://zips.z.cash/protocol/protocol.pdf (section 5.4.9.3)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Jubjub;
+
+impl EllipticCurveParameters for Jubjub {
+    type BaseField = Bls12381ScalarField;
+}
+
+impl EdwardsParameters for Jubjub {
+    const D: [u16; MAX_NB_LIMBS] = [
+        16049, 54836, 24534, 262, 40230, 14167, 32621, 10541, 32724, 59069, 37383, 62973, 11080,
+        19450, 6375, 10899, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+
+    fn prime_group_order() -> BigUint {
+        BigUint::from_str_radix(
+            "0e7db4ea6533afa906673b0101343b00a6682093ccc81082d0970e5ed6f72cb7",
+            16,
+        )
+        .unwrap()
+    }
+
+    fn cofactor() -> BigUint {
+        BigUint::from(8u32)
+    }
+
+    fn generator() -> AffinePoint<Self> {
+        let u = BigUint::from_str_radix(
+            "11dafe5d23e1218086a365b99fbf3d3be72f6afd7d1f72623e6b071492d1122b",
+            16,
+        )
+        .unwrap();
+        let v = BigUint::from_str_radix(
+            "1d523cf1ddab1a1793132e78c866c0c33e26ba5cc220fed7cc3f870e59d292aa",
+            16,
+        )
+        .unwrap();
+        AffinePoint::new(u, v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::One;
+
+    use super::*;
+    use crate::chip::field::parameters::FieldParameters;
+
+    #[test]
+    fn test_jubjub_parameters() {
+        type E = Jubjub;
+        let p = Bls12381ScalarField::modulus();
+        let base = E::generator();
+
+        // d = -10240 / 10241.
+        assert_eq!(
+            (E::d_biguint() * BigUint::from(10241u32) + BigUint::from(10240u32)) % &p,
+            BigUint::from(0u32)
+        );
+
+        // The base point lies on the curve -u^2 + v^2 = 1 + d * u^2 * v^2.
+        let uu = (&base.x * &base.x) % &p;
+        let vv = (&base.y * &base.y) % &p;
+        assert_eq!(
+            (&p - &uu + &vv) % &p,
+            (BigUint::one() + E::d_biguint() * &uu * &vv) % &p
+        );
+
+        // The base point has order r_J.
+        let order = E::prime_group_order();
+        assert_eq!(&base * &order, E::neutral());
+        assert_ne!(&base * &(&order - 1u32), E::neutral());
+    }
+}
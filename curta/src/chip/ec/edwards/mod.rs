@@ -1,14 +1,28 @@
 use num::{BigUint, Zero};
 
-use super::point::AffinePoint;
+use super::gadget::CurveValidation;
+use super::model::CurveModel;
+use super::point::{AffinePoint, AffinePointRegister};
 use super::EllipticCurveParameters;
+use crate::chip::builder::AirBuilder;
+use crate::chip::field::instruction::FromFieldInstruction;
 use crate::chip::field::parameters::{FieldParameters, MAX_NB_LIMBS};
+use crate::chip::register::bit::BitRegister;
+use crate::chip::AirParameters;
 
 pub mod add;
+pub mod batch_verify;
 pub mod bigint_operations;
+#[cfg(feature = "plonky2")]
+pub mod compression;
+pub mod coordinates;
 pub mod ed25519;
+pub mod jubjub;
 pub mod scalar_mul;
 
+#[cfg(feature = "plonky2")]
+pub mod verify;
+
 pub trait EdwardsParameters: EllipticCurveParameters {
     const D: [u16; MAX_NB_LIMBS];
 
@@ -16,6 +30,9 @@ pub trait EdwardsParameters: EllipticCurveParameters {
 
     fn prime_group_order() -> BigUint;
 
+    /// The index of the prime subgroup in the group of points of the curve.
+    fn cofactor() -> BigUint;
+
     fn d_biguint() -> BigUint {
         let mut modulus = BigUint::zero();
         for (i, limb) in Self::D.iter().enumerate() {
@@ -32,3 +49,92 @@ pub trait EdwardsParameters: EllipticCurveParameters {
         AffinePoint::new(BigUint::from(0u32), BigUint::from(1u32))
     }
 }
+
+/// The addition formula of twisted Edwards curves is complete, so the group law operates on
+/// affine points directly.
+impl<E: EdwardsParameters> CurveModel for E {
+    type PointRegister = AffinePointRegister<E>;
+
+    fn from_affine<L: AirParameters>(
+        _builder: &mut AirBuilder<L>,
+        p: &AffinePointRegister<E>,
+    ) -> AffinePointRegister<E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        *p
+    }
+
+    fn to_affine<L: AirParameters>(
+        _builder: &mut AirBuilder<L>,
+        p: &AffinePointRegister<E>,
+    ) -> AffinePointRegister<E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        *p
+    }
+
+    fn neutral<L: AirParameters>(builder: &mut AirBuilder<L>) -> AffinePointRegister<E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        builder.ed_neutral()
+    }
+
+    fn add<L: AirParameters>(
+        builder: &mut AirBuilder<L>,
+        p: &AffinePointRegister<E>,
+        q: &AffinePointRegister<E>,
+    ) -> AffinePointRegister<E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        builder.ed_add(p, q).result
+    }
+
+    fn double<L: AirParameters>(
+        builder: &mut AirBuilder<L>,
+        p: &AffinePointRegister<E>,
+    ) -> AffinePointRegister<E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        builder.ed_double(p).result
+    }
+
+    fn select<L: AirParameters>(
+        builder: &mut AirBuilder<L>,
+        bit: &BitRegister,
+        p: &AffinePointRegister<E>,
+        q: &AffinePointRegister<E>,
+    ) -> AffinePointRegister<E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        builder.select_point(bit, p, q)
+    }
+}
+
+impl<E: EdwardsParameters> CurveValidation for E {
+    fn cofactor() -> BigUint {
+        <E as EdwardsParameters>::cofactor()
+    }
+
+    fn assert_curve_equation<L: AirParameters>(
+        builder: &mut AirBuilder<L>,
+        p: &AffinePointRegister<E>,
+    ) where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        builder.ed_assert_on_curve(p);
+    }
+
+    fn cofactor_root(p: &AffinePoint<E>) -> AffinePoint<E> {
+        // [h^-1 mod n] * p, with the inverse from Fermat's little theorem as `n` is prime.
+        let order = E::prime_group_order();
+        let cofactor = <E as EdwardsParameters>::cofactor();
+        let cofactor_inverse = cofactor.modpow(&(&order - 2u32), &order);
+        p * cofactor_inverse
+    }
+}
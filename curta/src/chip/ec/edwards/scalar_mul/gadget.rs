@@ -65,9 +65,7 @@ impl<L: AirParameters> AirBuilder<L> {
         let double_gadget = self.ed_double(temp);
 
         // result = if bit == 1 then result + temp else result.
-        let select_x = self.select(bit, &add_gadget.result.x, &result.x);
-        let select_y = self.select(bit, &add_gadget.result.y, &result.y);
-        let result_next = AffinePointRegister::new(select_x, select_y);
+        let result_next = self.select_point(bit, &add_gadget.result, result);
 
         EdDoubleAndAddGadget {
             bit: *bit,
@@ -397,7 +397,7 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleScalarMulEd25519HintGen
         }
     }
 
-    fn id() -> String {
+    pub fn id() -> String {
         "SimpleScalarMulEd25519HintGenerator".to_string()
     }
 }
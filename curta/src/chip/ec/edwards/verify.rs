@@ -0,0 +1,418 @@
+//! Verification of single Ed25519 signatures in a plonky2 circuit.
+//!
+//! A signature `(R, s)` of a message `M` under the public key `A` is valid if `s < l` and
+//!
+//! [s] * B = R + [k] * A,
+//!
+//! where `k = SHA-512(R || A || M) mod l`. The gadget decompresses `A` and `R` with the help of a
+//! hint, reduces the digest modulo `l` with a witnessed quotient, and checks the equation as a
+//! batch of a single signature with a randomizer of one.
+//!
+//! The equation is cofactorless: it must hold exactly, rather than after multiplying both sides
+//! by the cofactor 8 as in cofactored verifiers such as ed25519-zebra. A signature whose `R` or
+//! `A` has a small-order component that does not cancel out is therefore rejected by the gadget,
+//! even though a cofactored verifier accepts it.
+//!
+//! The encodings of `A` and `R` must be canonical encodings of points of the curve. Otherwise the
+//! circuit cannot be satisfied, rather than giving a validity bit of zero.
+
+use num::BigUint;
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::generator::{GeneratedValues, SimpleGenerator};
+use plonky2::iop::target::{BoolTarget, Target};
+use plonky2::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::circuit_data::CommonCircuitData;
+use serde::{Deserialize, Serialize};
+
+use super::batch_verify::generator::{
+    bits_to_u16_limbs, columns_to_bits, product_columns, Ed25519BatchEntryTarget,
+    Ed25519BatchVerifyGadget,
+};
+use super::compression::{bytes_to_bits, decompress_point, u16_limbs_less_than};
+use super::ed25519::Ed25519;
+use super::EdwardsParameters;
+use crate::chip::hash::sha::sha256::builder_gadget::CurtaBytes;
+use crate::chip::hash::sha::sha512::builder_gadget::{SHA512Builder, SHA512BuilderGadget};
+use crate::chip::hash::sha::sha512::SHA512Gadget;
+use crate::chip::utils::biguint_to_16_digits_field;
+use crate::math::extension::CubicParameters;
+use crate::math::prelude::*;
+use crate::plonky2::stark::config::CurtaConfig;
+use crate::utils::serde::{BufferRead, BufferWrite};
+
+/// The number of 16-bit limbs of the quotient of a SHA-512 digest by the group order.
+const QUOTIENT_LIMBS: usize = 17;
+
+#[derive(Debug, Clone)]
+pub struct Ed25519VerifyTarget {
+    /// The compressed public key `A`.
+    pub pubkey: CurtaBytes<32>,
+    /// The bytes of the message.
+    pub message: Vec<Target>,
+    /// The signature `R || s`.
+    pub signature: CurtaBytes<64>,
+    /// One if the signature is valid and zero otherwise.
+    pub valid: BoolTarget,
+}
+
+pub trait Ed25519VerifyGadget<F: RichField + Extendable<D>, const D: usize> {
+    /// Allocates a public key, a message of `message_len` bytes and a signature, and verifies
+    /// the signature.
+    fn add_virtual_ed25519_verify<
+        E: CubicParameters<F>,
+        C: CurtaConfig<D, F = F, FE = F::Extension>,
+    >(
+        &mut self,
+        message_len: usize,
+        sha_gadget: &mut SHA512BuilderGadget<F, E, D>,
+    ) -> Ed25519VerifyTarget;
+
+    /// Verifies the signature `R || s` of `message` under the compressed public key `pubkey`,
+    /// returning a bit that is one if the signature is valid. The length of the message is fixed
+    /// by the circuit, and its digest is added to `sha_gadget`.
+    ///
+    /// The cofactorless equation `[s] * B = R + [k] * A` is checked, as a batch of a single
+    /// signature with a randomizer of one.
+    fn verify_ed25519<E: CubicParameters<F>, C: CurtaConfig<D, F = F, FE = F::Extension>>(
+        &mut self,
+        pubkey: &CurtaBytes<32>,
+        message: &[Target],
+        signature: &CurtaBytes<64>,
+        sha_gadget: &mut SHA512BuilderGadget<F, E, D>,
+    ) -> BoolTarget;
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Ed25519VerifyGadget<F, D>
+    for CircuitBuilder<F, D>
+{
+    fn add_virtual_ed25519_verify<
+        E: CubicParameters<F>,
+        C: CurtaConfig<D, F = F, FE = F::Extension>,
+    >(
+        &mut self,
+        message_len: usize,
+        sha_gadget: &mut SHA512BuilderGadget<F, E, D>,
+    ) -> Ed25519VerifyTarget {
+        let pubkey = CurtaBytes(self.add_virtual_target_arr::<32>());
+        let message = self.add_virtual_targets(message_len);
+        let signature = CurtaBytes(self.add_virtual_target_arr::<64>());
+        let valid = self.verify_ed25519::<E, C>(&pubkey, &message, &signature, sha_gadget);
+        Ed25519VerifyTarget {
+            pubkey,
+            message,
+            signature,
+            valid,
+        }
+    }
+
+    fn verify_ed25519<E: CubicParameters<F>, C: CurtaConfig<D, F = F, FE = F::Extension>>(
+        &mut self,
+        pubkey: &CurtaBytes<32>,
+        message: &[Target],
+        signature: &CurtaBytes<64>,
+        sha_gadget: &mut SHA512BuilderGadget<F, E, D>,
+    ) -> BoolTarget {
+        let sig_r_bytes: [Target; 32] = signature.0[..32].try_into().unwrap();
+        let sig_s_bytes = &signature.0[32..];
+
+        let pubkey_point = decompress_point(self, &pubkey.0);
+        let sig_r = decompress_point(self, &sig_r_bytes);
+
+        // k = SHA-512(R || A || M) mod l
+        let hash_input = sig_r_bytes
+            .iter()
+            .chain(pubkey.0.iter())
+            .chain(message.iter())
+            .copied()
+            .collect::<Vec<_>>();
+        let digest = sha512_message(self, &hash_input, sha_gadget);
+        let challenge = reduce_mod_order(self, &digest.0);
+
+        // The scalar `s` must be reduced, so that the signature is not malleable.
+        let s_bits = bytes_to_bits(self, sig_s_bytes);
+        let s_limbs = bits_to_u16_limbs(self, &s_bits);
+        let s_is_reduced = u16_limbs_less_than(self, &s_limbs, &Ed25519::prime_group_order());
+
+        let entry = Ed25519BatchEntryTarget {
+            pubkey: pubkey_point,
+            sig_r,
+            sig_s: u16_limbs_to_u32_limbs(self, &s_limbs),
+            challenge: u16_limbs_to_u32_limbs(self, &challenge),
+        };
+        let one = self.one();
+        let equation_holds = self.batch_verify_ed25519::<E, C, 1>(&[entry], &[one]);
+
+        self.and(BoolTarget::new_unsafe(equation_holds), s_is_reduced)
+    }
+}
+
+/// Hashes a message whose length is fixed by the circuit, so that its padding is constant.
+fn sha512_message<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    message: &[Target],
+    gadget: &mut SHA512BuilderGadget<F, E, D>,
+) -> CurtaBytes<64> {
+    let length = message.len();
+    let padding = SHA512Gadget::pad(&vec![0u8; length])[length..]
+        .iter()
+        .map(|byte| builder.constant(F::from_canonical_u8(*byte)))
+        .collect::<Vec<_>>();
+
+    let padded_message = message.iter().copied().chain(padding).collect::<Vec<_>>();
+    SHA512Builder::<F, E, D>::sha512_bytes(builder, &padded_message, gadget)
+}
+
+/// Reduces a little-endian SHA-512 digest modulo the group order `l`, returning the 16-bit limbs
+/// of the remainder. The quotient and remainder are given by a hint and checked with
+///
+/// digest = quotient * l + remainder, remainder < l.
+fn reduce_mod_order<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    digest: &[Target; 64],
+) -> Vec<Target> {
+    let order = Ed25519::prime_group_order();
+
+    let quotient = builder.add_virtual_targets(QUOTIENT_LIMBS);
+    let remainder = builder.add_virtual_targets(16);
+    builder.add_simple_generator(Ed25519ReduceHintGenerator {
+        digest: digest.to_vec(),
+        quotient: quotient.clone(),
+        remainder: remainder.clone(),
+    });
+    for limb in quotient.iter().chain(remainder.iter()) {
+        builder.range_check(*limb, 16);
+    }
+
+    let order_limbs = biguint_to_16_digits_field::<F>(&order, 16)
+        .into_iter()
+        .map(|limb| builder.constant(limb))
+        .collect::<Vec<_>>();
+    let mut columns = product_columns(builder, &quotient, &order_limbs);
+    for (column, limb) in columns.iter_mut().zip(remainder.iter()) {
+        *column = builder.add(*column, *limb);
+    }
+    let bits = columns_to_bits(builder, &columns);
+
+    let digest_bits = bytes_to_bits(builder, digest);
+    for (bit, digest_bit) in bits.iter().zip(digest_bits.iter()) {
+        builder.connect(bit.target, digest_bit.target);
+    }
+    for bit in bits[digest_bits.len()..].iter() {
+        builder.assert_zero(bit.target);
+    }
+
+    let remainder_is_reduced = u16_limbs_less_than(builder, &remainder, &order);
+    builder.assert_one(remainder_is_reduced.target);
+
+    remainder
+}
+
+/// Joins pairs of 16-bit limbs into 32-bit limbs.
+fn u16_limbs_to_u32_limbs<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    limbs: &[Target],
+) -> Vec<Target> {
+    limbs
+        .chunks(2)
+        .map(|pair| match pair {
+            [low, high] => builder.mul_const_add(F::from_canonical_u32(1 << 16), *high, *low),
+            [low] => *low,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+/// Computes the quotient and remainder of a little-endian SHA-512 digest by the group order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ed25519ReduceHintGenerator {
+    digest: Vec<Target>,
+    quotient: Vec<Target>,
+    remainder: Vec<Target>,
+}
+
+impl Ed25519ReduceHintGenerator {
+    pub fn id() -> String {
+        "Ed25519ReduceHintGenerator".to_string()
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
+    for Ed25519ReduceHintGenerator
+{
+    fn id(&self) -> String {
+        Self::id()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        self.digest.clone()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let digest = witness
+            .get_targets(&self.digest)
+            .iter()
+            .map(|byte| byte.as_canonical_u64() as u8)
+            .collect::<Vec<_>>();
+        let digest = BigUint::from_bytes_le(&digest);
+        let order = Ed25519::prime_group_order();
+
+        let quotient = biguint_to_16_digits_field(&(&digest / &order), self.quotient.len());
+        let remainder = biguint_to_16_digits_field(&(&digest % &order), self.remainder.len());
+        for (target, value) in self.quotient.iter().zip(quotient) {
+            out_buffer.set_target(*target, value);
+        }
+        for (target, value) in self.remainder.iter().zip(remainder) {
+            out_buffer.set_target(*target, value);
+        }
+    }
+
+    fn serialize(
+        &self,
+        dst: &mut Vec<u8>,
+        _common_data: &CommonCircuitData<F, D>,
+    ) -> plonky2::util::serialization::IoResult<()> {
+        let data = bincode::serialize(&self).unwrap();
+        dst.write_bytes(&data)
+    }
+
+    fn deserialize(
+        src: &mut plonky2::util::serialization::Buffer,
+        _common_data: &CommonCircuitData<F, D>,
+    ) -> plonky2::util::serialization::IoResult<Self> {
+        let bytes = src.read_bytes()?;
+        let data = bincode::deserialize(&bytes).unwrap();
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::iop::witness::PartialWitness;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+
+    use num::Zero;
+
+    use super::*;
+    use crate::chip::ec::edwards::ed25519::Ed25519BaseField;
+    use crate::chip::ec::point::AffinePoint;
+    use crate::chip::field::parameters::FieldParameters;
+    use crate::chip::hash::sha::sha512::generator::SHA512_NUM_BLOCKS;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    // Test 2 of RFC 8032, section 7.1.
+    const PUBKEY: &str = "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c";
+    const MESSAGE: &str = "72";
+    const SIGNATURE: &str = concat!(
+        "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da",
+        "085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00"
+    );
+
+    /// The compressed encoding of a point: the little-endian bytes of `y`, with the sign of `x`
+    /// in the most significant bit.
+    fn encode_point(point: &AffinePoint<Ed25519>) -> Vec<u8> {
+        let mut bytes = point.y.to_bytes_le();
+        bytes.resize(32, 0);
+        if point.x.bit(0) {
+            bytes[31] |= 0x80;
+        }
+        bytes
+    }
+
+    fn prove_verify(pubkey: &[u8], message: &[u8], signature: &[u8], expected_valid: bool) {
+        type F = GoldilocksField;
+        type E = GoldilocksCubicParameters;
+        type SC = CurtaPoseidonGoldilocksConfig;
+        type C = PoseidonGoldilocksConfig;
+        const D: usize = 2;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let mut gadget: SHA512BuilderGadget<F, E, D> = builder.init_sha512();
+        let target = builder.add_virtual_ed25519_verify::<E, SC>(message.len(), &mut gadget);
+        let expected = builder.constant_bool(expected_valid);
+        builder.connect(target.valid.target, expected.target);
+
+        // Fill the remaining blocks of the SHA-512 trace with empty messages.
+        let num_blocks = gadget.chunk_sizes.iter().sum::<usize>();
+        for _ in num_blocks..SHA512_NUM_BLOCKS {
+            sha512_message(&mut builder, &[], &mut gadget);
+        }
+        builder.constrain_sha512_gadget::<SC>(gadget);
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+
+        let bytes = [
+            (&target.pubkey.0[..], pubkey),
+            (&target.message[..], message),
+            (&target.signature.0[..], signature),
+        ];
+        for (targets, values) in bytes {
+            for (target, value) in targets.iter().zip(values.iter()) {
+                pw.set_target(*target, F::from_canonical_u8(*value));
+            }
+        }
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+
+    #[test]
+    fn test_ed25519_verify() {
+        let message = hex::decode(MESSAGE).unwrap();
+        let signature = hex::decode(SIGNATURE).unwrap();
+        let pubkey = hex::decode(PUBKEY).unwrap();
+        prove_verify(&pubkey, &message, &signature, true);
+    }
+
+    #[test]
+    fn test_ed25519_verify_wrong_message() {
+        let signature = hex::decode(SIGNATURE).unwrap();
+        let pubkey = hex::decode(PUBKEY).unwrap();
+        prove_verify(&pubkey, &[0x73], &signature, false);
+    }
+
+    #[test]
+    fn test_ed25519_verify_unreduced_s() {
+        // Adding `l` to `s` gives a signature for which the equation still holds.
+        let message = hex::decode(MESSAGE).unwrap();
+        let mut signature = hex::decode(SIGNATURE).unwrap();
+        let s = BigUint::from_bytes_le(&signature[32..]) + Ed25519::prime_group_order();
+        let mut s_bytes = s.to_bytes_le();
+        s_bytes.resize(32, 0);
+        signature[32..].copy_from_slice(&s_bytes);
+        let pubkey = hex::decode(PUBKEY).unwrap();
+        prove_verify(&pubkey, &message, &signature, false);
+    }
+
+    #[test]
+    fn test_ed25519_verify_small_order_component() {
+        // Sign with the nonce point `R = [r] * B + T`, where `T = (0, -1)` has order two. The
+        // cofactored equation holds since `[8] * T` is the neutral element, but the cofactorless
+        // equation is off by `T`, so the signature is rejected.
+        let base = Ed25519::generator();
+        let order = Ed25519::prime_group_order();
+        let torsion =
+            AffinePoint::<Ed25519>::new(BigUint::zero(), Ed25519BaseField::modulus() - 1u32);
+        let secret = BigUint::from(0x1234_5678_9abc_def0u64);
+        let nonce = BigUint::from(0x0fed_cba9_8765_4321u64);
+
+        let pubkey = encode_point(&(&base * &secret));
+        let sig_r = encode_point(&(&base * &nonce + &torsion));
+        let message = hex::decode(MESSAGE).unwrap();
+        let digest = SHA512Gadget::hash(&[sig_r.clone(), pubkey.clone(), message.clone()].concat());
+        let challenge = BigUint::from_bytes_le(&digest) % &order;
+        let mut s_bytes = ((&nonce + &challenge * &secret) % &order).to_bytes_le();
+        s_bytes.resize(32, 0);
+
+        prove_verify(&pubkey, &message, &[sig_r, s_bytes].concat(), false);
+    }
+}
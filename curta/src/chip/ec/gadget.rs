@@ -1,11 +1,17 @@
+use num::{BigUint, One};
+use serde::{Deserialize, Serialize};
+
+use super::model::CurveModel;
 use super::point::{AffinePoint, AffinePointRegister};
 use super::EllipticCurveParameters;
 use crate::chip::builder::AirBuilder;
+use crate::chip::field::instruction::FromFieldInstruction;
 use crate::chip::field::parameters::FieldParameters;
 use crate::chip::field::register::FieldRegister;
+use crate::chip::register::bit::BitRegister;
 use crate::chip::register::Register;
 use crate::chip::trace::writer::TraceWriter;
-use crate::chip::utils::field_limbs_to_biguint;
+use crate::chip::utils::{biguint_to_bits_le, field_limbs_to_biguint};
 use crate::chip::AirParameters;
 use crate::math::prelude::*;
 use crate::polynomial::to_u16_le_limbs_polynomial;
@@ -37,6 +43,33 @@ pub trait EllipticCurveWriter<E: EllipticCurveParameters> {
     );
 }
 
+/// The checks of a curve on affine points given by the trace, for the circuits whose points are
+/// not trusted.
+pub trait CurveValidation: CurveModel {
+    /// The index of the prime subgroup in the group of points of the curve.
+    fn cofactor() -> BigUint;
+
+    /// Asserts that `p` satisfies the curve equation in every row.
+    fn assert_curve_equation<L: AirParameters>(
+        builder: &mut AirBuilder<L>,
+        p: &AffinePointRegister<Self>,
+    ) where
+        L::Instruction: FromFieldInstruction<Self::BaseField>;
+
+    /// Returns a point `q` such that `[h] * q = p`, where `h` is the cofactor, for a point `p` of
+    /// the prime subgroup.
+    fn cofactor_root(p: &AffinePoint<Self>) -> AffinePoint<Self>;
+}
+
+/// The registers of a check that a point is in the prime subgroup.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PrimeSubgroupGadget<E: EllipticCurveParameters> {
+    pub point: AffinePointRegister<E>,
+    /// A point `q` such that `[h] * q = point`, which is only needed if the cofactor `h` is not
+    /// one.
+    pub cofactor_root: Option<AffinePointRegister<E>>,
+}
+
 impl<L: AirParameters, E: EllipticCurveParameters> EllipticCurveGadget<E> for AirBuilder<L> {
     /// Allocates registers for a next affine elliptic curve point without range-checking.
     fn alloc_unchecked_ec_point(&mut self) -> AffinePointRegister<E> {
@@ -74,6 +107,92 @@ impl<L: AirParameters, E: EllipticCurveParameters> EllipticCurveGadget<E> for Ai
     }
 }
 
+impl<L: AirParameters> AirBuilder<L> {
+    /// Selects `p` if `bit` is one and `q` otherwise, coordinate by coordinate.
+    pub fn select_point<E: EllipticCurveParameters>(
+        &mut self,
+        bit: &BitRegister,
+        p: &AffinePointRegister<E>,
+        q: &AffinePointRegister<E>,
+    ) -> AffinePointRegister<E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        let x = self.select(bit, &p.x, &q.x);
+        let y = self.select(bit, &p.y, &q.y);
+        AffinePointRegister::new(x, y)
+    }
+
+    /// Asserts that the affine points `p` and `q` are equal, coordinate by coordinate.
+    ///
+    /// The coordinates are compared limb by limb, so both points must be given by the canonical
+    /// representations of their coordinates, as the results of the field instructions are.
+    pub fn assert_point_eq<E: EllipticCurveParameters>(
+        &mut self,
+        p: &AffinePointRegister<E>,
+        q: &AffinePointRegister<E>,
+    ) {
+        self.assert_equal(&p.x, &q.x);
+        self.assert_equal(&p.y, &q.y);
+    }
+
+    /// Asserts that `p` is a point of the curve in every row.
+    pub fn assert_on_curve<C: CurveValidation>(&mut self, p: &AffinePointRegister<C>)
+    where
+        L::Instruction: FromFieldInstruction<C::BaseField>,
+    {
+        C::assert_curve_equation(self, p);
+    }
+
+    /// Asserts that `p` is a point of the prime subgroup of order `n` in every row.
+    ///
+    /// If the cofactor `h` is one, every point of the curve is in the subgroup. Otherwise, as `h`
+    /// is prime to `n`, the subgroup is the image of the multiplication by `h`, and the check is
+    /// that `p = [h] * q` for a point `q` of the curve, which is written to the trace by
+    /// `write_prime_subgroup_witness`. The multiplication by `h` takes a doubling per bit of `h`
+    /// in the row.
+    ///
+    /// The coordinates of `p` are compared with those of `[h] * q` as in `assert_point_eq`, so they
+    /// must be reduced.
+    pub fn assert_in_prime_subgroup<C: CurveValidation>(
+        &mut self,
+        p: &AffinePointRegister<C>,
+    ) -> PrimeSubgroupGadget<C>
+    where
+        L::Instruction: FromFieldInstruction<C::BaseField>,
+    {
+        let cofactor = C::cofactor();
+        if cofactor.is_one() {
+            self.assert_on_curve(p);
+            return PrimeSubgroupGadget {
+                point: *p,
+                cofactor_root: None,
+            };
+        }
+
+        let root: AffinePointRegister<C> = self.alloc_ec_point();
+        self.assert_on_curve(&root);
+
+        // [h] * q, by a double-and-add over the bits of `h`, most significant first.
+        let root_model = C::from_affine(self, &root);
+        let mut multiple = root_model;
+        let bits = biguint_to_bits_le(&cofactor, cofactor.bits() as usize);
+        for bit in bits.iter().rev().skip(1) {
+            multiple = C::double(self, &multiple);
+            if *bit {
+                multiple = C::add(self, &multiple, &root_model);
+            }
+        }
+        let multiple = C::to_affine(self, &multiple);
+        self.assert_point_eq(&multiple, p);
+
+        PrimeSubgroupGadget {
+            point: *p,
+            cofactor_root: Some(root),
+        }
+    }
+}
+
 impl<F: PrimeField64, E: EllipticCurveParameters> EllipticCurveWriter<E> for TraceWriter<F> {
     fn read_ec_point(&self, data: &AffinePointRegister<E>, row_index: usize) -> AffinePoint<E> {
         let p_x = self.read(&data.x, row_index);
@@ -97,3 +216,309 @@ impl<F: PrimeField64, E: EllipticCurveParameters> EllipticCurveWriter<E> for Tra
         self.write(&data.y, &value_y, row_index);
     }
 }
+
+impl<F: PrimeField64> TraceWriter<F> {
+    /// Writes the witness of the subgroup check of `gadget` for the point `p` of the prime
+    /// subgroup.
+    pub fn write_prime_subgroup_witness<C: CurveValidation>(
+        &self,
+        gadget: &PrimeSubgroupGadget<C>,
+        p: &AffinePoint<C>,
+        row_index: usize,
+    ) {
+        if let Some(root) = gadget.cofactor_root {
+            self.write_ec_point(&root, &C::cofactor_root(p), row_index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::BigUint;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::ec::edwards::ed25519::{Ed25519, Ed25519BaseField};
+    use crate::chip::ec::edwards::EdwardsParameters;
+    use crate::chip::ec::weierstrass::secp256k1::{Secp256k1, Secp256k1BaseField};
+    use crate::chip::ec::weierstrass::WeierstrassParameters;
+    use crate::chip::field::instruction::FpInstruction;
+    use crate::chip::field::parameters::FieldParameters;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct SelectPointTest;
+
+    impl AirParameters for SelectPointTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 96;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 153;
+
+        type Instruction = FpInstruction<Ed25519BaseField>;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    #[test]
+    fn test_select_point() {
+        type F = GoldilocksField;
+        type L = SelectPointTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type E = Ed25519;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let bit = builder.alloc::<BitRegister>();
+        let p = builder.alloc_ec_point();
+        let q = builder.alloc_ec_point();
+        let result = builder.select_point::<E>(&bit, &p, &q);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let base = E::generator();
+        let base_double = &base + &base;
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            let writer = generator.new_writer();
+            // Alternate between the two values of the bit.
+            let bit_value = i % 2 == 0;
+            writer.write(&bit, &F::from_canonical_u8(bit_value as u8), i);
+            writer.write_ec_point(&p, &base, i);
+            writer.write_ec_point(&q, &base_double, i);
+            writer.write_row_instructions(&generator.air_data, i);
+
+            let expected = if bit_value { &base } else { &base_double };
+            assert_eq!(&writer.read_ec_point(&result, i), expected);
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct AssertPointEqTest;
+
+    impl AirParameters for AssertPointEqTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 800;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 1209;
+
+        type Instruction = FpInstruction<Ed25519BaseField>;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    /// Asserts that `G + G`, computed in the AIR, is equal to `[2]G` computed by a native scalar
+    /// multiplication, adding one to the x-coordinate of the latter if `perturb` is true.
+    fn prove_point_eq(perturb: bool) {
+        type L = AssertPointEqTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type E = Ed25519;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let p = builder.alloc_ec_point();
+        let expected = builder.alloc_ec_point();
+        let sum = builder.ed_add::<E>(&p, &p).result;
+        builder.assert_point_eq(&sum, &expected);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let base = E::generator();
+        let mut base_double = &base * BigUint::from(2u32);
+        if perturb {
+            base_double.x += 1u32;
+        }
+        let writer = generator.new_writer();
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            writer.write_ec_point(&p, &base, i);
+            writer.write_ec_point(&expected, &base_double, i);
+            writer.write_row_instructions(&generator.air_data, i);
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+
+    #[test]
+    fn test_assert_point_eq() {
+        prove_point_eq(false);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_assert_point_eq_perturbed() {
+        prove_point_eq(true);
+    }
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct Ed25519SubgroupTest;
+
+    impl AirParameters for Ed25519SubgroupTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 3000;
+        const NUM_FREE_COLUMNS: usize = 4;
+        const EXTENDED_COLUMNS: usize = 4509;
+
+        type Instruction = FpInstruction<Ed25519BaseField>;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    /// Checks that multiples of the base point are in the prime subgroup, adding the point
+    /// `(0, -1)` of order two to them if `torsion` is true.
+    fn prove_prime_subgroup(torsion: bool) {
+        type L = Ed25519SubgroupTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type E = Ed25519;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let p = builder.alloc_ec_point();
+        let gadget = builder.assert_in_prime_subgroup::<E>(&p);
+        assert!(gadget.cofactor_root.is_some());
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let base = E::generator();
+        let order_two = AffinePoint::new(BigUint::from(0u32), Ed25519BaseField::modulus() - 1u32);
+        let points = (1..=16u32)
+            .map(|k| {
+                let point = &base * BigUint::from(k);
+                if torsion {
+                    &point + &order_two
+                } else {
+                    point
+                }
+            })
+            .collect::<Vec<_>>();
+        let writer = generator.new_writer();
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            let value = &points[i % 16];
+            writer.write_ec_point(&p, value, i);
+            writer.write_prime_subgroup_witness(&gadget, value, i);
+            writer.write_row_instructions(&generator.air_data, i);
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+
+    #[test]
+    fn test_ed25519_prime_subgroup() {
+        prove_prime_subgroup(false);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_ed25519_prime_subgroup_torsion() {
+        prove_prime_subgroup(true);
+    }
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct Secp256k1OnCurveTest;
+
+    impl AirParameters for Secp256k1OnCurveTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 420;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 639;
+
+        type Instruction = FpInstruction<Secp256k1BaseField>;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    /// Checks that multiples of the generator of secp256k1 are on the curve, which is of prime
+    /// order, adding one to their y-coordinates if `perturb` is true.
+    fn prove_secp256k1_on_curve(perturb: bool) {
+        type L = Secp256k1OnCurveTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type E = Secp256k1;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let p = builder.alloc_ec_point();
+        let gadget = builder.assert_in_prime_subgroup::<E>(&p);
+        assert!(gadget.cofactor_root.is_none());
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let base = E::generator();
+        let points = (1..=16u32)
+            .map(|k| {
+                let mut point = base.sw_scalar_mul(&BigUint::from(k)).unwrap();
+                if perturb {
+                    point.y = (&point.y + 1u32) % Secp256k1BaseField::modulus();
+                }
+                point
+            })
+            .collect::<Vec<_>>();
+        let writer = generator.new_writer();
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            let value = &points[i % 16];
+            writer.write_ec_point(&p, value, i);
+            writer.write_prime_subgroup_witness(&gadget, value, i);
+            writer.write_row_instructions(&generator.air_data, i);
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+
+    #[test]
+    fn test_secp256k1_on_curve() {
+        prove_secp256k1_on_curve(false);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_secp256k1_on_curve_perturbed() {
+        prove_secp256k1_on_curve(true);
+    }
+}
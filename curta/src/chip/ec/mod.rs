@@ -2,7 +2,11 @@ use super::field::parameters::FieldParameters;
 
 pub mod edwards;
 pub mod gadget;
+pub mod model;
+pub mod montgomery;
+pub mod msm;
 pub mod point;
+pub mod weierstrass;
 
 pub trait EllipticCurveParameters: Send + Sync + Copy + 'static {
     type BaseField: FieldParameters;
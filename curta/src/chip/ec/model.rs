@@ -0,0 +1,303 @@
+//! Scalar multiplication gadgets shared by all curve models.
+//!
+//! A curve exposes its group law through `CurveModel`, in whichever coordinates suit its
+//! formulas: affine coordinates for twisted Edwards curves, whose addition is complete, and
+//! Jacobian coordinates for short Weierstrass curves. The double-and-add ladder and the
+//! multi-scalar multiplication are written once against this trait.
+
+use core::fmt::Debug;
+
+use super::point::AffinePointRegister;
+use super::EllipticCurveParameters;
+use crate::chip::builder::AirBuilder;
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::AirParameters;
+
+/// The group law of a curve, in the representation of points of its choice.
+pub trait CurveModel: EllipticCurveParameters {
+    /// The registers of a point on which the group law operates.
+    type PointRegister: Debug + Clone + Copy + Send + Sync;
+
+    /// Converts an affine point to the representation of the group law.
+    fn from_affine<L: AirParameters>(
+        builder: &mut AirBuilder<L>,
+        p: &AffinePointRegister<Self>,
+    ) -> Self::PointRegister
+    where
+        L::Instruction: FromFieldInstruction<Self::BaseField>;
+
+    /// Converts a point to affine coordinates. The point must have an affine representation.
+    fn to_affine<L: AirParameters>(
+        builder: &mut AirBuilder<L>,
+        p: &Self::PointRegister,
+    ) -> AffinePointRegister<Self>
+    where
+        L::Instruction: FromFieldInstruction<Self::BaseField>;
+
+    /// Allocates a point that is equal to the neutral element in every row.
+    fn neutral<L: AirParameters>(builder: &mut AirBuilder<L>) -> Self::PointRegister
+    where
+        L::Instruction: FromFieldInstruction<Self::BaseField>;
+
+    /// Adds two points. The addition must be complete, as the ladders below may add equal points
+    /// or the neutral element.
+    fn add<L: AirParameters>(
+        builder: &mut AirBuilder<L>,
+        p: &Self::PointRegister,
+        q: &Self::PointRegister,
+    ) -> Self::PointRegister
+    where
+        L::Instruction: FromFieldInstruction<Self::BaseField>;
+
+    /// Doubles a point.
+    fn double<L: AirParameters>(
+        builder: &mut AirBuilder<L>,
+        p: &Self::PointRegister,
+    ) -> Self::PointRegister
+    where
+        L::Instruction: FromFieldInstruction<Self::BaseField>;
+
+    /// Selects `p` if `bit` is one and `q` otherwise.
+    fn select<L: AirParameters>(
+        builder: &mut AirBuilder<L>,
+        bit: &BitRegister,
+        p: &Self::PointRegister,
+        q: &Self::PointRegister,
+    ) -> Self::PointRegister
+    where
+        L::Instruction: FromFieldInstruction<Self::BaseField>;
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Computes `scalar * point` by a double-and-add over the little-endian bits of the scalar.
+    pub fn ec_scalar_mul_bits<C: CurveModel>(
+        &mut self,
+        bits: &[BitRegister],
+        point: &C::PointRegister,
+    ) -> C::PointRegister
+    where
+        L::Instruction: FromFieldInstruction<C::BaseField>,
+    {
+        let mut result = C::neutral(self);
+        let mut temp = *point;
+        for (i, bit) in bits.iter().enumerate() {
+            let sum = C::add(self, &result, &temp);
+            result = C::select(self, bit, &sum, &result);
+            if i + 1 < bits.len() {
+                temp = C::double(self, &temp);
+            }
+        }
+        result
+    }
+
+    /// Computes `sum_j scalar_j * point_j` with a shared doubling of the accumulator (Straus'
+    /// method), where each scalar is given by its little-endian bits.
+    ///
+    /// All the scalars must have the same number of bits.
+    pub fn ec_multi_scalar_mul<C: CurveModel>(
+        &mut self,
+        scalars: &[Vec<BitRegister>],
+        points: &[C::PointRegister],
+    ) -> C::PointRegister
+    where
+        L::Instruction: FromFieldInstruction<C::BaseField>,
+    {
+        assert_eq!(
+            scalars.len(),
+            points.len(),
+            "One scalar is needed per point"
+        );
+        let num_bits = scalars.first().map_or(0, |scalar| scalar.len());
+        assert!(
+            scalars.iter().all(|scalar| scalar.len() == num_bits),
+            "All scalars must have the same number of bits"
+        );
+
+        let mut result = C::neutral(self);
+        for i in (0..num_bits).rev() {
+            // The accumulator is the neutral element before the first step.
+            if i + 1 < num_bits {
+                result = C::double(self, &result);
+            }
+            for (scalar, point) in scalars.iter().zip(points.iter()) {
+                let sum = C::add(self, &result, point);
+                result = C::select(self, &scalar[i], &sum, &result);
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::BigUint;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::ec::edwards::ed25519::{Ed25519, Ed25519BaseField};
+    use crate::chip::ec::edwards::EdwardsParameters;
+    use crate::chip::ec::gadget::{EllipticCurveGadget, EllipticCurveWriter};
+    use crate::chip::ec::point::AffinePoint;
+    use crate::chip::ec::weierstrass::bls12_381::{Bls12381, Bls12381BaseField};
+    use crate::chip::ec::weierstrass::secp256k1::{Secp256k1, Secp256k1BaseField};
+    use crate::chip::ec::weierstrass::WeierstrassParameters;
+    use crate::chip::field::instruction::FpInstruction;
+    use crate::chip::Chip;
+    use crate::math::prelude::*;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct Ed25519ModelTest;
+
+    impl AirParameters for Ed25519ModelTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 2400;
+        const NUM_FREE_COLUMNS: usize = 4;
+        const EXTENDED_COLUMNS: usize = 3609;
+        type Instruction = FpInstruction<Ed25519BaseField>;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct Secp256k1ModelTest;
+
+    impl AirParameters for Secp256k1ModelTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 12000;
+        const NUM_FREE_COLUMNS: usize = 16;
+        const EXTENDED_COLUMNS: usize = 18009;
+        type Instruction = FpInstruction<Secp256k1BaseField>;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct Bls12381ModelTest;
+
+    impl AirParameters for Bls12381ModelTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 18300;
+        const NUM_FREE_COLUMNS: usize = 16;
+        const EXTENDED_COLUMNS: usize = 27459;
+        type Instruction = FpInstruction<Bls12381BaseField>;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    /// Builds the generic scalar multiplication of a point by a scalar of `NUM_BITS` bits and
+    /// writes its trace, checking the results against `scalar_mul`.
+    ///
+    /// The points are multiples of `base`, and the scalars are nonzero so that the results have
+    /// affine coordinates.
+    fn model_scalar_mul<C, L>(
+        base: &AffinePoint<C>,
+        scalar_mul: impl Fn(&AffinePoint<C>, usize) -> AffinePoint<C> + Sync,
+    ) -> (Chip<L>, ArithmeticGenerator<L>)
+    where
+        C: CurveModel + Debug + PartialEq,
+        L: AirParameters<Field = GoldilocksField>,
+        L::Instruction: FromFieldInstruction<C::BaseField>,
+    {
+        type F = GoldilocksField;
+        const NUM_BITS: usize = 2;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let point = builder.alloc_ec_point();
+        let bits = (0..NUM_BITS)
+            .map(|_| builder.alloc::<BitRegister>())
+            .collect::<Vec<_>>();
+        let model_point = C::from_affine(&mut builder, &point);
+        let result = builder.ec_scalar_mul_bits::<C>(&bits, &model_point);
+        let result = C::to_affine(&mut builder, &result);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let points = (1..=16).map(|k| scalar_mul(base, k)).collect::<Vec<_>>();
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            let writer = generator.new_writer();
+            let scalar = 1 + i % ((1 << NUM_BITS) - 1);
+            let value = &points[i % 16];
+            writer.write_ec_point(&point, value, i);
+            for (j, bit) in bits.iter().enumerate() {
+                writer.write(bit, &F::from_canonical_usize((scalar >> j) & 1), i);
+            }
+            writer.write_row_instructions(&generator.air_data, i);
+
+            assert_eq!(writer.read_ec_point(&result, i), scalar_mul(value, scalar));
+        });
+
+        (air, generator)
+    }
+
+    #[test]
+    fn test_ed25519_model_scalar_mul() {
+        type L = Ed25519ModelTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let (air, generator) =
+            model_scalar_mul::<Ed25519, L>(&Ed25519::generator(), |p, k| p * BigUint::from(k));
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+
+    #[test]
+    fn test_secp256k1_model_scalar_mul() {
+        type L = Secp256k1ModelTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let (air, generator) = model_scalar_mul::<Secp256k1, L>(&Secp256k1::generator(), |p, k| {
+            p.sw_scalar_mul(&BigUint::from(k)).unwrap()
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+
+    #[test]
+    fn test_bls12_381_model_scalar_mul() {
+        type L = Bls12381ModelTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let (air, generator) = model_scalar_mul::<Bls12381, L>(&Bls12381::generator(), |p, k| {
+            p.sw_scalar_mul(&BigUint::from(k)).unwrap()
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+}
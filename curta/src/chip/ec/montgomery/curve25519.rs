@@ -0,0 +1,108 @@
+use num::BigUint;
+use serde::{Deserialize, Serialize};
+
+use super::{montgomery_ladder, MontgomeryParameters};
+use crate::chip::ec::edwards::ed25519::Ed25519BaseField;
+use crate::chip::ec::EllipticCurveParameters;
+use crate::chip::field::parameters::{FieldParameters, MAX_NB_LIMBS};
+
+/// Curve25519 in Montgomery form, `v^2 = u^3 + 486662 * u^2 + u`, which is birationally
+/// equivalent to Ed25519.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Curve25519;
+
+impl EllipticCurveParameters for Curve25519 {
+    type BaseField = Ed25519BaseField;
+}
+
+impl MontgomeryParameters for Curve25519 {
+    // a24 = (486662 - 2) / 4 = 121665
+    const A24: [u16; MAX_NB_LIMBS] = [
+        56129, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0,
+    ];
+}
+
+/// The u-coordinate of the base point of X25519.
+pub const X25519_BASE_POINT: u32 = 9;
+
+/// Decodes an X25519 scalar, clearing the three least significant bits and the most significant
+/// bit and setting the second most significant bit.
+pub fn clamp_scalar(scalar: &[u8; 32]) -> BigUint {
+    let mut bytes = *scalar;
+    bytes[0] &= 248;
+    bytes[31] &= 127;
+    bytes[31] |= 64;
+    BigUint::from_bytes_le(&bytes)
+}
+
+/// Decodes an X25519 u-coordinate, ignoring the most significant bit and reducing the
+/// non-canonical values.
+pub fn decode_u_coordinate(u: &[u8; 32]) -> BigUint {
+    let mut bytes = *u;
+    bytes[31] &= 127;
+    BigUint::from_bytes_le(&bytes) % Ed25519BaseField::modulus()
+}
+
+/// The X25519 function of RFC 7748, computing `[k] * u` for the clamped scalar `k`.
+pub fn x25519(scalar: &[u8; 32], u: &[u8; 32]) -> [u8; 32] {
+    let k = clamp_scalar(scalar);
+    let u = decode_u_coordinate(u);
+    let mut bytes =
+        montgomery_ladder::<Curve25519>(&k, &u, Curve25519::nb_scalar_bits()).to_bytes_le();
+    bytes.resize(32, 0);
+    bytes.try_into().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_hex(s: &str) -> [u8; 32] {
+        hex::decode(s).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_x25519_vectors() {
+        // Section 5.2 of RFC 7748.
+        let vectors = [
+            (
+                "a546e36bf0527c9d3b16154b82465edd62144c0ac1fc5a18506a2244ba449ac4",
+                "e6db6867583030db3594c1a424b15f7c726624ec26b3353b10a903a6d0ab1c4c",
+                "c3da55379de9c6908e94ea4df28d084f32eccf03491c71f754b4075577a28552",
+            ),
+            (
+                "4b66e9d4d1b4673c5ad22691957d6af5c11b6421e0ea01d42ca4169e7918ba0d",
+                "e5210f12786811d3f4b7959d0538ae2c31dbe7106fc03c3efc4cd549c715a493",
+                "95cbde9476e8907d7aade45cb4b873f88b595a68799fa152e6f8f7647aac7957",
+            ),
+        ];
+        for (scalar, u, expected) in vectors {
+            assert_eq!(x25519(&from_hex(scalar), &from_hex(u)), from_hex(expected));
+        }
+    }
+
+    #[test]
+    fn test_x25519_key_agreement() {
+        // Section 6.1 of RFC 7748.
+        let alice = from_hex("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a");
+        let bob = from_hex("5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb");
+        let mut base_point = [0u8; 32];
+        base_point[0] = X25519_BASE_POINT as u8;
+
+        let alice_public = x25519(&alice, &base_point);
+        let bob_public = x25519(&bob, &base_point);
+        assert_eq!(
+            alice_public,
+            from_hex("8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a")
+        );
+        assert_eq!(
+            bob_public,
+            from_hex("de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f")
+        );
+
+        let shared = from_hex("4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742");
+        assert_eq!(x25519(&alice, &bob_public), shared);
+        assert_eq!(x25519(&bob, &alice_public), shared);
+    }
+}
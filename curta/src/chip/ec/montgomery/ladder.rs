@@ -0,0 +1,261 @@
+use num::BigUint;
+use serde::{Deserialize, Serialize};
+
+use super::MontgomeryParameters;
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::builder::AirBuilder;
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::field::register::FieldRegister;
+use crate::chip::instruction::cycle::Cycle;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::Register;
+use crate::chip::trace::writer::TraceWriter;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::to_u16_le_limbs_polynomial;
+
+/// The registers of the state of the ladder, the projective u-coordinates `(x2 : z2)` and
+/// `(x3 : z3)`, after a step.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct MontLadderState<E: MontgomeryParameters> {
+    pub x2: FieldRegister<E::BaseField>,
+    pub z2: FieldRegister<E::BaseField>,
+    pub x3: FieldRegister<E::BaseField>,
+    pub z3: FieldRegister<E::BaseField>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MontLadderGadget<F, E: MontgomeryParameters> {
+    pub cycle: Cycle<F>,
+    pub bit: BitRegister,
+    /// The u-coordinate of the input point, which is constant in every cycle.
+    pub u: FieldRegister<E::BaseField>,
+    pub state: MontLadderState<E>,
+    pub state_next: MontLadderState<E>,
+    /// The u-coordinate `x2 / z2` of the state after the step of the row. On the last row of a
+    /// cycle, it is the result of the ladder.
+    pub result: FieldRegister<E::BaseField>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Computes one step of the Montgomery ladder with the formulas of RFC 7748. The two points of
+    /// the state are swapped before and after the step if `bit` is one, so that the step takes
+    /// the same operations for both values of the bit:
+    ///
+    /// (x2 : z2) = 2 * (x2 : z2)
+    /// (x3 : z3) = (x2 : z2) + (x3 : z3)
+    pub fn mont_ladder_step<E: MontgomeryParameters>(
+        &mut self,
+        bit: &BitRegister,
+        u: &FieldRegister<E::BaseField>,
+        state: &MontLadderState<E>,
+    ) -> MontLadderState<E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        let x2 = self.select(bit, &state.x3, &state.x2);
+        let z2 = self.select(bit, &state.z3, &state.z2);
+        let x3 = self.select(bit, &state.x2, &state.x3);
+        let z3 = self.select(bit, &state.z2, &state.z3);
+
+        let a = self.fp_add(&x2, &z2);
+        let aa = self.fp_mul(&a, &a).result;
+        let b = self.fp_sub(&x2, &z2);
+        let bb = self.fp_mul(&b, &b).result;
+        let e = self.fp_sub(&aa, &bb);
+        let c = self.fp_add(&x3, &z3);
+        let d = self.fp_sub(&x3, &z3);
+        let da = self.fp_mul(&d, &a).result;
+        let cb = self.fp_mul(&c, &b).result;
+
+        // x3 = (da + cb)^2, z3 = u * (da - cb)^2.
+        let sum = self.fp_add(&da, &cb);
+        let difference = self.fp_sub(&da, &cb);
+        let x3_next = self.fp_mul(&sum, &sum).result;
+        let difference_squared = self.fp_mul(&difference, &difference).result;
+        let z3_next = self.fp_mul(u, &difference_squared).result;
+
+        // x2 = aa * bb, z2 = e * (aa + a24 * e).
+        let x2_next = self.fp_mul(&aa, &bb).result;
+        let a24_e = self.fp_mul_const(&e, E::A24).result;
+        let aa_plus_a24_e = self.fp_add(&aa, &a24_e);
+        let z2_next = self.fp_mul(&e, &aa_plus_a24_e).result;
+
+        MontLadderState {
+            x2: self.select(bit, &x3_next, &x2_next),
+            z2: self.select(bit, &z3_next, &z2_next),
+            x3: self.select(bit, &x2_next, &x3_next),
+            z3: self.select(bit, &z2_next, &z3_next),
+        }
+    }
+
+    /// Computes the u-coordinate of `[k] * P` in cycles of 256 rows, one step of the ladder per
+    /// row. The bits of the scalar `k` are given in `bit`, most significant first, and the
+    /// u-coordinate of `P` in `u` on the first row of the cycle.
+    ///
+    /// The state starts from `(1 : 0)` and `(u : 1)` on the first row of every cycle, and the
+    /// result is read on its last row. If `[k] * P` is the point at infinity, which happens only
+    /// for points of small order, the result cannot be computed and the trace is not valid.
+    pub fn mont_ladder<E: MontgomeryParameters>(
+        &mut self,
+        bit: &BitRegister,
+        u: &FieldRegister<E::BaseField>,
+    ) -> MontLadderGadget<L::Field, E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        let cycle = self.cycle(8);
+
+        let state = MontLadderState {
+            x2: self.alloc::<FieldRegister<E::BaseField>>(),
+            z2: self.alloc::<FieldRegister<E::BaseField>>(),
+            x3: self.alloc::<FieldRegister<E::BaseField>>(),
+            z3: self.alloc::<FieldRegister<E::BaseField>>(),
+        };
+        let state_next = self.mont_ladder_step(bit, u, &state);
+
+        // The initial state of every cycle.
+        let nb_limbs = E::BaseField::NB_LIMBS;
+        let mut one_limbs = vec![L::Field::ZERO; nb_limbs];
+        one_limbs[0] = L::Field::ONE;
+        let one = ArithmeticExpression::from_constant_vec(one_limbs);
+        let start = cycle.start_bit.expr::<L::Field>();
+        self.assert_expression_zero(start.clone() * (state.x2.expr() - one.clone()));
+        self.assert_expression_zero(start.clone() * state.z2.expr());
+        self.assert_expression_zero(start.clone() * (state.x3.expr() - u.expr()));
+        self.assert_expression_zero(start * (state.z3.expr() - one));
+
+        // Copy the state and `u` to the next row, except on the last row of a cycle.
+        let flag_bit = cycle.start_bit.next().expr::<L::Field>();
+        let transitions = [
+            (state.x2, state_next.x2),
+            (state.z2, state_next.z2),
+            (state.x3, state_next.x3),
+            (state.z3, state_next.z3),
+            (*u, *u),
+        ];
+        for (register, value) in transitions.iter() {
+            let next_value = flag_bit.clone() * register.next().expr()
+                + (ArithmeticExpression::one() - flag_bit.clone()) * value.expr();
+            self.set_to_expression_transition(&register.next(), next_value);
+        }
+
+        // result = x2 / z2 on the last row of a cycle. The denominator is one on the other rows,
+        // where `z2` may be zero.
+        let one = self.alloc_constant_field_register::<E::BaseField>(&BigUint::from(1u32));
+        let denominator = self.select(&cycle.end_bit, &state_next.z2, &one);
+        let result = self.fp_div(&state_next.x2, &denominator);
+
+        MontLadderGadget {
+            cycle,
+            bit: *bit,
+            u: *u,
+            state,
+            state_next,
+            result,
+        }
+    }
+}
+
+impl<F: PrimeField64> TraceWriter<F> {
+    /// Writes the u-coordinate `u` and the initial state of the ladder on the first row of a
+    /// cycle.
+    pub fn write_mont_ladder_input<E: MontgomeryParameters>(
+        &self,
+        gadget: &MontLadderGadget<F, E>,
+        u: &BigUint,
+        row_index: usize,
+    ) {
+        let one = to_u16_le_limbs_polynomial::<F, E::BaseField>(&BigUint::from(1u32));
+        let zero = to_u16_le_limbs_polynomial::<F, E::BaseField>(&BigUint::from(0u32));
+        let u = to_u16_le_limbs_polynomial::<F, E::BaseField>(u);
+        self.write(&gadget.u, &u, row_index);
+        self.write(&gadget.state.x2, &one, row_index);
+        self.write(&gadget.state.z2, &zero, row_index);
+        self.write(&gadget.state.x3, &u, row_index);
+        self.write(&gadget.state.z3, &one, row_index);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use rand::{thread_rng, Rng};
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::ec::edwards::ed25519::Ed25519BaseField;
+    use crate::chip::ec::montgomery::curve25519::{clamp_scalar, Curve25519};
+    use crate::chip::ec::montgomery::montgomery_ladder;
+    use crate::chip::field::instruction::FpInstruction;
+    use crate::chip::utils::{biguint_to_bits_le, field_limbs_to_biguint};
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    pub struct X25519LadderTest;
+
+    impl AirParameters for X25519LadderTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 2096;
+        const NUM_FREE_COLUMNS: usize = 72;
+        const EXTENDED_COLUMNS: usize = 3153;
+        type Instruction = FpInstruction<Ed25519BaseField>;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    #[test]
+    fn test_x25519_ladder() {
+        type F = GoldilocksField;
+        type L = X25519LadderTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type E = Curve25519;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let bit = builder.alloc::<BitRegister>();
+        let u = builder.alloc::<FieldRegister<Ed25519BaseField>>();
+        let gadget = builder.mont_ladder::<E>(&bit, &u);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let p = Ed25519BaseField::modulus();
+        let nb_bits = E::nb_scalar_bits();
+        let writer = generator.new_writer();
+        (0..L::num_rows() / 256).into_par_iter().for_each(|k| {
+            let starting_row = 256 * k;
+            let mut rng = thread_rng();
+            let scalar = clamp_scalar(&rng.gen::<[u8; 32]>());
+            let u_value = rng.gen_biguint_below(&p);
+
+            writer.write_mont_ladder_input(&gadget, &u_value, starting_row);
+            let scalar_bits = biguint_to_bits_le(&scalar, nb_bits);
+            for (i, bit_value) in scalar_bits.iter().rev().enumerate() {
+                let f_bit = F::from_canonical_u8(*bit_value as u8);
+                writer.write(&bit, &f_bit, starting_row + i);
+                writer.write_row_instructions(&generator.air_data, starting_row + i);
+            }
+
+            let result = writer.read(&gadget.result, starting_row + 255);
+            let expected = montgomery_ladder::<E>(&scalar, &u_value, nb_bits);
+            assert_eq!(field_limbs_to_biguint(result.coefficients()), expected);
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+}
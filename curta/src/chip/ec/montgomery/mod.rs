@@ -0,0 +1,89 @@
+//! Montgomery curves `B * v^2 = u^3 + A * u^2 + u` and the x-only Montgomery ladder.
+//!
+//! The ladder keeps the projective u-coordinates `(x2 : z2)` and `(x3 : z3)` of the points
+//! `[m] * P` and `[m + 1] * P` for the leading bits `m` of the scalar, and updates both with the
+//! same operations whatever the next bit is, which makes it constant-time.
+
+use num::{BigUint, One, Zero};
+
+use super::EllipticCurveParameters;
+use crate::chip::field::parameters::{FieldParameters, MAX_NB_LIMBS};
+
+pub mod curve25519;
+pub mod ladder;
+
+pub trait MontgomeryParameters: EllipticCurveParameters {
+    /// The constant `a24 = (A - 2) / 4` of the doubling formula of the ladder.
+    const A24: [u16; MAX_NB_LIMBS];
+
+    fn a24_biguint() -> BigUint {
+        let mut a24 = BigUint::zero();
+        for (i, limb) in Self::A24.iter().enumerate() {
+            a24 += BigUint::from(*limb) << (16 * i);
+        }
+        a24
+    }
+
+    fn nb_scalar_bits() -> usize {
+        Self::BaseField::NB_LIMBS * 16
+    }
+}
+
+/// Computes the u-coordinate of `[k] * P` for the point `P` with u-coordinate `u`, running the
+/// ladder over the `nb_bits` least significant bits of `k`, most significant first. The result is
+/// zero if `[k] * P` is the point at infinity.
+pub fn montgomery_ladder<E: MontgomeryParameters>(
+    k: &BigUint,
+    u: &BigUint,
+    nb_bits: usize,
+) -> BigUint {
+    let p = E::BaseField::modulus();
+    let a24 = E::a24_biguint();
+    let u = u % &p;
+
+    let (mut x2, mut z2) = (BigUint::one(), BigUint::zero());
+    let (mut x3, mut z3) = (u.clone(), BigUint::one());
+    for t in (0..nb_bits).rev() {
+        let bit = k.bit(t as u64);
+        if bit {
+            core::mem::swap(&mut x2, &mut x3);
+            core::mem::swap(&mut z2, &mut z3);
+        }
+        (x2, z2, x3, z3) = ladder_step(&u, &a24, &p, (&x2, &z2), (&x3, &z3));
+        if bit {
+            core::mem::swap(&mut x2, &mut x3);
+            core::mem::swap(&mut z2, &mut z3);
+        }
+    }
+
+    x2 * z2.modpow(&(&p - 2u32), &p) % &p
+}
+
+/// A step of the ladder, returning the double of `(x2 : z2)` and the sum of `(x2 : z2)` and
+/// `(x3 : z3)`, whose difference has u-coordinate `u`.
+fn ladder_step(
+    u: &BigUint,
+    a24: &BigUint,
+    p: &BigUint,
+    (x2, z2): (&BigUint, &BigUint),
+    (x3, z3): (&BigUint, &BigUint),
+) -> (BigUint, BigUint, BigUint, BigUint) {
+    let a = (x2 + z2) % p;
+    let aa = &a * &a % p;
+    let b = (x2 + p - z2) % p;
+    let bb = &b * &b % p;
+    let e = (&aa + p - &bb) % p;
+    let c = (x3 + z3) % p;
+    let d = (x3 + p - z3) % p;
+    let da = d * &a % p;
+    let cb = c * &b % p;
+
+    let sum = (&da + &cb) % p;
+    let difference = (&da + p - &cb) % p;
+    let x3 = &sum * &sum % p;
+    let z3 = u * (&difference * &difference % p) % p;
+    let x2 = &aa * &bb % p;
+    let z2 = &e * ((&aa + a24 * &e) % p) % p;
+
+    (x2, z2, x3, z3)
+}
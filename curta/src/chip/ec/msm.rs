@@ -0,0 +1,482 @@
+//! Multi-scalar multiplication `sum_i k_i * P_i` with bucket accumulation (Pippenger's method).
+//!
+//! The trace is divided in cycles of `2^cycle_log` rows, each holding one `(k_i, P_i)` pair per
+//! row except for the last one. The cycles go through the windows of `c` bits of the scalars,
+//! most significant first, and in each cycle every pair adds its point to the bucket of the
+//! digit of its scalar. The buckets are trace columns, reset at the start of every cycle.
+//!
+//! The buckets of a window are reduced to `sum_j j * B_j` during the next cycle, one bucket per
+//! row with a running sum, while the accumulated result is doubled `c` times. At the end of the
+//! cycle the reduced window is added to the result, so that the last cycle only reduces the
+//! buckets of the least significant window.
+//!
+//! The pairs are repeated in every cycle, which is enforced with a bus relating each row to the
+//! same row of the next cycle.
+
+use num::{BigUint, Zero};
+use serde::{Deserialize, Serialize};
+
+use super::gadget::{EllipticCurveGadget, EllipticCurveWriter};
+use super::point::{AffinePoint, AffinePointRegister, JacobianPointRegister};
+use super::weierstrass::ecdsa::EcdsaParameters;
+use super::EllipticCurveParameters;
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::builder::AirBuilder;
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::field::register::FieldRegister;
+use crate::chip::instruction::cycle::Cycle;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::cubic::CubicRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::TraceWriter;
+use crate::chip::utils::biguint_to_bits_le;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::to_u16_le_limbs_polynomial;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(dead_code)]
+pub struct MsmGadget<F, E: EcdsaParameters> {
+    pub cycle: Cycle<F>,
+    pub window_bits: usize,
+    pub cycle_log: usize,
+    /// The point of the pair of the row.
+    pub point: AffinePointRegister<E>,
+    /// The scalar of the pair of the row.
+    pub scalar: FieldRegister<E::ScalarField>,
+    /// The accumulated result after the current row. On the last row of the trace, it is the
+    /// multi-scalar multiplication.
+    pub result: JacobianPointRegister<E>,
+    scalar_bits: ArrayRegister<BitRegister>,
+    windows: ArrayRegister<BitRegister>,
+    digit: ArrayRegister<BitRegister>,
+    buckets: Vec<JacobianPointRegister<E>>,
+    queue: Vec<JacobianPointRegister<E>>,
+    running: JacobianPointRegister<E>,
+    sum: JacobianPointRegister<E>,
+    total: JacobianPointRegister<E>,
+    reduce_bits: ArrayRegister<BitRegister>,
+    double_bits: ArrayRegister<BitRegister>,
+    key: ElementRegister,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Computes the multi-scalar multiplication of the pairs written in every row of the trace,
+    /// with windows of `window_bits` bits and cycles of `2^cycle_log` rows.
+    ///
+    /// All the rows of the trace are used: there are `L::num_rows() >> cycle_log` cycles, all but
+    /// the last of which process a window, so their number times `window_bits` must cover the bits
+    /// of the scalars. A cycle must be long enough to reduce the `2^window_bits - 1` buckets.
+    ///
+    /// The points must be on the curve, which is not checked here.
+    pub fn msm<E: EcdsaParameters>(
+        &mut self,
+        window_bits: usize,
+        cycle_log: usize,
+    ) -> MsmGadget<L::Field, E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        let nb_buckets = (1 << window_bits) - 1;
+        let nb_windows = (L::num_rows() >> cycle_log) - 1;
+        let nb_bits = E::ScalarField::NB_LIMBS * 16;
+        assert!(
+            nb_buckets < (1 << cycle_log) - 1,
+            "A cycle must be longer than the number of buckets"
+        );
+        assert!(
+            nb_windows * window_bits >= nb_bits,
+            "The windows must cover the bits of the scalars"
+        );
+
+        let cycle = self.cycle(cycle_log);
+        let end_bit = cycle.end_bit.expr::<L::Field>();
+        let next_start_bit = cycle.start_bit.next().expr::<L::Field>();
+        let one = ArithmeticExpression::<L::Field>::one();
+        let infinity = self.jacobian_infinity::<E>();
+
+        // The pair of the row and the bits of its scalar.
+        let point: AffinePointRegister<E> = self.alloc_ec_point();
+        let scalar = self.alloc::<FieldRegister<E::ScalarField>>();
+        let scalar_bits = self.alloc_array::<BitRegister>(nb_bits);
+        let limbs = ArrayRegister::<U16Register>::from_register_unsafe(*scalar.register());
+        for (j, limb) in limbs.iter().enumerate() {
+            let mut limb_value = ArithmeticExpression::zero();
+            for k in 0..16 {
+                limb_value = limb_value
+                    + scalar_bits.get(16 * j + k).expr() * L::Field::from_canonical_u32(1 << k);
+            }
+            self.assert_expression_zero(limb_value - limb.expr());
+        }
+
+        // Each pair is repeated in the same row of the next cycle, cyclically: the row `r` puts
+        // `(w^r, P, k)` in the bus and takes `(w^(r + 2^cycle_log), P, k)` from it, where `w` is a
+        // root of unity of order the number of rows.
+        let key = self.alloc::<ElementRegister>();
+        let root = L::Field::primitive_root_of_unity(L::num_rows_bits());
+        self.set_to_expression_first_row(&key, ArithmeticExpression::one());
+        self.set_to_expression_transition(&key.next(), key.expr() * root);
+        let shift = root.pow(1 << cycle_log);
+        let challenges = self.alloc_challenge_array::<CubicRegister>(
+            1 + 2 * FieldRegister::<E::BaseField>::size_of()
+                + FieldRegister::<E::ScalarField>::size_of(),
+        );
+        let input = self.accumulate_expressions(
+            &challenges,
+            &[key.expr(), point.x.expr(), point.y.expr(), scalar.expr()],
+        );
+        let output = self.accumulate_expressions(
+            &challenges,
+            &[
+                key.expr() * shift,
+                point.x.expr(),
+                point.y.expr(),
+                scalar.expr(),
+            ],
+        );
+        let mut bus = self.new_bus();
+        let channel_idx = bus.new_channel(self);
+        self.input_to_bus(channel_idx, input);
+        self.output_from_bus(channel_idx, output);
+        self.constrain_bus(bus);
+
+        // The window of the cycle, as a one-hot array shifted down at the start of every cycle,
+        // which is zero in the last cycle, and the digit of the scalar in the window, as bits
+        // selecting a bucket. The digit bits are all zero for the digit zero and in the last row
+        // of a cycle, which holds no pair.
+        let windows = self.alloc_array::<BitRegister>(nb_windows);
+        let digit = self.alloc_array::<BitRegister>(nb_buckets);
+
+        // The buckets of the current window, and a queue of the buckets of the previous window
+        // to be reduced with the running sum.
+        let buckets = (0..nb_buckets)
+            .map(|_| self.alloc_unchecked_jacobian_point::<E>())
+            .collect::<Vec<_>>();
+        let queue = (0..nb_buckets)
+            .map(|_| self.alloc_unchecked_jacobian_point::<E>())
+            .collect::<Vec<_>>();
+        let running = self.alloc_unchecked_jacobian_point::<E>();
+        let sum = self.alloc_unchecked_jacobian_point::<E>();
+        let total = self.alloc_unchecked_jacobian_point::<E>();
+
+        // Bits that are one for the first `2^c - 1` rows of a cycle, in which the sum of the
+        // running sums is accumulated, and for the first `c` rows, in which the result is doubled.
+        let reduce_bits = self.alloc_array::<BitRegister>(nb_buckets);
+        let double_bits = self.alloc_array::<BitRegister>(window_bits);
+
+        // The values of the first row, set before the instructions reading them.
+        for (w, window) in windows.iter().enumerate() {
+            let first_value = if w == nb_windows - 1 {
+                ArithmeticExpression::one()
+            } else {
+                ArithmeticExpression::zero()
+            };
+            self.set_to_expression_first_row(&window, first_value);
+        }
+        for point in buckets
+            .iter()
+            .chain(queue.iter())
+            .chain([&running, &sum, &total])
+        {
+            for (register, inf) in coordinates(point).iter().zip(coordinates(&infinity).iter()) {
+                self.set_to_expression_first_row(register, inf.expr());
+            }
+        }
+        for bit in reduce_bits.iter().chain(double_bits.iter()) {
+            self.set_to_expression_first_row(&bit, ArithmeticExpression::one());
+        }
+
+        // Check that the digit bits select the digit of the scalar in the window.
+        let mut digit_value = ArithmeticExpression::zero();
+        for b in 0..window_bits {
+            let mut bit_value = ArithmeticExpression::zero();
+            for (w, window) in windows.iter().enumerate() {
+                if w * window_bits + b < nb_bits {
+                    bit_value =
+                        bit_value + window.expr() * scalar_bits.get(w * window_bits + b).expr();
+                }
+            }
+            digit_value = digit_value + bit_value * L::Field::from_canonical_u32(1 << b);
+        }
+        let mut selected_value = ArithmeticExpression::zero();
+        let mut nb_selected = ArithmeticExpression::zero();
+        for (j, bit) in digit.iter().enumerate() {
+            selected_value = selected_value + bit.expr() * L::Field::from_canonical_usize(j + 1);
+            nb_selected = nb_selected + bit.expr();
+        }
+        self.assert_expression_zero(digit_value - selected_value);
+        self.assert_expression_zero(nb_selected.clone() * (nb_selected.clone() - one.clone()));
+        self.assert_expression_zero(end_bit.clone() * nb_selected);
+
+        // Add the point to the selected bucket. The selected bucket is zero if no bucket is
+        // selected, which is a point at infinity.
+        let selected = self.alloc_unchecked_jacobian_point::<E>();
+        for coordinate in 0..3 {
+            let mut value = ArithmeticExpression::zero();
+            for (bit, bucket) in digit.iter().zip(buckets.iter()) {
+                value = value + bit.expr() * coordinates(bucket)[coordinate].expr();
+            }
+            self.set_to_expression(&coordinates(&selected)[coordinate], value);
+        }
+        let point_jacobian = self.affine_to_jacobian(&point);
+        let bucket_sum = self.jacobian_add(&selected, &point_jacobian);
+
+        // running = running + head, sum = sum + running, so that after `2^c - 1` rows
+        // sum = sum_j j * B_j.
+        let running_next = self.jacobian_add(&running, &queue[0]);
+        let sum_added = self.jacobian_add(&sum, &running_next);
+        let sum_next = self.select_jacobian_point(&reduce_bits.get(0), &sum_added, &sum);
+
+        // total = 2 * total in the first `c` rows, and total = total + sum in the last row.
+        let total_doubled = self.jacobian_double(&total);
+        let total_double_step =
+            self.select_jacobian_point(&double_bits.get(0), &total_doubled, &total);
+        let total_added = self.jacobian_add(&total_double_step, &sum_next);
+        let result = self.select_jacobian_point(&cycle.end_bit, &total_added, &total_double_step);
+
+        // Shift the window down at the start of a new cycle.
+        for (w, window) in windows.iter().enumerate() {
+            let shifted = if w == nb_windows - 1 {
+                ArithmeticExpression::zero()
+            } else {
+                windows.get(w + 1).expr()
+            };
+            let next_value = next_start_bit.clone() * shifted
+                + (one.clone() - next_start_bit.clone()) * window.expr();
+            self.set_to_expression_transition(&window.next(), next_value);
+        }
+
+        // The buckets are updated where they are selected, and reset after the last row of a
+        // cycle, which selects no bucket.
+        for (bit, bucket) in digit.iter().zip(buckets.iter()) {
+            for ((register, value), inf) in coordinates(bucket)
+                .iter()
+                .zip(coordinates(&bucket_sum).iter())
+                .zip(coordinates(&infinity).iter())
+            {
+                let next_value = (one.clone() - end_bit.clone()) * register.expr()
+                    + end_bit.clone() * inf.expr()
+                    + bit.expr() * (value.expr() - register.expr());
+                self.set_to_expression_transition(&register.next(), next_value);
+            }
+        }
+
+        // The buckets, from the last to the first, are loaded in the queue at the start of a new
+        // cycle. Otherwise, the queue is shifted by one so that each row reads the next bucket.
+        for (t, slot) in queue.iter().enumerate() {
+            let bucket = &buckets[nb_buckets - 1 - t];
+            let shifted = queue.get(t + 1).unwrap_or(&infinity);
+            for ((register, bucket_value), shifted_value) in coordinates(slot)
+                .iter()
+                .zip(coordinates(bucket).iter())
+                .zip(coordinates(shifted).iter())
+            {
+                let next_value = next_start_bit.clone() * bucket_value.expr()
+                    + (one.clone() - next_start_bit.clone()) * shifted_value.expr();
+                self.set_to_expression_transition(&register.next(), next_value);
+            }
+        }
+
+        // The reduction and doubling bits are shifted, and set again at the start of a new cycle.
+        for bits in [reduce_bits, double_bits] {
+            for k in 0..bits.len() {
+                let shifted = if k + 1 < bits.len() {
+                    bits.get(k + 1).expr()
+                } else {
+                    ArithmeticExpression::zero()
+                };
+                let next_value =
+                    next_start_bit.clone() + (one.clone() - next_start_bit.clone()) * shifted;
+                self.set_to_expression_transition(&bits.get(k).next(), next_value);
+            }
+        }
+
+        // The running sum and the sum are reset at the start of a new cycle, and the result is
+        // carried over.
+        for (point, value, reset) in [
+            (running, running_next, true),
+            (sum, sum_next, true),
+            (total, result, false),
+        ] {
+            for ((register, value), inf) in coordinates(&point)
+                .iter()
+                .zip(coordinates(&value).iter())
+                .zip(coordinates(&infinity).iter())
+            {
+                let next_value = if reset {
+                    next_start_bit.clone() * inf.expr()
+                        + (one.clone() - next_start_bit.clone()) * value.expr()
+                } else {
+                    value.expr()
+                };
+                self.set_to_expression_transition(&register.next(), next_value);
+            }
+        }
+
+        MsmGadget {
+            cycle,
+            window_bits,
+            cycle_log,
+            point,
+            scalar,
+            result,
+            scalar_bits,
+            windows,
+            digit,
+            buckets,
+            queue,
+            running,
+            sum,
+            total,
+            reduce_bits,
+            double_bits,
+            key,
+        }
+    }
+}
+
+impl<F: PrimeField64> TraceWriter<F> {
+    /// Writes the pairs of a multi-scalar multiplication to every cycle of the trace, padded with
+    /// zero scalars, together with the bits of the scalars. The last row of every cycle holds no
+    /// pair, so at most `2^cycle_log - 1` pairs are supported.
+    ///
+    /// The instructions must then be written row by row in order, as every row depends on the
+    /// previous one.
+    pub fn write_msm_input<E: EcdsaParameters>(
+        &self,
+        gadget: &MsmGadget<F, E>,
+        pairs: &[(AffinePoint<E>, BigUint)],
+    ) {
+        let cycle_length = 1 << gadget.cycle_log;
+        assert!(
+            pairs.len() < cycle_length,
+            "The number of pairs must be less than the cycle length"
+        );
+        let padding = (E::generator(), BigUint::zero());
+        let nb_bits = gadget.scalar_bits.len();
+        let nb_windows = gadget.windows.len();
+        for row_index in 0..self.height() {
+            let (point, scalar) = pairs.get(row_index % cycle_length).unwrap_or(&padding);
+            let (point, scalar) = if row_index % cycle_length == cycle_length - 1 {
+                (&padding.0, &padding.1)
+            } else {
+                (point, scalar)
+            };
+            self.write_ec_point(&gadget.point, point, row_index);
+            let scalar_value = to_u16_le_limbs_polynomial::<F, E::ScalarField>(scalar);
+            self.write(&gadget.scalar, &scalar_value, row_index);
+            let bit_values = biguint_to_bits_le(scalar, nb_bits)
+                .into_iter()
+                .map(|bit| F::from_canonical_u8(bit as u8));
+            self.write_array(&gadget.scalar_bits, bit_values, row_index);
+
+            // The digit of the scalar in the window of the cycle, where the cycle `t` processes
+            // the window `nb_windows - 1 - t`.
+            let window = (nb_windows - 1).checked_sub(row_index / cycle_length);
+            let digit = window
+                .map(|w| (scalar >> (w * gadget.window_bits)) % (1u32 << gadget.window_bits))
+                .unwrap_or_default();
+            let digit_values = (0..gadget.digit.len()).map(|j| {
+                if digit == BigUint::from(j + 1) {
+                    F::ONE
+                } else {
+                    F::ZERO
+                }
+            });
+            self.write_array(&gadget.digit, digit_values, row_index);
+        }
+    }
+}
+
+fn coordinates<E: EllipticCurveParameters>(
+    point: &JacobianPointRegister<E>,
+) -> [FieldRegister<E::BaseField>; 3] {
+    [point.x, point.y, point.z]
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use num::One;
+    use rand::thread_rng;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::ec::weierstrass::bn254::{Bn254, Bn254BaseField};
+    use crate::chip::ec::weierstrass::WeierstrassParameters;
+    use crate::chip::field::instruction::FpInstruction;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct MsmTest;
+
+    impl AirParameters for MsmTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 17400;
+        const NUM_FREE_COLUMNS: usize = 440;
+        const EXTENDED_COLUMNS: usize = 26150;
+
+        type Instruction = FpInstruction<Bn254BaseField>;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    #[test]
+    fn test_msm() {
+        type L = MsmTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type E = Bn254;
+
+        let mut builder = AirBuilder::<L>::new();
+        let gadget = builder.msm::<E>(4, 9);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let order = E::prime_group_order();
+        let pairs = (0..(1 << 9) - 1)
+            .into_par_iter()
+            .map(|_| {
+                let mut rng = thread_rng();
+                let a = rng.gen_biguint_range(&BigUint::one(), &order);
+                let point = E::generator().sw_scalar_mul(&a).unwrap();
+                let scalar = rng.gen_biguint_below(&order);
+                (point, scalar)
+            })
+            .collect::<Vec<_>>();
+        let expected = pairs
+            .iter()
+            .filter_map(|(point, scalar)| point.sw_scalar_mul(scalar))
+            .fold(None, |acc: Option<AffinePoint<E>>, point| match acc {
+                None => Some(point),
+                Some(acc) if acc == point => Some(acc.sw_double()),
+                Some(acc) if acc == point.sw_neg() => None,
+                Some(acc) => Some(acc.sw_add(&point)),
+            });
+
+        let writer = generator.new_writer();
+        writer.write_msm_input(&gadget, &pairs);
+        for i in 0..L::num_rows() {
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+        let result = writer.read_jacobian_point(&gadget.result, L::num_rows() - 1);
+        assert_eq!(result.to_affine(), expected);
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+}
@@ -1,8 +1,11 @@
-use num::BigUint;
+use anyhow::{ensure, Result};
+use num::{BigUint, One, Zero};
 use serde::{Deserialize, Serialize};
 
 use super::EllipticCurveParameters;
+use crate::chip::field::parameters::FieldParameters;
 use crate::chip::field::register::FieldRegister;
+use crate::chip::utils::bigint_into_u16_digits;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AffinePoint<E: EllipticCurveParameters> {
@@ -20,6 +23,40 @@ impl<E: EllipticCurveParameters> AffinePoint<E> {
             _marker: std::marker::PhantomData,
         }
     }
+
+    /// The number of bytes in the encoding of a point.
+    pub const NUM_BYTES: usize = 4 * E::BaseField::NB_LIMBS;
+
+    /// Encodes the point as the 16-bit limbs of `x` followed by those of `y`, each limb in
+    /// little-endian byte order, matching the limb layout of the base field registers.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        [&self.x, &self.y]
+            .into_iter()
+            .flat_map(|coordinate| bigint_into_u16_digits(coordinate, E::BaseField::NB_LIMBS))
+            .flat_map(u16::to_le_bytes)
+            .collect()
+    }
+
+    /// Decodes a point encoded by `to_bytes`.
+    ///
+    /// Fails if `bytes` does not have length `NUM_BYTES` or if a coordinate is not reduced
+    /// modulo the base field modulus, so that every point has a unique encoding.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        ensure!(
+            bytes.len() == Self::NUM_BYTES,
+            "Expected {} bytes for an affine point, got {}",
+            Self::NUM_BYTES,
+            bytes.len()
+        );
+        let (x_bytes, y_bytes) = bytes.split_at(Self::NUM_BYTES / 2);
+        let x = BigUint::from_bytes_le(x_bytes);
+        let y = BigUint::from_bytes_le(y_bytes);
+
+        let modulus = E::BaseField::modulus();
+        ensure!(x < modulus, "The x coordinate is not reduced");
+        ensure!(y < modulus, "The y coordinate is not reduced");
+        Ok(Self::new(x, y))
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -34,3 +71,142 @@ impl<E: EllipticCurveParameters> AffinePointRegister<E> {
         Self { x, y }
     }
 }
+
+/// A point in Jacobian coordinates `(X : Y : Z)`, representing the affine point
+/// `(X / Z^2, Y / Z^3)`. Points with `Z = 0` represent the point at infinity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JacobianPoint<E: EllipticCurveParameters> {
+    pub x: BigUint,
+    pub y: BigUint,
+    pub z: BigUint,
+    _marker: std::marker::PhantomData<E>,
+}
+
+impl<E: EllipticCurveParameters> JacobianPoint<E> {
+    pub fn new(x: BigUint, y: BigUint, z: BigUint) -> Self {
+        Self {
+            x,
+            y,
+            z,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    pub fn from_affine(point: &AffinePoint<E>) -> Self {
+        Self::new(point.x.clone(), point.y.clone(), BigUint::one())
+    }
+
+    /// The canonical representation `(1 : 1 : 0)` of the point at infinity.
+    pub fn infinity() -> Self {
+        Self::new(BigUint::one(), BigUint::one(), BigUint::zero())
+    }
+
+    pub fn is_infinity(&self) -> bool {
+        (&self.z % E::BaseField::modulus()).is_zero()
+    }
+
+    /// Converts the point to affine coordinates, returning `None` for the point at infinity.
+    pub fn to_affine(&self) -> Option<AffinePoint<E>> {
+        if self.is_infinity() {
+            return None;
+        }
+        let p = E::BaseField::modulus();
+        let z_inv = self.z.modpow(&(&p - 2u32), &p);
+        let z_inv_sq = (&z_inv * &z_inv) % &p;
+        let x = (&self.x * &z_inv_sq) % &p;
+        let y = (&self.y * &z_inv_sq * &z_inv) % &p;
+        Some(AffinePoint::new(x, y))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct JacobianPointRegister<E: EllipticCurveParameters> {
+    pub x: FieldRegister<E::BaseField>,
+    pub y: FieldRegister<E::BaseField>,
+    pub z: FieldRegister<E::BaseField>,
+}
+
+impl<E: EllipticCurveParameters> JacobianPointRegister<E> {
+    pub fn new(
+        x: FieldRegister<E::BaseField>,
+        y: FieldRegister<E::BaseField>,
+        z: FieldRegister<E::BaseField>,
+    ) -> Self {
+        Self { x, y, z }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_builder::CircuitBuilder;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+    use plonky2::util::serialization::Buffer;
+
+    use super::*;
+    use crate::chip::ec::edwards::ed25519::Ed25519;
+    use crate::chip::ec::edwards::scalar_mul::generator::{
+        AffinePointTarget, ScalarMulEd25519Gadget,
+    };
+    use crate::chip::ec::edwards::EdwardsParameters;
+    use crate::chip::utils::biguint_to_16_digits_field;
+    use crate::utils::serde::{BufferRead, BufferWrite};
+
+    #[test]
+    fn test_affine_point_bytes() {
+        type F = GoldilocksField;
+        type C = PoseidonGoldilocksConfig;
+        const D: usize = 2;
+
+        let generator = Ed25519::generator();
+        let bytes = generator.to_bytes();
+        assert_eq!(bytes.len(), AffinePoint::<Ed25519>::NUM_BYTES);
+        assert_eq!(
+            AffinePoint::<Ed25519>::from_bytes(&bytes).unwrap(),
+            generator
+        );
+
+        // Bytes of the wrong length and unreduced coordinates are rejected.
+        assert!(AffinePoint::<Ed25519>::from_bytes(&bytes[1..]).is_err());
+        assert!(AffinePoint::<Ed25519>::from_bytes(&[bytes.clone(), vec![0]].concat()).is_err());
+        let unreduced = AffinePoint::<Ed25519>::new(
+            &generator.x + <Ed25519 as EllipticCurveParameters>::BaseField::modulus(),
+            generator.y.clone(),
+        );
+        assert!(AffinePoint::<Ed25519>::from_bytes(&unreduced.to_bytes()).is_err());
+
+        // Transport the point through a plonky2 buffer.
+        let mut dst = Vec::new();
+        dst.write_affine_point(&generator).unwrap();
+        let mut src = Buffer::new(&dst);
+        let point = src.read_affine_point::<Ed25519>().unwrap();
+        assert!(Buffer::new(&dst[1..])
+            .read_affine_point::<Ed25519>()
+            .is_err());
+
+        // Check the decoded point against the generator in a circuit.
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let expected = builder.constant_affine_point(generator);
+        let decoded = AffinePointTarget {
+            x: builder.add_virtual_target_arr(),
+            y: builder.add_virtual_target_arr(),
+        };
+        builder.connect_affine_point(&expected, &decoded);
+        let data = builder.build::<C>();
+
+        let mut pw = PartialWitness::new();
+        for (targets, value) in [(decoded.x, &point.x), (decoded.y, &point.y)] {
+            for (target, limb) in targets
+                .iter()
+                .zip(biguint_to_16_digits_field::<F>(value, 16))
+            {
+                pw.set_target(*target, limb);
+            }
+        }
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+}
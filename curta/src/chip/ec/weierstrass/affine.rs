@@ -0,0 +1,181 @@
+//! The group law of short Weierstrass curves in affine coordinates.
+//!
+//! The affine formulas are not complete: the addition requires points with distinct `x`
+//! coordinates and the doubling a point with `y != 0`, and neither handles the point at infinity.
+//! They are cheaper than the Jacobian gadgets when the inputs are known to avoid these cases, as
+//! when adding a fixed point to a running sum. The scalar multiplication ladders of
+//! [`crate::chip::ec::model`] use the complete Jacobian formulas instead.
+
+use num::Zero;
+
+use super::WeierstrassParameters;
+use crate::chip::builder::AirBuilder;
+use crate::chip::ec::point::AffinePointRegister;
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::parameters::MAX_NB_LIMBS;
+use crate::chip::field::register::FieldRegister;
+use crate::chip::AirParameters;
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Adds two points with the chord formula. The points must have distinct `x` coordinates.
+    pub fn sw_affine_add<E: WeierstrassParameters>(
+        &mut self,
+        p: &AffinePointRegister<E>,
+        q: &AffinePointRegister<E>,
+    ) -> AffinePointRegister<E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        // slope = (y2 - y1) / (x2 - x1)
+        let dy = self.fp_sub(&q.y, &p.y);
+        let dx = self.fp_sub(&q.x, &p.x);
+        let slope = self.fp_div(&dy, &dx);
+
+        self.sw_affine_from_slope(&slope, p, &q.x)
+    }
+
+    /// Doubles a point with the tangent formula. The point must not have `y = 0`.
+    pub fn sw_affine_double<E: WeierstrassParameters>(
+        &mut self,
+        p: &AffinePointRegister<E>,
+    ) -> AffinePointRegister<E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        // slope = (3 * x^2 + a) / (2 * y), where `a` is omitted for curves with `a = 0`.
+        let xx = self.fp_mul(&p.x, &p.x).result;
+        let mut three = [0u16; MAX_NB_LIMBS];
+        three[0] = 3;
+        let three_xx = self.fp_mul_const(&xx, three).result;
+        let numerator = if E::a_biguint().is_zero() {
+            three_xx
+        } else {
+            let a = self.fp_constant::<E::BaseField>(&E::a_biguint());
+            self.fp_add(&three_xx, &a)
+        };
+        let two_y = self.fp_add(&p.y, &p.y);
+        let slope = self.fp_div(&numerator, &two_y);
+
+        self.sw_affine_from_slope(&slope, p, &p.x)
+    }
+
+    /// Asserts that `p` satisfies the curve equation `y^2 = x^3 + a * x + b` in every row.
+    pub fn sw_assert_on_curve<E: WeierstrassParameters>(&mut self, p: &AffinePointRegister<E>)
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        let xx = self.fp_mul(&p.x, &p.x).result;
+        let xxx = self.fp_mul(&xx, &p.x).result;
+        let xxx_plus_ax = if E::a_biguint().is_zero() {
+            xxx
+        } else {
+            let ax = self.fp_mul_const(&p.x, E::A).result;
+            self.fp_add(&xxx, &ax)
+        };
+        let b = self.fp_constant::<E::BaseField>(&E::b_biguint());
+        let rhs = self.fp_add(&xxx_plus_ax, &b);
+
+        let yy = self.fp_mul(&p.y, &p.y).result;
+        self.assert_equal(&yy, &rhs);
+    }
+
+    /// Given the slope of the line through `p` and a second point with x-coordinate `x2`, returns
+    /// the negation of the third point of the curve on the line:
+    ///
+    /// x3 = slope^2 - x1 - x2
+    /// y3 = slope * (x1 - x3) - y1
+    fn sw_affine_from_slope<E: WeierstrassParameters>(
+        &mut self,
+        slope: &FieldRegister<E::BaseField>,
+        p: &AffinePointRegister<E>,
+        x2: &FieldRegister<E::BaseField>,
+    ) -> AffinePointRegister<E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        let slope_squared = self.fp_mul(slope, slope).result;
+        let slope_squared_minus_x1 = self.fp_sub(&slope_squared, &p.x);
+        let x3 = self.fp_sub(&slope_squared_minus_x1, x2);
+
+        let x1_minus_x3 = self.fp_sub(&p.x, &x3);
+        let slope_mul_x1_minus_x3 = self.fp_mul(slope, &x1_minus_x3).result;
+        let y3 = self.fp_sub(&slope_mul_x1_minus_x3, &p.y);
+
+        AffinePointRegister::new(x3, y3)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::ec::gadget::{EllipticCurveGadget, EllipticCurveWriter};
+    use crate::chip::ec::weierstrass::secp256k1::{Secp256k1, Secp256k1BaseField};
+    use crate::chip::field::instruction::FpInstruction;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct AffineAddDoubleTest;
+
+    impl AirParameters for AffineAddDoubleTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 2212;
+        const NUM_FREE_COLUMNS: usize = 4;
+        const EXTENDED_COLUMNS: usize = 3327;
+
+        type Instruction = FpInstruction<Secp256k1BaseField>;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    #[test]
+    fn test_sw_affine_add_double() {
+        type L = AffineAddDoubleTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type E = Secp256k1;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let p: AffinePointRegister<E> = builder.alloc_ec_point();
+        let q: AffinePointRegister<E> = builder.alloc_ec_point();
+        let sum = builder.sw_affine_add(&p, &q);
+        let double = builder.sw_affine_double(&p);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        // The points G, 2G, ..., 16G, of which consecutive ones have distinct x coordinates.
+        let base = E::generator();
+        let mut points = vec![base.clone(), base.sw_double()];
+        for i in 2..16 {
+            let next = points[i - 1].sw_add(&base);
+            points.push(next);
+        }
+
+        let writer = generator.new_writer();
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            let a = &points[i % 16];
+            let b = &points[(i + 1) % 16];
+            writer.write_ec_point(&p, a, i);
+            writer.write_ec_point(&q, b, i);
+            writer.write_row_instructions(&generator.air_data, i);
+
+            assert_eq!(writer.read_ec_point(&sum, i), a.sw_add(b));
+            assert_eq!(writer.read_ec_point(&double, i), a.sw_double());
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+}
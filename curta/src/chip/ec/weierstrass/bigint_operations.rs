@@ -0,0 +1,133 @@
+use num::BigUint;
+
+use super::WeierstrassParameters;
+use crate::chip::ec::point::AffinePoint;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::field::sqrt::biguint_sqrt;
+use crate::chip::utils::biguint_to_bits_le;
+
+impl<E: WeierstrassParameters> AffinePoint<E> {
+    /// Adds two points with distinct `x` coordinates using the affine chord formula.
+    pub fn sw_add(&self, other: &AffinePoint<E>) -> AffinePoint<E> {
+        let p = E::BaseField::modulus();
+        assert_ne!(
+            &self.x % &p,
+            &other.x % &p,
+            "The chord formula requires distinct x coordinates"
+        );
+        let slope_numerator = (&p + &other.y - &self.y) % &p;
+        let slope_denominator = (&p + &other.x - &self.x) % &p;
+        let slope_denominator_inv = slope_denominator.modpow(&(&p - 2u32), &p);
+        let slope = (slope_numerator * &slope_denominator_inv) % &p;
+
+        let x_3n = (&slope * &slope + &p + &p - &self.x - &other.x) % &p;
+        let y_3n = (&slope * &(&p + &self.x - &x_3n) + &p - &self.y) % &p;
+
+        AffinePoint::new(x_3n, y_3n)
+    }
+
+    /// Doubles a point using the affine tangent formula. The point must not have `y = 0`.
+    pub fn sw_double(&self) -> AffinePoint<E> {
+        let p = E::BaseField::modulus();
+        let a = E::a_biguint();
+        let slope_numerator = (&a + &(&self.x * &self.x) * 3u32) % &p;
+        let slope_denominator = (&self.y * 2u32) % &p;
+        let slope_denominator_inv = slope_denominator.modpow(&(&p - 2u32), &p);
+        let slope = (slope_numerator * &slope_denominator_inv) % &p;
+
+        let x_3n = (&slope * &slope + &p + &p - &self.x - &self.x) % &p;
+        let y_3n = (&slope * &(&p + &self.x - &x_3n) + &p - &self.y) % &p;
+
+        AffinePoint::new(x_3n, y_3n)
+    }
+
+    pub fn sw_neg(&self) -> AffinePoint<E> {
+        let p = E::BaseField::modulus();
+        AffinePoint::new(self.x.clone(), (&p - &self.y) % &p)
+    }
+
+    /// Computes `scalar * self`, returning `None` if the result is the point at infinity.
+    pub fn sw_scalar_mul(&self, scalar: &BigUint) -> Option<AffinePoint<E>> {
+        let mut result: Option<AffinePoint<E>> = None;
+        let mut temp = self.clone();
+        let bits = biguint_to_bits_le(scalar, E::nb_scalar_bits());
+        for bit in bits {
+            if bit {
+                result = match result {
+                    None => Some(temp.clone()),
+                    Some(r) if r == temp => Some(r.sw_double()),
+                    Some(r) if r == temp.sw_neg() => None,
+                    Some(r) => Some(r.sw_add(&temp)),
+                };
+            }
+            temp = temp.sw_double();
+        }
+        result
+    }
+
+    /// Maps the field element `u` to a point by try-and-increment. Returns the first increment
+    /// `i < nb_tries` for which `x = u + i` is the x-coordinate of a point, together with the point
+    /// whose y-coordinate is at most `(p - 1) / 2`.
+    pub fn sw_map_to_curve(u: &BigUint, nb_tries: usize) -> Option<(usize, AffinePoint<E>)> {
+        let p = E::BaseField::modulus();
+        (0..nb_tries).find_map(|i| {
+            let x = (u + i) % &p;
+            let rhs = (&x * &x * &x + E::a_biguint() * &x + E::b_biguint()) % &p;
+            biguint_sqrt(&rhs, &p).map(|y| (i, AffinePoint::new(x, y)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use num::One;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::ec::weierstrass::secp256k1::Secp256k1;
+    use crate::chip::ec::EllipticCurveParameters;
+
+    #[test]
+    fn test_bigint_sw_add() {
+        type E = Secp256k1;
+        let base = E::generator();
+        let p = <E as EllipticCurveParameters>::BaseField::modulus();
+
+        // The generator lies on the curve y^2 = x^3 + 7.
+        assert_eq!(
+            (&base.y * &base.y) % &p,
+            (&base.x * &base.x * &base.x + E::b_biguint()) % &p
+        );
+
+        let two_base = base.sw_double();
+        let three_base = two_base.sw_add(&base);
+        assert_eq!(three_base, base.sw_add(&two_base));
+        assert_eq!(three_base.sw_add(&base), two_base.sw_double());
+        assert_eq!(base.sw_scalar_mul(&BigUint::from(3u32)), Some(three_base));
+    }
+
+    #[test]
+    fn test_biguint_sw_scalar_mul() {
+        type E = Secp256k1;
+        let base = E::generator();
+        let order = E::prime_group_order();
+
+        assert_eq!(base.sw_scalar_mul(&order), None);
+        assert_eq!(
+            base.sw_scalar_mul(&(&order + BigUint::one())),
+            Some(base.clone())
+        );
+
+        let mut rng = thread_rng();
+        for _ in 0..10 {
+            let x = rng.gen_biguint(24);
+            let y = rng.gen_biguint(25);
+
+            let x_base = base.sw_scalar_mul(&x).unwrap();
+            let y_x_base = x_base.sw_scalar_mul(&y).unwrap();
+            let xy_base = base.sw_scalar_mul(&(&x * &y)).unwrap();
+            assert_eq!(y_x_base, xy_base);
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use num::{BigUint, Num};
+use serde::{Deserialize, Serialize};
+
+use super::WeierstrassParameters;
+use crate::chip::ec::point::AffinePoint;
+use crate::chip::ec::EllipticCurveParameters;
+use crate::chip::field::parameters::{FieldParameters, MAX_NB_LIMBS};
+
+/// The group G1 of the BLS12-381 curve, `y^2 = x^3 + 4` over a 381-bit prime field.
+///
+/// The curve has a cofactor, so the group order returned by the parameters is the order of the
+/// prime subgroup generated by `generator()`.
+///
+/// The group law is given by the Jacobian gadgets through the [`CurveModel`] of the curve, which
+/// also provides the scalar multiplications of [`crate::chip::ec::model`], and by the affine
+/// gadgets of [`super::affine`].
+///
+/// [`CurveModel`]: crate::chip::ec::model::CurveModel
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Bls12381;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Bls12381BaseField;
+
+impl FieldParameters for Bls12381BaseField {
+    const NB_BITS_PER_LIMB: usize = 16;
+    const NB_LIMBS: usize = 24;
+    const NB_WITNESS_LIMBS: usize = 2 * Self::NB_LIMBS - 2;
+    const MODULUS: [u16; MAX_NB_LIMBS] = [
+        43691, 65535, 65535, 47614, 65535, 45395, 65534, 7851, 63012, 63152, 53920, 26416, 4799,
+        62341, 19332, 25719, 44247, 17227, 42934, 19227, 59034, 14719, 4586, 6657, 0, 0, 0, 0, 0,
+        0, 0, 0,
+    ];
+    const WITNESS_OFFSET: usize = 1usize << 21;
+}
+
+/// The field of integers modulo the order of the prime subgroup of BLS12-381.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Bls12381ScalarField;
+
+impl FieldParameters for Bls12381ScalarField {
+    const NB_BITS_PER_LIMB: usize = 16;
+    const NB_LIMBS: usize = 16;
+    const NB_WITNESS_LIMBS: usize = 2 * Self::NB_LIMBS - 2;
+    const MODULUS: [u16; MAX_NB_LIMBS] = [
+        1, 0, 65535, 65535, 23550, 65534, 41986, 21437, 55301, 2465, 55304, 13113, 32072, 10653,
+        42835, 29677, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    const WITNESS_OFFSET: usize = 1usize << 20;
+}
+
+impl EllipticCurveParameters for Bls12381 {
+    type BaseField = Bls12381BaseField;
+}
+
+impl WeierstrassParameters for Bls12381 {
+    const A: [u16; MAX_NB_LIMBS] = [0; MAX_NB_LIMBS];
+    const B: [u16; MAX_NB_LIMBS] = [
+        4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0,
+    ];
+
+    fn prime_group_order() -> BigUint {
+        BigUint::from_str_radix(
+            "73EDA753299D7D483339D80809A1D80553BDA402FFFE5BFEFFFFFFFF00000001",
+            16,
+        )
+        .unwrap()
+    }
+
+    fn cofactor() -> BigUint {
+        BigUint::from_str_radix("396C8C005555E1568C00AAAB0000AAAB", 16).unwrap()
+    }
+
+    fn generator() -> AffinePoint<Self> {
+        let x = BigUint::from_str_radix(
+            concat!(
+                "17F1D3A73197D7942695638C4FA9AC0FC3688C4F9774B905A14E3A3F171BAC58",
+                "6C55E83FF97A1AEFFB3AF00ADB22C6BB"
+            ),
+            16,
+        )
+        .unwrap();
+        let y = BigUint::from_str_radix(
+            concat!(
+                "08B3F481E3AAA0F1A09E30ED741D8AE4FCF5E095D5D00AF600DB18CB2C04B3ED",
+                "D03CC744A2888AE40CAA232946C5E7E1"
+            ),
+            16,
+        )
+        .unwrap();
+        AffinePoint::new(x, y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bls12_381_parameters() {
+        type E = Bls12381;
+        let p = Bls12381BaseField::modulus();
+        let base = E::generator();
+
+        assert_eq!(Bls12381ScalarField::modulus(), E::prime_group_order());
+        assert_eq!(
+            p,
+            BigUint::from_str_radix(
+                concat!(
+                    "1A0111EA397FE69A4B1BA7B6434BACD764774B84F38512BF6730D2A0F6B0F624",
+                    "1EABFFFEB153FFFFB9FEFFFFFFFFAAAB"
+                ),
+                16
+            )
+            .unwrap()
+        );
+
+        // The generator lies on the curve y^2 = x^3 + 4, and has the order of the subgroup.
+        assert_eq!(
+            (&base.y * &base.y) % &p,
+            (&base.x * &base.x * &base.x + E::b_biguint()) % &p
+        );
+        assert_eq!(base.sw_scalar_mul(&E::prime_group_order()), None);
+    }
+}
@@ -0,0 +1,97 @@
+use num::{BigUint, Num};
+use serde::{Deserialize, Serialize};
+
+use super::WeierstrassParameters;
+use crate::chip::ec::point::AffinePoint;
+use crate::chip::ec::EllipticCurveParameters;
+use crate::chip::field::parameters::{FieldParameters, MAX_NB_LIMBS};
+
+/// The group G1 of the BN254 curve, also known as alt_bn128, used by the EVM precompiles.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Bn254;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Bn254BaseField;
+
+impl FieldParameters for Bn254BaseField {
+    const NB_BITS_PER_LIMB: usize = 16;
+    const NB_LIMBS: usize = 16;
+    const NB_WITNESS_LIMBS: usize = 2 * Self::NB_LIMBS - 2;
+    const MODULUS: [u16; MAX_NB_LIMBS] = [
+        64839, 55420, 35862, 15392, 51853, 26737, 27281, 38785, 22621, 33153, 17846, 47184, 41001,
+        57649, 20082, 12388, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    const WITNESS_OFFSET: usize = 1usize << 20;
+}
+
+/// The field of integers modulo the order of the BN254 group.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Bn254ScalarField;
+
+impl FieldParameters for Bn254ScalarField {
+    const NB_BITS_PER_LIMB: usize = 16;
+    const NB_LIMBS: usize = 16;
+    const NB_WITNESS_LIMBS: usize = 2 * Self::NB_LIMBS - 2;
+    const MODULUS: [u16; MAX_NB_LIMBS] = [
+        1, 61440, 62867, 17377, 28817, 31161, 59464, 10291, 22621, 33153, 17846, 47184, 41001,
+        57649, 20082, 12388, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    const WITNESS_OFFSET: usize = 1usize << 20;
+}
+
+impl EllipticCurveParameters for Bn254 {
+    type BaseField = Bn254BaseField;
+}
+
+impl WeierstrassParameters for Bn254 {
+    const A: [u16; MAX_NB_LIMBS] = [0; MAX_NB_LIMBS];
+    const B: [u16; MAX_NB_LIMBS] = [
+        3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0,
+    ];
+
+    fn prime_group_order() -> BigUint {
+        BigUint::from_str_radix(
+            "30644E72E131A029B85045B68181585D2833E84879B9709143E1F593F0000001",
+            16,
+        )
+        .unwrap()
+    }
+
+    fn cofactor() -> BigUint {
+        BigUint::from(1u32)
+    }
+
+    fn generator() -> AffinePoint<Self> {
+        AffinePoint::new(BigUint::from(1u32), BigUint::from(2u32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bn254_parameters() {
+        type E = Bn254;
+        let p = Bn254BaseField::modulus();
+        let base = E::generator();
+
+        assert_eq!(Bn254ScalarField::modulus(), E::prime_group_order());
+        assert_eq!(
+            p,
+            BigUint::from_str_radix(
+                "30644E72E131A029B85045B68181585D97816A916871CA8D3C208C16D87CFD47",
+                16
+            )
+            .unwrap()
+        );
+
+        // The generator lies on the curve y^2 = x^3 + 3, and has the order of the group.
+        assert_eq!(
+            (&base.y * &base.y) % &p,
+            (&base.x * &base.x * &base.x + E::b_biguint()) % &p
+        );
+        assert_eq!(base.sw_scalar_mul(&E::prime_group_order()), None);
+    }
+}
@@ -0,0 +1,911 @@
+use num::{BigUint, Zero};
+use serde::{Deserialize, Serialize};
+
+use super::p256::{P256BaseField, P256ScalarField};
+use super::secp256k1::{Secp256k1BaseField, Secp256k1ScalarField};
+use super::WeierstrassParameters;
+use crate::air::AirConstraint;
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::bignum::instruction::BigNumInstruction;
+use crate::chip::bignum::sub::BigNumSubInstruction;
+use crate::chip::bool::SelectInstruction;
+use crate::chip::builder::AirBuilder;
+use crate::chip::ec::gadget::EllipticCurveWriter;
+use crate::chip::ec::point::{
+    AffinePoint, AffinePointRegister, JacobianPoint, JacobianPointRegister,
+};
+use crate::chip::field::add::FpAddInstruction;
+use crate::chip::field::den::FpDenInstruction;
+use crate::chip::field::div::FpDivInstruction;
+use crate::chip::field::inner_product::FpInnerProductInstruction;
+use crate::chip::field::instruction::{FpInstruction, FromFieldInstruction};
+use crate::chip::field::is_zero::FpIsZeroInstruction;
+use crate::chip::field::mul::FpMulInstruction;
+use crate::chip::field::mul_const::FpMulConstInstruction;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::field::register::FieldRegister;
+use crate::chip::field::sqrt::FpSqrtInstruction;
+use crate::chip::field::sub::FpSubInstruction;
+use crate::chip::instruction::cycle::Cycle;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::memory::MemorySlice;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::TraceWriter;
+use crate::chip::utils::biguint_to_bits_le;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::parser::PolynomialParser;
+use crate::polynomial::to_u16_le_limbs_polynomial;
+
+/// Parameters of a Weierstrass curve used for ECDSA signatures.
+pub trait EcdsaParameters: WeierstrassParameters {
+    /// The field of integers modulo the order of the generator.
+    type ScalarField: FieldParameters;
+}
+
+/// Verification of ECDSA signatures, one signature per cycle of `2^8` rows.
+///
+/// The inputs are read at the first row of each cycle and copied to the other rows of the cycle.
+/// The scalars `u1` and `u2` are decomposed into bits at the first row, and each row adds one bit
+/// of both scalars to the accumulator `R`, most significant bit first. The signature is accepted
+/// at the last row of the cycle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(dead_code)]
+pub struct EcdsaGadget<F, E: EcdsaParameters> {
+    pub cycle: Cycle<F>,
+    pub public_key: AffinePointRegister<E>,
+    pub message_hash: FieldRegister<E::ScalarField>,
+    pub r: FieldRegister<E::ScalarField>,
+    pub s: FieldRegister<E::ScalarField>,
+    /// The value of `R` after the current row.
+    pub result: JacobianPointRegister<E>,
+    /// Whether `R.x = r + n` rather than `R.x = r`.
+    r_wraps: BitRegister,
+    u1: FieldRegister<E::ScalarField>,
+    u2: FieldRegister<E::ScalarField>,
+    u1_bits: ArrayRegister<BitRegister>,
+    u2_bits: ArrayRegister<BitRegister>,
+    accumulator: JacobianPointRegister<E>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Verifies the ECDSA signature `(r, s)` of the message hash `z` under the public key `Q`.
+    ///
+    /// The gadget computes `u1 = z / s` and `u2 = r / s` modulo the group order `n` and asserts
+    /// that `R = u1 * G + u2 * Q` is not the point at infinity and that `R.x mod n = r`. In
+    /// addition, it asserts that `Q` is on the curve, that `0 < r < n`, and that
+    /// `0 < s <= (n - 1) / 2`, i.e., only the low-s form of a signature is accepted.
+    ///
+    /// The message hash must be given as a scalar modulo `n`. Since `n < p < 2n`, the reduction of
+    /// `R.x` modulo `n` is checked with a witnessed bit which is set when `R.x = r + n`, in which
+    /// case `r + n < p` is also asserted.
+    pub fn verify_ecdsa<E: EcdsaParameters>(
+        &mut self,
+        public_key: &AffinePointRegister<E>,
+        message_hash: &FieldRegister<E::ScalarField>,
+        r: &FieldRegister<E::ScalarField>,
+        s: &FieldRegister<E::ScalarField>,
+    ) -> EcdsaGadget<L::Field, E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>
+            + From<FpDivInstruction<E::ScalarField>>
+            + From<FpIsZeroInstruction<E::ScalarField>>
+            + From<BigNumSubInstruction>,
+    {
+        self.ecdsa_gadget(public_key, message_hash, r, s, true)
+    }
+
+    /// Verifies the ECDSA signature `(r, s)` as `verify_ecdsa`, but accepts any `0 < s < n`.
+    ///
+    /// Both `(r, s)` and `(r, n - s)` are then accepted for the same message. This is needed for
+    /// signers which do not normalize `s`, such as the P-256 authenticators of WebAuthn, and must
+    /// not be used where signatures are expected to be unique.
+    pub fn verify_ecdsa_unnormalized<E: EcdsaParameters>(
+        &mut self,
+        public_key: &AffinePointRegister<E>,
+        message_hash: &FieldRegister<E::ScalarField>,
+        r: &FieldRegister<E::ScalarField>,
+        s: &FieldRegister<E::ScalarField>,
+    ) -> EcdsaGadget<L::Field, E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>
+            + From<FpDivInstruction<E::ScalarField>>
+            + From<FpIsZeroInstruction<E::ScalarField>>
+            + From<BigNumSubInstruction>,
+    {
+        self.ecdsa_gadget(public_key, message_hash, r, s, false)
+    }
+
+    fn ecdsa_gadget<E: EcdsaParameters>(
+        &mut self,
+        public_key: &AffinePointRegister<E>,
+        message_hash: &FieldRegister<E::ScalarField>,
+        r: &FieldRegister<E::ScalarField>,
+        s: &FieldRegister<E::ScalarField>,
+        low_s: bool,
+    ) -> EcdsaGadget<L::Field, E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>
+            + From<FpDivInstruction<E::ScalarField>>
+            + From<FpIsZeroInstruction<E::ScalarField>>
+            + From<BigNumSubInstruction>,
+    {
+        let nb_bits = E::nb_scalar_bits();
+        assert_eq!(
+            nb_bits,
+            1 << 8,
+            "The cycle length must match the scalar size"
+        );
+        assert_eq!(
+            E::BaseField::NB_LIMBS,
+            E::ScalarField::NB_LIMBS,
+            "The base field and the scalar field must have the same number of limbs"
+        );
+        let modulus = E::BaseField::modulus();
+        let order = E::ScalarField::modulus();
+        assert!(
+            order < modulus && modulus < &order << 1,
+            "The group order must lie between p / 2 and p"
+        );
+
+        let cycle = self.cycle(8);
+        let start_bit = cycle.start_bit.expr::<L::Field>();
+        let end_bit = cycle.end_bit.expr::<L::Field>();
+        let next_start_bit = cycle.start_bit.next().expr::<L::Field>();
+        let one = ArithmeticExpression::<L::Field>::one();
+
+        // Copy the inputs to the next row, except at the start of a new cycle.
+        for register in [public_key.x, public_key.y] {
+            let next_value = next_start_bit.clone() * register.next().expr()
+                + (one.clone() - next_start_bit.clone()) * register.expr();
+            self.set_to_expression_transition(&register.next(), next_value);
+        }
+        for register in [*message_hash, *r, *s] {
+            let next_value = next_start_bit.clone() * register.next().expr()
+                + (one.clone() - next_start_bit.clone()) * register.expr();
+            self.set_to_expression_transition(&register.next(), next_value);
+        }
+        let r_wraps = self.alloc::<BitRegister>();
+        let next_value = next_start_bit.clone() * r_wraps.next().expr()
+            + (one.clone() - next_start_bit.clone()) * r_wraps.expr();
+        self.set_to_expression_transition(&r_wraps.next(), next_value);
+
+        // Check that 0 < r < n and 0 < s <= (n - 1) / 2, or 0 < s < n if s is not normalized.
+        let s_bound = if low_s {
+            (&order - 1u32) >> 1
+        } else {
+            &order - 1u32
+        };
+        self.assert_field_nonzero(r);
+        self.assert_field_nonzero(s);
+        self.assert_limbs_at_most(r, &(&order - 1u32));
+        self.assert_limbs_at_most(s, &s_bound);
+
+        // Check that the public key is on the curve, i.e., y^2 = x^3 + a * x + b.
+        let b = self.alloc_constant_field_register::<E::BaseField>(&E::b_biguint());
+        let y_squared = self.fp_mul(&public_key.y, &public_key.y).result;
+        let x_squared = self.fp_mul(&public_key.x, &public_key.x).result;
+        let x_cubed = self.fp_mul(&x_squared, &public_key.x).result;
+        let mut rhs = self.fp_add(&x_cubed, &b);
+        if !E::a_biguint().is_zero() {
+            let a_x = self.fp_mul_const(&public_key.x, E::A).result;
+            rhs = self.fp_add(&rhs, &a_x);
+        }
+        self.assert_equal(&y_squared, &rhs);
+
+        // Compute u1 = z / s and u2 = r / s.
+        let u1 = self.fp_div(message_hash, s);
+        let u2 = self.fp_div(r, s);
+
+        // At the start of a cycle, the bits are the little endian decomposition of u1 and u2.
+        let u1_bits = self.alloc_array::<BitRegister>(nb_bits);
+        let u2_bits = self.alloc_array::<BitRegister>(nb_bits);
+        for (scalar, bits) in [(u1, u1_bits), (u2, u2_bits)] {
+            let limbs = ArrayRegister::<U16Register>::from_register_unsafe(*scalar.register());
+            for (j, limb) in limbs.iter().enumerate() {
+                let mut limb_value = ArithmeticExpression::zero();
+                for k in 0..16 {
+                    limb_value = limb_value
+                        + bits.get(16 * j + k).expr() * L::Field::from_canonical_u32(1 << k);
+                }
+                self.assert_expression_zero(start_bit.clone() * (limb_value - limb.expr()));
+            }
+
+            // Shift the bits up by one in the next row, so that each row reads the most
+            // significant bit, except at the start of a new cycle.
+            for k in 0..nb_bits {
+                let shifted = if k == 0 {
+                    ArithmeticExpression::zero()
+                } else {
+                    bits.get(k - 1).expr()
+                };
+                let bit = bits.get(k);
+                let next_value = next_start_bit.clone() * bit.next().expr()
+                    + (one.clone() - next_start_bit.clone()) * shifted;
+                self.set_to_expression_transition(&bit.next(), next_value);
+            }
+        }
+
+        // At the start of a cycle, the accumulator is the point at infinity (1 : 1 : 0).
+        let accumulator = self.alloc_unchecked_jacobian_point::<E>();
+        let one_limbs =
+            to_u16_le_limbs_polynomial::<L::Field, E::BaseField>(&BigUint::from(1u32)).coefficients;
+        self.assert_expression_zero(start_bit.clone() * (accumulator.x.expr() - one_limbs.clone()));
+        self.assert_expression_zero(start_bit.clone() * (accumulator.y.expr() - one_limbs));
+        self.assert_expression_zero(start_bit * accumulator.z.expr());
+
+        // Compute R = 2 * R + bit_1 * G + bit_2 * Q.
+        let generator = E::generator();
+        let z_one = self.alloc_constant_field_register::<E::BaseField>(&BigUint::from(1u32));
+        let generator_x = self.alloc_constant_field_register::<E::BaseField>(&generator.x);
+        let generator_y = self.alloc_constant_field_register::<E::BaseField>(&generator.y);
+        let points = [
+            JacobianPointRegister::new(generator_x, generator_y, z_one),
+            JacobianPointRegister::new(public_key.x, public_key.y, z_one),
+        ];
+        let bits = [u1_bits.get(nb_bits - 1), u2_bits.get(nb_bits - 1)];
+        let result = self.jacobian_multi_scalar_mul_step(&accumulator, &bits, &points);
+
+        // Copy the result to the accumulator of the next row, except at the start of a new cycle.
+        for (register, value) in [
+            (accumulator.x, result.x),
+            (accumulator.y, result.y),
+            (accumulator.z, result.z),
+        ] {
+            let next_value = next_start_bit.clone() * register.next().expr()
+                + (one.clone() - next_start_bit.clone()) * value.expr();
+            self.set_to_expression_transition(&register.next(), next_value);
+        }
+
+        // The x-coordinate of R is r + r_wraps * n. Since r < n < p, the limbs of r are also a
+        // canonical element of the base field, and r + n is one as long as r < p - n.
+        let order_limbs = self.alloc_constant_field_register::<E::BaseField>(&order);
+        let wrap_offset = self.alloc::<FieldRegister<E::BaseField>>();
+        self.set_to_expression(&wrap_offset, r_wraps.expr() * order_limbs.expr());
+        let wrapped_r = self.alloc::<FieldRegister<E::ScalarField>>();
+        self.set_to_expression(&wrapped_r, r_wraps.expr() * r.expr());
+        self.assert_limbs_at_most(&wrapped_r, &(&modulus - &order - 1u32));
+        let r_base = FieldRegister::<E::BaseField>::from_register(*r.register());
+        let x_affine = self.fp_add(&r_base, &wrap_offset);
+
+        // At the end of a cycle, check that R is not the point at infinity and that X = x * Z^2,
+        // which avoids an inversion.
+        let result_is_infinity = self.fp_is_zero(&result.z);
+        self.assert_expression_zero(end_bit.clone() * result_is_infinity.expr());
+        let z_squared = self.fp_mul(&result.z, &result.z).result;
+        let x_z_squared = self.fp_mul(&x_affine, &z_squared).result;
+        self.assert_expression_zero(end_bit * (result.x.expr() - x_z_squared.expr()));
+
+        EcdsaGadget {
+            cycle,
+            public_key: *public_key,
+            message_hash: *message_hash,
+            r: *r,
+            s: *s,
+            result,
+            r_wraps,
+            u1,
+            u2,
+            u1_bits,
+            u2_bits,
+            accumulator,
+        }
+    }
+}
+
+impl<F: PrimeField64> TraceWriter<F> {
+    /// Writes the inputs of an ECDSA verification to the first row of a cycle, together with the
+    /// bits of the scalars `u1` and `u2`, whether `R.x` exceeds `n`, and the initial value of the
+    /// accumulator.
+    ///
+    /// The inputs of all cycles must be written before the instructions of any row.
+    pub fn write_ecdsa_input<E: EcdsaParameters>(
+        &self,
+        gadget: &EcdsaGadget<F, E>,
+        public_key: &AffinePoint<E>,
+        message_hash: &BigUint,
+        signature: &(BigUint, BigUint),
+        row_index: usize,
+    ) {
+        let (r, s) = signature;
+        self.write_ec_point(&gadget.public_key, public_key, row_index);
+        for (register, value) in [
+            (&gadget.message_hash, message_hash),
+            (&gadget.r, r),
+            (&gadget.s, s),
+        ] {
+            let p_value = to_u16_le_limbs_polynomial::<F, E::ScalarField>(value);
+            self.write(register, &p_value, row_index);
+        }
+
+        let order = E::ScalarField::modulus();
+        let s_inv = s.modpow(&(&order - 2u32), &order);
+        let u1 = (message_hash * &s_inv) % &order;
+        let u2 = (r * &s_inv) % &order;
+        let r_wraps = match ecdsa_point(public_key, &u1, &u2) {
+            Some(point) => point.x >= order,
+            None => false,
+        };
+        self.write(
+            &gadget.r_wraps,
+            &F::from_canonical_u8(r_wraps as u8),
+            row_index,
+        );
+
+        let nb_bits = E::nb_scalar_bits();
+        for (bits, value) in [(&gadget.u1_bits, u1), (&gadget.u2_bits, u2)] {
+            let bit_values = biguint_to_bits_le(&value, nb_bits)
+                .into_iter()
+                .map(|bit| F::from_canonical_u8(bit as u8));
+            self.write_array(bits, bit_values, row_index);
+        }
+
+        self.write_jacobian_point(&gadget.accumulator, &JacobianPoint::infinity(), row_index);
+    }
+}
+
+/// Computes `R = u1 * G + u2 * Q`, returning `None` if it is the point at infinity.
+fn ecdsa_point<E: EcdsaParameters>(
+    public_key: &AffinePoint<E>,
+    u1: &BigUint,
+    u2: &BigUint,
+) -> Option<AffinePoint<E>> {
+    match (
+        E::generator().sw_scalar_mul(u1),
+        public_key.sw_scalar_mul(u2),
+    ) {
+        (Some(a), Some(b)) if a == b => Some(a.sw_double()),
+        (Some(a), Some(b)) if a == b.sw_neg() => None,
+        (Some(a), Some(b)) => Some(a.sw_add(&b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Defines the instruction set needed to verify ECDSA signatures over a curve, given the base and
+/// scalar fields of the curve. The field instructions of both fields are included, so that the
+/// set also covers gadgets with arithmetic modulo the group order, such as the GLV scalar
+/// multiplication.
+macro_rules! ecdsa_instruction {
+    ($(#[$attr:meta])* $name:ident, $base:ty, $scalar:ty) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub enum $name {
+            Base(FpInstruction<$base>),
+            Scalar(FpInstruction<$scalar>),
+            BigNum(BigNumInstruction<$scalar>),
+        }
+
+        impl FromFieldInstruction<$base> for $name {}
+
+        impl FromFieldInstruction<$scalar> for $name {}
+
+        impl<AP: PolynomialParser> AirConstraint<AP> for $name {
+            fn eval(&self, parser: &mut AP) {
+                match self {
+                    Self::Base(instruction) => {
+                        AirConstraint::<AP>::eval(instruction, parser)
+                    }
+                    Self::Scalar(instruction) => {
+                        AirConstraint::<AP>::eval(instruction, parser)
+                    }
+                    Self::BigNum(instruction) => {
+                        AirConstraint::<AP>::eval(instruction, parser)
+                    }
+                }
+            }
+        }
+
+        impl<F: PrimeField64> Instruction<F> for $name {
+            fn trace_layout(&self) -> Vec<MemorySlice> {
+                match self {
+                    Self::Base(instruction) => {
+                        Instruction::<F>::trace_layout(instruction)
+                    }
+                    Self::Scalar(instruction) => {
+                        Instruction::<F>::trace_layout(instruction)
+                    }
+                    Self::BigNum(instruction) => {
+                        Instruction::<F>::trace_layout(instruction)
+                    }
+                }
+            }
+
+            fn inputs(&self) -> Vec<MemorySlice> {
+                match self {
+                    Self::Base(instruction) => Instruction::<F>::inputs(instruction),
+                    Self::Scalar(instruction) => Instruction::<F>::inputs(instruction),
+                    Self::BigNum(instruction) => Instruction::<F>::inputs(instruction),
+                }
+            }
+
+            fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+                match self {
+                    Self::Base(instruction) => {
+                        Instruction::<F>::write(instruction, writer, row_index)
+                    }
+                    Self::Scalar(instruction) => {
+                        Instruction::<F>::write(instruction, writer, row_index)
+                    }
+                    Self::BigNum(instruction) => {
+                        Instruction::<F>::write(instruction, writer, row_index)
+                    }
+                }
+            }
+        }
+
+        impl From<FpAddInstruction<$base>> for $name {
+            fn from(instr: FpAddInstruction<$base>) -> Self {
+                Self::Base(instr.into())
+            }
+        }
+
+        impl From<FpMulInstruction<$base>> for $name {
+            fn from(instr: FpMulInstruction<$base>) -> Self {
+                Self::Base(instr.into())
+            }
+        }
+
+        impl From<FpMulConstInstruction<$base>> for $name {
+            fn from(instr: FpMulConstInstruction<$base>) -> Self {
+                Self::Base(instr.into())
+            }
+        }
+
+        impl From<FpInnerProductInstruction<$base>> for $name {
+            fn from(instr: FpInnerProductInstruction<$base>) -> Self {
+                Self::Base(instr.into())
+            }
+        }
+
+        impl From<FpDenInstruction<$base>> for $name {
+            fn from(instr: FpDenInstruction<$base>) -> Self {
+                Self::Base(instr.into())
+            }
+        }
+
+        impl From<SelectInstruction<FieldRegister<$base>>> for $name {
+            fn from(instr: SelectInstruction<FieldRegister<$base>>) -> Self {
+                Self::Base(instr.into())
+            }
+        }
+
+        impl From<FpSubInstruction<$base>> for $name {
+            fn from(instr: FpSubInstruction<$base>) -> Self {
+                Self::Base(instr.into())
+            }
+        }
+
+        impl From<FpDivInstruction<$base>> for $name {
+            fn from(instr: FpDivInstruction<$base>) -> Self {
+                Self::Base(instr.into())
+            }
+        }
+
+        impl From<FpIsZeroInstruction<$base>> for $name {
+            fn from(instr: FpIsZeroInstruction<$base>) -> Self {
+                Self::Base(instr.into())
+            }
+        }
+
+        impl From<FpSqrtInstruction<$base>> for $name {
+            fn from(instr: FpSqrtInstruction<$base>) -> Self {
+                Self::Base(instr.into())
+            }
+        }
+
+        impl From<FpAddInstruction<$scalar>> for $name {
+            fn from(instr: FpAddInstruction<$scalar>) -> Self {
+                Self::Scalar(instr.into())
+            }
+        }
+
+        impl From<FpMulInstruction<$scalar>> for $name {
+            fn from(instr: FpMulInstruction<$scalar>) -> Self {
+                Self::Scalar(instr.into())
+            }
+        }
+
+        impl From<FpMulConstInstruction<$scalar>> for $name {
+            fn from(instr: FpMulConstInstruction<$scalar>) -> Self {
+                Self::Scalar(instr.into())
+            }
+        }
+
+        impl From<FpInnerProductInstruction<$scalar>> for $name {
+            fn from(instr: FpInnerProductInstruction<$scalar>) -> Self {
+                Self::Scalar(instr.into())
+            }
+        }
+
+        impl From<FpDenInstruction<$scalar>> for $name {
+            fn from(instr: FpDenInstruction<$scalar>) -> Self {
+                Self::Scalar(instr.into())
+            }
+        }
+
+        impl From<SelectInstruction<FieldRegister<$scalar>>> for $name {
+            fn from(instr: SelectInstruction<FieldRegister<$scalar>>) -> Self {
+                Self::Scalar(instr.into())
+            }
+        }
+
+        impl From<FpSubInstruction<$scalar>> for $name {
+            fn from(instr: FpSubInstruction<$scalar>) -> Self {
+                Self::Scalar(instr.into())
+            }
+        }
+
+        impl From<FpDivInstruction<$scalar>> for $name {
+            fn from(instr: FpDivInstruction<$scalar>) -> Self {
+                Self::Scalar(instr.into())
+            }
+        }
+
+        impl From<FpIsZeroInstruction<$scalar>> for $name {
+            fn from(instr: FpIsZeroInstruction<$scalar>) -> Self {
+                Self::Scalar(instr.into())
+            }
+        }
+
+        impl From<BigNumSubInstruction> for $name {
+            fn from(instr: BigNumSubInstruction) -> Self {
+                Self::BigNum(BigNumInstruction::Sub(instr))
+            }
+        }
+    };
+}
+
+ecdsa_instruction!(
+    /// The instructions needed to verify secp256k1 ECDSA signatures.
+    Secp256k1EcdsaInstruction,
+    Secp256k1BaseField,
+    Secp256k1ScalarField
+);
+
+ecdsa_instruction!(
+    /// The instructions needed to verify P-256 ECDSA signatures.
+    P256EcdsaInstruction,
+    P256BaseField,
+    P256ScalarField
+);
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::ec::gadget::EllipticCurveGadget;
+    use crate::chip::ec::weierstrass::p256::P256;
+    use crate::chip::ec::weierstrass::secp256k1::Secp256k1;
+    use crate::chip::hash::keccak::Keccak256Gadget;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct EcdsaTest;
+
+    impl AirParameters for EcdsaTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 11196;
+        const NUM_FREE_COLUMNS: usize = 583;
+        const EXTENDED_COLUMNS: usize = 16803;
+
+        type Instruction = Secp256k1EcdsaInstruction;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    /// A signature `(r, s)` in low-s form with the parity `v` of the y-coordinate of `k * G`,
+    /// flipped if `s` was negated.
+    struct Signature {
+        r: BigUint,
+        s: BigUint,
+        v: bool,
+    }
+
+    fn sign(private_key: &BigUint, message_hash: &BigUint, nonce: &BigUint) -> Signature {
+        type E = Secp256k1;
+        let order = E::prime_group_order();
+        let point = E::generator().sw_scalar_mul(nonce).unwrap();
+        let r = &point.x % &order;
+        let nonce_inv = nonce.modpow(&(&order - 2u32), &order);
+        let s = (nonce_inv * (message_hash + &r * private_key)) % &order;
+        let v = point.y.bit(0);
+        if s > (&order - 1u32) >> 1 {
+            Signature {
+                r,
+                s: &order - s,
+                v: !v,
+            }
+        } else {
+            Signature { r, s, v }
+        }
+    }
+
+    /// Recovers the public key from a signature, assuming `r` is the x-coordinate of `k * G`.
+    fn recover(message_hash: &BigUint, signature: &Signature) -> AffinePoint<Secp256k1> {
+        type E = Secp256k1;
+        let p = Secp256k1BaseField::modulus();
+        let order = E::prime_group_order();
+
+        // Since p = 3 mod 4, a square root of y^2 = x^3 + 7 is (x^3 + 7)^((p + 1) / 4).
+        let x = signature.r.clone();
+        let y_squared = (x.modpow(&BigUint::from(3u32), &p) + E::b_biguint()) % &p;
+        let mut y = y_squared.modpow(&((&p + 1u32) >> 2), &p);
+        if y.bit(0) != signature.v {
+            y = &p - y;
+        }
+        let point = AffinePoint::<E>::new(x, y);
+
+        // Q = r^-1 * (s * R - z * G).
+        let r_inv = signature.r.modpow(&(&order - 2u32), &order);
+        let u1 = (&order - (message_hash * &r_inv) % &order) % &order;
+        let u2 = (&signature.s * &r_inv) % &order;
+        let a = E::generator().sw_scalar_mul(&u1).unwrap();
+        let b = point.sw_scalar_mul(&u2).unwrap();
+        a.sw_add(&b)
+    }
+
+    type Input = (AffinePoint<Secp256k1>, BigUint, (BigUint, BigUint));
+
+    /// Proves the verification of `inputs[k % inputs.len()]` in cycle `k`.
+    fn prove_ecdsa(inputs: &[Input]) {
+        type L = EcdsaTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type E = Secp256k1;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let public_key: AffinePointRegister<E> = builder.alloc_ec_point();
+        let message_hash = builder.alloc::<FieldRegister<Secp256k1ScalarField>>();
+        let r = builder.alloc::<FieldRegister<Secp256k1ScalarField>>();
+        let s = builder.alloc::<FieldRegister<Secp256k1ScalarField>>();
+        let gadget = builder.verify_ecdsa(&public_key, &message_hash, &r, &s);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let nb_cycles = L::num_rows() / 256;
+        let writer = generator.new_writer();
+        (0..nb_cycles).into_par_iter().for_each(|k| {
+            let (public_key, message_hash, signature) = &inputs[k % inputs.len()];
+            writer.write_ecdsa_input(&gadget, public_key, message_hash, signature, 256 * k);
+        });
+        (0..nb_cycles).into_par_iter().for_each(|k| {
+            for i in 0..256 {
+                writer.write_row_instructions(&generator.air_data, 256 * k + i);
+            }
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+
+    /// Signs a random message hash with a random key, and checks that the public key is recovered
+    /// from the signature.
+    fn random_input() -> Input {
+        type E = Secp256k1;
+        let order = E::prime_group_order();
+        let mut rng = thread_rng();
+        let private_key = rng.gen_biguint_range(&BigUint::from(1u32), &order);
+        let nonce = rng.gen_biguint_range(&BigUint::from(1u32), &order);
+        let message_hash = rng.gen_biguint_below(&order);
+        let public_key = E::generator().sw_scalar_mul(&private_key).unwrap();
+        let signature = sign(&private_key, &message_hash, &nonce);
+        assert_eq!(recover(&message_hash, &signature), public_key);
+        (public_key, message_hash, (signature.r, signature.s))
+    }
+
+    /// A signature whose point `R` satisfies `n <= R.x < p`, so that `r = R.x - n`.
+    ///
+    /// Since no nonce `k` with `k * G = R` is known, the signature is built from random `s` and
+    /// `u1`, and the public key is solved from `R = u1 * G + u2 * Q`.
+    fn wrapped_input() -> Input {
+        type E = Secp256k1;
+        let p = Secp256k1BaseField::modulus();
+        let order = E::prime_group_order();
+
+        // Find the smallest x >= n for which x^3 + 7 is a square modulo p = 3 mod 4.
+        let mut x = order.clone();
+        let point = loop {
+            let y_squared = (x.modpow(&BigUint::from(3u32), &p) + E::b_biguint()) % &p;
+            let y = y_squared.modpow(&((&p + 1u32) >> 2), &p);
+            if (&y * &y) % &p == y_squared {
+                break AffinePoint::<E>::new(x, y);
+            }
+            x += 1u32;
+        };
+        let r = &point.x - &order;
+
+        let mut rng = thread_rng();
+        let s = rng.gen_biguint_range(&BigUint::from(1u32), &((&order + 1u32) >> 1));
+        let u1 = rng.gen_biguint_range(&BigUint::from(1u32), &order);
+        let s_inv = s.modpow(&(&order - 2u32), &order);
+        let u2 = (&r * &s_inv) % &order;
+        let u2_inv = u2.modpow(&(&order - 2u32), &order);
+        let message_hash = (&u1 * &s) % &order;
+
+        // Q = u2^-1 * (R - u1 * G).
+        let u1_g = E::generator().sw_scalar_mul(&u1).unwrap();
+        let public_key = point.sw_add(&u1_g.sw_neg()).sw_scalar_mul(&u2_inv).unwrap();
+        (public_key, message_hash, (r, s))
+    }
+
+    /// The signature of the example transaction of EIP-155, signed with the private key
+    /// `0x4646...46` on chain 1, and the address of its signer.
+    fn ethereum_signature() -> (BigUint, Signature, [u8; 20]) {
+        let message_hash = BigUint::parse_bytes(
+            b"daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53",
+            16,
+        )
+        .unwrap();
+        let r = BigUint::parse_bytes(
+            b"18515461264373351373200002665853028612451056578545711640558177340181847433846",
+            10,
+        )
+        .unwrap();
+        let s = BigUint::parse_bytes(
+            b"46948507304638947509940763649030358759909902576025900602547168820602576006531",
+            10,
+        )
+        .unwrap();
+        // The signature has v = 37 = 35 + 2 * 1 + 0, so the y-coordinate of k * G is even.
+        let signature = Signature { r, s, v: false };
+        let address = hex::decode("9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        (message_hash, signature, address)
+    }
+
+    /// The Ethereum address of a public key, i.e., the last 20 bytes of the Keccak-256 hash of
+    /// its uncompressed coordinates.
+    fn ethereum_address(public_key: &AffinePoint<Secp256k1>) -> [u8; 20] {
+        let mut bytes = [0u8; 64];
+        for (chunk, coordinate) in bytes
+            .chunks_exact_mut(32)
+            .zip([&public_key.x, &public_key.y])
+        {
+            let coordinate_bytes = coordinate.to_bytes_be();
+            chunk[32 - coordinate_bytes.len()..].copy_from_slice(&coordinate_bytes);
+        }
+        Keccak256Gadget::hash(&bytes)[12..].try_into().unwrap()
+    }
+
+    #[test]
+    fn test_ecdsa_verify() {
+        let (message_hash, signature, address) = ethereum_signature();
+        let public_key = recover(&message_hash, &signature);
+        assert_eq!(ethereum_address(&public_key), address);
+
+        let mut inputs = vec![
+            (public_key, message_hash, (signature.r, signature.s)),
+            wrapped_input(),
+        ];
+        inputs.extend((0..14).map(|_| random_input()));
+        prove_ecdsa(&inputs);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_ecdsa_verify_tampered_s() {
+        let (public_key, message_hash, (r, s)) = random_input();
+        prove_ecdsa(&[(public_key, message_hash, (r, s + 1u32))]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_ecdsa_verify_high_s() {
+        let order = Secp256k1::prime_group_order();
+        let (public_key, message_hash, (r, s)) = random_input();
+        prove_ecdsa(&[(public_key, message_hash, (r, order - s))]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_ecdsa_verify_zero_r() {
+        let (public_key, message_hash, (_, s)) = random_input();
+        prove_ecdsa(&[(public_key, message_hash, (BigUint::zero(), s))]);
+    }
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct P256EcdsaTest;
+
+    impl AirParameters for P256EcdsaTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 11748;
+        const NUM_FREE_COLUMNS: usize = 583;
+        const EXTENDED_COLUMNS: usize = 17631;
+
+        type Instruction = P256EcdsaInstruction;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    #[test]
+    fn test_p256_ecdsa_verify_unnormalized() {
+        type L = P256EcdsaTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type E = P256;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let public_key: AffinePointRegister<E> = builder.alloc_ec_point();
+        let message_hash = builder.alloc::<FieldRegister<P256ScalarField>>();
+        let r = builder.alloc::<FieldRegister<P256ScalarField>>();
+        let s = builder.alloc::<FieldRegister<P256ScalarField>>();
+        let gadget = builder.verify_ecdsa_unnormalized(&public_key, &message_hash, &r, &s);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let order = E::prime_group_order();
+        let half_order = (&order - 1u32) >> 1;
+        let nb_cycles = L::num_rows() / 256;
+
+        // Sign a random message hash with a random key for each cycle, with `s` in high form in
+        // the even cycles and in low form in the odd ones.
+        let inputs = (0..nb_cycles)
+            .into_par_iter()
+            .map(|k| {
+                let mut rng = thread_rng();
+                let private_key = rng.gen_biguint_range(&BigUint::from(1u32), &order);
+                let nonce = rng.gen_biguint_range(&BigUint::from(1u32), &order);
+                let message_hash = rng.gen_biguint_below(&order);
+                let public_key = E::generator().sw_scalar_mul(&private_key).unwrap();
+
+                let r = E::generator().sw_scalar_mul(&nonce).unwrap().x % &order;
+                let nonce_inv = nonce.modpow(&(&order - 2u32), &order);
+                let mut s = (nonce_inv * (&message_hash + &r * &private_key)) % &order;
+                if (k % 2 == 0) != (s > half_order) {
+                    s = &order - s;
+                }
+                (public_key, message_hash, (r, s))
+            })
+            .collect::<Vec<_>>();
+
+        let writer = generator.new_writer();
+        inputs
+            .par_iter()
+            .enumerate()
+            .for_each(|(k, (public_key, message_hash, signature))| {
+                writer.write_ecdsa_input(&gadget, public_key, message_hash, signature, 256 * k);
+            });
+        inputs
+            .par_iter()
+            .enumerate()
+            .for_each(|(k, (_, _, (r, _)))| {
+                for i in 0..256 {
+                    writer.write_row_instructions(&generator.air_data, 256 * k + i);
+                }
+                let result = writer.read_jacobian_point(&gadget.result, 256 * k + 255);
+                assert_eq!(result.to_affine().unwrap().x, *r);
+            });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+}
@@ -0,0 +1,473 @@
+//! Scalar multiplication with the GLV endomorphism.
+//!
+//! On curves with `a = 0` and `p = 1 mod 3`, such as secp256k1 and BN254, the map
+//! `phi(x, y) = (beta * x, y)`, where `beta` is a cube root of unity modulo `p`, acts on the group
+//! as the multiplication by a cube root of unity `lambda` modulo `n`. A scalar `k` is decomposed as
+//! `k = k1 + k2 * lambda mod n` with `|k1|` and `|k2|` of about half the size of `n`, so that
+//! `[k] * P = [k1] * P + [k2] * phi(P)` takes half as many doublings as the plain double-and-add.
+
+use num::bigint::Sign;
+use num::{BigInt, BigUint, Integer, Num, One, Signed, Zero};
+use serde::{Deserialize, Serialize};
+
+use super::bn254::{Bn254, Bn254ScalarField};
+use super::ecdsa::EcdsaParameters;
+use super::secp256k1::Secp256k1;
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::builder::AirBuilder;
+use crate::chip::ec::gadget::EllipticCurveWriter;
+use crate::chip::ec::point::{
+    AffinePoint, AffinePointRegister, JacobianPoint, JacobianPointRegister,
+};
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::parameters::MAX_NB_LIMBS;
+use crate::chip::field::register::FieldRegister;
+use crate::chip::instruction::cycle::Cycle;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::TraceWriter;
+use crate::chip::utils::biguint_to_bits_le;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::to_u16_le_limbs_polynomial;
+
+/// Parameters of a Weierstrass curve with an efficiently computable endomorphism
+/// `phi(x, y) = (beta * x, y) = [lambda] * (x, y)`.
+pub trait GlvParameters: EcdsaParameters {
+    /// The cube root of unity `beta` modulo the base field modulus.
+    const BETA: [u16; MAX_NB_LIMBS];
+    /// The cube root of unity `lambda` modulo the group order matching `beta`.
+    const LAMBDA: [u16; MAX_NB_LIMBS];
+
+    /// A reduced basis `(a1, b1), (a2, b2)` of the lattice of the pairs `(x, y)` with
+    /// `x + y * lambda = 0 mod n`.
+    fn lattice_basis() -> [(BigInt, BigInt); 2];
+
+    fn beta_biguint() -> BigUint {
+        let mut beta = BigUint::zero();
+        for (i, limb) in Self::BETA.iter().enumerate() {
+            beta += BigUint::from(*limb) << (16 * i);
+        }
+        beta
+    }
+
+    fn lambda_biguint() -> BigUint {
+        let mut lambda = BigUint::zero();
+        for (i, limb) in Self::LAMBDA.iter().enumerate() {
+            lambda += BigUint::from(*limb) << (16 * i);
+        }
+        lambda
+    }
+
+    /// The number of bits of the absolute values of the decomposed scalars.
+    fn nb_glv_scalar_bits() -> usize {
+        Self::nb_scalar_bits() / 2
+    }
+}
+
+/// The decomposition `k = k1 + k2 * lambda mod n`, with `k1` and `k2` given by their absolute
+/// values and signs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlvDecomposition {
+    pub k1: BigUint,
+    pub k1_neg: bool,
+    pub k2: BigUint,
+    pub k2_neg: bool,
+}
+
+/// Decomposes the scalar `k` by rounding `k` to the closest vector of the lattice, following
+/// section 4 of Gallant, Lambert and Vanstone.
+pub fn glv_decompose<E: GlvParameters>(k: &BigUint) -> GlvDecomposition {
+    let n = BigInt::from(E::prime_group_order());
+    let k = BigInt::from(k % E::prime_group_order());
+    let [(a1, b1), (a2, b2)] = E::lattice_basis();
+
+    // c1 = round(b2 * k / n), c2 = round(-b1 * k / n).
+    let round_div = |x: BigInt| (x * 2 + &n).div_floor(&(&n * 2));
+    let c1 = round_div(&b2 * &k);
+    let c2 = round_div(-&b1 * &k);
+
+    let k1 = &k - &c1 * &a1 - &c2 * &a2;
+    let k2 = -&c1 * &b1 - &c2 * &b2;
+
+    let bound = BigInt::one() << E::nb_glv_scalar_bits();
+    debug_assert!(k1.abs() < bound && k2.abs() < bound);
+    GlvDecomposition {
+        k1: k1.magnitude().clone(),
+        k1_neg: k1.sign() == Sign::Minus,
+        k2: k2.magnitude().clone(),
+        k2_neg: k2.sign() == Sign::Minus,
+    }
+}
+
+/// Scalar multiplication `[k] * P` with the GLV endomorphism, one scalar multiplication per cycle
+/// of `2^7` rows.
+///
+/// The inputs are read at the first row of each cycle and copied to the other rows of the cycle,
+/// together with the decomposition of `k`. Each row adds one bit of both `|k1|` and `|k2|` to the
+/// accumulator, most significant bit first, and the result is read at the last row of the cycle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(dead_code)]
+pub struct GlvScalarMulGadget<F, E: GlvParameters> {
+    pub cycle: Cycle<F>,
+    pub point: AffinePointRegister<E>,
+    pub scalar: FieldRegister<E::ScalarField>,
+    /// The value of the accumulator after the current row.
+    pub result: JacobianPointRegister<E>,
+    k1: FieldRegister<E::ScalarField>,
+    k2: FieldRegister<E::ScalarField>,
+    k1_neg: BitRegister,
+    k2_neg: BitRegister,
+    k1_bits: ArrayRegister<BitRegister>,
+    k2_bits: ArrayRegister<BitRegister>,
+    accumulator: JacobianPointRegister<E>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Computes `[k] * P` as `[k1] * P + [k2] * phi(P)`, in half the rows of the double-and-add
+    /// of the ECDSA gadget.
+    ///
+    /// The decomposition is a witness, and the gadget asserts that `|k1|` and `|k2|` fit in
+    /// `E::nb_glv_scalar_bits()` bits and that `k = k1 + k2 * lambda` modulo `n`. The scalar must
+    /// be given in canonical form, and the point must be on the curve, which is not checked here.
+    pub fn glv_scalar_mul<E: GlvParameters>(
+        &mut self,
+        point: &AffinePointRegister<E>,
+        scalar: &FieldRegister<E::ScalarField>,
+    ) -> GlvScalarMulGadget<L::Field, E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField> + FromFieldInstruction<E::ScalarField>,
+    {
+        let nb_bits = E::nb_glv_scalar_bits();
+        assert_eq!(
+            nb_bits,
+            1 << 7,
+            "The cycle length must match the size of the decomposed scalars"
+        );
+
+        let cycle = self.cycle(7);
+        let start_bit = cycle.start_bit.expr::<L::Field>();
+        let next_start_bit = cycle.start_bit.next().expr::<L::Field>();
+        let one = ArithmeticExpression::<L::Field>::one();
+
+        let k1 = self.alloc::<FieldRegister<E::ScalarField>>();
+        let k2 = self.alloc::<FieldRegister<E::ScalarField>>();
+        let k1_neg = self.alloc::<BitRegister>();
+        let k2_neg = self.alloc::<BitRegister>();
+
+        // Copy the inputs and the decomposition to the next row, except at the start of a new
+        // cycle.
+        for register in [point.x, point.y] {
+            let next_value = next_start_bit.clone() * register.next().expr()
+                + (one.clone() - next_start_bit.clone()) * register.expr();
+            self.set_to_expression_transition(&register.next(), next_value);
+        }
+        for register in [*scalar, k1, k2] {
+            let next_value = next_start_bit.clone() * register.next().expr()
+                + (one.clone() - next_start_bit.clone()) * register.expr();
+            self.set_to_expression_transition(&register.next(), next_value);
+        }
+        for register in [k1_neg, k2_neg] {
+            let next_value = next_start_bit.clone() * register.next().expr()
+                + (one.clone() - next_start_bit.clone()) * register.expr();
+            self.set_to_expression_transition(&register.next(), next_value);
+        }
+
+        // Check that k = k1 + k2 * lambda mod n, with the signs of k1 and k2 applied.
+        let k1_negated = self.fp_neg(&k1);
+        let k1_signed = self.select(&k1_neg, &k1_negated, &k1);
+        let k2_negated = self.fp_neg(&k2);
+        let k2_signed = self.select(&k2_neg, &k2_negated, &k2);
+        let lambda_k2 = self.fp_mul_const(&k2_signed, E::LAMBDA).result;
+        let k_value = self.fp_add(&k1_signed, &lambda_k2);
+        self.assert_equal(&k_value, scalar);
+
+        // At the start of a cycle, the bits are the little endian decomposition of |k1| and |k2|,
+        // and the limbs above the bits are zero.
+        let k1_bits = self.alloc_array::<BitRegister>(nb_bits);
+        let k2_bits = self.alloc_array::<BitRegister>(nb_bits);
+        for (value, bits) in [(k1, k1_bits), (k2, k2_bits)] {
+            let limbs = ArrayRegister::<U16Register>::from_register_unsafe(*value.register());
+            for (j, limb) in limbs.iter().enumerate() {
+                let mut limb_value = ArithmeticExpression::zero();
+                for k in 0..nb_bits.saturating_sub(16 * j).min(16) {
+                    limb_value = limb_value
+                        + bits.get(16 * j + k).expr() * L::Field::from_canonical_u32(1 << k);
+                }
+                self.assert_expression_zero(start_bit.clone() * (limb_value - limb.expr()));
+            }
+
+            // Shift the bits up by one in the next row, so that each row reads the most
+            // significant bit, except at the start of a new cycle.
+            for k in 0..nb_bits {
+                let shifted = if k == 0 {
+                    ArithmeticExpression::zero()
+                } else {
+                    bits.get(k - 1).expr()
+                };
+                let bit = bits.get(k);
+                let next_value = next_start_bit.clone() * bit.next().expr()
+                    + (one.clone() - next_start_bit.clone()) * shifted;
+                self.set_to_expression_transition(&bit.next(), next_value);
+            }
+        }
+
+        // The points sign(k1) * P and sign(k2) * phi(P), where phi(P) = (beta * x, y).
+        let z_one = self.alloc_constant_field_register::<E::BaseField>(&BigUint::one());
+        let y_negated = self.fp_neg(&point.y);
+        let y1 = self.select(&k1_neg, &y_negated, &point.y);
+        let y2 = self.select(&k2_neg, &y_negated, &point.y);
+        let beta_x = self.fp_mul_const(&point.x, E::BETA).result;
+        let points = [
+            JacobianPointRegister::new(point.x, y1, z_one),
+            JacobianPointRegister::new(beta_x, y2, z_one),
+        ];
+
+        // At the start of a cycle, the accumulator is the point at infinity (1 : 1 : 0).
+        let accumulator = self.alloc_unchecked_jacobian_point::<E>();
+        let one_limbs =
+            to_u16_le_limbs_polynomial::<L::Field, E::BaseField>(&BigUint::one()).coefficients;
+        self.assert_expression_zero(start_bit.clone() * (accumulator.x.expr() - one_limbs.clone()));
+        self.assert_expression_zero(start_bit.clone() * (accumulator.y.expr() - one_limbs));
+        self.assert_expression_zero(start_bit * accumulator.z.expr());
+
+        // Compute R = 2 * R + bit_1 * sign(k1) * P + bit_2 * sign(k2) * phi(P).
+        let bits = [k1_bits.get(nb_bits - 1), k2_bits.get(nb_bits - 1)];
+        let result = self.jacobian_multi_scalar_mul_step(&accumulator, &bits, &points);
+
+        // Copy the result to the accumulator of the next row, except at the start of a new cycle.
+        for (register, value) in [
+            (accumulator.x, result.x),
+            (accumulator.y, result.y),
+            (accumulator.z, result.z),
+        ] {
+            let next_value = next_start_bit.clone() * register.next().expr()
+                + (one.clone() - next_start_bit.clone()) * value.expr();
+            self.set_to_expression_transition(&register.next(), next_value);
+        }
+
+        GlvScalarMulGadget {
+            cycle,
+            point: *point,
+            scalar: *scalar,
+            result,
+            k1,
+            k2,
+            k1_neg,
+            k2_neg,
+            k1_bits,
+            k2_bits,
+            accumulator,
+        }
+    }
+}
+
+impl<F: PrimeField64> TraceWriter<F> {
+    /// Writes the point and the scalar of a GLV scalar multiplication to the first row of a cycle,
+    /// together with the decomposition of the scalar and the initial value of the accumulator.
+    ///
+    /// The inputs of all cycles must be written before the instructions of any row.
+    pub fn write_glv_scalar_mul_input<E: GlvParameters>(
+        &self,
+        gadget: &GlvScalarMulGadget<F, E>,
+        point: &AffinePoint<E>,
+        scalar: &BigUint,
+        row_index: usize,
+    ) {
+        self.write_ec_point(&gadget.point, point, row_index);
+        let scalar_value = to_u16_le_limbs_polynomial::<F, E::ScalarField>(scalar);
+        self.write(&gadget.scalar, &scalar_value, row_index);
+
+        let decomposition = glv_decompose::<E>(scalar);
+        let nb_bits = E::nb_glv_scalar_bits();
+        for (register, bits, value) in [
+            (&gadget.k1, &gadget.k1_bits, &decomposition.k1),
+            (&gadget.k2, &gadget.k2_bits, &decomposition.k2),
+        ] {
+            let p_value = to_u16_le_limbs_polynomial::<F, E::ScalarField>(value);
+            self.write(register, &p_value, row_index);
+            let bit_values = biguint_to_bits_le(value, nb_bits)
+                .into_iter()
+                .map(|bit| F::from_canonical_u8(bit as u8));
+            self.write_array(bits, bit_values, row_index);
+        }
+        for (register, value) in [
+            (&gadget.k1_neg, decomposition.k1_neg),
+            (&gadget.k2_neg, decomposition.k2_neg),
+        ] {
+            self.write(register, &F::from_canonical_u8(value as u8), row_index);
+        }
+
+        self.write_jacobian_point(&gadget.accumulator, &JacobianPoint::infinity(), row_index);
+    }
+}
+
+impl GlvParameters for Secp256k1 {
+    const BETA: [u16; MAX_NB_LIMBS] = [
+        494, 29077, 27688, 49465, 35221, 4853, 18805, 40176, 13545, 44084, 18334, 28260, 1808,
+        25980, 27179, 31465, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    const LAMBDA: [u16; MAX_NB_LIMBS] = [
+        48498, 6947, 38524, 57090, 26232, 8321, 8938, 4654, 25690, 34834, 7170, 42278, 12512,
+        49244, 44364, 21347, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+
+    fn lattice_basis() -> [(BigInt, BigInt); 2] {
+        let a1 = BigInt::from_str_radix("3086d221a7d46bcde86c90e49284eb15", 16).unwrap();
+        let b1 = BigInt::from_str_radix("-e4437ed6010e88286f547fa90abfe4c3", 16).unwrap();
+        let a2 = BigInt::from_str_radix("114ca50f7a8e2f3f657c1108d9d44cfd8", 16).unwrap();
+        let b2 = a1.clone();
+        [(a1, b1), (a2, b2)]
+    }
+}
+
+impl EcdsaParameters for Bn254 {
+    type ScalarField = Bn254ScalarField;
+}
+
+impl GlvParameters for Bn254 {
+    const BETA: [u16; MAX_NB_LIMBS] = [
+        64840, 24700, 17637, 58557, 28221, 48022, 1695, 49807, 52400, 57516, 55783, 24173, 41001,
+        57649, 20082, 12388, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    const LAMBDA: [u16; MAX_NB_LIMBS] = [
+        28451, 13923, 2861, 47306, 50665, 60459, 42815, 52279, 16644, 16344, 28185, 1163, 41001,
+        57649, 20082, 12388, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+
+    fn lattice_basis() -> [(BigInt, BigInt); 2] {
+        let a1 = BigInt::from_str_radix("6f4d8248eeb859fc8211bbeb7d4f1128", 16).unwrap();
+        let b1 = BigInt::from_str_radix("-89d3256894d213e3", 16).unwrap();
+        let a2 = BigInt::from_str_radix("89d3256894d213e3", 16).unwrap();
+        let b2 = BigInt::from_str_radix("6f4d8248eeb859fd0be4e1541221250b", 16).unwrap();
+        [(a1, b1), (a2, b2)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use rand::thread_rng;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::ec::gadget::EllipticCurveGadget;
+    use crate::chip::ec::weierstrass::ecdsa::Secp256k1EcdsaInstruction;
+    use crate::chip::ec::weierstrass::WeierstrassParameters;
+    use crate::chip::field::parameters::FieldParameters;
+
+    fn check_glv_parameters<E: GlvParameters>() {
+        let n = E::prime_group_order();
+        let lambda = E::lambda_biguint();
+        let generator = E::generator();
+        let endomorphism = AffinePoint::<E>::new(
+            (E::beta_biguint() * &generator.x) % E::BaseField::modulus(),
+            generator.y.clone(),
+        );
+        assert_eq!(generator.sw_scalar_mul(&lambda), Some(endomorphism));
+
+        let mut rng = thread_rng();
+        let lambda = BigInt::from(lambda);
+        let bound = BigUint::one() << E::nb_glv_scalar_bits();
+        for _ in 0..1000 {
+            let k = rng.gen_biguint_below(&n);
+            let decomposition = glv_decompose::<E>(&k);
+            assert!(decomposition.k1 < bound && decomposition.k2 < bound);
+            let signed = |value: &BigUint, neg: bool| {
+                let value = BigInt::from(value.clone());
+                if neg {
+                    -value
+                } else {
+                    value
+                }
+            };
+            let k1 = signed(&decomposition.k1, decomposition.k1_neg);
+            let k2 = signed(&decomposition.k2, decomposition.k2_neg);
+            let value = (k1 + k2 * &lambda).mod_floor(&BigInt::from(n.clone()));
+            assert_eq!(value, BigInt::from(k));
+        }
+    }
+
+    #[test]
+    fn test_glv_decomposition() {
+        check_glv_parameters::<Secp256k1>();
+        check_glv_parameters::<Bn254>();
+    }
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct GlvScalarMulTest;
+
+    impl AirParameters for GlvScalarMulTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 10600;
+        const NUM_FREE_COLUMNS: usize = 325;
+        const EXTENDED_COLUMNS: usize = 15909;
+
+        type Instruction = Secp256k1EcdsaInstruction;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    #[test]
+    fn test_glv_scalar_mul() {
+        type L = GlvScalarMulTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type E = Secp256k1;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let point: AffinePointRegister<E> = builder.alloc_ec_point();
+        let scalar = builder.alloc::<FieldRegister<<E as EcdsaParameters>::ScalarField>>();
+        let gadget = builder.glv_scalar_mul(&point, &scalar);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let order = E::prime_group_order();
+        let nb_cycles = L::num_rows() / 128;
+        let inputs = (0..nb_cycles)
+            .into_par_iter()
+            .map(|_| {
+                let mut rng = thread_rng();
+                let a = rng.gen_biguint_range(&BigUint::one(), &order);
+                let point = E::generator().sw_scalar_mul(&a).unwrap();
+                let scalar = rng.gen_biguint_range(&BigUint::one(), &order);
+                (point, scalar)
+            })
+            .collect::<Vec<_>>();
+
+        let writer = generator.new_writer();
+        inputs
+            .par_iter()
+            .enumerate()
+            .for_each(|(k, (point, scalar))| {
+                writer.write_glv_scalar_mul_input(&gadget, point, scalar, 128 * k);
+            });
+        inputs
+            .par_iter()
+            .enumerate()
+            .for_each(|(k, (point, scalar))| {
+                for i in 0..128 {
+                    writer.write_row_instructions(&generator.air_data, 128 * k + i);
+                }
+                let result = writer.read_jacobian_point(&gadget.result, 128 * k + 127);
+                assert_eq!(result.to_affine(), point.sw_scalar_mul(scalar));
+            });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+}
@@ -0,0 +1,271 @@
+use num::{BigUint, One, Zero};
+use serde::{Deserialize, Serialize};
+
+use super::WeierstrassParameters;
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::bignum::sub::BigNumSubInstruction;
+use crate::chip::builder::AirBuilder;
+use crate::chip::ec::point::{AffinePoint, AffinePointRegister};
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::parameters::{FieldParameters, MAX_NB_LIMBS};
+use crate::chip::field::register::FieldRegister;
+use crate::chip::field::sqrt::FpSqrtInstruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::Register;
+use crate::chip::trace::writer::TraceWriter;
+use crate::chip::utils::field_limbs_to_biguint;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+
+/// A deterministic map from a field element `u` to a point of a Weierstrass curve by
+/// try-and-increment.
+///
+/// The result is the point `(u + k, y)` for the least increment `k` such that `(u + k)^3 +
+/// a * (u + k) + b` is a square, with `y <= (p - 1) / 2`. The increment is witnessed by the
+/// one-hot bits `increment_bits`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MapToCurveGadget<E: WeierstrassParameters> {
+    pub input: FieldRegister<E::BaseField>,
+    pub increment_bits: ArrayRegister<BitRegister>,
+    pub result: AffinePointRegister<E>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Maps the field element `u` to a point, trying the increments `0, ..., nb_tries - 1`.
+    ///
+    /// For the selected increment `k`, the gadget takes the square root of the right-hand side
+    /// `f(u + k)` of the curve equation. For every `i < k`, it takes the square root of
+    /// `g * f(u + i)` for a fixed non-square `g`, which proves that `f(u + i)` is not a square
+    /// unless it is zero. The coordinates of the result are asserted to be in canonical form, so
+    /// the map is deterministic. An input without a point in the first `nb_tries` increments,
+    /// which happens with probability about `2^-nb_tries`, has no valid witness.
+    pub fn map_to_curve<E: WeierstrassParameters>(
+        &mut self,
+        u: &FieldRegister<E::BaseField>,
+        nb_tries: usize,
+    ) -> MapToCurveGadget<E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>
+            + From<FpSqrtInstruction<E::BaseField>>
+            + From<BigNumSubInstruction>,
+    {
+        assert!(nb_tries > 0, "At least one increment must be tried");
+
+        // Exactly one of the increments is selected.
+        let increment_bits = self.alloc_array::<BitRegister>(nb_tries);
+        let nb_selected = increment_bits
+            .iter()
+            .fold(ArithmeticExpression::zero(), |acc, bit| acc + bit.expr());
+        self.assert_expression_zero(nb_selected - L::Field::ONE);
+
+        let p = E::BaseField::modulus();
+        let mut non_residue_limbs = [0u16; MAX_NB_LIMBS];
+        non_residue_limbs[0] = smallest_non_residue(&p);
+
+        let b = self.alloc_constant_field_register::<E::BaseField>(&E::b_biguint());
+        let mut x_values = Vec::with_capacity(nb_tries);
+        let mut y_values = Vec::with_capacity(nb_tries);
+        for i in 0..nb_tries {
+            let x = if i == 0 {
+                *u
+            } else {
+                let increment = self.alloc_constant_field_register(&BigUint::from(i));
+                self.fp_add(u, &increment)
+            };
+
+            // Compute f(x) = x^3 + a * x + b and g * f(x).
+            let x_squared = self.fp_mul(&x, &x).result;
+            let x_cubed = self.fp_mul(&x_squared, &x).result;
+            let mut rhs = self.fp_add(&x_cubed, &b);
+            if !E::a_biguint().is_zero() {
+                let a_x = self.fp_mul_const(&x, E::A).result;
+                rhs = self.fp_add(&rhs, &a_x);
+            }
+            let non_residue_rhs = self.fp_mul_const(&rhs, non_residue_limbs).result;
+
+            // Take the square root of f(x) at the selected increment, of g * f(x) at the previous
+            // increments, and of zero at the following ones.
+            let is_selected = increment_bits.get(i).expr();
+            let is_before = (i + 1..nb_tries).fold(ArithmeticExpression::zero(), |acc, j| {
+                acc + increment_bits.get(j).expr()
+            });
+            let radicand = self.alloc::<FieldRegister<E::BaseField>>();
+            self.set_to_expression(
+                &radicand,
+                is_selected * rhs.expr() + is_before * non_residue_rhs.expr(),
+            );
+            let y = self.fp_sqrt(&radicand);
+
+            x_values.push(x);
+            y_values.push(y);
+        }
+
+        // The result is the point at the selected increment.
+        let result_x = self.alloc::<FieldRegister<E::BaseField>>();
+        let result_y = self.alloc::<FieldRegister<E::BaseField>>();
+        for (result, values) in [(result_x, x_values), (result_y, y_values)] {
+            let value = values
+                .iter()
+                .enumerate()
+                .map(|(i, value)| increment_bits.get(i).expr() * value.expr())
+                .reduce(|acc, term| acc + term)
+                .unwrap();
+            self.set_to_expression(&result, value);
+        }
+
+        // Assert that x < p and y <= (p - 1) / 2.
+        self.assert_limbs_at_most(&result_x, &(&p - 1u32));
+        self.assert_limbs_at_most(&result_y, &((&p - 1u32) >> 1));
+
+        MapToCurveGadget {
+            input: *u,
+            increment_bits,
+            result: AffinePointRegister::new(result_x, result_y),
+        }
+    }
+}
+
+/// Returns the smallest integer which is not a square modulo the odd prime `p`.
+fn smallest_non_residue(p: &BigUint) -> u16 {
+    let legendre_exponent = (p - 1u32) >> 1;
+    (2..=u16::MAX)
+        .find(|g| {
+            let g = BigUint::from(*g);
+            let legendre = g.modpow(&legendre_exponent, p);
+            !legendre.is_one() && !legendre.is_zero()
+        })
+        .expect("No small non-residue")
+}
+
+impl<F: PrimeField64> TraceWriter<F> {
+    /// Writes the increment bits of the map for the input in the given row, which must already
+    /// be written, and returns the resulting point.
+    pub fn write_map_to_curve_increment<E: WeierstrassParameters>(
+        &self,
+        gadget: &MapToCurveGadget<E>,
+        row_index: usize,
+    ) -> AffinePoint<E> {
+        let nb_tries = gadget.increment_bits.len();
+        let u = field_limbs_to_biguint(self.read(&gadget.input, row_index).coefficients());
+        let (increment, point) = AffinePoint::<E>::sw_map_to_curve(&u, nb_tries)
+            .unwrap_or_else(|| panic!("No point found in {nb_tries} increments"));
+        let bits = (0..nb_tries).map(|i| F::from_canonical_u8((i == increment) as u8));
+        self.write_array(&gadget.increment_bits, bits, row_index);
+        point
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::ec::gadget::EllipticCurveWriter;
+    use crate::chip::ec::weierstrass::ecdsa::Secp256k1EcdsaInstruction;
+    use crate::chip::ec::weierstrass::secp256k1::{Secp256k1, Secp256k1BaseField};
+    use crate::chip::hash::sha::sha256::SHA256Gadget;
+    use crate::polynomial::to_u16_le_limbs_polynomial;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct MapToCurveTest;
+
+    impl AirParameters for MapToCurveTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 2420;
+        const NUM_FREE_COLUMNS: usize = 7;
+        const EXTENDED_COLUMNS: usize = 3639;
+
+        type Instruction = Secp256k1EcdsaInstruction;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    const NB_TRIES: usize = 4;
+
+    /// Hashes `message` with SHA-256 and reduces the digest modulo p.
+    fn hash_to_field(message: &[u8]) -> BigUint {
+        let digest = SHA256Gadget::hash(message);
+        BigUint::from_bytes_be(&digest) % Secp256k1BaseField::modulus()
+    }
+
+    #[test]
+    fn test_map_to_curve_native() {
+        type E = Secp256k1;
+        let p = Secp256k1BaseField::modulus();
+
+        let mut rng = thread_rng();
+        for _ in 0..100 {
+            let u = hash_to_field(&rng.gen::<[u8; 32]>());
+            let (increment, point) = AffinePoint::<E>::sw_map_to_curve(&u, 64).unwrap();
+            assert_eq!(point.x, (&u + increment) % &p);
+            assert_eq!(
+                (&point.y * &point.y) % &p,
+                (&point.x * &point.x * &point.x + E::b_biguint()) % &p
+            );
+            assert!(point.y <= (&p - 1u32) >> 1);
+            // No smaller increment gives a point.
+            if increment > 0 {
+                assert!(AffinePoint::<E>::sw_map_to_curve(&u, increment).is_none());
+            }
+        }
+    }
+
+    #[test]
+    fn test_map_to_curve() {
+        type F = GoldilocksField;
+        type L = MapToCurveTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type E = Secp256k1;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let u = builder.alloc::<FieldRegister<Secp256k1BaseField>>();
+        let gadget = builder.map_to_curve::<E>(&u, NB_TRIES);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        // The first row maps a fixed input, and the other rows map the hashes of their index with
+        // a counter, taking the first counter for which the map succeeds.
+        let fixed_input = hash_to_field(b"curta hash to curve");
+        let (_, fixed_point) = AffinePoint::<E>::sw_map_to_curve(&fixed_input, NB_TRIES).unwrap();
+
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            let writer = generator.new_writer();
+            let input = if i == 0 {
+                fixed_input.clone()
+            } else {
+                (0u32..)
+                    .map(|counter| {
+                        hash_to_field(&[&i.to_le_bytes()[..], &counter.to_le_bytes()].concat())
+                    })
+                    .find(|u| AffinePoint::<E>::sw_map_to_curve(u, NB_TRIES).is_some())
+                    .unwrap()
+            };
+            let p_input = to_u16_le_limbs_polynomial::<F, Secp256k1BaseField>(&input);
+            writer.write(&u, &p_input, i);
+            let expected = writer.write_map_to_curve_increment(&gadget, i);
+            writer.write_row_instructions(&generator.air_data, i);
+
+            assert_eq!(writer.read_ec_point(&gadget.result, i), expected);
+            if i == 0 {
+                assert_eq!(expected, fixed_point);
+            }
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+}
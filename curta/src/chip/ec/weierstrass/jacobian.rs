@@ -0,0 +1,621 @@
+use num::{BigUint, One, Zero};
+
+use super::bls12_381::Bls12381;
+use super::bn254::Bn254;
+use super::p256::P256;
+use super::secp256k1::Secp256k1;
+use super::WeierstrassParameters;
+use crate::chip::builder::AirBuilder;
+use crate::chip::ec::gadget::CurveValidation;
+use crate::chip::ec::model::CurveModel;
+use crate::chip::ec::point::{
+    AffinePoint, AffinePointRegister, JacobianPoint, JacobianPointRegister,
+};
+use crate::chip::ec::EllipticCurveParameters;
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::parameters::{FieldParameters, MAX_NB_LIMBS};
+use crate::chip::field::register::FieldRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::Register;
+use crate::chip::trace::writer::TraceWriter;
+use crate::chip::utils::field_limbs_to_biguint;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::to_u16_le_limbs_polynomial;
+
+/// The limbs of a small constant, to be used with `fp_mul_const`.
+fn small_constant(value: u16) -> [u16; MAX_NB_LIMBS] {
+    let mut limbs = [0u16; MAX_NB_LIMBS];
+    limbs[0] = value;
+    limbs
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    pub fn alloc_jacobian_point<E: EllipticCurveParameters>(&mut self) -> JacobianPointRegister<E> {
+        let x = self.alloc::<FieldRegister<E::BaseField>>();
+        let y = self.alloc::<FieldRegister<E::BaseField>>();
+        let z = self.alloc::<FieldRegister<E::BaseField>>();
+        JacobianPointRegister::new(x, y, z)
+    }
+
+    /// Allocates registers for a Jacobian point without range-checking.
+    pub fn alloc_unchecked_jacobian_point<E: EllipticCurveParameters>(
+        &mut self,
+    ) -> JacobianPointRegister<E> {
+        let x = FieldRegister::<E::BaseField>::from_register(
+            self.get_local_memory(E::BaseField::NB_LIMBS),
+        );
+        let y = FieldRegister::<E::BaseField>::from_register(
+            self.get_local_memory(E::BaseField::NB_LIMBS),
+        );
+        let z = FieldRegister::<E::BaseField>::from_register(
+            self.get_local_memory(E::BaseField::NB_LIMBS),
+        );
+        JacobianPointRegister::new(x, y, z)
+    }
+
+    /// Allocates a point that is equal to the point at infinity `(1 : 1 : 0)` in every row.
+    pub fn jacobian_infinity<E: EllipticCurveParameters>(&mut self) -> JacobianPointRegister<E> {
+        let infinity = JacobianPoint::<E>::infinity();
+        let x = self.alloc_constant_field_register(&infinity.x);
+        let y = self.alloc_constant_field_register(&infinity.y);
+        let z = self.alloc_constant_field_register(&infinity.z);
+        JacobianPointRegister::new(x, y, z)
+    }
+
+    /// Converts an affine point to Jacobian coordinates `(x : y : 1)`.
+    pub fn affine_to_jacobian<E: EllipticCurveParameters>(
+        &mut self,
+        p: &AffinePointRegister<E>,
+    ) -> JacobianPointRegister<E> {
+        let z = self.alloc_constant_field_register(&BigUint::one());
+        JacobianPointRegister::new(p.x, p.y, z)
+    }
+
+    /// Selects `p` if `bit` is one and `q` otherwise, coordinate by coordinate.
+    pub fn select_jacobian_point<E: EllipticCurveParameters>(
+        &mut self,
+        bit: &BitRegister,
+        p: &JacobianPointRegister<E>,
+        q: &JacobianPointRegister<E>,
+    ) -> JacobianPointRegister<E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        let x = self.select(bit, &p.x, &q.x);
+        let y = self.select(bit, &p.y, &q.y);
+        let z = self.select(bit, &p.z, &q.z);
+        JacobianPointRegister::new(x, y, z)
+    }
+
+    /// Doubles a point in Jacobian coordinates.
+    ///
+    /// The point at infinity is mapped to a point with `Z = 0`, and so to the point at infinity.
+    pub fn jacobian_double<E: WeierstrassParameters>(
+        &mut self,
+        p: &JacobianPointRegister<E>,
+    ) -> JacobianPointRegister<E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        // Weierstrass Elliptic Curve Doubling Formula (dbl-1998-cmo-2)
+        //
+        // Given a point (X1 : Y1 : Z1), compute the double (X3 : Y3 : Z3) with
+        //
+        // X3 = M^2 - 2 * S
+        // Y3 = M * (S - X3) - 8 * Y1^4
+        // Z3 = 2 * Y1 * Z1
+        //
+        // where S = 4 * X1 * Y1^2 and M = 3 * X1^2 + a * Z1^4.
+        //
+        // Reference: https://hyperelliptic.org/EFD/g1p/auto-shortw-jacobian.html
+
+        let x1 = p.x;
+        let y1 = p.y;
+        let z1 = p.z;
+
+        let xx = self.fp_mul(&x1, &x1).result;
+        let yy = self.fp_mul(&y1, &y1).result;
+        let yyyy = self.fp_mul(&yy, &yy).result;
+
+        // S = 4 * X1 * Y1^2.
+        let x1_mul_yy = self.fp_mul(&x1, &yy).result;
+        let s = self.fp_mul_const(&x1_mul_yy, small_constant(4)).result;
+
+        // M = 3 * X1^2 + a * Z1^4, where the second term is omitted for curves with `a = 0`.
+        let three_xx = self.fp_mul_const(&xx, small_constant(3)).result;
+        let m = if E::a_biguint().is_zero() {
+            three_xx
+        } else {
+            let zz = self.fp_mul(&z1, &z1).result;
+            let zzzz = self.fp_mul(&zz, &zz).result;
+            let a_zzzz = self.fp_mul_const(&zzzz, E::A).result;
+            self.fp_add(&three_xx, &a_zzzz)
+        };
+
+        // X3 = M^2 - 2 * S.
+        let m_squared = self.fp_mul(&m, &m).result;
+        let two_s = self.fp_add(&s, &s);
+        let x3 = self.fp_sub(&m_squared, &two_s);
+
+        // Y3 = M * (S - X3) - 8 * Y1^4.
+        let s_minus_x3 = self.fp_sub(&s, &x3);
+        let m_mul_s_minus_x3 = self.fp_mul(&m, &s_minus_x3).result;
+        let eight_yyyy = self.fp_mul_const(&yyyy, small_constant(8)).result;
+        let y3 = self.fp_sub(&m_mul_s_minus_x3, &eight_yyyy);
+
+        // Z3 = 2 * Y1 * Z1.
+        let y1_mul_z1 = self.fp_mul(&y1, &z1).result;
+        let z3 = self.fp_add(&y1_mul_z1, &y1_mul_z1);
+
+        JacobianPointRegister::new(x3, y3, z3)
+    }
+
+    /// Adds two points in Jacobian coordinates.
+    ///
+    /// The addition is complete: if one of the points is the point at infinity the other one is
+    /// returned, if the points coincide the doubling formula is used, and if the points are
+    /// opposite the result has `Z = 0`.
+    pub fn jacobian_add<E: WeierstrassParameters>(
+        &mut self,
+        p: &JacobianPointRegister<E>,
+        q: &JacobianPointRegister<E>,
+    ) -> JacobianPointRegister<E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        // Weierstrass Elliptic Curve Addition Formula (add-1998-cmo-2)
+        //
+        // Given two points (X1 : Y1 : Z1) and (X2 : Y2 : Z2), compute the sum (X3 : Y3 : Z3) with
+        //
+        // X3 = r^2 - H^3 - 2 * U1 * H^2
+        // Y3 = r * (U1 * H^2 - X3) - S1 * H^3
+        // Z3 = Z1 * Z2 * H
+        //
+        // where U1 = X1 * Z2^2, U2 = X2 * Z1^2, S1 = Y1 * Z2^3, S2 = Y2 * Z1^3, H = U2 - U1 and
+        // r = S2 - S1.
+        //
+        // Reference: https://hyperelliptic.org/EFD/g1p/auto-shortw-jacobian.html
+
+        let x1 = p.x;
+        let y1 = p.y;
+        let z1 = p.z;
+        let x2 = q.x;
+        let y2 = q.y;
+        let z2 = q.z;
+
+        let z1z1 = self.fp_mul(&z1, &z1).result;
+        let z2z2 = self.fp_mul(&z2, &z2).result;
+
+        // U1 = X1 * Z2^2, U2 = X2 * Z1^2.
+        let u1 = self.fp_mul(&x1, &z2z2).result;
+        let u2 = self.fp_mul(&x2, &z1z1).result;
+
+        // S1 = Y1 * Z2^3, S2 = Y2 * Z1^3.
+        let z2_cubed = self.fp_mul(&z2, &z2z2).result;
+        let s1 = self.fp_mul(&y1, &z2_cubed).result;
+        let z1_cubed = self.fp_mul(&z1, &z1z1).result;
+        let s2 = self.fp_mul(&y2, &z1_cubed).result;
+
+        // H = U2 - U1, r = S2 - S1.
+        let h = self.fp_sub(&u2, &u1);
+        let r = self.fp_sub(&s2, &s1);
+
+        let hh = self.fp_mul(&h, &h).result;
+        let hhh = self.fp_mul(&h, &hh).result;
+        let v = self.fp_mul(&u1, &hh).result;
+
+        // X3 = r^2 - H^3 - 2 * V, where V = U1 * H^2.
+        let r_squared = self.fp_mul(&r, &r).result;
+        let r_squared_minus_hhh = self.fp_sub(&r_squared, &hhh);
+        let two_v = self.fp_add(&v, &v);
+        let x3 = self.fp_sub(&r_squared_minus_hhh, &two_v);
+
+        // Y3 = r * (V - X3) - S1 * H^3.
+        let v_minus_x3 = self.fp_sub(&v, &x3);
+        let r_mul_v_minus_x3 = self.fp_mul(&r, &v_minus_x3).result;
+        let s1_mul_hhh = self.fp_mul(&s1, &hhh).result;
+        let y3 = self.fp_sub(&r_mul_v_minus_x3, &s1_mul_hhh);
+
+        // Z3 = Z1 * Z2 * H.
+        let z1_mul_z2 = self.fp_mul(&z1, &z2).result;
+        let z3 = self.fp_mul(&z1_mul_z2, &h).result;
+
+        let sum = JacobianPointRegister::new(x3, y3, z3);
+
+        // The formula fails when the points coincide, in which case H = r = 0, and when one of
+        // the points is the point at infinity. When the points are opposite, H = 0 and r != 0 so
+        // that Z3 = 0 as expected.
+        let z1_is_zero = self.fp_is_zero(&z1);
+        let z2_is_zero = self.fp_is_zero(&z2);
+        let h_is_zero = self.fp_is_zero(&h);
+        let r_is_zero = self.fp_is_zero(&r);
+
+        let is_double = self.alloc::<BitRegister>();
+        self.set_to_expression(&is_double, h_is_zero.expr::<L::Field>() * r_is_zero.expr());
+        let double = self.jacobian_double(p);
+
+        let result = self.select_jacobian_point(&is_double, &double, &sum);
+        let result = self.select_jacobian_point(&z2_is_zero, p, &result);
+        self.select_jacobian_point(&z1_is_zero, q, &result)
+    }
+
+    /// Computes one step of the multi-scalar multiplication `sum_j scalar_j * point_j` with a
+    /// shared double-and-add (Straus' method):
+    ///
+    /// result = 2 * accumulator + sum_j bit_j * point_j
+    ///
+    /// With the scalar bits given most significant bit first, and the accumulator starting at the
+    /// point at infinity, the result after the last step is the sum of the products.
+    pub fn jacobian_multi_scalar_mul_step<E: WeierstrassParameters>(
+        &mut self,
+        accumulator: &JacobianPointRegister<E>,
+        bits: &[BitRegister],
+        points: &[JacobianPointRegister<E>],
+    ) -> JacobianPointRegister<E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        assert_eq!(bits.len(), points.len(), "One scalar is needed per point");
+
+        // result = 2 * accumulator.
+        let mut result = self.jacobian_double(accumulator);
+
+        // result = if bit_j == 1 then result + point_j else result.
+        for (bit, point) in bits.iter().zip(points.iter()) {
+            let sum = self.jacobian_add(&result, point);
+            result = self.select_jacobian_point(bit, &sum, &result);
+        }
+        result
+    }
+
+    /// Converts a point in Jacobian coordinates to affine coordinates `(X / Z^2, Y / Z^3)`.
+    ///
+    /// This is the only inversion needed for a sequence of Jacobian operations. The point must not
+    /// be the point at infinity.
+    pub fn jacobian_to_affine<E: WeierstrassParameters>(
+        &mut self,
+        p: &JacobianPointRegister<E>,
+    ) -> AffinePointRegister<E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        let zz = self.fp_mul(&p.z, &p.z).result;
+        let zzz = self.fp_mul(&zz, &p.z).result;
+        let x = self.fp_div(&p.x, &zz);
+        let y = self.fp_div(&p.y, &zzz);
+        AffinePointRegister::new(x, y)
+    }
+}
+
+/// Implements `CurveModel` for a short Weierstrass curve with the Jacobian gadgets above, whose
+/// addition is complete, and `CurveValidation` with the affine curve equation.
+macro_rules! impl_jacobian_curve_model {
+    ($curve:ty) => {
+        impl CurveModel for $curve {
+            type PointRegister = JacobianPointRegister<$curve>;
+
+            fn from_affine<L: AirParameters>(
+                builder: &mut AirBuilder<L>,
+                p: &AffinePointRegister<$curve>,
+            ) -> JacobianPointRegister<$curve>
+            where
+                L::Instruction: FromFieldInstruction<Self::BaseField>,
+            {
+                builder.affine_to_jacobian(p)
+            }
+
+            fn to_affine<L: AirParameters>(
+                builder: &mut AirBuilder<L>,
+                p: &JacobianPointRegister<$curve>,
+            ) -> AffinePointRegister<$curve>
+            where
+                L::Instruction: FromFieldInstruction<Self::BaseField>,
+            {
+                builder.jacobian_to_affine(p)
+            }
+
+            fn neutral<L: AirParameters>(
+                builder: &mut AirBuilder<L>,
+            ) -> JacobianPointRegister<$curve>
+            where
+                L::Instruction: FromFieldInstruction<Self::BaseField>,
+            {
+                builder.jacobian_infinity()
+            }
+
+            fn add<L: AirParameters>(
+                builder: &mut AirBuilder<L>,
+                p: &JacobianPointRegister<$curve>,
+                q: &JacobianPointRegister<$curve>,
+            ) -> JacobianPointRegister<$curve>
+            where
+                L::Instruction: FromFieldInstruction<Self::BaseField>,
+            {
+                builder.jacobian_add(p, q)
+            }
+
+            fn double<L: AirParameters>(
+                builder: &mut AirBuilder<L>,
+                p: &JacobianPointRegister<$curve>,
+            ) -> JacobianPointRegister<$curve>
+            where
+                L::Instruction: FromFieldInstruction<Self::BaseField>,
+            {
+                builder.jacobian_double(p)
+            }
+
+            fn select<L: AirParameters>(
+                builder: &mut AirBuilder<L>,
+                bit: &BitRegister,
+                p: &JacobianPointRegister<$curve>,
+                q: &JacobianPointRegister<$curve>,
+            ) -> JacobianPointRegister<$curve>
+            where
+                L::Instruction: FromFieldInstruction<Self::BaseField>,
+            {
+                builder.select_jacobian_point(bit, p, q)
+            }
+        }
+
+        impl CurveValidation for $curve {
+            fn cofactor() -> BigUint {
+                <$curve as WeierstrassParameters>::cofactor()
+            }
+
+            fn assert_curve_equation<L: AirParameters>(
+                builder: &mut AirBuilder<L>,
+                p: &AffinePointRegister<$curve>,
+            ) where
+                L::Instruction: FromFieldInstruction<Self::BaseField>,
+            {
+                builder.sw_assert_on_curve(p);
+            }
+
+            fn cofactor_root(p: &AffinePoint<$curve>) -> AffinePoint<$curve> {
+                let order = <$curve as WeierstrassParameters>::prime_group_order();
+                let cofactor = <$curve as WeierstrassParameters>::cofactor();
+                let cofactor_inverse = cofactor.modpow(&(&order - 2u32), &order);
+                p.sw_scalar_mul(&cofactor_inverse)
+                    .expect("The point is not in the prime subgroup")
+            }
+        }
+    };
+}
+
+impl_jacobian_curve_model!(Secp256k1);
+impl_jacobian_curve_model!(P256);
+impl_jacobian_curve_model!(Bn254);
+impl_jacobian_curve_model!(Bls12381);
+
+impl<F: PrimeField64> TraceWriter<F> {
+    pub fn read_jacobian_point<E: EllipticCurveParameters>(
+        &self,
+        data: &JacobianPointRegister<E>,
+        row_index: usize,
+    ) -> JacobianPoint<E> {
+        let p_x = self.read(&data.x, row_index);
+        let p_y = self.read(&data.y, row_index);
+        let p_z = self.read(&data.z, row_index);
+
+        let x = field_limbs_to_biguint(p_x.coefficients());
+        let y = field_limbs_to_biguint(p_y.coefficients());
+        let z = field_limbs_to_biguint(p_z.coefficients());
+
+        JacobianPoint::new(x, y, z)
+    }
+
+    pub fn write_jacobian_point<E: EllipticCurveParameters>(
+        &self,
+        data: &JacobianPointRegister<E>,
+        value: &JacobianPoint<E>,
+        row_index: usize,
+    ) {
+        let value_x = to_u16_le_limbs_polynomial::<F, E::BaseField>(&value.x);
+        let value_y = to_u16_le_limbs_polynomial::<F, E::BaseField>(&value.y);
+        let value_z = to_u16_le_limbs_polynomial::<F, E::BaseField>(&value.z);
+        self.write(&data.x, &value_x, row_index);
+        self.write(&data.y, &value_y, row_index);
+        self.write(&data.z, &value_z, row_index);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use num::BigUint;
+    use rand::thread_rng;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::arithmetic::expression::ArithmeticExpression;
+    use crate::chip::builder::tests::*;
+    use crate::chip::ec::gadget::EllipticCurveWriter;
+    use crate::chip::ec::point::AffinePoint;
+    use crate::chip::ec::weierstrass::secp256k1::{Secp256k1, Secp256k1BaseField};
+    use crate::chip::field::instruction::FpInstruction;
+    use crate::chip::register::RegisterSerializable;
+    use crate::chip::utils::biguint_to_bits_le;
+
+    /// Returns a representation `(x * z^2 : y * z^3 : z)` of `point` with a random `z`.
+    fn random_jacobian<E: WeierstrassParameters>(point: &AffinePoint<E>) -> JacobianPoint<E> {
+        let p = E::BaseField::modulus();
+        let mut rng = thread_rng();
+        let z = rng.gen_biguint_range(&BigUint::from(1u32), &p);
+        let zz = (&z * &z) % &p;
+        let zzz = (&zz * &z) % &p;
+        JacobianPoint::new((&point.x * &zz) % &p, (&point.y * &zzz) % &p, z)
+    }
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct JacobianAddTest;
+
+    impl AirParameters for JacobianAddTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 4104;
+        const NUM_FREE_COLUMNS: usize = 6;
+        const EXTENDED_COLUMNS: usize = 6165;
+
+        type Instruction = FpInstruction<Secp256k1BaseField>;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    #[test]
+    fn test_jacobian_add_edge_cases() {
+        type L = JacobianAddTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type E = Secp256k1;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let p = builder.alloc_jacobian_point::<E>();
+        let q = builder.alloc_jacobian_point::<E>();
+        let sum = builder.jacobian_add(&p, &q);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        // The points G, 2G, ..., 16G.
+        let base = E::generator();
+        let mut points = vec![base.clone()];
+        points.push(base.sw_double());
+        for i in 2..16 {
+            let next = points[i - 1].sw_add(&base);
+            points.push(next);
+        }
+
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            let writer = generator.new_writer();
+            let a = &points[i % 16];
+            let b = &points[(i + 1) % 16];
+            // Cycle through the generic case, equal points, opposite points and the point at
+            // infinity as either input.
+            let (p_value, q_value, expected) = match i % 5 {
+                0 => (random_jacobian(a), random_jacobian(b), Some(a.sw_add(b))),
+                1 => (random_jacobian(a), random_jacobian(a), Some(a.sw_double())),
+                2 => (random_jacobian(a), random_jacobian(&a.sw_neg()), None),
+                3 => (
+                    JacobianPoint::infinity(),
+                    random_jacobian(b),
+                    Some(b.clone()),
+                ),
+                _ => (
+                    random_jacobian(a),
+                    JacobianPoint::infinity(),
+                    Some(a.clone()),
+                ),
+            };
+            writer.write_jacobian_point(&p, &p_value, i);
+            writer.write_jacobian_point(&q, &q_value, i);
+            writer.write_row_instructions(&generator.air_data, i);
+
+            let sum_value = writer.read_jacobian_point(&sum, i);
+            assert_eq!(sum_value.to_affine(), expected);
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct JacobianScalarMulTest;
+
+    impl AirParameters for JacobianScalarMulTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 6020;
+        const NUM_FREE_COLUMNS: usize = 108;
+        const EXTENDED_COLUMNS: usize = 9039;
+
+        type Instruction = FpInstruction<Secp256k1BaseField>;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    #[test]
+    fn test_jacobian_scalar_mul() {
+        type F = GoldilocksField;
+        type L = JacobianScalarMulTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type E = Secp256k1;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        // Each cycle of 256 rows computes one scalar multiplication with the double-and-add
+        // algorithm, one bit per row.
+        let cycle = builder.cycle(8);
+        let result = builder.alloc_unchecked_jacobian_point::<E>();
+        let temp = builder.alloc_unchecked_jacobian_point::<E>();
+        let bit = builder.alloc::<BitRegister>();
+
+        let sum = builder.jacobian_add(&result, &temp);
+        let temp_next = builder.jacobian_double(&temp);
+        let result_next = builder.select_jacobian_point(&bit, &sum, &result);
+        let affine_result = builder.jacobian_to_affine(&result_next);
+
+        // Copy the results to the next row, except at the start of a new cycle.
+        let flag_bit = cycle.start_bit.next().expr::<F>();
+        for (register, value) in [
+            (result.x, result_next.x),
+            (result.y, result_next.y),
+            (result.z, result_next.z),
+            (temp.x, temp_next.x),
+            (temp.y, temp_next.y),
+            (temp.z, temp_next.z),
+        ] {
+            let next_value = flag_bit.clone() * register.next().expr()
+                + (ArithmeticExpression::one() - flag_bit.clone()) * value.expr();
+            builder.set_to_expression_transition(&register.next(), next_value);
+        }
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let writer = generator.new_writer();
+        let nb_bits = E::nb_scalar_bits();
+        let base = E::generator();
+        let order = E::prime_group_order();
+        (0..256usize).into_par_iter().for_each(|k| {
+            let starting_row = 256 * k;
+            let mut rng = thread_rng();
+            // Odd scalars ensure that none of the partial results is the point at infinity, which
+            // has no affine representation.
+            let scalar = rng.gen_biguint_below(&order) | BigUint::from(1u32);
+            writer.write_jacobian_point(&result, &JacobianPoint::infinity(), starting_row);
+            writer.write_jacobian_point(&temp, &JacobianPoint::from_affine(&base), starting_row);
+            let scalar_bits = biguint_to_bits_le(&scalar, nb_bits);
+            for (i, bit_value) in scalar_bits.iter().enumerate() {
+                let f_bit = F::from_canonical_u8(*bit_value as u8);
+                writer.write(&bit, &f_bit, starting_row + i);
+                writer.write_row_instructions(&generator.air_data, starting_row + i);
+            }
+
+            let expected = base.sw_scalar_mul(&scalar).unwrap();
+            let last_row = starting_row + nb_bits - 1;
+            assert_eq!(writer.read_ec_point(&affine_result, last_row), expected);
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+}
@@ -0,0 +1,93 @@
+use num::{BigUint, Zero};
+
+use super::point::AffinePoint;
+use super::EllipticCurveParameters;
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::bignum::sub::BigNumSubInstruction;
+use crate::chip::builder::AirBuilder;
+use crate::chip::field::parameters::{FieldParameters, MAX_NB_LIMBS};
+use crate::chip::field::register::FieldRegister;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::AirParameters;
+use crate::polynomial::to_u16_le_limbs_polynomial;
+
+pub mod affine;
+pub mod bigint_operations;
+pub mod bls12_381;
+pub mod bn254;
+pub mod ecdsa;
+pub mod glv;
+pub mod hash_to_curve;
+pub mod jacobian;
+pub mod p256;
+pub mod secp256k1;
+
+/// Parameters of a short Weierstrass curve `y^2 = x^3 + a * x + b`.
+pub trait WeierstrassParameters: EllipticCurveParameters {
+    const A: [u16; MAX_NB_LIMBS];
+    const B: [u16; MAX_NB_LIMBS];
+
+    fn generator() -> AffinePoint<Self>;
+
+    fn prime_group_order() -> BigUint;
+
+    /// The index of the prime subgroup in the group of points of the curve.
+    fn cofactor() -> BigUint;
+
+    fn a_biguint() -> BigUint {
+        let mut modulus = BigUint::zero();
+        for (i, limb) in Self::A.iter().enumerate() {
+            modulus += BigUint::from(*limb) << (16 * i);
+        }
+        modulus
+    }
+
+    fn b_biguint() -> BigUint {
+        let mut modulus = BigUint::zero();
+        for (i, limb) in Self::B.iter().enumerate() {
+            modulus += BigUint::from(*limb) << (16 * i);
+        }
+        modulus
+    }
+
+    fn nb_scalar_bits() -> usize {
+        Self::BaseField::NB_LIMBS * 16
+    }
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Allocates a field register that is equal to `value` in every row.
+    ///
+    /// Canonical values are recorded so that field operations on the register can be folded.
+    pub(crate) fn alloc_constant_field_register<P: FieldParameters>(
+        &mut self,
+        value: &BigUint,
+    ) -> FieldRegister<P> {
+        let register = self.alloc::<FieldRegister<P>>();
+        let limbs = to_u16_le_limbs_polynomial::<L::Field, P>(value).coefficients;
+        self.set_to_expression(&register, ArithmeticExpression::from_constant_vec(limbs));
+        if value < &P::modulus() {
+            self.field_constants
+                .insert(*register.register(), value.clone());
+        }
+        register
+    }
+
+    /// Asserts that the integer given by the limbs of `a` is at most `bound`. Together with a
+    /// bound less than `p`, this asserts that `a` is in canonical form.
+    pub(crate) fn assert_limbs_at_most<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        bound: &BigUint,
+    ) where
+        L::Instruction: From<BigNumSubInstruction>,
+    {
+        let bound = self.alloc_constant_field_register::<P>(bound);
+        let a_limbs = ArrayRegister::<U16Register>::from_register_unsafe(*a.register());
+        let bound_limbs = ArrayRegister::<U16Register>::from_register_unsafe(*bound.register());
+        let (_, borrow) = self.bignum_sub(&bound_limbs, &a_limbs);
+        self.assert_expression_zero(borrow.expr());
+    }
+}
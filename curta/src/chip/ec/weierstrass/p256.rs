@@ -0,0 +1,204 @@
+use num::{BigUint, Num};
+use serde::{Deserialize, Serialize};
+
+use super::ecdsa::EcdsaParameters;
+use super::WeierstrassParameters;
+use crate::chip::ec::point::AffinePoint;
+use crate::chip::ec::EllipticCurveParameters;
+use crate::chip::field::parameters::{FieldParameters, MAX_NB_LIMBS};
+
+/// The NIST P-256 curve, also known as secp256r1.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct P256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct P256BaseField;
+
+impl FieldParameters for P256BaseField {
+    const NB_BITS_PER_LIMB: usize = 16;
+    const NB_LIMBS: usize = 16;
+    const NB_WITNESS_LIMBS: usize = 2 * Self::NB_LIMBS - 2;
+    const MODULUS: [u16; MAX_NB_LIMBS] = [
+        65535, 65535, 65535, 65535, 65535, 65535, 0, 0, 0, 0, 0, 0, 1, 0, 65535, 65535, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    const WITNESS_OFFSET: usize = 1usize << 20;
+}
+
+/// The field of integers modulo the order of the P-256 group.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct P256ScalarField;
+
+impl FieldParameters for P256ScalarField {
+    const NB_BITS_PER_LIMB: usize = 16;
+    const NB_LIMBS: usize = 16;
+    const NB_WITNESS_LIMBS: usize = 2 * Self::NB_LIMBS - 2;
+    const MODULUS: [u16; MAX_NB_LIMBS] = [
+        9553, 64611, 51906, 62393, 40580, 42775, 64173, 48358, 65535, 65535, 65535, 65535, 0, 0,
+        65535, 65535, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    const WITNESS_OFFSET: usize = 1usize << 20;
+}
+
+impl EllipticCurveParameters for P256 {
+    type BaseField = P256BaseField;
+}
+
+impl WeierstrassParameters for P256 {
+    // a = p - 3.
+    const A: [u16; MAX_NB_LIMBS] = [
+        65532, 65535, 65535, 65535, 65535, 65535, 0, 0, 0, 0, 0, 0, 1, 0, 65535, 65535, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    const B: [u16; MAX_NB_LIMBS] = [
+        24651, 10194, 15422, 15310, 45302, 52307, 1712, 25885, 34492, 30360, 48469, 46059, 37863,
+        43578, 13784, 23238, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+
+    fn prime_group_order() -> BigUint {
+        BigUint::from_str_radix(
+            "FFFFFFFF00000000FFFFFFFFFFFFFFFFBCE6FAADA7179E84F3B9CAC2FC632551",
+            16,
+        )
+        .unwrap()
+    }
+
+    fn cofactor() -> BigUint {
+        BigUint::from(1u32)
+    }
+
+    fn generator() -> AffinePoint<Self> {
+        let x = BigUint::from_str_radix(
+            "6B17D1F2E12C4247F8BCE6E563A440F277037D812DEB33A0F4A13945D898C296",
+            16,
+        )
+        .unwrap();
+        let y = BigUint::from_str_radix(
+            "4FE342E2FE1A7F9B8EE7EB4A7C0F9E162BCE33576B315ECECBB6406837BF51F5",
+            16,
+        )
+        .unwrap();
+        AffinePoint::new(x, y)
+    }
+}
+
+impl EcdsaParameters for P256 {
+    type ScalarField = P256ScalarField;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::builder::AirBuilder;
+    use crate::chip::ec::point::JacobianPoint;
+    use crate::chip::field::instruction::FpInstruction;
+    use crate::chip::AirParameters;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct P256Test;
+
+    impl AirParameters for P256Test {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 2364;
+        const NUM_FREE_COLUMNS: usize = 1;
+        const EXTENDED_COLUMNS: usize = 3555;
+
+        type Instruction = FpInstruction<P256BaseField>;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    /// The point 2G, from the NIST test vectors.
+    fn generator_double() -> AffinePoint<P256> {
+        let x = BigUint::from_str_radix(
+            "7CF27B188D034F7E8A52380304B51AC3C08969E277F21B35A60B48FC47669978",
+            16,
+        )
+        .unwrap();
+        let y = BigUint::from_str_radix(
+            "07775510DB8ED040293D9AC69F7430DBBA7DADE63CE982299E04B79D227873D1",
+            16,
+        )
+        .unwrap();
+        AffinePoint::new(x, y)
+    }
+
+    #[test]
+    fn test_p256_parameters() {
+        type E = P256;
+        let p = P256BaseField::modulus();
+        let base = E::generator();
+
+        assert_eq!(P256ScalarField::modulus(), E::prime_group_order());
+        assert_eq!(E::a_biguint(), &p - 3u32);
+
+        // The generator lies on the curve y^2 = x^3 - 3 * x + b.
+        assert_eq!(
+            (&base.y * &base.y) % &p,
+            (&base.x * &base.x * &base.x + E::a_biguint() * &base.x + E::b_biguint()) % &p
+        );
+        assert_eq!(base.sw_double(), generator_double());
+        assert_eq!(base.sw_scalar_mul(&E::prime_group_order()), None);
+    }
+
+    #[test]
+    fn test_p256_on_curve_and_double() {
+        type L = P256Test;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type E = P256;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let point = builder.alloc_jacobian_point::<E>();
+
+        // Assert that the point, which is written with Z = 1, is on the curve.
+        let b = builder.alloc_constant_field_register::<P256BaseField>(&E::b_biguint());
+        let y_squared = builder.fp_mul(&point.y, &point.y).result;
+        let x_squared = builder.fp_mul(&point.x, &point.x).result;
+        let x_cubed = builder.fp_mul(&x_squared, &point.x).result;
+        let a_x = builder.fp_mul_const(&point.x, E::A).result;
+        let x_cubed_plus_a_x = builder.fp_add(&x_cubed, &a_x);
+        let rhs = builder.fp_add(&x_cubed_plus_a_x, &b);
+        builder.assert_equal(&y_squared, &rhs);
+
+        let double = builder.jacobian_double(&point);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        // The points G, 2G, ..., 16G.
+        let base = E::generator();
+        let mut points = vec![base.clone(), base.sw_double()];
+        for i in 2..16 {
+            let next = points[i - 1].sw_add(&base);
+            points.push(next);
+        }
+
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            let writer = generator.new_writer();
+            let value = &points[i % 16];
+            writer.write_jacobian_point(&point, &JacobianPoint::from_affine(value), i);
+            writer.write_row_instructions(&generator.air_data, i);
+
+            let double_value = writer.read_jacobian_point(&double, i).to_affine();
+            assert_eq!(double_value, Some(value.sw_double()));
+            if i == 0 {
+                assert_eq!(double_value, Some(generator_double()));
+            }
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+}
@@ -0,0 +1,95 @@
+use num::{BigUint, Num};
+use serde::{Deserialize, Serialize};
+
+use super::ecdsa::EcdsaParameters;
+use super::WeierstrassParameters;
+use crate::chip::ec::point::AffinePoint;
+use crate::chip::ec::EllipticCurveParameters;
+use crate::chip::field::parameters::{FieldParameters, MAX_NB_LIMBS};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Secp256k1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Secp256k1BaseField;
+
+impl FieldParameters for Secp256k1BaseField {
+    const NB_BITS_PER_LIMB: usize = 16;
+    const NB_LIMBS: usize = 16;
+    const NB_WITNESS_LIMBS: usize = 2 * Self::NB_LIMBS - 2;
+    const MODULUS: [u16; MAX_NB_LIMBS] = [
+        64559, 65534, 65535, 65535, 65535, 65535, 65535, 65535, 65535, 65535, 65535, 65535, 65535,
+        65535, 65535, 65535, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    const WITNESS_OFFSET: usize = 1usize << 20;
+}
+
+/// The field of integers modulo the order of the secp256k1 group.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Secp256k1ScalarField;
+
+impl FieldParameters for Secp256k1ScalarField {
+    const NB_BITS_PER_LIMB: usize = 16;
+    const NB_LIMBS: usize = 16;
+    const NB_WITNESS_LIMBS: usize = 2 * Self::NB_LIMBS - 2;
+    const MODULUS: [u16; MAX_NB_LIMBS] = [
+        16705, 53302, 24204, 49106, 41019, 44872, 56550, 47790, 65534, 65535, 65535, 65535, 65535,
+        65535, 65535, 65535, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    const WITNESS_OFFSET: usize = 1usize << 20;
+}
+
+impl EllipticCurveParameters for Secp256k1 {
+    type BaseField = Secp256k1BaseField;
+}
+
+impl WeierstrassParameters for Secp256k1 {
+    const A: [u16; MAX_NB_LIMBS] = [0; MAX_NB_LIMBS];
+    const B: [u16; MAX_NB_LIMBS] = [
+        7, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0,
+    ];
+
+    fn prime_group_order() -> BigUint {
+        BigUint::from_str_radix(
+            "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141",
+            16,
+        )
+        .unwrap()
+    }
+
+    fn cofactor() -> BigUint {
+        BigUint::from(1u32)
+    }
+
+    fn generator() -> AffinePoint<Self> {
+        let x = BigUint::from_str_radix(
+            "79BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798",
+            16,
+        )
+        .unwrap();
+        let y = BigUint::from_str_radix(
+            "483ADA7726A3C4655DA4FBFC0E1108A8FD17B448A68554199C47D08FFB10D4B8",
+            16,
+        )
+        .unwrap();
+        AffinePoint::new(x, y)
+    }
+}
+
+impl EcdsaParameters for Secp256k1 {
+    type ScalarField = Secp256k1ScalarField;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secp256k1_parameters() {
+        assert_eq!(
+            Secp256k1ScalarField::modulus(),
+            Secp256k1::prime_group_order()
+        );
+    }
+}
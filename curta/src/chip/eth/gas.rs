@@ -0,0 +1,98 @@
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::Target;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+/// The number of bits of gas amounts. Block gas limits are far below `2^32`.
+pub const GAS_BITS: usize = 32;
+
+pub trait CircuitBuilderGas<F: RichField + Extendable<D>, const D: usize> {
+    /// Computes the running totals of the per-step gas costs `costs`, asserting that each of them
+    /// is at most `gas_limit`.
+    ///
+    /// The costs and the limit are range checked to `GAS_BITS` bits. Since every total is at
+    /// most the limit, the additions cannot overflow the field.
+    fn gas_accumulator(&mut self, costs: &[Target], gas_limit: Target) -> Vec<Target>;
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderGas<F, D>
+    for CircuitBuilder<F, D>
+{
+    fn gas_accumulator(&mut self, costs: &[Target], gas_limit: Target) -> Vec<Target> {
+        self.range_check(gas_limit, GAS_BITS);
+
+        let mut total = self.zero();
+        costs
+            .iter()
+            .map(|cost| {
+                self.range_check(*cost, GAS_BITS);
+                total = self.add(total, *cost);
+
+                // Since both are small, `gas_limit - total` is a small integer exactly when
+                // `total <= gas_limit`, and wraps around the field modulus otherwise.
+                let remaining = self.sub(gas_limit, total);
+                self.range_check(remaining, GAS_BITS);
+                total
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::Field;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+
+    use super::*;
+
+    type F = GoldilocksField;
+    type C = PoseidonGoldilocksConfig;
+    const D: usize = 2;
+
+    const GAS_LIMIT: u64 = 30_000_000;
+
+    fn prove_gas_accumulator(cost_values: &[u64], gas_limit: u64) {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let costs = builder.add_virtual_targets(cost_values.len());
+        let limit = builder.add_virtual_target();
+        let totals = builder.gas_accumulator(&costs, limit);
+
+        let mut expected = 0;
+        for (total, cost) in totals.iter().zip(cost_values) {
+            expected += cost;
+            let expected_target = builder.constant(F::from_canonical_u64(expected));
+            builder.connect(*total, expected_target);
+        }
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+
+        for (target, cost) in costs.iter().zip(cost_values) {
+            pw.set_target(*target, F::from_canonical_u64(*cost));
+        }
+        pw.set_target(limit, F::from_canonical_u64(gas_limit));
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+
+    #[test]
+    fn test_gas_accumulator() {
+        // A transfer, a few storage writes and calls, ending exactly at the limit.
+        let mut costs = vec![21_000, 20_000, 5_000, 2_600, 100, 700_000];
+        let used: u64 = costs.iter().sum();
+        costs.push(GAS_LIMIT - used);
+        prove_gas_accumulator(&costs, GAS_LIMIT);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_gas_accumulator_exceeds_limit() {
+        prove_gas_accumulator(&[21_000, 20_000_000, 9_979_000, 1], GAS_LIMIT);
+    }
+}
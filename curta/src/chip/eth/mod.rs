@@ -0,0 +1,6 @@
+//! Gadgets for EVM words, execution and precompiles, and for SSZ Merkleization.
+
+pub mod gas;
+pub mod precompile;
+pub mod ssz;
+pub mod word;
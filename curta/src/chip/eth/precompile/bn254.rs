@@ -0,0 +1,221 @@
+//! The encoding of BN254 points in the ECADD and ECMUL precompiles.
+//!
+//! A point is given by its affine coordinates as two integers, with the point at infinity encoded
+//! as `(0, 0)`. An input is valid if both coordinates are less than the modulus and the point is
+//! either on the curve or the point at infinity. The precompiles fail on invalid inputs, so the
+//! gadgets assert that their inputs are valid.
+
+use num::{BigUint, Zero};
+use serde::{Deserialize, Serialize};
+
+use crate::air::AirConstraint;
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::bignum::instruction::BigNumInstruction;
+use crate::chip::bignum::sub::BigNumSubInstruction;
+use crate::chip::bool::SelectInstruction;
+use crate::chip::builder::AirBuilder;
+use crate::chip::ec::point::{AffinePointRegister, JacobianPointRegister};
+use crate::chip::ec::weierstrass::bn254::{Bn254, Bn254BaseField};
+use crate::chip::ec::weierstrass::WeierstrassParameters;
+use crate::chip::field::add::FpAddInstruction;
+use crate::chip::field::den::FpDenInstruction;
+use crate::chip::field::div::FpDivInstruction;
+use crate::chip::field::inner_product::FpInnerProductInstruction;
+use crate::chip::field::instruction::{FpInstruction, FromFieldInstruction};
+use crate::chip::field::is_zero::FpIsZeroInstruction;
+use crate::chip::field::mul::FpMulInstruction;
+use crate::chip::field::mul_const::FpMulConstInstruction;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::field::register::FieldRegister;
+use crate::chip::field::sub::FpSubInstruction;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::memory::MemorySlice;
+use crate::chip::register::Register;
+use crate::chip::trace::writer::TraceWriter;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::parser::PolynomialParser;
+use crate::polynomial::to_u16_le_limbs_polynomial;
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Asserts that `point` is a valid input of the BN254 precompiles and returns it in Jacobian
+    /// coordinates, with `Z = 0` for the point at infinity.
+    pub fn bn254_precompile_input(
+        &mut self,
+        point: &AffinePointRegister<Bn254>,
+    ) -> JacobianPointRegister<Bn254>
+    where
+        L::Instruction: FromFieldInstruction<Bn254BaseField> + From<BigNumSubInstruction>,
+    {
+        // Check that the coordinates are less than the modulus.
+        let max_coordinate = Bn254BaseField::modulus() - 1u32;
+        self.assert_limbs_at_most(&point.x, &max_coordinate);
+        self.assert_limbs_at_most(&point.y, &max_coordinate);
+
+        let x_is_zero = self.fp_is_zero(&point.x);
+        let y_is_zero = self.fp_is_zero(&point.y);
+        let is_infinity = self.alloc::<BitRegister>();
+        self.set_to_expression(
+            &is_infinity,
+            x_is_zero.expr::<L::Field>() * y_is_zero.expr(),
+        );
+        let is_finite = ArithmeticExpression::one() - is_infinity.expr();
+
+        // Check that y^2 = x^3 + 3 unless the point is the point at infinity.
+        let b = self.alloc_constant_field_register::<Bn254BaseField>(&Bn254::b_biguint());
+        let y_squared = self.fp_mul(&point.y, &point.y).result;
+        let x_squared = self.fp_mul(&point.x, &point.x).result;
+        let x_cubed = self.fp_mul(&x_squared, &point.x).result;
+        let rhs = self.fp_add(&x_cubed, &b);
+        self.assert_expression_zero(is_finite.clone() * (y_squared.expr() - rhs.expr()));
+
+        // The point is (x : y : 1), or (0 : 0 : 0) for the point at infinity.
+        let one_limbs =
+            to_u16_le_limbs_polynomial::<L::Field, Bn254BaseField>(&BigUint::from(1u32));
+        let z = self.alloc::<FieldRegister<Bn254BaseField>>();
+        self.set_to_expression(
+            &z,
+            is_finite * ArithmeticExpression::from_constant_vec(one_limbs.coefficients),
+        );
+
+        JacobianPointRegister::new(point.x, point.y, z)
+    }
+
+    /// Converts a point in Jacobian coordinates to the output encoding of the BN254 precompiles,
+    /// which is `(0, 0)` for the point at infinity.
+    pub fn bn254_precompile_output(
+        &mut self,
+        point: &JacobianPointRegister<Bn254>,
+    ) -> AffinePointRegister<Bn254>
+    where
+        L::Instruction: FromFieldInstruction<Bn254BaseField>,
+    {
+        // Replace Z by one for the point at infinity, so that the conversion to affine
+        // coordinates is defined, and select the output (0, 0) afterwards.
+        let is_infinity = self.fp_is_zero(&point.z);
+        let one = self.alloc_constant_field_register::<Bn254BaseField>(&BigUint::from(1u32));
+        let z = self.select(&is_infinity, &one, &point.z);
+        let affine = self.jacobian_to_affine(&JacobianPointRegister::new(point.x, point.y, z));
+
+        let zero = self.alloc_constant_field_register::<Bn254BaseField>(&BigUint::zero());
+        let x = self.select(&is_infinity, &zero, &affine.x);
+        let y = self.select(&is_infinity, &zero, &affine.y);
+        AffinePointRegister::new(x, y)
+    }
+}
+
+/// The instructions needed for the BN254 precompiles.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Bn254PrecompileInstruction {
+    Base(FpInstruction<Bn254BaseField>),
+    BigNum(BigNumInstruction<Bn254BaseField>),
+}
+
+impl FromFieldInstruction<Bn254BaseField> for Bn254PrecompileInstruction {}
+
+impl<AP: PolynomialParser> AirConstraint<AP> for Bn254PrecompileInstruction {
+    fn eval(&self, parser: &mut AP) {
+        match self {
+            Bn254PrecompileInstruction::Base(instruction) => {
+                AirConstraint::<AP>::eval(instruction, parser)
+            }
+            Bn254PrecompileInstruction::BigNum(instruction) => {
+                AirConstraint::<AP>::eval(instruction, parser)
+            }
+        }
+    }
+}
+
+impl<F: PrimeField64> Instruction<F> for Bn254PrecompileInstruction {
+    fn trace_layout(&self) -> Vec<MemorySlice> {
+        match self {
+            Bn254PrecompileInstruction::Base(instruction) => {
+                Instruction::<F>::trace_layout(instruction)
+            }
+            Bn254PrecompileInstruction::BigNum(instruction) => {
+                Instruction::<F>::trace_layout(instruction)
+            }
+        }
+    }
+
+    fn inputs(&self) -> Vec<MemorySlice> {
+        match self {
+            Bn254PrecompileInstruction::Base(instruction) => Instruction::<F>::inputs(instruction),
+            Bn254PrecompileInstruction::BigNum(instruction) => {
+                Instruction::<F>::inputs(instruction)
+            }
+        }
+    }
+
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        match self {
+            Bn254PrecompileInstruction::Base(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            Bn254PrecompileInstruction::BigNum(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+        }
+    }
+}
+
+impl From<FpAddInstruction<Bn254BaseField>> for Bn254PrecompileInstruction {
+    fn from(instr: FpAddInstruction<Bn254BaseField>) -> Self {
+        Bn254PrecompileInstruction::Base(instr.into())
+    }
+}
+
+impl From<FpMulInstruction<Bn254BaseField>> for Bn254PrecompileInstruction {
+    fn from(instr: FpMulInstruction<Bn254BaseField>) -> Self {
+        Bn254PrecompileInstruction::Base(instr.into())
+    }
+}
+
+impl From<FpMulConstInstruction<Bn254BaseField>> for Bn254PrecompileInstruction {
+    fn from(instr: FpMulConstInstruction<Bn254BaseField>) -> Self {
+        Bn254PrecompileInstruction::Base(instr.into())
+    }
+}
+
+impl From<FpInnerProductInstruction<Bn254BaseField>> for Bn254PrecompileInstruction {
+    fn from(instr: FpInnerProductInstruction<Bn254BaseField>) -> Self {
+        Bn254PrecompileInstruction::Base(instr.into())
+    }
+}
+
+impl From<FpDenInstruction<Bn254BaseField>> for Bn254PrecompileInstruction {
+    fn from(instr: FpDenInstruction<Bn254BaseField>) -> Self {
+        Bn254PrecompileInstruction::Base(instr.into())
+    }
+}
+
+impl From<SelectInstruction<FieldRegister<Bn254BaseField>>> for Bn254PrecompileInstruction {
+    fn from(instr: SelectInstruction<FieldRegister<Bn254BaseField>>) -> Self {
+        Bn254PrecompileInstruction::Base(instr.into())
+    }
+}
+
+impl From<FpSubInstruction<Bn254BaseField>> for Bn254PrecompileInstruction {
+    fn from(instr: FpSubInstruction<Bn254BaseField>) -> Self {
+        Bn254PrecompileInstruction::Base(instr.into())
+    }
+}
+
+impl From<FpDivInstruction<Bn254BaseField>> for Bn254PrecompileInstruction {
+    fn from(instr: FpDivInstruction<Bn254BaseField>) -> Self {
+        Bn254PrecompileInstruction::Base(instr.into())
+    }
+}
+
+impl From<FpIsZeroInstruction<Bn254BaseField>> for Bn254PrecompileInstruction {
+    fn from(instr: FpIsZeroInstruction<Bn254BaseField>) -> Self {
+        Bn254PrecompileInstruction::Base(instr.into())
+    }
+}
+
+impl From<BigNumSubInstruction> for Bn254PrecompileInstruction {
+    fn from(instr: BigNumSubInstruction) -> Self {
+        Bn254PrecompileInstruction::BigNum(BigNumInstruction::Sub(instr))
+    }
+}
@@ -0,0 +1,172 @@
+//! The ECADD precompile, adding two points of the BN254 curve.
+
+use crate::chip::bignum::sub::BigNumSubInstruction;
+use crate::chip::builder::AirBuilder;
+use crate::chip::ec::point::AffinePointRegister;
+use crate::chip::ec::weierstrass::bn254::{Bn254, Bn254BaseField};
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::AirParameters;
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Computes `p + q` as the ECADD precompile, asserting that both inputs are valid.
+    ///
+    /// The inputs and the output use the encoding of the precompile, with `(0, 0)` for the point
+    /// at infinity.
+    pub fn bn254_ecadd(
+        &mut self,
+        p: &AffinePointRegister<Bn254>,
+        q: &AffinePointRegister<Bn254>,
+    ) -> AffinePointRegister<Bn254>
+    where
+        L::Instruction: FromFieldInstruction<Bn254BaseField> + From<BigNumSubInstruction>,
+    {
+        let p = self.bn254_precompile_input(p);
+        let q = self.bn254_precompile_input(q);
+        let sum = self.jacobian_add(&p, &q);
+        self.bn254_precompile_output(&sum)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::{BigUint, Num};
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::ec::gadget::{EllipticCurveGadget, EllipticCurveWriter};
+    use crate::chip::ec::point::AffinePoint;
+    use crate::chip::ec::weierstrass::WeierstrassParameters;
+    use crate::chip::eth::precompile::bn254::Bn254PrecompileInstruction;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct EcAddTest;
+
+    impl AirParameters for EcAddTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 6252;
+        const NUM_FREE_COLUMNS: usize = 17;
+        const EXTENDED_COLUMNS: usize = 9387;
+
+        type Instruction = Bn254PrecompileInstruction;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    /// The inputs of a row and the expected output, if any.
+    type EcAddRow = (
+        AffinePoint<Bn254>,
+        AffinePoint<Bn254>,
+        Option<AffinePoint<Bn254>>,
+    );
+
+    /// The encoding of the point at infinity.
+    fn infinity() -> AffinePoint<Bn254> {
+        AffinePoint::new(BigUint::from(0u32), BigUint::from(0u32))
+    }
+
+    /// The point 2G, the output of the precompile on input `(1, 2, 1, 2)`.
+    fn generator_double() -> AffinePoint<Bn254> {
+        let x = BigUint::from_str_radix(
+            "030644E72E131A029B85045B68181585D97816A916871CA8D3C208C16D87CFD3",
+            16,
+        )
+        .unwrap();
+        let y = BigUint::from_str_radix(
+            "15ED738C0E0A7C92E7845F96B2AE9C0A68A6A449E3538FC7FF3EBF7A5A18A2C4",
+            16,
+        )
+        .unwrap();
+        AffinePoint::new(x, y)
+    }
+
+    /// Proves the addition of the inputs given by `inputs` in each row, checking the result
+    /// against the expected output if there is one.
+    fn prove_ecadd(inputs: impl Fn(usize) -> EcAddRow + Sync) {
+        type L = EcAddTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let p = builder.alloc_ec_point();
+        let q = builder.alloc_ec_point();
+        let sum = builder.bn254_ecadd(&p, &q);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let writer = generator.new_writer();
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            let (p_value, q_value, expected) = inputs(i);
+            writer.write_ec_point(&p, &p_value, i);
+            writer.write_ec_point(&q, &q_value, i);
+            writer.write_row_instructions(&generator.air_data, i);
+
+            if let Some(expected) = expected {
+                assert_eq!(writer.read_ec_point(&sum, i), expected);
+            }
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+
+    /// The points G, 2G, ..., 16G.
+    fn small_multiples() -> Vec<AffinePoint<Bn254>> {
+        let base = Bn254::generator();
+        let mut points = vec![base.clone(), base.sw_double()];
+        for i in 2..16 {
+            let next = points[i - 1].sw_add(&base);
+            points.push(next);
+        }
+        points
+    }
+
+    #[test]
+    fn test_bn254_ecadd() {
+        let points = small_multiples();
+        assert_eq!(points[1], generator_double());
+
+        // Cycle through the generic case, equal points, opposite points and the point at infinity
+        // as either or both inputs.
+        prove_ecadd(|i| {
+            let a = &points[i % 16];
+            let b = &points[(i + 1) % 16];
+            match i % 6 {
+                0 => (a.clone(), b.clone(), Some(a.sw_add(b))),
+                1 => (a.clone(), a.clone(), Some(a.sw_double())),
+                2 => (a.clone(), a.sw_neg(), Some(infinity())),
+                3 => (infinity(), b.clone(), Some(b.clone())),
+                4 => (a.clone(), infinity(), Some(a.clone())),
+                _ => (infinity(), infinity(), Some(infinity())),
+            }
+        });
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bn254_ecadd_invalid_point() {
+        let points = small_multiples();
+
+        // The point (1, 3) is not on the curve.
+        let invalid = AffinePoint::new(BigUint::from(1u32), BigUint::from(3u32));
+        prove_ecadd(|i| {
+            let a = &points[i % 16];
+            if i == 5 {
+                (invalid.clone(), a.clone(), None)
+            } else {
+                (a.clone(), a.clone(), Some(a.sw_double()))
+            }
+        });
+    }
+}
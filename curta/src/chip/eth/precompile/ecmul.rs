@@ -0,0 +1,284 @@
+//! The ECMUL precompile, multiplying a point of the BN254 curve by a scalar.
+
+use num::BigUint;
+use serde::{Deserialize, Serialize};
+
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::bignum::sub::BigNumSubInstruction;
+use crate::chip::builder::AirBuilder;
+use crate::chip::ec::gadget::EllipticCurveWriter;
+use crate::chip::ec::point::{
+    AffinePoint, AffinePointRegister, JacobianPoint, JacobianPointRegister,
+};
+use crate::chip::ec::weierstrass::bn254::{Bn254, Bn254BaseField};
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::instruction::cycle::Cycle;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::Register;
+use crate::chip::trace::writer::TraceWriter;
+use crate::chip::utils::biguint_to_bits_le;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::{to_u16_le_limbs_polynomial, Polynomial};
+
+/// The number of 16-bit limbs of the scalar of the precompile.
+pub const ECMUL_SCALAR_LIMBS: usize = 16;
+
+/// The number of bits of the scalar of the precompile.
+pub const ECMUL_SCALAR_BITS: usize = 16 * ECMUL_SCALAR_LIMBS;
+
+/// The ECMUL precompile, one multiplication per cycle of `2^8` rows.
+///
+/// The inputs are read at the first row of each cycle and copied to the other rows of the cycle.
+/// The scalar is decomposed into bits at the first row, and each row adds one bit to the
+/// accumulator, most significant bit first. The result is read at the last row of the cycle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EcMulGadget<F> {
+    pub cycle: Cycle<F>,
+    pub point: AffinePointRegister<Bn254>,
+    pub scalar: ArrayRegister<U16Register>,
+    /// The output of the precompile for the accumulator after the current row.
+    pub result: AffinePointRegister<Bn254>,
+    scalar_bits: ArrayRegister<BitRegister>,
+    accumulator: JacobianPointRegister<Bn254>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Computes `scalar * point` as the ECMUL precompile, asserting that the point is valid.
+    ///
+    /// The point and the output use the encoding of the precompile, with `(0, 0)` for the point
+    /// at infinity. The scalar is a 256-bit integer given by its little-endian limbs, which is not
+    /// reduced modulo the order of the group.
+    pub fn bn254_ecmul(
+        &mut self,
+        point: &AffinePointRegister<Bn254>,
+        scalar: &ArrayRegister<U16Register>,
+    ) -> EcMulGadget<L::Field>
+    where
+        L::Instruction: FromFieldInstruction<Bn254BaseField> + From<BigNumSubInstruction>,
+    {
+        assert_eq!(scalar.len(), ECMUL_SCALAR_LIMBS, "Invalid number of limbs");
+
+        let cycle = self.cycle(8);
+        let start_bit = cycle.start_bit.expr::<L::Field>();
+        let next_start_bit = cycle.start_bit.next().expr::<L::Field>();
+        let one = ArithmeticExpression::<L::Field>::one();
+
+        // Copy the inputs to the next row, except at the start of a new cycle.
+        for register in [point.x, point.y] {
+            let next_value = next_start_bit.clone() * register.next().expr()
+                + (one.clone() - next_start_bit.clone()) * register.expr();
+            self.set_to_expression_transition(&register.next(), next_value);
+        }
+        for limb in scalar.iter() {
+            let next_value = next_start_bit.clone() * limb.next().expr()
+                + (one.clone() - next_start_bit.clone()) * limb.expr();
+            self.set_to_expression_transition(&limb.next(), next_value);
+        }
+
+        let input = self.bn254_precompile_input(point);
+
+        // At the start of a cycle, the bits are the little endian decomposition of the scalar.
+        let scalar_bits = self.alloc_array::<BitRegister>(ECMUL_SCALAR_BITS);
+        for (j, limb) in scalar.iter().enumerate() {
+            let mut limb_value = ArithmeticExpression::zero();
+            for k in 0..16 {
+                limb_value = limb_value
+                    + scalar_bits.get(16 * j + k).expr() * L::Field::from_canonical_u32(1 << k);
+            }
+            self.assert_expression_zero(start_bit.clone() * (limb_value - limb.expr()));
+        }
+
+        // Shift the bits up by one in the next row, so that each row reads the most significant
+        // bit, except at the start of a new cycle.
+        for k in 0..ECMUL_SCALAR_BITS {
+            let shifted = if k == 0 {
+                ArithmeticExpression::zero()
+            } else {
+                scalar_bits.get(k - 1).expr()
+            };
+            let bit = scalar_bits.get(k);
+            let next_value = next_start_bit.clone() * bit.next().expr()
+                + (one.clone() - next_start_bit.clone()) * shifted;
+            self.set_to_expression_transition(&bit.next(), next_value);
+        }
+
+        // At the start of a cycle, the accumulator is the point at infinity (1 : 1 : 0).
+        let accumulator = self.alloc_unchecked_jacobian_point::<Bn254>();
+        let one_limbs =
+            to_u16_le_limbs_polynomial::<L::Field, Bn254BaseField>(&BigUint::from(1u32))
+                .coefficients;
+        self.assert_expression_zero(start_bit.clone() * (accumulator.x.expr() - one_limbs.clone()));
+        self.assert_expression_zero(start_bit.clone() * (accumulator.y.expr() - one_limbs));
+        self.assert_expression_zero(start_bit * accumulator.z.expr());
+
+        // Compute R = 2 * R + bit * P.
+        let bits = [scalar_bits.get(ECMUL_SCALAR_BITS - 1)];
+        let sum = self.jacobian_multi_scalar_mul_step(&accumulator, &bits, &[input]);
+
+        // Copy the sum to the accumulator of the next row, except at the start of a new cycle.
+        for (register, value) in [
+            (accumulator.x, sum.x),
+            (accumulator.y, sum.y),
+            (accumulator.z, sum.z),
+        ] {
+            let next_value = next_start_bit.clone() * register.next().expr()
+                + (one.clone() - next_start_bit.clone()) * value.expr();
+            self.set_to_expression_transition(&register.next(), next_value);
+        }
+
+        let result = self.bn254_precompile_output(&sum);
+
+        EcMulGadget {
+            cycle,
+            point: *point,
+            scalar: *scalar,
+            result,
+            scalar_bits,
+            accumulator,
+        }
+    }
+}
+
+impl<F: PrimeField64> TraceWriter<F> {
+    /// Writes the inputs of an ECMUL to the first row of a cycle, together with the bits of the
+    /// scalar and the initial value of the accumulator.
+    ///
+    /// The inputs of all cycles must be written before the instructions of any row.
+    pub fn write_ecmul_input(
+        &self,
+        gadget: &EcMulGadget<F>,
+        point: &AffinePoint<Bn254>,
+        scalar: &BigUint,
+        row_index: usize,
+    ) {
+        self.write_ec_point(&gadget.point, point, row_index);
+        let p_scalar = Polynomial::<F>::from_biguint_field(scalar, 16, ECMUL_SCALAR_LIMBS);
+        self.write_array(&gadget.scalar, p_scalar.coefficients, row_index);
+
+        let bit_values = biguint_to_bits_le(scalar, ECMUL_SCALAR_BITS)
+            .into_iter()
+            .map(|bit| F::from_canonical_u8(bit as u8));
+        self.write_array(&gadget.scalar_bits, bit_values, row_index);
+
+        self.write_jacobian_point(&gadget.accumulator, &JacobianPoint::infinity(), row_index);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use num::{One, Zero};
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::ec::gadget::EllipticCurveGadget;
+    use crate::chip::ec::weierstrass::WeierstrassParameters;
+    use crate::chip::eth::precompile::bn254::Bn254PrecompileInstruction;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct EcMulTest;
+
+    impl AirParameters for EcMulTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 6952;
+        const NUM_FREE_COLUMNS: usize = 321;
+        const EXTENDED_COLUMNS: usize = 10437;
+
+        type Instruction = Bn254PrecompileInstruction;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    /// The output of the precompile, with `(0, 0)` for the point at infinity.
+    fn ecmul(point: &AffinePoint<Bn254>, scalar: &BigUint) -> AffinePoint<Bn254> {
+        let infinity = AffinePoint::new(BigUint::zero(), BigUint::zero());
+        if *point == infinity {
+            return infinity;
+        }
+        point.sw_scalar_mul(scalar).unwrap_or(infinity)
+    }
+
+    #[test]
+    fn test_bn254_ecmul() {
+        type L = EcMulTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type E = Bn254;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let point = builder.alloc_ec_point();
+        let scalar = builder.alloc_array::<U16Register>(ECMUL_SCALAR_LIMBS);
+        let gadget = builder.bn254_ecmul(&point, &scalar);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let base = E::generator();
+        let order = E::prime_group_order();
+        let max_scalar = (BigUint::one() << ECMUL_SCALAR_BITS) - 1u32;
+        let infinity = AffinePoint::new(BigUint::zero(), BigUint::zero());
+
+        // Known cases of the precompile: doubling, the group order and zero give the point at
+        // infinity, the point at infinity is absorbing, and the scalar is not reduced.
+        let known = vec![
+            (base.clone(), BigUint::from(2u32)),
+            (base.clone(), order.clone()),
+            (base.clone(), BigUint::zero()),
+            (infinity.clone(), BigUint::from(5u32)),
+            (base.sw_double(), &order + 1u32),
+            (base.clone(), max_scalar),
+        ];
+        assert_eq!(ecmul(&known[0].0, &known[0].1), base.sw_double());
+        assert_eq!(ecmul(&known[1].0, &known[1].1), infinity);
+        assert_eq!(ecmul(&known[4].0, &known[4].1), base.sw_double());
+
+        let nb_cycles = L::num_rows() / 256;
+        let inputs = (0..nb_cycles)
+            .into_par_iter()
+            .map(|k| {
+                if k < known.len() {
+                    return known[k].clone();
+                }
+                let mut rng = thread_rng();
+                let point_scalar = rng.gen_biguint_range(&BigUint::one(), &order);
+                let point = base.sw_scalar_mul(&point_scalar).unwrap();
+                (point, rng.gen_biguint(ECMUL_SCALAR_BITS as u64))
+            })
+            .collect::<Vec<_>>();
+
+        let writer = generator.new_writer();
+        inputs
+            .par_iter()
+            .enumerate()
+            .for_each(|(k, (point, scalar))| {
+                writer.write_ecmul_input(&gadget, point, scalar, 256 * k);
+            });
+        inputs
+            .par_iter()
+            .enumerate()
+            .for_each(|(k, (point, scalar))| {
+                for i in 0..256 {
+                    writer.write_row_instructions(&generator.air_data, 256 * k + i);
+                }
+                let result = writer.read_ec_point(&gadget.result, 256 * k + 255);
+                assert_eq!(result, ecmul(point, scalar));
+            });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+}
@@ -0,0 +1,6 @@
+//! Gadgets for the precompiled contracts of the EVM.
+
+pub mod bn254;
+pub mod ecadd;
+pub mod ecmul;
+pub mod modexp;
@@ -0,0 +1,395 @@
+//! The MODEXP precompile, computing `base^exponent mod modulus` for inputs of arbitrary lengths.
+//!
+//! The lengths of the inputs are fixed when building the AIR, by the number of limbs of the base
+//! and the modulus and the number of bits of the exponent. As in the precompile, the result has
+//! the length of the modulus, and a zero modulus gives a zero result.
+
+use serde::{Deserialize, Serialize};
+
+use crate::air::parser::AirParser;
+use crate::air::AirConstraint;
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::bignum::add::BigNumAddInstruction;
+use crate::chip::bignum::mul::BigNumMulInstruction;
+use crate::chip::bignum::rem::BigNumDivRemInstruction;
+use crate::chip::bignum::sub::BigNumSubInstruction;
+use crate::chip::builder::AirBuilder;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::memory::MemorySlice;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::TraceWriter;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::parser::PolynomialParser;
+
+/// Witnesses whether the modulus is zero, asserting that
+///
+/// s * is_zero = 0,
+/// s * s_inverse + is_zero - 1 = 0,
+///
+/// where `s` is the sum of the limbs of the modulus.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ModulusIsZeroInstruction {
+    pub modulus: ArrayRegister<U16Register>,
+    pub is_zero: BitRegister,
+    limb_sum_inverse: ElementRegister,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Computes `base^exponent mod modulus` as the MODEXP precompile, where `exponent_bits` are the
+    /// bits of the exponent, least significant first.
+    ///
+    /// The base is first reduced modulo the modulus, so it can have more limbs than the modulus.
+    /// A zero modulus is replaced by one, so that the result is zero.
+    pub fn modexp(
+        &mut self,
+        base: &ArrayRegister<U16Register>,
+        exponent_bits: &ArrayRegister<BitRegister>,
+        modulus: &ArrayRegister<U16Register>,
+    ) -> ArrayRegister<U16Register>
+    where
+        L::Instruction: From<BigNumAddInstruction>
+            + From<BigNumSubInstruction>
+            + From<BigNumMulInstruction>
+            + From<BigNumDivRemInstruction>
+            + From<ModulusIsZeroInstruction>,
+    {
+        let nb_limbs = modulus.len();
+
+        let instr = ModulusIsZeroInstruction {
+            modulus: *modulus,
+            is_zero: self.alloc::<BitRegister>(),
+            limb_sum_inverse: self.alloc::<ElementRegister>(),
+        };
+        self.register_instruction(instr);
+
+        // Since the limb sum of a zero modulus vanishes, adding the flag to the lowest limb
+        // cannot overflow.
+        let reduction_modulus = self.alloc_array::<U16Register>(nb_limbs);
+        for (i, (limb, modulus_limb)) in reduction_modulus.iter().zip(modulus.iter()).enumerate() {
+            if i == 0 {
+                self.set_to_expression(&limb, modulus_limb.expr() + instr.is_zero.expr());
+            } else {
+                self.set_to_expression(&limb, modulus_limb.expr());
+            }
+        }
+
+        let one = self.alloc_array::<U16Register>(nb_limbs);
+        for (i, limb) in one.iter().enumerate() {
+            let value = if i == 0 {
+                L::Field::ONE
+            } else {
+                L::Field::ZERO
+            };
+            self.set_to_expression(&limb, ArithmeticExpression::from_constant(value));
+        }
+
+        let base = self.bignum_rem(base, &reduction_modulus);
+        let mut result = self.bignum_rem(&one, &reduction_modulus);
+
+        // Square and multiply, from the most significant bit of the exponent.
+        for i in (0..exponent_bits.len()).rev() {
+            let bit = exponent_bits.get(i);
+            let square = self.bignum_mul(&result, &result);
+            let square = self.bignum_rem(&square, &reduction_modulus);
+            let product = self.bignum_mul(&square, &base);
+            let product = self.bignum_rem(&product, &reduction_modulus);
+            result = self.bignum_select(&bit, &product, &square);
+        }
+        result
+    }
+}
+
+impl<AP: AirParser> AirConstraint<AP> for ModulusIsZeroInstruction {
+    fn eval(&self, parser: &mut AP) {
+        let modulus = self.modulus.eval_vec(parser);
+        let is_zero = self.is_zero.eval(parser);
+        let limb_sum_inverse = self.limb_sum_inverse.eval(parser);
+
+        let zero = parser.zero();
+        let limb_sum = modulus
+            .iter()
+            .fold(zero, |acc, limb| parser.add(acc, *limb));
+
+        // limb_sum * is_zero = 0
+        let zero_constraint = parser.mul(limb_sum, is_zero);
+        parser.constraint(zero_constraint);
+
+        // limb_sum * limb_sum_inverse + is_zero - 1 = 0
+        let product = parser.mul(limb_sum, limb_sum_inverse);
+        let product_plus_bit = parser.add(product, is_zero);
+        let inverse_constraint = parser.sub_const(product_plus_bit, AP::Field::ONE);
+        parser.constraint(inverse_constraint);
+    }
+}
+
+impl<F: PrimeField64> Instruction<F> for ModulusIsZeroInstruction {
+    fn trace_layout(&self) -> Vec<MemorySlice> {
+        vec![*self.is_zero.register(), *self.limb_sum_inverse.register()]
+    }
+
+    fn inputs(&self) -> Vec<MemorySlice> {
+        vec![*self.modulus.register()]
+    }
+
+    fn constraint_degree(&self) -> usize {
+        2
+    }
+
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let modulus = writer.read_vec(&self.modulus, row_index);
+        let limb_sum = modulus.iter().fold(F::ZERO, |acc, limb| acc + *limb);
+
+        let is_zero = F::from_canonical_u8(limb_sum.is_zero() as u8);
+        writer.write(&self.is_zero, &is_zero, row_index);
+        writer.write(
+            &self.limb_sum_inverse,
+            &limb_sum.try_inverse().unwrap_or(F::ZERO),
+            row_index,
+        );
+    }
+}
+
+/// The instructions needed for the MODEXP precompile.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ModExpInstruction {
+    Add(BigNumAddInstruction),
+    Sub(BigNumSubInstruction),
+    Mul(BigNumMulInstruction),
+    DivRem(BigNumDivRemInstruction),
+    ModulusIsZero(ModulusIsZeroInstruction),
+}
+
+impl<AP: PolynomialParser> AirConstraint<AP> for ModExpInstruction {
+    fn eval(&self, parser: &mut AP) {
+        match self {
+            ModExpInstruction::Add(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+            ModExpInstruction::Sub(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+            ModExpInstruction::Mul(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+            ModExpInstruction::DivRem(instruction) => {
+                AirConstraint::<AP>::eval(instruction, parser)
+            }
+            ModExpInstruction::ModulusIsZero(instruction) => {
+                AirConstraint::<AP>::eval(instruction, parser)
+            }
+        }
+    }
+}
+
+impl<F: PrimeField64> Instruction<F> for ModExpInstruction {
+    fn trace_layout(&self) -> Vec<MemorySlice> {
+        match self {
+            ModExpInstruction::Add(instruction) => Instruction::<F>::trace_layout(instruction),
+            ModExpInstruction::Sub(instruction) => Instruction::<F>::trace_layout(instruction),
+            ModExpInstruction::Mul(instruction) => Instruction::<F>::trace_layout(instruction),
+            ModExpInstruction::DivRem(instruction) => Instruction::<F>::trace_layout(instruction),
+            ModExpInstruction::ModulusIsZero(instruction) => {
+                Instruction::<F>::trace_layout(instruction)
+            }
+        }
+    }
+
+    fn inputs(&self) -> Vec<MemorySlice> {
+        match self {
+            ModExpInstruction::Add(instruction) => Instruction::<F>::inputs(instruction),
+            ModExpInstruction::Sub(instruction) => Instruction::<F>::inputs(instruction),
+            ModExpInstruction::Mul(instruction) => Instruction::<F>::inputs(instruction),
+            ModExpInstruction::DivRem(instruction) => Instruction::<F>::inputs(instruction),
+            ModExpInstruction::ModulusIsZero(instruction) => Instruction::<F>::inputs(instruction),
+        }
+    }
+
+    fn constraint_degree(&self) -> usize {
+        match self {
+            ModExpInstruction::Add(instruction) => Instruction::<F>::constraint_degree(instruction),
+            ModExpInstruction::Sub(instruction) => Instruction::<F>::constraint_degree(instruction),
+            ModExpInstruction::Mul(instruction) => Instruction::<F>::constraint_degree(instruction),
+            ModExpInstruction::DivRem(instruction) => {
+                Instruction::<F>::constraint_degree(instruction)
+            }
+            ModExpInstruction::ModulusIsZero(instruction) => {
+                Instruction::<F>::constraint_degree(instruction)
+            }
+        }
+    }
+
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        match self {
+            ModExpInstruction::Add(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            ModExpInstruction::Sub(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            ModExpInstruction::Mul(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            ModExpInstruction::DivRem(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            ModExpInstruction::ModulusIsZero(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+        }
+    }
+}
+
+impl From<BigNumAddInstruction> for ModExpInstruction {
+    fn from(instr: BigNumAddInstruction) -> Self {
+        ModExpInstruction::Add(instr)
+    }
+}
+
+impl From<BigNumSubInstruction> for ModExpInstruction {
+    fn from(instr: BigNumSubInstruction) -> Self {
+        ModExpInstruction::Sub(instr)
+    }
+}
+
+impl From<BigNumMulInstruction> for ModExpInstruction {
+    fn from(instr: BigNumMulInstruction) -> Self {
+        ModExpInstruction::Mul(instr)
+    }
+}
+
+impl From<BigNumDivRemInstruction> for ModExpInstruction {
+    fn from(instr: BigNumDivRemInstruction) -> Self {
+        ModExpInstruction::DivRem(instr)
+    }
+}
+
+impl From<ModulusIsZeroInstruction> for ModExpInstruction {
+    fn from(instr: ModulusIsZeroInstruction) -> Self {
+        ModExpInstruction::ModulusIsZero(instr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use num::{BigUint, Zero};
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+    use crate::chip::bignum::limbs_to_biguint;
+    use crate::chip::builder::tests::*;
+    use crate::polynomial::Polynomial;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct ModExpTest;
+
+    impl AirParameters for ModExpTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 2208;
+        const NUM_FREE_COLUMNS: usize = 47;
+        const EXTENDED_COLUMNS: usize = 3321;
+
+        type Instruction = ModExpInstruction;
+
+        fn num_rows_bits() -> usize {
+            10
+        }
+    }
+
+    const NB_BASE_LIMBS: usize = 8;
+    const NB_MODULUS_LIMBS: usize = 4;
+    const NB_EXPONENT_BITS: usize = 8;
+
+    /// The output of the MODEXP precompile.
+    fn modexp(base: &BigUint, exponent: &BigUint, modulus: &BigUint) -> BigUint {
+        if modulus.is_zero() {
+            return BigUint::zero();
+        }
+        base.modpow(exponent, modulus)
+    }
+
+    #[test]
+    fn test_modexp() {
+        type F = GoldilocksField;
+        type L = ModExpTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let base = builder.alloc_array::<U16Register>(NB_BASE_LIMBS);
+        let exponent_bits = builder.alloc_array::<BitRegister>(NB_EXPONENT_BITS);
+        let modulus = builder.alloc_array::<U16Register>(NB_MODULUS_LIMBS);
+        let result = builder.modexp(&base, &exponent_bits, &modulus);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let int = |x: u64| BigUint::from(x);
+        let large_base = (int(1) << 127) + int(12345);
+        let large_modulus = (int(1) << 64) - int(59);
+
+        // Known results of the precompile, including a zero and a unit modulus, a zero exponent
+        // and a base longer than the modulus.
+        let known = [
+            (int(3), 5, int(100), int(43)),
+            (int(2), 10, int(1000), int(24)),
+            (int(0), 0, int(7), int(1)),
+            (int(0), 5, int(7), int(0)),
+            (int(5), 0, int(1), int(0)),
+            (int(5), 7, int(0), int(0)),
+            (int(0), 0, int(0), int(0)),
+            (
+                large_base.clone(),
+                1,
+                large_modulus.clone(),
+                &large_base % &large_modulus,
+            ),
+            (
+                int(3),
+                255,
+                large_modulus.clone(),
+                int(3).modpow(&int(255), &large_modulus),
+            ),
+        ];
+        for (base_int, exponent, modulus_int, expected) in known.iter() {
+            assert_eq!(modexp(base_int, &int(*exponent), modulus_int), *expected);
+        }
+
+        let writer = generator.new_writer();
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            let (base_int, exponent, modulus_int) = if i < known.len() {
+                let (base_int, exponent, modulus_int, _) = &known[i];
+                (base_int.clone(), *exponent, modulus_int.clone())
+            } else {
+                let mut rng = thread_rng();
+                (
+                    rng.gen_biguint(16 * NB_BASE_LIMBS as u64),
+                    rng.gen::<u8>() as u64,
+                    rng.gen_biguint(16 * NB_MODULUS_LIMBS as u64),
+                )
+            };
+
+            let p_base = Polynomial::<F>::from_biguint_field(&base_int, 16, NB_BASE_LIMBS);
+            let p_modulus = Polynomial::<F>::from_biguint_field(&modulus_int, 16, NB_MODULUS_LIMBS);
+            writer.write_slice(&base, &p_base.coefficients, i);
+            writer.write_slice(&modulus, &p_modulus.coefficients, i);
+            for (j, bit) in exponent_bits.iter().enumerate() {
+                writer.write(&bit, &F::from_canonical_u64((exponent >> j) & 1), i);
+            }
+            writer.write_row_instructions(&generator.air_data, i);
+
+            let result_int = limbs_to_biguint(&writer.read_vec(&result, i));
+            assert_eq!(result_int, modexp(&base_int, &int(exponent), &modulus_int));
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+}
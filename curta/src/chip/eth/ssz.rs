@@ -0,0 +1,390 @@
+//! SSZ Merkleization of Ethereum consensus objects with SHA-256.
+//!
+//! The hash-tree-root of a container of `n` fields is the root of the binary Merkle tree whose
+//! leaves are the 32-byte roots of the fields, padded with zero chunks to the next power of two.
+//! Lists are Merkleized up to their limit and the length is then mixed in, i.e. the root is
+//! `hash(root || length)` with the length as a 32-byte little-endian integer.
+//!
+//! Reference: https://github.com/ethereum/consensus-specs/blob/dev/ssz/simple-serialize.md
+
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::Target;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+use crate::chip::hash::sha::sha256::builder_gadget::{
+    CurtaBytes, SHA256Builder, SHA256BuilderGadget,
+};
+use crate::chip::hash::sha::sha256::merkle::SHA256MerkleBuilder;
+use crate::chip::hash::sha::sha256::SHA256Gadget;
+use crate::math::prelude::CubicParameters;
+
+/// Returns the depth of the tree over `limit` chunks.
+fn ssz_depth(limit: usize) -> usize {
+    limit.next_power_of_two().trailing_zeros() as usize
+}
+
+/// Returns the roots of the all-zero trees of depth `0..=depth`.
+pub fn ssz_zero_hashes(depth: usize) -> Vec<[u8; 32]> {
+    let mut zero_hashes = vec![[0u8; 32]];
+    for i in 0..depth {
+        let node = SHA256Gadget::hash(&[zero_hashes[i], zero_hashes[i]].concat());
+        zero_hashes.push(node);
+    }
+    zero_hashes
+}
+
+/// Encodes a `uint64` as a chunk.
+pub fn ssz_uint64(value: u64) -> [u8; 32] {
+    let mut chunk = [0u8; 32];
+    chunk[..8].copy_from_slice(&value.to_le_bytes());
+    chunk
+}
+
+/// Hashes the nodes of a level of the tree in pairs, completing the last pair with `zero`.
+fn ssz_next_level(level: &[[u8; 32]], zero: &[u8; 32]) -> Vec<[u8; 32]> {
+    level
+        .chunks(2)
+        .map(|pair| SHA256Gadget::hash(&[pair[0], *pair.get(1).unwrap_or(zero)].concat()))
+        .collect()
+}
+
+/// Computes the root of the tree over `chunks`, padded with zero chunks up to `limit`.
+pub fn ssz_merkleize(chunks: &[[u8; 32]], limit: usize) -> [u8; 32] {
+    assert!(chunks.len() <= limit, "More chunks than the limit");
+    let depth = ssz_depth(limit);
+    let zero_hashes = ssz_zero_hashes(depth);
+    let mut level = chunks.to_vec();
+    for zero in zero_hashes.iter().take(depth) {
+        level = ssz_next_level(&level, zero);
+    }
+    level.first().copied().unwrap_or(zero_hashes[depth])
+}
+
+/// Mixes the length of a list into the root of its chunks.
+pub fn ssz_mix_in_length(root: &[u8; 32], length: u64) -> [u8; 32] {
+    SHA256Gadget::hash(&[*root, ssz_uint64(length)].concat())
+}
+
+/// Returns the generalized index of the chunk at `index` in a tree over `limit` chunks.
+pub fn ssz_generalized_index(limit: usize, index: usize) -> usize {
+    assert!(index < limit, "Index out of bounds");
+    (1 << ssz_depth(limit)) + index
+}
+
+/// Returns the Merkle branch of the chunk at `index`, from the leaf up to the root.
+pub fn ssz_branch(chunks: &[[u8; 32]], limit: usize, index: usize) -> Vec<[u8; 32]> {
+    assert!(index < limit, "Index out of bounds");
+    let depth = ssz_depth(limit);
+    let zero_hashes = ssz_zero_hashes(depth);
+    let mut level = chunks.to_vec();
+    let mut position = index;
+    let mut branch = Vec::with_capacity(depth);
+    for zero in zero_hashes.iter().take(depth) {
+        branch.push(level.get(position ^ 1).copied().unwrap_or(*zero));
+        level = ssz_next_level(&level, zero);
+        position >>= 1;
+    }
+    branch
+}
+
+pub trait SSZBuilder<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize>:
+    SHA256MerkleBuilder<F, E, D>
+{
+    /// Encodes a `uint64` as a chunk. The value is range checked to 64 bits.
+    fn ssz_uint64(&mut self, value: Target) -> CurtaBytes<32>;
+
+    /// Computes the root of the tree over `chunks`, padded with zero chunks up to `limit`, as in
+    /// `ssz_merkleize`.
+    ///
+    /// The subtrees made of padding only are constants, so that only the nodes with at least one
+    /// chunk below them are hashed.
+    fn ssz_merkleize(
+        &mut self,
+        chunks: &[CurtaBytes<32>],
+        limit: usize,
+        gadget: &mut Self::Gadget,
+    ) -> CurtaBytes<32>;
+
+    /// Mixes the length of a list into the root of its chunks. The length is range checked to
+    /// 64 bits.
+    fn ssz_mix_in_length(
+        &mut self,
+        root: &CurtaBytes<32>,
+        length: Target,
+        gadget: &mut Self::Gadget,
+    ) -> CurtaBytes<32>;
+
+    /// Verifies that `leaf` is the chunk at the generalized index `gindex` in the tree of `root`,
+    /// given its branch from the leaf up to the root.
+    fn verify_ssz_proof(
+        &mut self,
+        leaf: &CurtaBytes<32>,
+        branch: &[CurtaBytes<32>],
+        gindex: usize,
+        root: &CurtaBytes<32>,
+        gadget: &mut Self::Gadget,
+    );
+}
+
+impl<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize> SSZBuilder<F, E, D>
+    for CircuitBuilder<F, D>
+{
+    fn ssz_uint64(&mut self, value: Target) -> CurtaBytes<32> {
+        let bytes = self.split_le_base::<256>(value, 8);
+        let zero = self.zero();
+        CurtaBytes(core::array::from_fn(|i| {
+            bytes.get(i).copied().unwrap_or(zero)
+        }))
+    }
+
+    fn ssz_merkleize(
+        &mut self,
+        chunks: &[CurtaBytes<32>],
+        limit: usize,
+        gadget: &mut SHA256BuilderGadget<F, E, D>,
+    ) -> CurtaBytes<32> {
+        assert!(chunks.len() <= limit, "More chunks than the limit");
+        let depth = ssz_depth(limit);
+        let zero_hashes = ssz_zero_hashes(depth)
+            .iter()
+            .map(|node| CurtaBytes(node.map(|byte| self.constant(F::from_canonical_u8(byte)))))
+            .collect::<Vec<_>>();
+
+        let mut level = chunks.to_vec();
+        for zero in zero_hashes.iter().take(depth) {
+            level = level
+                .chunks(2)
+                .map(|pair| {
+                    let right = pair.get(1).unwrap_or(zero);
+                    SHA256MerkleBuilder::<F, E, D>::merkle_node(self, &pair[0], right, gadget)
+                })
+                .collect();
+        }
+        level.first().copied().unwrap_or(zero_hashes[depth])
+    }
+
+    fn ssz_mix_in_length(
+        &mut self,
+        root: &CurtaBytes<32>,
+        length: Target,
+        gadget: &mut SHA256BuilderGadget<F, E, D>,
+    ) -> CurtaBytes<32> {
+        let length_chunk = SSZBuilder::<F, E, D>::ssz_uint64(self, length);
+        SHA256MerkleBuilder::<F, E, D>::merkle_node(self, root, &length_chunk, gadget)
+    }
+
+    fn verify_ssz_proof(
+        &mut self,
+        leaf: &CurtaBytes<32>,
+        branch: &[CurtaBytes<32>],
+        gindex: usize,
+        root: &CurtaBytes<32>,
+        gadget: &mut SHA256BuilderGadget<F, E, D>,
+    ) {
+        assert!(gindex > 0, "Generalized indices start at one");
+        let depth = (usize::BITS - 1 - gindex.leading_zeros()) as usize;
+        assert_eq!(
+            branch.len(),
+            depth,
+            "The branch length must be the depth of the generalized index"
+        );
+
+        // The bits of the generalized index below its leading one give the position of the node
+        // at every level, from the leaf up.
+        let mut current = *leaf;
+        for (level, sibling) in branch.iter().enumerate() {
+            current = if (gindex >> level) & 1 == 1 {
+                SHA256MerkleBuilder::<F, E, D>::merkle_node(self, sibling, &current, gadget)
+            } else {
+                SHA256MerkleBuilder::<F, E, D>::merkle_node(self, &current, sibling, gadget)
+            };
+        }
+
+        for (a, b) in current.0.iter().zip(root.0.iter()) {
+            self.connect(*a, *b);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::types::Field;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    type F = GoldilocksField;
+    type E = GoldilocksCubicParameters;
+    type SC = CurtaPoseidonGoldilocksConfig;
+    type C = PoseidonGoldilocksConfig;
+    const D: usize = 2;
+
+    /// The number of fields of a `BeaconBlockHeader`.
+    const HEADER_FIELDS: usize = 5;
+
+    /// The index of `state_root` in a `BeaconBlockHeader`.
+    const STATE_ROOT_INDEX: usize = 3;
+
+    const SLOT: u64 = 7_000_000;
+    const PROPOSER_INDEX: u64 = 123_456;
+
+    fn to_field_bytes(bytes: &[u8]) -> Vec<F> {
+        bytes.iter().map(|x| F::from_canonical_u8(*x)).collect()
+    }
+
+    /// The chunks of a `BeaconBlockHeader { slot, proposer_index, parent_root, state_root,
+    /// body_root }` with the roots taken as hashes of fixed strings.
+    fn header_chunks() -> Vec<[u8; 32]> {
+        vec![
+            ssz_uint64(SLOT),
+            ssz_uint64(PROPOSER_INDEX),
+            SHA256Gadget::hash(b"parent"),
+            SHA256Gadget::hash(b"state"),
+            SHA256Gadget::hash(b"body"),
+        ]
+    }
+
+    fn list_chunks() -> Vec<[u8; 32]> {
+        vec![
+            SHA256Gadget::hash(b"a"),
+            SHA256Gadget::hash(b"b"),
+            SHA256Gadget::hash(b"c"),
+        ]
+    }
+
+    #[test]
+    fn test_ssz_native() {
+        // Reference roots computed with an independent implementation of the specification.
+        let header_root = ssz_merkleize(&header_chunks(), HEADER_FIELDS);
+        assert_eq!(
+            hex::encode(header_root),
+            "f29c6635cbd0af5844a6e0f8eb140d5f0a7b77e1f49caf66796a86c627d33358"
+        );
+        let list_root = ssz_mix_in_length(&ssz_merkleize(&list_chunks(), 4), 3);
+        assert_eq!(
+            hex::encode(list_root),
+            "6864f3be54104fcedf33aa7d1782b158574e1802bcf19aa7b2845b71b8f9e33a"
+        );
+
+        // The zero hashes are the roots of empty trees.
+        assert_eq!(ssz_merkleize(&[], 8), ssz_zero_hashes(3)[3]);
+        assert_eq!(ssz_merkleize(&[[1u8; 32]], 1), [1u8; 32]);
+
+        assert_eq!(ssz_generalized_index(HEADER_FIELDS, STATE_ROOT_INDEX), 11);
+        assert_eq!(ssz_branch(&header_chunks(), HEADER_FIELDS, 4)[0], [0u8; 32]);
+    }
+
+    fn prove_ssz(perturb_branch: bool) {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let mut gadget = SHA256Builder::<F, E, D>::init_sha256(&mut builder);
+
+        // The hash-tree-root of the header, from its fields.
+        let slot = builder.add_virtual_target();
+        let proposer_index = builder.add_virtual_target();
+        let roots = (0..3)
+            .map(|_| CurtaBytes(builder.add_virtual_target_arr::<32>()))
+            .collect::<Vec<_>>();
+        let mut chunks = vec![
+            SSZBuilder::<F, E, D>::ssz_uint64(&mut builder, slot),
+            SSZBuilder::<F, E, D>::ssz_uint64(&mut builder, proposer_index),
+        ];
+        chunks.extend_from_slice(&roots);
+        let header_root = builder.ssz_merkleize(&chunks, HEADER_FIELDS, &mut gadget);
+        let expected_header_root = CurtaBytes(builder.add_virtual_target_arr::<32>());
+        for (a, b) in header_root.0.iter().zip(expected_header_root.0.iter()) {
+            builder.connect(*a, *b);
+        }
+
+        // A proof of the state root against the header root.
+        let branch = (0..3)
+            .map(|_| CurtaBytes(builder.add_virtual_target_arr::<32>()))
+            .collect::<Vec<_>>();
+        let gindex = ssz_generalized_index(HEADER_FIELDS, STATE_ROOT_INDEX);
+        builder.verify_ssz_proof(
+            &roots[STATE_ROOT_INDEX - 2],
+            &branch,
+            gindex,
+            &expected_header_root,
+            &mut gadget,
+        );
+
+        // The root of a list of three chunks with a limit of four.
+        let list = (0..3)
+            .map(|_| CurtaBytes(builder.add_virtual_target_arr::<32>()))
+            .collect::<Vec<_>>();
+        let list_length = builder.add_virtual_target();
+        let list_chunks_root = builder.ssz_merkleize(&list, 4, &mut gadget);
+        let list_root = builder.ssz_mix_in_length(&list_chunks_root, list_length, &mut gadget);
+        let expected_list_root = CurtaBytes(builder.add_virtual_target_arr::<32>());
+        for (a, b) in list_root.0.iter().zip(expected_list_root.0.iter()) {
+            builder.connect(*a, *b);
+        }
+
+        // The SHA256 gadget processes a fixed number of 1024 chunks, two for every node: six for
+        // the header, three for the proof, and four for the list.
+        let dummy_messages = (0..1024 - 2 * 13)
+            .map(|_| CurtaBytes(builder.add_virtual_target_arr::<64>()))
+            .collect::<Vec<_>>();
+        for message in dummy_messages.iter() {
+            builder.sha256(message, &mut gadget);
+        }
+        builder.constrain_sha256_gadget::<SC>(gadget);
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+
+        let header = header_chunks();
+        pw.set_target(slot, F::from_canonical_u64(SLOT));
+        pw.set_target(proposer_index, F::from_canonical_u64(PROPOSER_INDEX));
+        for (root, value) in roots.iter().zip(header[2..].iter()) {
+            pw.set_target_arr(&root.0, &to_field_bytes(value));
+        }
+        pw.set_target_arr(
+            &expected_header_root.0,
+            &to_field_bytes(&ssz_merkleize(&header, HEADER_FIELDS)),
+        );
+
+        let mut branch_values = ssz_branch(&header, HEADER_FIELDS, STATE_ROOT_INDEX);
+        if perturb_branch {
+            branch_values[1][0] ^= 1;
+        }
+        for (sibling, value) in branch.iter().zip(branch_values.iter()) {
+            pw.set_target_arr(&sibling.0, &to_field_bytes(value));
+        }
+
+        let list_values = list_chunks();
+        for (chunk, value) in list.iter().zip(list_values.iter()) {
+            pw.set_target_arr(&chunk.0, &to_field_bytes(value));
+        }
+        pw.set_target(list_length, F::from_canonical_usize(list_values.len()));
+        let list_root_value = ssz_mix_in_length(&ssz_merkleize(&list_values, 4), 3);
+        pw.set_target_arr(&expected_list_root.0, &to_field_bytes(&list_root_value));
+
+        let dummy_padded_message = to_field_bytes(&SHA256Gadget::pad(b""));
+        for message in dummy_messages.iter() {
+            pw.set_target_arr(&message.0, &dummy_padded_message);
+        }
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+
+    #[test]
+    fn test_ssz_header_root_and_proof() {
+        prove_ssz(false);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_ssz_header_proof_perturbed() {
+        prove_ssz(true);
+    }
+}
@@ -0,0 +1,133 @@
+use plonky2::field::extension::Extendable;
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::Target;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+/// The number of bytes of an EVM word.
+pub const EVM_WORD_BYTES: usize = 32;
+
+/// The number of 32-bit limbs of a packed EVM word.
+pub const EVM_WORD_LIMBS: usize = 8;
+
+pub trait CircuitBuilderEvmWord<F: RichField + Extendable<D>, const D: usize> {
+    /// Converts a little-endian byte encoding of a 256-bit integer to the big-endian encoding of
+    /// an EVM word. The bytes are range checked to 8 bits.
+    fn to_evm_word(&mut self, bytes_le: &[Target; EVM_WORD_BYTES]) -> [Target; EVM_WORD_BYTES];
+
+    /// Converts an EVM word to the little-endian byte encoding of the same integer. The bytes are
+    /// range checked to 8 bits.
+    fn from_evm_word(&mut self, bytes_be: &[Target; EVM_WORD_BYTES]) -> [Target; EVM_WORD_BYTES];
+
+    /// Packs an EVM word into 32-bit limbs, least significant limb first, so that the word is
+    /// read as the `uint256` value `sum_i limbs[i] * 2^(32 * i)`, as in Solidity's
+    /// `uint256(bytes32)`. The bytes are range checked to 8 bits.
+    fn pack_evm_word(&mut self, bytes_be: &[Target; EVM_WORD_BYTES]) -> [Target; EVM_WORD_LIMBS];
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderEvmWord<F, D>
+    for CircuitBuilder<F, D>
+{
+    fn to_evm_word(&mut self, bytes_le: &[Target; EVM_WORD_BYTES]) -> [Target; EVM_WORD_BYTES] {
+        for byte in bytes_le.iter() {
+            self.range_check(*byte, 8);
+        }
+        core::array::from_fn(|i| bytes_le[EVM_WORD_BYTES - 1 - i])
+    }
+
+    fn from_evm_word(&mut self, bytes_be: &[Target; EVM_WORD_BYTES]) -> [Target; EVM_WORD_BYTES] {
+        // Reversing the byte order is an involution.
+        self.to_evm_word(bytes_be)
+    }
+
+    fn pack_evm_word(&mut self, bytes_be: &[Target; EVM_WORD_BYTES]) -> [Target; EVM_WORD_LIMBS] {
+        let bytes_le = self.from_evm_word(bytes_be);
+        let base = F::from_canonical_u32(1 << 8);
+        core::array::from_fn(|i| {
+            bytes_le[4 * i..4 * i + 4]
+                .iter()
+                .rev()
+                .fold(self.zero(), |acc, byte| {
+                    self.mul_const_add(base, acc, *byte)
+                })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::BigUint;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+
+    use super::*;
+
+    type F = GoldilocksField;
+    type C = PoseidonGoldilocksConfig;
+    const D: usize = 2;
+
+    /// Proves the conversions of the little-endian bytes of `value`, checking the EVM word against
+    /// `expected_word` and the packed limbs against the value.
+    fn prove_evm_word(value: &BigUint, expected_word: [u8; EVM_WORD_BYTES]) {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let bytes_le: [Target; EVM_WORD_BYTES] = builder.add_virtual_target_arr();
+        let word = builder.to_evm_word(&bytes_le);
+        let round_trip = builder.from_evm_word(&word);
+        let limbs = builder.pack_evm_word(&word);
+
+        for (byte, expected) in word.iter().zip(expected_word) {
+            let expected = builder.constant(F::from_canonical_u8(expected));
+            builder.connect(*byte, expected);
+        }
+        for (byte, original) in round_trip.iter().zip(bytes_le) {
+            builder.connect(*byte, original);
+        }
+        let mut limb_values = value.to_u32_digits();
+        limb_values.resize(EVM_WORD_LIMBS, 0);
+        for (limb, expected) in limbs.iter().zip(limb_values) {
+            let expected = builder.constant(F::from_canonical_u32(expected));
+            builder.connect(*limb, expected);
+        }
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+
+        let mut value_bytes = value.to_bytes_le();
+        value_bytes.resize(EVM_WORD_BYTES, 0);
+        for (target, byte) in bytes_le.iter().zip(value_bytes) {
+            pw.set_target(*target, F::from_canonical_u8(byte));
+        }
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+
+    #[test]
+    fn test_evm_word() {
+        // Solidity reads bytes32(uint256(1)) as 0x00...01.
+        let mut one_word = [0u8; EVM_WORD_BYTES];
+        one_word[31] = 1;
+        prove_evm_word(&BigUint::from(1u32), one_word);
+
+        // The word 0x0102...20 is the uint256 with these big-endian bytes.
+        let word: [u8; EVM_WORD_BYTES] = core::array::from_fn(|i| i as u8 + 1);
+        prove_evm_word(&BigUint::from_bytes_be(&word), word);
+
+        // The largest uint256.
+        let max_word = [0xffu8; EVM_WORD_BYTES];
+        prove_evm_word(&BigUint::from_bytes_be(&max_word), max_word);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_evm_word_wrong_order() {
+        let word: [u8; EVM_WORD_BYTES] = core::array::from_fn(|i| i as u8 + 1);
+        let mut reversed = word;
+        reversed.reverse();
+        prove_evm_word(&BigUint::from_bytes_be(&word), reversed);
+    }
+}
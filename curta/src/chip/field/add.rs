@@ -37,6 +37,8 @@ impl<P: FieldParameters> FpAddInstruction<P> {
 
 impl<L: AirParameters> AirBuilder<L> {
     /// Given two field elements `a` and `b`, computes the sum `a + b = c`.
+    ///
+    /// The addition is folded if both inputs are constants or if either of them is zero.
     pub fn fp_add<P: FieldParameters>(
         &mut self,
         a: &FieldRegister<P>,
@@ -45,6 +47,9 @@ impl<L: AirParameters> AirBuilder<L> {
     where
         L::Instruction: From<FpAddInstruction<P>>,
     {
+        if let Some(result) = self.fold_fp_add(a, b) {
+            return result;
+        }
         let result = self.alloc::<FieldRegister<P>>();
         self.set_fp_add(a, b, &result);
         result
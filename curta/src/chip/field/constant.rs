@@ -0,0 +1,175 @@
+//! Constant folding for field operations.
+//!
+//! Registers allocated with `alloc_constant_field_register` are recorded by the builder together
+//! with their value. When the inputs of an addition, subtraction, negation or multiplication are
+//! known in this way, the result is computed at build time and no instruction is registered.
+//! Multiplication by zero or one and addition or subtraction of zero are elided even when the
+//! other operand is not a constant.
+
+use num::{BigUint, One, Zero};
+
+use super::mul::FpMulInstruction;
+use super::parameters::FieldParameters;
+use super::register::FieldRegister;
+use crate::chip::builder::AirBuilder;
+use crate::chip::register::Register;
+use crate::chip::AirParameters;
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Returns the value of `a` if it was allocated as a constant register.
+    pub fn fp_constant_value<P: FieldParameters>(&self, a: &FieldRegister<P>) -> Option<BigUint> {
+        self.field_constants.get(a.register()).cloned()
+    }
+
+    /// Allocates a constant register holding `value` reduced modulo `P`.
+    pub fn fp_constant<P: FieldParameters>(&mut self, value: &BigUint) -> FieldRegister<P> {
+        self.alloc_constant_field_register(&(value % P::modulus()))
+    }
+
+    /// Given two field elements `a` and `b`, returns a register equal to the product `a * b`.
+    ///
+    /// Unlike `fp_mul`, the multiplication is folded away if either input is a constant zero or
+    /// one, or if both inputs are constants.
+    pub fn fp_mul_folded<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        b: &FieldRegister<P>,
+    ) -> FieldRegister<P>
+    where
+        L::Instruction: From<FpMulInstruction<P>>,
+    {
+        match (self.fp_constant_value(a), self.fp_constant_value(b)) {
+            (Some(a_val), Some(b_val)) => self.fp_constant(&(a_val * b_val)),
+            (Some(a_val), _) if a_val.is_zero() => *a,
+            (_, Some(b_val)) if b_val.is_zero() => *b,
+            (Some(a_val), _) if a_val.is_one() => *b,
+            (_, Some(b_val)) if b_val.is_one() => *a,
+            _ => self.fp_mul(a, b).result,
+        }
+    }
+
+    /// Folds `a + b` if both inputs are constants or if either of them is zero.
+    pub(crate) fn fold_fp_add<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        b: &FieldRegister<P>,
+    ) -> Option<FieldRegister<P>> {
+        match (self.fp_constant_value(a), self.fp_constant_value(b)) {
+            (Some(a_val), Some(b_val)) => Some(self.fp_constant(&(a_val + b_val))),
+            (Some(a_val), _) if a_val.is_zero() => Some(*b),
+            (_, Some(b_val)) if b_val.is_zero() => Some(*a),
+            _ => None,
+        }
+    }
+
+    /// Folds `a - b` if both inputs are constants or if `b` is zero.
+    pub(crate) fn fold_fp_sub<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        b: &FieldRegister<P>,
+    ) -> Option<FieldRegister<P>> {
+        match (self.fp_constant_value(a), self.fp_constant_value(b)) {
+            (Some(a_val), Some(b_val)) => Some(self.fp_constant(&(P::modulus() + a_val - b_val))),
+            (_, Some(b_val)) if b_val.is_zero() => Some(*a),
+            _ => None,
+        }
+    }
+
+    /// Folds `-a` if `a` is a constant.
+    pub(crate) fn fold_fp_neg<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+    ) -> Option<FieldRegister<P>> {
+        let a_val = self.fp_constant_value(a)?;
+        Some(self.fp_constant(&(P::modulus() - a_val)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::field::instruction::FpInstruction;
+    use crate::chip::field::parameters::tests::Fp25519;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct FpFoldTest;
+
+    impl AirParameters for FpFoldTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 400;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 600;
+
+        type Instruction = FpInstruction<Fp25519>;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    #[test]
+    fn test_fp_mul_by_one_is_folded() {
+        type L = FpFoldTest;
+        type P = Fp25519;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let a = builder.alloc::<FieldRegister<P>>();
+        let one = builder.alloc_constant_field_register::<P>(&BigUint::one());
+        let zero = builder.alloc_constant_field_register::<P>(&BigUint::zero());
+
+        let num_instructions = builder.instructions.len();
+        let num_constraints = builder.constraints.len();
+
+        let a_one = builder.fp_mul_folded(&a, &one);
+        let one_a = builder.fp_mul_folded(&one, &a);
+        let a_zero = builder.fp_mul_folded(&a, &zero);
+        let a_plus_zero = builder.fp_add(&a, &zero);
+        let a_minus_zero = builder.fp_sub(&a, &zero);
+
+        assert_eq!(a_one.register(), a.register());
+        assert_eq!(one_a.register(), a.register());
+        assert_eq!(a_zero.register(), zero.register());
+        assert_eq!(a_plus_zero.register(), a.register());
+        assert_eq!(a_minus_zero.register(), a.register());
+        assert_eq!(builder.instructions.len(), num_instructions);
+        assert_eq!(builder.constraints.len(), num_constraints);
+
+        // A product of two unknown inputs still registers a multiplication.
+        let a_squared = builder.fp_mul_folded(&a, &a);
+        assert_eq!(builder.fp_constant_value(&a_squared), None);
+        assert_eq!(builder.instructions.len(), num_instructions + 1);
+    }
+
+    #[test]
+    fn test_fp_constant_folding() {
+        type L = FpFoldTest;
+        type P = Fp25519;
+
+        let p = P::modulus();
+        let mut builder = AirBuilder::<L>::new();
+
+        let two = builder.fp_constant::<P>(&BigUint::from(2u32));
+        let three = builder.fp_constant::<P>(&BigUint::from(3u32));
+
+        let num_instructions = builder.instructions.len();
+
+        let five = builder.fp_add(&two, &three);
+        let minus_one = builder.fp_sub(&two, &three);
+        let minus_three = builder.fp_neg(&three);
+        let six = builder.fp_mul_folded(&two, &three);
+
+        assert_eq!(builder.fp_constant_value(&five), Some(BigUint::from(5u32)));
+        assert_eq!(builder.fp_constant_value(&minus_one), Some(&p - 1u32));
+        assert_eq!(builder.fp_constant_value(&minus_three), Some(&p - 3u32));
+        assert_eq!(builder.fp_constant_value(&six), Some(BigUint::from(6u32)));
+
+        // Each folded result is a single constant assignment.
+        assert_eq!(builder.instructions.len(), num_instructions + 4);
+    }
+}
@@ -4,10 +4,13 @@ use super::add::FpAddInstruction;
 use super::den::FpDenInstruction;
 use super::div::FpDivInstruction;
 use super::inner_product::FpInnerProductInstruction;
+use super::is_zero::FpIsZeroInstruction;
+use super::mont_mul::FpMontMulInstruction;
 use super::mul::FpMulInstruction;
 use super::mul_const::FpMulConstInstruction;
 use super::parameters::FieldParameters;
 use super::register::FieldRegister;
+use super::sqrt::FpSqrtInstruction;
 use super::sub::FpSubInstruction;
 use crate::air::AirConstraint;
 use crate::chip::bool::SelectInstruction;
@@ -28,6 +31,9 @@ pub enum FpInstruction<P: FieldParameters> {
     Select(SelectInstruction<FieldRegister<P>>),
     Sub(FpSubInstruction<P>),
     Div(FpDivInstruction<P>),
+    MontMul(FpMontMulInstruction<P>),
+    IsZero(FpIsZeroInstruction<P>),
+    Sqrt(FpSqrtInstruction<P>),
 }
 
 pub trait FromFieldInstruction<P: FieldParameters>:
@@ -37,6 +43,9 @@ pub trait FromFieldInstruction<P: FieldParameters>:
     + From<FpInnerProductInstruction<P>>
     + From<FpDenInstruction<P>>
     + From<SelectInstruction<FieldRegister<P>>>
+    + From<FpSubInstruction<P>>
+    + From<FpDivInstruction<P>>
+    + From<FpIsZeroInstruction<P>>
 {
 }
 
@@ -53,6 +62,9 @@ impl<AP: PolynomialParser, P: FieldParameters> AirConstraint<AP> for FpInstructi
             FpInstruction::Select(instruction) => AirConstraint::<AP>::eval(instruction, parser),
             FpInstruction::Sub(instruction) => AirConstraint::<AP>::eval(instruction, parser),
             FpInstruction::Div(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+            FpInstruction::MontMul(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+            FpInstruction::IsZero(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+            FpInstruction::Sqrt(instruction) => AirConstraint::<AP>::eval(instruction, parser),
         }
     }
 }
@@ -68,6 +80,9 @@ impl<F: PrimeField64, P: FieldParameters> Instruction<F> for FpInstruction<P> {
             FpInstruction::Select(instruction) => Instruction::<F>::trace_layout(instruction),
             FpInstruction::Sub(instruction) => Instruction::<F>::trace_layout(instruction),
             FpInstruction::Div(instruction) => Instruction::<F>::trace_layout(instruction),
+            FpInstruction::MontMul(instruction) => Instruction::<F>::trace_layout(instruction),
+            FpInstruction::IsZero(instruction) => Instruction::<F>::trace_layout(instruction),
+            FpInstruction::Sqrt(instruction) => Instruction::<F>::trace_layout(instruction),
         }
     }
 
@@ -81,6 +96,9 @@ impl<F: PrimeField64, P: FieldParameters> Instruction<F> for FpInstruction<P> {
             FpInstruction::Select(instruction) => Instruction::<F>::inputs(instruction),
             FpInstruction::Sub(instruction) => Instruction::<F>::inputs(instruction),
             FpInstruction::Div(instruction) => Instruction::<F>::inputs(instruction),
+            FpInstruction::MontMul(instruction) => Instruction::<F>::inputs(instruction),
+            FpInstruction::IsZero(instruction) => Instruction::<F>::inputs(instruction),
+            FpInstruction::Sqrt(instruction) => Instruction::<F>::inputs(instruction),
         }
     }
 
@@ -110,6 +128,15 @@ impl<F: PrimeField64, P: FieldParameters> Instruction<F> for FpInstruction<P> {
             FpInstruction::Div(instruction) => {
                 Instruction::<F>::write(instruction, writer, row_index)
             }
+            FpInstruction::MontMul(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            FpInstruction::IsZero(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            FpInstruction::Sqrt(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
         }
     }
 }
@@ -161,3 +188,21 @@ impl<P: FieldParameters> From<FpDivInstruction<P>> for FpInstruction<P> {
         FpInstruction::Div(instr)
     }
 }
+
+impl<P: FieldParameters> From<FpMontMulInstruction<P>> for FpInstruction<P> {
+    fn from(instr: FpMontMulInstruction<P>) -> Self {
+        FpInstruction::MontMul(instr)
+    }
+}
+
+impl<P: FieldParameters> From<FpIsZeroInstruction<P>> for FpInstruction<P> {
+    fn from(instr: FpIsZeroInstruction<P>) -> Self {
+        FpInstruction::IsZero(instr)
+    }
+}
+
+impl<P: FieldParameters> From<FpSqrtInstruction<P>> for FpInstruction<P> {
+    fn from(instr: FpSqrtInstruction<P>) -> Self {
+        FpInstruction::Sqrt(instr)
+    }
+}
@@ -0,0 +1,304 @@
+use num::{BigUint, Zero};
+use serde::{Deserialize, Serialize};
+
+use super::parameters::FieldParameters;
+use super::register::FieldRegister;
+use super::util;
+use crate::air::AirConstraint;
+use crate::chip::builder::AirBuilder;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::memory::MemorySlice;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::TraceWriter;
+use crate::chip::utils::{digits_to_biguint, split_u32_limbs_to_u16_limbs};
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::parser::PolynomialParser;
+use crate::polynomial::{to_u16_le_limbs_polynomial, Polynomial};
+
+/// Fp zero test. Computes a bit `is_zero` which is one if and only if `a = 0`.
+///
+/// The instruction witnesses an inverse `inv` of `a` and asserts that
+///
+/// a * inv - (1 - is_zero) - carry * p = 0,
+/// is_zero * a = 0,
+///
+/// where the second equation is asserted limb by limb. A zero value of `a` must therefore be given
+/// by its canonical representation, which is the case for the results of the field instructions
+/// written by the trace generator.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct FpIsZeroInstruction<P: FieldParameters> {
+    pub a: FieldRegister<P>,
+    pub is_zero: BitRegister,
+    pub(crate) inverse: FieldRegister<P>,
+    pub(crate) carry: FieldRegister<P>,
+    pub(crate) witness_low: ArrayRegister<U16Register>,
+    pub(crate) witness_high: ArrayRegister<U16Register>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Given a field element `a`, computes a bit which is one if `a = 0` and zero otherwise.
+    pub fn fp_is_zero<P: FieldParameters>(&mut self, a: &FieldRegister<P>) -> BitRegister
+    where
+        L::Instruction: From<FpIsZeroInstruction<P>>,
+    {
+        let is_zero = self.alloc::<BitRegister>();
+        let inverse = self.alloc::<FieldRegister<P>>();
+        let carry = self.alloc::<FieldRegister<P>>();
+        let witness_low = self.alloc_array::<U16Register>(P::NB_WITNESS_LIMBS);
+        let witness_high = self.alloc_array::<U16Register>(P::NB_WITNESS_LIMBS);
+        let instr = FpIsZeroInstruction {
+            a: *a,
+            is_zero,
+            inverse,
+            carry,
+            witness_low,
+            witness_high,
+        };
+        self.register_instruction(instr);
+        is_zero
+    }
+
+    /// Asserts that the field element `a` is not zero.
+    ///
+    /// The inverse of `a` is only witnessed for the check and is not returned, so this costs the
+    /// same as `fp_is_zero`.
+    pub fn assert_field_nonzero<P: FieldParameters>(&mut self, a: &FieldRegister<P>)
+    where
+        L::Instruction: From<FpIsZeroInstruction<P>>,
+    {
+        let is_zero = self.fp_is_zero(a);
+        self.assert_zero(&is_zero);
+    }
+}
+
+impl<AP: PolynomialParser, P: FieldParameters> AirConstraint<AP> for FpIsZeroInstruction<P> {
+    fn eval(&self, parser: &mut AP) {
+        let p_a = self.a.eval(parser);
+        let p_inverse = self.inverse.eval(parser);
+        let p_carry = self.carry.eval(parser);
+        let is_zero = self.is_zero.eval(parser);
+
+        // Assert that is_zero * a = 0 limb by limb.
+        for a_i in p_a.coefficients().iter() {
+            let constraint = parser.mul(is_zero, *a_i);
+            parser.constraint(constraint);
+        }
+
+        // Compute the vanishing polynomial a(x) * inv(x) - (1 - is_zero) - carry(x) * p(x).
+        let one = parser.one();
+        let one_minus_is_zero = parser.sub(one, is_zero);
+        let p_one_minus_is_zero = Polynomial::from_coefficients(vec![one_minus_is_zero]);
+
+        let p_a_mul_inverse = parser.poly_mul(&p_a, &p_inverse);
+        let p_a_mul_inverse_minus_one = parser.poly_sub(&p_a_mul_inverse, &p_one_minus_is_zero);
+        let p_limbs = parser.constant_poly(&Polynomial::from_iter(util::modulus_field_iter::<
+            AP::Field,
+            P,
+        >()));
+
+        let p_mul_times_carry = parser.poly_mul(&p_carry, &p_limbs);
+        let p_vanishing = parser.poly_sub(&p_a_mul_inverse_minus_one, &p_mul_times_carry);
+
+        let p_witness_low = Polynomial::from_coefficients(self.witness_low.eval_vec(parser));
+        let p_witness_high = Polynomial::from_coefficients(self.witness_high.eval_vec(parser));
+
+        util::eval_field_operation::<AP, P>(parser, &p_vanishing, &p_witness_low, &p_witness_high)
+    }
+}
+
+impl<F: PrimeField64, P: FieldParameters> Instruction<F> for FpIsZeroInstruction<P> {
+    fn trace_layout(&self) -> Vec<MemorySlice> {
+        vec![
+            *self.is_zero.register(),
+            *self.inverse.register(),
+            *self.carry.register(),
+            *self.witness_low.register(),
+            *self.witness_high.register(),
+        ]
+    }
+
+    fn inputs(&self) -> Vec<MemorySlice> {
+        vec![*self.a.register()]
+    }
+
+    fn constraint_degree(&self) -> usize {
+        2
+    }
+
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let p_a = writer.read(&self.a, row_index);
+
+        let a_digits = p_a
+            .coefficients
+            .iter()
+            .map(|x| x.as_canonical_u64() as u16)
+            .collect::<Vec<_>>();
+
+        let a = digits_to_biguint(&a_digits);
+
+        // Compute the inverse in the integers, with the convention that the inverse of zero is
+        // zero.
+        let modulus = P::modulus();
+        let (inverse, is_zero) = if (&a % &modulus).is_zero() {
+            (BigUint::zero(), F::ONE)
+        } else {
+            (a.modpow(&(&modulus - 2u32), &modulus), F::ZERO)
+        };
+        let one_minus_is_zero = if is_zero == F::ONE { 0u32 } else { 1u32 };
+        let carry = (&a * &inverse - one_minus_is_zero) / &modulus;
+        debug_assert_eq!(&carry * &modulus, &a * &inverse - one_minus_is_zero);
+
+        // Make little endian polynomial limbs.
+        let p_modulus = to_u16_le_limbs_polynomial::<F, P>(&modulus);
+        let p_inverse = to_u16_le_limbs_polynomial::<F, P>(&inverse);
+        let p_carry = to_u16_le_limbs_polynomial::<F, P>(&carry);
+        let p_one_minus_is_zero = Polynomial::from_coefficients(vec![F::ONE - is_zero]);
+
+        // Compute the vanishing polynomial.
+        let p_vanishing = &p_a * &p_inverse - &p_one_minus_is_zero - &p_carry * &p_modulus;
+        debug_assert_eq!(p_vanishing.degree(), P::NB_WITNESS_LIMBS);
+
+        // Compute the witness.
+        let p_witness = util::compute_root_quotient_and_shift(&p_vanishing, P::WITNESS_OFFSET);
+        let (p_witness_low, p_witness_high) = split_u32_limbs_to_u16_limbs(&p_witness);
+
+        let mut values = vec![is_zero];
+        values.extend_from_slice(p_inverse.coefficients());
+        values.extend_from_slice(p_carry.coefficients());
+        values.extend_from_slice(&p_witness_low);
+        values.extend_from_slice(&p_witness_high);
+
+        // Row must match layout of instruction.
+        writer.write_unsafe_batch_raw(
+            &[
+                *self.is_zero.register(),
+                *self.inverse.register(),
+                *self.carry.register(),
+                *self.witness_low.register(),
+                *self.witness_high.register(),
+            ],
+            &values,
+            row_index,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::field::parameters::tests::Fp25519;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct FpIsZeroTest;
+
+    impl AirParameters for FpIsZeroTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 108;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 171;
+
+        type Instruction = FpIsZeroInstruction<Fp25519>;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    #[test]
+    fn test_fp_is_zero() {
+        type F = GoldilocksField;
+        type L = FpIsZeroTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type P = Fp25519;
+
+        let p = Fp25519::modulus();
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let a = builder.alloc::<FieldRegister<P>>();
+        let is_zero = builder.fp_is_zero(&a);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            let writer = generator.new_writer();
+            // Every other row has a zero input.
+            let a_int = if i % 2 == 0 {
+                BigUint::zero()
+            } else {
+                let mut rng = thread_rng();
+                rng.gen_biguint(256) % &p
+            };
+            let p_a = Polynomial::<F>::from_biguint_field(&a_int, 16, 16);
+            writer.write(&a, &p_a, i);
+            writer.write_row_instructions(&generator.air_data, i);
+
+            let expected = F::from_canonical_u8(a_int.is_zero() as u8);
+            assert_eq!(writer.read(&is_zero, i), expected);
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+
+    /// Proves that the constant `value` written in every row is not zero.
+    fn prove_field_nonzero(value: &BigUint) {
+        type F = GoldilocksField;
+        type L = FpIsZeroTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type P = Fp25519;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let a = builder.alloc::<FieldRegister<P>>();
+        builder.assert_field_nonzero(&a);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let p_a = Polynomial::<F>::from_biguint_field(value, 16, 16);
+        let writer = generator.new_writer();
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            writer.write(&a, &p_a, i);
+            writer.write_row_instructions(&generator.air_data, i);
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+
+    #[test]
+    fn test_assert_field_nonzero() {
+        prove_field_nonzero(&BigUint::from(7u32));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_assert_field_nonzero_zero() {
+        prove_field_nonzero(&BigUint::zero());
+    }
+}
@@ -28,13 +28,17 @@
 //! overflow.
 
 pub mod add;
+pub mod constant;
 pub mod den;
 pub mod div;
 pub mod inner_product;
 pub mod instruction;
+pub mod is_zero;
+pub mod mont_mul;
 pub mod mul;
 pub mod mul_const;
 pub mod parameters;
 pub mod register;
+pub mod sqrt;
 pub mod sub;
-mod util;
+pub(crate) mod util;
@@ -0,0 +1,349 @@
+use num::BigUint;
+use serde::{Deserialize, Serialize};
+
+use super::parameters::FieldParameters;
+use super::register::FieldRegister;
+use super::util;
+use crate::air::AirConstraint;
+use crate::chip::bignum;
+use crate::chip::builder::AirBuilder;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::memory::MemorySlice;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::TraceWriter;
+use crate::chip::utils::{digits_to_biguint, split_u32_limbs_to_u16_limbs};
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::parser::PolynomialParser;
+use crate::polynomial::{to_u16_le_limbs_polynomial, Polynomial};
+
+/// Montgomery multiplication `result = a * b * R^(-1) mod p`, where `R = 2^(16 * NB_LIMBS)`.
+///
+/// The instruction witnesses `m = a * b * n' mod R` and a bit `s` such that
+///
+/// a * b + m * p = (result + s * p) * R,
+///
+/// which is proven as the polynomial identity `a(x) * b(x) + m(x) * p(x) - (result(x) +
+/// s * p(x)) * x^NB_LIMBS = (x - 2^16) * w(x)`. The reduction by `R` is a shift of the limbs, so
+/// that no carry of the product modulo `p` is needed. As with the other field instructions, the
+/// result is not constrained to be less than `p`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct FpMontMulInstruction<P: FieldParameters> {
+    pub a: FieldRegister<P>,
+    pub b: FieldRegister<P>,
+    pub result: FieldRegister<P>,
+    pub(crate) m: FieldRegister<P>,
+    pub(crate) subtract: BitRegister,
+    pub(crate) witness_low: ArrayRegister<U16Register>,
+    pub(crate) witness_high: ArrayRegister<U16Register>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Given two field elements `a` and `b` in Montgomery form, computes their product
+    /// `a * b * R^(-1)` in Montgomery form.
+    pub fn fp_mont_mul<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        b: &FieldRegister<P>,
+    ) -> FpMontMulInstruction<P>
+    where
+        L::Instruction: From<FpMontMulInstruction<P>>,
+    {
+        let result = self.alloc::<FieldRegister<P>>();
+        let m = self.alloc::<FieldRegister<P>>();
+        let subtract = self.alloc::<BitRegister>();
+        let witness_low = self.alloc_array::<U16Register>(2 * P::NB_LIMBS - 1);
+        let witness_high = self.alloc_array::<U16Register>(2 * P::NB_LIMBS - 1);
+        let instr = FpMontMulInstruction {
+            a: *a,
+            b: *b,
+            result,
+            m,
+            subtract,
+            witness_low,
+            witness_high,
+        };
+        self.register_instruction(instr);
+        instr
+    }
+}
+
+impl<P: FieldParameters> FpMontMulInstruction<P> {
+    /// The offset of the witness, which bounds the sum of the two products `a * b` and `m * p`.
+    fn witness_offset() -> usize {
+        bignum::witness_offset(2 * P::NB_LIMBS + 1)
+    }
+}
+
+impl<AP: PolynomialParser, P: FieldParameters> AirConstraint<AP> for FpMontMulInstruction<P> {
+    fn eval(&self, parser: &mut AP) {
+        let p_a = self.a.eval(parser);
+        let p_b = self.b.eval(parser);
+        let p_result = self.result.eval(parser);
+        let p_m = self.m.eval(parser);
+        let subtract = self.subtract.eval(parser);
+
+        let p_limbs = Polynomial::from_iter(util::modulus_field_iter::<AP::Field, P>());
+
+        // (result(x) + s * p(x)) * x^NB_LIMBS
+        let p_modulus = parser.constant_poly(&p_limbs);
+        let p_subtract_modulus = parser.poly_scalar_mul(&p_modulus, &subtract);
+        let p_result_plus_subtract = parser.poly_add(&p_result, &p_subtract_modulus);
+        let zero = parser.zero();
+        let mut shifted_coefficients = vec![zero; P::NB_LIMBS];
+        shifted_coefficients.extend_from_slice(p_result_plus_subtract.coefficients());
+        let p_shifted = Polynomial::from_coefficients(shifted_coefficients);
+
+        // Compute the vanishing polynomial a(x) * b(x) + m(x) * p(x) - (result(x) + s * p(x)) *
+        // x^NB_LIMBS.
+        let p_a_mul_b = parser.poly_mul(&p_a, &p_b);
+        let p_m_mul_modulus = parser.poly_mul_poly_const(&p_m, &p_limbs);
+        let p_sum = parser.poly_add(&p_a_mul_b, &p_m_mul_modulus);
+        let p_vanishing = parser.poly_sub(&p_sum, &p_shifted);
+
+        let p_witness_low = Polynomial::from_coefficients(self.witness_low.eval_vec(parser));
+        let p_witness_high = Polynomial::from_coefficients(self.witness_high.eval_vec(parser));
+
+        util::eval_root_quotient(
+            parser,
+            &p_vanishing,
+            &p_witness_low,
+            &p_witness_high,
+            Self::witness_offset(),
+        )
+    }
+}
+
+impl<F: PrimeField64, P: FieldParameters> Instruction<F> for FpMontMulInstruction<P> {
+    fn trace_layout(&self) -> Vec<MemorySlice> {
+        vec![
+            *self.result.register(),
+            *self.m.register(),
+            *self.subtract.register(),
+            *self.witness_low.register(),
+            *self.witness_high.register(),
+        ]
+    }
+
+    fn inputs(&self) -> Vec<MemorySlice> {
+        vec![*self.a.register(), *self.b.register()]
+    }
+
+    fn constraint_degree(&self) -> usize {
+        2
+    }
+
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let p_a = writer.read(&self.a, row_index);
+        let p_b = writer.read(&self.b, row_index);
+
+        let a_digits = p_a
+            .coefficients
+            .iter()
+            .map(|x| x.as_canonical_u64() as u16)
+            .collect::<Vec<_>>();
+        let b_digits = p_b
+            .coefficients
+            .iter()
+            .map(|x| x.as_canonical_u64() as u16)
+            .collect::<Vec<_>>();
+
+        let a = digits_to_biguint(&a_digits);
+        let b = digits_to_biguint(&b_digits);
+
+        // Compute the Montgomery reduction in the integers.
+        let modulus = P::modulus();
+        let nb_bits = 16 * P::NB_LIMBS;
+        let radix_mask = (BigUint::from(1u32) << nb_bits) - 1u32;
+        let a_mul_b = &a * &b;
+        let m = ((&a_mul_b & &radix_mask) * P::montgomery_n_prime()) & &radix_mask;
+        let sum = a_mul_b + &m * &modulus;
+        debug_assert_eq!(&sum & &radix_mask, BigUint::from(0u32));
+        let reduced = sum >> nb_bits;
+        let (result, subtract) = if reduced >= modulus {
+            (reduced - &modulus, F::ONE)
+        } else {
+            (reduced, F::ZERO)
+        };
+
+        // Make little endian polynomial limbs.
+        let p_modulus = to_u16_le_limbs_polynomial::<F, P>(&modulus);
+        let p_result = to_u16_le_limbs_polynomial::<F, P>(&result);
+        let p_m = to_u16_le_limbs_polynomial::<F, P>(&m);
+
+        // Compute the vanishing polynomial.
+        let mut shifted_coefficients = vec![F::ZERO; P::NB_LIMBS];
+        shifted_coefficients.extend(
+            p_result
+                .coefficients()
+                .iter()
+                .zip(p_modulus.coefficients())
+                .map(|(r, p)| *r + subtract * *p),
+        );
+        let p_shifted = Polynomial::from_coefficients(shifted_coefficients);
+        let p_vanishing = &p_a * &p_b + &p_m * &p_modulus - &p_shifted;
+        debug_assert_eq!(p_vanishing.degree(), self.witness_low.len());
+
+        // Compute the witness.
+        let p_witness = util::compute_root_quotient_and_shift(&p_vanishing, Self::witness_offset());
+        let (p_witness_low, p_witness_high) = split_u32_limbs_to_u16_limbs(&p_witness);
+
+        let mut values = p_result.coefficients;
+        values.extend_from_slice(p_m.coefficients());
+        values.push(subtract);
+        values.extend_from_slice(&p_witness_low);
+        values.extend_from_slice(&p_witness_high);
+
+        // Row must match layout of instruction.
+        writer.write_unsafe_batch_raw(
+            &[
+                *self.result.register(),
+                *self.m.register(),
+                *self.subtract.register(),
+                *self.witness_low.register(),
+                *self.witness_high.register(),
+            ],
+            &values,
+            row_index,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::bignum::instruction::BigNumInstruction;
+    use crate::chip::builder::tests::*;
+    use crate::chip::ec::weierstrass::secp256k1::Secp256k1BaseField;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct FpMontMulTest;
+
+    impl AirParameters for FpMontMulTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 126;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 198;
+
+        type Instruction = FpMontMulInstruction<Secp256k1BaseField>;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct NaiveMulReduceTest;
+
+    impl AirParameters for NaiveMulReduceTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 222;
+        const NUM_FREE_COLUMNS: usize = 1;
+        const EXTENDED_COLUMNS: usize = 342;
+
+        type Instruction = BigNumInstruction<Secp256k1BaseField>;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    /// The number of trace columns written by the instructions of `builder`.
+    fn num_instruction_columns<L: AirParameters>(builder: &AirBuilder<L>) -> usize {
+        builder
+            .instructions
+            .iter()
+            .flat_map(|instr| Instruction::<L::Field>::trace_layout(instr))
+            .map(|slice| slice.len())
+            .sum()
+    }
+
+    #[test]
+    fn test_fp_mont_mul_cost() {
+        type P = Secp256k1BaseField;
+
+        let mut mont_builder = AirBuilder::<FpMontMulTest>::new();
+        let a = mont_builder.alloc::<FieldRegister<P>>();
+        let b = mont_builder.alloc::<FieldRegister<P>>();
+        mont_builder.fp_mont_mul(&a, &b);
+        let mont_columns = num_instruction_columns(&mont_builder);
+
+        let mut naive_builder = AirBuilder::<NaiveMulReduceTest>::new();
+        let a = naive_builder.alloc_array::<U16Register>(P::NB_LIMBS);
+        let b = naive_builder.alloc_array::<U16Register>(P::NB_LIMBS);
+        let product = naive_builder.bignum_mul(&a, &b);
+        naive_builder.bignum_mod_reduce::<P>(&product);
+        let naive_columns = num_instruction_columns(&naive_builder);
+
+        assert_eq!(mont_columns, 95);
+        assert_eq!(naive_columns, 189);
+    }
+
+    #[test]
+    fn test_fp_mont_mul() {
+        type F = GoldilocksField;
+        type L = FpMontMulTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type P = Secp256k1BaseField;
+
+        let p = P::modulus();
+        let r = P::montgomery_r();
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let a = builder.alloc::<FieldRegister<P>>();
+        let b = builder.alloc::<FieldRegister<P>>();
+        let mont_mul = builder.fp_mont_mul(&a, &b);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            let writer = generator.new_writer();
+            // The first row has the maximal canonical inputs.
+            let (a_int, b_int) = match i {
+                0 => (&p - 1u32, &p - 1u32),
+                _ => {
+                    let mut rng = thread_rng();
+                    (rng.gen_biguint(256) % &p, rng.gen_biguint(256) % &p)
+                }
+            };
+
+            // Convert into Montgomery form, where x is represented by x * R mod p.
+            let a_mont = (&a_int * &r) % &p;
+            let b_mont = (&b_int * &r) % &p;
+
+            writer.write(&a, &to_u16_le_limbs_polynomial::<F, P>(&a_mont), i);
+            writer.write(&b, &to_u16_le_limbs_polynomial::<F, P>(&b_mont), i);
+            writer.write_row_instructions(&generator.air_data, i);
+
+            let result = writer.read(&mont_mul.result, i);
+            let result_digits = result
+                .coefficients
+                .iter()
+                .map(|x| x.as_canonical_u64() as u16)
+                .collect::<Vec<_>>();
+            assert_eq!(digits_to_biguint(&result_digits), (a_int * b_int * &r) % &p);
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+}
@@ -1,6 +1,6 @@
 use core::fmt::Debug;
 
-use num::{BigUint, Zero};
+use num::{BigUint, One, Zero};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
@@ -23,14 +23,48 @@ pub trait FieldParameters:
         }
         modulus
     }
+
+    /// The Montgomery radix `R = 2^(16 * NB_LIMBS)` reduced modulo the modulus.
+    fn montgomery_r() -> BigUint {
+        (BigUint::one() << (16 * Self::NB_LIMBS)) % Self::modulus()
+    }
+
+    /// The square of the Montgomery radix modulo the modulus, used to convert into Montgomery
+    /// form.
+    fn montgomery_r2() -> BigUint {
+        let r = Self::montgomery_r();
+        (&r * &r) % Self::modulus()
+    }
+
+    /// The constant `n' = -p^(-1) mod R` of Montgomery reduction. The modulus must be odd.
+    fn montgomery_n_prime() -> BigUint {
+        let modulus = Self::modulus();
+        assert!(
+            modulus.bit(0),
+            "Montgomery reduction requires an odd modulus"
+        );
+        let nb_bits = 16 * Self::NB_LIMBS;
+        let radix = BigUint::one() << nb_bits;
+
+        // Newton iteration for the inverse modulo R, doubling the number of correct bits each time.
+        let mut inverse = BigUint::one();
+        let mut nb_correct_bits = 1;
+        while nb_correct_bits < nb_bits {
+            let correction =
+                (&radix + BigUint::from(2u32) - (&modulus * &inverse) % &radix) % &radix;
+            inverse = (inverse * correction) % &radix;
+            nb_correct_bits *= 2;
+        }
+        (&radix - inverse) % radix
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
-    use num::One;
     use serde::Deserialize;
 
     use super::*;
+    use crate::chip::ec::weierstrass::secp256k1::Secp256k1BaseField;
 
     #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
     pub struct Fp25519;
@@ -49,4 +83,34 @@ pub mod tests {
             (BigUint::one() << 255) - BigUint::from(19u32)
         }
     }
+
+    #[test]
+    fn test_montgomery_constants() {
+        for (modulus, r, r2, n_prime) in [
+            (
+                Fp25519::modulus(),
+                Fp25519::montgomery_r(),
+                Fp25519::montgomery_r2(),
+                Fp25519::montgomery_n_prime(),
+            ),
+            (
+                Secp256k1BaseField::modulus(),
+                Secp256k1BaseField::montgomery_r(),
+                Secp256k1BaseField::montgomery_r2(),
+                Secp256k1BaseField::montgomery_n_prime(),
+            ),
+        ] {
+            let radix = BigUint::one() << 256;
+            assert_eq!(r, &radix % &modulus);
+            assert_eq!(r2, (&radix * &radix) % &modulus);
+            assert_eq!(
+                (&modulus * &n_prime + BigUint::one()) % &radix,
+                BigUint::zero()
+            );
+        }
+        assert_eq!(
+            Secp256k1BaseField::modulus(),
+            (BigUint::one() << 256) - (BigUint::one() << 32) - BigUint::from(977u32)
+        );
+    }
 }
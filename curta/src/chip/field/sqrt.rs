@@ -0,0 +1,293 @@
+use num::{BigUint, One, Zero};
+use serde::{Deserialize, Serialize};
+
+use super::parameters::FieldParameters;
+use super::register::FieldRegister;
+use super::util;
+use crate::air::AirConstraint;
+use crate::chip::builder::AirBuilder;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::memory::MemorySlice;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::TraceWriter;
+use crate::chip::utils::{digits_to_biguint, split_u32_limbs_to_u16_limbs};
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::parser::PolynomialParser;
+use crate::polynomial::{to_u16_le_limbs_polynomial, Polynomial};
+
+/// Fp square root. Computes `result` such that `result^2 = a`, asserting that
+///
+/// result * result - a - carry * p = 0.
+///
+/// The trace generator writes the root which is at most `(p - 1) / 2`, but the constraints accept
+/// either root. If `a` is not a square, the instruction cannot be satisfied.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct FpSqrtInstruction<P: FieldParameters> {
+    pub a: FieldRegister<P>,
+    pub result: FieldRegister<P>,
+    pub(crate) carry: FieldRegister<P>,
+    pub(crate) witness_low: ArrayRegister<U16Register>,
+    pub(crate) witness_high: ArrayRegister<U16Register>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Given a square field element `a`, computes a square root of `a`.
+    pub fn fp_sqrt<P: FieldParameters>(&mut self, a: &FieldRegister<P>) -> FieldRegister<P>
+    where
+        L::Instruction: From<FpSqrtInstruction<P>>,
+    {
+        let result = self.alloc::<FieldRegister<P>>();
+        let carry = self.alloc::<FieldRegister<P>>();
+        let witness_low = self.alloc_array::<U16Register>(P::NB_WITNESS_LIMBS);
+        let witness_high = self.alloc_array::<U16Register>(P::NB_WITNESS_LIMBS);
+        let instr = FpSqrtInstruction {
+            a: *a,
+            result,
+            carry,
+            witness_low,
+            witness_high,
+        };
+        self.register_instruction(instr);
+        result
+    }
+}
+
+/// Computes the square root of `a` modulo the prime `modulus` which is at most `(modulus - 1) / 2`
+/// with the Tonelli-Shanks algorithm, or returns `None` if `a` is not a square.
+pub fn biguint_sqrt(a: &BigUint, modulus: &BigUint) -> Option<BigUint> {
+    let a = a % modulus;
+    if a.is_zero() {
+        return Some(BigUint::zero());
+    }
+
+    // Check that `a` is a square with Euler's criterion.
+    let one = BigUint::one();
+    let modulus_minus_one = modulus - 1u32;
+    let legendre_exponent = &modulus_minus_one >> 1;
+    if a.modpow(&legendre_exponent, modulus) != one {
+        return None;
+    }
+
+    // Write p - 1 = q * 2^s with q odd, and find a non-square z.
+    let s = modulus_minus_one.trailing_zeros().unwrap();
+    let q = &modulus_minus_one >> s;
+    let mut z = BigUint::from(2u32);
+    while z.modpow(&legendre_exponent, modulus) != modulus_minus_one {
+        z += 1u32;
+    }
+
+    let mut m = s;
+    let mut c = z.modpow(&q, modulus);
+    let mut t = a.modpow(&q, modulus);
+    let mut root = a.modpow(&((&q + 1u32) >> 1), modulus);
+    while t != one {
+        // Find the least i such that t^(2^i) = 1.
+        let mut i = 0;
+        let mut t_pow = t.clone();
+        while t_pow != one {
+            t_pow = (&t_pow * &t_pow) % modulus;
+            i += 1;
+        }
+        let b = c.modpow(&(BigUint::one() << (m - i - 1)), modulus);
+        m = i;
+        c = (&b * &b) % modulus;
+        t = (t * &c) % modulus;
+        root = (root * &b) % modulus;
+    }
+
+    if root > legendre_exponent {
+        root = modulus - root;
+    }
+    Some(root)
+}
+
+impl<AP: PolynomialParser, P: FieldParameters> AirConstraint<AP> for FpSqrtInstruction<P> {
+    fn eval(&self, parser: &mut AP) {
+        let p_a = self.a.eval(parser);
+        let p_result = self.result.eval(parser);
+        let p_carry = self.carry.eval(parser);
+
+        // Compute the vanishing polynomial result(x)^2 - a(x) - carry(x) * p(x).
+        let p_result_squared = parser.poly_mul(&p_result, &p_result);
+        let p_result_squared_minus_a = parser.poly_sub(&p_result_squared, &p_a);
+        let p_limbs = parser.constant_poly(&Polynomial::from_iter(util::modulus_field_iter::<
+            AP::Field,
+            P,
+        >()));
+
+        let p_mul_times_carry = parser.poly_mul(&p_carry, &p_limbs);
+        let p_vanishing = parser.poly_sub(&p_result_squared_minus_a, &p_mul_times_carry);
+
+        let p_witness_low = Polynomial::from_coefficients(self.witness_low.eval_vec(parser));
+        let p_witness_high = Polynomial::from_coefficients(self.witness_high.eval_vec(parser));
+
+        util::eval_field_operation::<AP, P>(parser, &p_vanishing, &p_witness_low, &p_witness_high)
+    }
+}
+
+impl<F: PrimeField64, P: FieldParameters> Instruction<F> for FpSqrtInstruction<P> {
+    fn trace_layout(&self) -> Vec<MemorySlice> {
+        vec![
+            *self.result.register(),
+            *self.carry.register(),
+            *self.witness_low.register(),
+            *self.witness_high.register(),
+        ]
+    }
+
+    fn inputs(&self) -> Vec<MemorySlice> {
+        vec![*self.a.register()]
+    }
+
+    fn constraint_degree(&self) -> usize {
+        2
+    }
+
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let p_a = writer.read(&self.a, row_index);
+
+        let a_digits = p_a
+            .coefficients
+            .iter()
+            .map(|x| x.as_canonical_u64() as u16)
+            .collect::<Vec<_>>();
+
+        let a = digits_to_biguint(&a_digits);
+
+        // Compute the square root in the integers. A non-square input has no valid witness, so
+        // the root is set to zero and the constraints fail.
+        let modulus = P::modulus();
+        let result = biguint_sqrt(&a, &modulus).unwrap_or_else(BigUint::zero);
+        let result_squared = &result * &result;
+        let carry = if result_squared >= a {
+            (&result_squared - &a) / &modulus
+        } else {
+            BigUint::zero()
+        };
+
+        // Make little endian polynomial limbs.
+        let p_modulus = to_u16_le_limbs_polynomial::<F, P>(&modulus);
+        let p_result = to_u16_le_limbs_polynomial::<F, P>(&result);
+        let p_carry = to_u16_le_limbs_polynomial::<F, P>(&carry);
+
+        // Compute the vanishing polynomial.
+        let p_vanishing = &p_result * &p_result - &p_a - &p_carry * &p_modulus;
+        debug_assert_eq!(p_vanishing.degree(), P::NB_WITNESS_LIMBS);
+
+        // Compute the witness.
+        let p_witness = util::compute_root_quotient_and_shift(&p_vanishing, P::WITNESS_OFFSET);
+        let (p_witness_low, p_witness_high) = split_u32_limbs_to_u16_limbs(&p_witness);
+
+        let mut values = p_result.coefficients;
+        values.extend_from_slice(p_carry.coefficients());
+        values.extend_from_slice(&p_witness_low);
+        values.extend_from_slice(&p_witness_high);
+
+        // Row must match layout of instruction.
+        writer.write_unsafe_batch_raw(
+            &[
+                *self.result.register(),
+                *self.carry.register(),
+                *self.witness_low.register(),
+                *self.witness_high.register(),
+            ],
+            &values,
+            row_index,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::field::parameters::tests::Fp25519;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct FpSqrtTest;
+
+    impl AirParameters for FpSqrtTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 108;
+        const NUM_FREE_COLUMNS: usize = 1;
+        const EXTENDED_COLUMNS: usize = 171;
+
+        type Instruction = FpSqrtInstruction<Fp25519>;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    #[test]
+    fn test_biguint_sqrt() {
+        let p = Fp25519::modulus();
+        let mut rng = thread_rng();
+        for _ in 0..100 {
+            let x = rng.gen_biguint(256) % &p;
+            let square = (&x * &x) % &p;
+            let root = biguint_sqrt(&square, &p).unwrap();
+            assert_eq!((&root * &root) % &p, square);
+            assert!(root <= (&p - 1u32) >> 1);
+        }
+        // Since p = 1 mod 4, -1 is a square, and 2 is not since p = 5 mod 8.
+        assert!(biguint_sqrt(&(&p - 1u32), &p).is_some());
+        assert!(biguint_sqrt(&BigUint::from(2u32), &p).is_none());
+    }
+
+    #[test]
+    fn test_fp_sqrt() {
+        type F = GoldilocksField;
+        type L = FpSqrtTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type P = Fp25519;
+
+        let p = Fp25519::modulus();
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let a = builder.alloc::<FieldRegister<P>>();
+        let root = builder.fp_sqrt(&a);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            let writer = generator.new_writer();
+            let mut rng = thread_rng();
+            let x = rng.gen_biguint(256) % &p;
+            let a_int = (&x * &x) % &p;
+            let p_a = Polynomial::<F>::from_biguint_field(&a_int, 16, 16);
+            writer.write(&a, &p_a, i);
+            writer.write_row_instructions(&generator.air_data, i);
+
+            let root_int = digits_to_biguint(
+                &writer
+                    .read(&root, i)
+                    .coefficients
+                    .iter()
+                    .map(|x| x.as_canonical_u64() as u16)
+                    .collect::<Vec<_>>(),
+            );
+            assert!(root_int == x || root_int == &p - &x);
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+}
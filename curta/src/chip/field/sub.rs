@@ -1,3 +1,4 @@
+use num::{BigUint, Zero};
 use serde::{Deserialize, Serialize};
 
 use super::add::FpAddInstruction;
@@ -27,6 +28,8 @@ pub struct FpSubInstruction<P: FieldParameters> {
 
 impl<L: AirParameters> AirBuilder<L> {
     /// given two field elements `a` and `b`, computes the difference `a - b = c`.
+    ///
+    /// The subtraction is folded if both inputs are constants or if `b` is zero.
     pub fn fp_sub<P: FieldParameters>(
         &mut self,
         a: &FieldRegister<P>,
@@ -35,6 +38,9 @@ impl<L: AirParameters> AirBuilder<L> {
     where
         L::Instruction: From<FpSubInstruction<P>>,
     {
+        if let Some(result) = self.fold_fp_sub(a, b) {
+            return result;
+        }
         let result = self.alloc::<FieldRegister<P>>();
         self.set_fp_sub(a, b, &result);
         result
@@ -63,6 +69,18 @@ impl<L: AirParameters> AirBuilder<L> {
         let instr = FpSubInstruction { inner: inner_instr };
         self.register_instruction(instr);
     }
+
+    /// given a field element `a`, computes the negation `-a = p - a`, which is zero for `a = 0`.
+    pub fn fp_neg<P: FieldParameters>(&mut self, a: &FieldRegister<P>) -> FieldRegister<P>
+    where
+        L::Instruction: From<FpSubInstruction<P>>,
+    {
+        if let Some(result) = self.fold_fp_neg(a) {
+            return result;
+        }
+        let zero = self.alloc_constant_field_register::<P>(&BigUint::zero());
+        self.fp_sub(&zero, a)
+    }
 }
 
 impl<AP: PolynomialParser, P: FieldParameters> AirConstraint<AP> for FpSubInstruction<P> {
@@ -122,7 +140,6 @@ impl<F: PrimeField64, P: FieldParameters> Instruction<F> for FpSubInstruction<P>
 #[cfg(test)]
 mod tests {
     use num::bigint::RandBigInt;
-    use num::BigUint;
     use rand::thread_rng;
 
     use super::*;
@@ -204,4 +221,53 @@ mod tests {
         // Test the recursive proof.
         test_recursive_starky(stark, config, generator, &[]);
     }
+
+    #[test]
+    fn test_fpneg() {
+        type F = GoldilocksField;
+        type L = FpSubTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type P = Fp25519;
+
+        let p = Fp25519::modulus();
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let a = builder.alloc::<FieldRegister<P>>();
+        let c = builder.fp_neg(&a);
+        let c_expected = builder.alloc::<FieldRegister<P>>();
+        builder.assert_equal(&c, &c_expected);
+
+        let (air, trace_data) = builder.build();
+
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            let mut rng = thread_rng();
+            let writer = generator.new_writer();
+            // Every eighth row negates zero, and the others negate a random element.
+            let a_int = if i % 8 == 0 {
+                BigUint::zero()
+            } else {
+                rng.gen_biguint(256) % &p
+            };
+            let c_int = (&p - &a_int) % &p;
+
+            let p_a = Polynomial::<F>::from_biguint_field(&a_int, 16, 16);
+            let p_c = Polynomial::<F>::from_biguint_field(&c_int, 16, 16);
+
+            writer.write(&a, &p_a, i);
+            writer.write(&c_expected, &p_c, i);
+            writer.write_row_instructions(&generator.air_data, i);
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
 }
@@ -10,6 +10,24 @@ pub fn eval_field_operation<AP: PolynomialParser, P: FieldParameters>(
     p_vanishing: &Polynomial<AP::Var>,
     p_witness_low: &Polynomial<AP::Var>,
     p_witness_high: &Polynomial<AP::Var>,
+) {
+    eval_root_quotient(
+        parser,
+        p_vanishing,
+        p_witness_low,
+        p_witness_high,
+        P::WITNESS_OFFSET,
+    )
+}
+
+/// Constrains `vanishing(x) = (x - 2^16) * w(x)`, where the witness `w(x)` is given by its low and
+/// high 16-bit limbs, shifted up by `offset`.
+pub fn eval_root_quotient<AP: PolynomialParser>(
+    parser: &mut AP,
+    p_vanishing: &Polynomial<AP::Var>,
+    p_witness_low: &Polynomial<AP::Var>,
+    p_witness_high: &Polynomial<AP::Var>,
+    offset: usize,
 ) {
     // Reconstruct and shift back the witness polynomial
     let limb_field = AP::Field::from_canonical_u32(2u32.pow(16));
@@ -20,7 +38,7 @@ pub fn eval_field_operation<AP: PolynomialParser, P: FieldParameters>(
 
     // Shift down the witness polynomial. Shifting is needed to range check that each
     // coefficient w_i of the witness polynomial satisfies |w_i| < 2^20.
-    let offset = AP::Field::from_canonical_u32(P::WITNESS_OFFSET as u32);
+    let offset = AP::Field::from_canonical_u32(offset as u32);
     let offset = parser.constant(offset);
     let p_witness = parser.poly_scalar_sub(&p_witness_shifted, &offset);
 
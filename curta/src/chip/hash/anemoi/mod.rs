@@ -0,0 +1,601 @@
+//! The Anemoi permutation and sponge over the native field of the AIR.
+//!
+//! The state of width `2 * l` is split into two halves `x` and `y` of `l` elements. Every round adds
+//! the round constants, applies the linear layer and then the open Flystel S-box to each pair
+//! `(x_i, y_i)`, and a final linear layer is applied after the last round. With `g` the generator of
+//! the parameters, the open Flystel maps `(x, y)` to `(u, v)` as
+//!
+//! x' = x - g * y^2 - 1/g,
+//! v = y - x'^(1/7),
+//! u = x' + g * v^2.
+//!
+//! Like the inverse S-box of Rescue, `v` is witnessed by an instruction and constrained by the
+//! closed Flystel, `(y - v)^7 = x - g * y^2 - 1/g`, while `u` is a degree 2 expression.
+//!
+//! Reference: https://eprint.iacr.org/2022/840.pdf
+
+use core::fmt::Debug;
+use core::marker::PhantomData;
+use core::ops::{Add, Mul};
+
+use plonky2::field::goldilocks_field::GoldilocksField;
+use serde::{Deserialize, Serialize};
+
+use crate::air::parser::AirParser;
+use crate::air::AirConstraint;
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::builder::AirBuilder;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::memory::MemorySlice;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::TraceWriter;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+
+/// The parameters of an Anemoi permutation with S-box exponent `7`.
+pub trait AnemoiParameters:
+    'static + Debug + Clone + Copy + Send + Sync + Serialize + for<'de> Deserialize<'de>
+{
+    /// The number of elements `l` in each half of the state. Only `1` and `2` are supported.
+    const NUM_COLUMNS: usize;
+    /// The number of elements in the permutation state.
+    const WIDTH: usize = 2 * Self::NUM_COLUMNS;
+    /// The number of state elements absorbed and squeezed by the sponge per permutation.
+    const RATE: usize;
+    const NUM_ROUNDS: usize;
+    /// The generator `g` of the multiplicative group, used in the Flystel and the linear layer.
+    const GENERATOR: u64;
+    /// The inverse of 7 modulo `p - 1`.
+    const INV_ALPHA: u64;
+
+    /// The constants `(c, d)` added to `x[index]` and `y[index]` at the start of `round`.
+    fn round_constants(round: usize, index: usize) -> (u64, u64);
+}
+
+/// An Anemoi instance of width 2 over the Goldilocks field.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct GoldilocksAnemoi2;
+
+/// An Anemoi instance of width 4 over the Goldilocks field.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct GoldilocksAnemoi4;
+
+impl AnemoiParameters for GoldilocksAnemoi2 {
+    const NUM_COLUMNS: usize = 1;
+    const RATE: usize = 1;
+    const NUM_ROUNDS: usize = 21;
+    const GENERATOR: u64 = 7;
+    const INV_ALPHA: u64 = 10540996611094048183;
+
+    fn round_constants(round: usize, index: usize) -> (u64, u64) {
+        goldilocks_round_constants::<Self>(round, index)
+    }
+}
+
+impl AnemoiParameters for GoldilocksAnemoi4 {
+    const NUM_COLUMNS: usize = 2;
+    const RATE: usize = 2;
+    const NUM_ROUNDS: usize = 14;
+    const GENERATOR: u64 = 7;
+    const INV_ALPHA: u64 = 10540996611094048183;
+
+    fn round_constants(round: usize, index: usize) -> (u64, u64) {
+        goldilocks_round_constants::<Self>(round, index)
+    }
+}
+
+/// The first 100 decimal digits of pi after the decimal point, reduced modulo the Goldilocks
+/// prime.
+const GOLDILOCKS_PI_0: u64 = 9337861830824449323;
+/// The next 100 decimal digits of pi, reduced modulo the Goldilocks prime.
+const GOLDILOCKS_PI_1: u64 = 13349066130162709819;
+
+/// The round constants of the paper over the Goldilocks field, derived from `pi_0` and `pi_1` as
+///
+/// c = g * (pi_0^round)^2 + (pi_0^round + pi_1^index)^7,
+/// d = g * (pi_1^index)^2 + (pi_0^round + pi_1^index)^7 + 1/g.
+fn goldilocks_round_constants<P: AnemoiParameters>(round: usize, index: usize) -> (u64, u64) {
+    type F = GoldilocksField;
+    let generator = F::from_canonical_u64(P::GENERATOR);
+    let pi_0 = F::from_canonical_u64(GOLDILOCKS_PI_0).pow(round as u64);
+    let pi_1 = F::from_canonical_u64(GOLDILOCKS_PI_1).pow(index as u64);
+    let sum_alpha = (pi_0 + pi_1).pow(7);
+    let c = generator * pi_0 * pi_0 + sum_alpha;
+    let d = generator * pi_1 * pi_1 + sum_alpha + generator.inverse();
+    (c.as_canonical_u64(), d.as_canonical_u64())
+}
+
+/// Checks that the parameters describe a supported instance.
+fn check_parameters<P: AnemoiParameters>() {
+    assert!(
+        P::NUM_COLUMNS == 1 || P::NUM_COLUMNS == 2,
+        "Anemoi is only supported for widths 2 and 4"
+    );
+    assert!(
+        P::RATE > 0 && P::RATE < P::WIDTH,
+        "The rate must be positive and less than the width"
+    );
+}
+
+/// Applies the linear layer to the halves `x` and `y` of the state: the matrix `M` to `x` and to
+/// `y` rotated by one element, followed by the pseudo-Hadamard transform.
+///
+/// The halves must have one or two elements, as checked by `check_parameters`. For one element,
+/// `M` is the identity.
+fn linear_layer<F, T>(x: &mut [T], y: &mut [T], generator: F)
+where
+    F: Field,
+    T: Clone + Add<Output = T> + Mul<F, Output = T>,
+{
+    if x.len() == 2 {
+        // M = [[1, g], [g, g^2 + 1]]
+        y.swap(0, 1);
+        for half in [&mut *x, &mut *y] {
+            half[0] = half[0].clone() + half[1].clone() * generator;
+            half[1] = half[1].clone() + half[0].clone() * generator;
+        }
+    }
+
+    for (x_i, y_i) in x.iter_mut().zip(y.iter_mut()) {
+        *y_i = y_i.clone() + x_i.clone();
+        *x_i = x_i.clone() + y_i.clone();
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnemoiGadget<P> {
+    pub input: ArrayRegister<ElementRegister>,
+    /// The output states of all the permutations of the sponge.
+    pub states: Vec<ArrayRegister<ElementRegister>>,
+    pub output: ArrayRegister<ElementRegister>,
+    _marker: PhantomData<P>,
+}
+
+/// Witnesses the output `v` of the open Flystel on the pairs `(x_i, y_i)`, constrained by the
+/// closed Flystel.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct FlystelInstruction {
+    x: ArrayRegister<ElementRegister>,
+    y: ArrayRegister<ElementRegister>,
+    pub v: ArrayRegister<ElementRegister>,
+    /// The cubes of `y - v`.
+    t_cube: ArrayRegister<ElementRegister>,
+    generator: u64,
+    inv_alpha: u64,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Hashes `input` into `num_outputs` elements with the Anemoi sponge, overwriting the rate
+    /// portion of the state with the input.
+    pub fn anemoi_hash_n_to_m<P: AnemoiParameters>(
+        &mut self,
+        input: &ArrayRegister<ElementRegister>,
+        num_outputs: usize,
+    ) -> AnemoiGadget<P>
+    where
+        L::Instruction: From<FlystelInstruction>,
+    {
+        assert!(!input.is_empty(), "Cannot hash an empty input");
+        assert!(num_outputs > 0, "Must squeeze at least one output");
+
+        let mut state = vec![ArithmeticExpression::zero(); P::WIDTH];
+        let mut states = Vec::new();
+        for start in (0..input.len()).step_by(P::RATE) {
+            let end = (start + P::RATE).min(input.len());
+            for (i, index) in (start..end).enumerate() {
+                state[i] = input.get(index).expr();
+            }
+            let next = self.anemoi_permutation::<P>(&state);
+            state = next.iter().map(|x| x.expr()).collect();
+            states.push(next);
+        }
+
+        // Squeeze the outputs, permuting again whenever the rate is exhausted.
+        let num_absorptions = states.len();
+        for _ in 1..num_outputs.div_ceil(P::RATE) {
+            let next = self.anemoi_permutation::<P>(&state);
+            state = next.iter().map(|x| x.expr()).collect();
+            states.push(next);
+        }
+
+        let output = self.alloc_array::<ElementRegister>(num_outputs);
+        let squeezed = &states[num_absorptions - 1..];
+        for (j, element) in output.iter().enumerate() {
+            self.set_to_expression(&element, squeezed[j / P::RATE].get(j % P::RATE).expr());
+        }
+
+        AnemoiGadget {
+            input: *input,
+            states,
+            output,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Applies the Anemoi permutation to `state` and returns the output state.
+    pub fn anemoi_permutation<P: AnemoiParameters>(
+        &mut self,
+        state: &[ArithmeticExpression<L::Field>],
+    ) -> ArrayRegister<ElementRegister>
+    where
+        L::Instruction: From<FlystelInstruction>,
+    {
+        check_parameters::<P>();
+        assert_eq!(state.len(), P::WIDTH, "Invalid state width");
+
+        let mut state = state.to_vec();
+        for round in 0..P::NUM_ROUNDS {
+            state = self.anemoi_round::<P>(&state, round);
+        }
+
+        let (x, y) = state.split_at_mut(P::NUM_COLUMNS);
+        linear_layer(x, y, L::Field::from_canonical_u64(P::GENERATOR));
+        let output = self.alloc_array::<ElementRegister>(P::WIDTH);
+        for (element, value) in output.iter().zip(state) {
+            self.set_to_expression(&element, value);
+        }
+        output
+    }
+
+    /// Applies a round to `state` and returns the output state `(u, v)` as expressions.
+    fn anemoi_round<P: AnemoiParameters>(
+        &mut self,
+        state: &[ArithmeticExpression<L::Field>],
+        round: usize,
+    ) -> Vec<ArithmeticExpression<L::Field>>
+    where
+        L::Instruction: From<FlystelInstruction>,
+    {
+        let generator = L::Field::from_canonical_u64(P::GENERATOR);
+
+        // Constant addition and linear layer.
+        let (mut x, mut y): (Vec<_>, Vec<_>) = (0..P::NUM_COLUMNS)
+            .map(|i| {
+                let (c, d) = P::round_constants(round, i);
+                (
+                    state[i].clone() + L::Field::from_canonical_u64(c),
+                    state[P::NUM_COLUMNS + i].clone() + L::Field::from_canonical_u64(d),
+                )
+            })
+            .unzip();
+        linear_layer(&mut x, &mut y, generator);
+
+        let flystel_x = self.alloc_array::<ElementRegister>(P::NUM_COLUMNS);
+        let flystel_y = self.alloc_array::<ElementRegister>(P::NUM_COLUMNS);
+        for (element, value) in flystel_x.iter().zip(x).chain(flystel_y.iter().zip(y)) {
+            self.set_to_expression(&element, value);
+        }
+
+        // S-box layer.
+        let instr = FlystelInstruction {
+            x: flystel_x,
+            y: flystel_y,
+            v: self.alloc_array::<ElementRegister>(P::NUM_COLUMNS),
+            t_cube: self.alloc_array::<ElementRegister>(P::NUM_COLUMNS),
+            generator: P::GENERATOR,
+            inv_alpha: P::INV_ALPHA,
+        };
+        self.register_instruction(instr);
+
+        // u = x - g * y^2 - 1/g + g * v^2
+        let u = (0..P::NUM_COLUMNS).map(|i| {
+            let (x_i, y_i, v_i) = (
+                flystel_x.get(i).expr(),
+                flystel_y.get(i).expr(),
+                instr.v.get(i).expr(),
+            );
+            x_i - y_i.clone() * y_i * generator - generator.inverse()
+                + v_i.clone() * v_i * generator
+        });
+        u.chain(instr.v.iter().map(|v_i| v_i.expr())).collect()
+    }
+}
+
+impl<P: AnemoiParameters> AnemoiGadget<P> {
+    /// Applies the open Flystel to `(x, y)` natively and returns `(u, v)`.
+    pub fn flystel<F: Field>(x: F, y: F) -> (F, F) {
+        let generator = F::from_canonical_u64(P::GENERATOR);
+        let x = x - generator * y * y - generator.inverse();
+        let v = y - x.pow(P::INV_ALPHA);
+        let u = x + generator * v * v;
+        (u, v)
+    }
+
+    /// Applies the Anemoi permutation to `state` natively.
+    pub fn permute<F: Field>(state: &[F]) -> Vec<F> {
+        check_parameters::<P>();
+        assert_eq!(state.len(), P::WIDTH, "Invalid state width");
+        let generator = F::from_canonical_u64(P::GENERATOR);
+
+        let mut state = state.to_vec();
+        for round in 0..P::NUM_ROUNDS {
+            let (x, y) = state.split_at_mut(P::NUM_COLUMNS);
+            for i in 0..P::NUM_COLUMNS {
+                let (c, d) = P::round_constants(round, i);
+                x[i] += F::from_canonical_u64(c);
+                y[i] += F::from_canonical_u64(d);
+            }
+            linear_layer(x, y, generator);
+            for (x_i, y_i) in x.iter_mut().zip(y.iter_mut()) {
+                (*x_i, *y_i) = Self::flystel(*x_i, *y_i);
+            }
+        }
+        let (x, y) = state.split_at_mut(P::NUM_COLUMNS);
+        linear_layer(x, y, generator);
+        state
+    }
+
+    /// Hashes `input` into `num_outputs` elements with the Anemoi sponge natively.
+    pub fn hash<F: Field>(input: &[F], num_outputs: usize) -> Vec<F> {
+        let mut state = vec![F::ZERO; P::WIDTH];
+        for chunk in input.chunks(P::RATE) {
+            state[..chunk.len()].copy_from_slice(chunk);
+            state = Self::permute(&state);
+        }
+
+        let mut outputs = Vec::with_capacity(num_outputs);
+        loop {
+            for element in state[..P::RATE].iter() {
+                outputs.push(*element);
+                if outputs.len() == num_outputs {
+                    return outputs;
+                }
+            }
+            state = Self::permute(&state);
+        }
+    }
+}
+
+impl<AP: AirParser> AirConstraint<AP> for FlystelInstruction {
+    fn eval(&self, parser: &mut AP) {
+        let x = self.x.register().eval_slice(parser).to_vec();
+        let y = self.y.register().eval_slice(parser).to_vec();
+        let v = self.v.register().eval_slice(parser).to_vec();
+        let t_cube = self.t_cube.register().eval_slice(parser).to_vec();
+
+        let generator = AP::Field::from_canonical_u64(self.generator);
+        for (((x, y), v), t_3) in x.iter().zip(y.iter()).zip(v.iter()).zip(t_cube.iter()) {
+            // t_3 = (y - v)^3
+            let t = parser.sub(*y, *v);
+            let t_2 = parser.mul(t, t);
+            let t_3_expected = parser.mul(t_2, t);
+            let cube_constraint = parser.sub(t_3_expected, *t_3);
+            parser.constraint(cube_constraint);
+
+            // t_3^2 * t = x - g * y^2 - 1/g
+            let t_6 = parser.mul(*t_3, *t_3);
+            let t_7 = parser.mul(t_6, t);
+            let y_2 = parser.mul(*y, *y);
+            let g_y_2 = parser.mul_const(y_2, generator);
+            let x_minus_g_y_2 = parser.sub(*x, g_y_2);
+            let x_prime = parser.sub_const(x_minus_g_y_2, generator.inverse());
+            let flystel_constraint = parser.sub(t_7, x_prime);
+            parser.constraint(flystel_constraint);
+        }
+    }
+}
+
+impl<F: Field> Instruction<F> for FlystelInstruction {
+    fn trace_layout(&self) -> Vec<MemorySlice> {
+        vec![*self.v.register(), *self.t_cube.register()]
+    }
+
+    fn inputs(&self) -> Vec<MemorySlice> {
+        vec![*self.x.register(), *self.y.register()]
+    }
+
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let x = writer.read_vec(&self.x, row_index);
+        let y = writer.read_vec(&self.y, row_index);
+        let generator = F::from_canonical_u64(self.generator);
+
+        let t = x
+            .iter()
+            .zip(y.iter())
+            .map(|(x, y)| (*x - generator * *y * *y - generator.inverse()).pow(self.inv_alpha))
+            .collect::<Vec<_>>();
+        let v = y
+            .iter()
+            .zip(t.iter())
+            .map(|(y, t)| *y - *t)
+            .collect::<Vec<_>>();
+        let t_cube = t.iter().map(|t| *t * *t * *t).collect::<Vec<_>>();
+        writer.write_array(&self.v, &v, row_index);
+        writer.write_array(&self.t_cube, &t_cube, row_index);
+    }
+
+    fn constraint_degree(&self) -> usize {
+        3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chip::builder::tests::*;
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub struct AnemoiTest;
+
+    impl AirParameters for AnemoiTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = FlystelInstruction;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 0;
+        const NUM_FREE_COLUMNS: usize = 355;
+
+        fn num_rows_bits() -> usize {
+            10
+        }
+    }
+
+    #[test]
+    fn test_anemoi_flystel() {
+        type F = GoldilocksField;
+        type P = GoldilocksAnemoi2;
+
+        let g = F::from_canonical_u64(P::GENERATOR);
+        for (x, y) in F::rand_vec(100).into_iter().zip(F::rand_vec(100)) {
+            let (u, v) = AnemoiGadget::<P>::flystel(x, y);
+
+            // The closed Flystel.
+            assert_eq!((y - v).pow(7), x - g * y * y - g.inverse());
+            assert_eq!(u, x - g * y * y - g.inverse() + g * v * v);
+
+            // Inverting the open Flystel recovers the input.
+            let x_prime = u - g * v * v;
+            let y_inv = v + x_prime.pow(P::INV_ALPHA);
+            let x_inv = x_prime + g * y_inv * y_inv + g.inverse();
+            assert_eq!((x_inv, y_inv), (x, y));
+        }
+    }
+
+    fn test_anemoi_permutation<P: AnemoiParameters>() {
+        type F = GoldilocksField;
+        type L = AnemoiTest;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let input = builder.alloc_array::<ElementRegister>(P::WIDTH);
+        let state = input.iter().map(|x| x.expr()).collect::<Vec<_>>();
+        let output = builder.anemoi_permutation::<P>(&state);
+
+        let (_, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let writer = generator.new_writer();
+        let input_values = F::rand_vec(P::WIDTH);
+        writer.write_array(&input, &input_values, 0);
+        writer.write_row_instructions(&generator.air_data, 0);
+
+        let expected = AnemoiGadget::<P>::permute(&input_values);
+        assert_eq!(writer.read_vec(&output, 0), expected);
+    }
+
+    #[test]
+    fn test_anemoi_permutation_width_2() {
+        test_anemoi_permutation::<GoldilocksAnemoi2>();
+    }
+
+    #[test]
+    fn test_anemoi_permutation_width_4() {
+        test_anemoi_permutation::<GoldilocksAnemoi4>();
+    }
+
+    /// Test vectors computed with an independent implementation of the permutation of the paper,
+    /// including the derivation of the round constants from the digits of pi.
+    #[test]
+    fn test_anemoi_permutation_vectors() {
+        type F = GoldilocksField;
+        // The largest canonical element, p - 1.
+        let max = 0xffff_ffff_0000_0000;
+
+        let vectors_2: [([u64; 2], [u64; 2]); 4] = [
+            ([0, 0], [7545990634645386590, 4719103426190805983]),
+            ([1, 1], [9707702494934692173, 9599302806636562591]),
+            ([0, 1], [12999580117694089006, 3325281217337322180]),
+            ([max, max], [3296881295489670107, 7592027220226917042]),
+        ];
+        for (input, output) in vectors_2 {
+            let input = input.map(F::from_canonical_u64);
+            let output = output.map(F::from_canonical_u64);
+            assert_eq!(AnemoiGadget::<GoldilocksAnemoi2>::permute(&input), output);
+        }
+
+        let vectors_4: [([u64; 4], [u64; 4]); 4] = [
+            (
+                [0, 0, 0, 0],
+                [
+                    12211729261115927961,
+                    7472832665624879529,
+                    9566490570050252402,
+                    16652260541958929540,
+                ],
+            ),
+            (
+                [1, 1, 1, 1],
+                [
+                    368293805039550990,
+                    11563838600966387312,
+                    18015323208571478493,
+                    16643348192445177938,
+                ],
+            ),
+            (
+                [0, 1, 2, 3],
+                [
+                    4422346397951879966,
+                    10462189976289320262,
+                    17688930836611178180,
+                    5135072929107803165,
+                ],
+            ),
+            (
+                [max, max, max, max],
+                [
+                    4079538805518919677,
+                    13831401576240320760,
+                    9371227393745069978,
+                    11930020914173320981,
+                ],
+            ),
+        ];
+        for (input, output) in vectors_4 {
+            let input = input.map(F::from_canonical_u64);
+            let output = output.map(F::from_canonical_u64);
+            assert_eq!(AnemoiGadget::<GoldilocksAnemoi4>::permute(&input), output);
+        }
+    }
+
+    #[test]
+    fn test_anemoi_round_constants() {
+        // With pi_0^0 = pi_1^0 = 1, the first constants are c = g + 2^7 and d = g + 2^7 + 1/g.
+        type F = GoldilocksField;
+        let g = F::from_canonical_u64(GoldilocksAnemoi2::GENERATOR);
+        let (c, d) = GoldilocksAnemoi2::round_constants(0, 0);
+        assert_eq!(c, 135);
+        assert_eq!(
+            F::from_canonical_u64(d),
+            g + F::from_canonical_u64(128) + g.inverse()
+        );
+    }
+
+    #[test]
+    fn test_anemoi_hash_n_to_m() {
+        type F = GoldilocksField;
+        type L = AnemoiTest;
+        type P = GoldilocksAnemoi4;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        // An input spanning three absorptions.
+        let input = builder.alloc_array::<ElementRegister>(5);
+        let gadget = builder.anemoi_hash_n_to_m::<P>(&input, 2);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let writer = generator.new_writer();
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            let input_values = F::rand_vec(5);
+            writer.write_array(&input, &input_values, i);
+            writer.write_row_instructions(&generator.air_data, i);
+
+            let expected = AnemoiGadget::<P>::hash(&input_values, 2);
+            assert_eq!(writer.read_vec(&gadget.output, i), expected);
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+}
@@ -0,0 +1,79 @@
+//! The round function shared by BLAKE2s and BLAKE3.
+//!
+//! Both hash functions mix a state of 16 words with eight applications of the same `G` function
+//! per round, on the four columns and then the four diagonals of the state. They differ in the
+//! number of rounds, in the order in which the message words are fed to the rounds, and in how a
+//! compression is initialized and finalized.
+
+use crate::chip::builder::AirBuilder;
+use crate::chip::uint::bytes::lookup_table::builder_operations::ByteLookupOperations;
+use crate::chip::uint::operations::instruction::U32Instructions;
+use crate::chip::uint::register::U32Register;
+use crate::chip::AirParameters;
+
+/// The state words mixed by the eight applications of `G` of a round, the four columns followed
+/// by the four diagonals. The `k`-th application mixes in the message words `2k` and `2k + 1`.
+pub const G_INDICES: [[usize; 4]; 8] = [
+    [0, 4, 8, 12],
+    [1, 5, 9, 13],
+    [2, 6, 10, 14],
+    [3, 7, 11, 15],
+    [0, 5, 10, 15],
+    [1, 6, 11, 12],
+    [2, 7, 8, 13],
+    [3, 4, 9, 14],
+];
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Applies a round to the 16 words of `state`, where `block` holds the message words in the
+    /// order of the round.
+    pub(crate) fn blake_round(
+        &mut self,
+        state: &[U32Register],
+        block: &[U32Register],
+        operations: &mut ByteLookupOperations,
+    ) -> Vec<U32Register>
+    where
+        L::Instruction: U32Instructions,
+    {
+        let mut state = state.to_vec();
+        for (k, [a, b, c, d]) in G_INDICES.into_iter().enumerate() {
+            let (x, y) = (&block[2 * k], &block[2 * k + 1]);
+
+            let sum = self.add_u32(&state[a], &state[b], operations);
+            state[a] = self.add_u32(&sum, x, operations);
+            let xor = self.bitwise_xor(&state[d], &state[a], operations);
+            state[d] = self.bit_rotate_right(&xor, 16, operations);
+            state[c] = self.add_u32(&state[c], &state[d], operations);
+            let xor = self.bitwise_xor(&state[b], &state[c], operations);
+            state[b] = self.bit_rotate_right(&xor, 12, operations);
+
+            let sum = self.add_u32(&state[a], &state[b], operations);
+            state[a] = self.add_u32(&sum, y, operations);
+            let xor = self.bitwise_xor(&state[d], &state[a], operations);
+            state[d] = self.bit_rotate_right(&xor, 8, operations);
+            state[c] = self.add_u32(&state[c], &state[d], operations);
+            let xor = self.bitwise_xor(&state[b], &state[c], operations);
+            state[b] = self.bit_rotate_right(&xor, 7, operations);
+        }
+        state
+    }
+}
+
+/// Applies a round to `state` natively, where `block` holds the message words in the order of
+/// the round.
+pub fn round(state: &mut [u32; 16], block: &[u32; 16]) {
+    for (k, [a, b, c, d]) in G_INDICES.into_iter().enumerate() {
+        let (x, y) = (block[2 * k], block[2 * k + 1]);
+
+        state[a] = state[a].wrapping_add(state[b]).wrapping_add(x);
+        state[d] = (state[d] ^ state[a]).rotate_right(16);
+        state[c] = state[c].wrapping_add(state[d]);
+        state[b] = (state[b] ^ state[c]).rotate_right(12);
+
+        state[a] = state[a].wrapping_add(state[b]).wrapping_add(y);
+        state[d] = (state[d] ^ state[a]).rotate_right(8);
+        state[c] = state[c].wrapping_add(state[d]);
+        state[b] = (state[b] ^ state[c]).rotate_right(7);
+    }
+}
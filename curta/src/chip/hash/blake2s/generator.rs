@@ -0,0 +1,281 @@
+use core::marker::PhantomData;
+
+use itertools::Itertools;
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::generator::{GeneratedValues, SimpleGenerator};
+use plonky2::iop::target::Target;
+use plonky2::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::circuit_data::CommonCircuitData;
+use plonky2::util::serialization::{Buffer, Read, Write};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    BLAKE2SGadget, BLAKE2SPublicData, BLAKE2S_BLOCK_LEN, BLAKE2S_CV_WORDS, BLAKE2S_CYCLE_LENGTH,
+};
+use crate::chip::trace::generator::ArithmeticGenerator;
+use crate::chip::uint::bytes::lookup_table::table::ByteLookupTable;
+use crate::chip::uint::operations::instruction::U32Instruction;
+use crate::chip::uint::util::u32_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::math::prelude::{CubicParameters, *};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct BLAKE2SAirParameters<F, E>(pub PhantomData<(F, E)>);
+
+/// The number of compressions of a single BLAKE2s proof.
+pub const BLAKE2S_NUM_COMPRESSIONS: usize = (1 << 16) / BLAKE2S_CYCLE_LENGTH;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct BLAKE2SGenerator<F: PrimeField64, E: CubicParameters<F>> {
+    pub gadget: BLAKE2SGadget,
+    pub table: ByteLookupTable,
+    pub messages: Vec<Target>,
+    pub message_lengths: Vec<usize>,
+    pub trace_generator: ArithmeticGenerator<BLAKE2SAirParameters<F, E>>,
+    pub pub_values_target: BLAKE2SPublicData<Target>,
+}
+
+impl<F: PrimeField64, E: CubicParameters<F>> AirParameters for BLAKE2SAirParameters<F, E> {
+    type Field = F;
+    type CubicParams = E;
+
+    type Instruction = U32Instruction;
+
+    const NUM_FREE_COLUMNS: usize = 1500;
+    const EXTENDED_COLUMNS: usize = 3700;
+    const NUM_ARITHMETIC_COLUMNS: usize = 0;
+
+    fn num_rows_bits() -> usize {
+        16
+    }
+}
+
+impl<F: RichField, E: CubicParameters<F>> BLAKE2SGenerator<F, E> {
+    pub fn id() -> String {
+        "BLAKE2SGenerator".to_string()
+    }
+}
+
+impl<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize> SimpleGenerator<F, D>
+    for BLAKE2SGenerator<F, E>
+{
+    fn id(&self) -> String {
+        Self::id()
+    }
+
+    fn serialize(
+        &self,
+        dst: &mut Vec<u8>,
+        _: &CommonCircuitData<F, D>,
+    ) -> plonky2::util::serialization::IoResult<()> {
+        let data = bincode::serialize(self).unwrap();
+        dst.write_bytes(&data)
+    }
+
+    fn deserialize(
+        src: &mut Buffer,
+        _: &CommonCircuitData<F, D>,
+    ) -> plonky2::util::serialization::IoResult<Self>
+    where
+        Self: Sized,
+    {
+        let bytes = src.read_bytes()?;
+        let data = bincode::deserialize(&bytes).unwrap();
+        Ok(data)
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        self.messages.clone()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let messages = self
+            .messages
+            .iter()
+            .map(|x| witness.get_target(*x).as_canonical_u64() as u8)
+            .collect::<Vec<_>>();
+
+        let messages = self.message_lengths.iter().scan(0, |idx, len| {
+            let message = messages[*idx..*idx + len].to_vec();
+            *idx += len;
+            Some(message)
+        });
+
+        // Write trace values
+        let writer = self.trace_generator.new_writer();
+        self.table.write_table_entries(&writer);
+        let blake2s_public_values = self.gadget.write(messages, &writer);
+        for i in 0..BLAKE2SAirParameters::<F, E>::num_rows() {
+            writer.write_row_instructions(&self.trace_generator.air_data, i);
+        }
+        self.table.write_multiplicities(&writer);
+
+        // Fill the chaining values into the output buffer
+        self.pub_values_target
+            .set_targets(blake2s_public_values, out_buffer);
+    }
+}
+
+impl BLAKE2SPublicData<Target> {
+    /// Allocates the public data targets for a batch of messages, where the `i`-th message is
+    /// given by the next `message_lengths[i]` bytes of `messages` and its digest by the `i`-th 32
+    /// bytes of `digests`.
+    ///
+    /// The message lengths are fixed by the circuit, so the block words, the counters and the last
+    /// block bits are given by the message targets and by constants. The chaining values after the
+    /// blocks of a message other than the last one are allocated as virtual targets.
+    pub fn add_virtual<F: RichField + Extendable<D>, const D: usize>(
+        builder: &mut CircuitBuilder<F, D>,
+        messages: &[Target],
+        message_lengths: &[usize],
+        digests: &[Target],
+    ) -> Self {
+        assert_eq!(
+            message_lengths.iter().sum::<usize>(),
+            messages.len(),
+            "Message lengths must add up to the number of message targets"
+        );
+
+        let mut public_block_targets: Vec<[Target; 4]> = Vec::new();
+        let mut counter_targets = Vec::new();
+        let mut last_bits_targets = Vec::new();
+        let mut hash_state_targets = Vec::new();
+
+        let mut message_index = 0;
+        for (len, digest) in message_lengths.iter().zip_eq(digests.chunks_exact(32)) {
+            let message = &messages[message_index..message_index + len];
+            message_index += len;
+
+            let num_blocks = core::cmp::max(1, len.div_ceil(BLAKE2S_BLOCK_LEN));
+            for i in 0..num_blocks {
+                // The last block is padded with zeros
+                public_block_targets.extend((0..16).map(|j| {
+                    core::array::from_fn(|k| {
+                        message
+                            .get(BLAKE2S_BLOCK_LEN * i + 4 * j + k)
+                            .copied()
+                            .unwrap_or_else(|| builder.zero())
+                    })
+                }));
+
+                let counter = core::cmp::min(BLAKE2S_BLOCK_LEN * (i + 1), *len) as u64;
+                counter_targets.extend(
+                    [counter as u32, (counter >> 32) as u32]
+                        .map(|word| u32_to_le_field_bytes(word).map(|x| builder.constant(x))),
+                );
+
+                let last = i == num_blocks - 1;
+                last_bits_targets.push(builder.constant_bool(last).target);
+
+                match last {
+                    true => hash_state_targets.extend(
+                        digest
+                            .chunks_exact(4)
+                            .map(|word| -> [Target; 4] { word.try_into().unwrap() }),
+                    ),
+                    false => hash_state_targets.extend(
+                        (0..BLAKE2S_CV_WORDS).map(|_| builder.add_virtual_target_arr::<4>()),
+                    ),
+                }
+            }
+        }
+        assert_eq!(
+            last_bits_targets.len(),
+            BLAKE2S_NUM_COMPRESSIONS,
+            "The messages must fill the {} compressions of the proof",
+            BLAKE2S_NUM_COMPRESSIONS
+        );
+
+        BLAKE2SPublicData {
+            public_block: public_block_targets,
+            counter: counter_targets,
+            last_bits: last_bits_targets,
+            hash_state: hash_state_targets,
+        }
+    }
+
+    /// Sets the chaining values, which are the only targets of the public data that are not
+    /// determined by the messages.
+    pub fn set_targets<F: RichField>(
+        &self,
+        values: BLAKE2SPublicData<F>,
+        out_buffer: &mut GeneratedValues<F>,
+    ) {
+        for (hash_target, hash_value) in self.hash_state.iter().zip_eq(values.hash_state.iter()) {
+            out_buffer.set_target_arr(hash_target, hash_value);
+        }
+    }
+
+    pub fn public_input_targets(&self) -> Vec<Target> {
+        self.public_block
+            .iter()
+            .flatten()
+            .chain(self.counter.iter().flatten())
+            .chain(self.last_bits.iter())
+            .chain(self.hash_state.iter().flatten())
+            .copied()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+
+    use super::*;
+
+    #[test]
+    fn test_blake2s_public_data_layout() {
+        type F = GoldilocksField;
+        const D: usize = 2;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        // A message of three blocks, an empty message and messages of a single full block.
+        let mut message_lengths = vec![130, 0];
+        message_lengths.extend([64; BLAKE2S_NUM_COMPRESSIONS - 4]);
+        let messages = builder.add_virtual_targets(message_lengths.iter().sum());
+        let digests = builder.add_virtual_targets(32 * message_lengths.len());
+
+        let public_data =
+            BLAKE2SPublicData::add_virtual(&mut builder, &messages, &message_lengths, &digests);
+
+        assert_eq!(
+            public_data.public_block.len(),
+            16 * BLAKE2S_NUM_COMPRESSIONS
+        );
+        assert_eq!(public_data.last_bits.len(), BLAKE2S_NUM_COMPRESSIONS);
+
+        // The last block of the first message holds two message bytes followed by zeros.
+        let zero = builder.zero();
+        assert_eq!(public_data.public_block[32][..2], messages[128..130]);
+        assert_eq!(public_data.public_block[32][2..], [zero, zero]);
+        assert!(public_data.public_block[33..48]
+            .iter()
+            .all(|word| *word == [zero; 4]));
+
+        // The counters are the number of bytes up to the end of every block.
+        let counter = |value: u32| u32_to_le_field_bytes(value).map(|x| builder.constant(x));
+        assert_eq!(public_data.counter[0], counter(64));
+        assert_eq!(public_data.counter[2], counter(128));
+        assert_eq!(public_data.counter[4], counter(130));
+        assert_eq!(public_data.counter[6], counter(0));
+
+        // The digests are the chaining values of the last blocks.
+        let one = builder.one();
+        assert_eq!(public_data.last_bits[..4], [zero, zero, one, one]);
+        assert_eq!(public_data.hash_state[16..24].concat(), digests[..32]);
+        assert_eq!(public_data.hash_state[24..32].concat(), digests[32..64]);
+
+        let public_inputs = public_data.public_input_targets();
+        assert_eq!(
+            public_inputs.len(),
+            (4 * (16 + 2 + BLAKE2S_CV_WORDS) + 1) * BLAKE2S_NUM_COMPRESSIONS
+        );
+    }
+}
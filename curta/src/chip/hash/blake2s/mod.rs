@@ -0,0 +1,618 @@
+//! BLAKE2s-256 over 32-bit words.
+//!
+//! A message is split into blocks of 64 bytes, the last of which is padded with zeros, and the
+//! blocks are compressed in sequence starting from the IV mixed with the parameter block of an
+//! unkeyed 32-byte digest. Each compression also takes the number of bytes hashed so far and a
+//! flag marking the last block.
+//!
+//! Every compression occupies a cycle of 16 rows, with the ten rounds on the first ten rows and
+//! the remaining rows carrying the state to the end of the cycle, where the chaining value is
+//! published. The counter and the last block flag are read from the bus at the first row of the
+//! cycle. Unlike BLAKE3, the message schedule of BLAKE2s is not the power of a single permutation,
+//! so every round row reads the message words in the order of its round from the bus.
+
+pub mod generator;
+
+use core::array::from_fn;
+use core::borrow::Borrow;
+
+use serde::{Deserialize, Serialize};
+
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::builder::AirBuilder;
+use crate::chip::hash::blake;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::cubic::CubicRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::{Register, RegisterSerializable, RegisterSized};
+use crate::chip::table::bus::global::Bus;
+use crate::chip::trace::writer::TraceWriter;
+use crate::chip::uint::bytes::lookup_table::builder_operations::ByteLookupOperations;
+use crate::chip::uint::bytes::operations::value::ByteOperation;
+use crate::chip::uint::bytes::register::ByteRegister;
+use crate::chip::uint::operations::instruction::U32Instructions;
+use crate::chip::uint::register::U32Register;
+use crate::chip::uint::util::u32_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+
+pub type U32Value<T> = <U32Register as Register>::Value<T>;
+
+/// The number of rows used to process a single compression.
+pub const BLAKE2S_CYCLE_LENGTH: usize = 16;
+
+/// The number of rounds of the compression function.
+pub const BLAKE2S_NUM_ROUNDS: usize = 10;
+
+/// The number of bytes of a block.
+pub const BLAKE2S_BLOCK_LEN: usize = 64;
+
+/// The number of words of a chaining value, which is also the digest.
+pub const BLAKE2S_CV_WORDS: usize = 8;
+
+const IV: [u32; 8] = [
+    0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A, 0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19,
+];
+
+/// The first word of the parameter block of an unkeyed hash with a 32-byte digest, i.e. a digest
+/// length of 32, a key length of 0, a fanout of 1 and a depth of 1.
+const PARAMETER_WORD: u32 = 0x01010020;
+
+/// The order of the message words in every round.
+const SIGMA: [[usize; 16]; BLAKE2S_NUM_ROUNDS] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BLAKE2SGadget {
+    /// The message words of every compression
+    pub public_block: ArrayRegister<U32Register>,
+    /// The low and high words of the byte counter of every compression
+    pub public_counter: ArrayRegister<U32Register>,
+    /// The bit marking the last block of a message for every compression
+    pub public_last_bits: ArrayRegister<BitRegister>,
+    /// The chaining value output by every compression, which is the digest at the last block
+    pub state: ArrayRegister<U32Register>,
+    /// Signifies the first row of a compression
+    pub load_bit: BitRegister,
+    /// Signifies the rows that perform a round
+    pub round_bit: BitRegister,
+    pub(crate) block: ArrayRegister<U32Register>,
+    pub(crate) counter: ArrayRegister<U32Register>,
+    pub(crate) last_bit: BitRegister,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BLAKE2SPublicData<T> {
+    pub public_block: Vec<U32Value<T>>,
+    pub counter: Vec<U32Value<T>>,
+    pub last_bits: Vec<T>,
+    pub hash_state: Vec<U32Value<T>>,
+}
+
+/// A call to the compression function on a block of a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BLAKE2SCompression {
+    pub chaining_value: [u32; 8],
+    pub block: [u32; 16],
+    /// The number of bytes of the message up to the end of the block
+    pub counter: u64,
+    pub last: bool,
+}
+
+impl BLAKE2SCompression {
+    pub fn output(&self) -> [u32; 8] {
+        BLAKE2SGadget::compress(&self.chaining_value, &self.block, self.counter, self.last)
+    }
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    pub fn process_blake2s_batch(
+        &mut self,
+        clk: &ElementRegister,
+        bus: &mut Bus<L::CubicParams>,
+        bus_channel_idx: usize,
+        operations: &mut ByteLookupOperations,
+    ) -> BLAKE2SGadget
+    where
+        L::Instruction: U32Instructions,
+    {
+        assert_eq!(
+            L::num_rows() % BLAKE2S_CYCLE_LENGTH,
+            0,
+            "The number of rows must be a multiple of the cycle length"
+        );
+        let num_compressions = L::num_rows() / BLAKE2S_CYCLE_LENGTH;
+
+        // Registers to be written to. The load bit and the inputs of a compression are written
+        // ahead of the row instructions so that the transition into a new compression can read
+        // them.
+        let load_bit = self.alloc::<BitRegister>();
+        let round_bit = self.alloc::<BitRegister>();
+        let block = self.alloc_array::<U32Register>(16);
+        let counter = self.alloc_array::<U32Register>(2);
+        let last_bit = self.alloc::<BitRegister>();
+        let cycle_16 = self.cycle(4);
+        self.assert_equal(&load_bit, &cycle_16.start_bit);
+
+        // Public values
+        let public_block = self.alloc_array_public::<U32Register>(16 * num_compressions);
+        let public_counter = self.alloc_array_public::<U32Register>(2 * num_compressions);
+        let public_last_bits = self.alloc_array_public::<BitRegister>(num_compressions);
+        let hash_state =
+            self.alloc_array_public::<U32Register>(BLAKE2S_CV_WORDS * num_compressions);
+
+        let one = ArithmeticExpression::<L::Field>::one();
+        let load_next = load_bit.next().expr::<L::Field>();
+
+        // Get the counter and the last block bit from the bus at the start of every cycle. The
+        // counter is zero elsewhere, so that it is only mixed into the state by the first round.
+        let input_challenges =
+            self.alloc_challenge_array::<CubicRegister>(1 + U32Register::size_of() * 2 + 1);
+        let clk_input = self.accumulate_expressions(
+            &input_challenges,
+            &[clk.expr(), counter.expr(), last_bit.expr()],
+        );
+        self.output_from_bus_filtered(bus_channel_idx, clk_input, load_bit.expr());
+        for word in counter.iter() {
+            self.assert_expression_zero(word.expr() * load_bit.not_expr());
+        }
+
+        // Get the message words in the order of the round at every round row. A round bit which
+        // does not match the rounds of the cycle leaves the bus unbalanced.
+        let block_challenges =
+            self.alloc_challenge_array::<CubicRegister>(1 + U32Register::size_of() * 16);
+        let clk_block = self.accumulate_expressions(&block_challenges, &[clk.expr(), block.expr()]);
+        self.output_from_bus_filtered(bus_channel_idx, clk_block, round_bit.expr());
+
+        // Copy the last block bit to the rest of the cycle
+        self.set_to_expression_transition(
+            &last_bit.next(),
+            load_next.clone() * last_bit.next().expr()
+                + (one.clone() - load_next.clone()) * last_bit.expr(),
+        );
+
+        let word_expression = |word: u32| {
+            ArithmeticExpression::from_constant_vec(
+                u32_to_le_field_bytes::<L::Field>(word).to_vec(),
+            )
+        };
+        let initial_hash: [ArithmeticExpression<L::Field>; 8] = from_fn(|i| match i {
+            0 => word_expression(IV[0] ^ PARAMETER_WORD),
+            _ => word_expression(IV[i]),
+        });
+
+        // The state words after the chaining value, where the counter is mixed in by the first
+        // round and the word 14 is inverted for the last block
+        let initial_words = |last: ArithmeticExpression<L::Field>| {
+            IV[..6]
+                .iter()
+                .map(|word| word_expression(*word))
+                .chain([
+                    last.clone() * word_expression(!IV[6])
+                        + (one.clone() - last) * word_expression(IV[6]),
+                    word_expression(IV[7]),
+                ])
+                .collect::<Vec<_>>()
+        };
+
+        // The first compression starts from the initial hash
+        let h = self.alloc_array::<U32Register>(BLAKE2S_CV_WORDS);
+        let state = self.alloc_array::<U32Register>(16);
+        for (word, value) in h.iter().zip(initial_hash.iter()) {
+            self.set_to_expression_first_row(&word, value.clone());
+        }
+        for (word, value) in state.iter().zip(
+            initial_hash
+                .iter()
+                .cloned()
+                .chain(initial_words(last_bit.expr())),
+        ) {
+            self.set_to_expression_first_row(&word, value);
+        }
+
+        // Mix in the counter and apply a round
+        let mut input = state.iter().collect::<Vec<_>>();
+        for (k, word) in counter.iter().enumerate() {
+            input[12 + k] = self.bitwise_xor(&input[12 + k], &word, operations);
+        }
+        let block_words = block.iter().collect::<Vec<_>>();
+        let output = self.blake_round(&input, &block_words, operations);
+
+        // The output chaining value
+        let chaining_value = self.alloc_array::<U32Register>(BLAKE2S_CV_WORDS);
+        for (i, word) in chaining_value.iter().enumerate() {
+            let xor = self.bitwise_xor(&state.get(i), &state.get(i + 8), operations);
+            self.set_bitwise_xor(&h.get(i), &xor, &word, operations);
+        }
+
+        // Assign the next state: the round output in the round rows, the current state in the
+        // remaining rows, and the initial state of the next compression at the end of a cycle,
+        // whose chaining value is the initial hash after the last block of a message and the
+        // current chaining value otherwise.
+        let continue_next = last_bit.not_expr();
+        let initial_next = initial_words(last_bit.next().expr());
+        for i in 0..BLAKE2S_CV_WORDS {
+            let initial = continue_next.clone() * chaining_value.get(i).expr()
+                + (one.clone() - continue_next.clone()) * initial_hash[i].clone();
+            self.set_to_expression_transition(
+                &h.get(i).next(),
+                load_next.clone() * initial.clone()
+                    + (one.clone() - load_next.clone()) * h.get(i).expr(),
+            );
+            self.set_to_expression_transition(
+                &state.get(i).next(),
+                load_next.clone() * initial
+                    + (one.clone() - load_next.clone())
+                        * (round_bit.expr() * output[i].expr()
+                            + round_bit.not_expr() * state.get(i).expr()),
+            );
+        }
+        for i in BLAKE2S_CV_WORDS..16 {
+            self.set_to_expression_transition(
+                &state.get(i).next(),
+                load_next.clone() * initial_next[i - 8].clone()
+                    + (one.clone() - load_next.clone())
+                        * (round_bit.expr() * output[i].expr()
+                            + round_bit.not_expr() * state.get(i).expr()),
+            );
+        }
+
+        // Put the chaining value of every compression in the bus at the end of its cycle
+        let state_challenges = self
+            .alloc_challenge_array::<CubicRegister>(U32Register::size_of() * BLAKE2S_CV_WORDS + 1);
+        let clk_state =
+            self.accumulate_expressions(&state_challenges, &[clk.expr(), chaining_value.expr()]);
+        self.input_to_bus_filtered(bus_channel_idx, clk_state, cycle_16.end_bit.expr());
+
+        // Put the public inputs and chaining values in the bus
+        for i in 0..num_compressions {
+            let clk_start = L::Field::from_canonical_usize(i * BLAKE2S_CYCLE_LENGTH);
+            let input_digest = self.accumulate_public_expressions(
+                &input_challenges,
+                &[
+                    ArithmeticExpression::from_constant(clk_start),
+                    public_counter.get_subarray(2 * i..2 * (i + 1)).expr(),
+                    public_last_bits.get(i).expr(),
+                ],
+            );
+            bus.insert_global_value(&input_digest);
+
+            for (r, sigma) in SIGMA.iter().enumerate() {
+                let clk_round = clk_start + L::Field::from_canonical_usize(r);
+                let values = [ArithmeticExpression::from_constant(clk_round)]
+                    .into_iter()
+                    .chain(sigma.iter().map(|j| public_block.get(16 * i + j).expr()))
+                    .collect::<Vec<_>>();
+                let block_digest = self.accumulate_public_expressions(&block_challenges, &values);
+                bus.insert_global_value(&block_digest);
+            }
+
+            let clk_end = clk_start + L::Field::from_canonical_usize(BLAKE2S_CYCLE_LENGTH - 1);
+            let state_digest = self.accumulate_public_expressions(
+                &state_challenges,
+                &[
+                    ArithmeticExpression::from_constant(clk_end),
+                    hash_state
+                        .get_subarray(BLAKE2S_CV_WORDS * i..BLAKE2S_CV_WORDS * (i + 1))
+                        .expr(),
+                ],
+            );
+            bus.output_global_value(&state_digest);
+        }
+
+        // The byte lookup needs an even number of operations
+        if operations.values.len() % 2 == 1 {
+            let dummy = self.alloc::<ByteRegister>();
+            let dummy_range = ByteOperation::Range(dummy);
+            self.set_byte_operation(&dummy_range, operations);
+        }
+
+        BLAKE2SGadget {
+            public_block,
+            public_counter,
+            public_last_bits,
+            state: hash_state,
+            load_bit,
+            round_bit,
+            block,
+            counter,
+            last_bit,
+        }
+    }
+}
+
+impl BLAKE2SGadget {
+    /// Writes the compressions of `messages`, which must fill all the cycles of the trace.
+    pub fn write<F: Field, I: IntoIterator>(
+        &self,
+        messages: I,
+        writer: &TraceWriter<F>,
+    ) -> BLAKE2SPublicData<F>
+    where
+        I::Item: Borrow<[u8]>,
+    {
+        let compressions = messages
+            .into_iter()
+            .flat_map(|message| Self::compressions(message.borrow()))
+            .collect::<Vec<_>>();
+        let num_compressions = self.public_last_bits.len();
+        assert!(
+            compressions.len() == num_compressions,
+            "Message compressions do not add up"
+        );
+
+        let mut public_block = Vec::new();
+        let mut counter_values = Vec::new();
+        let mut last_bits_values = Vec::new();
+        let mut hash_values = Vec::new();
+        for (i, compression) in compressions.iter().enumerate() {
+            let counter = [
+                compression.counter as u32,
+                (compression.counter >> 32) as u32,
+            ]
+            .map(u32_to_le_field_bytes::<F>);
+            let last_bit = F::from_canonical_u8(compression.last as u8);
+
+            let row = i * BLAKE2S_CYCLE_LENGTH;
+            for j in 0..BLAKE2S_CYCLE_LENGTH {
+                writer.write(
+                    &self.load_bit,
+                    &F::from_canonical_u8((j == 0) as u8),
+                    row + j,
+                );
+                writer.write(
+                    &self.round_bit,
+                    &F::from_canonical_u8((j < BLAKE2S_NUM_ROUNDS) as u8),
+                    row + j,
+                );
+            }
+            for (r, sigma) in SIGMA.iter().enumerate() {
+                let words = sigma.map(|j| u32_to_le_field_bytes::<F>(compression.block[j]));
+                writer.write_array(&self.block, words, row + r);
+            }
+            writer.write_array(&self.counter, counter, row);
+            writer.write(&self.last_bit, &last_bit, row);
+
+            public_block.extend(compression.block.map(u32_to_le_field_bytes::<F>));
+            counter_values.extend(counter);
+            last_bits_values.push(last_bit);
+            hash_values.extend(compression.output().map(u32_to_le_field_bytes::<F>));
+        }
+
+        writer.write_array(&self.public_block, &public_block, 0);
+        writer.write_array(&self.public_counter, &counter_values, 0);
+        writer.write_array(&self.public_last_bits, &last_bits_values, 0);
+        writer.write_array(&self.state, &hash_values, 0);
+
+        BLAKE2SPublicData {
+            public_block,
+            counter: counter_values,
+            last_bits: last_bits_values,
+            hash_state: hash_values,
+        }
+    }
+
+    /// The compressions of the blocks of `msg`, in order. The empty message has a single block.
+    pub fn compressions(msg: &[u8]) -> Vec<BLAKE2SCompression> {
+        let blocks = match msg.is_empty() {
+            true => vec![msg],
+            false => msg.chunks(BLAKE2S_BLOCK_LEN).collect(),
+        };
+        let num_blocks = blocks.len();
+
+        let mut chaining_value = Self::initial_hash();
+        let mut counter = 0;
+        blocks
+            .into_iter()
+            .enumerate()
+            .map(|(i, block)| {
+                counter += block.len() as u64;
+                let compression = BLAKE2SCompression {
+                    chaining_value,
+                    block: Self::block_words(block),
+                    counter,
+                    last: i == num_blocks - 1,
+                };
+                chaining_value = compression.output();
+                compression
+            })
+            .collect()
+    }
+
+    /// The chaining value of the first block of a message.
+    pub fn initial_hash() -> [u32; 8] {
+        let mut initial_hash = IV;
+        initial_hash[0] ^= PARAMETER_WORD;
+        initial_hash
+    }
+
+    /// The little endian words of a block, padded with zeros.
+    pub fn block_words(block: &[u8]) -> [u32; 16] {
+        from_fn(|i| {
+            let mut word = [0u8; 4];
+            for (byte, value) in word.iter_mut().zip(block.iter().skip(4 * i)) {
+                *byte = *value;
+            }
+            u32::from_le_bytes(word)
+        })
+    }
+
+    /// The BLAKE2s compression function.
+    pub fn compress(
+        chaining_value: &[u32; 8],
+        block: &[u32; 16],
+        counter: u64,
+        last: bool,
+    ) -> [u32; 8] {
+        let mut state: [u32; 16] = from_fn(|i| match i {
+            0..=7 => chaining_value[i],
+            12 => IV[4] ^ counter as u32,
+            13 => IV[5] ^ (counter >> 32) as u32,
+            14 if last => !IV[6],
+            _ => IV[i - 8],
+        });
+        for sigma in SIGMA.iter() {
+            blake::round(&mut state, &sigma.map(|j| block[j]));
+        }
+        from_fn(|i| chaining_value[i] ^ state[i] ^ state[i + 8])
+    }
+
+    /// Computes the BLAKE2s-256 digest of `msg` natively.
+    pub fn hash(msg: &[u8]) -> [u8; 32] {
+        let last = Self::compressions(msg).pop().unwrap();
+        last.output()
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::timed;
+    use plonky2::util::timing::TimingTree;
+
+    use super::*;
+    pub use crate::chip::builder::tests::*;
+    use crate::chip::builder::AirBuilder;
+    use crate::chip::uint::operations::instruction::U32Instruction;
+    use crate::chip::AirParameters;
+
+    const EMPTY_DIGEST: &str = "69217a3079908094e11121d042354a7c1f55b6482ca1a51e1b250dfd1ed0eef9";
+    const ABC_DIGEST: &str = "508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982";
+    /// The digest of the 200 bytes `0, 1, ..., 199`, which span four blocks.
+    const LONG_DIGEST: &str = "6d244e1a06ce4ef578dd0f63aff0936706735119ca9c8d22d86c801414ab9741";
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub struct BLAKE2STest;
+
+    impl AirParameters for BLAKE2STest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = U32Instruction;
+
+        const NUM_FREE_COLUMNS: usize = 1500;
+        const EXTENDED_COLUMNS: usize = 3700;
+        const NUM_ARITHMETIC_COLUMNS: usize = 0;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    #[test]
+    fn test_blake2s_native_hash() {
+        assert_eq!(hex::encode(BLAKE2SGadget::hash(b"")), EMPTY_DIGEST);
+        assert_eq!(hex::encode(BLAKE2SGadget::hash(b"abc")), ABC_DIGEST);
+        let long_msg = (0..200).map(|i| i as u8).collect::<Vec<_>>();
+        assert_eq!(hex::encode(BLAKE2SGadget::hash(&long_msg)), LONG_DIGEST);
+
+        // The counter of the last block is the length of the message, and a full last block is
+        // not followed by an empty one.
+        let compressions = BLAKE2SGadget::compressions(&long_msg);
+        assert_eq!(compressions.len(), 4);
+        assert_eq!(compressions[3].counter, 200);
+        assert!(compressions[3].last);
+        assert!(!compressions[2].last);
+        assert_eq!(BLAKE2SGadget::compressions(&[0u8; 128]).len(), 2);
+    }
+
+    #[test]
+    fn test_blake2s_stark() {
+        type F = GoldilocksField;
+        type L = BLAKE2STest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut timing = TimingTree::new("Blake2s test", log::Level::Debug);
+
+        let mut builder = AirBuilder::<L>::new();
+        let clk = builder.clock();
+
+        let (mut operations, table) = builder.byte_operations();
+
+        let mut bus = builder.new_bus();
+        let channel_idx = bus.new_channel(&mut builder);
+
+        let blake2s_gadget =
+            builder.process_blake2s_batch(&clk, &mut bus, channel_idx, &mut operations);
+
+        builder.register_byte_lookup(operations, &table);
+        builder.constrain_bus(bus);
+
+        let (air, trace_data) = builder.build();
+
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+        let writer = generator.new_writer();
+
+        // A message of four blocks followed by single block messages to fill the trace.
+        let mut messages = vec![(0..200).map(|i| i as u8).collect::<Vec<_>>()];
+        let mut expected_digests = vec![LONG_DIGEST];
+        for i in 0..L::num_rows() / BLAKE2S_CYCLE_LENGTH - 4 {
+            messages.push([b"".to_vec(), b"abc".to_vec()][i % 2].clone());
+            expected_digests.push([EMPTY_DIGEST, ABC_DIGEST][i % 2]);
+        }
+
+        timed!(timing, "Write the execution trace", {
+            table.write_table_entries(&writer);
+            blake2s_gadget.write(messages.iter().map(|m| m.as_slice()), &writer);
+            for i in 0..L::num_rows() {
+                writer.write_row_instructions(&generator.air_data, i);
+            }
+            table.write_multiplicities(&writer);
+        });
+
+        // The digest is the chaining value of the last compression of every message
+        let mut last = 0;
+        for (message, digest) in messages.iter().zip(expected_digests) {
+            last += BLAKE2SGadget::compressions(message).len();
+            let hash = writer.read_array(
+                &blake2s_gadget
+                    .state
+                    .get_subarray(BLAKE2S_CV_WORDS * (last - 1)..BLAKE2S_CV_WORDS * last),
+                0,
+            );
+            let expected = hex::decode(digest)
+                .unwrap()
+                .chunks_exact(4)
+                .map(|x| u32_to_le_field_bytes::<F>(u32::from_le_bytes(x.try_into().unwrap())))
+                .collect::<Vec<_>>();
+            assert_eq!(hash, expected);
+        }
+
+        let public_inputs = writer.0.public.read().unwrap().clone();
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        timed!(
+            timing,
+            "Stark proof and verify",
+            test_starky(&stark, &config, &generator, &public_inputs)
+        );
+
+        // Generate recursive proof
+        timed!(
+            timing,
+            "Recursive proof generation and verification",
+            test_recursive_starky(stark, config, generator, &public_inputs)
+        );
+
+        timing.print();
+    }
+}
@@ -0,0 +1,820 @@
+//! BLAKE3 with the chunk and parent compressions of its hash tree.
+//!
+//! A message is split into chunks of 1024 bytes and every chunk into blocks of 64 bytes. The
+//! blocks of a chunk are compressed in sequence, each block starting from the chaining value of
+//! the previous one, and the chaining values of the chunks are then merged pairwise by parent
+//! compressions into a binary tree whose root is the digest. The compressions are separated by
+//! the `CHUNK_START`, `CHUNK_END`, `PARENT` and `ROOT` flags.
+//!
+//! Every compression occupies a cycle of 8 rows, with the seven rounds on the first seven rows
+//! and the output at the last row. The message block, the counter, the block length, the flags
+//! and, for a parent, the keys of its two children are read from the bus at the first row of the
+//! cycle. Within a chunk, the chaining value is carried from one cycle to the next. The output of
+//! a chunk that is merged into a parent is put in the bus under the clock of its last row, and the
+//! parent takes the two halves of its block from the bus under the keys of its children.
+
+use core::array::from_fn;
+use core::borrow::Borrow;
+
+use serde::{Deserialize, Serialize};
+
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::builder::AirBuilder;
+use crate::chip::hash::blake;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::cubic::CubicRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::{Register, RegisterSerializable, RegisterSized};
+use crate::chip::table::bus::global::Bus;
+use crate::chip::trace::writer::TraceWriter;
+use crate::chip::uint::bytes::lookup_table::builder_operations::ByteLookupOperations;
+use crate::chip::uint::bytes::operations::value::ByteOperation;
+use crate::chip::uint::bytes::register::ByteRegister;
+use crate::chip::uint::operations::instruction::U32Instructions;
+use crate::chip::uint::register::U32Register;
+use crate::chip::uint::util::u32_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+
+pub type U32Value<T> = <U32Register as Register>::Value<T>;
+
+/// The number of rows used to process a single compression.
+pub const BLAKE3_CYCLE_LENGTH: usize = 8;
+
+/// The number of rounds of the compression function.
+pub const BLAKE3_NUM_ROUNDS: usize = 7;
+
+/// The number of bytes of a block.
+pub const BLAKE3_BLOCK_LEN: usize = 64;
+
+/// The number of bytes of a chunk.
+pub const BLAKE3_CHUNK_LEN: usize = 1024;
+
+/// The number of words of a chaining value, which is also the digest.
+pub const BLAKE3_CV_WORDS: usize = 8;
+
+pub const CHUNK_START: u32 = 1 << 0;
+pub const CHUNK_END: u32 = 1 << 1;
+pub const PARENT: u32 = 1 << 2;
+pub const ROOT: u32 = 1 << 3;
+
+/// The number of domain separation flags, which are read from the bus as bits.
+const NUM_FLAGS: usize = 4;
+
+const IV: [u32; 8] = [
+    0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A, 0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19,
+];
+
+const MSG_PERMUTATION: [usize; 16] = [2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Blake3Gadget {
+    /// The message words of every compression, which are the chaining values of the two children
+    /// for a parent compression
+    pub public_block: ArrayRegister<U32Register>,
+    /// The low and high words of the chunk counter of every compression
+    pub public_counter: ArrayRegister<U32Register>,
+    pub public_block_len: ArrayRegister<U32Register>,
+    /// The `CHUNK_START`, `CHUNK_END`, `PARENT` and `ROOT` bits of every compression
+    pub public_flags: ArrayRegister<BitRegister>,
+    /// The keys of the two children of every parent compression, and zero for the others
+    pub public_children: ArrayRegister<ElementRegister>,
+    /// The chaining value output by every compression, which is the digest at the root
+    pub state: ArrayRegister<U32Register>,
+    /// Signifies the first row of a compression
+    pub load_bit: BitRegister,
+    pub(crate) block: ArrayRegister<U32Register>,
+    pub(crate) counter: ArrayRegister<U32Register>,
+    pub(crate) block_len: U32Register,
+    pub(crate) flags: ArrayRegister<BitRegister>,
+    pub(crate) children: ArrayRegister<ElementRegister>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Blake3PublicData<T> {
+    pub public_block: Vec<U32Value<T>>,
+    pub counter: Vec<U32Value<T>>,
+    pub block_len: Vec<U32Value<T>>,
+    pub flags: Vec<T>,
+    pub children: Vec<T>,
+    pub hash_state: Vec<U32Value<T>>,
+}
+
+/// A call to the compression function in the hash tree of a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Blake3Compression {
+    pub chaining_value: [u32; 8],
+    pub block: [u32; 16],
+    pub counter: u64,
+    pub block_len: u32,
+    pub flags: u32,
+    /// The indices of the compressions whose chaining values form the block of a parent
+    pub children: Option<(usize, usize)>,
+}
+
+impl Blake3Compression {
+    pub fn output(&self) -> [u32; 16] {
+        Blake3Gadget::compress(
+            &self.chaining_value,
+            &self.block,
+            self.counter,
+            self.block_len,
+            self.flags,
+        )
+    }
+
+    pub fn output_chaining_value(&self) -> [u32; 8] {
+        let output = self.output();
+        from_fn(|i| output[i])
+    }
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    pub fn process_blake3_batch(
+        &mut self,
+        clk: &ElementRegister,
+        bus: &mut Bus<L::CubicParams>,
+        bus_channel_idx: usize,
+        operations: &mut ByteLookupOperations,
+    ) -> Blake3Gadget
+    where
+        L::Instruction: U32Instructions,
+    {
+        assert_eq!(
+            L::num_rows() % BLAKE3_CYCLE_LENGTH,
+            0,
+            "The number of rows must be a multiple of the cycle length"
+        );
+        let num_compressions = L::num_rows() / BLAKE3_CYCLE_LENGTH;
+
+        // Registers to be written to. The load bit and the inputs of a compression are written
+        // ahead of the row instructions so that the transition into a new compression can read
+        // them.
+        let load_bit = self.alloc::<BitRegister>();
+        let block = self.alloc_array::<U32Register>(16);
+        let counter = self.alloc_array::<U32Register>(2);
+        let block_len = self.alloc::<U32Register>();
+        let flags = self.alloc_array::<BitRegister>(NUM_FLAGS);
+        let children = self.alloc_array::<ElementRegister>(2);
+        let cycle_8 = self.cycle(3);
+        self.assert_equal(&load_bit, &cycle_8.start_bit);
+
+        // Public values
+        let public_block = self.alloc_array_public::<U32Register>(16 * num_compressions);
+        let public_counter = self.alloc_array_public::<U32Register>(2 * num_compressions);
+        let public_block_len = self.alloc_array_public::<U32Register>(num_compressions);
+        let public_flags = self.alloc_array_public::<BitRegister>(NUM_FLAGS * num_compressions);
+        let public_children = self.alloc_array_public::<ElementRegister>(2 * num_compressions);
+        let hash_state = self.alloc_array_public::<U32Register>(BLAKE3_CV_WORDS * num_compressions);
+
+        let one = ArithmeticExpression::<L::Field>::one();
+        let load_next = load_bit.next().expr::<L::Field>();
+        let [chunk_start, chunk_end, parent, root] = from_fn(|k| flags.get(k));
+
+        // Get the inputs of a compression from the bus at the start of every cycle
+        let input_challenges = self.alloc_challenge_array::<CubicRegister>(
+            1 + U32Register::size_of() * 19 + NUM_FLAGS + 2,
+        );
+        let clk_input = self.accumulate_expressions(
+            &input_challenges,
+            &[
+                clk.expr(),
+                block.expr(),
+                counter.expr(),
+                block_len.expr(),
+                flags.expr(),
+                children.expr(),
+            ],
+        );
+        self.output_from_bus_filtered(bus_channel_idx, clk_input, load_bit.expr());
+
+        // Copy the flags to the rest of the cycle and permute the message words between rounds
+        for flag in flags.iter() {
+            self.set_to_expression_transition(
+                &flag.next(),
+                load_next.clone() * flag.next().expr()
+                    + (one.clone() - load_next.clone()) * flag.expr(),
+            );
+        }
+        for (i, word) in block.iter().enumerate() {
+            self.set_to_expression_transition(
+                &word.next(),
+                load_next.clone() * word.next().expr()
+                    + (one.clone() - load_next.clone()) * block.get(MSG_PERMUTATION[i]).expr(),
+            );
+        }
+
+        let word_expression = |word: u32| {
+            ArithmeticExpression::from_constant_vec(
+                u32_to_le_field_bytes::<L::Field>(word).to_vec(),
+            )
+        };
+        let iv_words = IV.map(word_expression);
+
+        // The state words after the chaining value, for the current or the next row
+        let initial_words = |next: bool| {
+            let row = |register: U32Register| match next {
+                true => register.next().expr(),
+                false => register.expr(),
+            };
+            let bit = |register: BitRegister| match next {
+                true => register.next().expr(),
+                false => register.expr(),
+            };
+            let flag_byte = bit(chunk_start)
+                + bit(chunk_end) * L::Field::from_canonical_u32(CHUNK_END)
+                + bit(parent) * L::Field::from_canonical_u32(PARENT)
+                + bit(root) * L::Field::from_canonical_u32(ROOT);
+            let flag_word = flag_byte * word_expression(1);
+            IV[..4]
+                .iter()
+                .map(|word| word_expression(*word))
+                .chain([
+                    row(counter.get(0)),
+                    row(counter.get(1)),
+                    row(block_len),
+                    flag_word,
+                ])
+                .collect::<Vec<_>>()
+        };
+
+        // The first compression starts from the IV, either as the start of a chunk or as a parent
+        let state = self.alloc_array::<U32Register>(16);
+        self.assert_expression_zero_first_row(chunk_start.expr() + parent.expr() - L::Field::ONE);
+        for (word, value) in state
+            .iter()
+            .zip(iv_words.iter().cloned().chain(initial_words(false)))
+        {
+            self.set_to_expression_first_row(&word, value);
+        }
+
+        // Apply a round and compute the output chaining value
+        let input = state.iter().collect::<Vec<_>>();
+        let block_words = block.iter().collect::<Vec<_>>();
+        let output = self.blake_round(&input, &block_words, operations);
+        let chaining_value = self.alloc_array::<U32Register>(BLAKE3_CV_WORDS);
+        for (i, word) in chaining_value.iter().enumerate() {
+            self.set_bitwise_xor(&input[i], &input[i + 8], &word, operations);
+        }
+
+        // Assign the next state: the round output within a cycle and the initial state of the next
+        // compression at the end of a cycle, whose chaining value is the IV at the start of a chunk
+        // or for a parent, and the current chaining value otherwise.
+        let continue_next = one.clone() - chunk_start.next().expr() - parent.next().expr();
+        let initial_next = initial_words(true);
+        for (i, (word, word_next)) in state.iter().zip(output.iter()).enumerate() {
+            let initial = if i < 8 {
+                continue_next.clone() * chaining_value.get(i).expr()
+                    + (one.clone() - continue_next.clone()) * iv_words[i].clone()
+            } else {
+                initial_next[i - 8].clone()
+            };
+            self.set_to_expression_transition(
+                &word.next(),
+                load_next.clone() * initial + (one.clone() - load_next.clone()) * word_next.expr(),
+            );
+        }
+
+        // Put the chaining value of every compression in the bus at the end of its cycle
+        let state_challenges = self
+            .alloc_challenge_array::<CubicRegister>(U32Register::size_of() * BLAKE3_CV_WORDS + 1);
+        let clk_state =
+            self.accumulate_expressions(&state_challenges, &[clk.expr(), chaining_value.expr()]);
+        self.input_to_bus_filtered(bus_channel_idx, clk_state, cycle_8.end_bit.expr());
+
+        // The output of the last chunk compression or of a parent which is not the root is merged
+        // into a parent, which reads the chaining values of its children as its block.
+        let child_challenges = self
+            .alloc_challenge_array::<CubicRegister>(U32Register::size_of() * BLAKE3_CV_WORDS + 1);
+        let child_bit = self.alloc::<BitRegister>();
+        self.set_to_expression(
+            &child_bit,
+            cycle_8.end_bit.expr() * (chunk_end.expr() + parent.expr()) * root.not_expr(),
+        );
+        let child_output =
+            self.accumulate_expressions(&child_challenges, &[clk.expr(), chaining_value.expr()]);
+        self.input_to_bus_filtered(bus_channel_idx, child_output, child_bit.expr());
+
+        let parent_bit = self.alloc::<BitRegister>();
+        self.set_to_expression(&parent_bit, load_bit.expr() * parent.expr());
+        for (k, half) in [0..BLAKE3_CV_WORDS, BLAKE3_CV_WORDS..16]
+            .into_iter()
+            .enumerate()
+        {
+            let child_input = self.accumulate_expressions(
+                &child_challenges,
+                &[children.get(k).expr(), block.get_subarray(half).expr()],
+            );
+            self.output_from_bus_filtered(bus_channel_idx, child_input, parent_bit.expr());
+        }
+
+        // Put the public inputs and chaining values in the bus
+        for i in 0..num_compressions {
+            let clk_start = L::Field::from_canonical_usize(i * BLAKE3_CYCLE_LENGTH);
+            let input_digest = self.accumulate_public_expressions(
+                &input_challenges,
+                &[
+                    ArithmeticExpression::from_constant(clk_start),
+                    public_block.get_subarray(16 * i..16 * (i + 1)).expr(),
+                    public_counter.get_subarray(2 * i..2 * (i + 1)).expr(),
+                    public_block_len.get(i).expr(),
+                    public_flags
+                        .get_subarray(NUM_FLAGS * i..NUM_FLAGS * (i + 1))
+                        .expr(),
+                    public_children.get_subarray(2 * i..2 * (i + 1)).expr(),
+                ],
+            );
+            bus.insert_global_value(&input_digest);
+
+            let clk_end = clk_start + L::Field::from_canonical_usize(BLAKE3_CYCLE_LENGTH - 1);
+            let state_digest = self.accumulate_public_expressions(
+                &state_challenges,
+                &[
+                    ArithmeticExpression::from_constant(clk_end),
+                    hash_state
+                        .get_subarray(BLAKE3_CV_WORDS * i..BLAKE3_CV_WORDS * (i + 1))
+                        .expr(),
+                ],
+            );
+            bus.output_global_value(&state_digest);
+        }
+
+        // The byte lookup needs an even number of operations
+        if operations.values.len() % 2 == 1 {
+            let dummy = self.alloc::<ByteRegister>();
+            let dummy_range = ByteOperation::Range(dummy);
+            self.set_byte_operation(&dummy_range, operations);
+        }
+
+        Blake3Gadget {
+            public_block,
+            public_counter,
+            public_block_len,
+            public_flags,
+            public_children,
+            state: hash_state,
+            load_bit,
+            block,
+            counter,
+            block_len,
+            flags,
+            children,
+        }
+    }
+}
+
+impl Blake3Gadget {
+    /// Writes the compressions of `messages`, which must fill all the cycles of the trace.
+    pub fn write<F: Field, I: IntoIterator>(
+        &self,
+        messages: I,
+        writer: &TraceWriter<F>,
+    ) -> Blake3PublicData<F>
+    where
+        I::Item: Borrow<[u8]>,
+    {
+        let mut compressions = Vec::new();
+        for message in messages {
+            let offset = compressions.len();
+            compressions.extend(Self::compressions(message.borrow()).into_iter().map(
+                |mut compression| {
+                    if let Some((left, right)) = compression.children.as_mut() {
+                        *left += offset;
+                        *right += offset;
+                    }
+                    compression
+                },
+            ));
+        }
+        let num_compressions = self.public_block_len.len();
+        assert!(
+            compressions.len() == num_compressions,
+            "Message compressions do not add up"
+        );
+
+        // A child is referred to by the clock of the last row of its cycle
+        let key =
+            |i: usize| F::from_canonical_usize(i * BLAKE3_CYCLE_LENGTH + BLAKE3_CYCLE_LENGTH - 1);
+
+        let mut public_block = Vec::new();
+        let mut counter_values = Vec::new();
+        let mut block_len_values = Vec::new();
+        let mut flag_values = Vec::new();
+        let mut children_values = Vec::new();
+        let mut hash_values = Vec::new();
+        for (i, compression) in compressions.iter().enumerate() {
+            let block = compression.block.map(u32_to_le_field_bytes::<F>);
+            let counter = [
+                compression.counter as u32,
+                (compression.counter >> 32) as u32,
+            ]
+            .map(u32_to_le_field_bytes::<F>);
+            let block_len = u32_to_le_field_bytes::<F>(compression.block_len);
+            let flags: [F; NUM_FLAGS] =
+                from_fn(|k| F::from_canonical_u32((compression.flags >> k) & 1));
+            let children = match compression.children {
+                Some((left, right)) => [key(left), key(right)],
+                None => [F::ZERO; 2],
+            };
+
+            let row = i * BLAKE3_CYCLE_LENGTH;
+            for j in 0..BLAKE3_CYCLE_LENGTH {
+                writer.write(
+                    &self.load_bit,
+                    &F::from_canonical_u8((j == 0) as u8),
+                    row + j,
+                );
+            }
+            writer.write_array(&self.block, block, row);
+            writer.write_array(&self.counter, counter, row);
+            writer.write(&self.block_len, &block_len, row);
+            writer.write_array(&self.flags, flags, row);
+            writer.write_array(&self.children, children, row);
+
+            public_block.extend(block);
+            counter_values.extend(counter);
+            block_len_values.push(block_len);
+            flag_values.extend(flags);
+            children_values.extend(children);
+            hash_values.extend(
+                compression
+                    .output_chaining_value()
+                    .map(u32_to_le_field_bytes::<F>),
+            );
+        }
+
+        writer.write_array(&self.public_block, &public_block, 0);
+        writer.write_array(&self.public_counter, &counter_values, 0);
+        writer.write_array(&self.public_block_len, &block_len_values, 0);
+        writer.write_array(&self.public_flags, &flag_values, 0);
+        writer.write_array(&self.public_children, &children_values, 0);
+        writer.write_array(&self.state, &hash_values, 0);
+
+        Blake3PublicData {
+            public_block,
+            counter: counter_values,
+            block_len: block_len_values,
+            flags: flag_values,
+            children: children_values,
+            hash_state: hash_values,
+        }
+    }
+
+    /// The compressions of the hash tree of `msg`, in the order in which they are computed.
+    ///
+    /// The chaining values of two subtrees are merged as soon as a later chunk is known to exist,
+    /// so the root, whose output is the digest, is always the last compression.
+    pub fn compressions(msg: &[u8]) -> Vec<Blake3Compression> {
+        let chunks = match msg.is_empty() {
+            true => vec![msg],
+            false => msg.chunks(BLAKE3_CHUNK_LEN).collect(),
+        };
+        let num_chunks = chunks.len();
+
+        let mut compressions = Vec::new();
+        // The index of the compression and the chaining value of the roots of complete subtrees
+        let mut stack: Vec<(usize, [u32; 8])> = Vec::new();
+        for (i, chunk) in chunks.into_iter().enumerate() {
+            let blocks = match chunk.is_empty() {
+                true => vec![chunk],
+                false => chunk.chunks(BLAKE3_BLOCK_LEN).collect(),
+            };
+            let num_blocks = blocks.len();
+
+            let mut chaining_value = IV;
+            for (j, block) in blocks.into_iter().enumerate() {
+                let mut flags = 0;
+                if j == 0 {
+                    flags |= CHUNK_START;
+                }
+                if j == num_blocks - 1 {
+                    flags |= CHUNK_END;
+                    if num_chunks == 1 {
+                        flags |= ROOT;
+                    }
+                }
+                let compression = Blake3Compression {
+                    chaining_value,
+                    block: Self::block_words(block),
+                    counter: i as u64,
+                    block_len: block.len() as u32,
+                    flags,
+                    children: None,
+                };
+                chaining_value = compression.output_chaining_value();
+                compressions.push(compression);
+            }
+            stack.push((compressions.len() - 1, chaining_value));
+
+            // A chunk completes as many subtrees as there are trailing zeros in the number of
+            // chunks so far. The subtrees of the last chunk are merged below.
+            if i < num_chunks - 1 {
+                let mut total_chunks = i + 1;
+                while total_chunks % 2 == 0 {
+                    Self::merge_parent(&mut stack, &mut compressions, false);
+                    total_chunks /= 2;
+                }
+            }
+        }
+        while stack.len() > 1 {
+            let is_root = stack.len() == 2;
+            Self::merge_parent(&mut stack, &mut compressions, is_root);
+        }
+
+        compressions
+    }
+
+    /// Merges the two subtrees on top of the stack with a parent compression.
+    fn merge_parent(
+        stack: &mut Vec<(usize, [u32; 8])>,
+        compressions: &mut Vec<Blake3Compression>,
+        is_root: bool,
+    ) {
+        let (right, right_cv) = stack.pop().unwrap();
+        let (left, left_cv) = stack.pop().unwrap();
+        let block = from_fn(|i| match i < BLAKE3_CV_WORDS {
+            true => left_cv[i],
+            false => right_cv[i - BLAKE3_CV_WORDS],
+        });
+        let compression = Blake3Compression {
+            chaining_value: IV,
+            block,
+            counter: 0,
+            block_len: BLAKE3_BLOCK_LEN as u32,
+            flags: if is_root { PARENT | ROOT } else { PARENT },
+            children: Some((left, right)),
+        };
+        stack.push((compressions.len(), compression.output_chaining_value()));
+        compressions.push(compression);
+    }
+
+    /// The little endian words of a block, padded with zeros.
+    pub fn block_words(block: &[u8]) -> [u32; 16] {
+        from_fn(|i| {
+            let mut word = [0u8; 4];
+            for (byte, value) in word.iter_mut().zip(block.iter().skip(4 * i)) {
+                *byte = *value;
+            }
+            u32::from_le_bytes(word)
+        })
+    }
+
+    /// The BLAKE3 compression function.
+    pub fn compress(
+        chaining_value: &[u32; 8],
+        block: &[u32; 16],
+        counter: u64,
+        block_len: u32,
+        flags: u32,
+    ) -> [u32; 16] {
+        let mut state = [
+            chaining_value[0],
+            chaining_value[1],
+            chaining_value[2],
+            chaining_value[3],
+            chaining_value[4],
+            chaining_value[5],
+            chaining_value[6],
+            chaining_value[7],
+            IV[0],
+            IV[1],
+            IV[2],
+            IV[3],
+            counter as u32,
+            (counter >> 32) as u32,
+            block_len,
+            flags,
+        ];
+        let mut block = *block;
+        for _ in 0..BLAKE3_NUM_ROUNDS {
+            Self::round(&mut state, &block);
+            block = from_fn(|i| block[MSG_PERMUTATION[i]]);
+        }
+        from_fn(|i| match i < 8 {
+            true => state[i] ^ state[i + 8],
+            false => state[i] ^ chaining_value[i - 8],
+        })
+    }
+
+    pub fn round(state: &mut [u32; 16], block: &[u32; 16]) {
+        blake::round(state, block)
+    }
+
+    /// Computes the BLAKE3 digest of `msg` natively.
+    pub fn hash(msg: &[u8]) -> [u8; 32] {
+        let root = Self::compressions(msg).pop().unwrap();
+        root.output_chaining_value()
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::timed;
+    use plonky2::util::timing::TimingTree;
+
+    use super::*;
+    pub use crate::chip::builder::tests::*;
+    use crate::chip::builder::AirBuilder;
+    use crate::chip::uint::operations::instruction::U32Instruction;
+    use crate::chip::AirParameters;
+
+    /// The official test vectors, whose input of length `n` is the bytes `0, 1, ..., 250, 0, 1,
+    /// ...` of length `n`.
+    const TEST_VECTORS: [(usize, &str); 11] = [
+        (
+            0,
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262",
+        ),
+        (
+            1,
+            "2d3adedff11b61f14c886e35afa036736dcd87a74d27b5c1510225d0f592e213",
+        ),
+        (
+            1023,
+            "10108970eeda3eb932baac1428c7a2163b0e924c9a9e25b35bba72b28f70bd11",
+        ),
+        (
+            1024,
+            "42214739f095a406f3fc83deb889744ac00df831c10daa55189b5d121c855af7",
+        ),
+        (
+            1025,
+            "d00278ae47eb27b34faecf67b4fe263f82d5412916c1ffd97c8cb7fb814b8444",
+        ),
+        (
+            2048,
+            "e776b6028c7cd22a4d0ba182a8bf62205d2ef576467e838ed6f2529b85fba24a",
+        ),
+        (
+            2049,
+            "5f4d72f40d7a5f82b15ca2b2e44b1de3c2ef86c426c95c1af0b6879522563030",
+        ),
+        (
+            3072,
+            "b98cb0ff3623be03326b373de6b9095218513e64f1ee2edd2525c7ad1e5cffd2",
+        ),
+        (
+            3073,
+            "7124b49501012f81cc7f11ca069ec9226cecb8a2c850cfe644e327d22d3e1cd3",
+        ),
+        (
+            4096,
+            "015094013f57a5277b59d8475c0501042c0b642e531b0a1c8f58d2163229e969",
+        ),
+        (
+            4097,
+            "9b4052b38f1c5fc8b1f9ff7ac7b27cd242487b3d890d15c96a1c25b8aa0fb995",
+        ),
+    ];
+
+    const ABC_DIGEST: &str = "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85";
+
+    fn test_input(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub struct Blake3Test;
+
+    impl AirParameters for Blake3Test {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = U32Instruction;
+
+        const NUM_FREE_COLUMNS: usize = 1400;
+        const EXTENDED_COLUMNS: usize = 3600;
+        const NUM_ARITHMETIC_COLUMNS: usize = 0;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    #[test]
+    fn test_blake3_native_hash() {
+        for (len, digest) in TEST_VECTORS {
+            assert_eq!(hex::encode(Blake3Gadget::hash(&test_input(len))), digest);
+        }
+        assert_eq!(hex::encode(Blake3Gadget::hash(b"abc")), ABC_DIGEST);
+
+        // A single chunk is its own root, and three chunks are merged by two parents, the first
+        // of which is not the root.
+        let compressions = Blake3Gadget::compressions(&test_input(1023));
+        assert_eq!(compressions.len(), 16);
+        assert_eq!(compressions[15].flags, CHUNK_END | ROOT);
+
+        let compressions = Blake3Gadget::compressions(&test_input(2049));
+        assert_eq!(compressions.len(), 35);
+        assert_eq!(compressions[32].flags, PARENT);
+        assert_eq!(compressions[32].children, Some((15, 31)));
+        assert_eq!(compressions[33].flags, CHUNK_START | CHUNK_END);
+        assert_eq!(compressions[33].counter, 2);
+        assert_eq!(compressions[34].flags, PARENT | ROOT);
+        assert_eq!(compressions[34].children, Some((32, 33)));
+    }
+
+    #[test]
+    fn test_blake3_stark() {
+        type F = GoldilocksField;
+        type L = Blake3Test;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut timing = TimingTree::new("Blake3 test", log::Level::Debug);
+
+        let mut builder = AirBuilder::<L>::new();
+        let clk = builder.clock();
+
+        let (mut operations, table) = builder.byte_operations();
+
+        let mut bus = builder.new_bus();
+        let channel_idx = bus.new_channel(&mut builder);
+
+        let blake3_gadget =
+            builder.process_blake3_batch(&clk, &mut bus, channel_idx, &mut operations);
+
+        builder.register_byte_lookup(operations, &table);
+        builder.constrain_bus(bus);
+
+        let (air, trace_data) = builder.build();
+
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+        let writer = generator.new_writer();
+
+        // The test vectors, single and multi-chunk, are followed by empty messages of a single
+        // compression to fill the trace.
+        let mut messages = TEST_VECTORS
+            .iter()
+            .map(|(len, _)| test_input(*len))
+            .chain([b"abc".to_vec()])
+            .collect::<Vec<_>>();
+        let mut expected_digests = TEST_VECTORS
+            .iter()
+            .map(|(_, digest)| *digest)
+            .chain([ABC_DIGEST])
+            .collect::<Vec<_>>();
+        let num_compressions = messages
+            .iter()
+            .map(|m| Blake3Gadget::compressions(m).len())
+            .sum::<usize>();
+        for _ in num_compressions..L::num_rows() / BLAKE3_CYCLE_LENGTH {
+            messages.push(Vec::new());
+            expected_digests.push(TEST_VECTORS[0].1);
+        }
+
+        timed!(timing, "Write the execution trace", {
+            table.write_table_entries(&writer);
+            blake3_gadget.write(messages.iter().map(|m| m.as_slice()), &writer);
+            for i in 0..L::num_rows() {
+                writer.write_row_instructions(&generator.air_data, i);
+            }
+            table.write_multiplicities(&writer);
+        });
+
+        // The digest is the chaining value of the last compression of every message
+        let mut root = 0;
+        for (message, digest) in messages.iter().zip(expected_digests) {
+            root += Blake3Gadget::compressions(message).len();
+            let hash = writer.read_array(
+                &blake3_gadget
+                    .state
+                    .get_subarray(BLAKE3_CV_WORDS * (root - 1)..BLAKE3_CV_WORDS * root),
+                0,
+            );
+            let expected = hex::decode(digest)
+                .unwrap()
+                .chunks_exact(4)
+                .map(|x| u32_to_le_field_bytes::<F>(u32::from_le_bytes(x.try_into().unwrap())))
+                .collect::<Vec<_>>();
+            assert_eq!(hash, expected);
+        }
+
+        let public_inputs = writer.0.public.read().unwrap().clone();
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        timed!(
+            timing,
+            "Stark proof and verify",
+            test_starky(&stark, &config, &generator, &public_inputs)
+        );
+
+        // Generate recursive proof
+        timed!(
+            timing,
+            "Recursive proof generation and verification",
+            test_recursive_starky(stark, config, generator, &public_inputs)
+        );
+
+        timing.print();
+    }
+}
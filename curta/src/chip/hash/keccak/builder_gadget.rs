@@ -0,0 +1,244 @@
+use core::marker::PhantomData;
+
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::Target;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+use super::generator::{Keccak256AirParameters, Keccak256Generator, KeccakHintGenerator};
+use super::{Keccak256PublicData, KECCAK_RATE};
+use crate::chip::builder::AirBuilder;
+use crate::chip::hash::sha::sha256::builder_gadget::CurtaBytes;
+use crate::chip::trace::generator::ArithmeticGenerator;
+use crate::chip::AirParameters;
+use crate::math::prelude::CubicParameters;
+use crate::plonky2::stark::config::{CurtaConfig, StarkyConfig};
+use crate::plonky2::stark::gadget::StarkGadget;
+use crate::plonky2::stark::generator::simple::SimpleStarkWitnessGenerator;
+use crate::plonky2::stark::Starky;
+
+#[derive(Debug, Clone)]
+pub struct Keccak256BuilderGadget<F, E, const D: usize> {
+    pub padded_messages: Vec<Target>,
+    pub digests: Vec<Target>,
+    pub chunk_sizes: Vec<usize>,
+    _marker: PhantomData<(F, E)>,
+}
+
+pub trait Keccak256Builder<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize> {
+    type Gadget;
+
+    fn init_keccak256(&mut self) -> Self::Gadget;
+
+    fn keccak256<const N: usize>(
+        &mut self,
+        padded_message: &CurtaBytes<N>,
+        gadget: &mut Self::Gadget,
+    ) -> CurtaBytes<32>;
+
+    /// Computes the digest of a padded message whose length is only known when the circuit is
+    /// built, which must be a multiple of 136 bytes.
+    fn keccak256_bytes(
+        &mut self,
+        padded_message: &[Target],
+        gadget: &mut Self::Gadget,
+    ) -> CurtaBytes<32>;
+
+    fn constrain_keccak256_gadget<C: CurtaConfig<D, F = F, FE = F::Extension>>(
+        &mut self,
+        gadget: Self::Gadget,
+    );
+}
+
+impl<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize> Keccak256Builder<F, E, D>
+    for CircuitBuilder<F, D>
+{
+    type Gadget = Keccak256BuilderGadget<F, E, D>;
+
+    fn init_keccak256(&mut self) -> Self::Gadget {
+        Keccak256BuilderGadget {
+            padded_messages: Vec::new(),
+            digests: Vec::new(),
+            chunk_sizes: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    fn keccak256<const N: usize>(
+        &mut self,
+        padded_message: &CurtaBytes<N>,
+        gadget: &mut Self::Gadget,
+    ) -> CurtaBytes<32> {
+        Keccak256Builder::<F, E, D>::keccak256_bytes(self, &padded_message.0, gadget)
+    }
+
+    fn keccak256_bytes(
+        &mut self,
+        padded_message: &[Target],
+        gadget: &mut Self::Gadget,
+    ) -> CurtaBytes<32> {
+        let num_chunks = padded_message.len() / KECCAK_RATE;
+        assert_eq!(
+            padded_message.len(),
+            KECCAK_RATE * num_chunks,
+            "Padded message length must be a multiple of {}",
+            KECCAK_RATE
+        );
+        assert!(num_chunks > 0, "Padded message must not be empty");
+
+        gadget.padded_messages.extend_from_slice(padded_message);
+        let digest_bytes = self.add_virtual_target_arr::<32>();
+        let hint = KeccakHintGenerator::new(padded_message, digest_bytes);
+        self.add_simple_generator(hint);
+        gadget.digests.extend_from_slice(&digest_bytes);
+        gadget.chunk_sizes.push(num_chunks);
+        CurtaBytes(digest_bytes)
+    }
+
+    fn constrain_keccak256_gadget<C: CurtaConfig<D, F = F, FE = F::Extension>>(
+        &mut self,
+        gadget: Self::Gadget,
+    ) {
+        // Allocate public input targets
+        let public_keccak_targets =
+            Keccak256PublicData::add_virtual(self, &gadget.digests, &gadget.chunk_sizes);
+
+        // Make the air
+        let mut air_builder = AirBuilder::<Keccak256AirParameters<F, E>>::new();
+        let clk = air_builder.clock();
+
+        let (mut operations, table) = air_builder.byte_operations();
+
+        let mut bus = air_builder.new_bus();
+        let channel_idx = bus.new_channel(&mut air_builder);
+
+        let keccak_gadget =
+            air_builder.process_keccak_256_batch(&clk, &mut bus, channel_idx, &mut operations);
+
+        air_builder.register_byte_lookup(operations, &table);
+        air_builder.constrain_bus(bus);
+
+        let (air, trace_data) = air_builder.build();
+
+        let generator = ArithmeticGenerator::<Keccak256AirParameters<F, E>>::new(trace_data);
+
+        let public_input_target = public_keccak_targets.public_input_targets(self);
+
+        let keccak_generator = Keccak256Generator {
+            gadget: keccak_gadget,
+            table,
+            padded_messages: gadget.padded_messages,
+            chunk_sizes: gadget.chunk_sizes,
+            trace_generator: generator.clone(),
+            pub_values_target: public_keccak_targets,
+        };
+
+        self.add_simple_generator(keccak_generator);
+
+        let stark = Starky::new(air);
+        let config =
+            StarkyConfig::<C, D>::standard_fast_config(Keccak256AirParameters::<F, E>::num_rows());
+        let virtual_proof = self.add_virtual_stark_proof(&stark, &config);
+        self.verify_stark_proof(&config, &stark, &virtual_proof, &public_input_target);
+
+        let stark_generator = SimpleStarkWitnessGenerator::new(
+            config,
+            stark,
+            virtual_proof,
+            public_input_target,
+            generator,
+        );
+        self.add_simple_generator(stark_generator);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use plonky2::field::types::Field;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+    use plonky2::timed;
+    use plonky2::util::timing::TimingTree;
+
+    use super::*;
+    pub use crate::chip::builder::tests::*;
+    use crate::chip::hash::keccak::Keccak256Gadget;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    #[test]
+    fn test_keccak_256_plonky_gadget() {
+        type F = GoldilocksField;
+        type E = GoldilocksCubicParameters;
+        type SC = CurtaPoseidonGoldilocksConfig;
+        type C = PoseidonGoldilocksConfig;
+        const D: usize = 2;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut timing = TimingTree::new("Keccak256 Plonky2 gadget test", log::Level::Debug);
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let mut gadget: Keccak256BuilderGadget<F, E, D> = builder.init_keccak256();
+
+        // Single block messages together with a two block message fill the 2048 blocks of the
+        // trace.
+        let mut messages = (0..1023)
+            .flat_map(|_| [b"".to_vec(), b"abc".to_vec()])
+            .collect::<Vec<_>>();
+        messages.push(vec![0x61; 200]);
+
+        let mut msg_targets = Vec::new();
+        let mut digest_targets = Vec::new();
+        for _ in 0..2046 {
+            let msg = CurtaBytes(builder.add_virtual_target_arr::<KECCAK_RATE>());
+            digest_targets.push(builder.keccak256(&msg, &mut gadget));
+            msg_targets.push(msg.0.to_vec());
+        }
+        let long_msg = builder.add_virtual_targets(2 * KECCAK_RATE);
+        digest_targets.push(builder.keccak256_bytes(&long_msg, &mut gadget));
+        msg_targets.push(long_msg);
+
+        let expected_digests = digest_targets
+            .iter()
+            .map(|digest| {
+                let expected = builder.add_virtual_target_arr::<32>();
+                for (d, e) in digest.0.iter().zip(expected.iter()) {
+                    builder.connect(*d, *e);
+                }
+                expected
+            })
+            .collect::<Vec<_>>();
+
+        builder.constrain_keccak256_gadget::<SC>(gadget);
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+
+        for ((msg, msg_target), expected) in messages
+            .iter()
+            .zip(msg_targets.iter())
+            .zip(expected_digests.iter())
+        {
+            let padded_msg = Keccak256Gadget::pad(msg)
+                .into_iter()
+                .map(F::from_canonical_u8)
+                .collect::<Vec<_>>();
+            pw.set_target_arr(msg_target, &padded_msg);
+            let digest = Keccak256Gadget::hash(msg).map(F::from_canonical_u8);
+            pw.set_target_arr(expected, &digest);
+        }
+
+        let recursive_proof = timed!(
+            timing,
+            "Generate proof",
+            plonky2::plonk::prover::prove(&data.prover_only, &data.common, pw, &mut timing)
+        )
+        .unwrap();
+        timing.print();
+        data.verify(recursive_proof).unwrap();
+    }
+}
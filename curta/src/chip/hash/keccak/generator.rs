@@ -0,0 +1,385 @@
+use core::marker::PhantomData;
+
+use anyhow::{ensure, Result};
+use itertools::Itertools;
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::generator::{GeneratedValues, SimpleGenerator};
+use plonky2::iop::target::Target;
+use plonky2::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::circuit_data::CommonCircuitData;
+use plonky2::util::serialization::{Buffer, IoError, Read, Write};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    Keccak256Gadget, Keccak256PublicData, KECCAK_CYCLE_LENGTH, KECCAK_DIGEST_LANES,
+    KECCAK_NUM_ROUNDS, KECCAK_RATE, KECCAK_RATE_LANES, ROUND_CONSTANTS,
+};
+use crate::chip::trace::generator::ArithmeticGenerator;
+use crate::chip::uint::bytes::lookup_table::table::ByteLookupTable;
+use crate::chip::uint::bytes::lookup_table::ByteInstructionSet;
+use crate::chip::uint::util::u64_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::math::prelude::{CubicParameters, *};
+use crate::utils::serde::{BufferRead, BufferWrite};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Keccak256AirParameters<F, E>(pub PhantomData<(F, E)>);
+
+/// The number of 136-byte blocks processed by a single Keccak-256 proof.
+pub const KECCAK_NUM_BLOCKS: usize = (1 << 16) / KECCAK_CYCLE_LENGTH;
+
+#[derive(Debug, Clone)]
+pub struct KeccakHintGenerator {
+    padded_message: Vec<Target>,
+    digest_bytes: [Target; 32],
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Keccak256Generator<F: PrimeField64, E: CubicParameters<F>> {
+    pub gadget: Keccak256Gadget,
+    pub table: ByteLookupTable,
+    pub padded_messages: Vec<Target>,
+    pub chunk_sizes: Vec<usize>,
+    pub trace_generator: ArithmeticGenerator<Keccak256AirParameters<F, E>>,
+    pub pub_values_target: Keccak256PublicData<Target>,
+}
+
+impl<F: PrimeField64, E: CubicParameters<F>> AirParameters for Keccak256AirParameters<F, E> {
+    type Field = F;
+    type CubicParams = E;
+
+    type Instruction = ByteInstructionSet;
+
+    const NUM_FREE_COLUMNS: usize = 2600;
+    const EXTENDED_COLUMNS: usize = 8000;
+    const NUM_ARITHMETIC_COLUMNS: usize = 0;
+
+    fn num_rows_bits() -> usize {
+        16
+    }
+}
+
+impl<F: RichField, E: CubicParameters<F>> Keccak256Generator<F, E> {
+    pub fn id() -> String {
+        "Keccak256Generator".to_string()
+    }
+}
+
+impl<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize> SimpleGenerator<F, D>
+    for Keccak256Generator<F, E>
+{
+    fn id(&self) -> String {
+        Self::id()
+    }
+
+    fn serialize(
+        &self,
+        dst: &mut Vec<u8>,
+        _: &CommonCircuitData<F, D>,
+    ) -> plonky2::util::serialization::IoResult<()> {
+        let data = bincode::serialize(self).unwrap();
+        dst.write_bytes(&data)
+    }
+
+    fn deserialize(
+        src: &mut Buffer,
+        _: &CommonCircuitData<F, D>,
+    ) -> plonky2::util::serialization::IoResult<Self>
+    where
+        Self: Sized,
+    {
+        let bytes = src.read_bytes()?;
+        let data = bincode::deserialize(&bytes).unwrap();
+        Ok(data)
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        self.padded_messages.clone()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let padded_messages = self
+            .padded_messages
+            .iter()
+            .map(|x| witness.get_target(*x).as_canonical_u64() as u8)
+            .collect::<Vec<_>>();
+        assert_eq!(padded_messages.len(), KECCAK_NUM_BLOCKS * KECCAK_RATE);
+
+        let message_chunks = self.chunk_sizes.iter().scan(0, |idx, size| {
+            let chunk = padded_messages[*idx..*idx + KECCAK_RATE * size].to_vec();
+            *idx += KECCAK_RATE * size;
+            Some(chunk)
+        });
+
+        // Write trace values
+        let writer = self.trace_generator.new_writer();
+        self.table.write_table_entries(&writer);
+        let keccak_public_values = self.gadget.write(message_chunks, &writer);
+        for i in 0..Keccak256AirParameters::<F, E>::num_rows() {
+            writer.write_row_instructions(&self.trace_generator.air_data, i);
+        }
+        self.table.write_multiplicities(&writer);
+
+        // Fill keccak public values into the output buffer
+        self.pub_values_target
+            .set_targets(keccak_public_values, out_buffer);
+    }
+}
+
+impl Keccak256PublicData<Target> {
+    /// Allocates the public data targets for a batch of messages, where the `i`-th message
+    /// consists of `chunk_sizes[i]` blocks of 136 bytes and its digest is given by the `i`-th 32
+    /// bytes of `digests`. The digest lanes after the blocks of a message other than the last one
+    /// are allocated as virtual targets.
+    pub fn add_virtual<F: RichField + Extendable<D>, const D: usize>(
+        builder: &mut CircuitBuilder<F, D>,
+        digests: &[Target],
+        chunk_sizes: &[usize],
+    ) -> Self {
+        let public_w_targets = (0..KECCAK_RATE_LANES * KECCAK_NUM_BLOCKS)
+            .map(|_| builder.add_virtual_target_arr::<8>())
+            .collect::<Vec<_>>();
+
+        let mut end_bits_targets = Vec::new();
+        let mut hash_state_targets = Vec::new();
+
+        for (chunk_size, digest) in chunk_sizes.iter().zip_eq(digests.chunks_exact(32)) {
+            end_bits_targets.extend((0..(chunk_size - 1)).map(|_| builder.zero()));
+            end_bits_targets.push(builder.one());
+
+            hash_state_targets.extend(
+                (0..KECCAK_DIGEST_LANES * (chunk_size - 1))
+                    .map(|_| builder.add_virtual_target_arr::<8>()),
+            );
+
+            // The digest is the little endian encoding of the lanes
+            hash_state_targets.extend(
+                digest
+                    .chunks_exact(8)
+                    .map(|lane| -> [Target; 8] { lane.try_into().unwrap() }),
+            );
+        }
+
+        Keccak256PublicData {
+            public_w: public_w_targets,
+            hash_state: hash_state_targets,
+            end_bits: end_bits_targets,
+        }
+    }
+
+    pub fn set_targets<F: RichField>(
+        &self,
+        values: Keccak256PublicData<F>,
+        out_buffer: &mut GeneratedValues<F>,
+    ) {
+        for (pub_w_target, pub_w_value) in self.public_w.iter().zip_eq(values.public_w.iter()) {
+            out_buffer.set_target_arr(pub_w_target, pub_w_value);
+        }
+        for (hash_target, hash_value) in self.hash_state.iter().zip_eq(values.hash_state.iter()) {
+            out_buffer.set_target_arr(hash_target, hash_value);
+        }
+    }
+
+    /// Returns the public inputs of the Keccak-256 proof in the order in which the gadget
+    /// allocates them. The periodic table of round constants and row flags is a constant of the
+    /// circuit.
+    pub fn public_input_targets<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> Vec<Target> {
+        let round_constants = (0..KECCAK_CYCLE_LENGTH)
+            .flat_map(|j| u64_to_le_field_bytes(ROUND_CONSTANTS.get(j).copied().unwrap_or(0)))
+            .map(|x| builder.constant(x))
+            .collect::<Vec<_>>();
+        let load_bits = (0..KECCAK_CYCLE_LENGTH)
+            .map(|j| builder.constant(F::from_canonical_u8((j == 0) as u8)))
+            .collect::<Vec<_>>();
+        let round_bits = (0..KECCAK_CYCLE_LENGTH)
+            .map(|j| builder.constant(F::from_canonical_u8((j < KECCAK_NUM_ROUNDS) as u8)))
+            .collect::<Vec<_>>();
+
+        self.public_w
+            .iter()
+            .flatten()
+            .copied()
+            .chain(round_constants)
+            .chain(load_bits)
+            .chain(round_bits)
+            .chain(self.hash_state.iter().flatten().copied())
+            .chain(self.end_bits.iter().copied())
+            .collect()
+    }
+}
+
+impl KeccakHintGenerator {
+    pub fn new(padded_message: &[Target], digest_bytes: [Target; 32]) -> Self {
+        KeccakHintGenerator {
+            padded_message: padded_message.to_vec(),
+            digest_bytes,
+        }
+    }
+}
+
+impl KeccakHintGenerator {
+    pub fn id() -> String {
+        "KeccakHintGenerator".to_string()
+    }
+
+    /// Computes the digest of a padded message given as field elements.
+    ///
+    /// Returns an error if the message is not a whole number of 136-byte blocks or if one of its
+    /// values is not a byte.
+    pub fn padded_digest<F: RichField>(padded_message: &[F]) -> Result<[F; 32]> {
+        ensure!(
+            padded_message.len() % KECCAK_RATE == 0,
+            "KeccakHintGenerator: padded message has length {} bytes, which is not a multiple of \
+             the {}-byte block size",
+            padded_message.len(),
+            KECCAK_RATE
+        );
+        let padded_message = padded_message
+            .iter()
+            .enumerate()
+            .map(|(i, x)| {
+                let value = x.as_canonical_u64();
+                ensure!(
+                    value <= u8::MAX as u64,
+                    "KeccakHintGenerator: value {} at index {} of the padded message is not a byte",
+                    value,
+                    i
+                );
+                Ok(value as u8)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut state = [0u64; 25];
+        for block in padded_message.chunks_exact(KECCAK_RATE) {
+            state = Keccak256Gadget::absorb(state, &Keccak256Gadget::process_inputs(block));
+        }
+
+        let digest_bytes = state[..KECCAK_DIGEST_LANES]
+            .iter()
+            .flat_map(|lane| u64_to_le_field_bytes::<F>(*lane))
+            .collect::<Vec<_>>();
+        Ok(digest_bytes.try_into().unwrap())
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D> for KeccakHintGenerator {
+    fn id(&self) -> String {
+        Self::id()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        self.padded_message.clone()
+    }
+
+    fn serialize(
+        &self,
+        dst: &mut Vec<u8>,
+        _: &CommonCircuitData<F, D>,
+    ) -> plonky2::util::serialization::IoResult<()> {
+        dst.write_target_vec(&self.padded_message)?;
+        dst.write_target_vec(&self.digest_bytes)?;
+        Ok(())
+    }
+
+    fn deserialize(
+        src: &mut Buffer,
+        _: &CommonCircuitData<F, D>,
+    ) -> plonky2::util::serialization::IoResult<Self>
+    where
+        Self: Sized,
+    {
+        let padded_message = src.read_target_vec()?;
+        let digest_bytes = src.read_target_vec()?;
+        Ok(Self {
+            padded_message,
+            digest_bytes: digest_bytes.try_into().map_err(|_| IoError)?,
+        })
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let padded_message = witness.get_targets(&self.padded_message);
+        let digest_bytes = Self::padded_digest(&padded_message).unwrap_or_else(|e| panic!("{}", e));
+
+        out_buffer.set_target_arr(&self.digest_bytes, &digest_bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+
+    use super::*;
+
+    #[test]
+    fn test_keccak256_public_data_layout() {
+        type F = GoldilocksField;
+        const D: usize = 2;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let chunk_sizes = [1, 2];
+        let digests = builder.add_virtual_targets(64);
+
+        let public_data = Keccak256PublicData::add_virtual(&mut builder, &digests, &chunk_sizes);
+
+        assert_eq!(
+            public_data.public_w.len(),
+            KECCAK_RATE_LANES * KECCAK_NUM_BLOCKS
+        );
+        assert_eq!(public_data.end_bits.len(), 3);
+        assert_eq!(public_data.hash_state.len(), KECCAK_DIGEST_LANES * 3);
+
+        // The digests are the final digest lanes of the messages, in little endian order.
+        for (i, byte) in digests[..32].iter().enumerate() {
+            assert_eq!(public_data.hash_state[i / 8][i % 8], *byte);
+        }
+        for (i, byte) in digests[32..].iter().enumerate() {
+            assert_eq!(public_data.hash_state[8 + i / 8][i % 8], *byte);
+        }
+
+        let public_inputs = public_data.public_input_targets(&mut builder);
+        assert_eq!(
+            public_inputs.len(),
+            8 * (KECCAK_RATE_LANES * KECCAK_NUM_BLOCKS + KECCAK_CYCLE_LENGTH + 4 * 3)
+                + 2 * KECCAK_CYCLE_LENGTH
+                + 3
+        );
+    }
+
+    #[test]
+    fn test_keccak_hint_padded_digest() {
+        type F = GoldilocksField;
+
+        let to_field = |bytes: &[u8]| {
+            bytes
+                .iter()
+                .map(|b| F::from_canonical_u8(*b))
+                .collect::<Vec<_>>()
+        };
+
+        let digest =
+            KeccakHintGenerator::padded_digest(&to_field(&Keccak256Gadget::pad(b"abc"))).unwrap();
+        let expected =
+            hex::decode("4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45")
+                .unwrap();
+        assert_eq!(digest.to_vec(), to_field(&expected));
+
+        // An unpadded message is rejected with its length and the expected block size.
+        let err = KeccakHintGenerator::padded_digest(&to_field(&[0u8; 100])).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("length 100 bytes"), "{}", message);
+        assert!(
+            message.contains("multiple of the 136-byte block size"),
+            "{}",
+            message
+        );
+    }
+}
@@ -0,0 +1,1020 @@
+//! Keccak-256 over 64-bit lanes, as used by Ethereum for addresses and storage keys.
+//!
+//! Keccak-256 is the sponge of the Keccak-f[1600] permutation with a rate of 1088 bits and the
+//! original Keccak padding `0x01 ... 0x80`, which differs from the `0x06` padding of SHA3-256.
+//! The chip also supports the other sponges over Keccak-f[1600] given by a [`KeccakSponge`], such
+//! as the SHA-3 hash functions and the SHAKE128 and SHAKE256 extendable-output functions.
+//!
+//! Every block occupies a cycle of 32 rows. The block is absorbed into the state at the first row
+//! of the cycle, the first 24 rows perform the rounds of the permutation and the remaining rows
+//! carry the state to the end of the cycle, where its first lanes are published. As in the SHA-512
+//! chip, the round constants and the bits marking the rows that load a block and the rows that
+//! perform a round are read from a periodic table carried by the bus.
+//!
+//! An output longer than the rate is squeezed by permuting the state again after the last block of
+//! a message, which is the same as absorbing zero blocks. The squeezed bytes are the published
+//! lanes of the last block of the message and of these zero blocks.
+
+pub mod builder_gadget;
+pub mod generator;
+
+use core::borrow::Borrow;
+
+use serde::{Deserialize, Serialize};
+
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::builder::AirBuilder;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::cubic::CubicRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::{Register, RegisterSerializable, RegisterSized};
+use crate::chip::table::bus::global::Bus;
+use crate::chip::trace::writer::TraceWriter;
+use crate::chip::uint::bytes::lookup_table::builder_operations::ByteLookupOperations;
+use crate::chip::uint::bytes::lookup_table::ByteInstructions;
+use crate::chip::uint::bytes::operations::value::ByteOperation;
+use crate::chip::uint::bytes::register::ByteRegister;
+use crate::chip::uint::register::U64Register;
+use crate::chip::uint::util::u64_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+
+pub type U64Value<T> = <U64Register as Register>::Value<T>;
+
+/// The number of rows used to process a single 136-byte block.
+pub const KECCAK_CYCLE_LENGTH: usize = 32;
+
+/// The number of rounds of the Keccak-f[1600] permutation.
+pub const KECCAK_NUM_ROUNDS: usize = 24;
+
+/// The number of bytes of a block, i.e. the rate of the sponge.
+pub const KECCAK_RATE: usize = 136;
+
+/// The number of lanes of a block.
+pub const KECCAK_RATE_LANES: usize = KECCAK_RATE / 8;
+
+/// The number of lanes of the digest.
+pub const KECCAK_DIGEST_LANES: usize = 4;
+
+/// A sponge over the Keccak-f[1600] permutation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeccakSponge {
+    /// The number of bytes of a block, a multiple of 8 below the 200 bytes of the state
+    pub rate: usize,
+    /// The first byte of the padding: `0x01` for Keccak, `0x06` for SHA-3 and `0x1F` for SHAKE
+    pub domain: u8,
+    /// The number of bytes squeezed out of the sponge
+    pub output_len: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Keccak256Gadget {
+    /// The input blocks processed into the lanes of the rate, 17 for Keccak-256
+    pub public_word: ArrayRegister<U64Register>,
+    /// The first `sponge.output_lanes()` lanes of the state at the end of every block, which are
+    /// the digest at the last block of a message
+    pub state: ArrayRegister<U64Register>,
+    /// The lanes absorbed at the current row, which are zero except at the start of a cycle
+    pub block: ArrayRegister<U64Register>,
+    /// Signifies when to reset the state to zero
+    pub end_bit: BitRegister,
+    /// The sponge computed by the chip
+    pub sponge: KeccakSponge,
+    pub(crate) end_bits_public: ArrayRegister<BitRegister>,
+    pub(crate) round_constant: U64Register,
+    pub(crate) load_bit: BitRegister,
+    pub(crate) round_bit: BitRegister,
+    pub round_constants_public: ArrayRegister<U64Register>,
+    pub(crate) load_bits_public: ArrayRegister<BitRegister>,
+    pub(crate) round_bits_public: ArrayRegister<BitRegister>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Keccak256PublicData<T> {
+    pub public_w: Vec<U64Value<T>>,
+    pub hash_state: Vec<U64Value<T>>,
+    pub end_bits: Vec<T>,
+}
+
+const ROUND_CONSTANTS: [u64; KECCAK_NUM_ROUNDS] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// The left rotation of the lane `x + 5 * y` in the rho step.
+const ROTATION_OFFSETS: [usize; 25] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+/// The index of the lane that the lane `i` is moved to by the pi step, i.e. the lane `(x, y)` is
+/// moved to `(y, 2x + 3y)`.
+const fn pi_index(i: usize) -> usize {
+    let (x, y) = (i % 5, i / 5);
+    y + 5 * ((2 * x + 3 * y) % 5)
+}
+
+impl KeccakSponge {
+    pub fn new(rate: usize, domain: u8, output_len: usize) -> Self {
+        assert!(
+            rate % 8 == 0 && rate > 0 && rate < 200,
+            "The rate must be a positive multiple of 8 below 200"
+        );
+        assert!(output_len > 0, "The output must not be empty");
+        Self {
+            rate,
+            domain,
+            output_len,
+        }
+    }
+
+    pub fn keccak256() -> Self {
+        Self::new(KECCAK_RATE, 0x01, 32)
+    }
+
+    pub fn sha3_224() -> Self {
+        Self::new(144, 0x06, 28)
+    }
+
+    pub fn sha3_256() -> Self {
+        Self::new(136, 0x06, 32)
+    }
+
+    pub fn sha3_384() -> Self {
+        Self::new(104, 0x06, 48)
+    }
+
+    pub fn sha3_512() -> Self {
+        Self::new(72, 0x06, 64)
+    }
+
+    /// SHAKE128, squeezing `output_len` bytes.
+    pub fn shake128(output_len: usize) -> Self {
+        Self::new(168, 0x1F, output_len)
+    }
+
+    /// SHAKE256, squeezing `output_len` bytes.
+    pub fn shake256(output_len: usize) -> Self {
+        Self::new(136, 0x1F, output_len)
+    }
+
+    /// The number of lanes of a block.
+    pub fn rate_lanes(&self) -> usize {
+        self.rate / 8
+    }
+
+    /// The number of lanes of the state published at the end of every block.
+    pub fn output_lanes(&self) -> usize {
+        core::cmp::min(self.rate_lanes(), self.output_len.div_ceil(8))
+    }
+
+    /// The number of zero blocks absorbed after the last block of a message to squeeze the
+    /// output.
+    pub fn num_squeeze_blocks(&self) -> usize {
+        self.output_len.div_ceil(self.rate) - 1
+    }
+
+    /// Pads a message with the domain byte, zeros and a final bit to a multiple of the rate.
+    pub fn pad(&self, msg: &[u8]) -> Vec<u8> {
+        let mut padded_msg = Vec::new();
+        padded_msg.extend_from_slice(msg);
+        padded_msg.push(self.domain);
+
+        // Pad with zeros until the length is a multiple of the rate, and set the last bit
+        let padlen = (self.rate - padded_msg.len() % self.rate) % self.rate;
+        padded_msg.extend_from_slice(&vec![0u8; padlen]);
+        *padded_msg.last_mut().unwrap() |= 0x80;
+
+        padded_msg
+    }
+
+    /// The little endian lanes of a block.
+    pub fn process_inputs(&self, block: &[u8]) -> Vec<u64> {
+        block
+            .chunks_exact(8)
+            .map(|lane| u64::from_le_bytes(lane.try_into().unwrap()))
+            .collect()
+    }
+
+    /// Computes the output of the sponge on `msg` natively.
+    pub fn hash(&self, msg: &[u8]) -> Vec<u8> {
+        let mut state = [0u64; 25];
+        for block in self.pad(msg).chunks_exact(self.rate) {
+            for (lane, block_lane) in state.iter_mut().zip(self.process_inputs(block)) {
+                *lane ^= block_lane;
+            }
+            state = Keccak256Gadget::keccak_f(state);
+        }
+
+        let mut output = Vec::new();
+        loop {
+            output.extend(
+                state[..self.rate_lanes()]
+                    .iter()
+                    .flat_map(|lane| lane.to_le_bytes()),
+            );
+            if output.len() >= self.output_len {
+                break;
+            }
+            state = Keccak256Gadget::keccak_f(state);
+        }
+        output.truncate(self.output_len);
+        output
+    }
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    pub fn process_keccak_256_batch(
+        &mut self,
+        clk: &ElementRegister,
+        bus: &mut Bus<L::CubicParams>,
+        bus_channel_idx: usize,
+        operations: &mut ByteLookupOperations,
+    ) -> Keccak256Gadget
+    where
+        L::Instruction: ByteInstructions,
+    {
+        self.process_keccak_sponge_batch(
+            clk,
+            bus,
+            bus_channel_idx,
+            KeccakSponge::keccak256(),
+            operations,
+        )
+    }
+
+    /// Proves a batch of messages hashed with `sponge`. The messages must fill all the blocks of
+    /// the trace, including the zero blocks which squeeze an output longer than the rate.
+    pub fn process_keccak_sponge_batch(
+        &mut self,
+        clk: &ElementRegister,
+        bus: &mut Bus<L::CubicParams>,
+        bus_channel_idx: usize,
+        sponge: KeccakSponge,
+        operations: &mut ByteLookupOperations,
+    ) -> Keccak256Gadget
+    where
+        L::Instruction: ByteInstructions,
+    {
+        assert_eq!(
+            L::num_rows() % KECCAK_CYCLE_LENGTH,
+            0,
+            "The number of rows must be a multiple of the cycle length"
+        );
+        let num_blocks = L::num_rows() / KECCAK_CYCLE_LENGTH;
+        let rate_lanes = sponge.rate_lanes();
+        let output_lanes = sponge.output_lanes();
+
+        // Registers to be written to
+        let block = self.alloc_array::<U64Register>(rate_lanes);
+        let load_bit = self.alloc::<BitRegister>();
+        let round_bit = self.alloc::<BitRegister>();
+        let end_bit = self.alloc::<BitRegister>();
+        let round_constant = self.alloc::<U64Register>();
+        let cycle_32 = self.cycle(5);
+
+        // Public values
+        let public_w = self.alloc_array_public::<U64Register>(rate_lanes * num_blocks);
+        let round_constants_public = self.alloc_array_public::<U64Register>(KECCAK_CYCLE_LENGTH);
+        let load_bits_public = self.alloc_array_public::<BitRegister>(KECCAK_CYCLE_LENGTH);
+        let round_bits_public = self.alloc_array_public::<BitRegister>(KECCAK_CYCLE_LENGTH);
+        let hash_state = self.alloc_array_public::<U64Register>(output_lanes * num_blocks);
+        let end_bits_public = self.alloc_array_public::<BitRegister>(num_blocks);
+
+        // Get the block from the bus at the start of every cycle, and set it to zero elsewhere
+        let block_challenges =
+            self.alloc_challenge_array::<CubicRegister>(U64Register::size_of() * rate_lanes + 1);
+        let clk_block = self.accumulate_expressions(&block_challenges, &[clk.expr(), block.expr()]);
+        self.output_from_bus_filtered(bus_channel_idx, clk_block, load_bit.expr());
+        for lane in block.iter() {
+            self.assert_expression_zero(lane.expr() * load_bit.not_expr());
+        }
+
+        // Get hash state challenges
+        let state_challenges =
+            self.alloc_challenge_array::<CubicRegister>(U64Register::size_of() * output_lanes + 1);
+
+        // Get a challenge for the end bit
+        let end_bit_challenge = self.alloc_challenge_array::<CubicRegister>(2);
+
+        // Put the end_bit in the bus at the end of each block
+        let clk_end_bit =
+            self.accumulate_expressions(&end_bit_challenge, &[clk.expr(), end_bit.expr()]);
+        self.input_to_bus_filtered(bus_channel_idx, clk_end_bit, cycle_32.end_bit.expr());
+        // Constrain all other values of end_bit to zero
+        self.assert_expression_zero(end_bit.expr() * cycle_32.end_bit.not_expr());
+
+        // Put public blocks and hash states in the bus
+        for i in 0..num_blocks {
+            let block_end = i * KECCAK_CYCLE_LENGTH + KECCAK_CYCLE_LENGTH - 1;
+            let state_digest = self.accumulate_public_expressions(
+                &state_challenges,
+                &[
+                    ArithmeticExpression::from_constant(L::Field::from_canonical_usize(block_end)),
+                    hash_state
+                        .get_subarray(i * output_lanes..(i + 1) * output_lanes)
+                        .expr(),
+                ],
+            );
+            bus.output_global_value(&state_digest);
+
+            let bit_digest = self.accumulate_public_expressions(
+                &end_bit_challenge,
+                &[
+                    ArithmeticExpression::from_constant(L::Field::from_canonical_usize(block_end)),
+                    end_bits_public.get(i).expr(),
+                ],
+            );
+            bus.output_global_value(&bit_digest);
+
+            let clk_expr = ArithmeticExpression::from_constant(L::Field::from_canonical_usize(
+                i * KECCAK_CYCLE_LENGTH,
+            ));
+            let lanes = public_w.get_subarray(i * rate_lanes..(i + 1) * rate_lanes);
+            let digest =
+                self.accumulate_public_expressions(&block_challenges, &[clk_expr, lanes.expr()]);
+            bus.insert_global_value(&digest);
+        }
+
+        // Put the periodic table of round constants and row flags into the bus. Every row reads
+        // the entry of the row one cycle before it and passes it on to the row one cycle after.
+        let round_constant_challenges =
+            self.alloc_challenge_array::<CubicRegister>(U64Register::size_of() + 3);
+
+        for k in 0..KECCAK_CYCLE_LENGTH {
+            let entry = [
+                round_constants_public.get(k).expr(),
+                load_bits_public.get(k).expr(),
+                round_bits_public.get(k).expr(),
+            ];
+            let round_constant_public_input_digest = self.accumulate_public_expressions(
+                &round_constant_challenges,
+                &[
+                    &[ArithmeticExpression::from_constant(
+                        L::Field::from_canonical_usize(k)
+                            - L::Field::from_canonical_usize(KECCAK_CYCLE_LENGTH),
+                    )],
+                    &entry[..],
+                ]
+                .concat(),
+            );
+            bus.insert_global_value(&round_constant_public_input_digest);
+
+            let round_constants_public_output_digest = self.accumulate_public_expressions(
+                &round_constant_challenges,
+                &[
+                    &[ArithmeticExpression::from_constant(
+                        L::Field::from_canonical_usize(L::num_rows() - KECCAK_CYCLE_LENGTH + k),
+                    )],
+                    &entry[..],
+                ]
+                .concat(),
+            );
+            bus.output_global_value(&round_constants_public_output_digest);
+        }
+
+        let round_constant_output = self.accumulate_expressions(
+            &round_constant_challenges,
+            &[
+                clk.expr() - L::Field::from_canonical_usize(KECCAK_CYCLE_LENGTH),
+                round_constant.expr(),
+                load_bit.expr(),
+                round_bit.expr(),
+            ],
+        );
+        self.output_from_bus(bus_channel_idx, round_constant_output);
+
+        let round_constant_input = self.accumulate_expressions(
+            &round_constant_challenges,
+            &[
+                clk.expr(),
+                round_constant.expr(),
+                load_bit.expr(),
+                round_bit.expr(),
+            ],
+        );
+        self.input_to_bus(bus_channel_idx, round_constant_input);
+
+        // The state starts at zero
+        let state = self.alloc_array::<U64Register>(25);
+        for lane in state.iter() {
+            self.set_to_expression_first_row(
+                &lane,
+                ArithmeticExpression::from_constant_vec(vec![L::Field::ZERO; 8]),
+            );
+        }
+
+        // Absorb the block, which is zero outside of the load rows, and apply a round
+        let mut input = state.iter().collect::<Vec<_>>();
+        for (lane, block_lane) in input.iter_mut().zip(block.iter()) {
+            *lane = self.bitwise_xor(lane, &block_lane, operations);
+        }
+        let output = self.keccak_round(&input, &round_constant, operations);
+
+        // Assign next values to the next row registers: apply a round in the round rows, keep
+        // the state in the remaining rows, and reset it at the end of the last block of a message.
+        let bit = cycle_32.end_bit;
+        for (lane, lane_next) in state.iter().zip(output.iter()) {
+            self.set_to_expression_transition(
+                &lane.next(),
+                lane_next.expr() * round_bit.expr()
+                    + lane.expr() * (round_bit.not_expr() - bit.expr() * end_bit.expr()),
+            );
+        }
+
+        let clk_state = self.accumulate_expressions(
+            &state_challenges,
+            &[clk.expr(), state.get_subarray(0..output_lanes).expr()],
+        );
+        self.input_to_bus_filtered(bus_channel_idx, clk_state, cycle_32.end_bit.expr());
+
+        // The byte lookup needs an even number of operations
+        if operations.values.len() % 2 == 1 {
+            let dummy = self.alloc::<ByteRegister>();
+            let dummy_range = ByteOperation::Range(dummy);
+            self.set_byte_operation(&dummy_range, operations);
+        }
+
+        Keccak256Gadget {
+            public_word: public_w,
+            state: hash_state,
+            block,
+            end_bit,
+            sponge,
+            end_bits_public,
+            round_constant,
+            load_bit,
+            round_bit,
+            round_constants_public,
+            load_bits_public,
+            round_bits_public,
+        }
+    }
+
+    /// Applies a round of the Keccak-f[1600] permutation to the 25 lanes of `state`.
+    fn keccak_round(
+        &mut self,
+        state: &[U64Register],
+        round_constant: &U64Register,
+        operations: &mut ByteLookupOperations,
+    ) -> Vec<U64Register>
+    where
+        L::Instruction: ByteInstructions,
+    {
+        // Theta: c[x] = a[x, 0] ^ ... ^ a[x, 4] and d[x] = c[x - 1] ^ c[x + 1].rotate_left(1)
+        let c = (0..5)
+            .map(|x| {
+                let mut column = state[x];
+                for y in 1..5 {
+                    column = self.bitwise_xor(&column, &state[x + 5 * y], operations);
+                }
+                column
+            })
+            .collect::<Vec<_>>();
+        let d = (0..5)
+            .map(|x| {
+                let rotated = self.bit_rotate_right(&c[(x + 1) % 5], 63, operations);
+                self.bitwise_xor(&c[(x + 4) % 5], &rotated, operations)
+            })
+            .collect::<Vec<_>>();
+
+        // Rho and pi: b[y, 2x + 3y] = (a[x, y] ^ d[x]).rotate_left(r[x, y])
+        let mut b = state.to_vec();
+        for (i, lane) in state.iter().enumerate() {
+            let a = self.bitwise_xor(lane, &d[i % 5], operations);
+            b[pi_index(i)] = match ROTATION_OFFSETS[i] {
+                0 => a,
+                offset => self.bit_rotate_right(&a, 64 - offset, operations),
+            };
+        }
+
+        // Chi: a[x, y] = b[x, y] ^ (!b[x + 1, y] & b[x + 2, y])
+        let mut output = (0..25)
+            .map(|i| {
+                let (x, y) = (i % 5, i / 5);
+                let not_next = self.bitwise_not(&b[(x + 1) % 5 + 5 * y], operations);
+                let and = self.bitwise_and(&not_next, &b[(x + 2) % 5 + 5 * y], operations);
+                self.bitwise_xor(&b[i], &and, operations)
+            })
+            .collect::<Vec<_>>();
+
+        // Iota: a[0, 0] ^= round_constant
+        output[0] = self.bitwise_xor(&output[0], round_constant, operations);
+
+        output
+    }
+}
+
+impl Keccak256Gadget {
+    /// Writes the blocks of `padded_messages`, each followed by the zero blocks which squeeze its
+    /// output.
+    pub fn write<F: Field, I: IntoIterator>(
+        &self,
+        padded_messages: I,
+        writer: &TraceWriter<F>,
+    ) -> Keccak256PublicData<F>
+    where
+        I::Item: Borrow<[u8]>,
+    {
+        let mut blocks = Vec::new();
+        let mut end_bits_values = Vec::new();
+        let mut hash_values = Vec::new();
+        let mut public_w_values = Vec::new();
+
+        let sponge = self.sponge;
+        padded_messages.into_iter().for_each(|padded_msg| {
+            let padded_msg = padded_msg.borrow();
+            let num_blocks = padded_msg.len() / sponge.rate + sponge.num_squeeze_blocks();
+            end_bits_values.extend_from_slice(&vec![F::ZERO; num_blocks - 1]);
+            end_bits_values.push(F::ONE);
+
+            let squeeze_blocks = core::iter::repeat(vec![0u64; sponge.rate_lanes()])
+                .take(sponge.num_squeeze_blocks());
+            let mut state = [0u64; 25];
+            for lanes in padded_msg
+                .chunks_exact(sponge.rate)
+                .map(|block| sponge.process_inputs(block))
+                .chain(squeeze_blocks)
+            {
+                public_w_values.extend(lanes.iter().map(|x| u64_to_le_field_bytes::<F>(*x)));
+                for (lane, block_lane) in state.iter_mut().zip(lanes.iter()) {
+                    *lane ^= block_lane;
+                }
+                state = Keccak256Gadget::keccak_f(state);
+                blocks.push(lanes);
+                hash_values.extend(
+                    state[..sponge.output_lanes()]
+                        .iter()
+                        .map(|x| u64_to_le_field_bytes::<F>(*x)),
+                );
+            }
+        });
+        let num_blocks = self.end_bits_public.len();
+        assert!(
+            blocks.len() == num_blocks,
+            "Padded messages lengths do not add up"
+        );
+
+        let round_constants: [u64; KECCAK_CYCLE_LENGTH] =
+            core::array::from_fn(|j| ROUND_CONSTANTS.get(j).copied().unwrap_or(0));
+        let load_bits: [F; KECCAK_CYCLE_LENGTH] =
+            core::array::from_fn(|j| F::from_canonical_u8((j == 0) as u8));
+        let round_bits: [F; KECCAK_CYCLE_LENGTH] =
+            core::array::from_fn(|j| F::from_canonical_u8((j < KECCAK_NUM_ROUNDS) as u8));
+
+        writer.write_array(
+            &self.round_constants_public,
+            round_constants.map(u64_to_le_field_bytes),
+            0,
+        );
+        writer.write_array(&self.load_bits_public, load_bits, 0);
+        writer.write_array(&self.round_bits_public, round_bits, 0);
+        writer.write_array(&self.state, &hash_values, 0);
+        writer.write_array(&self.end_bits_public, &end_bits_values, 0);
+        writer.write_array(&self.public_word, &public_w_values, 0);
+        (0..num_blocks).for_each(|i| {
+            writer.write(
+                &self.end_bit,
+                &end_bits_values[i],
+                i * KECCAK_CYCLE_LENGTH + KECCAK_CYCLE_LENGTH - 1,
+            );
+            let rows = round_constants
+                .iter()
+                .zip(load_bits.iter())
+                .zip(round_bits.iter())
+                .enumerate();
+            for (j, ((round_constant, load_bit), round_bit)) in rows {
+                let row = i * KECCAK_CYCLE_LENGTH + j;
+                writer.write(
+                    &self.round_constant,
+                    &u64_to_le_field_bytes(*round_constant),
+                    row,
+                );
+                writer.write(&self.load_bit, load_bit, row);
+                writer.write(&self.round_bit, round_bit, row);
+                let lanes = if j == 0 {
+                    blocks[i].clone()
+                } else {
+                    vec![0; sponge.rate_lanes()]
+                };
+                writer.write_array(
+                    &self.block,
+                    lanes.into_iter().map(u64_to_le_field_bytes),
+                    row,
+                );
+            }
+        });
+
+        Keccak256PublicData {
+            public_w: public_w_values,
+            hash_state: hash_values,
+            end_bits: end_bits_values,
+        }
+    }
+
+    /// The published lanes which hold the output of a message whose last block has index
+    /// `last_block`. They span the zero blocks which follow it, and the output is the first
+    /// `sponge.output_len` bytes of the lanes.
+    pub fn output_lanes(&self, last_block: usize) -> ArrayRegister<U64Register> {
+        let start = self.sponge.output_lanes() * last_block;
+        self.state
+            .get_subarray(start..start + self.sponge.output_len.div_ceil(8))
+    }
+
+    pub fn process_inputs(block: &[u8]) -> [u64; KECCAK_RATE_LANES] {
+        core::array::from_fn(|i| u64::from_le_bytes(block[8 * i..8 * i + 8].try_into().unwrap()))
+    }
+
+    /// Absorbs the lanes of a block into the state and applies the permutation.
+    pub fn absorb(mut state: [u64; 25], lanes: &[u64; KECCAK_RATE_LANES]) -> [u64; 25] {
+        for (lane, block_lane) in state.iter_mut().zip(lanes.iter()) {
+            *lane ^= block_lane;
+        }
+        Self::keccak_f(state)
+    }
+
+    /// The Keccak-f[1600] permutation.
+    pub fn keccak_f(mut state: [u64; 25]) -> [u64; 25] {
+        for round_constant in ROUND_CONSTANTS {
+            state = Self::round(state, round_constant);
+        }
+        state
+    }
+
+    pub fn round(state: [u64; 25], round_constant: u64) -> [u64; 25] {
+        let c: [u64; 5] = core::array::from_fn(|x| {
+            state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20]
+        });
+        let d: [u64; 5] = core::array::from_fn(|x| c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1));
+
+        let mut b = [0u64; 25];
+        for (i, lane) in state.iter().enumerate() {
+            b[pi_index(i)] = (lane ^ d[i % 5]).rotate_left(ROTATION_OFFSETS[i] as u32);
+        }
+
+        let mut output: [u64; 25] = core::array::from_fn(|i| {
+            let (x, y) = (i % 5, i / 5);
+            b[i] ^ (!b[(x + 1) % 5 + 5 * y] & b[(x + 2) % 5 + 5 * y])
+        });
+        output[0] ^= round_constant;
+        output
+    }
+
+    pub fn pad(msg: &[u8]) -> Vec<u8> {
+        KeccakSponge::keccak256().pad(msg)
+    }
+
+    /// Computes the Keccak-256 digest of `msg` natively.
+    pub fn hash(msg: &[u8]) -> [u8; 32] {
+        let mut state = [0u64; 25];
+        for block in Self::pad(msg).chunks_exact(KECCAK_RATE) {
+            state = Self::absorb(state, &Self::process_inputs(block));
+        }
+        state[..KECCAK_DIGEST_LANES]
+            .iter()
+            .flat_map(|lane| lane.to_le_bytes())
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::timed;
+    use plonky2::util::timing::TimingTree;
+
+    use super::*;
+    pub use crate::chip::builder::tests::*;
+    use crate::chip::builder::AirBuilder;
+    use crate::chip::uint::bytes::lookup_table::ByteInstructionSet;
+    use crate::chip::AirParameters;
+
+    const EMPTY_DIGEST: &str = "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470";
+    const ABC_DIGEST: &str = "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45";
+    /// The digest of 200 bytes `a`, which span two blocks.
+    const LONG_DIGEST: &str = "96ea54061def936c4be90b518992fdc6f12f535068a256229aca54267b4d084d";
+
+    /// SHAKE128 of `abc` squeezed to 200 bytes, which is longer than the rate of 168 bytes.
+    const SHAKE128_ABC: &str = concat!(
+        "5881092dd818bf5cf8a3ddb793fbcba74097d5c526a6d35f97b83351940f2cc844c50af32acd3f2c",
+        "dd066568706f509bc1bdde58295dae3f891a9a0fca5783789a41f8611214ce612394df286a62d1a2",
+        "252aa94db9c538956c717dc2bed4f232a0294c857c730aa16067ac1062f1201fb0d377cfb9cde4c6",
+        "3599b27f3462bba4a0ed296c801f9ff7f57302bb3076ee145f97a32ae68e76ab66c48d51675bd49a",
+        "cc29082f5647584e6aa01b3f5af057805f973ff8ecb8b226ac32ada6f01c1fcd4818cb006aa5b4cd",
+    );
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub struct Keccak256Test;
+
+    impl AirParameters for Keccak256Test {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = ByteInstructionSet;
+
+        const NUM_FREE_COLUMNS: usize = 2600;
+        const EXTENDED_COLUMNS: usize = 8000;
+        const NUM_ARITHMETIC_COLUMNS: usize = 0;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    #[test]
+    fn test_keccak_256_native_hash() {
+        assert_eq!(hex::encode(Keccak256Gadget::hash(b"")), EMPTY_DIGEST);
+        assert_eq!(hex::encode(Keccak256Gadget::hash(b"abc")), ABC_DIGEST);
+        assert_eq!(
+            hex::encode(Keccak256Gadget::hash(&[0x61; 200])),
+            LONG_DIGEST
+        );
+
+        // A message one byte short of a block is padded with the single byte 0x81.
+        let padded = Keccak256Gadget::pad(&[0x61; KECCAK_RATE - 1]);
+        assert_eq!(padded.len(), KECCAK_RATE);
+        assert_eq!(padded[KECCAK_RATE - 1], 0x81);
+    }
+
+    #[test]
+    fn test_keccak_sponge_native_hash() {
+        let sha3 = [
+            (
+                KeccakSponge::sha3_224(),
+                "e642824c3f8cf24ad09234ee7d3c766fc9a3a5168d0c94ad73b46fdf",
+            ),
+            (
+                KeccakSponge::sha3_256(),
+                "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532",
+            ),
+            (
+                KeccakSponge::sha3_384(),
+                concat!(
+                    "ec01498288516fc926459f58e2c6ad8df9b473cb0fc08c2596da7cf0e49be4b2",
+                    "98d88cea927ac7f539f1edf228376d25"
+                ),
+            ),
+            (
+                KeccakSponge::sha3_512(),
+                concat!(
+                    "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e",
+                    "10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0"
+                ),
+            ),
+            (
+                KeccakSponge::shake256(64),
+                concat!(
+                    "483366601360a8771c6863080cc4114d8db44530f8f1e1ee4f94ea37e78b5739",
+                    "d5a15bef186a5386c75744c0527e1faa9f8726e462a12a4feb06bd8801e751e4"
+                ),
+            ),
+            (KeccakSponge::shake128(200), SHAKE128_ABC),
+        ];
+        for (sponge, digest) in sha3 {
+            assert_eq!(hex::encode(sponge.hash(b"abc")), digest);
+        }
+
+        assert_eq!(
+            hex::encode(KeccakSponge::sha3_256().hash(b"")),
+            "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a"
+        );
+        assert_eq!(
+            hex::encode(KeccakSponge::shake128(32).hash(b"")),
+            "7f9c2ba4e88f827d616045507605853ed73b8093f6efbc88eb1a6eacfa66ef26"
+        );
+        assert_eq!(
+            KeccakSponge::keccak256().hash(&[0x61; 200]),
+            Keccak256Gadget::hash(&[0x61; 200])
+        );
+
+        // The domain byte and the final bit of the padding share a byte when the message is one
+        // byte short of a block.
+        let sponge = KeccakSponge::shake128(32);
+        let padded = sponge.pad(&[0x61; 167]);
+        assert_eq!(padded.len(), 168);
+        assert_eq!(padded[167], 0x9F);
+        assert_eq!(KeccakSponge::shake128(200).num_squeeze_blocks(), 1);
+        assert_eq!(KeccakSponge::shake128(200).output_lanes(), 21);
+        assert_eq!(KeccakSponge::sha3_224().output_lanes(), 4);
+    }
+
+    #[test]
+    fn test_keccak_256_stark() {
+        type F = GoldilocksField;
+        type L = Keccak256Test;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut timing = TimingTree::new("Keccak256 test", log::Level::Debug);
+
+        let mut builder = AirBuilder::<L>::new();
+        let clk = builder.clock();
+
+        let (mut operations, table) = builder.byte_operations();
+
+        let mut bus = builder.new_bus();
+        let channel_idx = bus.new_channel(&mut builder);
+
+        let keccak_gadget =
+            builder.process_keccak_256_batch(&clk, &mut bus, channel_idx, &mut operations);
+
+        builder.register_byte_lookup(operations, &table);
+        builder.constrain_bus(bus);
+
+        let (air, trace_data) = builder.build();
+
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+        let writer = generator.new_writer();
+
+        // The single block messages together with a two block message fill the 2048 blocks of
+        // the trace.
+        let mut messages = (0..1023)
+            .flat_map(|_| [b"".to_vec(), b"abc".to_vec()])
+            .collect::<Vec<_>>();
+        messages.push(vec![0x61; 200]);
+        let padded_messages = messages
+            .iter()
+            .map(|m| Keccak256Gadget::pad(m))
+            .collect::<Vec<_>>();
+
+        let expected_digests: Vec<[u64; 4]> = (0..1023)
+            .flat_map(|_| [EMPTY_DIGEST, ABC_DIGEST])
+            .chain([LONG_DIGEST])
+            .map(|digest| {
+                hex::decode(digest)
+                    .unwrap()
+                    .chunks_exact(8)
+                    .map(|x| u64::from_le_bytes(x.try_into().unwrap()))
+                    .collect::<Vec<_>>()
+                    .try_into()
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let mut digest_iter = expected_digests.into_iter();
+        timed!(timing, "Write the execution trace", {
+            table.write_table_entries(&writer);
+            keccak_gadget.write(padded_messages, &writer);
+            for i in 0..L::num_rows() {
+                writer.write_row_instructions(&generator.air_data, i);
+                let end_bit = writer.read(&keccak_gadget.end_bit, i);
+                if end_bit == F::ONE {
+                    let j = i / KECCAK_CYCLE_LENGTH;
+                    let hash = writer.read_array(
+                        &keccak_gadget
+                            .state
+                            .get_subarray(j * KECCAK_DIGEST_LANES..(j + 1) * KECCAK_DIGEST_LANES),
+                        0,
+                    );
+                    let digest = digest_iter.next().unwrap();
+                    assert_eq!(hash, digest.map(u64_to_le_field_bytes));
+                }
+            }
+            table.write_multiplicities(&writer);
+        });
+        assert!(digest_iter.next().is_none());
+
+        let public_inputs = writer.0.public.read().unwrap().clone();
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        timed!(
+            timing,
+            "Stark proof and verify",
+            test_starky(&stark, &config, &generator, &public_inputs)
+        );
+
+        // Generate recursive proof
+        timed!(
+            timing,
+            "Recursive proof generation and verification",
+            test_recursive_starky(stark, config, generator, &public_inputs)
+        );
+
+        timing.print();
+    }
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub struct Shake128Test;
+
+    impl AirParameters for Shake128Test {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = ByteInstructionSet;
+
+        const NUM_FREE_COLUMNS: usize = 2700;
+        const EXTENDED_COLUMNS: usize = 8300;
+        const NUM_ARITHMETIC_COLUMNS: usize = 0;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    #[test]
+    fn test_shake_128_stark() {
+        type L = Shake128Test;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut timing = TimingTree::new("SHAKE128 test", log::Level::Debug);
+
+        let sponge = KeccakSponge::shake128(200);
+
+        let mut builder = AirBuilder::<L>::new();
+        let clk = builder.clock();
+
+        let (mut operations, table) = builder.byte_operations();
+
+        let mut bus = builder.new_bus();
+        let channel_idx = bus.new_channel(&mut builder);
+
+        let keccak_gadget = builder.process_keccak_sponge_batch(
+            &clk,
+            &mut bus,
+            channel_idx,
+            sponge,
+            &mut operations,
+        );
+
+        builder.register_byte_lookup(operations, &table);
+        builder.constrain_bus(bus);
+
+        let (air, trace_data) = builder.build();
+
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+        let writer = generator.new_writer();
+
+        // Every message takes a block and a squeeze block, so 1024 messages fill the trace.
+        let messages = (0..512)
+            .flat_map(|_| [b"".to_vec(), b"abc".to_vec()])
+            .collect::<Vec<_>>();
+        let padded_messages = messages.iter().map(|m| sponge.pad(m)).collect::<Vec<_>>();
+
+        timed!(timing, "Write the execution trace", {
+            table.write_table_entries(&writer);
+            keccak_gadget.write(padded_messages, &writer);
+            for i in 0..L::num_rows() {
+                writer.write_row_instructions(&generator.air_data, i);
+            }
+            table.write_multiplicities(&writer);
+        });
+
+        for (k, message) in messages.iter().enumerate() {
+            let output = writer.read_array(&keccak_gadget.output_lanes(2 * k), 0);
+            let expected = sponge
+                .process_inputs(&sponge.hash(message))
+                .into_iter()
+                .map(u64_to_le_field_bytes)
+                .collect::<Vec<_>>();
+            assert_eq!(output, expected);
+        }
+
+        let public_inputs = writer.0.public.read().unwrap().clone();
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        timed!(
+            timing,
+            "Stark proof and verify",
+            test_starky(&stark, &config, &generator, &public_inputs)
+        );
+
+        // Generate recursive proof
+        timed!(
+            timing,
+            "Recursive proof generation and verification",
+            test_recursive_starky(stark, config, generator, &public_inputs)
+        );
+
+        timing.print();
+    }
+}
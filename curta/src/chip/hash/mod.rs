@@ -1 +1,15 @@
+#[cfg(feature = "plonky2")]
+pub mod anemoi;
+pub mod blake;
+pub mod blake2s;
+pub mod blake3;
+pub mod keccak;
+pub mod pedersen;
+#[cfg(feature = "plonky2")]
+pub mod poseidon;
+#[cfg(feature = "plonky2")]
+pub mod poseidon2;
+#[cfg(feature = "plonky2")]
+pub mod rescue;
+pub mod ripemd160;
 pub mod sha;
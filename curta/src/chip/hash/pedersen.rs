@@ -0,0 +1,443 @@
+//! Zcash-style Pedersen hash over twisted Edwards curves.
+//!
+//! The message bits are split into segments of at most `PEDERSEN_SEGMENT_CHUNKS` chunks of three
+//! bits each. A chunk `(s0, s1, s2)` at position `j` of segment `i` is encoded as
+//!
+//! enc(s0, s1, s2) = (1 - 2 * s2) * (1 + s0 + 2 * s1)
+//!
+//! and the hash is given by `sum_i [sum_j enc(m_j) * 2^(4 * j)] * I_i`, where `I_i` is the
+//! generator of segment `i`.
+//!
+//! Reference: https://zips.z.cash/protocol/protocol.pdf (section 5.4.1.7)
+
+use num::{BigUint, Num};
+use serde::{Deserialize, Serialize};
+
+use crate::chip::builder::AirBuilder;
+use crate::chip::ec::edwards::jubjub::Jubjub;
+use crate::chip::ec::edwards::EdwardsParameters;
+use crate::chip::ec::gadget::{EllipticCurveGadget, EllipticCurveWriter};
+use crate::chip::ec::point::{AffinePoint, AffinePointRegister};
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::trace::writer::TraceWriter;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+
+/// The number of three-bit chunks in a segment of the message.
+pub const PEDERSEN_SEGMENT_CHUNKS: usize = 63;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PedersenHashGadget<E: EdwardsParameters> {
+    /// The message bits, a multiple of three.
+    pub bits: ArrayRegister<BitRegister>,
+    /// For every chunk, the public multiples `[1, 2, 3, 4] * 2^(4 * j) * I_i` of its generator.
+    pub windows: Vec<[AffinePointRegister<E>; 4]>,
+    pub result: AffinePointRegister<E>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Computes the Pedersen hash of `bits` by adding, for every chunk, the window point selected
+    /// by the chunk bits.
+    pub fn pedersen_hash<E: EdwardsParameters>(
+        &mut self,
+        bits: &ArrayRegister<BitRegister>,
+    ) -> PedersenHashGadget<E>
+    where
+        L::Instruction: FromFieldInstruction<E::BaseField>,
+    {
+        assert!(!bits.is_empty(), "Cannot hash an empty message");
+        assert_eq!(
+            bits.len() % 3,
+            0,
+            "Message must be padded to a multiple of 3 bits"
+        );
+
+        // The constant -1 mod p, used to negate the x-coordinate of a point.
+        let mut minus_one = E::BaseField::MODULUS;
+        minus_one[0] -= 1;
+
+        let mut windows = Vec::new();
+        let mut result: Option<AffinePointRegister<E>> = None;
+        for k in 0..bits.len() / 3 {
+            let (s_0, s_1, s_2) = (bits.get(3 * k), bits.get(3 * k + 1), bits.get(3 * k + 2));
+            let window: [AffinePointRegister<E>; 4] =
+                core::array::from_fn(|_| self.alloc_public_ec_point());
+
+            // Select the multiple `1 + s0 + 2 * s1` of the window base.
+            let low = self.select_point(&s_0, &window[1], &window[0]);
+            let high = self.select_point(&s_0, &window[3], &window[2]);
+            let point = self.select_point(&s_1, &high, &low);
+
+            // Negate the point if `s2` is set.
+            let neg_x = self.fp_mul_const(&point.x, minus_one).result;
+            let x = self.select(&s_2, &neg_x, &point.x);
+            let point = AffinePointRegister::new(x, point.y);
+
+            result = match result {
+                None => Some(point),
+                Some(acc) => Some(self.ed_add(&acc, &point).result),
+            };
+            windows.push(window);
+        }
+
+        PedersenHashGadget {
+            bits: *bits,
+            windows,
+            result: result.unwrap(),
+        }
+    }
+}
+
+impl<F: PrimeField64> TraceWriter<F> {
+    /// Writes the public window points of the gadget derived from the segment generators.
+    pub fn write_pedersen_windows<E: EdwardsParameters>(
+        &self,
+        gadget: &PedersenHashGadget<E>,
+        generators: &[AffinePoint<E>],
+    ) {
+        let windows = pedersen_windows(generators, gadget.windows.len());
+        for (window, values) in gadget.windows.iter().zip(windows.iter()) {
+            for (point, value) in window.iter().zip(values.iter()) {
+                self.write_ec_point(point, value, 0);
+            }
+        }
+    }
+}
+
+/// Computes the multiples `[1, 2, 3, 4] * 2^(4 * j) * I_i` for the first `num_chunks` chunks.
+pub fn pedersen_windows<E: EdwardsParameters>(
+    generators: &[AffinePoint<E>],
+    num_chunks: usize,
+) -> Vec<[AffinePoint<E>; 4]> {
+    assert!(
+        num_chunks <= generators.len() * PEDERSEN_SEGMENT_CHUNKS,
+        "Not enough generators for {num_chunks} chunks"
+    );
+    (0..num_chunks)
+        .map(|k| {
+            let mut base = generators[k / PEDERSEN_SEGMENT_CHUNKS].clone();
+            for _ in 0..4 * (k % PEDERSEN_SEGMENT_CHUNKS) {
+                base = &base + &base;
+            }
+            let base_2 = &base + &base;
+            let base_3 = &base_2 + &base;
+            let base_4 = &base_2 + &base_2;
+            [base, base_2, base_3, base_4]
+        })
+        .collect()
+}
+
+/// The first three generators `I_i = FindGroupHash("Zcash_PH", i)` of the Sapling Pedersen hash,
+/// enough for messages of up to `3 * 3 * PEDERSEN_SEGMENT_CHUNKS` bits such as the inputs of the
+/// Sapling `MerkleCRH`.
+pub fn zcash_pedersen_generators() -> Vec<AffinePoint<Jubjub>> {
+    [
+        (
+            "73c016a42ded9578b5ea25de7ec0e3782f0c718f6f0fbadd194e42926f661b51",
+            "289e87a2d3521b5779c9166b837edc5ef9472e8bc04e463277bfabd432243cca",
+        ),
+        (
+            "15a36d1f0f390d8852a35a8c1908dd87a361ee3fd48fdf77b9819dc82d90607e",
+            "015d8c7f5b43fe33f7891142c001d9251f3abeeb98fad3e87b0dc53c4ebf1891",
+        ),
+        (
+            "664321a58246e2f6eb69ae39f5c84210bae8e5c46641ae5c76d6f7c2b67fc475",
+            "362e1500d24eee9ee000a46c8e8ce8538bb22a7f1784b49880ed502c9793d457",
+        ),
+    ]
+    .iter()
+    .map(|(u, v)| {
+        AffinePoint::new(
+            BigUint::from_str_radix(u, 16).unwrap(),
+            BigUint::from_str_radix(v, 16).unwrap(),
+        )
+    })
+    .collect()
+}
+
+/// Computes the Pedersen hash of `bits`, padded with zeros to a multiple of three bits.
+pub fn pedersen_hash_native<E: EdwardsParameters>(
+    bits: &[bool],
+    generators: &[AffinePoint<E>],
+) -> AffinePoint<E> {
+    let mut padded_bits = bits.to_vec();
+    padded_bits.resize(bits.len().div_ceil(3) * 3, false);
+
+    let windows = pedersen_windows(generators, padded_bits.len() / 3);
+    padded_bits
+        .chunks_exact(3)
+        .zip(windows)
+        .map(|(chunk, window)| {
+            let point = window[chunk[0] as usize + 2 * chunk[1] as usize].clone();
+            if chunk[2] {
+                -point
+            } else {
+                point
+            }
+        })
+        .fold(E::neutral(), |acc, point| &acc + &point)
+}
+
+/// Computes the scalar `sum_j enc(m_j) * 2^(4 * j)` of a segment of at most
+/// `PEDERSEN_SEGMENT_CHUNKS` chunks, reduced modulo the order of the prime group.
+pub fn pedersen_segment_scalar<E: EdwardsParameters>(bits: &[bool]) -> BigUint {
+    assert!(bits.len() <= 3 * PEDERSEN_SEGMENT_CHUNKS);
+    let order = E::prime_group_order();
+    let mut padded_bits = bits.to_vec();
+    padded_bits.resize(bits.len().div_ceil(3) * 3, false);
+
+    padded_bits
+        .chunks_exact(3)
+        .enumerate()
+        .fold(BigUint::from(0u32), |acc, (j, chunk)| {
+            let magnitude = BigUint::from(1u32 + chunk[0] as u32 + 2 * chunk[1] as u32) << (4 * j);
+            if chunk[2] {
+                (acc + &order - magnitude % &order) % &order
+            } else {
+                (acc + magnitude) % &order
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::ec::edwards::ed25519::{Ed25519, Ed25519BaseField};
+    use crate::chip::ec::weierstrass::bls12_381::Bls12381ScalarField;
+    use crate::chip::field::instruction::FpInstruction;
+    use crate::chip::utils::biguint_to_bits_le;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    pub struct PedersenHashTest;
+
+    impl AirParameters for PedersenHashTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 1144;
+        const NUM_FREE_COLUMNS: usize = 8;
+        const EXTENDED_COLUMNS: usize = 1725;
+        type Instruction = FpInstruction<Ed25519BaseField>;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    pub struct PedersenJubjubTest;
+
+    impl AirParameters for PedersenJubjubTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 1144;
+        const NUM_FREE_COLUMNS: usize = 8;
+        const EXTENDED_COLUMNS: usize = 1725;
+        type Instruction = FpInstruction<Bls12381ScalarField>;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    /// The Sapling `MerkleCRH(layer, left, right)`, returning the `u`-coordinate of the hash as
+    /// 32 little-endian bytes.
+    fn sapling_merkle_crh(layer: u8, left: &BigUint, right: &BigUint) -> Vec<u8> {
+        let mut bits = biguint_to_bits_le(&BigUint::from(layer), 6);
+        bits.extend(biguint_to_bits_le(left, 255));
+        bits.extend(biguint_to_bits_le(right, 255));
+
+        let mut bytes = pedersen_hash_native(&bits, &zcash_pedersen_generators())
+            .x
+            .to_bytes_le();
+        bytes.resize(32, 0);
+        bytes
+    }
+
+    #[test]
+    fn test_zcash_pedersen_generators() {
+        type E = Jubjub;
+        let order = E::prime_group_order();
+        for generator in zcash_pedersen_generators() {
+            assert_ne!(generator, E::neutral());
+            assert_eq!(&generator * &order, E::neutral());
+        }
+    }
+
+    #[test]
+    fn test_sapling_merkle_crh() {
+        // The roots of the empty Sapling note commitment trees of depth 1 and 2, starting from the
+        // uncommitted leaf `1`.
+        let leaf = BigUint::from(1u32);
+        let root_1 = sapling_merkle_crh(0, &leaf, &leaf);
+        assert_eq!(
+            hex::encode(&root_1),
+            "817de36ab2d57feb077634bca77819c8e0bd298c04f6fed0e6a83cc1356ca155"
+        );
+
+        let node = BigUint::from_bytes_le(&root_1);
+        let root_2 = sapling_merkle_crh(1, &node, &node);
+        assert_eq!(
+            hex::encode(root_2),
+            "ffe9fc03f18b176c998806439ff0bb8ad193afdb27b2ccbc88856916dd804e34"
+        );
+    }
+
+    #[test]
+    fn test_pedersen_hash_native() {
+        type E = Ed25519;
+
+        let base = E::generator();
+        let mut rng = thread_rng();
+        let generators = (0..2)
+            .map(|_| &base * &rng.gen_biguint(250))
+            .collect::<Vec<_>>();
+
+        // A message spanning two segments, with a partial last chunk.
+        let bits = (0..3 * PEDERSEN_SEGMENT_CHUNKS + 10)
+            .map(|_| rng.gen::<bool>())
+            .collect::<Vec<_>>();
+        let (segment_0, segment_1) = bits.split_at(3 * PEDERSEN_SEGMENT_CHUNKS);
+
+        let expected = &(&generators[0] * &pedersen_segment_scalar::<E>(segment_0))
+            + &(&generators[1] * &pedersen_segment_scalar::<E>(segment_1));
+        assert_eq!(pedersen_hash_native(&bits, &generators), expected);
+    }
+
+    #[test]
+    fn test_pedersen_hash() {
+        type F = GoldilocksField;
+        type L = PedersenHashTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type E = Ed25519;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let bits = builder.alloc_array::<BitRegister>(6);
+        let gadget = builder.pedersen_hash::<E>(&bits);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let base = E::generator();
+        let mut rng = thread_rng();
+        let generators = vec![&base * &rng.gen_biguint(250)];
+
+        let writer = generator.new_writer();
+        writer.write_pedersen_windows(&gadget, &generators);
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            let mut rng = thread_rng();
+            let bit_values = (0..6).map(|_| rng.gen::<bool>()).collect::<Vec<_>>();
+            for (bit, value) in bits.iter().zip(bit_values.iter()) {
+                writer.write(&bit, &F::from_canonical_u8(*value as u8), i);
+            }
+            writer.write_row_instructions(&generator.air_data, i);
+
+            let expected = pedersen_hash_native(&bit_values, &generators);
+            assert_eq!(writer.read_ec_point(&gadget.result, i), expected);
+        });
+
+        let public_inputs = writer.0.public.read().unwrap().clone();
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public_inputs);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public_inputs);
+    }
+
+    #[test]
+    fn test_pedersen_hash_fixed_message() {
+        type F = GoldilocksField;
+        type L = PedersenHashTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type E = Ed25519;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let bits = builder.alloc_array::<BitRegister>(6);
+        let gadget = builder.pedersen_hash::<E>(&bits);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        // A fixed generator and message, with the expected hash computed from the segment scalar
+        // rather than from the window table.
+        let generators = vec![&E::generator() * &BigUint::from(0x5eedu32)];
+        let message = [true, false, true, true, true, false];
+        let expected = &generators[0] * &pedersen_segment_scalar::<E>(&message);
+        assert_eq!(pedersen_hash_native(&message, &generators), expected);
+
+        let writer = generator.new_writer();
+        writer.write_pedersen_windows(&gadget, &generators);
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            for (bit, value) in bits.iter().zip(message.iter()) {
+                writer.write(&bit, &F::from_canonical_u8(*value as u8), i);
+            }
+            writer.write_row_instructions(&generator.air_data, i);
+            assert_eq!(writer.read_ec_point(&gadget.result, i), expected);
+        });
+
+        let public_inputs = writer.0.public.read().unwrap().clone();
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public_inputs);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public_inputs);
+    }
+
+    #[test]
+    fn test_pedersen_hash_jubjub() {
+        type F = GoldilocksField;
+        type L = PedersenJubjubTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type E = Jubjub;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let bits = builder.alloc_array::<BitRegister>(6);
+        let gadget = builder.pedersen_hash::<E>(&bits);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let generators = zcash_pedersen_generators();
+
+        let writer = generator.new_writer();
+        writer.write_pedersen_windows(&gadget, &generators);
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            let mut rng = thread_rng();
+            let bit_values = (0..6).map(|_| rng.gen::<bool>()).collect::<Vec<_>>();
+            for (bit, value) in bits.iter().zip(bit_values.iter()) {
+                writer.write(&bit, &F::from_canonical_u8(*value as u8), i);
+            }
+            writer.write_row_instructions(&generator.air_data, i);
+
+            let expected = pedersen_hash_native(&bit_values, &generators);
+            assert_eq!(writer.read_ec_point(&gadget.result, i), expected);
+        });
+
+        let public_inputs = writer.0.public.read().unwrap().clone();
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public_inputs);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public_inputs);
+    }
+}
@@ -0,0 +1,260 @@
+//! The Poseidon permutation and sponge over the native field of the AIR.
+//!
+//! The whole permutation is laid out in a single row. Every round allocates a column for the cube
+//! of each S-box input and a column for each element of the output state, so that all constraints
+//! are of degree at most three.
+//!
+//! Reference: https://eprint.iacr.org/2019/458.pdf
+
+use core::fmt::Debug;
+use core::marker::PhantomData;
+
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::hash::poseidon::{self, Poseidon, ALL_ROUND_CONSTANTS};
+use serde::{Deserialize, Serialize};
+
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::builder::AirBuilder;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+
+/// The parameters of a Poseidon permutation with S-box `x -> x^7`.
+pub trait PoseidonParameters:
+    'static + Debug + Clone + Copy + Send + Sync + Serialize + for<'de> Deserialize<'de>
+{
+    /// The number of elements in the permutation state.
+    const WIDTH: usize;
+    /// The number of state elements absorbed and squeezed by the sponge per permutation.
+    const RATE: usize;
+    /// The number of full rounds before, and after, the partial rounds.
+    const HALF_N_FULL_ROUNDS: usize;
+    /// The number of partial rounds.
+    const N_PARTIAL_ROUNDS: usize;
+
+    /// The constant added to the `index`-th element of the state in round `round`.
+    fn round_constant(round: usize, index: usize) -> u64;
+
+    /// The entry of the MDS matrix at `(row, column)`.
+    fn mds_entry(row: usize, column: usize) -> u64;
+}
+
+/// The Poseidon instance used by plonky2 over the Goldilocks field.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct GoldilocksPoseidon;
+
+impl PoseidonParameters for GoldilocksPoseidon {
+    const WIDTH: usize = poseidon::SPONGE_WIDTH;
+    const RATE: usize = poseidon::SPONGE_RATE;
+    const HALF_N_FULL_ROUNDS: usize = poseidon::HALF_N_FULL_ROUNDS;
+    const N_PARTIAL_ROUNDS: usize = poseidon::N_PARTIAL_ROUNDS;
+
+    fn round_constant(round: usize, index: usize) -> u64 {
+        ALL_ROUND_CONSTANTS[index + Self::WIDTH * round]
+    }
+
+    fn mds_entry(row: usize, column: usize) -> u64 {
+        // The matrix is circulant plus a diagonal.
+        let circ = <GoldilocksField as Poseidon>::MDS_MATRIX_CIRC;
+        let diag = <GoldilocksField as Poseidon>::MDS_MATRIX_DIAG;
+        let entry = circ[(column + Self::WIDTH - row) % Self::WIDTH];
+        if row == column {
+            entry + diag[row]
+        } else {
+            entry
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoseidonGadget<P> {
+    pub input: ArrayRegister<ElementRegister>,
+    /// The output states of all the permutations of the sponge.
+    pub states: Vec<ArrayRegister<ElementRegister>>,
+    pub output: ArrayRegister<ElementRegister>,
+    _marker: PhantomData<P>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Hashes `input` into `num_outputs` elements with the Poseidon sponge, overwriting the rate
+    /// portion of the state with the input as in plonky2's `hash_n_to_m_no_pad`.
+    pub fn hash_n_to_m<P: PoseidonParameters>(
+        &mut self,
+        input: &ArrayRegister<ElementRegister>,
+        num_outputs: usize,
+    ) -> PoseidonGadget<P> {
+        assert!(!input.is_empty(), "Cannot hash an empty input");
+        assert!(num_outputs > 0, "Must squeeze at least one output");
+
+        let mut state = vec![ArithmeticExpression::zero(); P::WIDTH];
+        let mut states = Vec::new();
+        for start in (0..input.len()).step_by(P::RATE) {
+            let end = (start + P::RATE).min(input.len());
+            for (i, index) in (start..end).enumerate() {
+                state[i] = input.get(index).expr();
+            }
+            let next = self.poseidon_permutation::<P>(&state);
+            state = next.iter().map(|x| x.expr()).collect();
+            states.push(next);
+        }
+
+        // Squeeze the outputs, permuting again whenever the rate is exhausted.
+        let num_absorptions = states.len();
+        for _ in 1..num_outputs.div_ceil(P::RATE) {
+            let next = self.poseidon_permutation::<P>(&state);
+            state = next.iter().map(|x| x.expr()).collect();
+            states.push(next);
+        }
+
+        let output = self.alloc_array::<ElementRegister>(num_outputs);
+        let squeezed = &states[num_absorptions - 1..];
+        for (j, element) in output.iter().enumerate() {
+            self.set_to_expression(&element, squeezed[j / P::RATE].get(j % P::RATE).expr());
+        }
+
+        PoseidonGadget {
+            input: *input,
+            states,
+            output,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Applies the Poseidon permutation to `state` and returns the output state.
+    pub fn poseidon_permutation<P: PoseidonParameters>(
+        &mut self,
+        state: &[ArithmeticExpression<L::Field>],
+    ) -> ArrayRegister<ElementRegister> {
+        assert_eq!(state.len(), P::WIDTH, "Invalid state width");
+
+        let num_rounds = 2 * P::HALF_N_FULL_ROUNDS + P::N_PARTIAL_ROUNDS;
+        let mut state = state.to_vec();
+        let mut output = None;
+        for round in 0..num_rounds {
+            let is_full_round = round < P::HALF_N_FULL_ROUNDS
+                || round >= P::HALF_N_FULL_ROUNDS + P::N_PARTIAL_ROUNDS;
+            let next = self.poseidon_round::<P>(&state, round, is_full_round);
+            state = next.iter().map(|x| x.expr()).collect();
+            output = Some(next);
+        }
+        output.unwrap()
+    }
+
+    fn poseidon_round<P: PoseidonParameters>(
+        &mut self,
+        state: &[ArithmeticExpression<L::Field>],
+        round: usize,
+        is_full_round: bool,
+    ) -> ArrayRegister<ElementRegister> {
+        // Constant and S-box layers.
+        let mut sbox_output = Vec::with_capacity(P::WIDTH);
+        for (i, element) in state.iter().enumerate() {
+            let x = element.clone() + L::Field::from_canonical_u64(P::round_constant(round, i));
+            if is_full_round || i == 0 {
+                let x_3 = self.alloc::<ElementRegister>();
+                self.set_to_expression(&x_3, x.clone() * x.clone() * x.clone());
+                sbox_output.push(x_3.expr() * x_3.expr() * x);
+            } else {
+                sbox_output.push(x);
+            }
+        }
+
+        // MDS layer.
+        let next = self.alloc_array::<ElementRegister>(P::WIDTH);
+        for (i, element) in next.iter().enumerate() {
+            let value = sbox_output
+                .iter()
+                .enumerate()
+                .map(|(j, y)| y.clone() * L::Field::from_canonical_u64(P::mds_entry(i, j)))
+                .reduce(|acc, y| acc + y)
+                .unwrap();
+            self.set_to_expression(&element, value);
+        }
+        next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::hash::poseidon::PoseidonHash;
+    use plonky2::plonk::config::Hasher;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub struct PoseidonTest;
+
+    impl AirParameters for PoseidonTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = EmptyInstruction<GoldilocksField>;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 0;
+        const NUM_FREE_COLUMNS: usize = 970;
+
+        fn num_rows_bits() -> usize {
+            10
+        }
+    }
+
+    #[test]
+    fn test_poseidon_hash_n_to_m() {
+        type F = GoldilocksField;
+        type L = PoseidonTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        // An input spanning two absorptions.
+        let input = builder.alloc_array::<ElementRegister>(10);
+        let gadget = builder.hash_n_to_m::<GoldilocksPoseidon>(&input, 4);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let writer = generator.new_writer();
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            let input_values = F::rand_vec(10);
+            writer.write_array(&input, &input_values, i);
+            writer.write_row_instructions(&generator.air_data, i);
+
+            let expected = PoseidonHash::hash_no_pad(&input_values).elements;
+            assert_eq!(writer.read_vec(&gadget.output, i), expected);
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+
+    #[test]
+    fn test_poseidon_permutation() {
+        type F = GoldilocksField;
+        type L = PoseidonTest;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let input = builder.alloc_array::<ElementRegister>(GoldilocksPoseidon::WIDTH);
+        let state = input.iter().map(|x| x.expr()).collect::<Vec<_>>();
+        let output = builder.poseidon_permutation::<GoldilocksPoseidon>(&state);
+
+        let (_, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let writer = generator.new_writer();
+        let input_values = F::rand_array::<{ poseidon::SPONGE_WIDTH }>();
+        writer.write_array(&input, &input_values, 0);
+        writer.write_row_instructions(&generator.air_data, 0);
+
+        let expected = F::poseidon(input_values);
+        assert_eq!(writer.read_vec(&output, 0), expected.to_vec());
+    }
+}
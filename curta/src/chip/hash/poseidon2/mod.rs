@@ -0,0 +1,438 @@
+//! The Poseidon2 permutation over the native field of the AIR.
+//!
+//! Like the Poseidon chip, the whole permutation is laid out in a single row, with a column for the
+//! cube of each S-box input and a column for each element of the state after every round. The
+//! full rounds, and the input of the permutation, are multiplied by a matrix made of circulant
+//! blocks of a fixed 4x4 matrix, and the partial rounds by a matrix `J + D`, where `J` is all ones
+//! and `D` is diagonal, which only take a linear number of operations.
+//!
+//! Reference: https://eprint.iacr.org/2023/323.pdf
+
+pub mod parameters;
+
+use core::ops::{Add, Mul};
+
+use serde::{Deserialize, Serialize};
+
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::builder::AirBuilder;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::cubic::CubicRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::table::bus::global::Bus;
+use crate::chip::trace::writer::TraceWriter;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+
+/// The 4x4 matrix from which the matrix of the full rounds is built.
+const M4: [[u64; 4]; 4] = [[5, 7, 1, 3], [4, 6, 1, 1], [1, 3, 5, 7], [1, 1, 4, 6]];
+
+/// The constants of a Poseidon2 instance with S-box `x -> x^7`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Poseidon2Parameters {
+    /// The number of elements in the permutation state, a multiple of 4.
+    pub width: usize,
+    /// The constants of the full rounds, the first half of which come before the partial rounds.
+    pub external_constants: Vec<Vec<u64>>,
+    /// The constants added to the first element of the state in the partial rounds.
+    pub internal_constants: Vec<u64>,
+    /// The diagonal `D` of the matrix `J + D` of the partial rounds.
+    pub internal_diagonal: Vec<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Poseidon2Gadget {
+    pub parameters: Poseidon2Parameters,
+    /// The input state of the permutation at every row
+    pub input: ArrayRegister<ElementRegister>,
+    /// The output state of the permutation at every row
+    pub output: ArrayRegister<ElementRegister>,
+    /// The input states of all the rows
+    pub public_input: ArrayRegister<ElementRegister>,
+    /// The output states of all the rows
+    pub public_output: ArrayRegister<ElementRegister>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Poseidon2PublicData<T> {
+    pub input: Vec<T>,
+    pub output: Vec<T>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Applies the Poseidon2 permutation at every row of the trace. The input and output states of
+    /// all the rows are public values, which are tied to the rows by the bus.
+    pub fn process_poseidon2_batch(
+        &mut self,
+        clk: &ElementRegister,
+        bus: &mut Bus<L::CubicParams>,
+        bus_channel_idx: usize,
+        parameters: &Poseidon2Parameters,
+    ) -> Poseidon2Gadget {
+        let width = parameters.width;
+        let num_permutations = L::num_rows();
+
+        let input = self.alloc_array::<ElementRegister>(width);
+        let state = input.iter().map(|x| x.expr()).collect::<Vec<_>>();
+        let output = self.poseidon2_permutation(&state, parameters);
+
+        // Public values
+        let public_input = self.alloc_array_public::<ElementRegister>(width * num_permutations);
+        let public_output = self.alloc_array_public::<ElementRegister>(width * num_permutations);
+
+        // Get the input of every row from the bus and put its output in the bus
+        let input_challenges = self.alloc_challenge_array::<CubicRegister>(1 + width);
+        let clk_input = self.accumulate_expressions(&input_challenges, &[clk.expr(), input.expr()]);
+        self.output_from_bus(bus_channel_idx, clk_input);
+
+        let output_challenges = self.alloc_challenge_array::<CubicRegister>(1 + width);
+        let clk_output =
+            self.accumulate_expressions(&output_challenges, &[clk.expr(), output.expr()]);
+        self.input_to_bus(bus_channel_idx, clk_output);
+
+        // Put the public inputs and outputs in the bus
+        for i in 0..num_permutations {
+            let clk_row = ArithmeticExpression::from_constant(L::Field::from_canonical_usize(i));
+            let states = width * i..width * (i + 1);
+            let input_digest = self.accumulate_public_expressions(
+                &input_challenges,
+                &[
+                    clk_row.clone(),
+                    public_input.get_subarray(states.clone()).expr(),
+                ],
+            );
+            bus.insert_global_value(&input_digest);
+            let output_digest = self.accumulate_public_expressions(
+                &output_challenges,
+                &[clk_row, public_output.get_subarray(states).expr()],
+            );
+            bus.output_global_value(&output_digest);
+        }
+
+        Poseidon2Gadget {
+            parameters: parameters.clone(),
+            input,
+            output,
+            public_input,
+            public_output,
+        }
+    }
+
+    /// Applies the Poseidon2 permutation to `state` and returns the output state.
+    pub fn poseidon2_permutation(
+        &mut self,
+        state: &[ArithmeticExpression<L::Field>],
+        parameters: &Poseidon2Parameters,
+    ) -> ArrayRegister<ElementRegister> {
+        assert_eq!(state.len(), parameters.width, "Invalid state width");
+        let (first_full, last_full) = parameters
+            .external_constants
+            .split_at(parameters.half_full_rounds());
+
+        // The linear layer applied to the input is folded into the first round.
+        let mut state = external_layer::<L::Field, _>(state);
+        let mut output = None;
+        for constants in first_full {
+            let next = self.poseidon2_full_round(&state, constants);
+            state = next.iter().map(|x| x.expr()).collect();
+            output = Some(next);
+        }
+        for constant in parameters.internal_constants.iter() {
+            let next =
+                self.poseidon2_partial_round(&state, *constant, &parameters.internal_diagonal);
+            state = next.iter().map(|x| x.expr()).collect();
+            output = Some(next);
+        }
+        for constants in last_full {
+            let next = self.poseidon2_full_round(&state, constants);
+            state = next.iter().map(|x| x.expr()).collect();
+            output = Some(next);
+        }
+        output.unwrap()
+    }
+
+    fn poseidon2_sbox(
+        &mut self,
+        x: ArithmeticExpression<L::Field>,
+    ) -> ArithmeticExpression<L::Field> {
+        let x_3 = self.alloc::<ElementRegister>();
+        self.set_to_expression(&x_3, x.clone() * x.clone() * x.clone());
+        x_3.expr() * x_3.expr() * x
+    }
+
+    fn poseidon2_full_round(
+        &mut self,
+        state: &[ArithmeticExpression<L::Field>],
+        constants: &[u64],
+    ) -> ArrayRegister<ElementRegister> {
+        let sbox_output = state
+            .iter()
+            .zip(constants.iter())
+            .map(|(x, c)| self.poseidon2_sbox(x.clone() + L::Field::from_canonical_u64(*c)))
+            .collect::<Vec<_>>();
+
+        let next = self.alloc_array::<ElementRegister>(state.len());
+        for (element, value) in next.iter().zip(external_layer::<L::Field, _>(&sbox_output)) {
+            self.set_to_expression(&element, value);
+        }
+        next
+    }
+
+    fn poseidon2_partial_round(
+        &mut self,
+        state: &[ArithmeticExpression<L::Field>],
+        constant: u64,
+        diagonal: &[u64],
+    ) -> ArrayRegister<ElementRegister> {
+        let mut sbox_output = state.to_vec();
+        sbox_output[0] =
+            self.poseidon2_sbox(state[0].clone() + L::Field::from_canonical_u64(constant));
+
+        let next = self.alloc_array::<ElementRegister>(state.len());
+        for (element, value) in next
+            .iter()
+            .zip(internal_layer::<L::Field, _>(&sbox_output, diagonal))
+        {
+            self.set_to_expression(&element, value);
+        }
+        next
+    }
+}
+
+/// Multiplies `state` by the matrix of the full rounds, whose blocks are `M4` off the diagonal
+/// and `2 * M4` on the diagonal.
+fn external_layer<F: Field, T: Clone + Add<Output = T> + Mul<F, Output = T>>(
+    state: &[T],
+) -> Vec<T> {
+    let blocks = state
+        .chunks_exact(4)
+        .map(|x| {
+            M4.iter()
+                .map(|row| {
+                    row.iter()
+                        .zip(x.iter())
+                        .map(|(m, x)| x.clone() * F::from_canonical_u64(*m))
+                        .reduce(|acc, y| acc + y)
+                        .unwrap()
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let sums = (0..4)
+        .map(|k| {
+            blocks
+                .iter()
+                .map(|block| block[k].clone())
+                .reduce(|acc, y| acc + y)
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    blocks
+        .iter()
+        .flat_map(|block| {
+            block
+                .iter()
+                .zip(sums.iter())
+                .map(|(y, sum)| y.clone() + sum.clone())
+        })
+        .collect()
+}
+
+/// Multiplies `state` by the matrix `J + D` of the partial rounds, i.e. maps every element `x_i`
+/// to `d_i * x_i + sum(x)`.
+fn internal_layer<F: Field, T: Clone + Add<Output = T> + Mul<F, Output = T>>(
+    state: &[T],
+    diagonal: &[u64],
+) -> Vec<T> {
+    let sum = state.iter().cloned().reduce(|acc, x| acc + x).unwrap();
+    state
+        .iter()
+        .zip(diagonal.iter())
+        .map(|(x, d)| x.clone() * F::from_canonical_u64(*d) + sum.clone())
+        .collect()
+}
+
+impl Poseidon2Parameters {
+    /// The number of full rounds before, and after, the partial rounds.
+    pub fn half_full_rounds(&self) -> usize {
+        self.external_constants.len() / 2
+    }
+
+    /// Applies the permutation to `state` natively.
+    pub fn permute<F: Field>(&self, state: &[F]) -> Vec<F> {
+        assert_eq!(state.len(), self.width, "Invalid state width");
+        let sbox = |x: F| {
+            let x_3 = x * x * x;
+            x_3 * x_3 * x
+        };
+        let full_round = |state: &[F], constants: &[u64]| {
+            let sbox_output = state
+                .iter()
+                .zip(constants.iter())
+                .map(|(x, c)| sbox(*x + F::from_canonical_u64(*c)))
+                .collect::<Vec<_>>();
+            external_layer::<F, _>(&sbox_output)
+        };
+        let (first_full, last_full) = self.external_constants.split_at(self.half_full_rounds());
+
+        let mut state = external_layer::<F, _>(state);
+        for constants in first_full {
+            state = full_round(&state, constants);
+        }
+        for constant in self.internal_constants.iter() {
+            state[0] = sbox(state[0] + F::from_canonical_u64(*constant));
+            state = internal_layer::<F, _>(&state, &self.internal_diagonal);
+        }
+        for constants in last_full {
+            state = full_round(&state, constants);
+        }
+        state
+    }
+}
+
+impl Poseidon2Gadget {
+    /// Writes the permutations of `inputs`, one for every row of the trace.
+    pub fn write<F: Field>(
+        &self,
+        inputs: &[Vec<F>],
+        writer: &TraceWriter<F>,
+    ) -> Poseidon2PublicData<F> {
+        assert_eq!(
+            inputs.len() * self.parameters.width,
+            self.public_input.len(),
+            "There must be one input for every row"
+        );
+
+        let mut input_values = Vec::new();
+        let mut output_values = Vec::new();
+        for (i, input) in inputs.iter().enumerate() {
+            writer.write_array(&self.input, input, i);
+            input_values.extend_from_slice(input);
+            output_values.extend(self.parameters.permute(input));
+        }
+        writer.write_array(&self.public_input, &input_values, 0);
+        writer.write_array(&self.public_output, &output_values, 0);
+
+        Poseidon2PublicData {
+            input: input_values,
+            output: output_values,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::timed;
+    use plonky2::util::timing::TimingTree;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub struct Poseidon2Test;
+
+    impl AirParameters for Poseidon2Test {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = EmptyInstruction<GoldilocksField>;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 0;
+        const NUM_FREE_COLUMNS: usize = 700;
+        const EXTENDED_COLUMNS: usize = 24;
+
+        fn num_rows_bits() -> usize {
+            10
+        }
+    }
+
+    #[test]
+    fn test_poseidon2_permutation() {
+        type F = GoldilocksField;
+        type L = Poseidon2Test;
+
+        for width in [8, 12, 16] {
+            let parameters = Poseidon2Parameters::goldilocks(width);
+            let mut builder = AirBuilder::<L>::new();
+
+            let input = builder.alloc_array::<ElementRegister>(width);
+            let state = input.iter().map(|x| x.expr()).collect::<Vec<_>>();
+            let output = builder.poseidon2_permutation(&state, &parameters);
+
+            let (_, trace_data) = builder.build();
+            let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+            let writer = generator.new_writer();
+            let input_values = F::rand_vec(width);
+            writer.write_array(&input, &input_values, 0);
+            writer.write_row_instructions(&generator.air_data, 0);
+
+            let expected = parameters.permute(&input_values);
+            assert_eq!(writer.read_vec(&output, 0), expected);
+            assert_ne!(expected, input_values);
+        }
+    }
+
+    #[test]
+    fn test_poseidon2_stark() {
+        type F = GoldilocksField;
+        type L = Poseidon2Test;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut timing = TimingTree::new("Poseidon2 test", log::Level::Debug);
+
+        let parameters = Poseidon2Parameters::goldilocks(12);
+
+        let mut builder = AirBuilder::<L>::new();
+        let clk = builder.clock();
+
+        let mut bus = builder.new_bus();
+        let channel_idx = bus.new_channel(&mut builder);
+
+        let gadget = builder.process_poseidon2_batch(&clk, &mut bus, channel_idx, &parameters);
+        builder.constrain_bus(bus);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+        let writer = generator.new_writer();
+
+        let inputs = (0..L::num_rows())
+            .map(|_| F::rand_vec(12))
+            .collect::<Vec<_>>();
+        let public_data = timed!(timing, "Write the execution trace", {
+            let public_data = gadget.write(&inputs, &writer);
+            for i in 0..L::num_rows() {
+                writer.write_row_instructions(&generator.air_data, i);
+            }
+            public_data
+        });
+
+        // The public outputs agree with the permutations computed in the trace
+        for (i, output) in public_data.output.chunks_exact(12).enumerate() {
+            assert_eq!(writer.read_vec(&gadget.output, i), output);
+        }
+
+        let public_inputs = writer.0.public.read().unwrap().clone();
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        timed!(
+            timing,
+            "Stark proof and verify",
+            test_starky(&stark, &config, &generator, &public_inputs)
+        );
+
+        // Generate recursive proof
+        timed!(
+            timing,
+            "Recursive proof generation and verification",
+            test_recursive_starky(stark, config, generator, &public_inputs)
+        );
+
+        timing.print();
+    }
+}
@@ -0,0 +1,353 @@
+//! Generation of Poseidon2 instances over the Goldilocks field.
+//!
+//! The round constants are sampled with the Grain LFSR of the Poseidon reference implementation,
+//! seeded with the field, the S-box, the width and the numbers of rounds of the instance. Every
+//! round gets `width` constants, of which the partial rounds only use the first one. The diagonal
+//! of the matrix of the partial rounds is then sampled from the same stream until the
+//! characteristic polynomials of the first `2 * width` powers of the matrix are irreducible, so
+//! that no subspace is invariant under the linear layer of the partial rounds.
+
+use plonky2::field::goldilocks_field::GoldilocksField;
+
+use super::Poseidon2Parameters;
+use crate::math::prelude::*;
+
+type F = GoldilocksField;
+
+/// The order of the Goldilocks field.
+const ORDER: u64 = 0xFFFF_FFFF_0000_0001;
+
+/// The number of bits of a sampled field element.
+const FIELD_SIZE: usize = 64;
+
+/// The numbers of full and partial rounds of the Goldilocks instances with S-box `x -> x^7`.
+const GOLDILOCKS_HALF_FULL_ROUNDS: usize = 4;
+const GOLDILOCKS_PARTIAL_ROUNDS: usize = 22;
+
+impl Poseidon2Parameters {
+    /// The Goldilocks instance of width 8, 12 or 16, with 8 full rounds and 22 partial rounds.
+    pub fn goldilocks(width: usize) -> Self {
+        assert!(
+            matches!(width, 8 | 12 | 16),
+            "The Goldilocks instances have width 8, 12 or 16"
+        );
+        Self::generate(
+            width,
+            GOLDILOCKS_HALF_FULL_ROUNDS,
+            GOLDILOCKS_PARTIAL_ROUNDS,
+        )
+    }
+
+    /// Generates an instance over the Goldilocks field with `2 * half_full_rounds` full rounds and
+    /// `partial_rounds` partial rounds.
+    pub fn generate(width: usize, half_full_rounds: usize, partial_rounds: usize) -> Self {
+        assert!(
+            width >= 8 && width % 4 == 0,
+            "The width must be a multiple of 4 of at least 8"
+        );
+
+        let mut grain = Grain::new(width, 2 * half_full_rounds, partial_rounds);
+        let round_constants = (0..2 * half_full_rounds + partial_rounds)
+            .map(|_| (0..width).map(|_| grain.next_element()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        let (first_full, rest) = round_constants.split_at(half_full_rounds);
+        let (partial, last_full) = rest.split_at(partial_rounds);
+
+        let internal_diagonal = loop {
+            let diagonal = (0..width).map(|_| grain.next_element()).collect::<Vec<_>>();
+            if is_valid_internal_diagonal(&diagonal) {
+                break diagonal;
+            }
+        };
+
+        Self {
+            width,
+            external_constants: first_full.iter().chain(last_full).cloned().collect(),
+            internal_constants: partial.iter().map(|constants| constants[0]).collect(),
+            internal_diagonal,
+        }
+    }
+}
+
+/// The Grain LFSR used as a pseudo-random generator, with its 80-bit state in the low bits.
+struct Grain {
+    state: u128,
+}
+
+impl Grain {
+    fn new(width: usize, full_rounds: usize, partial_rounds: usize) -> Self {
+        // A prime field in 2 bits, the S-box `x^alpha` in 4 bits, then the field size, the width
+        // and the numbers of rounds, all big-endian, padded with ones to 80 bits.
+        let fields = [
+            (1, 2),
+            (0, 4),
+            (FIELD_SIZE, 12),
+            (width, 12),
+            (full_rounds, 10),
+            (partial_rounds, 10),
+        ];
+        let bits = fields
+            .iter()
+            .flat_map(|(value, len)| (0..*len).rev().map(move |i| (value >> i) & 1 == 1))
+            .chain(core::iter::repeat(true))
+            .take(80);
+
+        let mut grain = Self {
+            state: bits
+                .enumerate()
+                .fold(0, |state, (i, bit)| state | ((bit as u128) << i)),
+        };
+        for _ in 0..160 {
+            grain.step();
+        }
+        grain
+    }
+
+    fn step(&mut self) -> bool {
+        let bit = |i: usize| (self.state >> i) & 1;
+        let new_bit = bit(62) ^ bit(51) ^ bit(38) ^ bit(23) ^ bit(13) ^ bit(0);
+        self.state = (self.state >> 1) | (new_bit << 79);
+        new_bit == 1
+    }
+
+    /// The next output bit. The bits are taken in pairs, and the second bit of a pair is output
+    /// only if the first one is set.
+    fn next_bit(&mut self) -> bool {
+        loop {
+            let keep = self.step();
+            let bit = self.step();
+            if keep {
+                return bit;
+            }
+        }
+    }
+
+    /// The next field element, read big-endian from the output bits and rejected if it is not
+    /// below the order of the field.
+    fn next_element(&mut self) -> u64 {
+        loop {
+            let value = (0..FIELD_SIZE).fold(0, |value, _| (value << 1) | self.next_bit() as u64);
+            if value < ORDER {
+                return value;
+            }
+        }
+    }
+}
+
+/// Whether the characteristic polynomials of the first `2 * width` powers of the matrix `J + D`,
+/// where `J` is all ones and `D` has the given diagonal, are irreducible.
+///
+/// An irreducible characteristic polynomial is also the minimal polynomial of the matrix, and
+/// implies that the matrix is invertible.
+fn is_valid_internal_diagonal(diagonal: &[u64]) -> bool {
+    let width = diagonal.len();
+    let matrix = (0..width)
+        .map(|i| {
+            (0..width)
+                .map(|j| match i == j {
+                    true => F::ONE + F::from_canonical_u64(diagonal[i]),
+                    false => F::ONE,
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut power = matrix.clone();
+    for _ in 0..2 * width {
+        if !is_irreducible(&characteristic_polynomial(&power)) {
+            return false;
+        }
+        power = matrix_mul(&power, &matrix);
+    }
+    true
+}
+
+fn matrix_mul(a: &[Vec<F>], b: &[Vec<F>]) -> Vec<Vec<F>> {
+    a.iter()
+        .map(|row| {
+            (0..b[0].len())
+                .map(|j| row.iter().zip(b).map(|(x, b_row)| *x * b_row[j]).sum())
+                .collect()
+        })
+        .collect()
+}
+
+/// The characteristic polynomial of a square matrix, computed with the Faddeev-LeVerrier
+/// algorithm. Polynomials are given by their coefficients, starting from the constant one.
+fn characteristic_polynomial(matrix: &[Vec<F>]) -> Vec<F> {
+    let n = matrix.len();
+    let mut coefficients = vec![F::ZERO; n + 1];
+    coefficients[n] = F::ONE;
+
+    let mut m = vec![vec![F::ZERO; n]; n];
+    for k in 1..=n {
+        m = matrix_mul(matrix, &m);
+        for (i, row) in m.iter_mut().enumerate() {
+            row[i] += coefficients[n - k + 1];
+        }
+        let product = matrix_mul(matrix, &m);
+        let trace = (0..n).map(|i| product[i][i]).sum::<F>();
+        coefficients[n - k] = -trace * F::from_canonical_usize(k).inverse();
+    }
+    coefficients
+}
+
+/// Whether the monic polynomial `f` of degree at least 2 is irreducible, by Rabin's test: `f` of
+/// degree `n` is irreducible if and only if it divides `x^(p^n) - x` and is coprime with
+/// `x^(p^(n/q)) - x` for every prime `q` dividing `n`.
+fn is_irreducible(f: &[F]) -> bool {
+    let n = f.len() - 1;
+    let mut x = vec![F::ZERO; n];
+    x[1] = F::ONE;
+
+    // The powers `x^(p^i)` modulo `f` for `i` up to `n`
+    let mut frobenius = vec![x.clone()];
+    for i in 0..n {
+        frobenius.push(poly_pow_mod(&frobenius[i], ORDER, f));
+    }
+
+    let minus_x = |poly: &[F]| {
+        poly.iter()
+            .zip(x.iter())
+            .map(|(a, b)| *a - *b)
+            .collect::<Vec<_>>()
+    };
+    if !trim(minus_x(&frobenius[n])).is_empty() {
+        return false;
+    }
+    (2..=n)
+        .filter(|q| n % q == 0 && (2..*q).all(|r| q % r != 0))
+        .all(|q| poly_gcd(f.to_vec(), minus_x(&frobenius[n / q])).len() == 1)
+}
+
+/// The product of `a` and `b` modulo the monic polynomial `f`, both of degree less than `f`.
+fn poly_mul_mod(a: &[F], b: &[F], f: &[F]) -> Vec<F> {
+    let n = f.len() - 1;
+    let mut product = vec![F::ZERO; a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            product[i + j] += *x * *y;
+        }
+    }
+    for i in (n..product.len()).rev() {
+        let quotient = product[i];
+        for (j, coefficient) in f.iter().enumerate() {
+            product[i - n + j] -= quotient * *coefficient;
+        }
+    }
+    product.truncate(n);
+    product
+}
+
+fn poly_pow_mod(base: &[F], exponent: u64, f: &[F]) -> Vec<F> {
+    let mut result = vec![F::ZERO; f.len() - 1];
+    result[0] = F::ONE;
+    let mut base = base.to_vec();
+    let mut exponent = exponent;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = poly_mul_mod(&result, &base, f);
+        }
+        base = poly_mul_mod(&base, &base, f);
+        exponent >>= 1;
+    }
+    result
+}
+
+/// Removes the leading zero coefficients of a polynomial.
+fn trim(mut poly: Vec<F>) -> Vec<F> {
+    while poly.last() == Some(&F::ZERO) {
+        poly.pop();
+    }
+    poly
+}
+
+/// A greatest common divisor of `a` and `b`, up to a constant factor.
+fn poly_gcd(a: Vec<F>, b: Vec<F>) -> Vec<F> {
+    let (mut a, mut b) = (trim(a), trim(b));
+    while !b.is_empty() {
+        let inverse = b[b.len() - 1].inverse();
+        while a.len() >= b.len() {
+            let quotient = a[a.len() - 1] * inverse;
+            let shift = a.len() - b.len();
+            for (j, coefficient) in b.iter().enumerate() {
+                a[shift + j] -= quotient * *coefficient;
+            }
+            a = trim(a);
+        }
+        core::mem::swap(&mut a, &mut b);
+    }
+    a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_irreducibility() {
+        // Minus one is a square modulo the Goldilocks prime, but the generator 7 is not.
+        let x2_plus_one = [F::ONE, F::ZERO, F::ONE];
+        assert!(!is_irreducible(&x2_plus_one));
+        let x2_minus_seven = [-F::from_canonical_u64(7), F::ZERO, F::ONE];
+        assert!(is_irreducible(&x2_minus_seven));
+
+        // The characteristic polynomial of a companion matrix is the polynomial itself.
+        let companion = vec![
+            vec![F::ZERO, F::from_canonical_u64(7)],
+            vec![F::ONE, F::ZERO],
+        ];
+        assert_eq!(
+            characteristic_polynomial(&companion),
+            x2_minus_seven.to_vec()
+        );
+    }
+
+    #[test]
+    fn test_goldilocks_parameters() {
+        // The first constants and diagonal entries of every instance.
+        let expected = [
+            (
+                8,
+                0xdd5743e7f2a5a5d9,
+                0x488897d85ff51f56,
+                0xab87e9cedfac5b4d,
+            ),
+            (
+                12,
+                0x13dcf33aba214f46,
+                0x4adf842aa75d4316,
+                0xbb4089f5abb4ee91,
+            ),
+            (
+                16,
+                0x15ebea3fc73397c3,
+                0x28eff4b01103d100,
+                0xfd42043292a3999a,
+            ),
+        ];
+        for (width, external, internal, diagonal) in expected {
+            let parameters = Poseidon2Parameters::goldilocks(width);
+            assert_eq!(parameters.width, width);
+            assert_eq!(
+                parameters.external_constants.len(),
+                2 * GOLDILOCKS_HALF_FULL_ROUNDS
+            );
+            assert!(parameters
+                .external_constants
+                .iter()
+                .all(|c| c.len() == width));
+            assert_eq!(
+                parameters.internal_constants.len(),
+                GOLDILOCKS_PARTIAL_ROUNDS
+            );
+            assert_eq!(parameters.internal_diagonal.len(), width);
+
+            assert_eq!(parameters.external_constants[0][0], external);
+            assert_eq!(parameters.internal_constants[0], internal);
+            assert_eq!(parameters.internal_diagonal[0], diagonal);
+            assert!(is_valid_internal_diagonal(&parameters.internal_diagonal));
+        }
+    }
+}
@@ -0,0 +1,734 @@
+//! The Rescue-Prime Optimized permutation and sponge over the native field of the AIR.
+//!
+//! Like the Poseidon chip, the whole permutation is laid out in a single row. Every round applies
+//! the MDS matrix, the first round constants and the S-box `x -> x^alpha`, then the MDS matrix, the
+//! second round constants and the inverse S-box `x -> x^(1/alpha)`. The inverse S-box is witnessed
+//! by an instruction and constrained by the forward power map.
+//!
+//! The sponge follows Rescue-Prime Optimized: the capacity is the first `WIDTH - RATE` elements of
+//! the state and the rate the remaining ones. An input whose length is not a multiple of the rate
+//! sets the first capacity element to one and is padded with a one followed by zeros.
+//!
+//! Reference: https://eprint.iacr.org/2022/1577.pdf
+
+use core::fmt::Debug;
+use core::marker::PhantomData;
+
+use serde::{Deserialize, Serialize};
+
+use crate::air::parser::AirParser;
+use crate::air::AirConstraint;
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::builder::AirBuilder;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::memory::MemorySlice;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::TraceWriter;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+
+/// The parameters of a Rescue-Prime Optimized permutation with S-box `x -> x^7`.
+pub trait RescueParameters:
+    'static + Debug + Clone + Copy + Send + Sync + Serialize + for<'de> Deserialize<'de>
+{
+    /// The number of elements in the permutation state.
+    const WIDTH: usize;
+    /// The number of state elements absorbed and squeezed by the sponge per permutation, which are
+    /// the last `RATE` elements of the state.
+    const RATE: usize;
+    /// The number of rounds, each consisting of a forward and an inverse half-round.
+    const NUM_ROUNDS: usize;
+    /// The inverse of 7 modulo `p - 1`, so that `x -> x^INV_ALPHA` is the inverse S-box.
+    const INV_ALPHA: u64;
+
+    /// The constant added to the `index`-th element of the state after the MDS matrix of the
+    /// half-round `half_round`, which is `2 * round` for the forward half and `2 * round + 1` for
+    /// the inverse half.
+    fn round_constant(half_round: usize, index: usize) -> u64;
+
+    /// The entry of the MDS matrix at `(row, column)`.
+    fn mds_entry(row: usize, column: usize) -> u64;
+}
+
+/// The Rescue-Prime Optimized instance over the Goldilocks field, with width 12, rate 8 and 7
+/// rounds. The round constants are generated with SHAKE256 seeded with `RPO(p, 12, 4, 128)`, and
+/// the MDS matrix is the circulant matrix of the reference implementation.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct GoldilocksRescue;
+
+impl RescueParameters for GoldilocksRescue {
+    const WIDTH: usize = 12;
+    const RATE: usize = 8;
+    const NUM_ROUNDS: usize = 7;
+    const INV_ALPHA: u64 = 10540996611094048183;
+
+    fn round_constant(half_round: usize, index: usize) -> u64 {
+        if half_round % 2 == 0 {
+            ARK1[half_round / 2][index]
+        } else {
+            ARK2[half_round / 2][index]
+        }
+    }
+
+    fn mds_entry(row: usize, column: usize) -> u64 {
+        MDS_FIRST_ROW[(column + Self::WIDTH - row) % Self::WIDTH]
+    }
+}
+
+/// The first row of the circulant MDS matrix, each row being the previous one rotated right.
+const MDS_FIRST_ROW: [u64; 12] = [7, 23, 8, 26, 13, 10, 9, 7, 6, 22, 21, 8];
+
+/// The constants added after the first MDS layer of each round.
+const ARK1: [[u64; 12]; 7] = [
+    [
+        5789762306288267392,
+        6522564764413701783,
+        17809893479458208203,
+        107145243989736508,
+        6388978042437517382,
+        15844067734406016715,
+        9975000513555218239,
+        3344984123768313364,
+        9959189626657347191,
+        12960773468763563665,
+        9602914297752488475,
+        16657542370200465908,
+    ],
+    [
+        12987190162843096997,
+        653957632802705281,
+        4441654670647621225,
+        4038207883745915761,
+        5613464648874830118,
+        13222989726778338773,
+        3037761201230264149,
+        16683759727265180203,
+        8337364536491240715,
+        3227397518293416448,
+        8110510111539674682,
+        2872078294163232137,
+    ],
+    [
+        18072785500942327487,
+        6200974112677013481,
+        17682092219085884187,
+        10599526828986756440,
+        975003873302957338,
+        8264241093196931281,
+        10065763900435475170,
+        2181131744534710197,
+        6317303992309418647,
+        1401440938888741532,
+        8884468225181997494,
+        13066900325715521532,
+    ],
+    [
+        5674685213610121970,
+        5759084860419474071,
+        13943282657648897737,
+        1352748651966375394,
+        17110913224029905221,
+        1003883795902368422,
+        4141870621881018291,
+        8121410972417424656,
+        14300518605864919529,
+        13712227150607670181,
+        17021852944633065291,
+        6252096473787587650,
+    ],
+    [
+        4887609836208846458,
+        3027115137917284492,
+        9595098600469470675,
+        10528569829048484079,
+        7864689113198939815,
+        17533723827845969040,
+        5781638039037710951,
+        17024078752430719006,
+        109659393484013511,
+        7158933660534805869,
+        2955076958026921730,
+        7433723648458773977,
+    ],
+    [
+        16308865189192447297,
+        11977192855656444890,
+        12532242556065780287,
+        14594890931430968898,
+        7291784239689209784,
+        5514718540551361949,
+        10025733853830934803,
+        7293794580341021693,
+        6728552937464861756,
+        6332385040983343262,
+        13277683694236792804,
+        2600778905124452676,
+    ],
+    [
+        7123075680859040534,
+        1034205548717903090,
+        7717824418247931797,
+        3019070937878604058,
+        11403792746066867460,
+        10280580802233112374,
+        337153209462421218,
+        13333398568519923717,
+        3596153696935337464,
+        8104208463525993784,
+        14345062289456085693,
+        17036731477169661256,
+    ],
+];
+
+/// The constants added after the second MDS layer of each round.
+const ARK2: [[u64; 12]; 7] = [
+    [
+        6077062762357204287,
+        15277620170502011191,
+        5358738125714196705,
+        14233283787297595718,
+        13792579614346651365,
+        11614812331536767105,
+        14871063686742261166,
+        10148237148793043499,
+        4457428952329675767,
+        15590786458219172475,
+        10063319113072092615,
+        14200078843431360086,
+    ],
+    [
+        6202948458916099932,
+        17690140365333231091,
+        3595001575307484651,
+        373995945117666487,
+        1235734395091296013,
+        14172757457833931602,
+        707573103686350224,
+        15453217512188187135,
+        219777875004506018,
+        17876696346199469008,
+        17731621626449383378,
+        2897136237748376248,
+    ],
+    [
+        8023374565629191455,
+        15013690343205953430,
+        4485500052507912973,
+        12489737547229155153,
+        9500452585969030576,
+        2054001340201038870,
+        12420704059284934186,
+        355990932618543755,
+        9071225051243523860,
+        12766199826003448536,
+        9045979173463556963,
+        12934431667190679898,
+    ],
+    [
+        18389244934624494276,
+        16731736864863925227,
+        4440209734760478192,
+        17208448209698888938,
+        8739495587021565984,
+        17000774922218161967,
+        13533282547195532087,
+        525402848358706231,
+        16987541523062161972,
+        5466806524462797102,
+        14512769585918244983,
+        10973956031244051118,
+    ],
+    [
+        6982293561042362913,
+        14065426295947720331,
+        16451845770444974180,
+        7139138592091306727,
+        9012006439959783127,
+        14619614108529063361,
+        1394813199588124371,
+        4635111139507788575,
+        16217473952264203365,
+        10782018226466330683,
+        6844229992533662050,
+        7446486531695178711,
+    ],
+    [
+        3736792340494631448,
+        577852220195055341,
+        6689998335515779805,
+        13886063479078013492,
+        14358505101923202168,
+        7744142531772274164,
+        16135070735728404443,
+        12290902521256031137,
+        12059913662657709804,
+        16456018495793751911,
+        4571485474751953524,
+        17200392109565783176,
+    ],
+    [
+        17130398059294018733,
+        519782857322261988,
+        9625384390925085478,
+        1664893052631119222,
+        7629576092524553570,
+        3485239601103661425,
+        9755891797164033838,
+        15218148195153269027,
+        16460604813734957368,
+        9643968136937729763,
+        3611348709641382851,
+        18256379591337759196,
+    ],
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RescueGadget<P> {
+    pub input: ArrayRegister<ElementRegister>,
+    /// The output states of all the permutations of the sponge.
+    pub states: Vec<ArrayRegister<ElementRegister>>,
+    pub output: ArrayRegister<ElementRegister>,
+    _marker: PhantomData<P>,
+}
+
+/// Witnesses `output = input^(1/7)` elementwise, constrained by `output^7 = input`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct InverseSboxInstruction {
+    input: ArrayRegister<ElementRegister>,
+    pub output: ArrayRegister<ElementRegister>,
+    output_cube: ArrayRegister<ElementRegister>,
+    inv_alpha: u64,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Hashes `input` into `num_outputs` elements with the Rescue-Prime Optimized sponge,
+    /// overwriting the rate portion of the state with the input.
+    pub fn rescue_hash_n_to_m<P: RescueParameters>(
+        &mut self,
+        input: &ArrayRegister<ElementRegister>,
+        num_outputs: usize,
+    ) -> RescueGadget<P>
+    where
+        L::Instruction: From<InverseSboxInstruction>,
+    {
+        assert!(!input.is_empty(), "Cannot hash an empty input");
+        assert!(num_outputs > 0, "Must squeeze at least one output");
+
+        let capacity = P::WIDTH - P::RATE;
+        let mut state = vec![ArithmeticExpression::zero(); P::WIDTH];
+        if input.len() % P::RATE != 0 {
+            state[0] = ArithmeticExpression::one();
+        }
+        let mut states = Vec::new();
+        for start in (0..input.len()).step_by(P::RATE) {
+            let end = (start + P::RATE).min(input.len());
+            for (i, index) in (start..end).enumerate() {
+                state[capacity + i] = input.get(index).expr();
+            }
+            // Pad a partial block with a one followed by zeros.
+            if end - start < P::RATE {
+                state[capacity + end - start] = ArithmeticExpression::one();
+                for element in state[capacity + end - start + 1..].iter_mut() {
+                    *element = ArithmeticExpression::zero();
+                }
+            }
+            let next = self.rescue_permutation::<P>(&state);
+            state = next.iter().map(|x| x.expr()).collect();
+            states.push(next);
+        }
+
+        // Squeeze the outputs, permuting again whenever the rate is exhausted.
+        let num_absorptions = states.len();
+        for _ in 1..num_outputs.div_ceil(P::RATE) {
+            let next = self.rescue_permutation::<P>(&state);
+            state = next.iter().map(|x| x.expr()).collect();
+            states.push(next);
+        }
+
+        let output = self.alloc_array::<ElementRegister>(num_outputs);
+        let squeezed = &states[num_absorptions - 1..];
+        for (j, element) in output.iter().enumerate() {
+            let value = squeezed[j / P::RATE].get(capacity + j % P::RATE).expr();
+            self.set_to_expression(&element, value);
+        }
+
+        RescueGadget {
+            input: *input,
+            states,
+            output,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Applies the Rescue-Prime Optimized permutation to `state` and returns the output state.
+    pub fn rescue_permutation<P: RescueParameters>(
+        &mut self,
+        state: &[ArithmeticExpression<L::Field>],
+    ) -> ArrayRegister<ElementRegister>
+    where
+        L::Instruction: From<InverseSboxInstruction>,
+    {
+        assert_eq!(state.len(), P::WIDTH, "Invalid state width");
+
+        let mut state = state.to_vec();
+        let mut output = None;
+        for round in 0..P::NUM_ROUNDS {
+            let next = self.rescue_round::<P>(&state, round);
+            state = next.iter().map(|x| x.expr()).collect();
+            output = Some(next);
+        }
+        output.unwrap()
+    }
+
+    fn rescue_round<P: RescueParameters>(
+        &mut self,
+        state: &[ArithmeticExpression<L::Field>],
+        round: usize,
+    ) -> ArrayRegister<ElementRegister>
+    where
+        L::Instruction: From<InverseSboxInstruction>,
+    {
+        // Forward half-round: MDS, constants and S-box.
+        let sbox_input = Self::rescue_linear_layer::<P>(state, 2 * round);
+        let mut sbox_output = Vec::with_capacity(P::WIDTH);
+        for x in sbox_input {
+            let x_3 = self.alloc::<ElementRegister>();
+            self.set_to_expression(&x_3, x.clone() * x.clone() * x.clone());
+            sbox_output.push(x_3.expr() * x_3.expr() * x);
+        }
+
+        // Inverse half-round: MDS, constants and inverse S-box.
+        let middle = self.alloc_array::<ElementRegister>(P::WIDTH);
+        let middle_values = Self::rescue_linear_layer::<P>(&sbox_output, 2 * round + 1);
+        for (element, value) in middle.iter().zip(middle_values) {
+            self.set_to_expression(&element, value);
+        }
+        let instr = InverseSboxInstruction {
+            input: middle,
+            output: self.alloc_array::<ElementRegister>(P::WIDTH),
+            output_cube: self.alloc_array::<ElementRegister>(P::WIDTH),
+            inv_alpha: P::INV_ALPHA,
+        };
+        self.register_instruction(instr);
+        instr.output
+    }
+
+    /// Applies the MDS matrix and adds the constants of the given half-round.
+    fn rescue_linear_layer<P: RescueParameters>(
+        state: &[ArithmeticExpression<L::Field>],
+        half_round: usize,
+    ) -> Vec<ArithmeticExpression<L::Field>> {
+        (0..P::WIDTH)
+            .map(|i| {
+                state
+                    .iter()
+                    .enumerate()
+                    .map(|(j, y)| y.clone() * L::Field::from_canonical_u64(P::mds_entry(i, j)))
+                    .reduce(|acc, y| acc + y)
+                    .unwrap()
+                    + L::Field::from_canonical_u64(P::round_constant(half_round, i))
+            })
+            .collect()
+    }
+}
+
+impl<P: RescueParameters> RescueGadget<P> {
+    /// Applies the Rescue-Prime Optimized permutation to `state` natively.
+    pub fn permute<F: Field>(state: &[F]) -> Vec<F> {
+        assert_eq!(state.len(), P::WIDTH, "Invalid state width");
+        let linear_layer = |state: &[F], half_round: usize| {
+            (0..P::WIDTH)
+                .map(|i| {
+                    state.iter().enumerate().fold(
+                        F::from_canonical_u64(P::round_constant(half_round, i)),
+                        |acc, (j, x)| acc + *x * F::from_canonical_u64(P::mds_entry(i, j)),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut state = state.to_vec();
+        for round in 0..P::NUM_ROUNDS {
+            state = linear_layer(&state, 2 * round)
+                .iter()
+                .map(|x| x.pow(7))
+                .collect();
+            state = linear_layer(&state, 2 * round + 1)
+                .iter()
+                .map(|x| x.pow(P::INV_ALPHA))
+                .collect();
+        }
+        state
+    }
+
+    /// Hashes `input` into `num_outputs` elements with the Rescue-Prime Optimized sponge natively.
+    pub fn hash<F: Field>(input: &[F], num_outputs: usize) -> Vec<F> {
+        let capacity = P::WIDTH - P::RATE;
+        let mut state = vec![F::ZERO; P::WIDTH];
+        if input.len() % P::RATE != 0 {
+            state[0] = F::ONE;
+        }
+        for chunk in input.chunks(P::RATE) {
+            state[capacity..capacity + chunk.len()].copy_from_slice(chunk);
+            // Pad a partial block with a one followed by zeros.
+            if chunk.len() < P::RATE {
+                state[capacity + chunk.len()] = F::ONE;
+                for element in state[capacity + chunk.len() + 1..].iter_mut() {
+                    *element = F::ZERO;
+                }
+            }
+            state = Self::permute(&state);
+        }
+
+        let mut outputs = Vec::with_capacity(num_outputs);
+        loop {
+            for element in state[capacity..].iter() {
+                outputs.push(*element);
+                if outputs.len() == num_outputs {
+                    return outputs;
+                }
+            }
+            state = Self::permute(&state);
+        }
+    }
+}
+
+impl<AP: AirParser> AirConstraint<AP> for InverseSboxInstruction {
+    fn eval(&self, parser: &mut AP) {
+        let input = self.input.register().eval_slice(parser).to_vec();
+        let output = self.output.register().eval_slice(parser).to_vec();
+        let output_cube = self.output_cube.register().eval_slice(parser).to_vec();
+
+        for ((x, y), y_3) in input.iter().zip(output.iter()).zip(output_cube.iter()) {
+            // y_3 = y^3
+            let y_2 = parser.mul(*y, *y);
+            let y_3_expected = parser.mul(y_2, *y);
+            let cube_constraint = parser.sub(y_3_expected, *y_3);
+            parser.constraint(cube_constraint);
+
+            // x = y_3^2 * y
+            let y_6 = parser.mul(*y_3, *y_3);
+            let y_7 = parser.mul(y_6, *y);
+            let power_constraint = parser.sub(y_7, *x);
+            parser.constraint(power_constraint);
+        }
+    }
+}
+
+impl<F: Field> Instruction<F> for InverseSboxInstruction {
+    fn trace_layout(&self) -> Vec<MemorySlice> {
+        vec![*self.output.register(), *self.output_cube.register()]
+    }
+
+    fn inputs(&self) -> Vec<MemorySlice> {
+        vec![*self.input.register()]
+    }
+
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let input = writer.read_vec(&self.input, row_index);
+        let output = input
+            .iter()
+            .map(|x| x.pow(self.inv_alpha))
+            .collect::<Vec<_>>();
+        let output_cube = output.iter().map(|y| *y * *y * *y).collect::<Vec<_>>();
+        writer.write_array(&self.output, &output, row_index);
+        writer.write_array(&self.output_cube, &output_cube, row_index);
+    }
+
+    fn constraint_degree(&self) -> usize {
+        3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chip::builder::tests::*;
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub struct RescueTest;
+
+    impl AirParameters for RescueTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = InverseSboxInstruction;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 0;
+        const NUM_FREE_COLUMNS: usize = 686;
+
+        fn num_rows_bits() -> usize {
+            10
+        }
+    }
+
+    #[test]
+    fn test_rescue_inverse_sbox() {
+        type F = GoldilocksField;
+
+        for x in F::rand_vec(100) {
+            assert_eq!(x.pow(GoldilocksRescue::INV_ALPHA).pow(7), x);
+            assert_eq!(x.pow(7).pow(GoldilocksRescue::INV_ALPHA), x);
+        }
+    }
+
+    /// Test vectors computed with an independent implementation of Rescue-Prime Optimized, with
+    /// the round constants generated from SHAKE256.
+    #[test]
+    fn test_rescue_permutation_vectors() {
+        type F = GoldilocksField;
+        type P = GoldilocksRescue;
+
+        let vectors: [([u64; 12], [u64; 12]); 2] = [
+            (
+                [0; 12],
+                [
+                    5096858464874356363,
+                    17467091117607601070,
+                    4492299921045254967,
+                    14327958870441829769,
+                    8635338869442206704,
+                    11671305615285950885,
+                    15253023094703789604,
+                    7398108415970215319,
+                    14084237001781243886,
+                    1403542540949983059,
+                    16876978449595478787,
+                    4949768242600167471,
+                ],
+            ),
+            (
+                core::array::from_fn(|i| i as u64),
+                [
+                    15056646954853821376,
+                    594518210294093573,
+                    10395398226526937664,
+                    3903707756219396109,
+                    7670128982698747483,
+                    4249514323476682720,
+                    16506822133651532340,
+                    10593868791806571942,
+                    9413309068803954142,
+                    15946782832277734471,
+                    7904287043744270535,
+                    16548919317472389167,
+                ],
+            ),
+        ];
+        for (input, output) in vectors {
+            let input = input.map(F::from_canonical_u64);
+            let output = output.map(F::from_canonical_u64);
+            assert_eq!(RescueGadget::<P>::permute(&input), output);
+        }
+    }
+
+    /// Digests of the inputs `[0, 1, ..., n - 1]`, computed with the same implementation.
+    #[test]
+    fn test_rescue_hash_vectors() {
+        type F = GoldilocksField;
+        type P = GoldilocksRescue;
+
+        let vectors: [(u64, [u64; 4]); 4] = [
+            (
+                1,
+                [
+                    1502364727743950833,
+                    5880949717274681448,
+                    162790463902224431,
+                    6901340476773664264,
+                ],
+            ),
+            (
+                2,
+                [
+                    7478710183745780580,
+                    3308077307559720969,
+                    3383561985796182409,
+                    17205078494700259815,
+                ],
+            ),
+            (
+                8,
+                [
+                    2242391899857912644,
+                    12689382052053305418,
+                    235236990017815546,
+                    5046143039268215739,
+                ],
+            ),
+            (
+                10,
+                [
+                    1994394001720334744,
+                    10866209900885216467,
+                    13836092831163031683,
+                    10814636682252756697,
+                ],
+            ),
+        ];
+        for (n, digest) in vectors {
+            let input = (0..n).map(F::from_canonical_u64).collect::<Vec<_>>();
+            let digest = digest.map(F::from_canonical_u64);
+            assert_eq!(RescueGadget::<P>::hash(&input, 4), digest);
+        }
+    }
+
+    #[test]
+    fn test_rescue_hash_n_to_m() {
+        type F = GoldilocksField;
+        type L = RescueTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        // An input spanning two absorptions.
+        let input = builder.alloc_array::<ElementRegister>(10);
+        let gadget = builder.rescue_hash_n_to_m::<GoldilocksRescue>(&input, 4);
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let writer = generator.new_writer();
+        (0..L::num_rows()).into_par_iter().for_each(|i| {
+            let input_values = F::rand_vec(10);
+            writer.write_array(&input, &input_values, i);
+            writer.write_row_instructions(&generator.air_data, i);
+
+            let expected = RescueGadget::<GoldilocksRescue>::hash(&input_values, 4);
+            assert_eq!(writer.read_vec(&gadget.output, i), expected);
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+
+    #[test]
+    fn test_rescue_permutation() {
+        type F = GoldilocksField;
+        type L = RescueTest;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let input = builder.alloc_array::<ElementRegister>(GoldilocksRescue::WIDTH);
+        let state = input.iter().map(|x| x.expr()).collect::<Vec<_>>();
+        let output = builder.rescue_permutation::<GoldilocksRescue>(&state);
+
+        let (_, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let writer = generator.new_writer();
+        let input_values = F::rand_vec(GoldilocksRescue::WIDTH);
+        writer.write_array(&input, &input_values, 0);
+        writer.write_row_instructions(&generator.air_data, 0);
+
+        let expected = RescueGadget::<GoldilocksRescue>::permute(&input_values);
+        assert_eq!(writer.read_vec(&output, 0), expected);
+    }
+}
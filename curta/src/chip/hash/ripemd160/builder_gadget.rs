@@ -0,0 +1,146 @@
+use core::marker::PhantomData;
+
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::Target;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+use super::generator::{RIPEMD160AirParameters, RIPEMD160Generator, RIPEMD160_NUM_COMPRESSIONS};
+use super::{RIPEMD160Gadget, RIPEMD160PublicData, RIPEMD160_BLOCK_LEN};
+use crate::chip::builder::AirBuilder;
+use crate::chip::hash::sha::sha256::builder_gadget::CurtaBytes;
+use crate::chip::trace::generator::ArithmeticGenerator;
+use crate::chip::AirParameters;
+use crate::math::prelude::CubicParameters;
+use crate::plonky2::stark::config::{CurtaConfig, StarkyConfig};
+use crate::plonky2::stark::gadget::StarkGadget;
+use crate::plonky2::stark::generator::simple::SimpleStarkWitnessGenerator;
+use crate::plonky2::stark::Starky;
+
+#[derive(Debug, Clone)]
+pub struct RIPEMD160BuilderGadget<F, E, const D: usize> {
+    pub messages: Vec<Target>,
+    pub message_lengths: Vec<usize>,
+    pub digests: Vec<Target>,
+    _marker: PhantomData<(F, E)>,
+}
+
+impl<F, E, const D: usize> RIPEMD160BuilderGadget<F, E, D> {
+    /// The number of compressions taken by the messages of the gadget.
+    pub fn num_compressions(&self) -> usize {
+        self.message_lengths
+            .iter()
+            .map(|len| RIPEMD160Gadget::pad(&vec![0u8; *len]).len() / RIPEMD160_BLOCK_LEN)
+            .sum()
+    }
+}
+
+pub trait RIPEMD160Builder<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize> {
+    type Gadget;
+
+    fn init_ripemd160(&mut self) -> Self::Gadget;
+
+    /// Computes the digest of a message whose length is fixed by the circuit.
+    fn ripemd160(&mut self, message: &[Target], gadget: &mut Self::Gadget) -> CurtaBytes<20>;
+
+    /// Proves the messages of the gadget in a single stark. The compressions which are not taken
+    /// by the messages are filled with empty messages.
+    fn constrain_ripemd160_gadget<C: CurtaConfig<D, F = F, FE = F::Extension>>(
+        &mut self,
+        gadget: Self::Gadget,
+    );
+}
+
+impl<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize> RIPEMD160Builder<F, E, D>
+    for CircuitBuilder<F, D>
+{
+    type Gadget = RIPEMD160BuilderGadget<F, E, D>;
+
+    fn init_ripemd160(&mut self) -> Self::Gadget {
+        RIPEMD160BuilderGadget {
+            messages: Vec::new(),
+            message_lengths: Vec::new(),
+            digests: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    fn ripemd160(&mut self, message: &[Target], gadget: &mut Self::Gadget) -> CurtaBytes<20> {
+        gadget.messages.extend_from_slice(message);
+        gadget.message_lengths.push(message.len());
+        let digest_bytes = self.add_virtual_target_arr::<20>();
+        gadget.digests.extend_from_slice(&digest_bytes);
+        CurtaBytes(digest_bytes)
+    }
+
+    fn constrain_ripemd160_gadget<C: CurtaConfig<D, F = F, FE = F::Extension>>(
+        &mut self,
+        mut gadget: Self::Gadget,
+    ) {
+        let num_compressions = gadget.num_compressions();
+        assert!(
+            num_compressions <= RIPEMD160_NUM_COMPRESSIONS,
+            "The messages take {} compressions, more than the {} of a proof",
+            num_compressions,
+            RIPEMD160_NUM_COMPRESSIONS
+        );
+        for _ in num_compressions..RIPEMD160_NUM_COMPRESSIONS {
+            RIPEMD160Builder::<F, E, D>::ripemd160(self, &[], &mut gadget);
+        }
+
+        // Allocate public input targets
+        let public_ripemd160_targets = RIPEMD160PublicData::add_virtual(
+            self,
+            &gadget.messages,
+            &gadget.message_lengths,
+            &gadget.digests,
+        );
+
+        // Make the air
+        let mut air_builder = AirBuilder::<RIPEMD160AirParameters<F, E>>::new();
+        let clk = air_builder.clock();
+
+        let (mut operations, table) = air_builder.byte_operations();
+
+        let mut bus = air_builder.new_bus();
+        let channel_idx = bus.new_channel(&mut air_builder);
+
+        let ripemd160_gadget =
+            air_builder.process_ripemd160_batch(&clk, &mut bus, channel_idx, &mut operations);
+
+        air_builder.register_byte_lookup(operations, &table);
+        air_builder.constrain_bus(bus);
+
+        let (air, trace_data) = air_builder.build();
+
+        let generator = ArithmeticGenerator::<RIPEMD160AirParameters<F, E>>::new(trace_data);
+
+        let public_input_target = public_ripemd160_targets.public_input_targets();
+
+        let ripemd160_generator = RIPEMD160Generator {
+            gadget: ripemd160_gadget,
+            table,
+            messages: gadget.messages,
+            message_lengths: gadget.message_lengths,
+            trace_generator: generator.clone(),
+            pub_values_target: public_ripemd160_targets,
+        };
+
+        self.add_simple_generator(ripemd160_generator);
+
+        let stark = Starky::new(air);
+        let config =
+            StarkyConfig::<C, D>::standard_fast_config(RIPEMD160AirParameters::<F, E>::num_rows());
+        let virtual_proof = self.add_virtual_stark_proof(&stark, &config);
+        self.verify_stark_proof(&config, &stark, &virtual_proof, &public_input_target);
+
+        let stark_generator = SimpleStarkWitnessGenerator::new(
+            config,
+            stark,
+            virtual_proof,
+            public_input_target,
+            generator,
+        );
+        self.add_simple_generator(stark_generator);
+    }
+}
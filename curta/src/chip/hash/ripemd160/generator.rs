@@ -0,0 +1,270 @@
+use core::marker::PhantomData;
+
+use itertools::Itertools;
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::generator::{GeneratedValues, SimpleGenerator};
+use plonky2::iop::target::Target;
+use plonky2::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::circuit_data::CommonCircuitData;
+use plonky2::util::serialization::{Buffer, Read, Write};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    RIPEMD160Gadget, RIPEMD160PublicData, RIPEMD160_BLOCK_LEN, RIPEMD160_CV_WORDS,
+    RIPEMD160_CYCLE_LENGTH,
+};
+use crate::chip::trace::generator::ArithmeticGenerator;
+use crate::chip::uint::bytes::lookup_table::table::ByteLookupTable;
+use crate::chip::uint::operations::instruction::U32Instruction;
+use crate::chip::AirParameters;
+use crate::math::prelude::{CubicParameters, *};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct RIPEMD160AirParameters<F, E>(pub PhantomData<(F, E)>);
+
+/// The number of compressions of a single RIPEMD-160 proof.
+pub const RIPEMD160_NUM_COMPRESSIONS: usize = (1 << 16) / RIPEMD160_CYCLE_LENGTH;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct RIPEMD160Generator<F: PrimeField64, E: CubicParameters<F>> {
+    pub gadget: RIPEMD160Gadget,
+    pub table: ByteLookupTable,
+    pub messages: Vec<Target>,
+    pub message_lengths: Vec<usize>,
+    pub trace_generator: ArithmeticGenerator<RIPEMD160AirParameters<F, E>>,
+    pub pub_values_target: RIPEMD160PublicData<Target>,
+}
+
+impl<F: PrimeField64, E: CubicParameters<F>> AirParameters for RIPEMD160AirParameters<F, E> {
+    type Field = F;
+    type CubicParams = E;
+
+    type Instruction = U32Instruction;
+
+    const NUM_FREE_COLUMNS: usize = 1500;
+    const EXTENDED_COLUMNS: usize = 3700;
+    const NUM_ARITHMETIC_COLUMNS: usize = 0;
+
+    fn num_rows_bits() -> usize {
+        16
+    }
+}
+
+impl<F: RichField, E: CubicParameters<F>> RIPEMD160Generator<F, E> {
+    pub fn id() -> String {
+        "RIPEMD160Generator".to_string()
+    }
+}
+
+impl<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize> SimpleGenerator<F, D>
+    for RIPEMD160Generator<F, E>
+{
+    fn id(&self) -> String {
+        Self::id()
+    }
+
+    fn serialize(
+        &self,
+        dst: &mut Vec<u8>,
+        _: &CommonCircuitData<F, D>,
+    ) -> plonky2::util::serialization::IoResult<()> {
+        let data = bincode::serialize(self).unwrap();
+        dst.write_bytes(&data)
+    }
+
+    fn deserialize(
+        src: &mut Buffer,
+        _: &CommonCircuitData<F, D>,
+    ) -> plonky2::util::serialization::IoResult<Self>
+    where
+        Self: Sized,
+    {
+        let bytes = src.read_bytes()?;
+        let data = bincode::deserialize(&bytes).unwrap();
+        Ok(data)
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        self.messages.clone()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let messages = self
+            .messages
+            .iter()
+            .map(|x| witness.get_target(*x).as_canonical_u64() as u8)
+            .collect::<Vec<_>>();
+
+        let padded_messages = self.message_lengths.iter().scan(0, |idx, len| {
+            let padded_msg = RIPEMD160Gadget::pad(&messages[*idx..*idx + len]);
+            *idx += len;
+            Some(padded_msg)
+        });
+
+        // Write trace values
+        let writer = self.trace_generator.new_writer();
+        self.table.write_table_entries(&writer);
+        let ripemd160_public_values = self.gadget.write(padded_messages, &writer);
+        for i in 0..RIPEMD160AirParameters::<F, E>::num_rows() {
+            writer.write_row_instructions(&self.trace_generator.air_data, i);
+        }
+        self.table.write_multiplicities(&writer);
+
+        // Fill the chaining values into the output buffer
+        self.pub_values_target
+            .set_targets(ripemd160_public_values, out_buffer);
+    }
+}
+
+impl RIPEMD160PublicData<Target> {
+    /// Allocates the public data targets for a batch of messages, where the `i`-th message is
+    /// given by the next `message_lengths[i]` bytes of `messages` and its digest by the `i`-th 20
+    /// bytes of `digests`.
+    ///
+    /// The message lengths are fixed by the circuit, so the padding of the messages and the end
+    /// bits are constants. The chaining values after the blocks of a message other than the last
+    /// one are allocated as virtual targets.
+    pub fn add_virtual<F: RichField + Extendable<D>, const D: usize>(
+        builder: &mut CircuitBuilder<F, D>,
+        messages: &[Target],
+        message_lengths: &[usize],
+        digests: &[Target],
+    ) -> Self {
+        assert_eq!(
+            message_lengths.iter().sum::<usize>(),
+            messages.len(),
+            "Message lengths must add up to the number of message targets"
+        );
+
+        let mut public_block_targets: Vec<[Target; 4]> = Vec::new();
+        let mut end_bits_targets = Vec::new();
+        let mut hash_state_targets = Vec::new();
+
+        let mut message_index = 0;
+        for (len, digest) in message_lengths.iter().zip_eq(digests.chunks_exact(20)) {
+            let message = &messages[message_index..message_index + len];
+            message_index += len;
+
+            let padding = RIPEMD160Gadget::pad(&vec![0u8; *len])[*len..]
+                .iter()
+                .map(|byte| builder.constant(F::from_canonical_u8(*byte)))
+                .collect::<Vec<_>>();
+            let padded_msg = message.iter().copied().chain(padding).collect::<Vec<_>>();
+
+            let num_blocks = padded_msg.len() / RIPEMD160_BLOCK_LEN;
+            public_block_targets.extend(
+                padded_msg
+                    .chunks_exact(4)
+                    .map(|word| -> [Target; 4] { word.try_into().unwrap() }),
+            );
+            for i in 0..num_blocks {
+                let last = i == num_blocks - 1;
+                end_bits_targets.push(builder.constant_bool(last).target);
+
+                match last {
+                    true => hash_state_targets.extend(
+                        digest
+                            .chunks_exact(4)
+                            .map(|word| -> [Target; 4] { word.try_into().unwrap() }),
+                    ),
+                    false => hash_state_targets.extend(
+                        (0..RIPEMD160_CV_WORDS).map(|_| builder.add_virtual_target_arr::<4>()),
+                    ),
+                }
+            }
+        }
+        assert_eq!(
+            end_bits_targets.len(),
+            RIPEMD160_NUM_COMPRESSIONS,
+            "The messages must fill the {} compressions of the proof",
+            RIPEMD160_NUM_COMPRESSIONS
+        );
+
+        RIPEMD160PublicData {
+            public_block: public_block_targets,
+            end_bits: end_bits_targets,
+            hash_state: hash_state_targets,
+        }
+    }
+
+    /// Sets the chaining values, which are the only targets of the public data that are not
+    /// determined by the messages.
+    pub fn set_targets<F: RichField>(
+        &self,
+        values: RIPEMD160PublicData<F>,
+        out_buffer: &mut GeneratedValues<F>,
+    ) {
+        for (hash_target, hash_value) in self.hash_state.iter().zip_eq(values.hash_state.iter()) {
+            out_buffer.set_target_arr(hash_target, hash_value);
+        }
+    }
+
+    pub fn public_input_targets(&self) -> Vec<Target> {
+        self.public_block
+            .iter()
+            .flatten()
+            .chain(self.end_bits.iter())
+            .chain(self.hash_state.iter().flatten())
+            .copied()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+
+    use super::*;
+
+    #[test]
+    fn test_ripemd160_public_data_layout() {
+        type F = GoldilocksField;
+        const D: usize = 2;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        // A message of three blocks once padded, an empty message and messages of a single block.
+        let mut message_lengths = vec![130, 0];
+        message_lengths.extend([55; RIPEMD160_NUM_COMPRESSIONS - 4]);
+        let messages = builder.add_virtual_targets(message_lengths.iter().sum());
+        let digests = builder.add_virtual_targets(20 * message_lengths.len());
+
+        let public_data =
+            RIPEMD160PublicData::add_virtual(&mut builder, &messages, &message_lengths, &digests);
+
+        assert_eq!(
+            public_data.public_block.len(),
+            16 * RIPEMD160_NUM_COMPRESSIONS
+        );
+        assert_eq!(public_data.end_bits.len(), RIPEMD160_NUM_COMPRESSIONS);
+
+        // The last block of the first message holds two message bytes followed by the padding,
+        // which ends with the length of the message in bits.
+        let constant = |x: u8| builder.constant(F::from_canonical_u8(x));
+        let zero = builder.zero();
+        assert_eq!(public_data.public_block[32][..2], messages[128..130]);
+        assert_eq!(public_data.public_block[32][2..], [constant(0x80), zero]);
+        assert_eq!(
+            public_data.public_block[46],
+            [constant(0x10), constant(0x04), zero, zero]
+        );
+        assert_eq!(public_data.public_block[47], [zero; 4]);
+
+        // The digests are the chaining values of the last blocks.
+        let one = builder.one();
+        assert_eq!(public_data.end_bits[..4], [zero, zero, one, one]);
+        assert_eq!(public_data.hash_state[10..15].concat(), digests[..20]);
+        assert_eq!(public_data.hash_state[15..20].concat(), digests[20..40]);
+
+        let public_inputs = public_data.public_input_targets();
+        assert_eq!(
+            public_inputs.len(),
+            (4 * (16 + RIPEMD160_CV_WORDS) + 1) * RIPEMD160_NUM_COMPRESSIONS
+        );
+    }
+}
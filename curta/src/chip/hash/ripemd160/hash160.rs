@@ -0,0 +1,141 @@
+//! HASH160, the RIPEMD-160 digest of the SHA-256 digest of a message, used by Bitcoin to derive
+//! addresses from public keys and scripts.
+
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::Target;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+use super::builder_gadget::{RIPEMD160Builder, RIPEMD160BuilderGadget};
+use super::RIPEMD160Gadget;
+use crate::chip::hash::sha::sha256::builder_gadget::{
+    CurtaBytes, SHA256Builder, SHA256BuilderGadget,
+};
+use crate::chip::hash::sha::sha256::hmac::sha256_message;
+use crate::chip::hash::sha::sha256::SHA256Gadget;
+use crate::math::prelude::CubicParameters;
+
+pub trait Hash160Builder<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize>:
+    SHA256Builder<F, E, D> + RIPEMD160Builder<F, E, D>
+{
+    /// Computes HASH160 of `message`, whose length is fixed by the circuit.
+    ///
+    /// The SHA-256 hash is added to `sha_gadget` and the RIPEMD-160 hash to `ripemd160_gadget`,
+    /// so that they are proven in the same starks as the other messages of the gadgets.
+    fn hash160(
+        &mut self,
+        message: &[Target],
+        sha_gadget: &mut <Self as SHA256Builder<F, E, D>>::Gadget,
+        ripemd160_gadget: &mut <Self as RIPEMD160Builder<F, E, D>>::Gadget,
+    ) -> CurtaBytes<20>;
+}
+
+impl<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize> Hash160Builder<F, E, D>
+    for CircuitBuilder<F, D>
+{
+    fn hash160(
+        &mut self,
+        message: &[Target],
+        sha_gadget: &mut SHA256BuilderGadget<F, E, D>,
+        ripemd160_gadget: &mut RIPEMD160BuilderGadget<F, E, D>,
+    ) -> CurtaBytes<20> {
+        let sha_digest = sha256_message(self, message, sha_gadget);
+        RIPEMD160Builder::<F, E, D>::ripemd160(self, &sha_digest.0, ripemd160_gadget)
+    }
+}
+
+impl RIPEMD160Gadget {
+    /// Computes HASH160 of `msg` natively.
+    pub fn hash160(msg: &[u8]) -> [u8; 20] {
+        Self::hash(&SHA256Gadget::hash(msg))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::types::Field;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    type F = GoldilocksField;
+    type E = GoldilocksCubicParameters;
+    type SC = CurtaPoseidonGoldilocksConfig;
+    type C = PoseidonGoldilocksConfig;
+    const D: usize = 2;
+
+    /// A compressed public key and its HASH160, from the Bitcoin wiki example of an address.
+    const PUBLIC_KEY: &str = "0250863ad64a87ae8a2fe83c1af1a8403cb53f53e486d8511dad8a04887e5b2352";
+    const PUBLIC_KEY_HASH: &str = "f54a5851e9372b87810a8e60cdd2e7cfd80b6e31";
+
+    fn to_field_bytes(bytes: &[u8]) -> Vec<F> {
+        bytes.iter().map(|x| F::from_canonical_u8(*x)).collect()
+    }
+
+    #[test]
+    fn test_hash160_native() {
+        let public_key = hex::decode(PUBLIC_KEY).unwrap();
+        assert_eq!(
+            hex::encode(RIPEMD160Gadget::hash160(&public_key)),
+            PUBLIC_KEY_HASH
+        );
+    }
+
+    #[test]
+    fn test_hash160_plonky_gadget() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let mut sha_gadget = SHA256Builder::<F, E, D>::init_sha256(&mut builder);
+        let mut ripemd160_gadget = RIPEMD160Builder::<F, E, D>::init_ripemd160(&mut builder);
+
+        let public_key = hex::decode(PUBLIC_KEY).unwrap();
+        let public_key_targets = builder.add_virtual_targets(public_key.len());
+        let digest = Hash160Builder::<F, E, D>::hash160(
+            &mut builder,
+            &public_key_targets,
+            &mut sha_gadget,
+            &mut ripemd160_gadget,
+        );
+        let expected = builder.add_virtual_target_arr::<20>();
+        for (a, b) in digest.0.iter().zip(expected.iter()) {
+            builder.connect(*a, *b);
+        }
+
+        // The public key takes a single SHA-256 chunk, the remaining ones are filled with dummy
+        // messages. The unused RIPEMD-160 compressions are filled by the gadget.
+        let dummy_messages = (0..1024 - 1)
+            .map(|_| CurtaBytes(builder.add_virtual_target_arr::<64>()))
+            .collect::<Vec<_>>();
+        for message in dummy_messages.iter() {
+            builder.sha256(message, &mut sha_gadget);
+        }
+        builder.constrain_sha256_gadget::<SC>(sha_gadget);
+        RIPEMD160Builder::<F, E, D>::constrain_ripemd160_gadget::<SC>(
+            &mut builder,
+            ripemd160_gadget,
+        );
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+
+        pw.set_target_arr(&public_key_targets, &to_field_bytes(&public_key));
+        pw.set_target_arr(
+            &expected,
+            &to_field_bytes(&hex::decode(PUBLIC_KEY_HASH).unwrap()),
+        );
+
+        let dummy_padded_message = to_field_bytes(&SHA256Gadget::pad(b""));
+        for message in dummy_messages.iter() {
+            pw.set_target_arr(&message.0, &dummy_padded_message);
+        }
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+}
@@ -0,0 +1,765 @@
+//! RIPEMD-160 over 32-bit words.
+//!
+//! A message is padded as in MD4, with a bit length appended in little endian, and split into
+//! blocks of 64 bytes. Every compression runs two lines of 80 steps in parallel on copies of the
+//! chaining value, which differ in the order of the message words, the rotations, the constants and
+//! the order of the boolean functions of their five rounds.
+//!
+//! Every compression occupies a cycle of 128 rows, with a step of both lines on each of the first
+//! 80 rows and the remaining rows carrying the state to the end of the cycle, where the chaining
+//! value is published. Every step row reads its message words, its round and the rotations of both
+//! lines from the bus. The rotation amounts are fixed by the byte lookup, so every step computes
+//! the rotations by all the amounts used by the hash function and selects one of them.
+
+pub mod builder_gadget;
+pub mod generator;
+pub mod hash160;
+
+use core::array::from_fn;
+use core::borrow::Borrow;
+
+use serde::{Deserialize, Serialize};
+
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::builder::AirBuilder;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::cubic::CubicRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::{Register, RegisterSerializable, RegisterSized};
+use crate::chip::table::bus::global::Bus;
+use crate::chip::trace::writer::TraceWriter;
+use crate::chip::uint::bytes::lookup_table::builder_operations::ByteLookupOperations;
+use crate::chip::uint::bytes::operations::value::ByteOperation;
+use crate::chip::uint::bytes::register::ByteRegister;
+use crate::chip::uint::operations::instruction::U32Instructions;
+use crate::chip::uint::register::U32Register;
+use crate::chip::uint::util::u32_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+
+pub type U32Value<T> = <U32Register as Register>::Value<T>;
+
+/// The number of rows used to process a single compression.
+pub const RIPEMD160_CYCLE_LENGTH: usize = 128;
+
+/// The number of steps of each line of the compression function.
+pub const RIPEMD160_NUM_STEPS: usize = 80;
+
+/// The number of rounds of each line, of 16 steps each.
+pub const RIPEMD160_NUM_ROUNDS: usize = 5;
+
+/// The number of bytes of a block.
+pub const RIPEMD160_BLOCK_LEN: usize = 64;
+
+/// The number of words of a chaining value, which is also the digest.
+pub const RIPEMD160_CV_WORDS: usize = 5;
+
+const IV: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+/// The left rotations of the steps of both lines, in increasing order.
+const ROTATIONS: [usize; 10] = [5, 6, 7, 8, 9, 11, 12, 13, 14, 15];
+
+/// The constants of one of the two lines of the compression function.
+struct Line {
+    /// The message word of every step of every round
+    words: [[usize; 16]; RIPEMD160_NUM_ROUNDS],
+    /// The left rotation of every step of every round
+    rotations: [[usize; 16]; RIPEMD160_NUM_ROUNDS],
+    /// The additive constant of every round
+    constants: [u32; RIPEMD160_NUM_ROUNDS],
+    /// The boolean function of every round, as an index into the functions `f1, ..., f5`
+    functions: [usize; RIPEMD160_NUM_ROUNDS],
+}
+
+const LEFT: Line = Line {
+    words: [
+        [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+        [7, 4, 13, 1, 10, 6, 15, 3, 12, 0, 9, 5, 2, 14, 11, 8],
+        [3, 10, 14, 4, 9, 15, 8, 1, 2, 7, 0, 6, 13, 11, 5, 12],
+        [1, 9, 11, 10, 0, 8, 12, 4, 13, 3, 7, 15, 14, 5, 6, 2],
+        [4, 0, 5, 9, 7, 12, 2, 10, 14, 1, 3, 8, 11, 6, 15, 13],
+    ],
+    rotations: [
+        [11, 14, 15, 12, 5, 8, 7, 9, 11, 13, 14, 15, 6, 7, 9, 8],
+        [7, 6, 8, 13, 11, 9, 7, 15, 7, 12, 15, 9, 11, 7, 13, 12],
+        [11, 13, 6, 7, 14, 9, 13, 15, 14, 8, 13, 6, 5, 12, 7, 5],
+        [11, 12, 14, 15, 14, 15, 9, 8, 9, 14, 5, 6, 8, 6, 5, 12],
+        [9, 15, 5, 11, 6, 8, 13, 12, 5, 12, 13, 14, 11, 8, 5, 6],
+    ],
+    constants: [0x00000000, 0x5A827999, 0x6ED9EBA1, 0x8F1BBCDC, 0xA953FD4E],
+    functions: [0, 1, 2, 3, 4],
+};
+
+const RIGHT: Line = Line {
+    words: [
+        [5, 14, 7, 0, 9, 2, 11, 4, 13, 6, 15, 8, 1, 10, 3, 12],
+        [6, 11, 3, 7, 0, 13, 5, 10, 14, 15, 8, 12, 4, 9, 1, 2],
+        [15, 5, 1, 3, 7, 14, 6, 9, 11, 8, 12, 2, 10, 0, 4, 13],
+        [8, 6, 4, 1, 3, 11, 15, 0, 5, 12, 2, 13, 9, 7, 10, 14],
+        [12, 15, 10, 4, 1, 5, 8, 7, 6, 2, 13, 14, 0, 3, 9, 11],
+    ],
+    rotations: [
+        [8, 9, 9, 11, 13, 15, 15, 5, 7, 7, 8, 11, 14, 14, 12, 6],
+        [9, 13, 15, 7, 12, 8, 9, 11, 7, 7, 12, 7, 6, 15, 13, 11],
+        [9, 7, 15, 11, 8, 6, 6, 14, 12, 13, 5, 14, 13, 13, 7, 5],
+        [15, 5, 8, 11, 14, 14, 6, 14, 6, 9, 12, 9, 12, 5, 15, 8],
+        [8, 5, 12, 9, 12, 5, 14, 6, 8, 13, 6, 5, 15, 13, 11, 11],
+    ],
+    constants: [0x50A28BE6, 0x5C4DD124, 0x6D703EF3, 0x7A6D76E9, 0x00000000],
+    functions: [4, 3, 2, 1, 0],
+};
+
+impl Line {
+    /// The message word, the round and the index of the rotation in `ROTATIONS` of a step.
+    fn step(&self, step: usize) -> (usize, usize, usize) {
+        let (round, i) = (step / 16, step % 16);
+        let rotation = ROTATIONS
+            .iter()
+            .position(|s| *s == self.rotations[round][i])
+            .unwrap();
+        (self.words[round][i], round, rotation)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RIPEMD160Gadget {
+    /// The message words of every compression
+    pub public_block: ArrayRegister<U32Register>,
+    /// The bit marking the last block of a message for every compression
+    pub public_end_bits: ArrayRegister<BitRegister>,
+    /// The chaining value output by every compression, which is the digest at the last block
+    pub state: ArrayRegister<U32Register>,
+    /// Signifies the first row of a compression
+    pub load_bit: BitRegister,
+    /// Signifies the rows that perform a step
+    pub step_bit: BitRegister,
+    pub(crate) end_bit: BitRegister,
+    pub(crate) word_left: U32Register,
+    pub(crate) word_right: U32Register,
+    pub(crate) round_selectors: ArrayRegister<BitRegister>,
+    pub(crate) rotation_left: ArrayRegister<BitRegister>,
+    pub(crate) rotation_right: ArrayRegister<BitRegister>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RIPEMD160PublicData<T> {
+    pub public_block: Vec<U32Value<T>>,
+    pub end_bits: Vec<T>,
+    pub hash_state: Vec<U32Value<T>>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    pub fn process_ripemd160_batch(
+        &mut self,
+        clk: &ElementRegister,
+        bus: &mut Bus<L::CubicParams>,
+        bus_channel_idx: usize,
+        operations: &mut ByteLookupOperations,
+    ) -> RIPEMD160Gadget
+    where
+        L::Instruction: U32Instructions,
+    {
+        assert_eq!(
+            L::num_rows() % RIPEMD160_CYCLE_LENGTH,
+            0,
+            "The number of rows must be a multiple of the cycle length"
+        );
+        let num_compressions = L::num_rows() / RIPEMD160_CYCLE_LENGTH;
+
+        // Registers to be written to. The load bit and the inputs of a compression are written
+        // ahead of the row instructions so that the transition into a new compression can read
+        // them.
+        let load_bit = self.alloc::<BitRegister>();
+        let step_bit = self.alloc::<BitRegister>();
+        let end_bit = self.alloc::<BitRegister>();
+        let word_left = self.alloc::<U32Register>();
+        let word_right = self.alloc::<U32Register>();
+        let round_selectors = self.alloc_array::<BitRegister>(RIPEMD160_NUM_ROUNDS);
+        let rotation_left = self.alloc_array::<BitRegister>(ROTATIONS.len());
+        let rotation_right = self.alloc_array::<BitRegister>(ROTATIONS.len());
+        let cycle_128 = self.cycle(7);
+        self.assert_equal(&load_bit, &cycle_128.start_bit);
+
+        // Public values
+        let public_block = self.alloc_array_public::<U32Register>(16 * num_compressions);
+        let public_end_bits = self.alloc_array_public::<BitRegister>(num_compressions);
+        let hash_state =
+            self.alloc_array_public::<U32Register>(RIPEMD160_CV_WORDS * num_compressions);
+
+        let one = ArithmeticExpression::<L::Field>::one();
+        let load_next = load_bit.next().expr::<L::Field>();
+
+        // Get the end bit from the bus at the start of every cycle and copy it to the rest of the
+        // cycle
+        let end_challenges = self.alloc_challenge_array::<CubicRegister>(2);
+        let clk_end = self.accumulate_expressions(&end_challenges, &[clk.expr(), end_bit.expr()]);
+        self.output_from_bus_filtered(bus_channel_idx, clk_end, load_bit.expr());
+        self.set_to_expression_transition(
+            &end_bit.next(),
+            load_next.clone() * end_bit.next().expr()
+                + (one.clone() - load_next.clone()) * end_bit.expr(),
+        );
+
+        // Get the message words, the round and the rotations of both lines at every step row. A
+        // step bit which does not match the steps of the cycle leaves the bus unbalanced.
+        let step_challenges = self.alloc_challenge_array::<CubicRegister>(
+            1 + 2 * U32Register::size_of() + RIPEMD160_NUM_ROUNDS + 2 * ROTATIONS.len(),
+        );
+        let clk_step = self.accumulate_expressions(
+            &step_challenges,
+            &[
+                clk.expr(),
+                word_left.expr(),
+                word_right.expr(),
+                round_selectors.expr(),
+                rotation_left.expr(),
+                rotation_right.expr(),
+            ],
+        );
+        self.output_from_bus_filtered(bus_channel_idx, clk_step, step_bit.expr());
+
+        let word_expression = |word: u32| {
+            ArithmeticExpression::from_constant_vec(
+                u32_to_le_field_bytes::<L::Field>(word).to_vec(),
+            )
+        };
+
+        // The first compression starts from the initial hash in both lines
+        let h = self.alloc_array::<U32Register>(RIPEMD160_CV_WORDS);
+        let left = self.alloc_array::<U32Register>(RIPEMD160_CV_WORDS);
+        let right = self.alloc_array::<U32Register>(RIPEMD160_CV_WORDS);
+        for i in 0..RIPEMD160_CV_WORDS {
+            for register in [&h, &left, &right] {
+                self.set_to_expression_first_row(&register.get(i), word_expression(IV[i]));
+            }
+        }
+
+        // Apply a step to both lines
+        let left_output = self.ripemd160_step(
+            &left,
+            &word_left,
+            &round_selectors,
+            &rotation_left,
+            &LEFT,
+            operations,
+        );
+        let right_output = self.ripemd160_step(
+            &right,
+            &word_right,
+            &round_selectors,
+            &rotation_right,
+            &RIGHT,
+            operations,
+        );
+
+        // The output chaining value
+        let chaining_value = self.alloc_array::<U32Register>(RIPEMD160_CV_WORDS);
+        for (i, word) in chaining_value.iter().enumerate() {
+            let sum = self.add_u32(&h.get((i + 1) % 5), &left.get((i + 2) % 5), operations);
+            let carry = self.alloc::<BitRegister>();
+            self.set_add_u32(
+                &sum,
+                &right.get((i + 3) % 5),
+                &None,
+                &word,
+                &carry,
+                operations,
+            );
+        }
+
+        // Assign the next state: the step output in the step rows, the current state in the
+        // remaining rows, and the initial state of the next compression at the end of a cycle,
+        // which is the initial hash after the last block of a message and the current chaining
+        // value otherwise.
+        for i in 0..RIPEMD160_CV_WORDS {
+            let initial = end_bit.expr() * word_expression(IV[i])
+                + end_bit.not_expr() * chaining_value.get(i).expr();
+            self.set_to_expression_transition(
+                &h.get(i).next(),
+                load_next.clone() * initial.clone()
+                    + (one.clone() - load_next.clone()) * h.get(i).expr(),
+            );
+            for (line, output) in [(&left, &left_output), (&right, &right_output)] {
+                self.set_to_expression_transition(
+                    &line.get(i).next(),
+                    load_next.clone() * initial.clone()
+                        + (one.clone() - load_next.clone())
+                            * (step_bit.expr() * output[i].expr()
+                                + step_bit.not_expr() * line.get(i).expr()),
+                );
+            }
+        }
+
+        // Put the chaining value of every compression in the bus at the end of its cycle
+        let state_challenges = self.alloc_challenge_array::<CubicRegister>(
+            U32Register::size_of() * RIPEMD160_CV_WORDS + 1,
+        );
+        let clk_state =
+            self.accumulate_expressions(&state_challenges, &[clk.expr(), chaining_value.expr()]);
+        self.input_to_bus_filtered(bus_channel_idx, clk_state, cycle_128.end_bit.expr());
+
+        // Put the public inputs and chaining values in the bus
+        let one_hot = |index: usize, len: usize| {
+            ArithmeticExpression::from_constant_vec(
+                (0..len)
+                    .map(|k| L::Field::from_canonical_u8((k == index) as u8))
+                    .collect(),
+            )
+        };
+        for i in 0..num_compressions {
+            let clk_start = L::Field::from_canonical_usize(i * RIPEMD160_CYCLE_LENGTH);
+            let end_digest = self.accumulate_public_expressions(
+                &end_challenges,
+                &[
+                    ArithmeticExpression::from_constant(clk_start),
+                    public_end_bits.get(i).expr(),
+                ],
+            );
+            bus.insert_global_value(&end_digest);
+
+            for j in 0..RIPEMD160_NUM_STEPS {
+                let (word_left, round, rotation_left) = LEFT.step(j);
+                let (word_right, _, rotation_right) = RIGHT.step(j);
+                let clk_step = clk_start + L::Field::from_canonical_usize(j);
+                let step_digest = self.accumulate_public_expressions(
+                    &step_challenges,
+                    &[
+                        ArithmeticExpression::from_constant(clk_step),
+                        public_block.get(16 * i + word_left).expr(),
+                        public_block.get(16 * i + word_right).expr(),
+                        one_hot(round, RIPEMD160_NUM_ROUNDS),
+                        one_hot(rotation_left, ROTATIONS.len()),
+                        one_hot(rotation_right, ROTATIONS.len()),
+                    ],
+                );
+                bus.insert_global_value(&step_digest);
+            }
+
+            let clk_end = clk_start + L::Field::from_canonical_usize(RIPEMD160_CYCLE_LENGTH - 1);
+            let state_digest = self.accumulate_public_expressions(
+                &state_challenges,
+                &[
+                    ArithmeticExpression::from_constant(clk_end),
+                    hash_state
+                        .get_subarray(RIPEMD160_CV_WORDS * i..RIPEMD160_CV_WORDS * (i + 1))
+                        .expr(),
+                ],
+            );
+            bus.output_global_value(&state_digest);
+        }
+
+        // The byte lookup needs an even number of operations
+        if operations.values.len() % 2 == 1 {
+            let dummy = self.alloc::<ByteRegister>();
+            let dummy_range = ByteOperation::Range(dummy);
+            self.set_byte_operation(&dummy_range, operations);
+        }
+
+        RIPEMD160Gadget {
+            public_block,
+            public_end_bits,
+            state: hash_state,
+            load_bit,
+            step_bit,
+            end_bit,
+            word_left,
+            word_right,
+            round_selectors,
+            rotation_left,
+            rotation_right,
+        }
+    }
+
+    /// Applies a step of `line` to the five words of `state`, with the round and the rotation
+    /// given by the one-hot `round_selectors` and `rotation_selectors`. The selectors are zero in
+    /// the rows without a step, so that all the values of the step are zero there as well.
+    fn ripemd160_step(
+        &mut self,
+        state: &ArrayRegister<U32Register>,
+        word: &U32Register,
+        round_selectors: &ArrayRegister<BitRegister>,
+        rotation_selectors: &ArrayRegister<BitRegister>,
+        line: &Line,
+        operations: &mut ByteLookupOperations,
+    ) -> [U32Register; 5]
+    where
+        L::Instruction: U32Instructions,
+    {
+        let [a, b, c, d, e]: [U32Register; 5] = from_fn(|i| state.get(i));
+        let functions = self.ripemd160_functions(&b, &c, &d, operations);
+
+        // The boolean function and the constant of the round
+        let function = self.alloc::<U32Register>();
+        let function_value = round_selectors
+            .iter()
+            .zip(line.functions)
+            .map(|(bit, k)| bit.expr() * functions[k].expr())
+            .reduce(|acc, x| acc + x)
+            .unwrap();
+        self.set_to_expression(&function, function_value);
+
+        let constant = self.alloc::<U32Register>();
+        let constant_value = round_selectors
+            .iter()
+            .zip(line.constants)
+            .map(|(bit, k)| {
+                bit.expr()
+                    * ArithmeticExpression::from_constant_vec(
+                        u32_to_le_field_bytes::<L::Field>(k).to_vec(),
+                    )
+            })
+            .reduce(|acc, x| acc + x)
+            .unwrap();
+        self.set_to_expression(&constant, constant_value);
+
+        let sum = self.add_u32(&a, &function, operations);
+        let sum = self.add_u32(&sum, word, operations);
+        let sum = self.add_u32(&sum, &constant, operations);
+
+        // The rotation of the step
+        let rotated = self.alloc::<U32Register>();
+        let rotated_value = ROTATIONS
+            .iter()
+            .zip(rotation_selectors.iter())
+            .map(|(s, bit)| bit.expr() * self.bit_rotate_right(&sum, 32 - s, operations).expr())
+            .reduce(|acc, x| acc + x)
+            .unwrap();
+        self.set_to_expression(&rotated, rotated_value);
+
+        let new_b = self.add_u32(&rotated, &e, operations);
+        let c_rotated = self.bit_rotate_right(&c, 22, operations);
+        [e, new_b, b, c_rotated, d]
+    }
+
+    /// The five boolean functions of RIPEMD-160, written with `xor`, `and` and `not`:
+    ///  - `f1 = b ^ c ^ d`,
+    ///  - `f2 = (b & c) | (!b & d) = d ^ (b & (c ^ d))`,
+    ///  - `f3 = (b | !c) ^ d = !((!b & c) ^ d)`,
+    ///  - `f4 = (b & d) | (c & !d) = c ^ (d & (b ^ c))`,
+    ///  - `f5 = b ^ (c | !d) = !(b ^ (!c & d))`.
+    fn ripemd160_functions(
+        &mut self,
+        b: &U32Register,
+        c: &U32Register,
+        d: &U32Register,
+        operations: &mut ByteLookupOperations,
+    ) -> [U32Register; 5]
+    where
+        L::Instruction: U32Instructions,
+    {
+        let b_xor_c = self.bitwise_xor(b, c, operations);
+        let f1 = self.bitwise_xor(&b_xor_c, d, operations);
+
+        let c_xor_d = self.bitwise_xor(c, d, operations);
+        let b_and = self.bitwise_and(b, &c_xor_d, operations);
+        let f2 = self.bitwise_xor(d, &b_and, operations);
+
+        let not_b = self.bitwise_not(b, operations);
+        let not_b_and_c = self.bitwise_and(&not_b, c, operations);
+        let xor = self.bitwise_xor(&not_b_and_c, d, operations);
+        let f3 = self.bitwise_not(&xor, operations);
+
+        let d_and = self.bitwise_and(d, &b_xor_c, operations);
+        let f4 = self.bitwise_xor(c, &d_and, operations);
+
+        let not_c = self.bitwise_not(c, operations);
+        let not_c_and_d = self.bitwise_and(&not_c, d, operations);
+        let xor = self.bitwise_xor(b, &not_c_and_d, operations);
+        let f5 = self.bitwise_not(&xor, operations);
+
+        [f1, f2, f3, f4, f5]
+    }
+}
+
+impl RIPEMD160Gadget {
+    /// Writes the compressions of `padded_messages`, which must fill all the cycles of the trace.
+    pub fn write<F: Field, I: IntoIterator>(
+        &self,
+        padded_messages: I,
+        writer: &TraceWriter<F>,
+    ) -> RIPEMD160PublicData<F>
+    where
+        I::Item: Borrow<[u8]>,
+    {
+        let mut public_block = Vec::new();
+        let mut end_bits_values = Vec::new();
+        let mut hash_values = Vec::new();
+        for padded_msg in padded_messages {
+            let padded_msg = padded_msg.borrow();
+            assert_eq!(
+                padded_msg.len() % RIPEMD160_BLOCK_LEN,
+                0,
+                "Padded message length must be a multiple of 64"
+            );
+            let num_blocks = padded_msg.len() / RIPEMD160_BLOCK_LEN;
+
+            let mut state = IV;
+            for (k, chunk) in padded_msg.chunks_exact(RIPEMD160_BLOCK_LEN).enumerate() {
+                let block = Self::block_words(chunk);
+                state = Self::compress(&state, &block);
+
+                public_block.extend(block.map(u32_to_le_field_bytes::<F>));
+                end_bits_values.push(F::from_canonical_u8((k == num_blocks - 1) as u8));
+                hash_values.extend(state.map(u32_to_le_field_bytes::<F>));
+            }
+        }
+        let num_compressions = self.public_end_bits.len();
+        assert!(
+            end_bits_values.len() == num_compressions,
+            "Padded messages lengths do not add up"
+        );
+
+        let one_hot = |index: usize, len: usize| {
+            (0..len)
+                .map(|k| F::from_canonical_u8((k == index) as u8))
+                .collect::<Vec<_>>()
+        };
+        for (i, end_bit) in end_bits_values.iter().enumerate() {
+            let row = i * RIPEMD160_CYCLE_LENGTH;
+            for j in 0..RIPEMD160_CYCLE_LENGTH {
+                writer.write(
+                    &self.load_bit,
+                    &F::from_canonical_u8((j == 0) as u8),
+                    row + j,
+                );
+                writer.write(
+                    &self.step_bit,
+                    &F::from_canonical_u8((j < RIPEMD160_NUM_STEPS) as u8),
+                    row + j,
+                );
+            }
+            writer.write(&self.end_bit, end_bit, row);
+            for j in 0..RIPEMD160_NUM_STEPS {
+                let (word_left, round, rotation_left) = LEFT.step(j);
+                let (word_right, _, rotation_right) = RIGHT.step(j);
+                writer.write(&self.word_left, &public_block[16 * i + word_left], row + j);
+                writer.write(
+                    &self.word_right,
+                    &public_block[16 * i + word_right],
+                    row + j,
+                );
+                writer.write_array(
+                    &self.round_selectors,
+                    one_hot(round, RIPEMD160_NUM_ROUNDS),
+                    row + j,
+                );
+                writer.write_array(
+                    &self.rotation_left,
+                    one_hot(rotation_left, ROTATIONS.len()),
+                    row + j,
+                );
+                writer.write_array(
+                    &self.rotation_right,
+                    one_hot(rotation_right, ROTATIONS.len()),
+                    row + j,
+                );
+            }
+        }
+
+        writer.write_array(&self.public_block, &public_block, 0);
+        writer.write_array(&self.public_end_bits, &end_bits_values, 0);
+        writer.write_array(&self.state, &hash_values, 0);
+
+        RIPEMD160PublicData {
+            public_block,
+            end_bits: end_bits_values,
+            hash_state: hash_values,
+        }
+    }
+
+    /// Pads a message to a multiple of 64 bytes with a one bit, zeros and the length of the
+    /// message in bits as a little endian 64-bit number.
+    pub fn pad(msg: &[u8]) -> Vec<u8> {
+        let mut padded_msg = msg.to_vec();
+        padded_msg.push(1 << 7);
+        let padlen = (RIPEMD160_BLOCK_LEN - 8 - padded_msg.len() % RIPEMD160_BLOCK_LEN)
+            % RIPEMD160_BLOCK_LEN;
+        padded_msg.extend_from_slice(&vec![0u8; padlen]);
+        padded_msg.extend_from_slice(&(8 * msg.len() as u64).to_le_bytes());
+        padded_msg
+    }
+
+    /// The little endian words of a block.
+    pub fn block_words(block: &[u8]) -> [u32; 16] {
+        from_fn(|i| u32::from_le_bytes(block[4 * i..4 * (i + 1)].try_into().unwrap()))
+    }
+
+    /// The RIPEMD-160 compression function.
+    pub fn compress(chaining_value: &[u32; 5], block: &[u32; 16]) -> [u32; 5] {
+        let step = |state: [u32; 5], line: &Line, j: usize| {
+            let [a, b, c, d, e] = state;
+            let (word, round, rotation) = line.step(j);
+            let function = match line.functions[round] {
+                0 => b ^ c ^ d,
+                1 => (b & c) | (!b & d),
+                2 => (b | !c) ^ d,
+                3 => (b & d) | (c & !d),
+                _ => b ^ (c | !d),
+            };
+            let sum = a
+                .wrapping_add(function)
+                .wrapping_add(block[word])
+                .wrapping_add(line.constants[round]);
+            let new_b = sum.rotate_left(ROTATIONS[rotation] as u32).wrapping_add(e);
+            [e, new_b, b, c.rotate_left(10), d]
+        };
+
+        let (mut left, mut right) = (*chaining_value, *chaining_value);
+        for j in 0..RIPEMD160_NUM_STEPS {
+            left = step(left, &LEFT, j);
+            right = step(right, &RIGHT, j);
+        }
+        from_fn(|i| {
+            chaining_value[(i + 1) % 5]
+                .wrapping_add(left[(i + 2) % 5])
+                .wrapping_add(right[(i + 3) % 5])
+        })
+    }
+
+    /// Computes the RIPEMD-160 digest of `msg` natively.
+    pub fn hash(msg: &[u8]) -> [u8; 20] {
+        let mut state = IV;
+        for chunk in Self::pad(msg).chunks_exact(RIPEMD160_BLOCK_LEN) {
+            state = Self::compress(&state, &Self::block_words(chunk));
+        }
+        state.map(u32::to_le_bytes).concat().try_into().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::timed;
+    use plonky2::util::timing::TimingTree;
+
+    use super::*;
+    pub use crate::chip::builder::tests::*;
+    use crate::chip::builder::AirBuilder;
+    use crate::chip::uint::operations::instruction::U32Instruction;
+    use crate::chip::AirParameters;
+
+    const EMPTY_DIGEST: &str = "9c1185a5c5e9fc54612808977ee8f548b2258d31";
+    const ABC_DIGEST: &str = "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc";
+    /// The digest of the 200 bytes `0, 1, ..., 199`, which span four blocks once padded.
+    const LONG_DIGEST: &str = "c315823ea8fe07a2dd18de4e545255afe3af0738";
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub struct RIPEMD160Test;
+
+    impl AirParameters for RIPEMD160Test {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = U32Instruction;
+
+        const NUM_FREE_COLUMNS: usize = 1500;
+        const EXTENDED_COLUMNS: usize = 3700;
+        const NUM_ARITHMETIC_COLUMNS: usize = 0;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    #[test]
+    fn test_ripemd160_native_hash() {
+        assert_eq!(hex::encode(RIPEMD160Gadget::hash(b"")), EMPTY_DIGEST);
+        assert_eq!(hex::encode(RIPEMD160Gadget::hash(b"abc")), ABC_DIGEST);
+        let long_msg = (0..200).map(|i| i as u8).collect::<Vec<_>>();
+        assert_eq!(hex::encode(RIPEMD160Gadget::hash(&long_msg)), LONG_DIGEST);
+
+        // A message of 55 bytes fits in a single block with its padding, one of 56 bytes does not.
+        assert_eq!(RIPEMD160Gadget::pad(&[0u8; 55]).len(), 64);
+        assert_eq!(RIPEMD160Gadget::pad(&[0u8; 56]).len(), 128);
+        assert_eq!(RIPEMD160Gadget::pad(&[0u8; 64]).len(), 128);
+    }
+
+    #[test]
+    fn test_ripemd160_stark() {
+        type F = GoldilocksField;
+        type L = RIPEMD160Test;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut timing = TimingTree::new("RIPEMD-160 test", log::Level::Debug);
+
+        let mut builder = AirBuilder::<L>::new();
+        let clk = builder.clock();
+
+        let (mut operations, table) = builder.byte_operations();
+
+        let mut bus = builder.new_bus();
+        let channel_idx = bus.new_channel(&mut builder);
+
+        let ripemd160_gadget =
+            builder.process_ripemd160_batch(&clk, &mut bus, channel_idx, &mut operations);
+
+        builder.register_byte_lookup(operations, &table);
+        builder.constrain_bus(bus);
+
+        let (air, trace_data) = builder.build();
+
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+        let writer = generator.new_writer();
+
+        // A message of four blocks followed by single block messages to fill the trace.
+        let mut messages = vec![(0..200).map(|i| i as u8).collect::<Vec<_>>()];
+        let mut expected_digests = vec![LONG_DIGEST];
+        for i in 0..L::num_rows() / RIPEMD160_CYCLE_LENGTH - 4 {
+            messages.push([b"".to_vec(), b"abc".to_vec()][i % 2].clone());
+            expected_digests.push([EMPTY_DIGEST, ABC_DIGEST][i % 2]);
+        }
+        let padded_messages = messages
+            .iter()
+            .map(|m| RIPEMD160Gadget::pad(m))
+            .collect::<Vec<_>>();
+
+        timed!(timing, "Write the execution trace", {
+            table.write_table_entries(&writer);
+            ripemd160_gadget.write(padded_messages.iter().map(|m| m.as_slice()), &writer);
+            for i in 0..L::num_rows() {
+                writer.write_row_instructions(&generator.air_data, i);
+            }
+            table.write_multiplicities(&writer);
+        });
+
+        // The digest is the chaining value of the last compression of every message
+        let mut last = 0;
+        for (padded_msg, digest) in padded_messages.iter().zip(expected_digests) {
+            last += padded_msg.len() / RIPEMD160_BLOCK_LEN;
+            let hash = writer.read_array(
+                &ripemd160_gadget
+                    .state
+                    .get_subarray(RIPEMD160_CV_WORDS * (last - 1)..RIPEMD160_CV_WORDS * last),
+                0,
+            );
+            let expected = hex::decode(digest)
+                .unwrap()
+                .chunks_exact(4)
+                .map(|x| u32_to_le_field_bytes::<F>(u32::from_le_bytes(x.try_into().unwrap())))
+                .collect::<Vec<_>>();
+            assert_eq!(hash, expected);
+        }
+
+        let public_inputs = writer.0.public.read().unwrap().clone();
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        timed!(
+            timing,
+            "Stark proof and verify",
+            test_starky(&stark, &config, &generator, &public_inputs)
+        );
+
+        // Generate recursive proof
+        timed!(
+            timing,
+            "Recursive proof generation and verification",
+            test_recursive_starky(stark, config, generator, &public_inputs)
+        );
+
+        timing.print();
+    }
+}
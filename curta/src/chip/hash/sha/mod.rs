@@ -1 +1,4 @@
+#[cfg(feature = "legacy-hashes")]
+pub mod sha1;
 pub mod sha256;
+pub mod sha512;
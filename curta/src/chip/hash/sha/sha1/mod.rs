@@ -0,0 +1,612 @@
+//! SHA-1, for legacy formats such as X.509 certificates and git objects.
+//!
+//! SHA-1 is broken as a collision resistant hash function, so this chip is only built with the
+//! `legacy-hashes` feature and should only be used to verify data whose format requires it.
+//!
+//! Every compression occupies a cycle of 128 rows, with one of the 80 rounds on each of the first
+//! 80 rows and the remaining rows carrying the state to the end of the cycle, where the chaining
+//! value is published. The message schedule is computed in a window of the last 16 words, which is
+//! shifted at every row. Every round row reads from the bus whether its word is a message word,
+//! the message word itself and the stage of the round, which selects the boolean function and the
+//! constant of the round.
+
+use core::array::from_fn;
+use core::borrow::Borrow;
+
+use serde::{Deserialize, Serialize};
+
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::builder::AirBuilder;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::cubic::CubicRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::{Register, RegisterSerializable, RegisterSized};
+use crate::chip::table::bus::global::Bus;
+use crate::chip::trace::writer::TraceWriter;
+use crate::chip::uint::bytes::lookup_table::builder_operations::ByteLookupOperations;
+use crate::chip::uint::bytes::operations::value::ByteOperation;
+use crate::chip::uint::bytes::register::ByteRegister;
+use crate::chip::uint::operations::instruction::U32Instructions;
+use crate::chip::uint::register::U32Register;
+use crate::chip::uint::util::u32_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+
+pub type U32Value<T> = <U32Register as Register>::Value<T>;
+
+/// The number of rows used to process a single compression.
+pub const SHA1_CYCLE_LENGTH: usize = 128;
+
+/// The number of rounds of the compression function.
+pub const SHA1_NUM_ROUNDS: usize = 80;
+
+/// The number of stages of 20 rounds, each with its own boolean function and constant.
+pub const SHA1_NUM_STAGES: usize = 4;
+
+/// The number of bytes of a block.
+pub const SHA1_BLOCK_LEN: usize = 64;
+
+/// The number of words of a chaining value, which is also the digest.
+pub const SHA1_CV_WORDS: usize = 5;
+
+const INITIAL_HASH: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+const ROUND_CONSTANTS: [u32; SHA1_NUM_STAGES] = [0x5A827999, 0x6ED9EBA1, 0x8F1BBCDC, 0xCA62C1D6];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SHA1Gadget {
+    /// The message words of every compression
+    pub public_block: ArrayRegister<U32Register>,
+    /// The bit marking the last block of a message for every compression
+    pub public_end_bits: ArrayRegister<BitRegister>,
+    /// The chaining value output by every compression, which is the digest at the last block
+    pub state: ArrayRegister<U32Register>,
+    /// Signifies the first row of a compression
+    pub load_bit: BitRegister,
+    /// Signifies the rows that perform a round
+    pub round_bit: BitRegister,
+    pub(crate) end_bit: BitRegister,
+    pub(crate) input_bit: BitRegister,
+    pub(crate) input_word: U32Register,
+    pub(crate) stage_selectors: ArrayRegister<BitRegister>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SHA1PublicData<T> {
+    pub public_block: Vec<U32Value<T>>,
+    pub end_bits: Vec<T>,
+    pub hash_state: Vec<U32Value<T>>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    pub fn process_sha1_batch(
+        &mut self,
+        clk: &ElementRegister,
+        bus: &mut Bus<L::CubicParams>,
+        bus_channel_idx: usize,
+        operations: &mut ByteLookupOperations,
+    ) -> SHA1Gadget
+    where
+        L::Instruction: U32Instructions,
+    {
+        assert_eq!(
+            L::num_rows() % SHA1_CYCLE_LENGTH,
+            0,
+            "The number of rows must be a multiple of the cycle length"
+        );
+        let num_compressions = L::num_rows() / SHA1_CYCLE_LENGTH;
+
+        // Registers to be written to. The load bit and the inputs of a compression are written
+        // ahead of the row instructions so that the transition into a new compression can read
+        // them.
+        let load_bit = self.alloc::<BitRegister>();
+        let round_bit = self.alloc::<BitRegister>();
+        let end_bit = self.alloc::<BitRegister>();
+        let input_bit = self.alloc::<BitRegister>();
+        let input_word = self.alloc::<U32Register>();
+        let stage_selectors = self.alloc_array::<BitRegister>(SHA1_NUM_STAGES);
+        let cycle_128 = self.cycle(7);
+        self.assert_equal(&load_bit, &cycle_128.start_bit);
+
+        // Public values
+        let public_block = self.alloc_array_public::<U32Register>(16 * num_compressions);
+        let public_end_bits = self.alloc_array_public::<BitRegister>(num_compressions);
+        let hash_state = self.alloc_array_public::<U32Register>(SHA1_CV_WORDS * num_compressions);
+
+        let one = ArithmeticExpression::<L::Field>::one();
+        let load_next = load_bit.next().expr::<L::Field>();
+
+        // Get the end bit from the bus at the start of every cycle and copy it to the rest of the
+        // cycle
+        let end_challenges = self.alloc_challenge_array::<CubicRegister>(2);
+        let clk_end = self.accumulate_expressions(&end_challenges, &[clk.expr(), end_bit.expr()]);
+        self.output_from_bus_filtered(bus_channel_idx, clk_end, load_bit.expr());
+        self.set_to_expression_transition(
+            &end_bit.next(),
+            load_next.clone() * end_bit.next().expr()
+                + (one.clone() - load_next.clone()) * end_bit.expr(),
+        );
+
+        // Get the message word and the stage at every round row. A round bit which does not match
+        // the rounds of the cycle leaves the bus unbalanced.
+        let round_challenges = self
+            .alloc_challenge_array::<CubicRegister>(2 + U32Register::size_of() + SHA1_NUM_STAGES);
+        let clk_round = self.accumulate_expressions(
+            &round_challenges,
+            &[
+                clk.expr(),
+                input_bit.expr(),
+                input_word.expr(),
+                stage_selectors.expr(),
+            ],
+        );
+        self.output_from_bus_filtered(bus_channel_idx, clk_round, round_bit.expr());
+
+        // The word of the round, which is a message word in the first 16 rounds and is given by
+        // the last 16 words of the schedule otherwise
+        let window = self.alloc_array::<U32Register>(16);
+        let xor = self.bitwise_xor(&window.get(13), &window.get(8), operations);
+        let xor = self.bitwise_xor(&xor, &window.get(2), operations);
+        let xor = self.bitwise_xor(&xor, &window.get(0), operations);
+        let scheduled_word = self.bit_rotate_right(&xor, 31, operations);
+        let word = self.alloc::<U32Register>();
+        self.set_to_expression(
+            &word,
+            input_bit.expr() * input_word.expr() + input_bit.not_expr() * scheduled_word.expr(),
+        );
+        for k in 0..15 {
+            self.set_to_expression_transition(&window.get(k).next(), window.get(k + 1).expr());
+        }
+        self.set_to_expression_transition(&window.get(15).next(), word.expr());
+
+        let word_expression = |word: u32| {
+            ArithmeticExpression::from_constant_vec(
+                u32_to_le_field_bytes::<L::Field>(word).to_vec(),
+            )
+        };
+
+        // The first compression starts from the initial hash
+        let h = self.alloc_array::<U32Register>(SHA1_CV_WORDS);
+        let state = self.alloc_array::<U32Register>(SHA1_CV_WORDS);
+        for i in 0..SHA1_CV_WORDS {
+            for register in [&h, &state] {
+                self.set_to_expression_first_row(
+                    &register.get(i),
+                    word_expression(INITIAL_HASH[i]),
+                );
+            }
+        }
+
+        // Apply a round
+        let output = self.sha1_round(&state, &word, &stage_selectors, operations);
+
+        // The output chaining value
+        let chaining_value = self.alloc_array::<U32Register>(SHA1_CV_WORDS);
+        for (i, cv) in chaining_value.iter().enumerate() {
+            let carry = self.alloc::<BitRegister>();
+            self.set_add_u32(&h.get(i), &state.get(i), &None, &cv, &carry, operations);
+        }
+
+        // Assign the next state: the round output in the round rows, the current state in the
+        // remaining rows, and the initial state of the next compression at the end of a cycle,
+        // which is the initial hash after the last block of a message and the current chaining
+        // value otherwise.
+        for i in 0..SHA1_CV_WORDS {
+            let initial = end_bit.expr() * word_expression(INITIAL_HASH[i])
+                + end_bit.not_expr() * chaining_value.get(i).expr();
+            self.set_to_expression_transition(
+                &h.get(i).next(),
+                load_next.clone() * initial.clone()
+                    + (one.clone() - load_next.clone()) * h.get(i).expr(),
+            );
+            self.set_to_expression_transition(
+                &state.get(i).next(),
+                load_next.clone() * initial
+                    + (one.clone() - load_next.clone())
+                        * (round_bit.expr() * output[i].expr()
+                            + round_bit.not_expr() * state.get(i).expr()),
+            );
+        }
+
+        // Put the chaining value of every compression in the bus at the end of its cycle
+        let state_challenges =
+            self.alloc_challenge_array::<CubicRegister>(U32Register::size_of() * SHA1_CV_WORDS + 1);
+        let clk_state =
+            self.accumulate_expressions(&state_challenges, &[clk.expr(), chaining_value.expr()]);
+        self.input_to_bus_filtered(bus_channel_idx, clk_state, cycle_128.end_bit.expr());
+
+        // Put the public inputs and chaining values in the bus
+        let stage_expression = |stage: usize| {
+            ArithmeticExpression::from_constant_vec(
+                (0..SHA1_NUM_STAGES)
+                    .map(|k| L::Field::from_canonical_u8((k == stage) as u8))
+                    .collect(),
+            )
+        };
+        for i in 0..num_compressions {
+            let clk_start = L::Field::from_canonical_usize(i * SHA1_CYCLE_LENGTH);
+            let end_digest = self.accumulate_public_expressions(
+                &end_challenges,
+                &[
+                    ArithmeticExpression::from_constant(clk_start),
+                    public_end_bits.get(i).expr(),
+                ],
+            );
+            bus.insert_global_value(&end_digest);
+
+            for t in 0..SHA1_NUM_ROUNDS {
+                let clk_t = clk_start + L::Field::from_canonical_usize(t);
+                let (input, word) = match t < 16 {
+                    true => (L::Field::ONE, public_block.get(16 * i + t).expr()),
+                    false => (L::Field::ZERO, word_expression(0)),
+                };
+                let round_digest = self.accumulate_public_expressions(
+                    &round_challenges,
+                    &[
+                        ArithmeticExpression::from_constant(clk_t),
+                        ArithmeticExpression::from_constant(input),
+                        word,
+                        stage_expression(t / 20),
+                    ],
+                );
+                bus.insert_global_value(&round_digest);
+            }
+
+            let clk_end = clk_start + L::Field::from_canonical_usize(SHA1_CYCLE_LENGTH - 1);
+            let state_digest = self.accumulate_public_expressions(
+                &state_challenges,
+                &[
+                    ArithmeticExpression::from_constant(clk_end),
+                    hash_state
+                        .get_subarray(SHA1_CV_WORDS * i..SHA1_CV_WORDS * (i + 1))
+                        .expr(),
+                ],
+            );
+            bus.output_global_value(&state_digest);
+        }
+
+        // The byte lookup needs an even number of operations
+        if operations.values.len() % 2 == 1 {
+            let dummy = self.alloc::<ByteRegister>();
+            let dummy_range = ByteOperation::Range(dummy);
+            self.set_byte_operation(&dummy_range, operations);
+        }
+
+        SHA1Gadget {
+            public_block,
+            public_end_bits,
+            state: hash_state,
+            load_bit,
+            round_bit,
+            end_bit,
+            input_bit,
+            input_word,
+            stage_selectors,
+        }
+    }
+
+    /// Applies a round to the five words of `state`, with the boolean function and the constant
+    /// of the stage given by the one-hot `stage_selectors`. The selectors are zero in the rows
+    /// without a round.
+    ///
+    /// The boolean functions are written with `xor` and `and` as `Ch = d ^ (b & (c ^ d))`,
+    /// `Parity = b ^ c ^ d` and `Maj = (b & c) ^ (d & (b ^ c))`.
+    fn sha1_round(
+        &mut self,
+        state: &ArrayRegister<U32Register>,
+        word: &U32Register,
+        stage_selectors: &ArrayRegister<BitRegister>,
+        operations: &mut ByteLookupOperations,
+    ) -> [U32Register; 5]
+    where
+        L::Instruction: U32Instructions,
+    {
+        let [a, b, c, d, e]: [U32Register; 5] = from_fn(|i| state.get(i));
+
+        let c_xor_d = self.bitwise_xor(&c, &d, operations);
+        let b_and = self.bitwise_and(&b, &c_xor_d, operations);
+        let choice = self.bitwise_xor(&d, &b_and, operations);
+
+        let b_xor_c = self.bitwise_xor(&b, &c, operations);
+        let parity = self.bitwise_xor(&b_xor_c, &d, operations);
+
+        let b_and_c = self.bitwise_and(&b, &c, operations);
+        let d_and = self.bitwise_and(&d, &b_xor_c, operations);
+        let majority = self.bitwise_xor(&b_and_c, &d_and, operations);
+
+        // The boolean function and the constant of the stage
+        let function = self.alloc::<U32Register>();
+        let function_value = stage_selectors
+            .iter()
+            .zip([choice, parity, majority, parity])
+            .map(|(bit, f)| bit.expr() * f.expr())
+            .reduce(|acc, x| acc + x)
+            .unwrap();
+        self.set_to_expression(&function, function_value);
+
+        let constant = self.alloc::<U32Register>();
+        let constant_value = stage_selectors
+            .iter()
+            .zip(ROUND_CONSTANTS)
+            .map(|(bit, k)| {
+                bit.expr()
+                    * ArithmeticExpression::from_constant_vec(
+                        u32_to_le_field_bytes::<L::Field>(k).to_vec(),
+                    )
+            })
+            .reduce(|acc, x| acc + x)
+            .unwrap();
+        self.set_to_expression(&constant, constant_value);
+
+        let a_rotated = self.bit_rotate_right(&a, 27, operations);
+        let sum = self.add_u32(&a_rotated, &function, operations);
+        let sum = self.add_u32(&sum, &e, operations);
+        let sum = self.add_u32(&sum, &constant, operations);
+        let temp = self.add_u32(&sum, word, operations);
+        let b_rotated = self.bit_rotate_right(&b, 2, operations);
+
+        [temp, a, b_rotated, c, d]
+    }
+}
+
+impl SHA1Gadget {
+    /// Writes the compressions of `padded_messages`, which must fill all the cycles of the trace.
+    pub fn write<F: Field, I: IntoIterator>(
+        &self,
+        padded_messages: I,
+        writer: &TraceWriter<F>,
+    ) -> SHA1PublicData<F>
+    where
+        I::Item: Borrow<[u8]>,
+    {
+        let mut public_block = Vec::new();
+        let mut end_bits_values = Vec::new();
+        let mut hash_values = Vec::new();
+        for padded_msg in padded_messages {
+            let padded_msg = padded_msg.borrow();
+            assert_eq!(
+                padded_msg.len() % SHA1_BLOCK_LEN,
+                0,
+                "Padded message length must be a multiple of 64"
+            );
+            let num_blocks = padded_msg.len() / SHA1_BLOCK_LEN;
+
+            let mut state = INITIAL_HASH;
+            for (k, chunk) in padded_msg.chunks_exact(SHA1_BLOCK_LEN).enumerate() {
+                let block = Self::block_words(chunk);
+                state = Self::compress(&state, &block);
+
+                public_block.extend(block.map(u32_to_le_field_bytes::<F>));
+                end_bits_values.push(F::from_canonical_u8((k == num_blocks - 1) as u8));
+                hash_values.extend(state.map(u32_to_le_field_bytes::<F>));
+            }
+        }
+        let num_compressions = self.public_end_bits.len();
+        assert!(
+            end_bits_values.len() == num_compressions,
+            "Padded messages lengths do not add up"
+        );
+
+        for (i, end_bit) in end_bits_values.iter().enumerate() {
+            let row = i * SHA1_CYCLE_LENGTH;
+            for j in 0..SHA1_CYCLE_LENGTH {
+                writer.write(
+                    &self.load_bit,
+                    &F::from_canonical_u8((j == 0) as u8),
+                    row + j,
+                );
+                writer.write(
+                    &self.round_bit,
+                    &F::from_canonical_u8((j < SHA1_NUM_ROUNDS) as u8),
+                    row + j,
+                );
+            }
+            writer.write(&self.end_bit, end_bit, row);
+            for t in 0..SHA1_NUM_ROUNDS {
+                if t < 16 {
+                    writer.write(&self.input_bit, &F::ONE, row + t);
+                    writer.write(&self.input_word, &public_block[16 * i + t], row + t);
+                }
+                writer.write_array(
+                    &self.stage_selectors,
+                    (0..SHA1_NUM_STAGES).map(|k| F::from_canonical_u8((k == t / 20) as u8)),
+                    row + t,
+                );
+            }
+        }
+
+        writer.write_array(&self.public_block, &public_block, 0);
+        writer.write_array(&self.public_end_bits, &end_bits_values, 0);
+        writer.write_array(&self.state, &hash_values, 0);
+
+        SHA1PublicData {
+            public_block,
+            end_bits: end_bits_values,
+            hash_state: hash_values,
+        }
+    }
+
+    /// Pads a message to a multiple of 64 bytes with a one bit, zeros and the length of the
+    /// message in bits as a big endian 64-bit number.
+    pub fn pad(msg: &[u8]) -> Vec<u8> {
+        let mut padded_msg = msg.to_vec();
+        padded_msg.push(1 << 7);
+        let padlen = (SHA1_BLOCK_LEN - 8 - padded_msg.len() % SHA1_BLOCK_LEN) % SHA1_BLOCK_LEN;
+        padded_msg.extend_from_slice(&vec![0u8; padlen]);
+        padded_msg.extend_from_slice(&(8 * msg.len() as u64).to_be_bytes());
+        padded_msg
+    }
+
+    /// The big endian words of a block.
+    pub fn block_words(block: &[u8]) -> [u32; 16] {
+        from_fn(|i| u32::from_be_bytes(block[4 * i..4 * (i + 1)].try_into().unwrap()))
+    }
+
+    /// The SHA-1 compression function.
+    pub fn compress(chaining_value: &[u32; 5], block: &[u32; 16]) -> [u32; 5] {
+        let mut w = [0u32; SHA1_NUM_ROUNDS];
+        w[..16].copy_from_slice(block);
+        for t in 16..SHA1_NUM_ROUNDS {
+            w[t] = (w[t - 3] ^ w[t - 8] ^ w[t - 14] ^ w[t - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = *chaining_value;
+        for (t, word) in w.iter().enumerate() {
+            let function = match t / 20 {
+                0 => (b & c) | (!b & d),
+                2 => (b & c) | (b & d) | (c & d),
+                _ => b ^ c ^ d,
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(function)
+                .wrapping_add(e)
+                .wrapping_add(ROUND_CONSTANTS[t / 20])
+                .wrapping_add(*word);
+            (a, b, c, d, e) = (temp, a, b.rotate_left(30), c, d);
+        }
+        from_fn(|i| chaining_value[i].wrapping_add([a, b, c, d, e][i]))
+    }
+
+    /// Computes the SHA-1 digest of `msg` natively.
+    pub fn hash(msg: &[u8]) -> [u8; 20] {
+        let mut state = INITIAL_HASH;
+        for chunk in Self::pad(msg).chunks_exact(SHA1_BLOCK_LEN) {
+            state = Self::compress(&state, &Self::block_words(chunk));
+        }
+        state.map(u32::to_be_bytes).concat().try_into().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::timed;
+    use plonky2::util::timing::TimingTree;
+
+    use super::*;
+    pub use crate::chip::builder::tests::*;
+    use crate::chip::builder::AirBuilder;
+    use crate::chip::uint::operations::instruction::U32Instruction;
+    use crate::chip::AirParameters;
+
+    const EMPTY_DIGEST: &str = "da39a3ee5e6b4b0d3255bfef95601890afd80709";
+    const ABC_DIGEST: &str = "a9993e364706816aba3e25717850c26c9cd0d89d";
+    /// The digest of the 200 bytes `0, 1, ..., 199`, which span four blocks once padded.
+    const LONG_DIGEST: &str = "54d11e99127d159799dbce10f51a75e697780478";
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub struct SHA1Test;
+
+    impl AirParameters for SHA1Test {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = U32Instruction;
+
+        const NUM_FREE_COLUMNS: usize = 600;
+        const EXTENDED_COLUMNS: usize = 1200;
+        const NUM_ARITHMETIC_COLUMNS: usize = 0;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    #[test]
+    fn test_sha1_native_hash() {
+        assert_eq!(hex::encode(SHA1Gadget::hash(b"")), EMPTY_DIGEST);
+        assert_eq!(hex::encode(SHA1Gadget::hash(b"abc")), ABC_DIGEST);
+        let long_msg = (0..200).map(|i| i as u8).collect::<Vec<_>>();
+        assert_eq!(hex::encode(SHA1Gadget::hash(&long_msg)), LONG_DIGEST);
+        assert_eq!(SHA1Gadget::pad(&long_msg).len(), 256);
+    }
+
+    #[test]
+    fn test_sha1_stark() {
+        type F = GoldilocksField;
+        type L = SHA1Test;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut timing = TimingTree::new("SHA-1 test", log::Level::Debug);
+
+        let mut builder = AirBuilder::<L>::new();
+        let clk = builder.clock();
+
+        let (mut operations, table) = builder.byte_operations();
+
+        let mut bus = builder.new_bus();
+        let channel_idx = bus.new_channel(&mut builder);
+
+        let sha1_gadget = builder.process_sha1_batch(&clk, &mut bus, channel_idx, &mut operations);
+
+        builder.register_byte_lookup(operations, &table);
+        builder.constrain_bus(bus);
+
+        let (air, trace_data) = builder.build();
+
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+        let writer = generator.new_writer();
+
+        // A message of four blocks followed by single block messages to fill the trace.
+        let mut messages = vec![(0..200).map(|i| i as u8).collect::<Vec<_>>()];
+        let mut expected_digests = vec![LONG_DIGEST];
+        for i in 0..L::num_rows() / SHA1_CYCLE_LENGTH - 4 {
+            messages.push([b"".to_vec(), b"abc".to_vec()][i % 2].clone());
+            expected_digests.push([EMPTY_DIGEST, ABC_DIGEST][i % 2]);
+        }
+        let padded_messages = messages
+            .iter()
+            .map(|m| SHA1Gadget::pad(m))
+            .collect::<Vec<_>>();
+
+        timed!(timing, "Write the execution trace", {
+            table.write_table_entries(&writer);
+            sha1_gadget.write(padded_messages.iter().map(|m| m.as_slice()), &writer);
+            for i in 0..L::num_rows() {
+                writer.write_row_instructions(&generator.air_data, i);
+            }
+            table.write_multiplicities(&writer);
+        });
+
+        // The digest is the chaining value of the last compression of every message
+        let mut last = 0;
+        for (padded_msg, digest) in padded_messages.iter().zip(expected_digests) {
+            last += padded_msg.len() / SHA1_BLOCK_LEN;
+            let hash = writer.read_array(
+                &sha1_gadget
+                    .state
+                    .get_subarray(SHA1_CV_WORDS * (last - 1)..SHA1_CV_WORDS * last),
+                0,
+            );
+            let expected = hex::decode(digest)
+                .unwrap()
+                .chunks_exact(4)
+                .map(|x| u32_to_le_field_bytes::<F>(u32::from_be_bytes(x.try_into().unwrap())))
+                .collect::<Vec<_>>();
+            assert_eq!(hash, expected);
+        }
+
+        let public_inputs = writer.0.public.read().unwrap().clone();
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        timed!(
+            timing,
+            "Stark proof and verify",
+            test_starky(&stark, &config, &generator, &public_inputs)
+        );
+
+        // Generate recursive proof
+        timed!(
+            timing,
+            "Recursive proof generation and verification",
+            test_recursive_starky(stark, config, generator, &public_inputs)
+        );
+
+        timing.print();
+    }
+}
@@ -23,7 +23,10 @@ pub struct CurtaBytes<const N: usize>(pub [Target; N]);
 pub struct SHA256BuilderGadget<F, E, const D: usize> {
     pub padded_messages: Vec<Target>,
     pub digests: Vec<Target>,
+    /// The hash states after every chunk of a message but the last one.
+    pub hash_states: Vec<Target>,
     pub chunk_sizes: Vec<usize>,
+    pub digest_sizes: Vec<usize>,
     _marker: PhantomData<(F, E)>,
 }
 
@@ -38,6 +41,25 @@ pub trait SHA256Builder<F: RichField + Extendable<D>, E: CubicParameters<F>, con
         gadget: &mut Self::Gadget,
     ) -> CurtaBytes<32>;
 
+    /// Computes the digest of a padded message whose length is only known when the circuit is
+    /// built, which must be a multiple of 64 bytes.
+    fn sha256_bytes(
+        &mut self,
+        padded_message: &[Target],
+        gadget: &mut Self::Gadget,
+    ) -> CurtaBytes<32>;
+
+    /// Computes the digest of a padded message as `sha256_bytes` does, returning the hash states
+    /// after each of its chunks in the byte order of a digest. The last state is the digest.
+    ///
+    /// The states after the chunks but the last one are public inputs of the SHA-256 stark in any
+    /// case, so exposing them adds no targets.
+    fn sha256_compression_states(
+        &mut self,
+        padded_message: &[Target],
+        gadget: &mut Self::Gadget,
+    ) -> Vec<CurtaBytes<32>>;
+
     fn constrain_sha256_gadget<C: CurtaConfig<D, F = F, FE = F::Extension>>(
         &mut self,
         gadget: Self::Gadget,
@@ -53,7 +75,9 @@ impl<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize> SHA256
         SHA256BuilderGadget {
             padded_messages: Vec::new(),
             digests: Vec::new(),
+            hash_states: Vec::new(),
             chunk_sizes: Vec::new(),
+            digest_sizes: Vec::new(),
             _marker: PhantomData,
         }
     }
@@ -63,22 +87,61 @@ impl<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize> SHA256
         padded_message: &CurtaBytes<N>,
         gadget: &mut Self::Gadget,
     ) -> CurtaBytes<32> {
-        gadget.padded_messages.extend_from_slice(&padded_message.0);
+        SHA256Builder::<F, E, D>::sha256_bytes(self, &padded_message.0, gadget)
+    }
+
+    fn sha256_bytes(
+        &mut self,
+        padded_message: &[Target],
+        gadget: &mut Self::Gadget,
+    ) -> CurtaBytes<32> {
+        let num_chunks = padded_message.len() / 64;
+        assert_eq!(
+            padded_message.len(),
+            64 * num_chunks,
+            "Padded message length must be a multiple of 64"
+        );
+        assert!(num_chunks > 0, "Padded message must not be empty");
+
+        gadget.padded_messages.extend_from_slice(padded_message);
         let digest_bytes = self.add_virtual_target_arr::<32>();
-        let hint = SHA256HintGenerator::new(&padded_message.0, digest_bytes);
+        let hint = SHA256HintGenerator::new(padded_message, digest_bytes);
         self.add_simple_generator(hint);
         gadget.digests.extend_from_slice(&digest_bytes);
-        gadget.chunk_sizes.push(N / 64);
+        gadget
+            .hash_states
+            .extend(self.add_virtual_targets(32 * (num_chunks - 1)));
+        gadget.chunk_sizes.push(num_chunks);
+        gadget.digest_sizes.push(32);
         CurtaBytes(digest_bytes)
     }
 
+    fn sha256_compression_states(
+        &mut self,
+        padded_message: &[Target],
+        gadget: &mut Self::Gadget,
+    ) -> Vec<CurtaBytes<32>> {
+        let start = gadget.hash_states.len();
+        let digest = SHA256Builder::<F, E, D>::sha256_bytes(self, padded_message, gadget);
+        gadget.hash_states[start..]
+            .chunks_exact(32)
+            .map(|state| CurtaBytes(state.try_into().unwrap()))
+            .chain(core::iter::once(digest))
+            .collect()
+    }
+
     fn constrain_sha256_gadget<C: CurtaConfig<D, F = F, FE = F::Extension>>(
         &mut self,
         gadget: Self::Gadget,
     ) {
         // Allocate public input targets
-        let public_sha_targets =
-            SHA256PublicData::add_virtual(self, &gadget.digests, &gadget.chunk_sizes);
+        let public_sha_targets = SHA256PublicData::add_virtual(
+            self,
+            &gadget.digests,
+            &gadget.hash_states,
+            &gadget.chunk_sizes,
+            &gadget.digest_sizes,
+        );
 
         // Make the air
         let mut air_builder = AirBuilder::<SHA256AirParameters<F, E>>::new();
@@ -142,7 +205,7 @@ mod tests {
 
     use super::*;
     pub use crate::chip::builder::tests::*;
-    use crate::chip::hash::sha::sha256::SHA256Gadget;
+    use crate::chip::hash::sha::sha256::{SHA256Gadget, INITIAL_HASH, ROUND_CONSTANTS};
     use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
 
     #[test]
@@ -288,4 +351,68 @@ mod tests {
         timing.print();
         data.verify(recursive_proof).unwrap();
     }
+    #[test]
+    fn test_sha256_compression_states() {
+        type F = GoldilocksField;
+        type E = GoldilocksCubicParameters;
+        type SC = CurtaPoseidonGoldilocksConfig;
+        type C = PoseidonGoldilocksConfig;
+        const D: usize = 2;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let mut gadget: SHA256BuilderGadget<F, E, D> = builder.init_sha256();
+
+        // A message of two blocks, followed by single block messages to fill the 1024 chunks.
+        let padded_msg_target = CurtaBytes(builder.add_virtual_target_arr::<128>());
+        let states = builder.sha256_compression_states(&padded_msg_target.0, &mut gadget);
+        assert_eq!(states.len(), 2);
+        let expected_states = (0..2)
+            .map(|_| CurtaBytes(builder.add_virtual_target_arr::<32>()))
+            .collect::<Vec<_>>();
+        for (state, expected) in states.iter().zip(expected_states.iter()) {
+            for (a, b) in state.0.iter().zip(expected.0.iter()) {
+                builder.connect(*a, *b);
+            }
+        }
+
+        let short_padded_msg_targets = (0..1022)
+            .map(|_| CurtaBytes(builder.add_virtual_target_arr::<64>()))
+            .collect::<Vec<_>>();
+        for padded_msg in short_padded_msg_targets.iter() {
+            builder.sha256(padded_msg, &mut gadget);
+        }
+
+        builder.constrain_sha256_gadget::<SC>(gadget);
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+
+        let to_field = |bytes: &[u8]| {
+            bytes
+                .iter()
+                .map(|b| F::from_canonical_u8(*b))
+                .collect::<Vec<_>>()
+        };
+
+        // The reference state after the first block.
+        let msg = (0..100).map(|i| (i * 5 + 1) as u8).collect::<Vec<_>>();
+        let padded_msg = SHA256Gadget::pad(&msg);
+        let w_val = SHA256Gadget::process_inputs(&padded_msg[..64]);
+        let first_state = SHA256Gadget::compress_round(INITIAL_HASH, &w_val, ROUND_CONSTANTS);
+        let first_state_bytes = first_state.map(u32::to_be_bytes).concat();
+
+        pw.set_target_arr(&padded_msg_target.0, &to_field(&padded_msg));
+        pw.set_target_arr(&expected_states[0].0, &to_field(&first_state_bytes));
+        pw.set_target_arr(&expected_states[1].0, &to_field(&SHA256Gadget::hash(&msg)));
+        let padded_short_msg = to_field(&SHA256Gadget::pad(b"abc"));
+        for padded_msg in short_padded_msg_targets.iter() {
+            pw.set_target_arr(&padded_msg.0, &padded_short_msg);
+        }
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
 }
@@ -1,5 +1,6 @@
 use core::marker::PhantomData;
 
+use anyhow::{ensure, Result};
 use itertools::Itertools;
 use plonky2::field::extension::Extendable;
 use plonky2::hash::hash_types::RichField;
@@ -8,7 +9,7 @@ use plonky2::iop::target::Target;
 use plonky2::iop::witness::{PartitionWitness, Witness, WitnessWrite};
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 use plonky2::plonk::circuit_data::CommonCircuitData;
-use plonky2::util::serialization::{Buffer, Read, Write};
+use plonky2::util::serialization::{Buffer, IoError, Read, Write};
 use serde::{Deserialize, Serialize};
 
 use super::{SHA256Gadget, SHA256PublicData, INITIAL_HASH, ROUND_CONSTANTS};
@@ -18,7 +19,7 @@ use crate::chip::uint::bytes::lookup_table::table::ByteLookupTable;
 use crate::chip::uint::operations::instruction::U32Instruction;
 use crate::chip::uint::register::U32Register;
 use crate::chip::uint::util::u32_to_le_field_bytes;
-use crate::chip::AirParameters;
+use crate::chip::{AirLayout, AirParameters};
 use crate::math::prelude::{CubicParameters, *};
 use crate::utils::serde::{BufferRead, BufferWrite};
 
@@ -65,6 +66,10 @@ impl<F: PrimeField64, E: CubicParameters<F>> AirParameters for SHA256AirParamete
     fn num_rows_bits() -> usize {
         16
     }
+
+    fn validate_layout(layout: &AirLayout) {
+        layout.assert_declared::<Self>();
+    }
 }
 
 impl<F: RichField, E: CubicParameters<F>> SHA256Generator<F, E> {
@@ -135,11 +140,33 @@ impl<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize> Simple
 }
 
 impl SHA256PublicData<Target> {
+    /// Allocates the public data targets for a batch of messages, where the `i`-th message
+    /// consists of `chunk_sizes[i]` chunks and its digest is given by the next `digest_sizes[i]`
+    /// bytes of `digests`. Digests shorter than 32 bytes are a prefix of the final hash state, the
+    /// remaining bytes of which are allocated as virtual targets.
+    ///
+    /// The hash states after the chunks of a message other than the last one are given by the
+    /// next `32 * (chunk_sizes[i] - 1)` bytes of `hash_states`, in the byte order of a digest.
     pub fn add_virtual<F: RichField + Extendable<D>, const D: usize>(
         builder: &mut CircuitBuilder<F, D>,
         digests: &[Target],
+        hash_states: &[Target],
         chunk_sizes: &[usize],
+        digest_sizes: &[usize],
     ) -> Self {
+        assert_eq!(
+            digest_sizes.iter().sum::<usize>(),
+            digests.len(),
+            "Digest sizes must add up to the number of digest targets"
+        );
+        assert_eq!(
+            chunk_sizes
+                .iter()
+                .map(|size| 32 * (size - 1))
+                .sum::<usize>(),
+            hash_states.len(),
+            "Chunk sizes must match the number of intermediate hash state targets"
+        );
         let public_w_targets = (0..16 * 1024)
             .map(|_| builder.add_virtual_target_arr::<4>())
             .collect::<Vec<_>>();
@@ -148,20 +175,37 @@ impl SHA256PublicData<Target> {
         let mut end_bits_targets = Vec::new();
         let mut hash_state_targets = Vec::new();
 
-        for (digest, chunk_size) in digests.chunks_exact(32).zip_eq(chunk_sizes.iter()) {
+        let mut digest_index = 0;
+        let mut hash_state_index = 0;
+        for (chunk_size, digest_size) in chunk_sizes.iter().zip_eq(digest_sizes.iter()) {
+            assert!(*digest_size <= 32, "Digest size must be at most 32 bytes");
+            let digest = &digests[digest_index..digest_index + digest_size];
+            digest_index += digest_size;
+
             end_bits_targets.extend((0..(chunk_size - 1)).map(|_| builder.zero()));
             end_bits_targets.push(builder.one());
 
-            hash_state_targets
-                .extend((0..8 * (chunk_size - 1)).map(|_| builder.add_virtual_target_arr::<4>()));
+            // Convert the intermediate states to little endian u32 chunks
+            let states = &hash_states[hash_state_index..hash_state_index + 32 * (chunk_size - 1)];
+            hash_state_index += 32 * (chunk_size - 1);
+            hash_state_targets.extend(states.chunks_exact(4).map(|word| {
+                let mut array: [Target; 4] = word.try_into().unwrap();
+                array.reverse();
+                array
+            }));
 
             // Convert digest to little endian u32 chunks
-            let u32_digest = digest.chunks_exact(4).map(|arr| {
-                let mut array: [Target; 4] = arr.try_into().unwrap();
+            let u32_digest = (0..8).map(|i| {
+                let mut array: [Target; 4] = core::array::from_fn(|j| {
+                    digest
+                        .get(4 * i + j)
+                        .copied()
+                        .unwrap_or_else(|| builder.add_virtual_target())
+                });
                 array.reverse();
                 array
             });
-            hash_state_targets.extend(u32_digest);
+            hash_state_targets.extend(u32_digest.collect::<Vec<_>>());
         }
 
         SHA256PublicData {
@@ -223,6 +267,48 @@ impl SHA256HintGenerator {
     pub fn id() -> String {
         "SHA256HintGenerator".to_string()
     }
+
+    /// Computes the digest of a padded message given as field elements.
+    ///
+    /// Returns an error if the message is not a whole number of 64-byte blocks or if one of its
+    /// values is not a byte.
+    pub fn padded_digest<F: RichField>(padded_message: &[F]) -> Result<[F; 32]> {
+        ensure!(
+            padded_message.len() % 64 == 0,
+            "SHA256HintGenerator: padded message has length {} bytes, which is not a multiple of \
+             the 64-byte block size",
+            padded_message.len()
+        );
+        let padded_message = padded_message
+            .iter()
+            .enumerate()
+            .map(|(i, x)| {
+                let value = x.as_canonical_u64();
+                ensure!(
+                    value <= u8::MAX as u64,
+                    "SHA256HintGenerator: value {} at index {} of the padded message is not a byte",
+                    value,
+                    i
+                );
+                Ok(value as u8)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut state = INITIAL_HASH;
+        for chunk in padded_message.chunks_exact(64) {
+            let w_val = SHA256Gadget::process_inputs(chunk);
+            state = SHA256Gadget::compress_round(state, &w_val, ROUND_CONSTANTS);
+        }
+
+        let digest_bytes = state
+            .map(|x| {
+                let mut arr = u32_to_le_field_bytes::<F>(x);
+                arr.reverse();
+                arr
+            })
+            .concat();
+        Ok(digest_bytes.try_into().unwrap())
+    }
 }
 
 impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D> for SHA256HintGenerator {
@@ -255,31 +341,123 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D> for SHA
         let digest_bytes = src.read_target_vec()?;
         Ok(Self {
             padded_message,
-            digest_bytes: digest_bytes.try_into().unwrap(),
+            digest_bytes: digest_bytes.try_into().map_err(|_| IoError)?,
         })
     }
 
     fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
-        let padded_message = witness
-            .get_targets(&self.padded_message)
-            .into_iter()
-            .map(|x| x.as_canonical_u64() as u8)
+        let padded_message = witness.get_targets(&self.padded_message);
+        let digest_bytes = Self::padded_digest(&padded_message).unwrap_or_else(|e| panic!("{}", e));
+
+        out_buffer.set_target_arr(&self.digest_bytes, &digest_bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+
+    use super::*;
+    use crate::chip::builder::AirBuilder;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+
+    #[test]
+    fn test_sha256_public_data_layout() {
+        type F = GoldilocksField;
+        const D: usize = 2;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let chunk_sizes = [1, 2];
+        let digest_sizes = [32, 20];
+        let digests = builder.add_virtual_targets(52);
+        let hash_states = builder.add_virtual_targets(32);
+
+        let public_data = SHA256PublicData::add_virtual(
+            &mut builder,
+            &digests,
+            &hash_states,
+            &chunk_sizes,
+            &digest_sizes,
+        );
+
+        assert_eq!(public_data.public_w.len(), 16 * 1024);
+        assert_eq!(public_data.end_bits.len(), 3);
+        assert_eq!(public_data.hash_state.len(), 8 * 3);
+
+        // The full digest is the final state of the first message.
+        let first_state = public_data.hash_state[..8].concat();
+        let expected_first_state = digests[..32]
+            .chunks_exact(4)
+            .flat_map(|word| word.iter().rev().copied())
             .collect::<Vec<_>>();
+        assert_eq!(first_state, expected_first_state);
 
-        let mut state = INITIAL_HASH;
-        for chunk in padded_message.chunks_exact(64) {
-            let w_val = SHA256Gadget::process_inputs(chunk);
-            state = SHA256Gadget::compress_round(state, &w_val, ROUND_CONSTANTS);
+        // The truncated digest is a prefix of the final state of the second message.
+        let last_state = &public_data.hash_state[16..];
+        for (i, byte) in digests[32..].iter().enumerate() {
+            assert_eq!(last_state[i / 4][3 - i % 4], *byte);
         }
 
-        let digest_bytes = state
-            .map(|x| {
-                let mut arr = u32_to_le_field_bytes::<F>(x);
-                arr.reverse();
-                arr
-            })
-            .concat();
+        let public_inputs = public_data.public_input_targets(&mut builder);
+        assert_eq!(public_inputs.len(), 4 * (16 * 1024 + 8 + 64 + 8 * 3) + 3);
+    }
 
-        out_buffer.set_target_arr(&self.digest_bytes, &digest_bytes);
+    #[test]
+    fn test_sha256_report_usage() {
+        type L = SHA256AirParameters<GoldilocksField, GoldilocksCubicParameters>;
+
+        let mut builder = AirBuilder::<L>::new();
+        let clk = builder.clock();
+        let (mut operations, table) = builder.byte_operations();
+        let mut bus = builder.new_bus();
+        let channel_idx = bus.new_channel(&mut builder);
+        builder.process_sha_256_batch(&clk, &mut bus, channel_idx, &mut operations);
+        builder.register_byte_lookup(operations, &table);
+        builder.constrain_bus(bus);
+
+        let (_, trace_data) = builder.build();
+        let usage = trace_data.report_usage();
+
+        assert_eq!(usage.layout, AirLayout::declared::<L>());
+        assert_eq!(usage.layout.num_free_columns, 551);
+        assert!(usage.num_lookup_values > 0);
+    }
+
+    #[test]
+    fn test_sha256_hint_padded_digest() {
+        type F = GoldilocksField;
+
+        let to_field = |bytes: &[u8]| {
+            bytes
+                .iter()
+                .map(|b| F::from_canonical_u8(*b))
+                .collect::<Vec<_>>()
+        };
+
+        let digest =
+            SHA256HintGenerator::padded_digest(&to_field(&SHA256Gadget::pad(b"abc"))).unwrap();
+        let expected =
+            hex::decode("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+                .unwrap();
+        assert_eq!(digest.to_vec(), to_field(&expected));
+
+        // An unpadded message is rejected with its length and the expected block size.
+        let err = SHA256HintGenerator::padded_digest(&to_field(&[0u8; 100])).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("length 100 bytes"), "{}", message);
+        assert!(
+            message.contains("multiple of the 64-byte block size"),
+            "{}",
+            message
+        );
+
+        // A value that does not fit in a byte is rejected instead of being truncated.
+        let mut padded_message = to_field(&SHA256Gadget::pad(b"abc"));
+        padded_message[5] = F::from_canonical_u16(256);
+        let err = SHA256HintGenerator::padded_digest(&padded_message).unwrap_err();
+        assert!(err.to_string().contains("value 256 at index 5"), "{}", err);
     }
 }
@@ -0,0 +1,216 @@
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::Target;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+use super::builder_gadget::{CurtaBytes, SHA256Builder, SHA256BuilderGadget};
+use super::SHA256Gadget;
+use crate::math::prelude::CubicParameters;
+
+/// The block size of SHA-256, to which the HMAC key is padded.
+const BLOCK_LEN: usize = 64;
+
+const IPAD: u8 = 0x36;
+const OPAD: u8 = 0x5c;
+
+pub trait SHA256HmacBuilder<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize>:
+    SHA256Builder<F, E, D>
+{
+    /// Computes HMAC-SHA256 of `message` under `key`, both given as byte targets.
+    ///
+    /// The lengths of the key and of the message are fixed by the circuit. The inner and outer
+    /// hashes, and the hash of a key longer than a block, are added to `gadget`, so that they are
+    /// proven in the same stark as the other messages of the gadget.
+    fn hmac_sha256(
+        &mut self,
+        key: &[Target],
+        message: &[Target],
+        gadget: &mut Self::Gadget,
+    ) -> CurtaBytes<32>;
+}
+
+impl<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize> SHA256HmacBuilder<F, E, D>
+    for CircuitBuilder<F, D>
+{
+    fn hmac_sha256(
+        &mut self,
+        key: &[Target],
+        message: &[Target],
+        gadget: &mut SHA256BuilderGadget<F, E, D>,
+    ) -> CurtaBytes<32> {
+        // Keys longer than a block are replaced by their digest.
+        let key = match key.len() > BLOCK_LEN {
+            true => sha256_message(self, key, gadget).0.to_vec(),
+            false => key.to_vec(),
+        };
+        let zero = self.zero();
+        let key_block = key
+            .into_iter()
+            .chain(core::iter::repeat(zero))
+            .take(BLOCK_LEN)
+            .collect::<Vec<_>>();
+
+        let inner_message = key_block
+            .iter()
+            .map(|byte| xor_byte(self, *byte, IPAD))
+            .chain(message.iter().copied())
+            .collect::<Vec<_>>();
+        let inner_digest = sha256_message(self, &inner_message, gadget);
+
+        let outer_message = key_block
+            .iter()
+            .map(|byte| xor_byte(self, *byte, OPAD))
+            .chain(inner_digest.0)
+            .collect::<Vec<_>>();
+        sha256_message(self, &outer_message, gadget)
+    }
+}
+
+/// Hashes a message whose length is fixed by the circuit, so that its padding is constant.
+pub(crate) fn sha256_message<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    message: &[Target],
+    gadget: &mut SHA256BuilderGadget<F, E, D>,
+) -> CurtaBytes<32> {
+    let length = message.len();
+    let padding = SHA256Gadget::pad(&vec![0u8; length])[length..]
+        .iter()
+        .map(|byte| builder.constant(F::from_canonical_u8(*byte)))
+        .collect::<Vec<_>>();
+
+    let padded_message = message.iter().copied().chain(padding).collect::<Vec<_>>();
+    SHA256Builder::<F, E, D>::sha256_bytes(builder, &padded_message, gadget)
+}
+
+/// Computes `byte ^ mask`, range checking `byte` to 8 bits.
+fn xor_byte<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    byte: Target,
+    mask: u8,
+) -> Target {
+    let bits = builder
+        .split_le(byte, 8)
+        .into_iter()
+        .enumerate()
+        .map(|(i, bit)| match (mask >> i) & 1 {
+            1 => builder.not(bit),
+            _ => bit,
+        })
+        .collect::<Vec<_>>();
+    builder.le_sum(bits.into_iter())
+}
+
+impl SHA256Gadget {
+    /// Computes HMAC-SHA256 of `message` under `key` natively.
+    pub fn hmac(key: &[u8], message: &[u8]) -> [u8; 32] {
+        let mut key_block = match key.len() > BLOCK_LEN {
+            true => Self::hash(key).to_vec(),
+            false => key.to_vec(),
+        };
+        key_block.resize(BLOCK_LEN, 0);
+
+        let inner_key = key_block.iter().map(|byte| byte ^ IPAD).collect::<Vec<_>>();
+        let inner_digest = Self::hash(&[inner_key.as_slice(), message].concat());
+
+        let outer_key = key_block.iter().map(|byte| byte ^ OPAD).collect::<Vec<_>>();
+        Self::hash(&[outer_key.as_slice(), &inner_digest[..]].concat())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::types::Field;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    type F = GoldilocksField;
+    type E = GoldilocksCubicParameters;
+    type SC = CurtaPoseidonGoldilocksConfig;
+    type C = PoseidonGoldilocksConfig;
+    const D: usize = 2;
+
+    fn to_field_bytes(bytes: &[u8]) -> Vec<F> {
+        bytes.iter().map(|x| F::from_canonical_u8(*x)).collect()
+    }
+
+    /// Test cases 2 and 6 of RFC 4231, with a short key and with a key longer than a block.
+    fn test_vectors() -> [(Vec<u8>, Vec<u8>, Vec<u8>); 2] {
+        [
+            (
+                b"Jefe".to_vec(),
+                b"what do ya want for nothing?".to_vec(),
+                hex::decode("5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843")
+                    .unwrap(),
+            ),
+            (
+                vec![0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First".to_vec(),
+                hex::decode("60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54")
+                    .unwrap(),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_hmac_sha256_native() {
+        for (key, message, expected) in test_vectors() {
+            assert_eq!(SHA256Gadget::hmac(&key, &message).to_vec(), expected);
+        }
+    }
+
+    #[test]
+    fn test_hmac_sha256_plonky_gadget() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let mut gadget = SHA256Builder::<F, E, D>::init_sha256(&mut builder);
+
+        let vectors = test_vectors();
+        let mut targets = Vec::new();
+        for (key, message, _) in vectors.iter() {
+            let key_targets = builder.add_virtual_targets(key.len());
+            let message_targets = builder.add_virtual_targets(message.len());
+            let digest = builder.hmac_sha256(&key_targets, &message_targets, &mut gadget);
+            let expected = builder.add_virtual_target_arr::<32>();
+            for (a, b) in digest.0.iter().zip(expected.iter()) {
+                builder.connect(*a, *b);
+            }
+            targets.push((key_targets, message_targets, expected));
+        }
+
+        // The first HMAC takes two chunks for each of the inner and outer hashes, the second one
+        // three more chunks for hashing the key.
+        let dummy_messages = (0..1024 - 4 - 7)
+            .map(|_| CurtaBytes(builder.add_virtual_target_arr::<64>()))
+            .collect::<Vec<_>>();
+        for message in dummy_messages.iter() {
+            builder.sha256(message, &mut gadget);
+        }
+        builder.constrain_sha256_gadget::<SC>(gadget);
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+
+        for ((key, message, expected), (key_targets, message_targets, expected_targets)) in
+            vectors.iter().zip(targets.iter())
+        {
+            pw.set_target_arr(key_targets, &to_field_bytes(key));
+            pw.set_target_arr(message_targets, &to_field_bytes(message));
+            pw.set_target_arr(expected_targets, &to_field_bytes(expected));
+        }
+
+        let dummy_padded_message = to_field_bytes(&SHA256Gadget::pad(b""));
+        for message in dummy_messages.iter() {
+            pw.set_target_arr(&message.0, &dummy_padded_message);
+        }
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+}
@@ -0,0 +1,368 @@
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::BoolTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+use super::builder_gadget::{CurtaBytes, SHA256Builder, SHA256BuilderGadget};
+use super::SHA256Gadget;
+use crate::math::prelude::CubicParameters;
+
+/// A Merkle inclusion proof, from the leaf up to the root.
+#[derive(Debug, Clone)]
+pub struct MerkleProofTarget {
+    /// The sibling digests along the path.
+    pub siblings: Vec<CurtaBytes<32>>,
+    /// The direction bits along the path. A set bit means that the current node is the right
+    /// child, so that its parent is `hash(sibling || current)`.
+    pub path_bits: Vec<BoolTarget>,
+}
+
+pub trait SHA256MerkleBuilder<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize>:
+    SHA256Builder<F, E, D>
+{
+    /// Allocates the targets of a Merkle proof of the given depth.
+    fn add_virtual_merkle_proof(&mut self, depth: usize) -> MerkleProofTarget;
+
+    /// Computes the parent of `left` and `right`, i.e. `hash(left || right)`.
+    fn merkle_node(
+        &mut self,
+        left: &CurtaBytes<32>,
+        right: &CurtaBytes<32>,
+        gadget: &mut Self::Gadget,
+    ) -> CurtaBytes<32>;
+
+    /// Verifies that the leaf with the given padded message is included in the tree of `root`.
+    ///
+    /// The order of the children at every level is selected in the circuit by the corresponding
+    /// path bit.
+    fn verify_merkle_proof<const N: usize>(
+        &mut self,
+        padded_leaf: &CurtaBytes<N>,
+        proof: &MerkleProofTarget,
+        root: &CurtaBytes<32>,
+        gadget: &mut Self::Gadget,
+    );
+
+    /// Computes the parent of `left` and `right` with a domain tag, i.e. `hash(tag || left ||
+    /// right)`.
+    fn keyed_merkle_node(
+        &mut self,
+        tag: u8,
+        left: &CurtaBytes<32>,
+        right: &CurtaBytes<32>,
+        gadget: &mut Self::Gadget,
+    ) -> CurtaBytes<32>;
+
+    /// Verifies a Merkle proof in a tree whose levels are domain separated, the parents at level
+    /// `i` above the leaves being computed with `keyed_merkle_node` and the tag `domain_tags[i]`.
+    fn verify_keyed_merkle_proof<const N: usize>(
+        &mut self,
+        padded_leaf: &CurtaBytes<N>,
+        proof: &MerkleProofTarget,
+        domain_tags: &[u8],
+        root: &CurtaBytes<32>,
+        gadget: &mut Self::Gadget,
+    );
+}
+
+impl<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize>
+    SHA256MerkleBuilder<F, E, D> for CircuitBuilder<F, D>
+{
+    fn add_virtual_merkle_proof(&mut self, depth: usize) -> MerkleProofTarget {
+        MerkleProofTarget {
+            siblings: (0..depth)
+                .map(|_| CurtaBytes(self.add_virtual_target_arr::<32>()))
+                .collect(),
+            path_bits: (0..depth)
+                .map(|_| self.add_virtual_bool_target_safe())
+                .collect(),
+        }
+    }
+
+    fn merkle_node(
+        &mut self,
+        left: &CurtaBytes<32>,
+        right: &CurtaBytes<32>,
+        gadget: &mut SHA256BuilderGadget<F, E, D>,
+    ) -> CurtaBytes<32> {
+        // The padding of a 64-byte message is constant.
+        let padding = SHA256Gadget::pad(&[0u8; 64])[64..]
+            .iter()
+            .map(|byte| self.constant(F::from_canonical_u8(*byte)))
+            .collect::<Vec<_>>();
+
+        let padded_message = left
+            .0
+            .iter()
+            .chain(right.0.iter())
+            .chain(padding.iter())
+            .copied()
+            .collect::<Vec<_>>();
+        self.sha256(
+            &CurtaBytes::<128>(padded_message.try_into().unwrap()),
+            gadget,
+        )
+    }
+
+    fn verify_merkle_proof<const N: usize>(
+        &mut self,
+        padded_leaf: &CurtaBytes<N>,
+        proof: &MerkleProofTarget,
+        root: &CurtaBytes<32>,
+        gadget: &mut SHA256BuilderGadget<F, E, D>,
+    ) {
+        assert_eq!(
+            proof.siblings.len(),
+            proof.path_bits.len(),
+            "Merkle proof must have one path bit per sibling"
+        );
+
+        let mut current = self.sha256(padded_leaf, gadget);
+        for (sibling, bit) in proof.siblings.iter().zip(proof.path_bits.iter()) {
+            let left = CurtaBytes(core::array::from_fn(|i| {
+                self.select(*bit, sibling.0[i], current.0[i])
+            }));
+            let right = CurtaBytes(core::array::from_fn(|i| {
+                self.select(*bit, current.0[i], sibling.0[i])
+            }));
+            current = self.merkle_node(&left, &right, gadget);
+        }
+
+        for (a, b) in current.0.iter().zip(root.0.iter()) {
+            self.connect(*a, *b);
+        }
+    }
+
+    fn keyed_merkle_node(
+        &mut self,
+        tag: u8,
+        left: &CurtaBytes<32>,
+        right: &CurtaBytes<32>,
+        gadget: &mut SHA256BuilderGadget<F, E, D>,
+    ) -> CurtaBytes<32> {
+        // The message has 65 bytes, so it still fits in two chunks with a constant padding.
+        let tag = self.constant(F::from_canonical_u8(tag));
+        let padding = SHA256Gadget::pad(&[0u8; 65])[65..]
+            .iter()
+            .map(|byte| self.constant(F::from_canonical_u8(*byte)))
+            .collect::<Vec<_>>();
+
+        let padded_message = core::iter::once(tag)
+            .chain(left.0.iter().copied())
+            .chain(right.0.iter().copied())
+            .chain(padding)
+            .collect::<Vec<_>>();
+        self.sha256(
+            &CurtaBytes::<128>(padded_message.try_into().unwrap()),
+            gadget,
+        )
+    }
+
+    fn verify_keyed_merkle_proof<const N: usize>(
+        &mut self,
+        padded_leaf: &CurtaBytes<N>,
+        proof: &MerkleProofTarget,
+        domain_tags: &[u8],
+        root: &CurtaBytes<32>,
+        gadget: &mut SHA256BuilderGadget<F, E, D>,
+    ) {
+        assert_eq!(
+            proof.siblings.len(),
+            proof.path_bits.len(),
+            "Merkle proof must have one path bit per sibling"
+        );
+        assert_eq!(
+            proof.siblings.len(),
+            domain_tags.len(),
+            "Merkle proof must have one domain tag per level"
+        );
+
+        let mut current = self.sha256(padded_leaf, gadget);
+        for ((sibling, bit), tag) in proof
+            .siblings
+            .iter()
+            .zip(proof.path_bits.iter())
+            .zip(domain_tags.iter())
+        {
+            let left = CurtaBytes(core::array::from_fn(|i| {
+                self.select(*bit, sibling.0[i], current.0[i])
+            }));
+            let right = CurtaBytes(core::array::from_fn(|i| {
+                self.select(*bit, current.0[i], sibling.0[i])
+            }));
+            current = self.keyed_merkle_node(*tag, &left, &right, gadget);
+        }
+
+        for (a, b) in current.0.iter().zip(root.0.iter()) {
+            self.connect(*a, *b);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::types::Field;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    type F = GoldilocksField;
+    type E = GoldilocksCubicParameters;
+    type SC = CurtaPoseidonGoldilocksConfig;
+    type C = PoseidonGoldilocksConfig;
+    const D: usize = 2;
+
+    const DEPTH: usize = 3;
+
+    fn to_field_bytes(bytes: &[u8]) -> Vec<F> {
+        bytes.iter().map(|x| F::from_canonical_u8(*x)).collect()
+    }
+
+    /// The levels of the tree of `leaves`, domain separated by `domain_tags` if given.
+    fn merkle_tree(leaves: &[Vec<u8>], domain_tags: Option<&[u8]>) -> Vec<Vec<[u8; 32]>> {
+        let mut levels = vec![leaves
+            .iter()
+            .map(|leaf| SHA256Gadget::hash(leaf))
+            .collect::<Vec<_>>()];
+        while levels.last().unwrap().len() > 1 {
+            let tag = domain_tags.map(|tags| vec![tags[levels.len() - 1]]);
+            let level = levels
+                .last()
+                .unwrap()
+                .chunks_exact(2)
+                .map(|pair| {
+                    let prefix = tag.clone().unwrap_or_default();
+                    SHA256Gadget::hash(&[prefix.as_slice(), &pair[0][..], &pair[1][..]].concat())
+                })
+                .collect();
+            levels.push(level);
+        }
+        levels
+    }
+
+    /// Proves the inclusion of the leaf at `index` in a depth-3 tree, flipping the path bit at
+    /// `flipped_level` if given.
+    fn prove_inclusion(index: usize, flipped_level: Option<usize>) {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let mut gadget = SHA256Builder::<F, E, D>::init_sha256(&mut builder);
+
+        let leaf = CurtaBytes(builder.add_virtual_target_arr::<64>());
+        let proof = SHA256MerkleBuilder::<F, E, D>::add_virtual_merkle_proof(&mut builder, DEPTH);
+        let root = CurtaBytes(builder.add_virtual_target_arr::<32>());
+        builder.verify_merkle_proof(&leaf, &proof, &root, &mut gadget);
+
+        // The SHA256 gadget processes a fixed number of 1024 chunks, one for the leaf and two for
+        // every level of the tree.
+        let dummy_messages = (0..1024 - 1 - 2 * DEPTH)
+            .map(|_| CurtaBytes(builder.add_virtual_target_arr::<64>()))
+            .collect::<Vec<_>>();
+        for message in dummy_messages.iter() {
+            builder.sha256(message, &mut gadget);
+        }
+        builder.constrain_sha256_gadget::<SC>(gadget);
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+
+        let leaves = (0..1 << DEPTH)
+            .map(|i| format!("leaf {}", i).into_bytes())
+            .collect::<Vec<_>>();
+        let tree = merkle_tree(&leaves, None);
+
+        pw.set_target_arr(&leaf.0, &to_field_bytes(&SHA256Gadget::pad(&leaves[index])));
+        for level in 0..DEPTH {
+            let position = index >> level;
+            let sibling = tree[level][position ^ 1];
+            let bit = (position & 1 == 1) ^ (flipped_level == Some(level));
+            pw.set_target_arr(&proof.siblings[level].0, &to_field_bytes(&sibling));
+            pw.set_bool_target(proof.path_bits[level], bit);
+        }
+        pw.set_target_arr(&root.0, &to_field_bytes(&tree[DEPTH][0]));
+
+        let dummy_padded_message = to_field_bytes(&SHA256Gadget::pad(b""));
+        for message in dummy_messages.iter() {
+            pw.set_target_arr(&message.0, &dummy_padded_message);
+        }
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+
+    #[test]
+    fn test_merkle_inclusion_proof() {
+        prove_inclusion(5, None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_merkle_inclusion_proof_flipped_bit() {
+        prove_inclusion(5, Some(1));
+    }
+
+    const DOMAIN_TAGS: [u8; DEPTH] = [1, 2, 3];
+
+    /// Proves the inclusion of the leaf at `index` in a domain-separated depth-3 tree, with the
+    /// proof taken from a tree without domain separation if `keyed_tree` is false.
+    fn prove_keyed_inclusion(index: usize, keyed_tree: bool) {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let mut gadget = SHA256Builder::<F, E, D>::init_sha256(&mut builder);
+
+        let leaf = CurtaBytes(builder.add_virtual_target_arr::<64>());
+        let proof = SHA256MerkleBuilder::<F, E, D>::add_virtual_merkle_proof(&mut builder, DEPTH);
+        let root = CurtaBytes(builder.add_virtual_target_arr::<32>());
+        builder.verify_keyed_merkle_proof(&leaf, &proof, &DOMAIN_TAGS, &root, &mut gadget);
+
+        // As for the plain tree, one chunk for the leaf and two for every level of the tree.
+        let dummy_messages = (0..1024 - 1 - 2 * DEPTH)
+            .map(|_| CurtaBytes(builder.add_virtual_target_arr::<64>()))
+            .collect::<Vec<_>>();
+        for message in dummy_messages.iter() {
+            builder.sha256(message, &mut gadget);
+        }
+        builder.constrain_sha256_gadget::<SC>(gadget);
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+
+        let leaves = (0..1 << DEPTH)
+            .map(|i| format!("leaf {}", i).into_bytes())
+            .collect::<Vec<_>>();
+        let tree = merkle_tree(&leaves, keyed_tree.then_some(DOMAIN_TAGS.as_slice()));
+
+        pw.set_target_arr(&leaf.0, &to_field_bytes(&SHA256Gadget::pad(&leaves[index])));
+        for level in 0..DEPTH {
+            let position = index >> level;
+            let sibling = tree[level][position ^ 1];
+            pw.set_target_arr(&proof.siblings[level].0, &to_field_bytes(&sibling));
+            pw.set_bool_target(proof.path_bits[level], position & 1 == 1);
+        }
+        pw.set_target_arr(&root.0, &to_field_bytes(&tree[DEPTH][0]));
+
+        let dummy_padded_message = to_field_bytes(&SHA256Gadget::pad(b""));
+        for message in dummy_messages.iter() {
+            pw.set_target_arr(&message.0, &dummy_padded_message);
+        }
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+
+    #[test]
+    fn test_keyed_merkle_inclusion_proof() {
+        prove_keyed_inclusion(6, true);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_keyed_merkle_inclusion_proof_plain_tree() {
+        prove_keyed_inclusion(6, false);
+    }
+}
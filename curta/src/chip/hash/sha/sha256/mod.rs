@@ -1,5 +1,8 @@
 pub mod builder_gadget;
 pub mod generator;
+pub mod hmac;
+pub mod merkle;
+pub mod stream;
 
 use core::borrow::Borrow;
 
@@ -579,6 +582,16 @@ impl SHA256Gadget {
         // .map(|x| u32::from_be_bytes(x.try_into().unwrap()))
         // .collect::<Vec<_>>()
     }
+
+    /// Computes the SHA-256 digest of `msg` natively.
+    pub fn hash(msg: &[u8]) -> [u8; 32] {
+        let mut state = INITIAL_HASH;
+        for chunk in Self::pad(msg).chunks_exact(64) {
+            let w_val = Self::process_inputs(chunk);
+            state = Self::compress_round(state, &w_val, ROUND_CONSTANTS);
+        }
+        state.map(u32::to_be_bytes).concat().try_into().unwrap()
+    }
 }
 
 #[cfg(test)]
@@ -593,7 +606,7 @@ mod tests {
     use crate::chip::builder::AirBuilder;
     use crate::chip::uint::operations::instruction::U32Instruction;
     use crate::chip::uint::util::u32_to_le_field_bytes;
-    use crate::chip::AirParameters;
+    use crate::chip::{AirLayout, AirParameters};
 
     #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
     pub struct SHA256Test;
@@ -611,6 +624,10 @@ mod tests {
         fn num_rows_bits() -> usize {
             16
         }
+
+        fn validate_layout(layout: &AirLayout) {
+            layout.assert_declared::<Self>();
+        }
     }
 
     #[test]
@@ -715,4 +732,12 @@ mod tests {
 
         timing.print();
     }
+
+    #[test]
+    fn test_sha_256_native_hash() {
+        assert_eq!(
+            hex::encode(SHA256Gadget::hash(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
 }
@@ -0,0 +1,210 @@
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::Target;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+use super::builder_gadget::{CurtaBytes, SHA256Builder, SHA256BuilderGadget};
+use super::generator::SHA256HintGenerator;
+use super::SHA256Gadget;
+use crate::math::prelude::CubicParameters;
+
+/// The running state of a SHA-256 computation over a message that is fed in 64-byte blocks.
+#[derive(Debug, Clone)]
+pub struct SHA256StreamTarget {
+    /// The message blocks absorbed so far.
+    blocks: Vec<Target>,
+    /// The hash states after every absorbed block, in the byte order of a digest.
+    states: Vec<Target>,
+}
+
+impl SHA256StreamTarget {
+    /// The number of message bytes absorbed so far.
+    pub fn num_bytes(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// The hash state after the last absorbed block, if any.
+    pub fn state(&self) -> Option<CurtaBytes<32>> {
+        (!self.states.is_empty())
+            .then(|| CurtaBytes(self.states[self.states.len() - 32..].try_into().unwrap()))
+    }
+}
+
+pub trait SHA256StreamBuilder<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize>:
+    SHA256Builder<F, E, D>
+{
+    fn init_sha256_stream(&mut self) -> SHA256StreamTarget;
+
+    /// Absorbs whole 64-byte blocks of the message, returning the hash state after the last one.
+    ///
+    /// The carried states are public inputs of the SHA-256 stark, so that they can be exposed to
+    /// chain the segments of a long message. They are only constrained once the stream is
+    /// finalized.
+    fn update_sha256_stream(
+        &mut self,
+        stream: &mut SHA256StreamTarget,
+        blocks: &[Target],
+    ) -> CurtaBytes<32>;
+
+    /// Absorbs the remaining bytes of the message, applies the padding with the message length
+    /// and returns the digest.
+    fn finalize_sha256_stream(
+        &mut self,
+        stream: SHA256StreamTarget,
+        last_bytes: &[Target],
+        gadget: &mut Self::Gadget,
+    ) -> CurtaBytes<32>;
+}
+
+impl<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize>
+    SHA256StreamBuilder<F, E, D> for CircuitBuilder<F, D>
+{
+    fn init_sha256_stream(&mut self) -> SHA256StreamTarget {
+        SHA256StreamTarget {
+            blocks: Vec::new(),
+            states: Vec::new(),
+        }
+    }
+
+    fn update_sha256_stream(
+        &mut self,
+        stream: &mut SHA256StreamTarget,
+        blocks: &[Target],
+    ) -> CurtaBytes<32> {
+        assert!(
+            !blocks.is_empty() && blocks.len() % 64 == 0,
+            "Stream updates must consist of whole 64-byte blocks"
+        );
+        stream.blocks.extend_from_slice(blocks);
+        stream
+            .states
+            .extend(self.add_virtual_targets(32 * (blocks.len() / 64)));
+        stream.state().unwrap()
+    }
+
+    fn finalize_sha256_stream(
+        &mut self,
+        stream: SHA256StreamTarget,
+        last_bytes: &[Target],
+        gadget: &mut SHA256BuilderGadget<F, E, D>,
+    ) -> CurtaBytes<32> {
+        // The padding only depends on the length of the message.
+        let length = stream.num_bytes() + last_bytes.len();
+        let padding = SHA256Gadget::pad(&vec![0u8; length])[length..]
+            .iter()
+            .map(|byte| self.constant(F::from_canonical_u8(*byte)))
+            .collect::<Vec<_>>();
+
+        let mut padded_message = stream.blocks;
+        padded_message.extend_from_slice(last_bytes);
+        padded_message.extend(padding);
+        let num_chunks = padded_message.len() / 64;
+        let num_last_chunks = num_chunks - stream.states.len() / 32;
+
+        let digest_bytes = self.add_virtual_target_arr::<32>();
+        let hint = SHA256HintGenerator::new(&padded_message, digest_bytes);
+        self.add_simple_generator(hint);
+
+        gadget.padded_messages.extend(padded_message);
+        gadget.digests.extend_from_slice(&digest_bytes);
+        gadget.hash_states.extend(stream.states);
+        gadget
+            .hash_states
+            .extend(self.add_virtual_targets(32 * (num_last_chunks - 1)));
+        gadget.chunk_sizes.push(num_chunks);
+        gadget.digest_sizes.push(32);
+        CurtaBytes(digest_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::types::Field;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::hash::sha::sha256::{INITIAL_HASH, ROUND_CONSTANTS};
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    #[test]
+    fn test_sha256_stream() {
+        type F = GoldilocksField;
+        type E = GoldilocksCubicParameters;
+        type SC = CurtaPoseidonGoldilocksConfig;
+        type C = PoseidonGoldilocksConfig;
+        const D: usize = 2;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let mut gadget: SHA256BuilderGadget<F, E, D> = builder.init_sha256();
+
+        // A 300-byte message fed in two updates of two blocks and a 44-byte remainder.
+        let msg_targets = builder.add_virtual_targets(300);
+        let mut stream = builder.init_sha256_stream();
+        let first_state = builder.update_sha256_stream(&mut stream, &msg_targets[..128]);
+        builder.update_sha256_stream(&mut stream, &msg_targets[128..256]);
+        let streamed_digest =
+            builder.finalize_sha256_stream(stream, &msg_targets[256..], &mut gadget);
+
+        // The same message hashed in one shot.
+        let padded_msg_targets = CurtaBytes(builder.add_virtual_target_arr::<320>());
+        let digest = builder.sha256(&padded_msg_targets, &mut gadget);
+        for (a, b) in streamed_digest.0.iter().zip(digest.0.iter()) {
+            builder.connect(*a, *b);
+        }
+
+        let expected_state = CurtaBytes(builder.add_virtual_target_arr::<32>());
+        for (a, b) in first_state.0.iter().zip(expected_state.0.iter()) {
+            builder.connect(*a, *b);
+        }
+        let expected_digest = CurtaBytes(builder.add_virtual_target_arr::<32>());
+        for (a, b) in digest.0.iter().zip(expected_digest.0.iter()) {
+            builder.connect(*a, *b);
+        }
+
+        // Fill the rest of the 1024 chunks with short messages.
+        let short_padded_msg_targets = (0..1024 - 10)
+            .map(|_| CurtaBytes(builder.add_virtual_target_arr::<64>()))
+            .collect::<Vec<_>>();
+        for padded_msg in short_padded_msg_targets.iter() {
+            builder.sha256(padded_msg, &mut gadget);
+        }
+
+        builder.constrain_sha256_gadget::<SC>(gadget);
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+
+        let msg = (0..300).map(|i| (i * 7 + 3) as u8).collect::<Vec<_>>();
+        let to_field = |bytes: &[u8]| {
+            bytes
+                .iter()
+                .map(|b| F::from_canonical_u8(*b))
+                .collect::<Vec<_>>()
+        };
+
+        let mut state = INITIAL_HASH;
+        for chunk in msg[..128].chunks_exact(64) {
+            let w_val = SHA256Gadget::process_inputs(chunk);
+            state = SHA256Gadget::compress_round(state, &w_val, ROUND_CONSTANTS);
+        }
+        let state_bytes = state.map(u32::to_be_bytes).concat();
+
+        pw.set_target_arr(&msg_targets, &to_field(&msg));
+        pw.set_target_arr(&padded_msg_targets.0, &to_field(&SHA256Gadget::pad(&msg)));
+        pw.set_target_arr(&expected_state.0, &to_field(&state_bytes));
+        pw.set_target_arr(&expected_digest.0, &to_field(&SHA256Gadget::hash(&msg)));
+        let padded_short_msg = to_field(&SHA256Gadget::pad(b"abc"));
+        for padded_msg in short_padded_msg_targets.iter() {
+            pw.set_target_arr(&padded_msg.0, &padded_short_msg);
+        }
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+}
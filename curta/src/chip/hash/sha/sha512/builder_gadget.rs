@@ -0,0 +1,250 @@
+use core::marker::PhantomData;
+
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::Target;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+use super::generator::{SHA512AirParameters, SHA512Generator, SHA512HintGenerator};
+use super::SHA512PublicData;
+use crate::chip::builder::AirBuilder;
+use crate::chip::hash::sha::sha256::builder_gadget::CurtaBytes;
+use crate::chip::trace::generator::ArithmeticGenerator;
+use crate::chip::AirParameters;
+use crate::math::prelude::CubicParameters;
+use crate::plonky2::stark::config::{CurtaConfig, StarkyConfig};
+use crate::plonky2::stark::gadget::StarkGadget;
+use crate::plonky2::stark::generator::simple::SimpleStarkWitnessGenerator;
+use crate::plonky2::stark::Starky;
+
+#[derive(Debug, Clone)]
+pub struct SHA512BuilderGadget<F, E, const D: usize> {
+    pub padded_messages: Vec<Target>,
+    pub digests: Vec<Target>,
+    pub chunk_sizes: Vec<usize>,
+    _marker: PhantomData<(F, E)>,
+}
+
+pub trait SHA512Builder<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize> {
+    type Gadget;
+
+    fn init_sha512(&mut self) -> Self::Gadget;
+
+    /// Computes the digest of a padded message, whose length must be a multiple of 128 bytes.
+    fn sha512<const N: usize>(
+        &mut self,
+        padded_message: &CurtaBytes<N>,
+        gadget: &mut Self::Gadget,
+    ) -> CurtaBytes<64>;
+
+    /// Computes the digest of a padded message whose length is only known when the circuit is
+    /// built, which must be a multiple of 128 bytes.
+    fn sha512_bytes(
+        &mut self,
+        padded_message: &[Target],
+        gadget: &mut Self::Gadget,
+    ) -> CurtaBytes<64>;
+
+    fn constrain_sha512_gadget<C: CurtaConfig<D, F = F, FE = F::Extension>>(
+        &mut self,
+        gadget: Self::Gadget,
+    );
+}
+
+impl<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize> SHA512Builder<F, E, D>
+    for CircuitBuilder<F, D>
+{
+    type Gadget = SHA512BuilderGadget<F, E, D>;
+
+    fn init_sha512(&mut self) -> Self::Gadget {
+        SHA512BuilderGadget {
+            padded_messages: Vec::new(),
+            digests: Vec::new(),
+            chunk_sizes: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    fn sha512<const N: usize>(
+        &mut self,
+        padded_message: &CurtaBytes<N>,
+        gadget: &mut Self::Gadget,
+    ) -> CurtaBytes<64> {
+        SHA512Builder::<F, E, D>::sha512_bytes(self, &padded_message.0, gadget)
+    }
+
+    fn sha512_bytes(
+        &mut self,
+        padded_message: &[Target],
+        gadget: &mut Self::Gadget,
+    ) -> CurtaBytes<64> {
+        let num_chunks = padded_message.len() / 128;
+        assert_eq!(
+            padded_message.len(),
+            128 * num_chunks,
+            "Padded message length must be a multiple of 128"
+        );
+        assert!(num_chunks > 0, "Padded message must not be empty");
+
+        gadget.padded_messages.extend_from_slice(padded_message);
+        let digest_bytes = self.add_virtual_target_arr::<64>();
+        let hint = SHA512HintGenerator::new(padded_message, digest_bytes);
+        self.add_simple_generator(hint);
+        gadget.digests.extend_from_slice(&digest_bytes);
+        gadget.chunk_sizes.push(num_chunks);
+        CurtaBytes(digest_bytes)
+    }
+
+    fn constrain_sha512_gadget<C: CurtaConfig<D, F = F, FE = F::Extension>>(
+        &mut self,
+        gadget: Self::Gadget,
+    ) {
+        // Allocate public input targets
+        let public_sha_targets =
+            SHA512PublicData::add_virtual(self, &gadget.digests, &gadget.chunk_sizes);
+
+        // Make the air
+        let mut air_builder = AirBuilder::<SHA512AirParameters<F, E>>::new();
+        let clk = air_builder.clock();
+
+        let (mut operations, table) = air_builder.byte_operations();
+
+        let mut bus = air_builder.new_bus();
+        let channel_idx = bus.new_channel(&mut air_builder);
+
+        let sha_gadget =
+            air_builder.process_sha_512_batch(&clk, &mut bus, channel_idx, &mut operations);
+
+        air_builder.register_byte_lookup(operations, &table);
+        air_builder.constrain_bus(bus);
+
+        let (air, trace_data) = air_builder.build();
+
+        let generator = ArithmeticGenerator::<SHA512AirParameters<F, E>>::new(trace_data);
+
+        let public_input_target = public_sha_targets.public_input_targets(self);
+
+        let sha_generator = SHA512Generator {
+            gadget: sha_gadget,
+            table,
+            padded_messages: gadget.padded_messages,
+            chunk_sizes: gadget.chunk_sizes,
+            trace_generator: generator.clone(),
+            pub_values_target: public_sha_targets,
+        };
+
+        self.add_simple_generator(sha_generator);
+
+        let stark = Starky::new(air);
+        let config =
+            StarkyConfig::<C, D>::standard_fast_config(SHA512AirParameters::<F, E>::num_rows());
+        let virtual_proof = self.add_virtual_stark_proof(&stark, &config);
+        self.verify_stark_proof(&config, &stark, &virtual_proof, &public_input_target);
+
+        let stark_generator = SimpleStarkWitnessGenerator::new(
+            config,
+            stark,
+            virtual_proof,
+            public_input_target,
+            generator,
+        );
+        self.add_simple_generator(stark_generator);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use plonky2::field::types::Field;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+    use plonky2::timed;
+    use plonky2::util::timing::TimingTree;
+
+    use super::*;
+    pub use crate::chip::builder::tests::*;
+    use crate::chip::hash::sha::sha512::SHA512Gadget;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    #[test]
+    fn test_sha_512_plonky_gadget() {
+        type F = GoldilocksField;
+        type E = GoldilocksCubicParameters;
+        type SC = CurtaPoseidonGoldilocksConfig;
+        type C = PoseidonGoldilocksConfig;
+        const D: usize = 2;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut timing = TimingTree::new("Sha512 Plonky2 gadget test", log::Level::Debug);
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let mut gadget: SHA512BuilderGadget<F, E, D> = builder.init_sha512();
+
+        // A long message of two blocks and two short messages of one block each, repeated to
+        // fill the 512 blocks of the trace.
+        let messages = [vec![0x61u8; 112], b"".to_vec(), b"abc".to_vec()];
+        let mut msg_targets = Vec::new();
+        let mut expected_digests = Vec::new();
+        for _ in 0..128 {
+            let long_msg = CurtaBytes(builder.add_virtual_target_arr::<256>());
+            let long_digest = builder.sha512(&long_msg, &mut gadget);
+            let short_msgs = [
+                CurtaBytes(builder.add_virtual_target_arr::<128>()),
+                CurtaBytes(builder.add_virtual_target_arr::<128>()),
+            ];
+            let short_digests = short_msgs
+                .iter()
+                .map(|msg| builder.sha512(msg, &mut gadget))
+                .collect::<Vec<_>>();
+
+            msg_targets.push(long_msg.0.to_vec());
+            msg_targets.extend(short_msgs.iter().map(|msg| msg.0.to_vec()));
+            expected_digests.push(long_digest);
+            expected_digests.extend(short_digests);
+        }
+
+        let expected_digest_targets = expected_digests
+            .iter()
+            .map(|digest| {
+                let expected = builder.add_virtual_target_arr::<64>();
+                for (d, e) in digest.0.iter().zip(expected.iter()) {
+                    builder.connect(*d, *e);
+                }
+                expected
+            })
+            .collect::<Vec<_>>();
+
+        builder.constrain_sha512_gadget::<SC>(gadget);
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+
+        let messages = (0..128).flat_map(|_| messages.clone()).collect::<Vec<_>>();
+        for ((msg, msg_target), digest_target) in messages
+            .iter()
+            .zip(msg_targets.iter())
+            .zip(expected_digest_targets.iter())
+        {
+            let padded_msg = SHA512Gadget::pad(msg)
+                .into_iter()
+                .map(F::from_canonical_u8)
+                .collect::<Vec<_>>();
+            pw.set_target_arr(msg_target, &padded_msg);
+            let digest = SHA512Gadget::hash(msg).map(F::from_canonical_u8);
+            pw.set_target_arr(digest_target, &digest);
+        }
+
+        let recursive_proof = timed!(
+            timing,
+            "Generate proof",
+            plonky2::plonk::prover::prove(&data.prover_only, &data.common, pw, &mut timing)
+        )
+        .unwrap();
+        timing.print();
+        data.verify(recursive_proof).unwrap();
+    }
+}
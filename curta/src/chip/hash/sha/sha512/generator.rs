@@ -0,0 +1,382 @@
+use core::marker::PhantomData;
+
+use anyhow::{ensure, Result};
+use itertools::Itertools;
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::generator::{GeneratedValues, SimpleGenerator};
+use plonky2::iop::target::Target;
+use plonky2::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::circuit_data::CommonCircuitData;
+use plonky2::util::serialization::{Buffer, IoError, Read, Write};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    SHA512Gadget, SHA512PublicData, INITIAL_HASH, ROUND_CONSTANTS, SHA512_CYCLE_LENGTH,
+    SHA512_NUM_ROUNDS,
+};
+use crate::chip::trace::generator::ArithmeticGenerator;
+use crate::chip::uint::bytes::lookup_table::table::ByteLookupTable;
+use crate::chip::uint::operations::instruction::U32Instruction;
+use crate::chip::uint::util::u64_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::math::prelude::{CubicParameters, *};
+use crate::utils::serde::{BufferRead, BufferWrite};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SHA512AirParameters<F, E>(pub PhantomData<(F, E)>);
+
+/// The number of 128-byte blocks processed by a single SHA-512 proof.
+pub const SHA512_NUM_BLOCKS: usize = (1 << 16) / SHA512_CYCLE_LENGTH;
+
+#[derive(Debug, Clone)]
+pub struct SHA512HintGenerator {
+    padded_message: Vec<Target>,
+    digest_bytes: [Target; 64],
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct SHA512Generator<F: PrimeField64, E: CubicParameters<F>> {
+    pub gadget: SHA512Gadget,
+    pub table: ByteLookupTable,
+    pub padded_messages: Vec<Target>,
+    pub chunk_sizes: Vec<usize>,
+    pub trace_generator: ArithmeticGenerator<SHA512AirParameters<F, E>>,
+    pub pub_values_target: SHA512PublicData<Target>,
+}
+
+impl<F: PrimeField64, E: CubicParameters<F>> AirParameters for SHA512AirParameters<F, E> {
+    type Field = F;
+    type CubicParams = E;
+
+    type Instruction = U32Instruction;
+
+    const NUM_FREE_COLUMNS: usize = 1400;
+    const EXTENDED_COLUMNS: usize = 2100;
+    const NUM_ARITHMETIC_COLUMNS: usize = 0;
+
+    fn num_rows_bits() -> usize {
+        16
+    }
+}
+
+impl<F: RichField, E: CubicParameters<F>> SHA512Generator<F, E> {
+    pub fn id() -> String {
+        "SHA512Generator".to_string()
+    }
+}
+
+impl<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize> SimpleGenerator<F, D>
+    for SHA512Generator<F, E>
+{
+    fn id(&self) -> String {
+        Self::id()
+    }
+
+    fn serialize(
+        &self,
+        dst: &mut Vec<u8>,
+        _: &CommonCircuitData<F, D>,
+    ) -> plonky2::util::serialization::IoResult<()> {
+        let data = bincode::serialize(self).unwrap();
+        dst.write_bytes(&data)
+    }
+
+    fn deserialize(
+        src: &mut Buffer,
+        _: &CommonCircuitData<F, D>,
+    ) -> plonky2::util::serialization::IoResult<Self>
+    where
+        Self: Sized,
+    {
+        let bytes = src.read_bytes()?;
+        let data = bincode::deserialize(&bytes).unwrap();
+        Ok(data)
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        self.padded_messages.clone()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let padded_messages = self
+            .padded_messages
+            .iter()
+            .map(|x| witness.get_target(*x).as_canonical_u64() as u8)
+            .collect::<Vec<_>>();
+        assert_eq!(padded_messages.len(), SHA512_NUM_BLOCKS * 128);
+
+        let message_chunks = self.chunk_sizes.iter().scan(0, |idx, size| {
+            let chunk = padded_messages[*idx..*idx + 128 * size].to_vec();
+            *idx += 128 * size;
+            Some(chunk)
+        });
+
+        // Write trace values
+        let writer = self.trace_generator.new_writer();
+        self.table.write_table_entries(&writer);
+        let sha_public_values = self.gadget.write(message_chunks, &writer);
+        for i in 0..SHA512AirParameters::<F, E>::num_rows() {
+            writer.write_row_instructions(&self.trace_generator.air_data, i);
+        }
+        self.table.write_multiplicities(&writer);
+
+        // Fill sha public values into the output buffer
+        self.pub_values_target
+            .set_targets(sha_public_values, out_buffer);
+    }
+}
+
+impl SHA512PublicData<Target> {
+    /// Allocates the public data targets for a batch of messages, where the `i`-th message
+    /// consists of `chunk_sizes[i]` blocks of 128 bytes and its digest is given by the `i`-th 64
+    /// bytes of `digests`. The hash states after the blocks of a message other than the last one
+    /// are allocated as virtual targets.
+    pub fn add_virtual<F: RichField + Extendable<D>, const D: usize>(
+        builder: &mut CircuitBuilder<F, D>,
+        digests: &[Target],
+        chunk_sizes: &[usize],
+    ) -> Self {
+        let public_w_targets = (0..16 * SHA512_NUM_BLOCKS)
+            .map(|_| builder.add_virtual_target_arr::<8>())
+            .collect::<Vec<_>>();
+
+        let mut end_bits_targets = Vec::new();
+        let mut hash_state_targets = Vec::new();
+
+        for (chunk_size, digest) in chunk_sizes.iter().zip_eq(digests.chunks_exact(64)) {
+            end_bits_targets.extend((0..(chunk_size - 1)).map(|_| builder.zero()));
+            end_bits_targets.push(builder.one());
+
+            hash_state_targets
+                .extend((0..8 * (chunk_size - 1)).map(|_| builder.add_virtual_target_arr::<8>()));
+
+            // Convert digest to little endian u64 chunks
+            hash_state_targets.extend(digest.chunks_exact(8).map(|word| {
+                let mut array: [Target; 8] = word.try_into().unwrap();
+                array.reverse();
+                array
+            }));
+        }
+
+        SHA512PublicData {
+            public_w: public_w_targets,
+            hash_state: hash_state_targets,
+            end_bits: end_bits_targets,
+        }
+    }
+
+    pub fn set_targets<F: RichField>(
+        &self,
+        values: SHA512PublicData<F>,
+        out_buffer: &mut GeneratedValues<F>,
+    ) {
+        for (pub_w_target, pub_w_value) in self.public_w.iter().zip_eq(values.public_w.iter()) {
+            out_buffer.set_target_arr(pub_w_target, pub_w_value);
+        }
+        for (hash_target, hash_value) in self.hash_state.iter().zip_eq(values.hash_state.iter()) {
+            out_buffer.set_target_arr(hash_target, hash_value);
+        }
+    }
+
+    /// Returns the public inputs of the SHA-512 proof in the order in which the gadget allocates
+    /// them. The initial hash and the periodic table of round constants and row flags are
+    /// constants of the circuit.
+    pub fn public_input_targets<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> Vec<Target> {
+        let round_constants = (0..SHA512_CYCLE_LENGTH)
+            .flat_map(|j| u64_to_le_field_bytes(ROUND_CONSTANTS.get(j).copied().unwrap_or(0)))
+            .map(|x| builder.constant(x))
+            .collect::<Vec<_>>();
+        let load_bits = (0..SHA512_CYCLE_LENGTH)
+            .map(|j| builder.constant(F::from_canonical_u8((j < 16) as u8)))
+            .collect::<Vec<_>>();
+        let round_bits = (0..SHA512_CYCLE_LENGTH)
+            .map(|j| builder.constant(F::from_canonical_u8((j < SHA512_NUM_ROUNDS) as u8)))
+            .collect::<Vec<_>>();
+
+        self.public_w
+            .iter()
+            .flatten()
+            .copied()
+            .chain(
+                INITIAL_HASH
+                    .map(|value| u64_to_le_field_bytes(value).map(|x| builder.constant(x)))
+                    .into_iter()
+                    .flatten(),
+            )
+            .chain(round_constants)
+            .chain(load_bits)
+            .chain(round_bits)
+            .chain(self.hash_state.iter().flatten().copied())
+            .chain(self.end_bits.iter().copied())
+            .collect()
+    }
+}
+
+impl SHA512HintGenerator {
+    pub fn new(padded_message: &[Target], digest_bytes: [Target; 64]) -> Self {
+        SHA512HintGenerator {
+            padded_message: padded_message.to_vec(),
+            digest_bytes,
+        }
+    }
+}
+
+impl SHA512HintGenerator {
+    pub fn id() -> String {
+        "SHA512HintGenerator".to_string()
+    }
+
+    /// Computes the digest of a padded message given as field elements.
+    ///
+    /// Returns an error if the message is not a whole number of 128-byte blocks or if one of its
+    /// values is not a byte.
+    pub fn padded_digest<F: RichField>(padded_message: &[F]) -> Result<[F; 64]> {
+        ensure!(
+            padded_message.len() % 128 == 0,
+            "SHA512HintGenerator: padded message has length {} bytes, which is not a multiple of \
+             the 128-byte block size",
+            padded_message.len()
+        );
+        let padded_message = padded_message
+            .iter()
+            .enumerate()
+            .map(|(i, x)| {
+                let value = x.as_canonical_u64();
+                ensure!(
+                    value <= u8::MAX as u64,
+                    "SHA512HintGenerator: value {} at index {} of the padded message is not a byte",
+                    value,
+                    i
+                );
+                Ok(value as u8)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut state = INITIAL_HASH;
+        for block in padded_message.chunks_exact(128) {
+            let w_val = SHA512Gadget::process_inputs(block);
+            state = SHA512Gadget::compress_round(state, &w_val, ROUND_CONSTANTS);
+        }
+
+        let digest_bytes = state
+            .map(|x| x.to_be_bytes().map(F::from_canonical_u8))
+            .concat();
+        Ok(digest_bytes.try_into().unwrap())
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D> for SHA512HintGenerator {
+    fn id(&self) -> String {
+        Self::id()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        self.padded_message.clone()
+    }
+
+    fn serialize(
+        &self,
+        dst: &mut Vec<u8>,
+        _: &CommonCircuitData<F, D>,
+    ) -> plonky2::util::serialization::IoResult<()> {
+        dst.write_target_vec(&self.padded_message)?;
+        dst.write_target_vec(&self.digest_bytes)?;
+        Ok(())
+    }
+
+    fn deserialize(
+        src: &mut Buffer,
+        _: &CommonCircuitData<F, D>,
+    ) -> plonky2::util::serialization::IoResult<Self>
+    where
+        Self: Sized,
+    {
+        let padded_message = src.read_target_vec()?;
+        let digest_bytes = src.read_target_vec()?;
+        Ok(Self {
+            padded_message,
+            digest_bytes: digest_bytes.try_into().map_err(|_| IoError)?,
+        })
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let padded_message = witness.get_targets(&self.padded_message);
+        let digest_bytes = Self::padded_digest(&padded_message).unwrap_or_else(|e| panic!("{}", e));
+
+        out_buffer.set_target_arr(&self.digest_bytes, &digest_bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+
+    use super::*;
+
+    #[test]
+    fn test_sha512_public_data_layout() {
+        type F = GoldilocksField;
+        const D: usize = 2;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let chunk_sizes = [1, 2];
+        let digests = builder.add_virtual_targets(128);
+
+        let public_data = SHA512PublicData::add_virtual(&mut builder, &digests, &chunk_sizes);
+
+        assert_eq!(public_data.public_w.len(), 16 * SHA512_NUM_BLOCKS);
+        assert_eq!(public_data.end_bits.len(), 3);
+        assert_eq!(public_data.hash_state.len(), 8 * 3);
+
+        // The digests are the final states of the messages, with big endian words.
+        for (i, byte) in digests[..64].iter().enumerate() {
+            assert_eq!(public_data.hash_state[i / 8][7 - i % 8], *byte);
+        }
+        for (i, byte) in digests[64..].iter().enumerate() {
+            assert_eq!(public_data.hash_state[16 + i / 8][7 - i % 8], *byte);
+        }
+
+        let public_inputs = public_data.public_input_targets(&mut builder);
+        assert_eq!(
+            public_inputs.len(),
+            8 * (16 * SHA512_NUM_BLOCKS + 8 + SHA512_CYCLE_LENGTH + 8 * 3)
+                + 2 * SHA512_CYCLE_LENGTH
+                + 3
+        );
+    }
+
+    #[test]
+    fn test_sha512_hint_padded_digest() {
+        type F = GoldilocksField;
+
+        let to_field = |bytes: &[u8]| {
+            bytes
+                .iter()
+                .map(|b| F::from_canonical_u8(*b))
+                .collect::<Vec<_>>()
+        };
+
+        let digest =
+            SHA512HintGenerator::padded_digest(&to_field(&SHA512Gadget::pad(b"abc"))).unwrap();
+        assert_eq!(digest.to_vec(), to_field(&SHA512Gadget::hash(b"abc")));
+
+        // An unpadded message is rejected with its length and the expected block size.
+        let err = SHA512HintGenerator::padded_digest(&to_field(&[0u8; 100])).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("length 100 bytes"), "{}", message);
+        assert!(
+            message.contains("multiple of the 128-byte block size"),
+            "{}",
+            message
+        );
+    }
+}
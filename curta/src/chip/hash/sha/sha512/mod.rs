@@ -0,0 +1,821 @@
+//! SHA-512 over 64-bit words, as used by Ed25519 signatures.
+//!
+//! Every 128-byte block occupies a cycle of 128 rows: the first 80 rows perform the compression
+//! rounds while the remaining rows carry the working state to the end of the cycle, where the
+//! chaining value is published. The round constants, together with the bits marking the rows
+//! that load message words and the rows that perform a round, are read from a periodic table
+//! carried by the bus, in the same way the SHA-256 chip reads its round constants.
+
+pub mod builder_gadget;
+pub mod generator;
+
+use core::borrow::Borrow;
+
+use serde::{Deserialize, Serialize};
+
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::builder::AirBuilder;
+use crate::chip::instruction::cycle::Cycle;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::cubic::CubicRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::{Register, RegisterSerializable, RegisterSized};
+use crate::chip::table::bus::global::Bus;
+use crate::chip::trace::writer::TraceWriter;
+use crate::chip::uint::bytes::lookup_table::builder_operations::ByteLookupOperations;
+use crate::chip::uint::bytes::operations::value::ByteOperation;
+use crate::chip::uint::bytes::register::ByteRegister;
+use crate::chip::uint::operations::instruction::U32Instructions;
+use crate::chip::uint::register::U64Register;
+use crate::chip::uint::util::u64_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+
+pub type U64Value<T> = <U64Register as Register>::Value<T>;
+
+/// The number of rows used to process a single 128-byte block.
+pub const SHA512_CYCLE_LENGTH: usize = 128;
+
+/// The number of compression rounds of a block.
+pub const SHA512_NUM_ROUNDS: usize = 80;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SHA512Gadget {
+    /// The input blocks processed into 16-words of U64 values
+    pub public_word: ArrayRegister<U64Register>,
+    /// The hash states at the end of every block
+    pub state: ArrayRegister<U64Register>,
+    /// The window of 16 w-values
+    pub w_window: ArrayRegister<U64Register>,
+    /// Signifies when to reset the state to the initial hash
+    pub end_bit: BitRegister,
+    pub(crate) end_bits_public: ArrayRegister<BitRegister>,
+    pub(crate) initial_state: ArrayRegister<U64Register>,
+    pub(crate) round_constant: U64Register,
+    pub(crate) load_bit: BitRegister,
+    pub(crate) round_bit: BitRegister,
+    pub round_constants_public: ArrayRegister<U64Register>,
+    pub(crate) load_bits_public: ArrayRegister<BitRegister>,
+    pub(crate) round_bits_public: ArrayRegister<BitRegister>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SHA512PublicData<T> {
+    pub public_w: Vec<U64Value<T>>,
+    pub hash_state: Vec<U64Value<T>>,
+    pub end_bits: Vec<T>,
+}
+
+const ROUND_CONSTANTS: [u64; 80] = [
+    0x428a2f98d728ae22,
+    0x7137449123ef65cd,
+    0xb5c0fbcfec4d3b2f,
+    0xe9b5dba58189dbbc,
+    0x3956c25bf348b538,
+    0x59f111f1b605d019,
+    0x923f82a4af194f9b,
+    0xab1c5ed5da6d8118,
+    0xd807aa98a3030242,
+    0x12835b0145706fbe,
+    0x243185be4ee4b28c,
+    0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f,
+    0x80deb1fe3b1696b1,
+    0x9bdc06a725c71235,
+    0xc19bf174cf692694,
+    0xe49b69c19ef14ad2,
+    0xefbe4786384f25e3,
+    0x0fc19dc68b8cd5b5,
+    0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275,
+    0x4a7484aa6ea6e483,
+    0x5cb0a9dcbd41fbd4,
+    0x76f988da831153b5,
+    0x983e5152ee66dfab,
+    0xa831c66d2db43210,
+    0xb00327c898fb213f,
+    0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2,
+    0xd5a79147930aa725,
+    0x06ca6351e003826f,
+    0x142929670a0e6e70,
+    0x27b70a8546d22ffc,
+    0x2e1b21385c26c926,
+    0x4d2c6dfc5ac42aed,
+    0x53380d139d95b3df,
+    0x650a73548baf63de,
+    0x766a0abb3c77b2a8,
+    0x81c2c92e47edaee6,
+    0x92722c851482353b,
+    0xa2bfe8a14cf10364,
+    0xa81a664bbc423001,
+    0xc24b8b70d0f89791,
+    0xc76c51a30654be30,
+    0xd192e819d6ef5218,
+    0xd69906245565a910,
+    0xf40e35855771202a,
+    0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8,
+    0x1e376c085141ab53,
+    0x2748774cdf8eeb99,
+    0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63,
+    0x4ed8aa4ae3418acb,
+    0x5b9cca4f7763e373,
+    0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc,
+    0x78a5636f43172f60,
+    0x84c87814a1f0ab72,
+    0x8cc702081a6439ec,
+    0x90befffa23631e28,
+    0xa4506cebde82bde9,
+    0xbef9a3f7b2c67915,
+    0xc67178f2e372532b,
+    0xca273eceea26619c,
+    0xd186b8c721c0c207,
+    0xeada7dd6cde0eb1e,
+    0xf57d4f7fee6ed178,
+    0x06f067aa72176fba,
+    0x0a637dc5a2c898a6,
+    0x113f9804bef90dae,
+    0x1b710b35131c471b,
+    0x28db77f523047d84,
+    0x32caab7b40c72493,
+    0x3c9ebe0a15c9bebc,
+    0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6,
+    0x597f299cfc657e2a,
+    0x5fcb6fab3ad6faec,
+    0x6c44198c4a475817,
+];
+
+const INITIAL_HASH: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+impl<L: AirParameters> AirBuilder<L> {
+    pub fn process_sha_512_batch(
+        &mut self,
+        clk: &ElementRegister,
+        bus: &mut Bus<L::CubicParams>,
+        bus_channel_idx: usize,
+        operations: &mut ByteLookupOperations,
+    ) -> SHA512Gadget
+    where
+        L::Instruction: U32Instructions,
+    {
+        assert_eq!(
+            L::num_rows() % SHA512_CYCLE_LENGTH,
+            0,
+            "The number of rows must be a multiple of the cycle length"
+        );
+        let num_blocks = L::num_rows() / SHA512_CYCLE_LENGTH;
+
+        // Registers to be written to
+        let w_window = self.alloc_array::<U64Register>(17);
+        let load_bit = self.alloc::<BitRegister>();
+        let round_bit = self.alloc::<BitRegister>();
+        let end_bit = self.alloc::<BitRegister>();
+        let msg_array = self.alloc_array::<U64Register>(8);
+        let round_constant = self.alloc::<U64Register>();
+        let cycle_128 = self.cycle(7);
+
+        // Public values
+        let public_w = self.alloc_array_public::<U64Register>(16 * num_blocks);
+        let initial_state = self.alloc_array_public::<U64Register>(8);
+        let round_constants_public = self.alloc_array_public::<U64Register>(SHA512_CYCLE_LENGTH);
+        let load_bits_public = self.alloc_array_public::<BitRegister>(SHA512_CYCLE_LENGTH);
+        let round_bits_public = self.alloc_array_public::<BitRegister>(SHA512_CYCLE_LENGTH);
+        let hash_state = self.alloc_array_public::<U64Register>(8 * num_blocks);
+        let end_bits_public = self.alloc_array_public::<BitRegister>(num_blocks);
+
+        // Get the w value from the bus
+        let w_challenges = self.alloc_challenge_array::<CubicRegister>(U64Register::size_of() + 1);
+        let clk_w =
+            self.accumulate_expressions(&w_challenges, &[clk.expr(), w_window.get(0).expr()]);
+        self.output_from_bus_filtered(bus_channel_idx, clk_w, load_bit.expr());
+
+        // Get hash state challenges
+        let state_challenges =
+            self.alloc_challenge_array::<CubicRegister>(U64Register::size_of() * 8 + 1);
+
+        // Get a challenge for the end bit
+        let end_bit_challenge = self.alloc_challenge_array::<CubicRegister>(2);
+
+        // Put the end_bit in the bus at the end of each block
+        let clk_end_bit =
+            self.accumulate_expressions(&end_bit_challenge, &[clk.expr(), end_bit.expr()]);
+        self.input_to_bus_filtered(bus_channel_idx, clk_end_bit, cycle_128.end_bit.expr());
+        // Constrain all other values of end_bit to zero
+        self.assert_expression_zero(end_bit.expr() * cycle_128.end_bit.not_expr());
+
+        // Put public w values and hash state in the bus
+        for i in 0..num_blocks {
+            let block_end = i * SHA512_CYCLE_LENGTH + SHA512_CYCLE_LENGTH - 1;
+            let state_digest = self.accumulate_public_expressions(
+                &state_challenges,
+                &[
+                    ArithmeticExpression::from_constant(L::Field::from_canonical_usize(block_end)),
+                    hash_state.get_subarray(i * 8..i * 8 + 8).expr(),
+                ],
+            );
+            bus.output_global_value(&state_digest);
+
+            let bit_digest = self.accumulate_public_expressions(
+                &end_bit_challenge,
+                &[
+                    ArithmeticExpression::from_constant(L::Field::from_canonical_usize(block_end)),
+                    end_bits_public.get(i).expr(),
+                ],
+            );
+            bus.output_global_value(&bit_digest);
+
+            for k in 0..16 {
+                let w = public_w.get(i * 16 + k);
+                let clk_expr = ArithmeticExpression::from_constant(L::Field::from_canonical_usize(
+                    i * SHA512_CYCLE_LENGTH + k,
+                ));
+                let digest =
+                    self.accumulate_public_expressions(&w_challenges, &[clk_expr, w.expr()]);
+                bus.insert_global_value(&digest);
+            }
+        }
+
+        // Put the periodic table of round constants and row flags into the bus. Every row reads
+        // the entry of the row one cycle before it and passes it on to the row one cycle after.
+        let round_constant_challenges =
+            self.alloc_challenge_array::<CubicRegister>(U64Register::size_of() + 3);
+
+        for k in 0..SHA512_CYCLE_LENGTH {
+            let entry = [
+                round_constants_public.get(k).expr(),
+                load_bits_public.get(k).expr(),
+                round_bits_public.get(k).expr(),
+            ];
+            let round_constant_public_input_digest = self.accumulate_public_expressions(
+                &round_constant_challenges,
+                &[
+                    &[ArithmeticExpression::from_constant(
+                        L::Field::from_canonical_usize(k)
+                            - L::Field::from_canonical_usize(SHA512_CYCLE_LENGTH),
+                    )],
+                    &entry[..],
+                ]
+                .concat(),
+            );
+            bus.insert_global_value(&round_constant_public_input_digest);
+
+            let round_constants_public_output_digest = self.accumulate_public_expressions(
+                &round_constant_challenges,
+                &[
+                    &[ArithmeticExpression::from_constant(
+                        L::Field::from_canonical_usize(L::num_rows() - SHA512_CYCLE_LENGTH + k),
+                    )],
+                    &entry[..],
+                ]
+                .concat(),
+            );
+            bus.output_global_value(&round_constants_public_output_digest);
+        }
+
+        let round_constant_output = self.accumulate_expressions(
+            &round_constant_challenges,
+            &[
+                clk.expr() - L::Field::from_canonical_usize(SHA512_CYCLE_LENGTH),
+                round_constant.expr(),
+                load_bit.expr(),
+                round_bit.expr(),
+            ],
+        );
+        self.output_from_bus(bus_channel_idx, round_constant_output);
+
+        let round_constant_input = self.accumulate_expressions(
+            &round_constant_challenges,
+            &[
+                clk.expr(),
+                round_constant.expr(),
+                load_bit.expr(),
+                round_bit.expr(),
+            ],
+        );
+        self.input_to_bus(bus_channel_idx, round_constant_input);
+
+        // The message schedule
+        self.sha_512_premessage(&w_window, &load_bit, &round_bit, operations);
+
+        // Set the window values
+        for i in 1..17 {
+            self.set_to_expression_transition(&w_window.get(i).next(), w_window.get(i - 1).expr());
+        }
+
+        let hash = self.alloc_array::<U64Register>(8);
+        for (h, init) in hash.iter().zip(initial_state.iter()) {
+            self.set_to_expression_first_row(&h, init.expr());
+        }
+        // The SHA step phase
+        let hash_next = self.sha_512_step(
+            &hash,
+            &msg_array,
+            &w_window,
+            &initial_state,
+            &round_constant,
+            &round_bit,
+            &cycle_128,
+            &end_bit,
+            operations,
+        );
+
+        // Connect hash to hash next depending on end_bit
+        for i in 0..8 {
+            self.set_to_expression_transition(
+                &hash.get(i).next(),
+                hash.get(i).expr() * cycle_128.end_bit.not_expr()
+                    + msg_array.get(i).next().expr() * cycle_128.end_bit.expr(),
+            );
+        }
+
+        let clk_hash_next =
+            self.accumulate_expressions(&state_challenges, &[clk.expr(), hash_next.expr()]);
+        self.input_to_bus_filtered(bus_channel_idx, clk_hash_next, cycle_128.end_bit.expr());
+
+        // The byte lookup needs an even number of operations
+        if operations.values.len() % 2 == 1 {
+            let dummy = self.alloc::<ByteRegister>();
+            let dummy_range = ByteOperation::Range(dummy);
+            self.set_byte_operation(&dummy_range, operations);
+        }
+
+        SHA512Gadget {
+            public_word: public_w,
+            state: hash_state,
+            end_bit,
+            w_window,
+            initial_state,
+            round_constant,
+            load_bit,
+            round_bit,
+            round_constants_public,
+            load_bits_public,
+            round_bits_public,
+            end_bits_public,
+        }
+    }
+
+    fn sha_512_premessage(
+        &mut self,
+        w_window: &ArrayRegister<U64Register>,
+        load_bit: &BitRegister,
+        round_bit: &BitRegister,
+        operations: &mut ByteLookupOperations,
+    ) where
+        L::Instruction: U32Instructions,
+    {
+        // Calculate s_0 = w_i_minus_15.rotate_right(1) ^ w_i_minus_15.rotate_right(8) ^ (w_i_minus_15 >> 7);
+        let w_i_minus_15_rotate_1 = self.bit_rotate_right(&w_window.get(15), 1, operations);
+        let w_i_minus_15_rotate_8 = self.bit_rotate_right(&w_window.get(15), 8, operations);
+        let w_i_minus_15_shr_7 = self.bit_shr(&w_window.get(15), 7, operations);
+
+        let mut s_0 = self.bitwise_xor(&w_i_minus_15_rotate_1, &w_i_minus_15_rotate_8, operations);
+        s_0 = self.bitwise_xor(&s_0, &w_i_minus_15_shr_7, operations);
+
+        // Calculate s_1 = w_i_minus_2.rotate_right(19) ^ w_i_minus_2.rotate_right(61) ^ (w_i_minus_2 >> 6);
+        let w_i_minus_2_rotate_19 = self.bit_rotate_right(&w_window.get(2), 19, operations);
+        let w_i_minus_2_rotate_61 = self.bit_rotate_right(&w_window.get(2), 61, operations);
+        let w_i_minus_2_shr_6 = self.bit_shr(&w_window.get(2), 6, operations);
+
+        let mut s_1 = self.bitwise_xor(&w_i_minus_2_rotate_19, &w_i_minus_2_rotate_61, operations);
+        s_1 = self.bitwise_xor(&s_1, &w_i_minus_2_shr_6, operations);
+
+        // Calculate w_i = w_i_minus_16 + s_0 + w_i_minus_7 + s_1;
+        let mut w_i = self.add_u64(&w_window.get(16), &s_0, operations);
+        w_i = self.add_u64(&w_i, &w_window.get(7), operations);
+        w_i = self.add_u64(&w_i, &s_1, operations);
+
+        // The schedule applies in the rounds that do not load a message word
+        self.assert_expression_zero(
+            (round_bit.expr() - load_bit.expr()) * (w_i.expr() - w_window.get(0).expr()),
+        );
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn sha_512_step(
+        &mut self,
+        hash: &ArrayRegister<U64Register>,
+        msg: &ArrayRegister<U64Register>,
+        w_window: &ArrayRegister<U64Register>,
+        initial_state: &ArrayRegister<U64Register>,
+        round_constant: &U64Register,
+        round_bit: &BitRegister,
+        cycle_128: &Cycle<L::Field>,
+        end_bit: &BitRegister,
+        operations: &mut ByteLookupOperations,
+    ) -> ArrayRegister<U64Register>
+    where
+        L::Instruction: U32Instructions,
+    {
+        // Initialize working variables
+        let a = msg.get(0);
+        let b = msg.get(1);
+        let c = msg.get(2);
+        let d = msg.get(3);
+        let e = msg.get(4);
+        let f = msg.get(5);
+        let g = msg.get(6);
+        let h = msg.get(7);
+
+        for i in 0..8 {
+            self.set_to_expression_first_row(&msg.get(i), initial_state.get(i).expr());
+        }
+
+        // Calculate sum_1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+        let e_rotate_14 = self.bit_rotate_right(&e, 14, operations);
+        let e_rotate_18 = self.bit_rotate_right(&e, 18, operations);
+        let e_rotate_41 = self.bit_rotate_right(&e, 41, operations);
+        let mut sum_1 = self.bitwise_xor(&e_rotate_14, &e_rotate_18, operations);
+        sum_1 = self.bitwise_xor(&sum_1, &e_rotate_41, operations);
+
+        // Calculate ch = (e & f) ^ (!e & g);
+        let e_and_f = self.bitwise_and(&e, &f, operations);
+        let not_e = self.bitwise_not(&e, operations);
+        let not_e_and_g = self.bitwise_and(&not_e, &g, operations);
+        let ch = self.bitwise_xor(&e_and_f, &not_e_and_g, operations);
+
+        // Calculate temp_1 = h + sum_1 + ch + round_constant + w;
+        let mut temp_1 = self.add_u64(&h, &sum_1, operations);
+        temp_1 = self.add_u64(&temp_1, &ch, operations);
+        temp_1 = self.add_u64(&temp_1, round_constant, operations);
+        temp_1 = self.add_u64(&temp_1, &w_window.get(0), operations);
+
+        // Calculate sum_0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+        let a_rotate_28 = self.bit_rotate_right(&a, 28, operations);
+        let a_rotate_34 = self.bit_rotate_right(&a, 34, operations);
+        let a_rotate_39 = self.bit_rotate_right(&a, 39, operations);
+        let mut sum_0 = self.bitwise_xor(&a_rotate_28, &a_rotate_34, operations);
+        sum_0 = self.bitwise_xor(&sum_0, &a_rotate_39, operations);
+
+        // Calculate maj = (a & b) ^ (a & c) ^ (b & c);
+        let a_and_b = self.bitwise_and(&a, &b, operations);
+        let a_and_c = self.bitwise_and(&a, &c, operations);
+        let b_and_c = self.bitwise_and(&b, &c, operations);
+        let mut maj = self.bitwise_xor(&a_and_b, &a_and_c, operations);
+        maj = self.bitwise_xor(&maj, &b_and_c, operations);
+
+        // Calculate temp_2 = sum_0 + maj;
+        let temp_2 = self.add_u64(&sum_0, &maj, operations);
+
+        // Calculate the next cycle values
+        let a_next = self.add_u64(&temp_1, &temp_2, operations);
+        let b_next = a;
+        let c_next = b;
+        let d_next = c;
+        let e_next = self.add_u64(&d, &temp_1, operations);
+        let f_next = e;
+        let g_next = f;
+        let h_next = g;
+
+        let msg_next = [
+            a_next, b_next, c_next, d_next, e_next, f_next, g_next, h_next,
+        ];
+
+        // Assign the hash values in the end of the block
+        let hash_next = self.alloc_array::<U64Register>(8);
+        for ((h, m_next), h_next) in hash.iter().zip(msg_next.iter()).zip(hash_next.iter()) {
+            let carry = self.alloc::<BitRegister>();
+            self.set_add_u64(&h, m_next, &None, &h_next, &carry, operations);
+        }
+
+        // Assign next values to the next row registers: apply a round in the round rows, keep
+        // the state in the remaining rows, and start a new block at the end of the cycle.
+        let bit = cycle_128.end_bit;
+        for (((m, m_next), h_next), init) in msg
+            .iter()
+            .zip(msg_next.iter())
+            .zip(hash_next.iter())
+            .zip(initial_state.iter())
+        {
+            self.set_to_expression_transition(
+                &m.next(),
+                m_next.expr() * round_bit.expr()
+                    + m.expr() * (round_bit.not_expr() - bit.expr())
+                    + (h_next.expr() * end_bit.not_expr() + init.expr() * end_bit.expr())
+                        * bit.expr(),
+            );
+        }
+
+        hash_next
+    }
+}
+
+impl SHA512Gadget {
+    pub fn write<F: Field, I: IntoIterator>(
+        &self,
+        padded_messages: I,
+        writer: &TraceWriter<F>,
+    ) -> SHA512PublicData<F>
+    where
+        I::Item: Borrow<[u8]>,
+    {
+        let mut w_values = Vec::new();
+        let mut end_bits_values = Vec::new();
+        let mut hash_values = Vec::new();
+        let mut public_w_values = Vec::new();
+
+        padded_messages.into_iter().for_each(|padded_msg| {
+            let padded_msg = padded_msg.borrow();
+            let num_blocks = padded_msg.len() / 128;
+            end_bits_values.extend_from_slice(&vec![F::ZERO; num_blocks - 1]);
+            end_bits_values.push(F::ONE);
+
+            let mut state = INITIAL_HASH;
+            for block in padded_msg.chunks_exact(128) {
+                let w_val = SHA512Gadget::process_inputs(block);
+                public_w_values.extend(w_val[0..16].iter().map(|x| u64_to_le_field_bytes::<F>(*x)));
+                state = SHA512Gadget::compress_round(state, &w_val, ROUND_CONSTANTS);
+                w_values.push(w_val);
+                hash_values.extend_from_slice(&state.map(u64_to_le_field_bytes::<F>));
+            }
+        });
+        let num_blocks = self.end_bits_public.len();
+        assert!(
+            w_values.len() == num_blocks,
+            "Padded messages lengths do not add up"
+        );
+
+        let round_constants: [u64; SHA512_CYCLE_LENGTH] =
+            core::array::from_fn(|j| ROUND_CONSTANTS.get(j).copied().unwrap_or(0));
+        let load_bits: [F; SHA512_CYCLE_LENGTH] =
+            core::array::from_fn(|j| F::from_canonical_u8((j < 16) as u8));
+        let round_bits: [F; SHA512_CYCLE_LENGTH] =
+            core::array::from_fn(|j| F::from_canonical_u8((j < SHA512_NUM_ROUNDS) as u8));
+
+        writer.write_array(
+            &self.initial_state,
+            INITIAL_HASH.map(u64_to_le_field_bytes),
+            0,
+        );
+        writer.write_array(
+            &self.round_constants_public,
+            round_constants.map(u64_to_le_field_bytes),
+            0,
+        );
+        writer.write_array(&self.load_bits_public, load_bits, 0);
+        writer.write_array(&self.round_bits_public, round_bits, 0);
+        writer.write_array(&self.state, &hash_values, 0);
+        writer.write_array(&self.end_bits_public, &end_bits_values, 0);
+        writer.write_array(&self.public_word, &public_w_values, 0);
+        (0..num_blocks).for_each(|i| {
+            writer.write(
+                &self.end_bit,
+                &end_bits_values[i],
+                i * SHA512_CYCLE_LENGTH + SHA512_CYCLE_LENGTH - 1,
+            );
+            let rows = round_constants
+                .iter()
+                .zip(load_bits.iter())
+                .zip(round_bits.iter())
+                .enumerate();
+            for (j, ((round_constant, load_bit), round_bit)) in rows {
+                let row = i * SHA512_CYCLE_LENGTH + j;
+                writer.write(
+                    &self.round_constant,
+                    &u64_to_le_field_bytes(*round_constant),
+                    row,
+                );
+                writer.write(&self.load_bit, load_bit, row);
+                writer.write(&self.round_bit, round_bit, row);
+                let w = w_values[i].get(j).copied().unwrap_or(0);
+                writer.write(&self.w_window.get(0), &u64_to_le_field_bytes(w), row);
+            }
+        });
+
+        SHA512PublicData {
+            public_w: public_w_values,
+            hash_state: hash_values,
+            end_bits: end_bits_values,
+        }
+    }
+
+    pub fn process_inputs(block: &[u8]) -> [u64; 80] {
+        let block_u64 = block
+            .chunks_exact(8)
+            .map(|x| u64::from_be_bytes(x.try_into().unwrap()))
+            .collect::<Vec<_>>();
+        let mut w = [0u64; 80];
+
+        w[..16].copy_from_slice(&block_u64[..16]);
+
+        for i in 16..80 {
+            let s0 = w[i - 15].rotate_right(1) ^ w[i - 15].rotate_right(8) ^ (w[i - 15] >> 7);
+            let s1 = w[i - 2].rotate_right(19) ^ w[i - 2].rotate_right(61) ^ (w[i - 2] >> 6);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+        w
+    }
+
+    pub fn compress_round(hash: [u64; 8], w: &[u64; 80], round_constants: [u64; 80]) -> [u64; 8] {
+        let mut msg = hash;
+        for (w_i, round_constant) in w.iter().zip(round_constants.iter()) {
+            msg = SHA512Gadget::step(msg, *w_i, *round_constant);
+        }
+
+        core::array::from_fn(|i| hash[i].wrapping_add(msg[i]))
+    }
+
+    pub fn step(msg: [u64; 8], w_i: u64, round_constant: u64) -> [u64; 8] {
+        let [a, b, c, d, e, f, g, h] = msg;
+
+        let sum_1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+        let ch = (e & f) ^ (!e & g);
+        let temp_1 = h
+            .wrapping_add(sum_1)
+            .wrapping_add(ch)
+            .wrapping_add(round_constant)
+            .wrapping_add(w_i);
+        let sum_0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let temp_2 = sum_0.wrapping_add(maj);
+
+        [
+            temp_1.wrapping_add(temp_2),
+            a,
+            b,
+            c,
+            d.wrapping_add(temp_1),
+            e,
+            f,
+            g,
+        ]
+    }
+
+    pub fn pad(msg: &[u8]) -> Vec<u8> {
+        let mut padded_msg = Vec::new();
+        padded_msg.extend_from_slice(msg);
+        padded_msg.push(1 << 7);
+
+        // Pad with zeros until the length is 112 mod 128
+        let mdi = msg.len() % 128;
+        let padlen = if mdi < 112 { 111 - mdi } else { 239 - mdi };
+        padded_msg.extend_from_slice(&vec![0u8; padlen]);
+
+        // add length as 128 bit number
+        let len = ((msg.len() as u128) * 8).to_be_bytes();
+        padded_msg.extend_from_slice(&len);
+
+        padded_msg
+    }
+
+    /// Computes the SHA-512 digest of `msg` natively.
+    pub fn hash(msg: &[u8]) -> [u8; 64] {
+        let mut state = INITIAL_HASH;
+        for block in Self::pad(msg).chunks_exact(128) {
+            let w_val = Self::process_inputs(block);
+            state = Self::compress_round(state, &w_val, ROUND_CONSTANTS);
+        }
+        state.map(u64::to_be_bytes).concat().try_into().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::timed;
+    use plonky2::util::timing::TimingTree;
+
+    use super::*;
+    pub use crate::chip::builder::tests::*;
+    use crate::chip::builder::AirBuilder;
+    use crate::chip::uint::operations::instruction::U32Instruction;
+    use crate::chip::AirParameters;
+
+    const EMPTY_DIGEST: &str = "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e";
+    const ABC_DIGEST: &str = "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f";
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub struct SHA512Test;
+
+    impl AirParameters for SHA512Test {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = U32Instruction;
+
+        const NUM_FREE_COLUMNS: usize = 1400;
+        const EXTENDED_COLUMNS: usize = 2100;
+        const NUM_ARITHMETIC_COLUMNS: usize = 0;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    #[test]
+    fn test_sha_512_native_hash() {
+        assert_eq!(hex::encode(SHA512Gadget::hash(b"")), EMPTY_DIGEST);
+        assert_eq!(hex::encode(SHA512Gadget::hash(b"abc")), ABC_DIGEST);
+
+        // A message whose padding spills into a second block.
+        let msg = [0x61u8; 112];
+        assert_eq!(SHA512Gadget::pad(&msg).len(), 256);
+    }
+
+    #[test]
+    fn test_sha_512_stark() {
+        type F = GoldilocksField;
+        type L = SHA512Test;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut timing = TimingTree::new("Sha512 test", log::Level::Debug);
+
+        let mut builder = AirBuilder::<L>::new();
+        let clk = builder.clock();
+
+        let (mut operations, table) = builder.byte_operations();
+
+        let mut bus = builder.new_bus();
+        let channel_idx = bus.new_channel(&mut builder);
+
+        let sha_gadget =
+            builder.process_sha_512_batch(&clk, &mut bus, channel_idx, &mut operations);
+
+        builder.register_byte_lookup(operations, &table);
+        builder.constrain_bus(bus);
+
+        let (air, trace_data) = builder.build();
+
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+        let writer = generator.new_writer();
+
+        // Both messages fit in a single block, filling the 512 blocks of the trace.
+        let messages = (0..256)
+            .flat_map(|_| [b"".to_vec(), b"abc".to_vec()])
+            .collect::<Vec<_>>();
+        let padded_messages = messages
+            .iter()
+            .map(|m| SHA512Gadget::pad(m))
+            .collect::<Vec<_>>();
+
+        let expected_digests: Vec<[u64; 8]> = (0..256)
+            .flat_map(|_| [EMPTY_DIGEST, ABC_DIGEST])
+            .map(|digest| {
+                hex::decode(digest)
+                    .unwrap()
+                    .chunks_exact(8)
+                    .map(|x| u64::from_be_bytes(x.try_into().unwrap()))
+                    .collect::<Vec<_>>()
+                    .try_into()
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let mut digest_iter = expected_digests.into_iter();
+        timed!(timing, "Write the execution trace", {
+            table.write_table_entries(&writer);
+            sha_gadget.write(padded_messages, &writer);
+            for i in 0..L::num_rows() {
+                writer.write_row_instructions(&generator.air_data, i);
+                let end_bit = writer.read(&sha_gadget.end_bit, i);
+                if end_bit == F::ONE {
+                    let j = i / SHA512_CYCLE_LENGTH;
+                    let hash =
+                        writer.read_array(&sha_gadget.state.get_subarray(j * 8..j * 8 + 8), 0);
+                    let digest = digest_iter.next().unwrap();
+                    assert_eq!(hash, digest.map(u64_to_le_field_bytes));
+                }
+            }
+            table.write_multiplicities(&writer);
+        });
+        assert!(digest_iter.next().is_none());
+
+        let public_inputs = writer.0.public.read().unwrap().clone();
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        timed!(
+            timing,
+            "Stark proof and verify",
+            test_starky(&stark, &config, &generator, &public_inputs)
+        );
+
+        // Generate recursive proof
+        timed!(
+            timing,
+            "Recursive proof generation and verification",
+            test_recursive_starky(stark, config, generator, &public_inputs)
+        );
+
+        timing.print();
+    }
+}
@@ -56,6 +56,13 @@ impl<L: AirParameters> AirBuilder<L> {
     }
 }
 
+impl<F> Cycle<F> {
+    /// The number of rows in one period of the cycle.
+    pub fn length(&self) -> usize {
+        self.group.len()
+    }
+}
+
 impl<AP: AirParser<Field = F>, F: Field> AirConstraint<AP> for Cycle<F> {
     fn eval(&self, parser: &mut AP) {
         // Impose first row constraints
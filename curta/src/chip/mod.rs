@@ -1,22 +1,34 @@
+#[cfg(feature = "plonky2")]
+use plonky2::iop::target::Target;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 use self::constraint::Constraint;
 use self::instruction::Instruction;
+use self::register::memory::MemorySlice;
 use crate::math::extension::cubic::parameters::CubicParameters;
 use crate::math::prelude::*;
 use crate::plonky2::stark::Starky;
 
 pub mod air;
 pub mod arithmetic;
+pub mod bignum;
 pub mod bool;
 pub mod builder;
+#[cfg(feature = "plonky2")]
+pub mod commitment;
 pub mod constraint;
+#[cfg(feature = "plonky2")]
+pub mod cosmos;
 pub mod ec;
+#[cfg(feature = "plonky2")]
+pub mod eth;
 pub mod field;
 pub mod hash;
 pub mod instruction;
 pub mod register;
+#[cfg(feature = "plonky2")]
+pub mod substrate;
 pub mod table;
 pub mod trace;
 pub mod uint;
@@ -61,6 +73,61 @@ pub trait AirParameters:
     fn id() -> String {
         format!("{:?}", std::any::TypeId::of::<Self>()).to_string()
     }
+
+    /// Validates the columns used by the builder against the declared column counts.
+    ///
+    /// This method is called by `AirBuilder::build` in debug builds only. By default, no check is
+    /// made beyond the ones of `AirBuilder::build`. Chips whose layout must match the declared
+    /// constants exactly should override it with `layout.assert_declared::<Self>()`.
+    fn validate_layout(_layout: &AirLayout) {}
+}
+
+/// The number of columns of each kind used by a chip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AirLayout {
+    pub num_arithmetic_columns: usize,
+    pub num_free_columns: usize,
+    pub num_extended_columns: usize,
+}
+
+impl AirLayout {
+    /// The layout declared by the constants of the air parameters.
+    pub fn declared<L: AirParameters>() -> Self {
+        Self {
+            num_arithmetic_columns: L::NUM_ARITHMETIC_COLUMNS,
+            num_free_columns: L::NUM_FREE_COLUMNS,
+            num_extended_columns: L::EXTENDED_COLUMNS,
+        }
+    }
+
+    /// Panics if the layout does not match the one declared by `L`.
+    pub fn assert_declared<L: AirParameters>(&self) {
+        let declared = Self::declared::<L>();
+        assert!(
+            *self == declared,
+            "Column layout mismatch for {}: the builder used {} arithmetic, {} free and {} \
+            extended columns, but the parameters declare {} arithmetic, {} free and {} extended \
+            columns",
+            core::any::type_name::<L>(),
+            self.num_arithmetic_columns,
+            self.num_free_columns,
+            self.num_extended_columns,
+            declared.num_arithmetic_columns,
+            declared.num_free_columns,
+            declared.num_extended_columns,
+        );
+    }
+}
+
+/// The resources used by a chip, as realized by `AirBuilder::build`.
+///
+/// Unlike `AirLayout::declared`, this reports the columns the builder actually allocated, which
+/// makes it possible to compare the cost of a gadget before and after a change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AirUsage {
+    pub layout: AirLayout,
+    /// The number of registers whose values are looked up, summed over all lookups.
+    pub num_lookup_values: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -72,6 +139,56 @@ pub struct Chip<L: AirParameters> {
     num_challenges: usize,
     pub num_public_values: usize,
     pub num_global_values: usize,
+    public_inputs_layout: Vec<MemorySlice>,
+}
+
+impl<L: AirParameters> Chip<L> {
+    /// The public registers allocated by the chip, in the order of their public input indices.
+    ///
+    /// This is the canonical ordering of the public inputs of the STARK, which only depends on
+    /// the order in which the registers were allocated by the builder.
+    pub fn public_inputs_layout(&self) -> &[MemorySlice] {
+        &self.public_inputs_layout
+    }
+
+    /// Assembles the public input targets of the STARK from the targets bound to each public
+    /// register, in the order expected by the verifier.
+    ///
+    /// Panics if a register is not public, if a public input is bound more than once, or if a
+    /// public input is left unbound.
+    #[cfg(feature = "plonky2")]
+    pub fn bind_public_inputs(&self, bindings: &[(MemorySlice, &[Target])]) -> Vec<Target> {
+        let mut public_inputs = vec![None; self.num_public_values];
+        for (register, targets) in bindings.iter() {
+            let (index, length) = match register {
+                MemorySlice::Public(index, length) => (*index, *length),
+                _ => panic!("Register {:?} is not public", register),
+            };
+            assert_eq!(
+                targets.len(),
+                length,
+                "Invalid number of targets for register {:?}",
+                register
+            );
+            for (input, target) in public_inputs[index..index + length]
+                .iter_mut()
+                .zip(targets.iter())
+            {
+                assert!(
+                    input.is_none(),
+                    "Public input bound twice in {:?}",
+                    register
+                );
+                *input = Some(*target);
+            }
+        }
+
+        public_inputs
+            .into_iter()
+            .enumerate()
+            .map(|(i, target)| target.unwrap_or_else(|| panic!("Public input {} is not bound", i)))
+            .collect()
+    }
 }
 
 impl<L: AirParameters> Starky<Chip<L>> {
@@ -0,0 +1,484 @@
+//! GRANDPA justifications, which finalize the blocks of Substrate/Polkadot chains.
+//!
+//! A justification of a block contains the precommits for the block of the authorities of the
+//! current set, each signed with Ed25519. An authority signs the SCALE encoding of the tuple
+//! `(Message::Precommit(precommit), round, set_id)`, i.e. the index of the precommit variant,
+//! the hash and the number of the target block, the round and the id of the authority set. The
+//! justification is valid if the signing authorities are distinct and hold more than two thirds
+//! of the total weight of the set.
+//!
+//! GRANDPA allows a precommit to target a descendant of the finalized block, with the ancestry
+//! given in the justification. The gadget only accepts precommits for the target block itself,
+//! so that all the authorities sign the same message.
+
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::Target;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+use crate::chip::ec::edwards::batch_verify::generator::{
+    Ed25519BatchEntryTarget, Ed25519BatchVerifyGadget,
+};
+use crate::chip::ec::edwards::scalar_mul::generator::AffinePointTarget;
+use crate::math::prelude::CubicParameters;
+use crate::plonky2::stark::config::CurtaConfig;
+
+/// The index of the precommit variant of the GRANDPA `Message` enum.
+pub const PRECOMMIT_MESSAGE_INDEX: u8 = 1;
+
+/// The length of the signed message of a precommit.
+pub const PRECOMMIT_MESSAGE_LEN: usize = 1 + 32 + 4 + 8 + 8;
+
+/// The number of bits of the weight of an authority.
+pub const GRANDPA_WEIGHT_BITS: usize = 32;
+
+/// The maximal number of authorities of a set, which bounds the total weight.
+pub const GRANDPA_MAX_AUTHORITIES: usize = 1 << 12;
+
+/// A vote of an authority to finalize a block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Precommit {
+    pub target_hash: [u8; 32],
+    pub target_number: u32,
+}
+
+impl Precommit {
+    /// The message signed by an authority voting for the precommit in `round` of the set `set_id`.
+    pub fn signed_message(&self, round: u64, set_id: u64) -> Vec<u8> {
+        let mut message = vec![PRECOMMIT_MESSAGE_INDEX];
+        message.extend_from_slice(&self.target_hash);
+        message.extend_from_slice(&self.target_number.to_le_bytes());
+        message.extend_from_slice(&round.to_le_bytes());
+        message.extend_from_slice(&set_id.to_le_bytes());
+        message
+    }
+}
+
+/// Returns `true` if the authorities at `indices` hold more than two thirds of `weights`.
+pub fn has_supermajority(weights: &[u64], indices: &[usize]) -> bool {
+    let total = weights.iter().map(|weight| *weight as u128).sum::<u128>();
+    let signed = indices
+        .iter()
+        .map(|index| weights[*index] as u128)
+        .sum::<u128>();
+    3 * signed > 2 * total
+}
+
+#[derive(Debug, Clone)]
+pub struct GrandpaAuthoritySetTarget {
+    /// The little endian bytes of the id of the set.
+    pub set_id: Vec<Target>,
+    pub keys: Vec<AffinePointTarget>,
+    pub weights: Vec<Target>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GrandpaJustificationTarget {
+    /// The little endian bytes of the round.
+    pub round: Vec<Target>,
+    pub target_hash: Vec<Target>,
+    /// The little endian bytes of the number of the target block.
+    pub target_number: Vec<Target>,
+    /// The indices of the signing authorities in the set, in increasing order.
+    pub authority_indices: Vec<Target>,
+    /// The signature of every signing authority, whose key is that of its index in the set.
+    pub signatures: Vec<Ed25519BatchEntryTarget>,
+}
+
+pub trait CircuitBuilderGrandpa<F: RichField + Extendable<D>, const D: usize> {
+    fn add_virtual_grandpa_authority_set(
+        &mut self,
+        num_authorities: usize,
+    ) -> GrandpaAuthoritySetTarget;
+
+    fn add_virtual_grandpa_justification(
+        &mut self,
+        num_precommits: usize,
+    ) -> GrandpaJustificationTarget;
+
+    /// Computes the message signed by the authorities of `justification`, range checking its
+    /// bytes.
+    fn grandpa_precommit_message(
+        &mut self,
+        authority_set: &GrandpaAuthoritySetTarget,
+        justification: &GrandpaJustificationTarget,
+    ) -> Vec<Target>;
+
+    /// Verifies that the signing authorities of `justification` are distinct members of
+    /// `authority_set` holding more than two thirds of its weight, and verifies their signatures
+    /// as a batch.
+    ///
+    /// As for [`Ed25519BatchVerifyGadget`], the challenge of every signature is an input, which
+    /// the caller must check to be `SHA-512(R || A || M) mod l` for the message `M` given by
+    /// [`Self::grandpa_precommit_message`].
+    fn verify_grandpa_justification<
+        E: CubicParameters<F>,
+        C: CurtaConfig<D, F = F, FE = F::Extension>,
+        const N: usize,
+    >(
+        &mut self,
+        authority_set: &GrandpaAuthoritySetTarget,
+        justification: &GrandpaJustificationTarget,
+    );
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderGrandpa<F, D>
+    for CircuitBuilder<F, D>
+{
+    fn add_virtual_grandpa_authority_set(
+        &mut self,
+        num_authorities: usize,
+    ) -> GrandpaAuthoritySetTarget {
+        assert!(
+            num_authorities > 0 && num_authorities <= GRANDPA_MAX_AUTHORITIES,
+            "Invalid number of authorities"
+        );
+        GrandpaAuthoritySetTarget {
+            set_id: self.add_virtual_targets(8),
+            keys: (0..num_authorities)
+                .map(|_| AffinePointTarget {
+                    x: self.add_virtual_target_arr(),
+                    y: self.add_virtual_target_arr(),
+                })
+                .collect(),
+            weights: self.add_virtual_targets(num_authorities),
+        }
+    }
+
+    fn add_virtual_grandpa_justification(
+        &mut self,
+        num_precommits: usize,
+    ) -> GrandpaJustificationTarget {
+        GrandpaJustificationTarget {
+            round: self.add_virtual_targets(8),
+            target_hash: self.add_virtual_targets(32),
+            target_number: self.add_virtual_targets(4),
+            authority_indices: self.add_virtual_targets(num_precommits),
+            signatures: (0..num_precommits)
+                .map(|_| self.add_virtual_ed25519_batch_entry())
+                .collect(),
+        }
+    }
+
+    fn grandpa_precommit_message(
+        &mut self,
+        authority_set: &GrandpaAuthoritySetTarget,
+        justification: &GrandpaJustificationTarget,
+    ) -> Vec<Target> {
+        assert_eq!(authority_set.set_id.len(), 8, "Invalid set id length");
+        assert_eq!(justification.round.len(), 8, "Invalid round length");
+        assert_eq!(justification.target_hash.len(), 32, "Invalid hash length");
+        assert_eq!(
+            justification.target_number.len(),
+            4,
+            "Invalid block number length"
+        );
+
+        let mut message = vec![self.constant(F::from_canonical_u8(PRECOMMIT_MESSAGE_INDEX))];
+        for byte in justification
+            .target_hash
+            .iter()
+            .chain(justification.target_number.iter())
+            .chain(justification.round.iter())
+            .chain(authority_set.set_id.iter())
+        {
+            self.range_check(*byte, 8);
+            message.push(*byte);
+        }
+        message
+    }
+
+    fn verify_grandpa_justification<
+        E: CubicParameters<F>,
+        C: CurtaConfig<D, F = F, FE = F::Extension>,
+        const N: usize,
+    >(
+        &mut self,
+        authority_set: &GrandpaAuthoritySetTarget,
+        justification: &GrandpaJustificationTarget,
+    ) {
+        assert_eq!(
+            justification.authority_indices.len(),
+            N,
+            "Expected {} precommits",
+            N
+        );
+        assert_eq!(
+            authority_set.keys.len(),
+            authority_set.weights.len(),
+            "Expected one weight per authority"
+        );
+
+        let mut total_weight = self.zero();
+        for weight in authority_set.weights.iter() {
+            self.range_check(*weight, GRANDPA_WEIGHT_BITS);
+            total_weight = self.add(total_weight, *weight);
+        }
+
+        // The indices are increasing, hence distinct.
+        for pair in justification.authority_indices.windows(2) {
+            let difference = self.sub(pair[1], pair[0]);
+            let one = self.one();
+            let gap = self.sub(difference, one);
+            self.range_check(gap, 32);
+        }
+
+        // Select the key and the weight of every signing authority, with exactly one authority
+        // matching its index.
+        let mut signed_weight = self.zero();
+        for (index, signature) in justification
+            .authority_indices
+            .iter()
+            .zip(justification.signatures.iter())
+        {
+            let mut num_matches = self.zero();
+            let mut x = [self.zero(); 16];
+            let mut y = [self.zero(); 16];
+            for (j, (key, weight)) in authority_set
+                .keys
+                .iter()
+                .zip(authority_set.weights.iter())
+                .enumerate()
+            {
+                let j_target = self.constant(F::from_canonical_usize(j));
+                let is_match = self.is_equal(*index, j_target).target;
+                num_matches = self.add(num_matches, is_match);
+                signed_weight = self.mul_add(is_match, *weight, signed_weight);
+                for (limb, key_limb) in x.iter_mut().zip(key.x.iter()) {
+                    *limb = self.mul_add(is_match, *key_limb, *limb);
+                }
+                for (limb, key_limb) in y.iter_mut().zip(key.y.iter()) {
+                    *limb = self.mul_add(is_match, *key_limb, *limb);
+                }
+            }
+            self.assert_one(num_matches);
+            for (limb, pubkey_limb) in x
+                .iter()
+                .chain(y.iter())
+                .zip(signature.pubkey.x.iter().chain(signature.pubkey.y.iter()))
+            {
+                self.connect(*limb, *pubkey_limb);
+            }
+        }
+
+        // 3 * signed_weight > 2 * total_weight, where both sides are less than 2^46.
+        let three = F::from_canonical_u32(3);
+        let two = F::from_canonical_u32(2);
+        let signed = self.mul_const(three, signed_weight);
+        let total = self.mul_const(two, total_weight);
+        let one = self.one();
+        let margin = self.sub(signed, total);
+        let margin = self.sub(margin, one);
+        self.range_check(margin, GRANDPA_WEIGHT_BITS + 14);
+
+        let randomizers = self.ed25519_batch_randomizers(&justification.signatures);
+        let is_valid =
+            self.batch_verify_ed25519::<E, C, N>(&justification.signatures, &randomizers);
+        self.assert_one(is_valid);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use num::BigUint;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::ec::edwards::ed25519::Ed25519;
+    use crate::chip::ec::edwards::EdwardsParameters;
+    use crate::chip::ec::point::AffinePoint;
+    use crate::chip::hash::sha::sha512::SHA512Gadget;
+    use crate::chip::utils::biguint_to_16_digits_field;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::math::prelude::*;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    const N: usize = 3;
+
+    const ROUND: u64 = 5071;
+    const SET_ID: u64 = 2;
+
+    fn precommit() -> Precommit {
+        Precommit {
+            target_hash: [0xab; 32],
+            target_number: 0x0102_0304,
+        }
+    }
+
+    /// The compressed encoding of a point: the little-endian bytes of `y`, with the sign of `x`
+    /// in the most significant bit.
+    fn encode_point(point: &AffinePoint<Ed25519>) -> Vec<u8> {
+        let mut bytes = point.y.to_bytes_le();
+        bytes.resize(32, 0);
+        if point.x.bit(0) {
+            bytes[31] |= 0x80;
+        }
+        bytes
+    }
+
+    fn set_u32_limbs(
+        pw: &mut PartialWitness<GoldilocksField>,
+        targets: &[Target],
+        value: &BigUint,
+    ) {
+        let mut limbs = value.to_u32_digits();
+        limbs.resize(targets.len(), 0);
+        for (target, limb) in targets.iter().zip(limbs) {
+            pw.set_target(*target, GoldilocksField::from_canonical_u32(limb));
+        }
+    }
+
+    fn set_point(
+        pw: &mut PartialWitness<GoldilocksField>,
+        target: &AffinePointTarget,
+        point: &AffinePoint<Ed25519>,
+    ) {
+        let x: [_; 16] = biguint_to_16_digits_field(&point.x, 16).try_into().unwrap();
+        let y: [_; 16] = biguint_to_16_digits_field(&point.y, 16).try_into().unwrap();
+        pw.set_target_arr(&target.x, &x);
+        pw.set_target_arr(&target.y, &y);
+    }
+
+    fn set_bytes(pw: &mut PartialWitness<GoldilocksField>, targets: &[Target], bytes: &[u8]) {
+        for (target, byte) in targets.iter().zip(bytes) {
+            pw.set_target(*target, GoldilocksField::from_canonical_u8(*byte));
+        }
+    }
+
+    /// Proves a justification of the precommit signed by the authorities at `indices` of a set
+    /// with the given weights.
+    fn prove_justification(weights: &[u64], indices: [usize; N]) {
+        type F = GoldilocksField;
+        type E = GoldilocksCubicParameters;
+        type SC = CurtaPoseidonGoldilocksConfig;
+        type C = PoseidonGoldilocksConfig;
+        const D: usize = 2;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let authority_set = builder.add_virtual_grandpa_authority_set(weights.len());
+        let justification = builder.add_virtual_grandpa_justification(N);
+        let message_target = builder.grandpa_precommit_message(&authority_set, &justification);
+        builder.verify_grandpa_justification::<E, SC, N>(&authority_set, &justification);
+
+        // The message of the circuit is the signed message.
+        let message = precommit().signed_message(ROUND, SET_ID);
+        assert_eq!(message_target.len(), PRECOMMIT_MESSAGE_LEN);
+        for (target, byte) in message_target.iter().zip(message.iter()) {
+            let expected = builder.constant(F::from_canonical_u8(*byte));
+            builder.connect(*target, expected);
+        }
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+
+        let mut rng = thread_rng();
+        let base = Ed25519::generator();
+        let order = Ed25519::prime_group_order();
+        let secrets = weights
+            .iter()
+            .map(|_| rng.gen_biguint_below(&order))
+            .collect::<Vec<_>>();
+        let keys = secrets
+            .iter()
+            .map(|secret| &base * secret)
+            .collect::<Vec<_>>();
+
+        set_bytes(&mut pw, &authority_set.set_id, &SET_ID.to_le_bytes());
+        for ((key_target, weight_target), (key, weight)) in authority_set
+            .keys
+            .iter()
+            .zip(authority_set.weights.iter())
+            .zip(keys.iter().zip(weights.iter()))
+        {
+            set_point(&mut pw, key_target, key);
+            pw.set_target(*weight_target, F::from_canonical_u64(*weight));
+        }
+
+        set_bytes(&mut pw, &justification.round, &ROUND.to_le_bytes());
+        set_bytes(
+            &mut pw,
+            &justification.target_hash,
+            &precommit().target_hash,
+        );
+        set_bytes(
+            &mut pw,
+            &justification.target_number,
+            &precommit().target_number.to_le_bytes(),
+        );
+        for ((index_target, entry), index) in justification
+            .authority_indices
+            .iter()
+            .zip(justification.signatures.iter())
+            .zip(indices)
+        {
+            let nonce = rng.gen_biguint_below(&order);
+            let sig_r = &base * &nonce;
+            let digest = SHA512Gadget::hash(
+                &[
+                    encode_point(&sig_r),
+                    encode_point(&keys[index]),
+                    message.clone(),
+                ]
+                .concat(),
+            );
+            let challenge = BigUint::from_bytes_le(&digest) % &order;
+            let sig_s = (&nonce + &challenge * &secrets[index]) % &order;
+
+            pw.set_target(*index_target, F::from_canonical_usize(index));
+            set_point(&mut pw, &entry.pubkey, &keys[index]);
+            set_point(&mut pw, &entry.sig_r, &sig_r);
+            set_u32_limbs(&mut pw, &entry.sig_s, &sig_s);
+            set_u32_limbs(&mut pw, &entry.challenge, &challenge);
+        }
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+
+    #[test]
+    fn test_precommit_signed_message() {
+        let message = precommit().signed_message(ROUND, SET_ID);
+        assert_eq!(message.len(), PRECOMMIT_MESSAGE_LEN);
+        assert_eq!(
+            hex::encode(message),
+            [
+                "01",
+                &"ab".repeat(32),
+                "04030201",
+                "cf13000000000000",
+                "0200000000000000",
+            ]
+            .concat()
+        );
+
+        assert!(has_supermajority(&[1, 1, 1, 1], &[0, 2, 3]));
+        assert!(!has_supermajority(&[1, 1, 1, 1], &[0, 2]));
+        assert!(!has_supermajority(&[1, 1, 1, 3], &[0, 1, 2]));
+    }
+
+    #[test]
+    fn test_grandpa_justification() {
+        let weights = [1, 1, 1, 1];
+        let indices = [0, 2, 3];
+        assert!(has_supermajority(&weights, &indices));
+        prove_justification(&weights, indices);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_grandpa_justification_insufficient_weight() {
+        let weights = [1, 1, 1, 3];
+        let indices = [0, 1, 2];
+        assert!(!has_supermajority(&weights, &indices));
+        prove_justification(&weights, indices);
+    }
+}
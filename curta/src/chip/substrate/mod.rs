@@ -0,0 +1,3 @@
+//! Gadgets for Substrate light clients.
+
+pub mod grandpa;
@@ -58,6 +58,14 @@ pub struct LogLookup<T: EvalCubic, F: Field, E: CubicParameters<F>> {
     _marker: core::marker::PhantomData<(F, E)>,
 }
 
+impl<T: EvalCubic, F: Field, E: CubicParameters<F>> LogLookup<T, F, E> {
+    /// The number of registers whose values are looked up, in the trace and among the public
+    /// values.
+    pub fn num_values(&self) -> usize {
+        self.values_data.trace_values.len() + self.values_data.public_values.len()
+    }
+}
+
 // LogLookUp Memory allocation
 impl<L: AirParameters> AirBuilder<L> {
     pub fn lookup_table<T: EvalCubic>(
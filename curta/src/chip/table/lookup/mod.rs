@@ -22,6 +22,17 @@ pub enum Lookup<F: Field, E: CubicParameters<F>> {
     CubicElement(LogLookup<CubicRegister, F, E>),
 }
 
+impl<F: Field, E: CubicParameters<F>> Lookup<F, E> {
+    /// The number of registers whose values are looked up, in the trace and among the public
+    /// values.
+    pub fn num_values(&self) -> usize {
+        match self {
+            Lookup::Element(log) => log.num_values(),
+            Lookup::CubicElement(log) => log.num_values(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(bound = "")]
 pub enum LookupChipConstraint<F: Field, E: CubicParameters<F>> {
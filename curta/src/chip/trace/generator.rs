@@ -5,6 +5,7 @@ use serde::{Deserialize, Serialize};
 
 use super::writer::TraceWriter;
 use crate::chip::builder::AirTraceData;
+use crate::chip::instruction::set::AirInstruction;
 use crate::chip::register::element::ElementRegister;
 use crate::chip::table::lookup::Lookup;
 use crate::chip::{AirParameters, Chip};
@@ -52,6 +53,49 @@ impl<L: AirParameters> ArithmeticGenerator<L> {
     pub fn strong_count(&self) -> usize {
         Arc::strong_count(&self.writer.0)
     }
+
+    /// Writes the row instructions for all rows of the trace, splitting the rows into chunks of
+    /// `chunk_size` consecutive rows. When the `parallel` feature is enabled, the chunks are
+    /// written in parallel, otherwise they are written one after the other.
+    ///
+    /// Rows within a chunk are written in order, but no ordering is guaranteed between chunks, so
+    /// the instructions of a chunk must not read values written by the instructions of another
+    /// chunk. The chunk size must be a multiple of the length of every cycle of the chip, so that
+    /// no cycle period is split between two chunks. Each chunk counts its byte lookup
+    /// multiplicities in a buffer of its own, and the buffers are folded into the shared counters
+    /// of `MultiplicityData` once all chunks are done.
+    pub fn write_row_instructions_par(&self, chunk_size: usize) {
+        let num_rows = L::num_rows();
+        assert!(chunk_size > 0, "Chunk size must be positive");
+        assert_eq!(
+            num_rows % chunk_size,
+            0,
+            "Chunk size {chunk_size} must divide the number of rows {num_rows}"
+        );
+        for instruction in self.air_data.instructions.iter() {
+            if let AirInstruction::Cycle(cycle) = instruction {
+                assert_eq!(
+                    chunk_size % cycle.length(),
+                    0,
+                    "Chunk size {chunk_size} must be a multiple of the cycle length {}",
+                    cycle.length()
+                );
+            }
+        }
+        let buffers = (0..num_rows / chunk_size)
+            .into_par_iter()
+            .map(|chunk_index| {
+                let writer = self.writer.with_multiplicity_buffer();
+                for i in chunk_index * chunk_size..(chunk_index + 1) * chunk_size {
+                    writer.write_row_instructions(&self.air_data, i);
+                }
+                writer.take_multiplicity_buffer().unwrap()
+            })
+            .collect::<Vec<_>>();
+        for buffer in buffers {
+            buffer.fold();
+        }
+    }
 }
 
 impl<L: AirParameters> TraceGenerator<L::Field, Chip<L>> for ArithmeticGenerator<L> {
@@ -152,3 +196,122 @@ impl<L: AirParameters> TraceGenerator<L::Field, Chip<L>> for ArithmeticGenerator
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::{thread_rng, Rng};
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::builder::AirBuilder;
+    use crate::chip::register::Register;
+    use crate::chip::uint::bytes::lookup_table::table::ByteLookupTable;
+    use crate::chip::uint::bytes::lookup_table::ByteInstructionSet;
+    use crate::chip::uint::bytes::operations::value::ByteOperation;
+    use crate::chip::uint::bytes::register::ByteRegister;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct ParallelWriteTest;
+
+    impl AirParameters for ParallelWriteTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = ByteInstructionSet;
+
+        const NUM_FREE_COLUMNS: usize = 141;
+        const EXTENDED_COLUMNS: usize = 132;
+        const NUM_ARITHMETIC_COLUMNS: usize = 0;
+
+        fn num_rows_bits() -> usize {
+            16
+        }
+    }
+
+    type Inputs = Vec<(ByteRegister, ByteRegister)>;
+
+    fn byte_ops_chip(
+        num_pairs: usize,
+        cycle_length_log: usize,
+    ) -> (
+        ArithmeticGenerator<ParallelWriteTest>,
+        ByteLookupTable,
+        Inputs,
+    ) {
+        let mut builder = AirBuilder::<ParallelWriteTest>::new();
+        let (mut operations, table) = builder.byte_operations();
+
+        let mut inputs = Vec::new();
+        for _ in 0..num_pairs {
+            let a = builder.alloc::<ByteRegister>();
+            let b = builder.alloc::<ByteRegister>();
+            let a_and_b = builder.alloc::<ByteRegister>();
+            builder.set_byte_operation(&ByteOperation::And(a, b, a_and_b), &mut operations);
+            let a_xor_b = builder.alloc::<ByteRegister>();
+            builder.set_byte_operation(&ByteOperation::Xor(a, b, a_xor_b), &mut operations);
+            inputs.push((a, b));
+        }
+        builder.register_byte_lookup(operations, &table);
+        builder.cycle(cycle_length_log);
+
+        let (_, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::new(trace_data);
+        (generator, table, inputs)
+    }
+
+    #[test]
+    fn test_parallel_row_instructions() {
+        type F = GoldilocksField;
+        type L = ParallelWriteTest;
+
+        let (serial_generator, serial_table, serial_inputs) = byte_ops_chip(2, 4);
+        let (generator, table, inputs) = byte_ops_chip(2, 4);
+
+        let mut rng = thread_rng();
+        let values = (0..L::num_rows())
+            .map(|_| {
+                (0..inputs.len())
+                    .map(|_| (rng.gen::<u8>(), rng.gen::<u8>()))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let write_inputs = |writer: &TraceWriter<F>, inputs: &Inputs| {
+            for (i, row_values) in values.iter().enumerate() {
+                for ((a, b), (a_v, b_v)) in inputs.iter().zip(row_values.iter()) {
+                    writer.write(a, &F::from_canonical_u8(*a_v), i);
+                    writer.write(b, &F::from_canonical_u8(*b_v), i);
+                }
+            }
+        };
+
+        // Write the trace one row at a time.
+        let writer = serial_generator.new_writer();
+        serial_table.write_table_entries(&writer);
+        write_inputs(&writer, &serial_inputs);
+        for i in 0..L::num_rows() {
+            writer.write_row_instructions(&serial_generator.air_data, i);
+        }
+        serial_table.write_multiplicities(&writer);
+
+        // Write the trace in parallel chunks.
+        let writer = generator.new_writer();
+        table.write_table_entries(&writer);
+        write_inputs(&writer, &inputs);
+        generator.write_row_instructions_par(1 << 12);
+        table.write_multiplicities(&writer);
+
+        assert_eq!(
+            serial_generator.trace_clone().values,
+            generator.trace_clone().values
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "must be a multiple of the cycle length")]
+    fn test_parallel_row_instructions_split_cycle() {
+        let (generator, _, _) = byte_ops_chip(1, 4);
+        generator.write_row_instructions_par(1 << 3);
+    }
+}
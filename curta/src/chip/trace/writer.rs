@@ -1,7 +1,7 @@
 use alloc::sync::Arc;
 use core::borrow::Borrow;
 use core::ops::Deref;
-use std::sync::{LockResult, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::sync::{LockResult, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 use serde::{Deserialize, Serialize};
 
@@ -11,6 +11,10 @@ use crate::chip::instruction::Instruction;
 use crate::chip::register::array::ArrayRegister;
 use crate::chip::register::memory::MemorySlice;
 use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::uint::bytes::lookup_table::multiplicity_data::{
+    MultiplicityBuffer, MultiplicityData,
+};
+use crate::chip::uint::bytes::operations::value::ByteOperation;
 use crate::chip::AirParameters;
 use crate::math::prelude::*;
 use crate::trace::window::TraceWindow;
@@ -27,19 +31,25 @@ pub struct WriterData<T> {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TraceWriter<T>(pub Arc<WriterData<T>>);
+pub struct TraceWriter<T>(
+    pub Arc<WriterData<T>>,
+    #[serde(skip)] Option<Arc<Mutex<MultiplicityBuffer>>>,
+);
 
 impl<T> TraceWriter<T> {
     #[inline]
     pub fn new(width: usize, num_rows: usize) -> Self {
         let height = num_rows;
-        Self(Arc::new(WriterData {
-            trace: RwLock::new(AirTrace::new_with_capacity(width, num_rows)),
-            global: RwLock::new(Vec::new()),
-            challenges: RwLock::new(Vec::new()),
-            public: RwLock::new(Vec::new()),
-            height,
-        }))
+        Self(
+            Arc::new(WriterData {
+                trace: RwLock::new(AirTrace::new_with_capacity(width, num_rows)),
+                global: RwLock::new(Vec::new()),
+                challenges: RwLock::new(Vec::new()),
+                public: RwLock::new(Vec::new()),
+                height,
+            }),
+            None,
+        )
     }
 
     #[inline]
@@ -54,13 +64,16 @@ impl<T> TraceWriter<T> {
         T: Copy,
     {
         let height = num_rows;
-        Self(Arc::new(WriterData {
-            trace: RwLock::new(AirTrace::new_with_value(width, num_rows, value)),
-            global: RwLock::new(vec![value; num_global_values]),
-            public: RwLock::new(vec![value; num_public_inputs]),
-            challenges: RwLock::new(Vec::new()),
-            height,
-        }))
+        Self(
+            Arc::new(WriterData {
+                trace: RwLock::new(AirTrace::new_with_value(width, num_rows, value)),
+                global: RwLock::new(vec![value; num_global_values]),
+                public: RwLock::new(vec![value; num_public_inputs]),
+                challenges: RwLock::new(Vec::new()),
+                height,
+            }),
+            None,
+        )
     }
 
     #[inline]
@@ -68,6 +81,32 @@ impl<T> TraceWriter<T> {
         self.height
     }
 
+    /// Returns a writer to the same trace which counts byte lookup multiplicities in a buffer of
+    /// its own instead of the shared counters. The counts are handed back by
+    /// `take_multiplicity_buffer`.
+    pub fn with_multiplicity_buffer(&self) -> Self {
+        Self(
+            self.0.clone(),
+            Some(Arc::new(Mutex::new(MultiplicityBuffer::new()))),
+        )
+    }
+
+    /// Takes the multiplicities counted by this writer so far, if it has a buffer.
+    pub fn take_multiplicity_buffer(&self) -> Option<MultiplicityBuffer> {
+        self.1
+            .as_ref()
+            .map(|buffer| core::mem::take(&mut *buffer.lock().unwrap()))
+    }
+
+    /// Counts a byte lookup of `operation` in the buffer of the writer if it has one, or in the
+    /// shared counters of `data` otherwise.
+    pub fn update_multiplicity(&self, data: &Arc<MultiplicityData>, operation: &ByteOperation<u8>) {
+        match &self.1 {
+            Some(buffer) => buffer.lock().unwrap().update(data, operation),
+            None => data.update(operation),
+        }
+    }
+
     pub fn read_trace(&self) -> LockResult<RwLockReadGuard<'_, AirTrace<T>>>
     where
         T: Clone,
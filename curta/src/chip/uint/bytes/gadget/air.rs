@@ -5,7 +5,7 @@ use plonky2::hash::hash_types::RichField;
 use serde::{Deserialize, Serialize};
 
 use crate::chip::uint::bytes::lookup_table::ByteInstructionSet;
-use crate::chip::AirParameters;
+use crate::chip::{AirLayout, AirParameters};
 use crate::math::prelude::*;
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -25,4 +25,8 @@ impl<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize> AirPar
     fn num_rows_bits() -> usize {
         16
     }
+
+    fn validate_layout(layout: &AirLayout) {
+        layout.assert_declared::<Self>();
+    }
 }
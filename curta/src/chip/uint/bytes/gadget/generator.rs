@@ -5,6 +5,7 @@ use plonky2::iop::target::Target;
 use plonky2::iop::witness::{PartitionWitness, Witness, WitnessWrite};
 use plonky2::plonk::circuit_data::CommonCircuitData;
 use plonky2::util::serialization::{Buffer, IoResult};
+use serde::{Deserialize, Serialize};
 
 use super::air::ByteGadgetParameters;
 use crate::chip::trace::generator::ArithmeticGenerator;
@@ -13,9 +14,11 @@ use crate::chip::uint::bytes::lookup_table::table::ByteLookupTable;
 use crate::chip::uint::bytes::operations::value::ByteOperation;
 use crate::chip::uint::bytes::register::ByteRegister;
 use crate::math::prelude::*;
+use crate::utils::serde::{BufferRead, BufferWrite};
 
 // A generator for the byte lookup STARK
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
 pub struct BytesLookupGenerator<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize>
 {
     operations: Vec<ByteOperation<Target>>,
@@ -44,6 +47,10 @@ impl<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize>
         }
     }
 
+    pub fn id() -> String {
+        "byte operation lookup".to_string()
+    }
+
     pub fn hint(
         &self,
         witness: &PartitionWitness<F>,
@@ -181,7 +188,7 @@ impl<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize> Simple
     for BytesLookupGenerator<F, E, D>
 {
     fn id(&self) -> String {
-        "byte operation lookup".to_string()
+        Self::id()
     }
 
     fn dependencies(&self) -> Vec<Target> {
@@ -205,18 +212,61 @@ impl<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize> Simple
         self.table.write_multiplicities(&writer);
     }
 
-    fn serialize(
-        &self,
-        _dst: &mut Vec<u8>,
-        _common_data: &CommonCircuitData<F, D>,
-    ) -> IoResult<()> {
-        unimplemented!("ByteOperation::serialize")
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        let data = bincode::serialize(self).unwrap();
+        dst.write_bytes(&data)
+    }
+
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self>
+    where
+        Self: Sized,
+    {
+        let bytes = src.read_bytes()?;
+        let data = bincode::deserialize(&bytes).unwrap();
+        Ok(data)
+    }
+}
+
+/// Splits a 16-bit value into its low and high bytes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ByteSplitGenerator {
+    pub value: Target,
+    pub low: Target,
+    pub high: Target,
+}
+
+impl ByteSplitGenerator {
+    pub fn id() -> String {
+        "ByteSplitGenerator".to_string()
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D> for ByteSplitGenerator {
+    fn id(&self) -> String {
+        Self::id()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        vec![self.value]
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let value = witness.get_target(self.value).as_canonical_u64();
+        out_buffer.set_target(self.low, F::from_canonical_u64(value & 0xff));
+        out_buffer.set_target(self.high, F::from_canonical_u64(value >> 8));
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        let data = bincode::serialize(self).unwrap();
+        dst.write_bytes(&data)
     }
 
-    fn deserialize(_src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self>
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self>
     where
         Self: Sized,
     {
-        unimplemented!("ByteOperation::deserialize")
+        let bytes = src.read_bytes()?;
+        let data = bincode::deserialize(&bytes).unwrap();
+        Ok(data)
     }
 }
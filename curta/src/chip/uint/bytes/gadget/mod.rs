@@ -8,7 +8,7 @@ use plonky2::iop::target::Target;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 
 use self::air::ByteGadgetParameters;
-use self::generator::BytesLookupGenerator;
+use self::generator::{ByteSplitGenerator, BytesLookupGenerator};
 use crate::chip::builder::AirBuilder;
 use crate::chip::trace::generator::ArithmeticGenerator;
 use crate::chip::uint::bytes::lookup_table::builder_operations::ByteLookupOperations;
@@ -43,6 +43,18 @@ pub trait CircuitBuilderBytes<F: RichField + Extendable<D>, E: CubicParameters<F
         b: ByteTarget,
         gadget: &mut BytesGadget<F, E, D>,
     ) -> ByteTarget;
+
+    /// Computes the bytewise XOR of two vectors of bytes of the same length.
+    ///
+    /// The inputs are arbitrary targets, so each of them is range checked to 8 bits by its own
+    /// lookup, in addition to the lookup of the XOR.
+    fn xor_byte_slices(
+        &mut self,
+        a: &[Target],
+        b: &[Target],
+        gadget: &mut BytesGadget<F, E, D>,
+    ) -> Vec<Target>;
+
     fn not_bytes(&mut self, a: ByteTarget, gadget: &mut BytesGadget<F, E, D>) -> ByteTarget;
     fn shr_bytes(
         &mut self,
@@ -51,6 +63,46 @@ pub trait CircuitBuilderBytes<F: RichField + Extendable<D>, E: CubicParameters<F
         gadget: &mut BytesGadget<F, E, D>,
     ) -> ByteTarget;
 
+    /// Computes `a >> shift` together with the `shift` low bits of `a` shifted out.
+    fn shr_carry_bytes(
+        &mut self,
+        a: ByteTarget,
+        shift: u8,
+        gadget: &mut BytesGadget<F, E, D>,
+    ) -> (ByteTarget, ByteTarget);
+
+    /// Computes the logical left shift by `shift` bits of the little endian bytes `a`.
+    ///
+    /// The bits shifted past the last byte are dropped and the vacated bits are set to zero. The
+    /// result has the same number of bytes as `a`.
+    fn shift_left_bytes(
+        &mut self,
+        a: &[Target],
+        shift: usize,
+        gadget: &mut BytesGadget<F, E, D>,
+    ) -> Vec<Target>;
+
+    /// Computes the logical right shift by `shift` bits of the little endian bytes `a`.
+    ///
+    /// The bits shifted past the first byte are dropped and the vacated bits are set to zero. The
+    /// result has the same number of bytes as `a`.
+    fn shift_right_bytes(
+        &mut self,
+        a: &[Target],
+        shift: usize,
+        gadget: &mut BytesGadget<F, E, D>,
+    ) -> Vec<Target>;
+
+    /// Asserts that `x` is less than `2^bits`.
+    ///
+    /// Ranges of at most 16 bits are checked by lookups into the byte table, splitting `x` into
+    /// two bytes if it has more than 8 bits. Wider ranges fall back to a bit decomposition.
+    ///
+    /// This is not claimed to be cheaper than `range_check`. Every lookup is a public byte
+    /// operation of the STARK of `gadget`, which the recursive verifier processes in the circuit,
+    /// while `range_check` takes a single base-sum gate for these widths.
+    fn range_check_lookup(&mut self, x: Target, bits: usize, gadget: &mut BytesGadget<F, E, D>);
+
     fn register_byte_operations<C: CurtaConfig<D, F = F, FE = F::Extension>>(
         &mut self,
         gadget: BytesGadget<F, E, D>,
@@ -139,6 +191,24 @@ impl<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize>
         ByteTarget(result)
     }
 
+    fn xor_byte_slices(
+        &mut self,
+        a: &[Target],
+        b: &[Target],
+        gadget: &mut BytesGadget<F, E, D>,
+    ) -> Vec<Target> {
+        assert_eq!(a.len(), b.len(), "Mismatched number of bytes");
+        a.iter()
+            .zip(b.iter())
+            .map(|(a_byte, b_byte)| {
+                self.set_byte_operation(ByteOperation::Range(*a_byte), gadget);
+                self.set_byte_operation(ByteOperation::Range(*b_byte), gadget);
+                self.xor_bytes(ByteTarget(*a_byte), ByteTarget(*b_byte), gadget)
+                    .0
+            })
+            .collect()
+    }
+
     fn not_bytes(&mut self, a: ByteTarget, gadget: &mut BytesGadget<F, E, D>) -> ByteTarget {
         let result = self.add_virtual_target();
         let not_op = ByteOperation::Not(a.0, result);
@@ -158,6 +228,109 @@ impl<F: RichField + Extendable<D>, E: CubicParameters<F>, const D: usize>
         ByteTarget(result)
     }
 
+    fn shr_carry_bytes(
+        &mut self,
+        a: ByteTarget,
+        shift: u8,
+        gadget: &mut BytesGadget<F, E, D>,
+    ) -> (ByteTarget, ByteTarget) {
+        let result = self.add_virtual_target();
+        let carry = self.add_virtual_target();
+        let shr_carry_op = ByteOperation::ShrCarry(a.0, shift, result, carry);
+        self.set_byte_operation(shr_carry_op, gadget);
+        (ByteTarget(result), ByteTarget(carry))
+    }
+
+    fn shift_left_bytes(
+        &mut self,
+        a: &[Target],
+        shift: usize,
+        gadget: &mut BytesGadget<F, E, D>,
+    ) -> Vec<Target> {
+        let n = a.len();
+        let byte_shift = shift / 8;
+        let bit_shift = shift % 8;
+
+        let zero = self.zero();
+        let mut result = vec![zero; n];
+        if byte_shift >= n {
+            return result;
+        }
+
+        // Each byte is split into its `8 - bit_shift` low bits, which move up by `bit_shift` bits
+        // within the byte, and its `bit_shift` high bits, which carry into the next byte.
+        let mult = F::from_canonical_u32(1 << bit_shift);
+        let mut high_bits = zero;
+        for i in 0..(n - byte_shift) {
+            let (high, low) = if bit_shift == 0 {
+                let (byte, _) = self.shr_carry_bytes(ByteTarget(a[i]), 0, gadget);
+                (zero, byte.0)
+            } else {
+                let (high, low) =
+                    self.shr_carry_bytes(ByteTarget(a[i]), (8 - bit_shift) as u8, gadget);
+                (high.0, low.0)
+            };
+            result[i + byte_shift] = self.mul_const_add(mult, low, high_bits);
+            high_bits = high;
+        }
+        result
+    }
+
+    fn shift_right_bytes(
+        &mut self,
+        a: &[Target],
+        shift: usize,
+        gadget: &mut BytesGadget<F, E, D>,
+    ) -> Vec<Target> {
+        let n = a.len();
+        let byte_shift = shift / 8;
+        let bit_shift = shift % 8;
+
+        let zero = self.zero();
+        let mut result = vec![zero; n];
+        if byte_shift >= n {
+            return result;
+        }
+
+        // Each byte is split into its high bits, which move down by `bit_shift` bits within the
+        // byte, and its `bit_shift` low bits, which carry into the previous byte.
+        let mult = F::from_canonical_u32(1 << (8 - bit_shift));
+        let mut carry = zero;
+        for i in (0..(n - byte_shift)).rev() {
+            let (shifted, next_carry) =
+                self.shr_carry_bytes(ByteTarget(a[i + byte_shift]), bit_shift as u8, gadget);
+            result[i] = self.mul_const_add(mult, carry, shifted.0);
+            carry = next_carry.0;
+        }
+        result
+    }
+
+    fn range_check_lookup(&mut self, x: Target, bits: usize, gadget: &mut BytesGadget<F, E, D>) {
+        match bits {
+            0 => self.assert_zero(x),
+            1..=7 => {
+                // The lookup of the shift asserts that `x` is a byte.
+                let high_bits = self.shr_bytes(ByteTarget(x), bits as u8, gadget);
+                self.assert_zero(high_bits.0);
+            }
+            8 => self.set_byte_operation(ByteOperation::Range(x), gadget),
+            9..=16 => {
+                let low = self.add_virtual_target();
+                let high = self.add_virtual_target();
+                self.add_simple_generator(ByteSplitGenerator {
+                    value: x,
+                    low,
+                    high,
+                });
+                self.set_byte_operation(ByteOperation::Range(low), gadget);
+                self.range_check_lookup(high, bits - 8, gadget);
+                let value = self.mul_const_add(F::from_canonical_u32(1 << 8), high, low);
+                self.connect(value, x);
+            }
+            _ => self.range_check(x, bits),
+        }
+    }
+
     fn register_byte_operations<C>(&mut self, gadget: BytesGadget<F, E, D>)
     where
         C: CurtaConfig<D, F = F, FE = F::Extension>,
@@ -304,6 +477,149 @@ mod tests {
         data.verify(recursive_proof).unwrap();
     }
 
+    #[test]
+    fn test_xor_byte_slices() {
+        type F = GoldilocksField;
+        type E = GoldilocksCubicParameters;
+        type SC = CurtaPoseidonGoldilocksConfig;
+        type C = PoseidonGoldilocksConfig;
+        const D: usize = 2;
+
+        const NUM_BYTES: usize = 16;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let mut gadget = BytesGadget::<F, E, D>::new();
+
+        let a = builder.add_virtual_targets(NUM_BYTES);
+        let b = builder.add_virtual_targets(NUM_BYTES);
+        let a_xor_b = builder.xor_byte_slices(&a, &b, &mut gadget);
+        let expected = builder.add_virtual_targets(NUM_BYTES);
+        for (result, expected) in a_xor_b.iter().zip(expected.iter()) {
+            builder.connect(*result, *expected);
+        }
+
+        builder.register_byte_operations::<SC>(gadget);
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+
+        let mut rng = thread_rng();
+        let a_val = (0..NUM_BYTES).map(|_| rng.gen::<u8>()).collect::<Vec<_>>();
+        let b_val = (0..NUM_BYTES).map(|_| rng.gen::<u8>()).collect::<Vec<_>>();
+        for i in 0..NUM_BYTES {
+            pw.set_target(a[i], F::from_canonical_u8(a_val[i]));
+            pw.set_target(b[i], F::from_canonical_u8(b_val[i]));
+            pw.set_target(expected[i], F::from_canonical_u8(a_val[i] ^ b_val[i]));
+        }
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+
+    #[test]
+    fn test_shift_bytes() {
+        type F = GoldilocksField;
+        type E = GoldilocksCubicParameters;
+        type SC = CurtaPoseidonGoldilocksConfig;
+        type C = PoseidonGoldilocksConfig;
+        const D: usize = 2;
+
+        const SHIFTS: [usize; 2] = [3, 11];
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let mut gadget = BytesGadget::<F, E, D>::new();
+
+        let a = builder.add_virtual_targets(4);
+        let mut expected = Vec::new();
+        for shift in SHIFTS {
+            let left = builder.shift_left_bytes(&a, shift, &mut gadget);
+            let right = builder.shift_right_bytes(&a, shift, &mut gadget);
+            let left_expected = builder.add_virtual_targets(4);
+            let right_expected = builder.add_virtual_targets(4);
+            for i in 0..4 {
+                builder.connect(left[i], left_expected[i]);
+                builder.connect(right[i], right_expected[i]);
+            }
+            expected.push((shift, left_expected, right_expected));
+        }
+
+        builder.register_byte_operations::<SC>(gadget);
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+
+        let a_val = 0xdead_beef_u32;
+        for (target, byte) in a.iter().zip(a_val.to_le_bytes()) {
+            pw.set_target(*target, F::from_canonical_u8(byte));
+        }
+        for (shift, left, right) in expected {
+            let left_val = (a_val << shift).to_le_bytes();
+            let right_val = (a_val >> shift).to_le_bytes();
+            for i in 0..4 {
+                pw.set_target(left[i], F::from_canonical_u8(left_val[i]));
+                pw.set_target(right[i], F::from_canonical_u8(right_val[i]));
+            }
+        }
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+
+    /// Range checks each value of `values` to its number of bits with lookups.
+    fn prove_range_check_lookup(values: &[(u32, usize)]) {
+        type F = GoldilocksField;
+        type E = GoldilocksCubicParameters;
+        type SC = CurtaPoseidonGoldilocksConfig;
+        type C = PoseidonGoldilocksConfig;
+        const D: usize = 2;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let mut gadget = BytesGadget::<F, E, D>::new();
+
+        let targets = builder.add_virtual_targets(values.len());
+        for (target, (_, bits)) in targets.iter().zip(values) {
+            builder.range_check_lookup(*target, *bits, &mut gadget);
+        }
+
+        builder.register_byte_operations::<SC>(gadget);
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+        for (target, (value, _)) in targets.iter().zip(values) {
+            pw.set_target(*target, F::from_canonical_u32(*value));
+        }
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+
+    #[test]
+    fn test_range_check_lookup() {
+        let mut rng = thread_rng();
+        let mut values = vec![
+            (0, 0),
+            (15, 4),
+            (255, 8),
+            (4095, 12),
+            (65535, 16),
+            (1 << 19, 20),
+        ];
+        for _ in 0..1000 {
+            values.push((rng.gen::<u8>() as u32, 8));
+            values.push((rng.gen::<u16>() as u32 >> 5, 11));
+        }
+        prove_range_check_lookup(&values);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_range_check_lookup_out_of_range() {
+        prove_range_check_lookup(&[(100, 8), (1 << 12, 12)]);
+    }
+
     #[test]
     fn test_bit_equivalent() {
         type F = GoldilocksField;
@@ -16,7 +16,7 @@ use crate::chip::register::bit::BitRegister;
 use crate::chip::register::cubic::CubicRegister;
 use crate::chip::register::memory::MemorySlice;
 use crate::chip::trace::writer::TraceWriter;
-use crate::chip::uint::bytes::operations::NUM_CHALLENGES;
+use crate::chip::uint::bytes::operations::{NUM_CHALLENGES, OPCODE_INDICES};
 use crate::chip::AirParameters;
 
 pub mod builder_operations;
@@ -47,6 +47,23 @@ impl ByteInstructions for ByteInstructionSet {}
 
 impl<L: AirParameters> AirBuilder<L> {
     pub fn byte_operations(&mut self) -> (ByteLookupOperations, ByteLookupTable)
+    where
+        L::Instruction: From<ByteInstructionSet>
+            + From<SelectInstruction<BitRegister>>
+            + From<ByteDecodeInstruction>,
+    {
+        self.byte_operations_with_opcodes(&OPCODE_INDICES)
+    }
+
+    /// Allocates byte operations whose lookup argument only covers the operations of `opcodes`.
+    ///
+    /// Each opcode left out saves a digest and a multiplicity column of the table, at the cost of
+    /// declaring upfront which operations the chip uses. Registering an operation whose opcode is
+    /// not in `opcodes` panics.
+    pub fn byte_operations_with_opcodes(
+        &mut self,
+        opcodes: &[u32],
+    ) -> (ByteLookupOperations, ByteLookupTable)
     where
         L::Instruction: From<ByteInstructionSet>
             + From<SelectInstruction<BitRegister>>
@@ -54,7 +71,7 @@ impl<L: AirParameters> AirBuilder<L> {
     {
         let row_acc_challenges = self.alloc_challenge_array::<CubicRegister>(NUM_CHALLENGES);
 
-        let lookup_table = self.new_byte_lookup_table(row_acc_challenges);
+        let lookup_table = self.new_byte_lookup_table(row_acc_challenges, opcodes);
         let operations =
             ByteLookupOperations::new(lookup_table.multiplicity_data.clone(), row_acc_challenges);
 
@@ -173,6 +190,7 @@ mod tests {
     use crate::chip::builder::AirBuilder;
     use crate::chip::register::Register;
     use crate::chip::uint::bytes::operations::value::ByteOperation;
+    use crate::chip::uint::bytes::operations::OPCODE_RANGE;
     use crate::chip::uint::bytes::register::ByteRegister;
     use crate::chip::AirParameters;
     use crate::math::field::Field;
@@ -378,4 +396,77 @@ mod tests {
         // Test the recursive proof.
         test_recursive_starky(stark, config, generator, &public_inputs);
     }
+
+    #[test]
+    fn test_byte_lookup_opcodes() {
+        type F = GoldilocksField;
+        const NUM_VALS: usize = 10;
+        type L = ByteOpTest<NUM_VALS>;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        // Range checks `NUM_VALS` bytes against a table covering the given opcodes.
+        let range_checks = |opcodes: &[u32]| {
+            let mut builder = AirBuilder::<L>::new();
+            let (mut operations, table) = builder.byte_operations_with_opcodes(opcodes);
+            let values = (0..NUM_VALS)
+                .map(|_| builder.alloc::<ByteRegister>())
+                .collect::<Vec<_>>();
+            for value in values.iter() {
+                builder.set_byte_operation(&ByteOperation::Range(*value), &mut operations);
+            }
+            builder.register_byte_lookup(operations, &table);
+            let (air, trace_data) = builder.build();
+            (air, trace_data, table, values)
+        };
+
+        let (_, full_trace_data, ..) = range_checks(&OPCODE_INDICES);
+        let (air, trace_data, table, values) = range_checks(&[OPCODE_RANGE]);
+
+        // Each opcode left out of the table saves its multiplicity column, as well as the three
+        // extended columns of its digest and the three of its multiplicity inverse.
+        let full = full_trace_data.report_usage().layout;
+        let compact = trace_data.report_usage().layout;
+        let num_skipped = OPCODE_INDICES.len() - 1;
+        assert_eq!(
+            full.num_free_columns - compact.num_free_columns,
+            num_skipped
+        );
+        assert_eq!(
+            full.num_extended_columns - compact.num_extended_columns,
+            6 * num_skipped
+        );
+
+        let generator = ArithmeticGenerator::<L>::new(trace_data);
+        let writer = generator.new_writer();
+        table.write_table_entries(&writer);
+        for i in 0..L::num_rows() {
+            for (k, value) in values.iter().enumerate() {
+                writer.write(value, &F::from_canonical_u8(23 * k as u8), i);
+            }
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+        table.write_multiplicities(&writer);
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(L::num_rows());
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_byte_lookup_missing_opcode() {
+        type L = ByteOpTest<1>;
+
+        let mut builder = AirBuilder::<L>::new();
+        let (mut operations, _) = builder.byte_operations_with_opcodes(&[OPCODE_RANGE]);
+        let a = builder.alloc::<ByteRegister>();
+        let b = builder.alloc::<ByteRegister>();
+        let result = builder.alloc::<ByteRegister>();
+        builder.set_byte_operation(&ByteOperation::And(a, b, result), &mut operations);
+    }
 }
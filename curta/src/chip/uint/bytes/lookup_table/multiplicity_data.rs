@@ -1,3 +1,4 @@
+use alloc::sync::Arc;
 use core::sync::atomic::{AtomicUsize, Ordering};
 use std::collections::HashMap;
 
@@ -22,11 +23,17 @@ pub struct MultiplicityValues(Vec<[AtomicUsize; NUM_BIT_OPPS + 1]>);
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MultiplicityData {
     multiplicities: ArrayRegister<ElementRegister>,
+    opcodes: Vec<u32>,
     multiplicities_values: MultiplicityValues,
     operations_multipcitiy_dict: HashMap<ByteOperation<u8>, (usize, usize)>,
     pub operations_dict: HashMap<usize, Vec<ByteOperation<u8>>>,
 }
 
+/// Multiplicities counted by a single writer, kept apart from the shared counters of
+/// `MultiplicityData` until they are folded in with `fold`.
+#[derive(Debug, Default)]
+pub struct MultiplicityBuffer(Vec<(Arc<MultiplicityData>, HashMap<(usize, usize), usize>)>);
+
 impl MultiplicityValues {
     pub fn new(num_rows: usize) -> Self {
         Self(
@@ -39,15 +46,57 @@ impl MultiplicityValues {
     pub fn update(&self, row: usize, col: usize) {
         self.0[row][col].fetch_add(1, Ordering::Relaxed);
     }
+
+    pub fn add(&self, row: usize, col: usize, count: usize) {
+        self.0[row][col].fetch_add(count, Ordering::Relaxed);
+    }
+}
+
+impl MultiplicityBuffer {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn update(&mut self, data: &Arc<MultiplicityData>, operation: &ByteOperation<u8>) {
+        let entry = data.entry(operation);
+        let index = match self.0.iter().position(|(d, _)| Arc::ptr_eq(d, data)) {
+            Some(index) => index,
+            None => {
+                self.0.push((data.clone(), HashMap::new()));
+                self.0.len() - 1
+            }
+        };
+        *self.0[index].1.entry(entry).or_insert(0) += 1;
+    }
+
+    /// Adds the buffered counts to the shared counters of their multiplicity data.
+    pub fn fold(self) {
+        for (data, counts) in self.0 {
+            for ((row, col), count) in counts {
+                data.multiplicities_values.add(row, col, count);
+            }
+        }
+    }
 }
 
 impl MultiplicityData {
-    pub fn new(num_rows: usize, multiplicities: ArrayRegister<ElementRegister>) -> Self {
+    /// Creates the multiplicity data of a table whose lookup argument covers the operations of
+    /// `opcodes`, with one multiplicity column per opcode in the given order.
+    pub fn new(
+        num_rows: usize,
+        multiplicities: ArrayRegister<ElementRegister>,
+        opcodes: &[u32],
+    ) -> Self {
+        assert_eq!(
+            multiplicities.len(),
+            opcodes.len(),
+            "Expected one multiplicity column per opcode"
+        );
         let mut operations_multipcitiy_dict = HashMap::new();
         let mut operations_dict = HashMap::new();
         for (row_index, (a, b)) in (0..=u8::MAX).cartesian_product(0..=u8::MAX).enumerate() {
             let mut operations = Vec::with_capacity(NUM_BIT_OPPS + 1);
-            for (op_index, opcode) in OPCODE_INDICES.into_iter().enumerate() {
+            for opcode in OPCODE_INDICES {
                 let operation = match opcode {
                     OPCODE_AND => ByteOperation::and(a, b),
                     OPCODE_XOR => ByteOperation::xor(a, b),
@@ -57,7 +106,9 @@ impl MultiplicityData {
                     OPCODE_RANGE => ByteOperation::range(a),
                     _ => unreachable!("Invalid opcode: {}", opcode),
                 };
-                operations_multipcitiy_dict.insert(operation, (row_index, op_index));
+                if let Some(op_index) = opcodes.iter().position(|op| *op == opcode) {
+                    operations_multipcitiy_dict.insert(operation, (row_index, op_index));
+                }
                 operations.push(operation);
             }
             operations_dict.insert(row_index, operations);
@@ -66,14 +117,22 @@ impl MultiplicityData {
 
         Self {
             multiplicities,
+            opcodes: opcodes.to_vec(),
             multiplicities_values: multiplicity_values,
             operations_dict,
             operations_multipcitiy_dict,
         }
     }
 
+    fn entry(&self, operation: &ByteOperation<u8>) -> (usize, usize) {
+        *self
+            .operations_multipcitiy_dict
+            .get(operation)
+            .unwrap_or_else(|| panic!("Operation {:?} is not in the lookup table", operation))
+    }
+
     pub fn update(&self, operation: &ByteOperation<u8>) {
-        let (row, col) = self.operations_multipcitiy_dict[operation];
+        let (row, col) = self.entry(operation);
         self.multiplicities_values.update(row, col);
     }
 
@@ -81,6 +140,15 @@ impl MultiplicityData {
         &self.multiplicities
     }
 
+    /// The opcodes covered by the lookup argument, in the order of the multiplicity columns.
+    pub fn opcodes(&self) -> &[u32] {
+        &self.opcodes
+    }
+
+    pub fn contains_opcode(&self, opcode: u32) -> bool {
+        self.opcodes.contains(&opcode)
+    }
+
     pub fn write_multiplicities<F: Field>(&self, writer: &TraceWriter<F>) {
         let multiplicities_array = self.multiplicities;
         writer
@@ -93,7 +161,8 @@ impl MultiplicityData {
                 })
             }))
             .for_each(|(row, multiplicities)| {
-                multiplicities_array.assign_to_raw_slice(row, &multiplicities);
+                multiplicities_array
+                    .assign_to_raw_slice(row, &multiplicities[..multiplicities_array.len()]);
             });
     }
 }
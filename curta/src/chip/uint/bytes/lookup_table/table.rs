@@ -42,16 +42,28 @@ pub struct ByteLookupTable {
 }
 
 impl<L: AirParameters> AirBuilder<L> {
+    /// Allocates a byte lookup table whose lookup argument covers the operations of `opcodes`.
+    ///
+    /// The table entries of every operation are always written to the trace, but only the
+    /// opcodes in `opcodes` get a digest and a multiplicity column.
     pub fn new_byte_lookup_table(
         &mut self,
         row_acc_challenges: ArrayRegister<CubicRegister>,
+        opcodes: &[u32],
     ) -> ByteLookupTable
     where
         L::Instruction: From<ByteInstructionSet>
             + From<SelectInstruction<BitRegister>>
             + From<ByteDecodeInstruction>,
     {
-        let multiplicities = self.alloc_array::<ElementRegister>(NUM_BIT_OPPS + 1);
+        for opcode in opcodes.iter() {
+            assert!(OPCODE_INDICES.contains(opcode), "Invalid opcode {}", opcode);
+        }
+        assert!(
+            opcodes.iter().all_unique(),
+            "Duplicate opcode in the byte lookup table"
+        );
+        let multiplicities = self.alloc_array::<ElementRegister>(opcodes.len());
 
         let a = self.alloc::<ByteRegister>();
         let b = self.alloc::<ByteRegister>();
@@ -61,7 +73,7 @@ impl<L: AirParameters> AirBuilder<L> {
         let b_bits = self.alloc_array::<BitRegister>(8);
         let results_bits = from_fn::<_, NUM_BIT_OPPS, _>(|_| self.alloc_array::<BitRegister>(8));
 
-        let multiplicity_data = MultiplicityData::new(L::num_rows(), multiplicities);
+        let multiplicity_data = MultiplicityData::new(L::num_rows(), multiplicities, opcodes);
 
         // Constrain the bit instructions
         for (k, &opcode) in OPCODE_INDICES.iter().enumerate() {
@@ -109,7 +121,8 @@ impl<L: AirParameters> AirBuilder<L> {
 
         // Accumulate entries for the lookup table
         let mut digests = Vec::new();
-        for (k, opcode) in OPCODE_INDICES.iter().enumerate() {
+        for opcode in opcodes.iter() {
+            let k = OPCODE_INDICES.iter().position(|op| op == opcode).unwrap();
             let operation =
                 ByteOperation::from_opcode_and_values(*opcode, a, b, results.get(k).copied());
             let acc_expressions = operation.expression_array();
@@ -51,6 +51,6 @@ impl<F: PrimeField64> Instruction<F> for ByteOperationInstruction {
             return;
         }
         let value = self.inner.write(writer, row_index);
-        self.multiplicity_data.update(&value);
+        writer.update_multiplicity(&self.multiplicity_data, &value);
     }
 }
@@ -39,6 +39,11 @@ impl<L: AirParameters> AirBuilder<L> {
         L::Instruction: From<ByteOperationInstruction>,
     {
         let mult_data = lookup_values.multiplicity_data.clone();
+        assert!(
+            mult_data.contains_opcode(op.opcode()),
+            "The byte lookup table does not cover the opcode {}",
+            op.opcode()
+        );
 
         let digest =
             self.accumulate_expressions(&lookup_values.row_acc_challenges, &op.expression_array());
@@ -57,6 +62,11 @@ impl<L: AirParameters> AirBuilder<L> {
     {
         // TODO: Check that the inputs are public
         let mult_data = lookup_values.multiplicity_data.clone();
+        assert!(
+            mult_data.contains_opcode(op.opcode()),
+            "The byte lookup table does not cover the opcode {}",
+            op.opcode()
+        );
 
         let digest = self.accumulate_public_expressions(
             &lookup_values.row_acc_challenges,
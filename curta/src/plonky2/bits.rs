@@ -0,0 +1,156 @@
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::{BoolTarget, Target};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+pub trait CircuitBuilderBits<F: RichField + Extendable<D>, const D: usize> {
+    /// Returns the little-endian decomposition of `x` into `num_bits` bits, asserting that `x` is
+    /// less than `2^num_bits`.
+    ///
+    /// If `2^num_bits` exceeds the field order, the decomposition is also asserted to be
+    /// canonical, i.e. to represent an integer less than the field order.
+    fn to_le_bits(&mut self, x: Target, num_bits: usize) -> Vec<BoolTarget>;
+
+    /// Returns the field element represented by the little-endian bits `bits`.
+    ///
+    /// The integer is reduced modulo the field order, so the bits must be canonical for the
+    /// conversion to be injective. This is the case for the output of `to_le_bits`.
+    fn from_le_bits(&mut self, bits: &[BoolTarget]) -> Target;
+
+    /// Asserts that the little-endian bits `bits` represent an integer less than the field order.
+    fn assert_le_bits_canonical(&mut self, bits: &[BoolTarget]);
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderBits<F, D>
+    for CircuitBuilder<F, D>
+{
+    fn to_le_bits(&mut self, x: Target, num_bits: usize) -> Vec<BoolTarget> {
+        assert!(
+            num_bits <= F::BITS,
+            "Cannot decompose a field element into more than {} bits",
+            F::BITS
+        );
+        let bits = self.split_le(x, num_bits);
+        // Only a decomposition into as many bits as the field order can represent an integer
+        // which is at least the order.
+        if num_bits == F::BITS {
+            self.assert_le_bits_canonical(&bits);
+        }
+        bits
+    }
+
+    fn from_le_bits(&mut self, bits: &[BoolTarget]) -> Target {
+        assert!(
+            bits.len() <= F::BITS,
+            "Cannot recompose more than {} bits into a field element",
+            F::BITS
+        );
+        self.le_sum(bits.iter())
+    }
+
+    fn assert_le_bits_canonical(&mut self, bits: &[BoolTarget]) {
+        if bits.len() < F::BITS {
+            return;
+        }
+        assert_eq!(bits.len(), F::BITS, "Too many bits for a field element");
+
+        // Compare the bits to those of `p - 1` from the least significant one up, keeping track
+        // of whether the low bits are at most the low bits of `p - 1`.
+        let max = F::ORDER - 1;
+        let mut is_at_most = self._true();
+        for (i, bit) in bits.iter().enumerate() {
+            let bit_is_zero = self.not(*bit);
+            is_at_most = if (max >> i) & 1 == 1 {
+                self.or(bit_is_zero, is_at_most)
+            } else {
+                self.and(bit_is_zero, is_at_most)
+            };
+        }
+        self.assert_one(is_at_most.target);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::{Field, Field64, PrimeField64, Sample};
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+
+    use super::*;
+
+    type F = GoldilocksField;
+    type C = PoseidonGoldilocksConfig;
+    const D: usize = 2;
+
+    #[test]
+    fn test_le_bits_round_trip() {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let x = builder.add_virtual_target();
+        let bits = builder.to_le_bits(x, F::BITS);
+        let recomposed = builder.from_le_bits(&bits);
+        builder.connect(x, recomposed);
+
+        // A short decomposition of the low 20 bits of `x`.
+        let low = builder.add_virtual_target();
+        let low_bits = builder.to_le_bits(low, 20);
+        for (a, b) in low_bits.iter().zip(bits.iter()) {
+            builder.connect(a.target, b.target);
+        }
+
+        let data = builder.build::<C>();
+
+        for value in [F::rand(), F::ZERO, F::NEG_ONE] {
+            let mut pw = PartialWitness::new();
+            pw.set_target(x, value);
+            pw.set_target(
+                low,
+                F::from_canonical_u64(value.to_canonical_u64() & ((1 << 20) - 1)),
+            );
+            let proof = data.prove(pw).unwrap();
+            data.verify(proof).unwrap();
+        }
+    }
+
+    /// Asserts that the bits of `value`, which may exceed the field order, are canonical.
+    fn prove_canonical(value: u64) {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let bits = (0..F::BITS)
+            .map(|_| builder.add_virtual_bool_target_safe())
+            .collect::<Vec<_>>();
+        builder.assert_le_bits_canonical(&bits);
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+        for (i, bit) in bits.iter().enumerate() {
+            pw.set_bool_target(*bit, (value >> i) & 1 == 1);
+        }
+
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+
+    #[test]
+    fn test_le_bits_canonical() {
+        prove_canonical(0);
+        prove_canonical(F::ORDER - 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_le_bits_non_canonical() {
+        // The bits of `p + 1` represent the same field element as those of `1`.
+        prove_canonical(F::ORDER + 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_le_bits_order() {
+        prove_canonical(F::ORDER);
+    }
+}
@@ -10,9 +10,11 @@ use self::parser::global::{GlobalRecursiveStarkParser, GlobalStarkParser};
 use self::parser::{RecursiveStarkParser, StarkParser};
 use crate::air::RAir;
 
+pub mod bits;
 pub mod challenger;
 pub mod field;
 pub mod parser;
+pub mod serialization;
 pub mod stark;
 
 /// an air that can generate constraints for the Starky proving system.
@@ -0,0 +1,510 @@
+use core::marker::PhantomData;
+
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::generator::{SimpleGenerator, WitnessGeneratorRef};
+use plonky2::plonk::circuit_data::CommonCircuitData;
+use plonky2::util::serialization::{
+    Buffer, DefaultGeneratorSerializer, IoError, IoResult, Read, WitnessGeneratorSerializer, Write,
+};
+
+use crate::chip::commitment::interval::IntervalIndexGenerator;
+use crate::chip::ec::edwards::batch_verify::air::Ed25519BatchVerify;
+use crate::chip::ec::edwards::batch_verify::generator::Ed25519BatchVerifyGenerator;
+use crate::chip::ec::edwards::compression::{
+    Ed25519DecompressHintGenerator, Ed25519MulAddHintGenerator,
+};
+use crate::chip::ec::edwards::scalar_mul::air::ScalarMulEd25519;
+use crate::chip::ec::edwards::scalar_mul::generator::{
+    SimpleScalarMulEd25519Generator, SimpleScalarMulEd25519HintGenerator,
+};
+use crate::chip::ec::edwards::verify::Ed25519ReduceHintGenerator;
+use crate::chip::hash::blake2s::generator::{BLAKE2SAirParameters, BLAKE2SGenerator};
+use crate::chip::hash::keccak::generator::{
+    Keccak256AirParameters, Keccak256Generator, KeccakHintGenerator,
+};
+use crate::chip::hash::ripemd160::generator::{RIPEMD160AirParameters, RIPEMD160Generator};
+use crate::chip::hash::sha::sha256::generator::{
+    SHA256AirParameters, SHA256Generator, SHA256HintGenerator,
+};
+use crate::chip::hash::sha::sha512::generator::{
+    SHA512AirParameters, SHA512Generator, SHA512HintGenerator,
+};
+use crate::chip::uint::bytes::gadget::air::ByteGadgetParameters;
+use crate::chip::uint::bytes::gadget::generator::{ByteSplitGenerator, BytesLookupGenerator};
+use crate::math::prelude::*;
+use crate::plonky2::stark::config::CurtaConfig;
+use crate::plonky2::stark::generator::simple::SimpleStarkWitnessGenerator;
+use crate::utils::serde::{BufferRead, BufferWrite};
+
+const DEFAULT_GENERATOR_TAG: u8 = 0;
+const CURTA_GENERATOR_TAG: u8 = 1;
+
+/// A witness generator serializer that knows all the generators defined in Curta, keyed on their
+/// `id()` values. Generators that are not defined in Curta are delegated to Plonky2's
+/// `DefaultGeneratorSerializer`.
+#[derive(Debug, Clone, Copy)]
+pub struct CurtaGeneratorSerializer<C, E, const D: usize> {
+    _marker: PhantomData<(C, E)>,
+}
+
+impl<C, E, const D: usize> CurtaGeneratorSerializer<C, E, D> {
+    pub fn new() -> Self {
+        Self {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<C, E, const D: usize> Default for CurtaGeneratorSerializer<C, E, D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C, E, const D: usize> CurtaGeneratorSerializer<C, E, D>
+where
+    C: CurtaConfig<D, FE = <C::F as Extendable<D>>::Extension>,
+    E: CubicParameters<C::F>,
+{
+    /// The ids of all the Curta generators known to the serializer.
+    pub fn generator_ids() -> Vec<String> {
+        vec![
+            SHA256Generator::<C::F, E>::id(),
+            SHA256HintGenerator::id(),
+            SimpleScalarMulEd25519Generator::<C::F, E, C, D>::id(),
+            SimpleScalarMulEd25519HintGenerator::<C::F, D>::id(),
+            // Batch verification is registered for the batch sizes 1, 2, 3, 4, 8 and 16.
+            Ed25519BatchVerifyGenerator::<C::F, E, 1, D>::id(),
+            Ed25519BatchVerifyGenerator::<C::F, E, 2, D>::id(),
+            Ed25519BatchVerifyGenerator::<C::F, E, 3, D>::id(),
+            Ed25519BatchVerifyGenerator::<C::F, E, 4, D>::id(),
+            Ed25519BatchVerifyGenerator::<C::F, E, 8, D>::id(),
+            Ed25519BatchVerifyGenerator::<C::F, E, 16, D>::id(),
+            IntervalIndexGenerator::id(),
+            BytesLookupGenerator::<C::F, E, D>::id(),
+            ByteSplitGenerator::id(),
+            SHA512Generator::<C::F, E>::id(),
+            SHA512HintGenerator::id(),
+            Keccak256Generator::<C::F, E>::id(),
+            KeccakHintGenerator::id(),
+            BLAKE2SGenerator::<C::F, E>::id(),
+            RIPEMD160Generator::<C::F, E>::id(),
+            Ed25519DecompressHintGenerator::id(),
+            Ed25519ReduceHintGenerator::id(),
+            Ed25519MulAddHintGenerator::id(),
+            SimpleStarkWitnessGenerator::<SHA256AirParameters<C::F, E>, C, D>::id(),
+            SimpleStarkWitnessGenerator::<ScalarMulEd25519<C::F, E>, C, D>::id(),
+            SimpleStarkWitnessGenerator::<ByteGadgetParameters<C::F, E, D>, C, D>::id(),
+            SimpleStarkWitnessGenerator::<Ed25519BatchVerify<C::F, E, 1>, C, D>::id(),
+            SimpleStarkWitnessGenerator::<Ed25519BatchVerify<C::F, E, 2>, C, D>::id(),
+            SimpleStarkWitnessGenerator::<Ed25519BatchVerify<C::F, E, 3>, C, D>::id(),
+            SimpleStarkWitnessGenerator::<Ed25519BatchVerify<C::F, E, 4>, C, D>::id(),
+            SimpleStarkWitnessGenerator::<Ed25519BatchVerify<C::F, E, 8>, C, D>::id(),
+            SimpleStarkWitnessGenerator::<Ed25519BatchVerify<C::F, E, 16>, C, D>::id(),
+            SimpleStarkWitnessGenerator::<SHA512AirParameters<C::F, E>, C, D>::id(),
+            SimpleStarkWitnessGenerator::<Keccak256AirParameters<C::F, E>, C, D>::id(),
+            SimpleStarkWitnessGenerator::<BLAKE2SAirParameters<C::F, E>, C, D>::id(),
+            SimpleStarkWitnessGenerator::<RIPEMD160AirParameters<C::F, E>, C, D>::id(),
+        ]
+    }
+
+    fn read_curta_generator(
+        &self,
+        id: &str,
+        buf: &mut Buffer,
+        common_data: &CommonCircuitData<C::F, D>,
+    ) -> IoResult<WitnessGeneratorRef<C::F, D>> {
+        macro_rules! read_generator {
+            ($($generator:ty),+ $(,)?) => {
+                $(
+                    if id == <$generator>::id() {
+                        let generator = <$generator as SimpleGenerator<C::F, D>>::deserialize(
+                            buf,
+                            common_data,
+                        )?;
+                        return Ok(WitnessGeneratorRef::new(generator.adapter()));
+                    }
+                )+
+            };
+        }
+
+        read_generator!(
+            SHA256Generator<C::F, E>,
+            SHA256HintGenerator,
+            SimpleScalarMulEd25519Generator<C::F, E, C, D>,
+            SimpleScalarMulEd25519HintGenerator<C::F, D>,
+            Ed25519BatchVerifyGenerator<C::F, E, 1, D>,
+            Ed25519BatchVerifyGenerator<C::F, E, 2, D>,
+            Ed25519BatchVerifyGenerator<C::F, E, 3, D>,
+            Ed25519BatchVerifyGenerator<C::F, E, 4, D>,
+            Ed25519BatchVerifyGenerator<C::F, E, 8, D>,
+            Ed25519BatchVerifyGenerator<C::F, E, 16, D>,
+            IntervalIndexGenerator,
+            BytesLookupGenerator<C::F, E, D>,
+            ByteSplitGenerator,
+            SHA512Generator<C::F, E>,
+            SHA512HintGenerator,
+            Keccak256Generator<C::F, E>,
+            KeccakHintGenerator,
+            BLAKE2SGenerator<C::F, E>,
+            RIPEMD160Generator<C::F, E>,
+            Ed25519DecompressHintGenerator,
+            Ed25519ReduceHintGenerator,
+            Ed25519MulAddHintGenerator,
+            SimpleStarkWitnessGenerator<SHA256AirParameters<C::F, E>, C, D>,
+            SimpleStarkWitnessGenerator<ScalarMulEd25519<C::F, E>, C, D>,
+            SimpleStarkWitnessGenerator<ByteGadgetParameters<C::F, E, D>, C, D>,
+            SimpleStarkWitnessGenerator<Ed25519BatchVerify<C::F, E, 1>, C, D>,
+            SimpleStarkWitnessGenerator<Ed25519BatchVerify<C::F, E, 2>, C, D>,
+            SimpleStarkWitnessGenerator<Ed25519BatchVerify<C::F, E, 3>, C, D>,
+            SimpleStarkWitnessGenerator<Ed25519BatchVerify<C::F, E, 4>, C, D>,
+            SimpleStarkWitnessGenerator<Ed25519BatchVerify<C::F, E, 8>, C, D>,
+            SimpleStarkWitnessGenerator<Ed25519BatchVerify<C::F, E, 16>, C, D>,
+            SimpleStarkWitnessGenerator<SHA512AirParameters<C::F, E>, C, D>,
+            SimpleStarkWitnessGenerator<Keccak256AirParameters<C::F, E>, C, D>,
+            SimpleStarkWitnessGenerator<BLAKE2SAirParameters<C::F, E>, C, D>,
+            SimpleStarkWitnessGenerator<RIPEMD160AirParameters<C::F, E>, C, D>,
+        );
+
+        log::error!("Unknown Curta generator id: {}", id);
+        Err(IoError)
+    }
+}
+
+impl<F, C, E, const D: usize> WitnessGeneratorSerializer<F, D> for CurtaGeneratorSerializer<C, E, D>
+where
+    F: RichField + Extendable<D>,
+    C: CurtaConfig<D, F = F, FE = F::Extension>,
+    C::GenericConfig: 'static,
+    E: CubicParameters<F>,
+{
+    fn read_generator(
+        &self,
+        buf: &mut Buffer,
+        common_data: &CommonCircuitData<F, D>,
+    ) -> IoResult<WitnessGeneratorRef<F, D>> {
+        match buf.read_u8()? {
+            DEFAULT_GENERATOR_TAG => DefaultGeneratorSerializer::<C::GenericConfig, D> {
+                _phantom: PhantomData,
+            }
+            .read_generator(buf, common_data),
+            CURTA_GENERATOR_TAG => {
+                let id = String::from_utf8(buf.read_bytes()?).map_err(|_| IoError)?;
+                self.read_curta_generator(&id, buf, common_data)
+            }
+            tag => {
+                log::error!("Invalid generator tag: {}", tag);
+                Err(IoError)
+            }
+        }
+    }
+
+    fn write_generator(
+        &self,
+        buf: &mut Vec<u8>,
+        generator: &WitnessGeneratorRef<F, D>,
+        common_data: &CommonCircuitData<F, D>,
+    ) -> IoResult<()> {
+        let id = generator.0.id();
+        if Self::generator_ids().contains(&id) {
+            buf.write_u8(CURTA_GENERATOR_TAG)?;
+            buf.write_bytes(id.as_bytes())?;
+            generator.0.serialize(buf, common_data)
+        } else {
+            buf.write_u8(DEFAULT_GENERATOR_TAG)?;
+            DefaultGeneratorSerializer::<C::GenericConfig, D> {
+                _phantom: PhantomData,
+            }
+            .write_generator(buf, generator, common_data)
+            .map_err(|e| {
+                log::error!("Generator {} is not supported by the serializer", id);
+                e
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::types::Field;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_builder::CircuitBuilder;
+    use plonky2::plonk::circuit_data::{CircuitConfig, CircuitData};
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+    use plonky2::util::serialization::DefaultGateSerializer;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::builder::AirBuilder;
+    use crate::chip::commitment::interval::CircuitBuilderIntervalSet;
+    use crate::chip::ec::edwards::batch_verify::generator::Ed25519BatchVerifyGadget;
+    use crate::chip::ec::edwards::compression::Ed25519CompressionGadget;
+    use crate::chip::ec::edwards::scalar_mul::generator::AffinePointTarget;
+    use crate::chip::ec::edwards::verify::Ed25519VerifyGadget;
+    use crate::chip::hash::blake2s::generator::BLAKE2S_NUM_COMPRESSIONS;
+    use crate::chip::hash::blake2s::{BLAKE2SPublicData, BLAKE2S_BLOCK_LEN};
+    use crate::chip::hash::keccak::builder_gadget::Keccak256Builder;
+    use crate::chip::hash::keccak::generator::KECCAK_NUM_BLOCKS;
+    use crate::chip::hash::keccak::KECCAK_RATE;
+    use crate::chip::hash::ripemd160::builder_gadget::RIPEMD160Builder;
+    use crate::chip::hash::sha::sha256::builder_gadget::{CurtaBytes, SHA256Builder};
+    use crate::chip::hash::sha::sha256::SHA256Gadget;
+    use crate::chip::hash::sha::sha512::builder_gadget::SHA512Builder;
+    use crate::chip::hash::sha::sha512::generator::SHA512_NUM_BLOCKS;
+    use crate::chip::AirParameters;
+    use crate::plonky2::stark::config::{CurtaPoseidonGoldilocksConfig, StarkyConfig};
+    use crate::plonky2::stark::gadget::StarkGadget;
+
+    type F = GoldilocksField;
+    type E = GoldilocksCubicParameters;
+    type SC = CurtaPoseidonGoldilocksConfig;
+    type C = PoseidonGoldilocksConfig;
+    const D: usize = 2;
+
+    /// Serializes the circuit, reads it back and checks that it serializes to the same bytes.
+    fn round_trip(data: &CircuitData<F, C, D>) -> CircuitData<F, C, D> {
+        let gate_serializer = DefaultGateSerializer;
+        let generator_serializer = CurtaGeneratorSerializer::<SC, E, D>::new();
+
+        let bytes = data
+            .to_bytes(&gate_serializer, &generator_serializer)
+            .unwrap();
+        let deserialized_data =
+            CircuitData::<F, C, D>::from_bytes(&bytes, &gate_serializer, &generator_serializer)
+                .unwrap();
+        assert_eq!(
+            deserialized_data
+                .to_bytes(&gate_serializer, &generator_serializer)
+                .unwrap(),
+            bytes
+        );
+        deserialized_data
+    }
+
+    #[test]
+    fn test_sha256_circuit_serialization() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        // The SHA256 gadget processes a fixed number of 1024 chunks.
+        let mut gadget = SHA256Builder::<F, E, D>::init_sha256(&mut builder);
+        let msg_targets = (0..1024)
+            .map(|_| CurtaBytes(builder.add_virtual_target_arr::<64>()))
+            .collect::<Vec<_>>();
+        let digests = msg_targets
+            .iter()
+            .map(|msg| builder.sha256(msg, &mut gadget))
+            .collect::<Vec<_>>();
+        builder.register_public_inputs(&digests[0].0);
+        builder.constrain_sha256_gadget::<SC>(gadget);
+
+        let data = builder.build::<C>();
+        let deserialized_data = round_trip(&data);
+
+        // Prove with the deserialized circuit
+        let padded_msg = SHA256Gadget::pad(b"abc")
+            .into_iter()
+            .map(F::from_canonical_u8)
+            .collect::<Vec<_>>();
+        let mut pw = PartialWitness::new();
+        for msg_target in msg_targets.iter() {
+            pw.set_target_arr(&msg_target.0, &padded_msg);
+        }
+
+        let proof = deserialized_data.prove(pw).unwrap();
+        let expected_digest =
+            hex::decode("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+                .unwrap()
+                .into_iter()
+                .map(F::from_canonical_u8)
+                .collect::<Vec<_>>();
+        assert_eq!(proof.public_inputs, expected_digest);
+        data.verify(proof).unwrap();
+    }
+    #[test]
+    fn test_ed25519_batch_verify_circuit_serialization() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let entries = (0..3)
+            .map(|_| builder.add_virtual_ed25519_batch_entry())
+            .collect::<Vec<_>>();
+        let randomizers = builder.ed25519_batch_randomizers(&entries);
+        let is_valid = builder.batch_verify_ed25519::<E, SC, 3>(&entries, &randomizers);
+        builder.register_public_input(is_valid);
+
+        let data = builder.build::<C>();
+        round_trip(&data);
+    }
+    #[test]
+    fn test_interval_set_circuit_serialization() {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let value = builder.add_virtual_target();
+        let intervals = (0..5)
+            .map(|_| (builder.add_virtual_target(), builder.add_virtual_target()))
+            .collect::<Vec<_>>();
+        builder.assert_in_sorted_interval_set(value, &intervals);
+
+        let data = builder.build::<C>();
+        let deserialized_data = round_trip(&data);
+
+        // Prove with the deserialized circuit
+        let bounds = [(0, 9), (20, 29), (40, 49), (60, 69), (80, 89)];
+        let mut pw = PartialWitness::new();
+        pw.set_target(value, F::from_canonical_u32(45));
+        for ((lo, hi), (lo_value, hi_value)) in intervals.iter().zip(bounds) {
+            pw.set_target(*lo, F::from_canonical_u32(lo_value));
+            pw.set_target(*hi, F::from_canonical_u32(hi_value));
+        }
+        let proof = deserialized_data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+
+    #[test]
+    fn test_sha512_circuit_serialization() {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let mut gadget = SHA512Builder::<F, E, D>::init_sha512(&mut builder);
+        for _ in 0..SHA512_NUM_BLOCKS {
+            let msg = CurtaBytes(builder.add_virtual_target_arr::<128>());
+            let digest = builder.sha512(&msg, &mut gadget);
+            builder.register_public_inputs(&digest.0);
+        }
+        builder.constrain_sha512_gadget::<SC>(gadget);
+
+        let data = builder.build::<C>();
+        round_trip(&data);
+    }
+
+    #[test]
+    fn test_keccak256_circuit_serialization() {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let mut gadget = Keccak256Builder::<F, E, D>::init_keccak256(&mut builder);
+        for _ in 0..KECCAK_NUM_BLOCKS {
+            let msg = CurtaBytes(builder.add_virtual_target_arr::<KECCAK_RATE>());
+            let digest = builder.keccak256(&msg, &mut gadget);
+            builder.register_public_inputs(&digest.0);
+        }
+        builder.constrain_keccak256_gadget::<SC>(gadget);
+
+        let data = builder.build::<C>();
+        round_trip(&data);
+    }
+
+    #[test]
+    fn test_blake2s_circuit_serialization() {
+        type L = BLAKE2SAirParameters<F, E>;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        // There is no builder gadget for BLAKE2s, so the air, its generators and the verification
+        // of its proof are added by hand, for single block messages filling the proof.
+        let message_lengths = vec![BLAKE2S_BLOCK_LEN; BLAKE2S_NUM_COMPRESSIONS];
+        let messages = builder.add_virtual_targets(message_lengths.iter().sum());
+        let digests = builder.add_virtual_targets(32 * message_lengths.len());
+        let public_data =
+            BLAKE2SPublicData::add_virtual(&mut builder, &messages, &message_lengths, &digests);
+        builder.register_public_inputs(&digests[..32]);
+
+        let mut air_builder = AirBuilder::<L>::new();
+        let clk = air_builder.clock();
+        let (mut operations, table) = air_builder.byte_operations();
+        let mut bus = air_builder.new_bus();
+        let channel_idx = bus.new_channel(&mut air_builder);
+        let gadget =
+            air_builder.process_blake2s_batch(&clk, &mut bus, channel_idx, &mut operations);
+        air_builder.register_byte_lookup(operations, &table);
+        air_builder.constrain_bus(bus);
+        let (air, trace_data) = air_builder.build();
+        let trace_generator = ArithmeticGenerator::<L>::new(trace_data);
+
+        let public_input_target = public_data.public_input_targets();
+        builder.add_simple_generator(BLAKE2SGenerator {
+            gadget,
+            table,
+            messages,
+            message_lengths,
+            trace_generator: trace_generator.clone(),
+            pub_values_target: public_data,
+        });
+
+        let stark = Starky::new(air);
+        let config = StarkyConfig::<SC, D>::standard_fast_config(L::num_rows());
+        let virtual_proof = builder.add_virtual_stark_proof(&stark, &config);
+        builder.verify_stark_proof(&config, &stark, &virtual_proof, &public_input_target);
+        builder.add_simple_generator(SimpleStarkWitnessGenerator::new(
+            config,
+            stark,
+            virtual_proof,
+            public_input_target,
+            trace_generator,
+        ));
+
+        let data = builder.build::<C>();
+        round_trip(&data);
+    }
+
+    #[test]
+    fn test_ripemd160_circuit_serialization() {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        // The gadget fills the compressions not taken by the messages with empty messages.
+        let mut gadget = RIPEMD160Builder::<F, E, D>::init_ripemd160(&mut builder);
+        let msg = builder.add_virtual_targets(32);
+        let digest = builder.ripemd160(&msg, &mut gadget);
+        builder.register_public_inputs(&digest.0);
+        builder.constrain_ripemd160_gadget::<SC>(gadget);
+
+        let data = builder.build::<C>();
+        round_trip(&data);
+    }
+
+    #[test]
+    fn test_ed25519_verify_circuit_serialization() {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let mut gadget = SHA512Builder::<F, E, D>::init_sha512(&mut builder);
+        let target = builder.add_virtual_ed25519_verify::<E, SC>(32, &mut gadget);
+        builder.register_public_input(target.valid.target);
+
+        // Fill the remaining blocks of the SHA-512 trace.
+        let num_blocks = gadget.chunk_sizes.iter().sum::<usize>();
+        for _ in num_blocks..SHA512_NUM_BLOCKS {
+            let msg = CurtaBytes(builder.add_virtual_target_arr::<128>());
+            builder.sha512(&msg, &mut gadget);
+        }
+        builder.constrain_sha512_gadget::<SC>(gadget);
+
+        let data = builder.build::<C>();
+        round_trip(&data);
+    }
+    #[test]
+    fn test_ed25519_compression_circuit_serialization() {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let point = AffinePointTarget {
+            x: builder.add_virtual_target_arr(),
+            y: builder.add_virtual_target_arr(),
+        };
+        let bytes = builder.compress_ed25519_point(&point);
+        let decompressed = builder.decompress_ed25519_point(&bytes);
+        builder.register_public_inputs(&decompressed.x);
+
+        let data = builder.build::<C>();
+        round_trip(&data);
+    }
+}
@@ -2,9 +2,12 @@ use plonky2::fri::proof::FriProofTarget;
 use plonky2::hash::hash_types::{HashOutTarget, MerkleCapTarget};
 use plonky2::iop::ext_target::ExtensionTarget;
 use plonky2::iop::target::Target;
-use plonky2::util::serialization::{Buffer, IoResult, Read, Write};
+use plonky2::util::serialization::{Buffer, IoError, IoResult, Read, Write};
 use serde::{Deserialize, Serialize};
 
+use crate::chip::ec::point::AffinePoint;
+use crate::chip::ec::EllipticCurveParameters;
+
 pub trait BufferRead: Read {
     fn read_bytes(&mut self) -> IoResult<Vec<u8>> {
         let len = self.read_usize()?;
@@ -12,6 +15,12 @@ pub trait BufferRead: Read {
         self.read_exact(&mut bytes)?;
         Ok(bytes)
     }
+
+    fn read_affine_point<E: EllipticCurveParameters>(&mut self) -> IoResult<AffinePoint<E>> {
+        let mut bytes = vec![0u8; AffinePoint::<E>::NUM_BYTES];
+        self.read_exact(&mut bytes)?;
+        AffinePoint::from_bytes(&bytes).map_err(|_| IoError)
+    }
 }
 
 impl<'a> BufferRead for Buffer<'a> {}
@@ -21,6 +30,14 @@ pub trait BufferWrite: Write {
         self.write_usize(bytes.len())?;
         self.write_all(bytes)
     }
+
+    /// Writes the fixed-length encoding of `point`, with no length prefix.
+    fn write_affine_point<E: EllipticCurveParameters>(
+        &mut self,
+        point: &AffinePoint<E>,
+    ) -> IoResult<()> {
+        self.write_all(&point.to_bytes())
+    }
 }
 
 impl BufferWrite for Vec<u8> {}